@@ -0,0 +1,128 @@
+//! DSP benchmarks: offline renders of the voices per waveform, filter mode and
+//! unison count, a full chord, and the spectrum analysis the A/B overlay runs.
+//! `cargo bench` compares each run against the last, so a rewrite that slows
+//! the audio path shows up here.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use nih_plug::prelude::{Enum, Param};
+
+use osc3_mcp_rust::dsp::spectrum::spectrum;
+use osc3_mcp_rust::render::{render, PatchValues, RenderNote, RenderSpec};
+use osc3_mcp_rust::{FilterMode, SineParams, Waveform};
+
+/// A quarter second of one note: long enough to get past the attack.
+fn one_note() -> RenderSpec {
+    RenderSpec {
+        notes: vec![RenderNote {
+            note: 57,
+            velocity: 0.8,
+            start: 0.0,
+            length: 0.25,
+        }],
+        seconds: 0.25,
+        sample_rate: 48_000.0,
+        bpm: 120.0,
+    }
+}
+
+fn set<P: Param>(values: &mut PatchValues, param: &P, value: P::Plain) {
+    values.insert(param.as_ptr(), param.preview_normalized(value));
+}
+
+/// Only osc 1, so each case measures one oscillator and the filter.
+fn solo_osc1(p: &SineParams) -> PatchValues {
+    let mut values = PatchValues::new();
+    for osc in [&p.osc2, &p.osc3] {
+        values.insert(osc.gain.as_ptr(), 0.0);
+    }
+    values
+}
+
+fn frames(spec: &RenderSpec) -> Throughput {
+    Throughput::Elements((spec.seconds * spec.sample_rate) as u64)
+}
+
+fn waveforms(c: &mut Criterion) {
+    let p = SineParams::default();
+    let spec = one_note();
+    let mut group = c.benchmark_group("waveform");
+    group.throughput(frames(&spec));
+    for index in 0..Waveform::variants().len() {
+        let waveform = Waveform::from_index(index);
+        let mut values = solo_osc1(&p);
+        set(&mut values, &p.osc1.waveform, waveform);
+        group.bench_function(BenchmarkId::from_parameter(format!("{waveform:?}")), |b| {
+            b.iter(|| render(&p, black_box(&values), &spec))
+        });
+    }
+    group.finish();
+}
+
+fn filter_modes(c: &mut Criterion) {
+    let p = SineParams::default();
+    let spec = one_note();
+    let mut group = c.benchmark_group("filter_mode");
+    group.throughput(frames(&spec));
+    for index in 0..FilterMode::variants().len() {
+        let mode = FilterMode::from_index(index);
+        let mut values = solo_osc1(&p);
+        set(&mut values, &p.filter.mode, mode);
+        group.bench_function(BenchmarkId::from_parameter(format!("{mode:?}")), |b| {
+            b.iter(|| render(&p, black_box(&values), &spec))
+        });
+    }
+    group.finish();
+}
+
+fn unison(c: &mut Criterion) {
+    let p = SineParams::default();
+    let spec = one_note();
+    let mut group = c.benchmark_group("unison");
+    group.throughput(frames(&spec));
+    for voices in [1, 4, 8] {
+        let mut values = solo_osc1(&p);
+        set(&mut values, &p.osc1.unison_voices, voices);
+        group.bench_function(BenchmarkId::from_parameter(voices), |b| {
+            b.iter(|| render(&p, black_box(&values), &spec))
+        });
+    }
+    group.finish();
+}
+
+/// All three oscillators on eight held notes: a busy patch.
+fn chord(c: &mut Criterion) {
+    let p = SineParams::default();
+    let mut spec = one_note();
+    spec.notes = (0..8)
+        .map(|i| RenderNote {
+            note: 48 + 3 * i,
+            velocity: 0.8,
+            start: 0.0,
+            length: 0.25,
+        })
+        .collect();
+    let values = PatchValues::new();
+    let mut group = c.benchmark_group("chord");
+    group.throughput(frames(&spec));
+    group.bench_function("8 notes", |b| {
+        b.iter(|| render(&p, black_box(&values), &spec))
+    });
+    group.finish();
+}
+
+fn analysis(c: &mut Criterion) {
+    let p = SineParams::default();
+    let spec = RenderSpec::single_note(57);
+    let mono: Vec<f32> = render(&p, &PatchValues::new(), &spec)
+        .iter()
+        .map(|[left, right]| 0.5 * (left + right))
+        .collect();
+    c.bench_function("spectrum", |b| {
+        b.iter(|| spectrum(black_box(&mono), spec.sample_rate))
+    });
+}
+
+criterion_group!(benches, waveforms, filter_modes, unison, chord, analysis);
+criterion_main!(benches);
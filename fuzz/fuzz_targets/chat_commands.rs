@@ -0,0 +1,17 @@
+//! Any chat message, as bytes the user could paste: parsing it must never
+//! panic, and every command it parses must read back the same from its text.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use osc3_mcp_rust::parse_chat_message;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    for command in parse_chat_message(text).into_iter().flatten().flatten() {
+        let again = parse_chat_message(&command.to_string());
+        assert_eq!(again, Some(vec![Ok(command)]));
+    }
+});
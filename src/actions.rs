@@ -0,0 +1,332 @@
+//! Patch-level actions (preset stepping, randomize, tap tempo) that hosts can
+//! trigger through the `trigger_*` params as well as from the editor.
+//!
+//! The audio thread only *detects* a trigger: `SineSynth::process` watches the
+//! trigger params for rising edges (and the MIDI input for program changes),
+//! flags the action in [`PatchActions`] (one relaxed atomic op — RT-safe) and
+//! hands the plugin's task executor a task to carry it out. Anything that
+//! reads files or rewrites params happens there, on the GUI thread, and the
+//! result goes to the host as `RawParamEvent`s like any other edit, so it
+//! records proper gestures. Tap tempo is the exception: it needs no params
+//! written, so it is resolved on the audio thread.
+//!
+//! MIDI program changes go the same way: the audio thread notes the bank and
+//! program, and the task executor loads the preset they pick (see
+//! `ai::bank::program`).
+//!
+//! Only the editor is given the host's context for writing params, so it
+//! lends it to a [`HostLink`] the first time it opens; the link keeps it,
+//! editor open or closed, until the plugin deactivates. Anything raised
+//! before that first attach is carried out by the editor's poll timer once
+//! it opens.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use nih_plug::prelude::{GuiContext, Param};
+use vizia_plug::widgets::RawParamEvent;
+
+use crate::SineParams;
+use crate::ai::{bank, preset};
+use crate::dsp::rng::XorShift;
+use crate::locks;
+
+/// An action a trigger param (or editor button) asks for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PatchAction {
+    NextPreset,
+    PrevPreset,
+    Randomize,
+}
+
+impl PatchAction {
+    pub const ALL: [PatchAction; 3] = [Self::NextPreset, Self::PrevPreset, Self::Randomize];
+
+    fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+/// Stored in `program` when no program change is waiting.
+const NO_PROGRAM: u32 = u32::MAX;
+
+/// Mailbox between the audio thread, which raises actions, and the task
+/// executor (or editor) that carries them out. Lock-free on the audio side.
+pub struct PatchActions {
+    /// One bit per [`PatchAction`].
+    pending: AtomicU32,
+    /// Set with each action raised, until the audio thread has handed the task
+    /// executor a task for it.
+    raised: AtomicBool,
+    /// The last program change not carried out yet: the bank in the high
+    /// half, the program in the low; [`NO_PROGRAM`] when there is none.
+    program: AtomicU32,
+    /// `f32` bits of the last tapped tempo in BPM; `0.0` until two taps land.
+    tapped_bpm: AtomicU32,
+    /// The loaded preset's name, empty when the patch isn't a saved preset;
+    /// next and previous step from it.
+    preset: Mutex<String>,
+    /// Set when an action changes `preset`, until the editor picks it up.
+    renamed: AtomicBool,
+}
+
+impl Default for PatchActions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PatchActions {
+    pub fn new() -> Self {
+        Self {
+            pending: AtomicU32::new(0),
+            raised: AtomicBool::new(false),
+            program: AtomicU32::new(NO_PROGRAM),
+            tapped_bpm: AtomicU32::new(0),
+            preset: Mutex::new(String::new()),
+            renamed: AtomicBool::new(false),
+        }
+    }
+
+    /// Flag `action` to be carried out. RT-safe.
+    #[inline]
+    pub fn request(&self, action: PatchAction) {
+        self.pending.fetch_or(action.bit(), Ordering::Relaxed);
+        self.raised.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether anything was raised since the last call, i.e. whether the audio
+    /// thread should hand the task executor a task. RT-safe.
+    #[inline]
+    pub fn take_raised(&self) -> bool {
+        self.raised.swap(false, Ordering::Relaxed)
+    }
+
+    /// Clears and returns every pending action, in [`PatchAction::ALL`] order.
+    pub fn take(&self) -> impl Iterator<Item = PatchAction> {
+        let bits = self.pending.swap(0, Ordering::Relaxed);
+        PatchAction::ALL
+            .into_iter()
+            .filter(move |action| bits & action.bit() != 0)
+    }
+
+    /// Ask for `program` in `bank` to be loaded. A later request replaces one
+    /// not carried out yet. RT-safe.
+    #[inline]
+    pub fn request_program(&self, bank: u16, program: u8) {
+        let packed = u32::from(bank) << 16 | u32::from(program);
+        self.program.store(packed, Ordering::Relaxed);
+        self.raised.store(true, Ordering::Relaxed);
+    }
+
+    /// Clears and returns the waiting program change as `(bank, program)`.
+    pub fn take_program(&self) -> Option<(u16, u8)> {
+        let packed = self.program.swap(NO_PROGRAM, Ordering::Relaxed);
+        (packed != NO_PROGRAM).then_some(((packed >> 16) as u16, packed as u8))
+    }
+
+    #[inline]
+    pub fn set_tapped_bpm(&self, bpm: f32) {
+        self.tapped_bpm.store(bpm.to_bits(), Ordering::Relaxed);
+    }
+
+    /// The tempo set by tap tempo, if the user has tapped at least twice.
+    pub fn tapped_bpm(&self) -> Option<f32> {
+        let bpm = f32::from_bits(self.tapped_bpm.load(Ordering::Relaxed));
+        (bpm > 0.0).then_some(bpm)
+    }
+
+    /// Tell the actions which preset is loaded, e.g. after the editor loaded
+    /// one itself.
+    pub fn set_preset_name(&self, name: &str) {
+        let mut preset = lock(&self.preset);
+        if *preset != name {
+            name.clone_into(&mut *preset);
+        }
+    }
+
+    /// The loaded preset's name if an action has changed it since the last
+    /// call.
+    pub fn take_renamed(&self) -> Option<String> {
+        self.renamed
+            .swap(false, Ordering::Relaxed)
+            .then(|| lock(&self.preset).clone())
+    }
+
+    /// Record that the preset `name` was loaded (empty for an unsaved patch),
+    /// for the editor to show.
+    pub fn rename(&self, name: &str) {
+        self.set_preset_name(name);
+        self.renamed.store(true, Ordering::Relaxed);
+    }
+
+    /// Carry out every pending action, then the waiting program change. Not
+    /// on the audio thread: it reads preset files.
+    pub fn carry_out(&self, p: &SineParams, emit: &mut impl FnMut(RawParamEvent)) {
+        for action in self.take() {
+            self.run(p, action, emit);
+        }
+        if let Some((bank, program)) = self.take_program() {
+            self.load_program(p, bank, program, emit);
+        }
+    }
+
+    /// Load the preset a MIDI program change picked.
+    fn load_program(
+        &self,
+        p: &SineParams,
+        bank: u16,
+        program: u8,
+        emit: &mut impl FnMut(RawParamEvent),
+    ) {
+        match bank::program(bank, program) {
+            Ok((name, data)) => {
+                data.apply(p, emit);
+                data.metadata.store(p);
+                tracing::info!(target: "editor", bank, program, preset = %name, "program change");
+                self.rename(&name);
+            }
+            Err(e) => tracing::warn!(target: "editor", bank, program, "program change: {e}"),
+        }
+    }
+
+    /// Carry out `action` now: load the next or previous saved preset, or roll
+    /// a random patch.
+    pub fn run(&self, p: &SineParams, action: PatchAction, emit: &mut impl FnMut(RawParamEvent)) {
+        match action {
+            PatchAction::NextPreset | PatchAction::PrevPreset => {
+                let names = preset::list();
+                let forward = action == PatchAction::NextPreset;
+                let current = lock(&self.preset).clone();
+                let Some(name) = step_preset(&names, &current, forward) else {
+                    return;
+                };
+                match preset::load(name) {
+                    Ok(data) => {
+                        data.apply(p, emit);
+                        data.metadata.store(p);
+                        self.rename(name);
+                        tracing::info!(target: "editor", preset = %name, "stepped to preset");
+                    }
+                    Err(e) => tracing::warn!(target: "editor", "couldn't load '{name}': {e}"),
+                }
+            }
+            PatchAction::Randomize => {
+                randomize(p, emit);
+                self.rename("");
+            }
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The host's context for writing params, lent by the editor so actions can
+/// be carried out while it's closed. The host only gives it to the editor, so
+/// until the editor has opened once there's none. The plugin lets go of it
+/// when it deactivates: the context holds the plugin's wrapper, which holds
+/// the plugin.
+#[derive(Default)]
+pub struct HostLink {
+    context: Mutex<Option<Arc<dyn GuiContext>>>,
+}
+
+impl HostLink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn attach(&self, context: Arc<dyn GuiContext>) {
+        *lock(&self.context) = Some(context);
+    }
+
+    pub fn detach(&self) {
+        lock(&self.context).take();
+    }
+
+    /// Run `f` with an emitter that writes params through the host, as the
+    /// editor's knobs do. Returns whether there was a context to run it with.
+    /// Call it on the GUI thread.
+    pub fn write(&self, f: impl FnOnce(&mut dyn FnMut(RawParamEvent))) -> bool {
+        let Some(context) = lock(&self.context).clone() else {
+            return false;
+        };
+        // SAFETY: every `ParamPtr` written points into the plugin's
+        // `SineParams`, which lives as long as the host's context.
+        f(&mut |event| unsafe {
+            match event {
+                RawParamEvent::BeginSetParameter(ptr) => context.raw_begin_set_parameter(ptr),
+                RawParamEvent::SetParameterNormalized(ptr, normalized) => {
+                    context.raw_set_parameter_normalized(ptr, normalized)
+                }
+                RawParamEvent::EndSetParameter(ptr) => context.raw_end_set_parameter(ptr),
+            }
+        });
+        true
+    }
+}
+
+/// Taps further apart than this start a new measurement instead of setting a
+/// (uselessly slow) tempo.
+pub const TAP_TIMEOUT_SECONDS: f32 = 2.0;
+
+/// The preset after (or before) `current` in `names`, wrapping around. Starts at
+/// the first (or last) preset when `current` isn't one of them.
+pub fn step_preset<'a>(names: &'a [String], current: &str, forward: bool) -> Option<&'a String> {
+    let len = names.len();
+    if len == 0 {
+        return None;
+    }
+    let index = match names.iter().position(|n| n == current) {
+        Some(i) if forward => (i + 1) % len,
+        Some(i) => (i + len - 1) % len,
+        None if forward => 0,
+        None => len - 1,
+    };
+    names.get(index)
+}
+
+/// Write `param` to a normalized value picked uniformly from `[lo, hi]`.
+fn randomize_param<P: Param>(
+    param: &P,
+    rng: &mut XorShift,
+    lo: f32,
+    hi: f32,
+    emit: &mut impl FnMut(RawParamEvent),
+) {
+    let ptr = param.as_ptr();
+    let normalized = lo + (hi - lo) * rng.next_f32();
+    emit(RawParamEvent::BeginSetParameter(ptr));
+    emit(RawParamEvent::SetParameterNormalized(ptr, normalized));
+    emit(RawParamEvent::EndSetParameter(ptr));
+}
+
+/// Roll a new patch. Only the sound-design params are touched, and within
+/// ranges that stay playable: levels never drop to silence, the filter never
+/// closes completely, and the envelopes stay short enough to hear. Glide,
+/// modulation and key tracking are left as they are, as are locked groups.
+pub fn randomize(p: &SineParams, emit: &mut impl FnMut(RawParamEvent)) {
+    let emit = &mut locks::skip_locked(p, emit);
+    let mut rng = XorShift::from_time();
+    for osc in [&p.osc1, &p.osc2, &p.osc3] {
+        randomize_param(&osc.waveform, &mut rng, 0.0, 1.0, emit);
+        randomize_param(&osc.detune, &mut rng, 0.35, 0.65, emit);
+        randomize_param(&osc.gain, &mut rng, 0.5, 0.9, emit);
+        randomize_param(&osc.octave, &mut rng, 0.3, 0.7, emit);
+        randomize_param(&osc.unison_voices, &mut rng, 0.0, 1.0, emit);
+        randomize_param(&osc.unison_detune, &mut rng, 0.0, 0.5, emit);
+        randomize_param(&osc.unison_blend, &mut rng, 0.0, 1.0, emit);
+    }
+    randomize_param(&p.filter.mode, &mut rng, 0.0, 1.0, emit);
+    randomize_param(&p.filter.cutoff, &mut rng, 0.35, 1.0, emit);
+    randomize_param(&p.filter.resonance, &mut rng, 0.0, 0.7, emit);
+    randomize_param(&p.filter.env_amount, &mut rng, 0.4, 0.8, emit);
+    for env in [&p.adsr, &p.filter_env] {
+        randomize_param(&env.attack, &mut rng, 0.0, 0.6, emit);
+        randomize_param(&env.decay, &mut rng, 0.2, 0.8, emit);
+        randomize_param(&env.sustain, &mut rng, 0.2, 1.0, emit);
+        randomize_param(&env.release, &mut rng, 0.1, 0.7, emit);
+    }
+}
@@ -0,0 +1,94 @@
+//! The `play_notes` tool: plays notes on the synth by the same path as played
+//! keys (snapped to the scale, then through the arp while it's on), so the
+//! assistant can let the user hear a patch, or give `adjust_brightness`
+//! something to measure, without a keyboard.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::ai::tool_bridge::scale_root_name;
+use crate::diagnostics::{NoteRequest, MAX_NOTE_REQUESTS};
+use crate::dsp::scale::{self, Quantizer};
+use crate::{Diagnostics, ScaleRoot};
+
+/// Longest a requested note is held.
+pub const MAX_SECONDS: f32 = 10.0;
+/// How long the audio thread gets to pick the notes up.
+const START_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// A MIDI note number, or a note name with its octave: `"C4"` is middle C
+/// (60), `"F#3"` 54, `"Bb-1"` 10.
+pub fn parse_note(value: &Value) -> Result<u8, String> {
+    let note = match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.trim().parse().ok().or_else(|| {
+            let s = s.trim();
+            // The octave starts at the first digit or minus sign after the letter.
+            let split = s
+                .char_indices()
+                .skip(1)
+                .find(|(_, c)| c.is_ascii_digit() || *c == '-');
+            let (name, octave) = s.split_at(split?.0);
+            // Checked: the octave is whatever was typed.
+            let octave: i64 = octave.parse().ok()?;
+            let c = octave.checked_add(1)?.checked_mul(12)?;
+            c.checked_add(i64::from(scale::pitch_class(name)?))
+        }),
+        _ => None,
+    };
+    note.filter(|n| (0..=127).contains(n))
+        .map(|n| n as u8)
+        .ok_or_else(|| format!("'{value}' isn't a note: use a name like C4 or F#3, or 0..127"))
+}
+
+/// `note` by name, e.g. 60 is `"C4"`.
+pub fn note_name(note: u8) -> String {
+    let name = scale_root_name(ScaleRoot::from_semitone(note % 12));
+    let octave = i32::from(note / 12) - 1;
+    format!("{name}{octave}")
+}
+
+/// Play `notes` together for `seconds`. Returns the notes as the scale
+/// quantizer sounds them.
+pub fn play(
+    diagnostics: &Diagnostics,
+    scale: Quantizer,
+    notes: &[u8],
+    velocity: f32,
+    seconds: f32,
+) -> Result<Value, String> {
+    if notes.is_empty() || notes.len() > MAX_NOTE_REQUESTS {
+        return Err(format!("play 1 to {MAX_NOTE_REQUESTS} notes at a time"));
+    }
+    let seconds = seconds.clamp(0.05, MAX_SECONDS);
+    for &note in notes {
+        let request = NoteRequest {
+            note,
+            velocity: velocity.clamp(0.0, 1.0),
+            seconds,
+        };
+        if !diagnostics.request_note(request) {
+            return Err("the previous notes haven't started yet; try again".to_string());
+        }
+    }
+
+    let deadline = Instant::now() + START_TIMEOUT;
+    while diagnostics.pending_note_requests() > 0 {
+        if Instant::now() > deadline {
+            // Don't let them go off whenever audio does start.
+            diagnostics.take_note_requests();
+            return Err("the audio engine isn't running, so nothing was played; \
+                        ask the user to start the host's audio"
+                .to_string());
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let played: Vec<String> = notes
+        .iter()
+        .map(|&note| note_name(scale.snap(note)))
+        .collect();
+    Ok(json!({ "status": "ok", "played": played, "seconds": seconds }))
+}
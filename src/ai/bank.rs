@@ -0,0 +1,95 @@
+//! Preset banks: a set of saved presets, metadata included, in one JSON file
+//! under `banks/<name>.json`, for sharing a sound pack. Exporting reads the
+//! presets from `presets/`; importing writes them back there, renaming,
+//! overwriting or skipping any whose name is already taken (see
+//! `bank_io.rs`).
+//!
+//! MIDI program changes load presets straight from here, without importing
+//! them: see [`program`].
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::preset::{self, app_dir, sanitize, PresetData, SCHEMA_VERSION};
+
+/// Bumped when the bank layout changes incompatibly; the presets inside carry
+/// their own schema version.
+pub(super) const BANK_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct BankFile {
+    pub(super) version: u32,
+    pub(super) presets: Vec<PresetData>,
+}
+
+pub fn banks_dir() -> PathBuf {
+    app_dir().join("banks")
+}
+
+/// Names (file stems) of every bank, sorted.
+pub fn list() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(banks_dir()) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.extension().and_then(|x| x.to_str()) == Some("json"))
+        .filter_map(|path| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Read `banks/<name>.json`, refusing a bank or preset newer than this build.
+pub(super) fn read(name: &str) -> Result<BankFile, String> {
+    let path = banks_dir().join(format!("{}.json", sanitize(name)));
+    let text =
+        std::fs::read_to_string(&path).map_err(|e| format!("read {}: {e}", path.display()))?;
+    let bank: BankFile =
+        serde_json::from_str(&text).map_err(|e| format!("parse {}: {e}", path.display()))?;
+    if bank.version > BANK_VERSION {
+        return Err(format!(
+            "bank '{name}' is version {} but this build reads up to {BANK_VERSION}",
+            bank.version
+        ));
+    }
+    let newer = bank.presets.iter().find(|p| p.schema_version > SCHEMA_VERSION);
+    if let Some(newer) = newer {
+        return Err(format!(
+            "preset '{}' uses schema version {} but this build supports up to {SCHEMA_VERSION}",
+            newer.name, newer.schema_version
+        ));
+    }
+    Ok(bank)
+}
+
+/// The preset a MIDI program change picks, and its name. Bank 0 is the saved
+/// presets; bank `n` is the `n`th bank file. Both are in name order, and
+/// `program` counts from 0 within them (a controller's "program 1").
+pub fn program(bank: u16, program: u8) -> Result<(String, PresetData), String> {
+    let index = usize::from(program);
+    if bank == 0 {
+        let names = preset::list();
+        let name = names
+            .get(index)
+            .ok_or_else(|| format!("there is no saved preset {}", index + 1))?;
+        return Ok((name.clone(), preset::load(name)?));
+    }
+    let banks = list();
+    let name = banks
+        .get(usize::from(bank) - 1)
+        .ok_or_else(|| format!("there is no bank {bank}"))?;
+    let mut presets = read(name)?.presets;
+    if index >= presets.len() {
+        return Err(format!("bank '{name}' has no preset {}", index + 1));
+    }
+    let data = presets.swap_remove(index);
+    let preset_name = if data.name.trim().is_empty() {
+        format!("{name} {}", index + 1)
+    } else {
+        data.name.clone()
+    };
+    Ok((preset_name, data))
+}
@@ -0,0 +1,140 @@
+//! Exporting and importing preset banks (see `bank.rs`), for the assistant's
+//! `export_bank` and `import_bank` tools and the chat's `/bank` command.
+
+use std::path::PathBuf;
+
+use super::bank::{banks_dir, read, BankFile, BANK_VERSION};
+use super::preset::{self, sanitize};
+
+/// What an import does with a preset whose name is already saved.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BankConflict {
+    /// Save it as the first free "<name> 2", "<name> 3", ...
+    #[default]
+    Rename,
+    Overwrite,
+    Skip,
+}
+
+impl BankConflict {
+    pub const ALL: [Self; 3] = [Self::Rename, Self::Overwrite, Self::Skip];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Rename => "rename",
+            Self::Overwrite => "overwrite",
+            Self::Skip => "skip",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Result<Self, String> {
+        let id = id.trim().to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|conflict| conflict.id() == id)
+            .ok_or_else(|| format!("unknown conflict policy '{id}' (rename, overwrite or skip)"))
+    }
+}
+
+/// What an import did.
+#[derive(Debug, Default)]
+pub struct BankImport {
+    /// Every preset saved, under the name it was saved as.
+    pub imported: Vec<String>,
+    /// Saved under a new name: (name in the bank, name saved as).
+    pub renamed: Vec<(String, String)>,
+    /// Saved over a preset of the same name.
+    pub overwritten: Vec<String>,
+    /// Left out because the name was taken.
+    pub skipped: Vec<String>,
+}
+
+impl BankImport {
+    /// One line for the chat, e.g. "imported 5 presets (2 renamed), skipped 1".
+    pub fn summary(&self) -> String {
+        let mut notes = Vec::new();
+        if !self.renamed.is_empty() {
+            notes.push(format!("{} renamed", self.renamed.len()));
+        }
+        if !self.overwritten.is_empty() {
+            notes.push(format!("{} overwritten", self.overwritten.len()));
+        }
+        let mut summary = format!("imported {} presets", self.imported.len());
+        if !notes.is_empty() {
+            summary.push_str(&format!(" ({})", notes.join(", ")));
+        }
+        if !self.skipped.is_empty() {
+            summary.push_str(&format!(", skipped {}", self.skipped.len()));
+        }
+        summary
+    }
+}
+
+/// Write the saved presets `names` (all of them if empty) to
+/// `banks/<name>.json`. Returns the file and how many presets went in.
+pub fn export(name: &str, names: &[String]) -> Result<(PathBuf, usize), String> {
+    let names = if names.is_empty() { preset::list() } else { names.to_vec() };
+    if names.is_empty() {
+        return Err("there are no saved presets to export".to_string());
+    }
+    let presets = names
+        .iter()
+        .map(|name| {
+            let mut data = preset::load(name)?;
+            if data.name.trim().is_empty() {
+                data.name = name.clone();
+            }
+            Ok(data)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let dir = banks_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| format!("create banks dir: {e}"))?;
+    let path = dir.join(format!("{}.json", sanitize(name)));
+    let bank = BankFile { version: BANK_VERSION, presets };
+    let json = serde_json::to_string_pretty(&bank).map_err(|e| format!("serialize: {e}"))?;
+    std::fs::write(&path, json).map_err(|e| format!("write {}: {e}", path.display()))?;
+    Ok((path, bank.presets.len()))
+}
+
+/// Read `banks/<name>.json` and save its presets, settling name clashes per
+/// `conflict`. Nothing is saved if the bank doesn't read or holds a preset
+/// newer than this build.
+pub fn import(name: &str, conflict: BankConflict) -> Result<BankImport, String> {
+    let bank = read(name)?;
+    let mut report = BankImport::default();
+    for mut data in bank.presets {
+        if data.name.trim().is_empty() {
+            data.name = "untitled".to_string();
+        }
+        let original = data.name.clone();
+        if preset::preset_path(&original).exists() {
+            match conflict {
+                BankConflict::Skip => {
+                    report.skipped.push(original);
+                    continue;
+                }
+                BankConflict::Overwrite => report.overwritten.push(original.clone()),
+                BankConflict::Rename => {
+                    data.name = free_name(&original);
+                    report.renamed.push((original, data.name.clone()));
+                }
+            }
+        }
+        preset::write(&data)?;
+        report.imported.push(data.name);
+    }
+    Ok(report)
+}
+
+/// `name` with the first number from 2 up that no saved preset has yet.
+fn free_name(name: &str) -> String {
+    let mut n = 2;
+    loop {
+        let candidate = format!("{name} {n}");
+        if !preset::preset_path(&candidate).exists() {
+            return candidate;
+        }
+        n += 1;
+    }
+}
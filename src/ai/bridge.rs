@@ -6,7 +6,13 @@
 //! gesture, and the audio thread picks it up by reading atomics. No mirror, no
 //! locks on the audio thread.
 
-use crate::{FilterMode, SineParams, Waveform};
+use crate::dsp::scale;
+use crate::params::{NUM_DRAWBARS, NUM_GATE_STEPS};
+use crate::{
+    ArpMode, BendRelease, DetuneCurve, DriveCharacter, FilterMode, GlideMode, LfoReset,
+    ModDestination, ModSource, NoiseColor, NoteDivision, Scale, ScaleRoot, SineParams, Smoothing,
+    SubWaveform, Waveform,
+};
 use nih_plug::prelude::Param;
 use serde_json::Value;
 use vizia_plug::widgets::RawParamEvent;
@@ -23,7 +29,7 @@ pub fn emit_set<P: Param>(param: &P, plain: P::Plain, emit: &mut impl FnMut(RawP
 }
 
 /// Read a JSON value as `f32`, accepting both numbers and numeric strings.
-fn as_f32(v: &Value) -> Result<f32, String> {
+pub(super) fn as_f32(v: &Value) -> Result<f32, String> {
     if let Some(n) = v.as_f64() {
         return Ok(n as f32);
     }
@@ -45,6 +51,87 @@ fn as_i32(v: &Value) -> Result<i32, String> {
         .ok_or_else(|| "expected an integer".to_string())
 }
 
+/// Read a level as linear gain. Numbers are taken as linear gain (the unit the
+/// params and preset files store); strings may also be given in dB (`"-6 dB"`)
+/// or as `"-inf"` for a muted stage.
+pub(super) fn as_gain(v: &Value) -> Result<f32, String> {
+    if let Some(n) = v.as_f64() {
+        return Ok(n as f32);
+    }
+    let s = v.as_str().map(str::trim).unwrap_or_default();
+    if s.eq_ignore_ascii_case("-inf") {
+        return Ok(0.0);
+    }
+    if let Some(db) = s
+        .strip_suffix("dB")
+        .or_else(|| s.strip_suffix("db"))
+        .and_then(|db| db.trim().parse::<f32>().ok())
+    {
+        return Ok(nih_plug::util::db_to_gain(db));
+    }
+    s.parse::<f32>()
+        .map_err(|_| "expected a linear gain, a level in dB (\"-6 dB\"), or \"-inf\"".to_string())
+}
+
+/// Read a JSON array of [`NUM_DRAWBARS`] levels (`0..1`, harmonic 1 first).
+fn as_drawbars(v: &Value) -> Result<Vec<f32>, String> {
+    let expected = || format!("expected an array of {NUM_DRAWBARS} drawbar levels (0..1)");
+    let levels = v.as_array().ok_or_else(expected)?;
+    if levels.len() != NUM_DRAWBARS {
+        return Err(expected());
+    }
+    levels.iter().map(as_f32).collect()
+}
+
+/// Read a trance gate pattern: a string of [`NUM_GATE_STEPS`] steps, `x` for
+/// open and `.` for gated (spaces ignored, e.g. `"x.xx x.x. x.xx x.x."`), or
+/// an array of [`NUM_GATE_STEPS`] booleans.
+fn as_gate_pattern(v: &Value) -> Result<Vec<bool>, String> {
+    let expected = || {
+        format!(
+            "expected {NUM_GATE_STEPS} steps: a string of x (open) and . (gated), \
+             or an array of booleans"
+        )
+    };
+    let steps = match v {
+        Value::String(s) => s
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| match c {
+                'x' | 'X' | '1' => Ok(true),
+                '.' | '-' | '0' => Ok(false),
+                _ => Err(expected()),
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        Value::Array(steps) => steps.iter().map(as_bool).collect::<Result<_, _>>()?,
+        _ => return Err(expected()),
+    };
+    if steps.len() != NUM_GATE_STEPS {
+        return Err(expected());
+    }
+    Ok(steps)
+}
+
+/// A trance gate pattern as `as_gate_pattern` reads it, without spaces.
+pub fn gate_pattern_to_id(steps: impl IntoIterator<Item = bool>) -> String {
+    steps.into_iter().map(|on| if on { 'x' } else { '.' }).collect()
+}
+
+/// Read a JSON value as `bool`, accepting booleans, 0/1, and on/off-style strings.
+fn as_bool(v: &Value) -> Result<bool, String> {
+    if let Some(b) = v.as_bool() {
+        return Ok(b);
+    }
+    if let Some(n) = v.as_f64() {
+        return Ok(n >= 0.5);
+    }
+    match v.as_str().map(|s| s.trim().to_lowercase()).as_deref() {
+        Some("true" | "on" | "yes" | "1") => Ok(true),
+        Some("false" | "off" | "no" | "0") => Ok(false),
+        _ => Err("expected true/false".to_string()),
+    }
+}
+
 pub fn wave_to_id(w: Waveform) -> &'static str {
     match w {
         Waveform::Sine => "sine",
@@ -69,6 +156,7 @@ pub fn mode_to_id(m: FilterMode) -> &'static str {
         FilterMode::HighPass => "highpass",
         FilterMode::BandPass => "bandpass",
         FilterMode::Notch => "notch",
+        FilterMode::Formant => "formant",
     }
 }
 
@@ -77,10 +165,231 @@ pub fn id_to_mode(s: &str) -> FilterMode {
         "highpass" | "hp" => FilterMode::HighPass,
         "bandpass" | "bp" => FilterMode::BandPass,
         "notch" => FilterMode::Notch,
+        "formant" | "vowel" => FilterMode::Formant,
         _ => FilterMode::LowPass,
     }
 }
 
+pub fn glide_mode_to_id(m: GlideMode) -> &'static str {
+    match m {
+        GlideMode::ConstantTime => "constant_time",
+        GlideMode::ConstantRate => "constant_rate",
+    }
+}
+
+pub fn id_to_glide_mode(s: &str) -> GlideMode {
+    match s.trim().to_lowercase().replace([' ', '-'], "_").as_str() {
+        "constant_rate" | "rate" => GlideMode::ConstantRate,
+        _ => GlideMode::ConstantTime,
+    }
+}
+
+pub fn bend_release_to_id(r: BendRelease) -> &'static str {
+    match r {
+        BendRelease::Follow => "follow",
+        BendRelease::Hold => "hold",
+        BendRelease::Return => "return",
+    }
+}
+
+pub fn id_to_bend_release(s: &str) -> BendRelease {
+    match s.trim().to_lowercase().as_str() {
+        "hold" => BendRelease::Hold,
+        "return" => BendRelease::Return,
+        _ => BendRelease::Follow,
+    }
+}
+
+pub fn drive_character_to_id(c: DriveCharacter) -> &'static str {
+    match c {
+        DriveCharacter::Soft => "soft",
+        DriveCharacter::Asymmetric => "asymmetric",
+        DriveCharacter::Tube => "tube",
+    }
+}
+
+pub fn id_to_drive_character(s: &str) -> DriveCharacter {
+    match s.trim().to_lowercase().as_str() {
+        "asymmetric" | "asym" => DriveCharacter::Asymmetric,
+        "tube" | "valve" => DriveCharacter::Tube,
+        _ => DriveCharacter::Soft,
+    }
+}
+
+pub fn lfo_reset_to_id(r: LfoReset) -> &'static str {
+    match r {
+        LfoReset::Free => "free",
+        LfoReset::Transport => "transport",
+        LfoReset::Bar => "bar",
+    }
+}
+
+pub fn id_to_lfo_reset(s: &str) -> LfoReset {
+    match s.trim().to_lowercase().as_str() {
+        "free" | "off" | "none" => LfoReset::Free,
+        "bar" | "bars" => LfoReset::Bar,
+        _ => LfoReset::Transport,
+    }
+}
+
+pub fn curve_to_id(c: DetuneCurve) -> &'static str {
+    match c {
+        DetuneCurve::Linear => "linear",
+        DetuneCurve::Exponential => "exponential",
+        DetuneCurve::SuperSaw => "supersaw",
+    }
+}
+
+pub fn id_to_curve(s: &str) -> DetuneCurve {
+    match s.trim().to_lowercase().replace([' ', '-', '_'], "").as_str() {
+        "exponential" | "exp" => DetuneCurve::Exponential,
+        "supersaw" => DetuneCurve::SuperSaw,
+        _ => DetuneCurve::Linear,
+    }
+}
+
+pub fn noise_color_to_id(c: NoiseColor) -> &'static str {
+    match c {
+        NoiseColor::White => "white",
+        NoiseColor::Pink => "pink",
+    }
+}
+
+pub fn id_to_noise_color(s: &str) -> NoiseColor {
+    match s.trim().to_lowercase().as_str() {
+        "pink" => NoiseColor::Pink,
+        _ => NoiseColor::White,
+    }
+}
+
+pub fn sub_wave_to_id(w: SubWaveform) -> &'static str {
+    match w {
+        SubWaveform::Sine => "sine",
+        SubWaveform::Square => "square",
+    }
+}
+
+pub fn id_to_sub_wave(s: &str) -> SubWaveform {
+    match s.trim().to_lowercase().as_str() {
+        "square" => SubWaveform::Square,
+        _ => SubWaveform::Sine,
+    }
+}
+
+pub fn id_to_smoothing(s: &str) -> Smoothing {
+    match s.trim().to_lowercase().as_str() {
+        "fast" => Smoothing::Fast,
+        "medium" | "med" => Smoothing::Medium,
+        _ => Smoothing::Slow,
+    }
+}
+
+pub fn mod_source_to_id(s: ModSource) -> &'static str {
+    match s {
+        ModSource::Lfo => "lfo",
+        ModSource::FilterEnv => "filter_env",
+        ModSource::Velocity => "velocity",
+        ModSource::Sidechain => "sidechain",
+        ModSource::Release => "release",
+    }
+}
+
+pub fn id_to_mod_source(s: &str) -> ModSource {
+    match s.trim().to_lowercase().replace([' ', '-'], "_").as_str() {
+        "filter_env" | "fenv" | "env" => ModSource::FilterEnv,
+        "velocity" | "vel" => ModSource::Velocity,
+        "sidechain" | "sc" => ModSource::Sidechain,
+        "release" | "rel" | "note_off" => ModSource::Release,
+        _ => ModSource::Lfo,
+    }
+}
+
+pub fn mod_dest_to_id(d: ModDestination) -> &'static str {
+    match d {
+        ModDestination::Off => "none",
+        ModDestination::Pitch => "pitch",
+        ModDestination::PulseWidth => "pulse_width",
+        ModDestination::Level => "level",
+        ModDestination::Cutoff => "cutoff",
+        ModDestination::Resonance => "resonance",
+        ModDestination::Vowel => "vowel",
+        ModDestination::Morph => "morph",
+    }
+}
+
+pub fn id_to_mod_dest(s: &str) -> ModDestination {
+    match s.trim().to_lowercase().replace([' ', '-'], "_").as_str() {
+        "pitch" => ModDestination::Pitch,
+        "pulse_width" | "pw" => ModDestination::PulseWidth,
+        "level" | "amp" => ModDestination::Level,
+        "cutoff" | "filter_cutoff" => ModDestination::Cutoff,
+        "resonance" | "filter_resonance" => ModDestination::Resonance,
+        "vowel" | "filter_vowel" => ModDestination::Vowel,
+        "morph" | "wave_morph" => ModDestination::Morph,
+        _ => ModDestination::Off,
+    }
+}
+
+pub fn division_to_id(d: NoteDivision) -> &'static str {
+    match d {
+        NoteDivision::ThirtySecond => "1/32",
+        NoteDivision::Sixteenth => "1/16",
+        NoteDivision::Eighth => "1/8",
+        NoteDivision::Quarter => "1/4",
+        NoteDivision::Half => "1/2",
+        NoteDivision::Whole => "1/1",
+    }
+}
+
+pub fn id_to_division(s: &str) -> NoteDivision {
+    match s.trim().to_lowercase().replace(' ', "").as_str() {
+        "1/32" => NoteDivision::ThirtySecond,
+        "1/16" => NoteDivision::Sixteenth,
+        "1/4" => NoteDivision::Quarter,
+        "1/2" => NoteDivision::Half,
+        "1/1" | "1" | "1bar" | "bar" => NoteDivision::Whole,
+        _ => NoteDivision::Eighth,
+    }
+}
+
+pub fn arp_mode_to_id(m: ArpMode) -> &'static str {
+    match m {
+        ArpMode::Up => "up",
+        ArpMode::Down => "down",
+        ArpMode::UpDown => "up_down",
+        ArpMode::Played => "played",
+        ArpMode::Random => "random",
+    }
+}
+
+pub fn id_to_arp_mode(s: &str) -> ArpMode {
+    match s.trim().to_lowercase().replace([' ', '-'], "_").as_str() {
+        "down" => ArpMode::Down,
+        "up_down" | "up/down" | "updown" | "pingpong" => ArpMode::UpDown,
+        "played" | "order" | "as_played" => ArpMode::Played,
+        "random" | "rand" => ArpMode::Random,
+        _ => ArpMode::Up,
+    }
+}
+
+pub fn id_to_scale(s: &str) -> Scale {
+    match s.trim().to_lowercase().replace([' ', '-'], "_").as_str() {
+        "major" | "ionian" => Scale::Major,
+        "minor" | "natural_minor" | "aeolian" => Scale::Minor,
+        "harmonic_minor" => Scale::HarmonicMinor,
+        "melodic_minor" => Scale::MelodicMinor,
+        "dorian" => Scale::Dorian,
+        "phrygian" => Scale::Phrygian,
+        "lydian" => Scale::Lydian,
+        "mixolydian" => Scale::Mixolydian,
+        "locrian" => Scale::Locrian,
+        "major_pentatonic" | "pentatonic" => Scale::MajorPentatonic,
+        "minor_pentatonic" => Scale::MinorPentatonic,
+        "blues" => Scale::Blues,
+        _ => Scale::Chromatic,
+    }
+}
+
 fn parse_wave(v: &Value) -> Result<Waveform, String> {
     v.as_str()
         .map(id_to_wave)
@@ -88,15 +397,104 @@ fn parse_wave(v: &Value) -> Result<Waveform, String> {
 }
 
 fn parse_mode(v: &Value) -> Result<FilterMode, String> {
+    v.as_str().map(id_to_mode).ok_or_else(|| {
+        "expected a filter mode (lowpass/highpass/bandpass/notch/formant)".to_string()
+    })
+}
+
+fn parse_mod_source(v: &Value) -> Result<ModSource, String> {
+    v.as_str().map(id_to_mod_source).ok_or_else(|| {
+        "expected a mod source (lfo/filter_env/velocity/sidechain/release)".to_string()
+    })
+}
+
+fn parse_mod_dest(v: &Value) -> Result<ModDestination, String> {
+    v.as_str().map(id_to_mod_dest).ok_or_else(|| {
+        "expected a mod destination (none/pitch/pulse_width/level/cutoff/resonance/vowel/morph)"
+            .to_string()
+    })
+}
+
+fn parse_division(v: &Value) -> Result<NoteDivision, String> {
     v.as_str()
-        .map(id_to_mode)
-        .ok_or_else(|| "expected a filter mode (lowpass/highpass/bandpass/notch)".to_string())
+        .map(id_to_division)
+        .ok_or_else(|| "expected a note division (1/32, 1/16, 1/8, 1/4, 1/2, 1/1)".to_string())
+}
+
+fn parse_glide_mode(v: &Value) -> Result<GlideMode, String> {
+    v.as_str()
+        .map(id_to_glide_mode)
+        .ok_or_else(|| "expected a glide mode (constant_time/constant_rate)".to_string())
+}
+
+fn parse_bend_release(v: &Value) -> Result<BendRelease, String> {
+    v.as_str()
+        .map(id_to_bend_release)
+        .ok_or_else(|| "expected a bend release (follow/hold/return)".to_string())
+}
+
+fn parse_drive_character(v: &Value) -> Result<DriveCharacter, String> {
+    v.as_str()
+        .map(id_to_drive_character)
+        .ok_or_else(|| "expected a drive character (soft/asymmetric/tube)".to_string())
+}
+
+fn parse_lfo_reset(v: &Value) -> Result<LfoReset, String> {
+    v.as_str()
+        .map(id_to_lfo_reset)
+        .ok_or_else(|| "expected an LFO reset mode (free/transport/bar)".to_string())
+}
+
+fn parse_curve(v: &Value) -> Result<DetuneCurve, String> {
+    v.as_str()
+        .map(id_to_curve)
+        .ok_or_else(|| "expected a detune curve (linear/exponential/supersaw)".to_string())
+}
+
+fn parse_noise_color(v: &Value) -> Result<NoiseColor, String> {
+    v.as_str()
+        .map(id_to_noise_color)
+        .ok_or_else(|| "expected a noise color (white/pink)".to_string())
+}
+
+fn parse_sub_wave(v: &Value) -> Result<SubWaveform, String> {
+    v.as_str()
+        .map(id_to_sub_wave)
+        .ok_or_else(|| "expected a sub waveform (sine/square)".to_string())
+}
+
+fn parse_arp_mode(v: &Value) -> Result<ArpMode, String> {
+    v.as_str()
+        .map(id_to_arp_mode)
+        .ok_or_else(|| "expected an arp mode (up/down/up_down/played/random)".to_string())
+}
+
+pub fn parse_scale(v: &Value) -> Result<Scale, String> {
+    v.as_str().map(id_to_scale).ok_or_else(|| {
+        "expected a scale (chromatic/major/minor/harmonic_minor/melodic_minor/dorian/\
+         phrygian/lydian/mixolydian/locrian/major_pentatonic/minor_pentatonic/blues)"
+            .to_string()
+    })
+}
+
+pub fn parse_scale_root(v: &Value) -> Result<ScaleRoot, String> {
+    v.as_str()
+        .and_then(scale::pitch_class)
+        .map(ScaleRoot::from_semitone)
+        .ok_or_else(|| "expected a key (a note name such as C, F# or Bb)".to_string())
+}
+
+fn parse_smoothing(v: &Value) -> Result<Smoothing, String> {
+    v.as_str()
+        .map(id_to_smoothing)
+        .ok_or_else(|| "expected a smoothing time (fast/medium/slow)".to_string())
 }
 
 /// Resolve a `set_parameter` tool call to a parameter write and emit it.
 ///
-/// `name` is the canonical snake-case vocabulary shared with [`read_state`] and
-/// the preset files (`frequency1`, `filter_cutoff`, `attack`, ...).
+/// `name` is the canonical snake-case vocabulary shared with `get_state` (see
+/// `tool_bridge.rs`) and the preset files (`frequency1`, `filter_cutoff`,
+/// `attack`, ...).
 pub fn apply_write(
     p: &SineParams,
     name: &str,
@@ -107,66 +505,291 @@ pub fn apply_write(
         // --- Oscillator 1 ---
         "waveform1" => emit_set(&p.osc1.waveform, parse_wave(value)?, emit),
         "frequency1" => emit_set(&p.osc1.frequency, as_f32(value)?, emit),
+        "key_track1" => emit_set(&p.osc1.key_track, as_bool(value)?, emit),
+        "mute1" => emit_set(&p.osc1.mute, as_bool(value)?, emit),
+        "solo1" => emit_set(&p.osc1.solo, as_bool(value)?, emit),
         "detune1" => emit_set(&p.osc1.detune, as_f32(value)?, emit),
         "phase1" => emit_set(&p.osc1.phase, as_f32(value)?, emit),
-        "gain1" => emit_set(&p.osc1.gain, as_f32(value)?, emit),
+        "phase_dist1" => emit_set(&p.osc1.phase_distortion, as_f32(value)?, emit),
+        "wavefold1" => emit_set(&p.osc1.wavefold, as_f32(value)?, emit),
+        "morph1" => emit_set(&p.osc1.morph, as_bool(value)?, emit),
+        "morph_position1" => emit_set(&p.osc1.morph_position, as_f32(value)?, emit),
+        "gain1" => emit_set(&p.osc1.gain, as_gain(value)?, emit),
         "octave1" => emit_set(&p.osc1.octave, as_i32(value)?, emit),
         "unison_voices1" => emit_set(&p.osc1.unison_voices, as_i32(value)?, emit),
         "unison_detune1" => emit_set(&p.osc1.unison_detune, as_f32(value)?, emit),
         "unison_blend1" => emit_set(&p.osc1.unison_blend, as_f32(value)?, emit),
-        "unison_volume1" => emit_set(&p.osc1.unison_volume, as_f32(value)?, emit),
+        "unison_volume1" => emit_set(&p.osc1.unison_volume, as_gain(value)?, emit),
+        "unison_curve1" => emit_set(&p.osc1.unison_curve, parse_curve(value)?, emit),
+        "unison_density1" => emit_set(&p.osc1.unison_density, as_f32(value)?, emit),
+        "unison_comp1" => emit_set(&p.osc1.unison_compensation, as_bool(value)?, emit),
+        "to_filter1" => emit_set(&p.osc1.to_filter, as_bool(value)?, emit),
+        "own_env1" => emit_set(&p.osc1.own_envelope, as_bool(value)?, emit),
+        "env_attack1" => emit_set(&p.osc1.envelope.attack, as_f32(value)?, emit),
+        "env_hold1" => emit_set(&p.osc1.envelope.hold, as_f32(value)?, emit),
+        "env_decay1" => emit_set(&p.osc1.envelope.decay, as_f32(value)?, emit),
+        "env_sustain1" => emit_set(&p.osc1.envelope.sustain, as_f32(value)?, emit),
+        "env_release1" => emit_set(&p.osc1.envelope.release, as_f32(value)?, emit),
+        "env_attack_sync1" => emit_set(&p.osc1.envelope.attack_sync, as_bool(value)?, emit),
+        "env_attack_division1" => {
+            emit_set(&p.osc1.envelope.attack_division, parse_division(value)?, emit)
+        }
+        "env_decay_sync1" => emit_set(&p.osc1.envelope.decay_sync, as_bool(value)?, emit),
+        "env_decay_division1" => {
+            emit_set(&p.osc1.envelope.decay_division, parse_division(value)?, emit)
+        }
+        "env_release_sync1" => emit_set(&p.osc1.envelope.release_sync, as_bool(value)?, emit),
+        "env_release_division1" => {
+            emit_set(&p.osc1.envelope.release_division, parse_division(value)?, emit)
+        }
+        "additive1" => emit_set(&p.additive.enabled, as_bool(value)?, emit),
+        "drawbars1" => {
+            for (drawbar, level) in p.additive.drawbars.iter().zip(as_drawbars(value)?) {
+                emit_set(&drawbar.level, level, emit);
+            }
+        }
 
         // --- Oscillator 2 ---
         "waveform2" => emit_set(&p.osc2.waveform, parse_wave(value)?, emit),
         "frequency2" => emit_set(&p.osc2.frequency, as_f32(value)?, emit),
+        "key_track2" => emit_set(&p.osc2.key_track, as_bool(value)?, emit),
+        "mute2" => emit_set(&p.osc2.mute, as_bool(value)?, emit),
+        "solo2" => emit_set(&p.osc2.solo, as_bool(value)?, emit),
         "detune2" => emit_set(&p.osc2.detune, as_f32(value)?, emit),
         "phase2" => emit_set(&p.osc2.phase, as_f32(value)?, emit),
-        "gain2" => emit_set(&p.osc2.gain, as_f32(value)?, emit),
+        "phase_dist2" => emit_set(&p.osc2.phase_distortion, as_f32(value)?, emit),
+        "wavefold2" => emit_set(&p.osc2.wavefold, as_f32(value)?, emit),
+        "morph2" => emit_set(&p.osc2.morph, as_bool(value)?, emit),
+        "morph_position2" => emit_set(&p.osc2.morph_position, as_f32(value)?, emit),
+        "gain2" => emit_set(&p.osc2.gain, as_gain(value)?, emit),
         "octave2" => emit_set(&p.osc2.octave, as_i32(value)?, emit),
         "unison_voices2" => emit_set(&p.osc2.unison_voices, as_i32(value)?, emit),
         "unison_detune2" => emit_set(&p.osc2.unison_detune, as_f32(value)?, emit),
         "unison_blend2" => emit_set(&p.osc2.unison_blend, as_f32(value)?, emit),
-        "unison_volume2" => emit_set(&p.osc2.unison_volume, as_f32(value)?, emit),
+        "unison_volume2" => emit_set(&p.osc2.unison_volume, as_gain(value)?, emit),
+        "unison_curve2" => emit_set(&p.osc2.unison_curve, parse_curve(value)?, emit),
+        "unison_density2" => emit_set(&p.osc2.unison_density, as_f32(value)?, emit),
+        "unison_comp2" => emit_set(&p.osc2.unison_compensation, as_bool(value)?, emit),
+        "to_filter2" => emit_set(&p.osc2.to_filter, as_bool(value)?, emit),
+        "own_env2" => emit_set(&p.osc2.own_envelope, as_bool(value)?, emit),
+        "env_attack2" => emit_set(&p.osc2.envelope.attack, as_f32(value)?, emit),
+        "env_hold2" => emit_set(&p.osc2.envelope.hold, as_f32(value)?, emit),
+        "env_decay2" => emit_set(&p.osc2.envelope.decay, as_f32(value)?, emit),
+        "env_sustain2" => emit_set(&p.osc2.envelope.sustain, as_f32(value)?, emit),
+        "env_release2" => emit_set(&p.osc2.envelope.release, as_f32(value)?, emit),
+        "env_attack_sync2" => emit_set(&p.osc2.envelope.attack_sync, as_bool(value)?, emit),
+        "env_attack_division2" => {
+            emit_set(&p.osc2.envelope.attack_division, parse_division(value)?, emit)
+        }
+        "env_decay_sync2" => emit_set(&p.osc2.envelope.decay_sync, as_bool(value)?, emit),
+        "env_decay_division2" => {
+            emit_set(&p.osc2.envelope.decay_division, parse_division(value)?, emit)
+        }
+        "env_release_sync2" => emit_set(&p.osc2.envelope.release_sync, as_bool(value)?, emit),
+        "env_release_division2" => {
+            emit_set(&p.osc2.envelope.release_division, parse_division(value)?, emit)
+        }
 
         // --- Oscillator 3 ---
         "waveform3" => emit_set(&p.osc3.waveform, parse_wave(value)?, emit),
         "frequency3" => emit_set(&p.osc3.frequency, as_f32(value)?, emit),
+        "key_track3" => emit_set(&p.osc3.key_track, as_bool(value)?, emit),
+        "mute3" => emit_set(&p.osc3.mute, as_bool(value)?, emit),
+        "solo3" => emit_set(&p.osc3.solo, as_bool(value)?, emit),
         "detune3" => emit_set(&p.osc3.detune, as_f32(value)?, emit),
         "phase3" => emit_set(&p.osc3.phase, as_f32(value)?, emit),
-        "gain3" => emit_set(&p.osc3.gain, as_f32(value)?, emit),
+        "phase_dist3" => emit_set(&p.osc3.phase_distortion, as_f32(value)?, emit),
+        "wavefold3" => emit_set(&p.osc3.wavefold, as_f32(value)?, emit),
+        "morph3" => emit_set(&p.osc3.morph, as_bool(value)?, emit),
+        "morph_position3" => emit_set(&p.osc3.morph_position, as_f32(value)?, emit),
+        "gain3" => emit_set(&p.osc3.gain, as_gain(value)?, emit),
         "octave3" => emit_set(&p.osc3.octave, as_i32(value)?, emit),
         "unison_voices3" => emit_set(&p.osc3.unison_voices, as_i32(value)?, emit),
         "unison_detune3" => emit_set(&p.osc3.unison_detune, as_f32(value)?, emit),
         "unison_blend3" => emit_set(&p.osc3.unison_blend, as_f32(value)?, emit),
-        "unison_volume3" => emit_set(&p.osc3.unison_volume, as_f32(value)?, emit),
+        "unison_volume3" => emit_set(&p.osc3.unison_volume, as_gain(value)?, emit),
+        "unison_curve3" => emit_set(&p.osc3.unison_curve, parse_curve(value)?, emit),
+        "unison_density3" => emit_set(&p.osc3.unison_density, as_f32(value)?, emit),
+        "unison_comp3" => emit_set(&p.osc3.unison_compensation, as_bool(value)?, emit),
+        "to_filter3" => emit_set(&p.osc3.to_filter, as_bool(value)?, emit),
+        "own_env3" => emit_set(&p.osc3.own_envelope, as_bool(value)?, emit),
+        "env_attack3" => emit_set(&p.osc3.envelope.attack, as_f32(value)?, emit),
+        "env_hold3" => emit_set(&p.osc3.envelope.hold, as_f32(value)?, emit),
+        "env_decay3" => emit_set(&p.osc3.envelope.decay, as_f32(value)?, emit),
+        "env_sustain3" => emit_set(&p.osc3.envelope.sustain, as_f32(value)?, emit),
+        "env_release3" => emit_set(&p.osc3.envelope.release, as_f32(value)?, emit),
+        "env_attack_sync3" => emit_set(&p.osc3.envelope.attack_sync, as_bool(value)?, emit),
+        "env_attack_division3" => {
+            emit_set(&p.osc3.envelope.attack_division, parse_division(value)?, emit)
+        }
+        "env_decay_sync3" => emit_set(&p.osc3.envelope.decay_sync, as_bool(value)?, emit),
+        "env_decay_division3" => {
+            emit_set(&p.osc3.envelope.decay_division, parse_division(value)?, emit)
+        }
+        "env_release_sync3" => emit_set(&p.osc3.envelope.release_sync, as_bool(value)?, emit),
+        "env_release_division3" => {
+            emit_set(&p.osc3.envelope.release_division, parse_division(value)?, emit)
+        }
 
         // --- Filter ---
         "filter_mode" => emit_set(&p.filter.mode, parse_mode(value)?, emit),
         "filter_cutoff" => emit_set(&p.filter.cutoff, as_f32(value)?, emit),
         "filter_resonance" => emit_set(&p.filter.resonance, as_f32(value)?, emit),
         "filter_drive" => emit_set(&p.filter.drive, as_f32(value)?, emit),
+        "filter_drive_character" => {
+            emit_set(&p.filter.drive_character, parse_drive_character(value)?, emit)
+        }
+        "filter_drive_compensation" => {
+            emit_set(&p.filter.drive_compensation, as_bool(value)?, emit)
+        }
         "filter_env_amount" => emit_set(&p.filter.env_amount, as_f32(value)?, emit),
+        "filter_vowel" => emit_set(&p.filter.vowel, as_f32(value)?, emit),
+
+        // --- Comb ---
+        "comb" => emit_set(&p.comb.enabled, as_bool(value)?, emit),
+        "comb_pluck" => emit_set(&p.comb.pluck, as_bool(value)?, emit),
+        "comb_feedback" => emit_set(&p.comb.feedback, as_f32(value)?, emit),
+        "comb_damping" => emit_set(&p.comb.damping, as_f32(value)?, emit),
+        "comb_mix" => emit_set(&p.comb.mix, as_f32(value)?, emit),
+
+        // --- Noise ---
+        "noise_color" => emit_set(&p.noise.color, parse_noise_color(value)?, emit),
+        "noise_level" => emit_set(&p.noise.level, as_gain(value)?, emit),
+
+        // --- Sub oscillator ---
+        "sub_waveform" => emit_set(&p.sub.waveform, parse_sub_wave(value)?, emit),
+        // "-2" reads as two octaves down, like "2".
+        "sub_octave" => emit_set(&p.sub.octave, as_i32(value)?.abs(), emit),
+        "sub_level" => emit_set(&p.sub.level, as_gain(value)?, emit),
 
         // --- Amp envelope ---
         "attack" => emit_set(&p.adsr.attack, as_f32(value)?, emit),
+        "hold" => emit_set(&p.adsr.hold, as_f32(value)?, emit),
         "decay" => emit_set(&p.adsr.decay, as_f32(value)?, emit),
         "sustain" => emit_set(&p.adsr.sustain, as_f32(value)?, emit),
         "release" => emit_set(&p.adsr.release, as_f32(value)?, emit),
+        "attack_sync" => emit_set(&p.adsr.attack_sync, as_bool(value)?, emit),
+        "attack_division" => emit_set(&p.adsr.attack_division, parse_division(value)?, emit),
+        "decay_sync" => emit_set(&p.adsr.decay_sync, as_bool(value)?, emit),
+        "decay_division" => emit_set(&p.adsr.decay_division, parse_division(value)?, emit),
+        "release_sync" => emit_set(&p.adsr.release_sync, as_bool(value)?, emit),
+        "release_division" => emit_set(&p.adsr.release_division, parse_division(value)?, emit),
 
         // --- Filter envelope ---
         "filter_attack" => emit_set(&p.filter_env.attack, as_f32(value)?, emit),
+        "filter_hold" => emit_set(&p.filter_env.hold, as_f32(value)?, emit),
         "filter_decay" => emit_set(&p.filter_env.decay, as_f32(value)?, emit),
         "filter_sustain" => emit_set(&p.filter_env.sustain, as_f32(value)?, emit),
         "filter_release" => emit_set(&p.filter_env.release, as_f32(value)?, emit),
+        "filter_attack_sync" => emit_set(&p.filter_env.attack_sync, as_bool(value)?, emit),
+        "filter_attack_division" => {
+            emit_set(&p.filter_env.attack_division, parse_division(value)?, emit)
+        }
+        "filter_decay_sync" => emit_set(&p.filter_env.decay_sync, as_bool(value)?, emit),
+        "filter_decay_division" => {
+            emit_set(&p.filter_env.decay_division, parse_division(value)?, emit)
+        }
+        "filter_release_sync" => emit_set(&p.filter_env.release_sync, as_bool(value)?, emit),
+        "filter_release_division" => {
+            emit_set(&p.filter_env.release_division, parse_division(value)?, emit)
+        }
+
+        // --- Glide ---
+        "glide_time" => emit_set(&p.glide.time, as_f32(value)?, emit),
+        "glide_legato" => emit_set(&p.glide.legato, as_bool(value)?, emit),
+        "glide_mode" => emit_set(&p.glide.mode, parse_glide_mode(value)?, emit),
+
+        // --- Pitch bend ---
+        "bend_range" => emit_set(&p.bend.range, as_i32(value)?, emit),
+        "bend_smoothing" => emit_set(&p.bend.smoothing, as_f32(value)?, emit),
+        "bend_release" => emit_set(&p.bend.release, parse_bend_release(value)?, emit),
+
+        // --- Modulation ---
+        "lfo_rate" => emit_set(&p.modulation.lfo_rate, as_f32(value)?, emit),
+        "lfo_shape" => emit_set(&p.modulation.lfo_shape, parse_wave(value)?, emit),
+        "lfo_sync" => emit_set(&p.modulation.lfo_sync, as_bool(value)?, emit),
+        "lfo_division" => emit_set(&p.modulation.lfo_division, parse_division(value)?, emit),
+        "lfo_reset" => emit_set(&p.modulation.lfo_reset, parse_lfo_reset(value)?, emit),
+        "sidechain_attack" => emit_set(&p.modulation.sidechain_attack, as_f32(value)?, emit),
+        "sidechain_release" => emit_set(&p.modulation.sidechain_release, as_f32(value)?, emit),
+        "release_blip" => emit_set(&p.modulation.release_blip, as_f32(value)?, emit),
+        "mod1_source" => emit_set(&p.modulation.slots[0].source, parse_mod_source(value)?, emit),
+        "mod1_destination" => emit_set(
+            &p.modulation.slots[0].destination,
+            parse_mod_dest(value)?,
+            emit,
+        ),
+        "mod1_amount" => emit_set(&p.modulation.slots[0].amount, as_f32(value)?, emit),
+        "mod2_source" => emit_set(&p.modulation.slots[1].source, parse_mod_source(value)?, emit),
+        "mod2_destination" => emit_set(
+            &p.modulation.slots[1].destination,
+            parse_mod_dest(value)?,
+            emit,
+        ),
+        "mod2_amount" => emit_set(&p.modulation.slots[1].amount, as_f32(value)?, emit),
+        "mod3_source" => emit_set(&p.modulation.slots[2].source, parse_mod_source(value)?, emit),
+        "mod3_destination" => emit_set(
+            &p.modulation.slots[2].destination,
+            parse_mod_dest(value)?,
+            emit,
+        ),
+        "mod3_amount" => emit_set(&p.modulation.slots[2].amount, as_f32(value)?, emit),
+
+        // --- Character ---
+        "analog" => emit_set(&p.analog, as_f32(value)?, emit),
+        "keyboard_pan" => emit_set(&p.keyboard_pan, as_f32(value)?, emit),
+        "color" => emit_set(&p.color.amount, as_f32(value)?, emit),
+        "color_cutoff" => emit_set(&p.color.cutoff, as_f32(value)?, emit),
+        "color_drive" => emit_set(&p.color.drive, as_f32(value)?, emit),
+        "color_shape" => emit_set(&p.color.shape, as_f32(value)?, emit),
+        "color_blend" => emit_set(&p.color.blend, as_f32(value)?, emit),
+        "release_velocity" => emit_set(&p.release_velocity, as_f32(value)?, emit),
+        "declick" => emit_set(&p.declick, as_bool(value)?, emit),
+        "smoothing" => emit_set(&p.smoothing, parse_smoothing(value)?, emit),
+        "auto_quality" => emit_set(&p.auto_quality, as_bool(value)?, emit),
+        "band_limited" => emit_set(&p.band_limited, as_bool(value)?, emit),
+        "program_change" => emit_set(&p.program_change, as_bool(value)?, emit),
+        "latch" => emit_set(&p.latch, as_bool(value)?, emit),
+        "input_mix" => emit_set(&p.input_mix, as_f32(value)?, emit),
+
+        // --- Master ---
+        "stereo_width" => emit_set(&p.stereo_width, as_f32(value)?, emit),
+        "width_crossover" => emit_set(&p.width_crossover, as_f32(value)?, emit),
+
+        // --- Trance gate ---
+        "gate" => emit_set(&p.gate.enabled, as_bool(value)?, emit),
+        "gate_division" => emit_set(&p.gate.division, parse_division(value)?, emit),
+        "gate_depth" => emit_set(&p.gate.depth, as_f32(value)?, emit),
+        "gate_smoothing" => emit_set(&p.gate.smoothing, as_f32(value)?, emit),
+        "gate_pan" => emit_set(&p.gate.pan, as_f32(value)?, emit),
+        "gate_pattern" => {
+            for (step, on) in p.gate.steps.iter().zip(as_gate_pattern(value)?) {
+                emit_set(&step.on, on, emit);
+            }
+        }
+
+        // --- Arpeggiator ---
+        "arp" => emit_set(&p.arp.enabled, as_bool(value)?, emit),
+        "arp_mode" => emit_set(&p.arp.mode, parse_arp_mode(value)?, emit),
+        "arp_division" => emit_set(&p.arp.division, parse_division(value)?, emit),
+        "arp_octaves" => emit_set(&p.arp.octaves, as_i32(value)?, emit),
+        "arp_gate" => emit_set(&p.arp.gate, as_f32(value)?, emit),
+        "arp_swing" => emit_set(&p.arp.swing, as_f32(value)?, emit),
+        "arp_timing_jitter" => emit_set(&p.arp.timing_jitter, as_f32(value)?, emit),
+        "arp_velocity_jitter" => emit_set(&p.arp.velocity_jitter, as_f32(value)?, emit),
+        "arp_seed" => emit_set(&p.arp.seed, as_i32(value)?, emit),
+        "arp_midi_out" => emit_set(&p.arp.midi_out, as_bool(value)?, emit),
+        "arp_local" => emit_set(&p.arp.local, as_bool(value)?, emit),
+
+        // --- Master tuning ---
+        "master_transpose" => emit_set(&p.master_transpose, as_i32(value)?, emit),
+        "master_tune" => emit_set(&p.master_tune, as_f32(value)?, emit),
+
+        // --- Scale quantizer ---
+        "scale_root" => emit_set(&p.scale_root, parse_scale_root(value)?, emit),
+        "scale" => emit_set(&p.scale, parse_scale(value)?, emit),
 
         _ => return Err(format!("unknown parameter '{name}'")),
     }
     Ok(())
 }
-
-/// Snapshot the live parameter values into the JSON shape the AI sees from the
-/// `get_state` tool (the same shape as a preset file's parameter block).
-pub fn read_state(p: &SineParams) -> Value {
-    serde_json::to_value(crate::ai::preset::PresetData::capture(p))
-        .unwrap_or_else(|_| Value::Null)
-}
@@ -0,0 +1,186 @@
+//! The `adjust_brightness` macro: "make it 20% brighter" as a measured change
+//! in the output's brightness (see `dsp::analysis`) rather than a guessed
+//! cutoff jump.
+//!
+//! The tool closes the loop itself: it measures the output, moves the filter
+//! cutoff, lets the change reach the audio, measures again, and refines its
+//! step using how strongly this patch actually responded, so one request lands
+//! the same way on a dull pad and a buzzy lead. When the cutoff runs out of
+//! range it steps an oscillator's waveform instead (sine → triangle → square →
+//! saw). It needs something sounding to measure.
+
+use std::f32::consts::LN_2;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nih_plug::prelude::Param;
+use serde_json::{json, Value};
+use vizia_plug::vizia::prelude::*;
+use vizia_plug::widgets::RawParamEvent;
+
+use super::bridge;
+use super::host::ToolContext;
+use super::tools::show_toast;
+use crate::ui::toast::ChangeRecorder;
+use crate::{Diagnostics, SineParams, Waveform};
+
+/// Corrections tried before settling for the closest result.
+const MAX_STEPS: usize = 5;
+/// Close enough, as a fraction of the target.
+const TOLERANCE: f32 = 0.03;
+/// Time for a write to reach the audio and the cutoff smoother to finish.
+const SETTLE: Duration = Duration::from_millis(200);
+/// Analysis windows averaged per measurement.
+const WINDOWS_PER_MEASUREMENT: usize = 2;
+const MEASURE_TIMEOUT: Duration = Duration::from_millis(800);
+/// Quieter than this counts as nothing playing.
+const SILENCE_RMS: f32 = 1e-3;
+/// Largest cutoff move per step, so one bad estimate can't slam the filter.
+const MAX_STEP_OCTAVES: f32 = 2.0;
+/// First guess at how much brightness follows cutoff (in log terms); refined
+/// from each step's measurement.
+const INITIAL_SENSITIVITY: f32 = 0.5;
+
+const SILENT: &str = "nothing is sounding, so brightness can't be measured; \
+                      ask the user to hold a note or start playback and try again";
+
+/// The mean brightness (Hz) of the next few analysis windows, or `None` if the
+/// output is silent.
+fn measure(diagnostics: &Diagnostics) -> Option<f32> {
+    let deadline = Instant::now() + MEASURE_TIMEOUT;
+    let mut seen = diagnostics.analysis_windows();
+    let mut total = 0.0;
+    let mut count = 0;
+    while count < WINDOWS_PER_MEASUREMENT {
+        if Instant::now() > deadline {
+            return None;
+        }
+        thread::sleep(Duration::from_millis(10));
+        let windows = diagnostics.analysis_windows();
+        if windows == seen {
+            continue;
+        }
+        seen = windows;
+        let reading = diagnostics.brightness();
+        if reading.rms < SILENCE_RMS {
+            return None;
+        }
+        total += reading.hz;
+        count += 1;
+    }
+    Some(total / count as f32)
+}
+
+/// Waveforms from darkest to brightest, with their `set_parameter` names.
+const WAVEFORM_LADDER: [(Waveform, &str); 4] = [
+    (Waveform::Sine, "sine"),
+    (Waveform::Triangle, "triangle"),
+    (Waveform::Square, "square"),
+    (Waveform::Sawtooth, "sawtooth"),
+];
+
+fn rung(waveform: Waveform) -> usize {
+    WAVEFORM_LADDER
+        .iter()
+        .position(|(w, _)| *w == waveform)
+        .unwrap_or(0)
+}
+
+/// Moves one audible oscillator a rung brighter (the darkest one) or darker
+/// (the brightest one). Returns what changed, or `None` if every audible
+/// oscillator is already at the end of the ladder.
+fn step_waveform(
+    p: &SineParams,
+    brighter: bool,
+    emit: &mut impl FnMut(RawParamEvent),
+) -> Option<String> {
+    let audible = [(1, &p.osc1), (2, &p.osc2), (3, &p.osc3)]
+        .into_iter()
+        .filter(|(_, osc)| !osc.mute.value() && osc.gain.value() > 0.0)
+        .map(|(n, osc)| (n, rung(osc.waveform.value())));
+    let (n, from) = if brighter {
+        audible
+            .filter(|&(_, r)| r + 1 < WAVEFORM_LADDER.len())
+            .min_by_key(|&(_, r)| r)?
+    } else {
+        audible.filter(|&(_, r)| r > 0).max_by_key(|&(_, r)| r)?
+    };
+    let to = if brighter { from + 1 } else { from - 1 };
+    let name = WAVEFORM_LADDER[to].1;
+    let parameter = format!("waveform{n}");
+    bridge::apply_write(p, &parameter, &json!(name), emit).ok()?;
+    Some(format!("{parameter} → {name}"))
+}
+
+/// Change the output's measured brightness by `percent` (e.g. `20` for 20%
+/// brighter, `-20` for 20% darker).
+pub fn adjust(proxy: &mut ContextProxy, ctx: &ToolContext, percent: f32) -> Result<Value, String> {
+    let p: &SineParams = &ctx.params;
+    if ctx.held.any_held(&[p.filter.cutoff.as_ptr()]) {
+        return Err("the user is adjusting filter_cutoff".to_string());
+    }
+    let before = measure(&ctx.diagnostics).ok_or(SILENT)?;
+    let target = before * (1.0 + percent / 100.0);
+    if target <= 0.0 {
+        return Err("brightness can't drop by 100% or more".to_string());
+    }
+
+    let (lo, hi) = (20.0f32, 20_000.0f32);
+    let mut cutoff = p.filter.cutoff.value();
+    let mut measured = before;
+    let mut sensitivity = INITIAL_SENSITIVITY;
+    let mut waveforms = Vec::new();
+    let mut steps = 0;
+    let mut changes = ChangeRecorder::new();
+    {
+        let mut emit = |event| {
+            changes.record(&event);
+            let _ = proxy.emit(event);
+        };
+        while steps < MAX_STEPS && (measured / target - 1.0).abs() > TOLERANCE {
+            steps += 1;
+            let max_step = MAX_STEP_OCTAVES * LN_2;
+            let wanted = ((target / measured).ln() / sensitivity).clamp(-max_step, max_step);
+            let next = (cutoff.ln() + wanted).exp().clamp(lo, hi);
+            let moved = (next / cutoff).ln();
+            if moved.abs() < 0.01 {
+                // The cutoff is pinned at the end of its range.
+                match step_waveform(p, target > measured, &mut emit) {
+                    Some(change) => waveforms.push(change),
+                    None => break,
+                }
+            } else {
+                bridge::apply_write(p, "filter_cutoff", &json!(next), &mut emit)?;
+            }
+
+            thread::sleep(SETTLE);
+            let now = measure(&ctx.diagnostics).ok_or(SILENT)?;
+            if moved.abs() >= 0.01 {
+                // Learn how this patch responds; ignore tiny moves, whose
+                // ratio is mostly measurement noise.
+                if moved.abs() > 0.05 {
+                    sensitivity = ((now / measured).ln() / moved).clamp(0.05, 2.0);
+                }
+                cutoff = next;
+            }
+            measured = now;
+        }
+    }
+
+    show_toast(proxy, p, changes, || {
+        format!(
+            "Assistant changed the brightness by {:+.0}%",
+            (measured / before - 1.0) * 100.0
+        )
+    });
+    Ok(json!({
+        "before_hz": before,
+        "target_hz": target,
+        "after_hz": measured,
+        "change_percent": (measured / before - 1.0) * 100.0,
+        "reached": (measured / target - 1.0).abs() <= TOLERANCE,
+        "filter_cutoff": cutoff,
+        "waveform_changes": waveforms,
+        "steps": steps,
+    }))
+}
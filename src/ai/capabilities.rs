@@ -0,0 +1,91 @@
+//! The `get_capabilities` tool: which optional subsystems this build has (see
+//! the Cargo features) and which the current patch uses, plus the versions of
+//! the tool set and the preset format, so a client can adapt up front instead
+//! of probing with calls that fail.
+
+use serde_json::{json, Value};
+
+use crate::ai::preset::SCHEMA_VERSION;
+use crate::params::NUM_MOD_SLOTS;
+use crate::xy::{self, NUM_XY_PADS};
+use crate::{Scale, SineParams};
+
+/// Bumped when a tool is removed or its arguments or results change
+/// incompatibly; adding a tool doesn't bump it.
+pub const TOOLS_VERSION: u32 = 1;
+
+/// The Cargo features this build was made with.
+fn features() -> Vec<&'static str> {
+    [
+        ("mcp", cfg!(feature = "mcp")),
+        ("chat", cfg!(feature = "chat")),
+        ("gui-extras", cfg!(feature = "gui-extras")),
+        ("fx", cfg!(feature = "fx")),
+    ]
+    .into_iter()
+    .filter_map(|(name, on)| on.then_some(name))
+    .collect()
+}
+
+/// `tools` is every tool's name.
+pub fn capabilities(p: &SineParams, tools: Vec<String>) -> Value {
+    // Drive is the filter's; the bus stages are only there with `fx`.
+    let bus_fx = cfg!(feature = "fx");
+    let stages: &[&str] = if bus_fx {
+        &["drive", "stereo_width", "trance_gate"]
+    } else {
+        &["drive"]
+    };
+    let width = p.stereo_width.value() != 1.0;
+    let fx = p.filter.drive.value() > 1.0 || (bus_fx && (width || p.gate.enabled.value()));
+    let active_slots = p
+        .modulation
+        .slots
+        .iter()
+        .filter(|slot| slot.amount.value() != 0.0)
+        .count();
+    let xy_assigned = xy::assignments(p)
+        .iter()
+        .any(|pad| pad.x.is_some() || pad.y.is_some());
+    let morphing = [&p.osc1, &p.osc2, &p.osc3]
+        .iter()
+        .any(|osc| osc.morph.value());
+    json!({
+        "plugin_version": env!("CARGO_PKG_VERSION"),
+        "tools_version": TOOLS_VERSION,
+        "preset_schema_version": SCHEMA_VERSION,
+        "features": features(),
+        // `available`: built in; `enabled`: in use by the current patch.
+        "subsystems": {
+            "fx": {
+                "available": true,
+                "enabled": fx,
+                "stages": stages
+            },
+            "mod_matrix": {
+                "available": true,
+                "enabled": active_slots > 0,
+                "slots": NUM_MOD_SLOTS,
+                "active_slots": active_slots
+            },
+            "arp": {
+                "available": true,
+                "enabled": p.arp.enabled.value(),
+                "midi_out": p.arp.midi_out.value()
+            },
+            "scale_quantizer": {
+                "available": true,
+                "enabled": p.scale.value() != Scale::Chromatic
+            },
+            "additive": { "available": true, "enabled": p.additive.enabled.value() },
+            "comb": { "available": true, "enabled": p.comb.enabled.value() },
+            "noise": { "available": true, "enabled": p.noise.level.value() > 0.0 },
+            "sub": { "available": true, "enabled": p.sub.level.value() > 0.0 },
+            "morph": { "available": true, "enabled": morphing },
+            "sidechain": { "available": true },
+            "xy_pads": { "available": true, "enabled": xy_assigned, "pads": NUM_XY_PADS },
+            "wavetables": { "available": false, "enabled": false }
+        },
+        "tools": tools
+    })
+}
@@ -2,12 +2,27 @@
 //! tool-calling loop in [`super::llm`]. Parameter writes reach the real
 //! `nih_plug` params through `RawParamEvent`s emitted from the background task.
 
-use crate::SineParams;
+use nih_plug::prelude::{Param, ParamPtr};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use vizia_plug::vizia::prelude::*;
+use vizia_plug::vizia::vg;
+use vizia_plug::widgets::RawParamEvent;
 
-use super::llm::{AiConfig, AiModel};
+use super::bridge::emit_set;
+use super::commands::{self, Command, ScriptAction};
+use super::config::AiConfig;
+use super::conflicts::ConflictPolicy;
+use super::host::{ToolContext, ToolLog};
+use super::plot::{self, Plot, PlotKind};
+use super::script::{self, ScriptRun};
+use super::session::AiModel;
+use super::{audition, bank_io, preset, transcript};
+use crate::dsp::Quantizer;
+use crate::isolate;
+use crate::persist::{ChatLine, ProjectState};
+use crate::ui::toast::ChangeRecorder;
 
 // NOTE: this `vizia_style` revision silently drops legacy spacing names like
 // `row-between`/`col-between`/`border-radius` (see editor.rs). Use `gap` for
@@ -63,7 +78,48 @@ pub const CHAT_STYLES: &str = r#"
         font-size: 10px;
         height: 12px;
     }
-    .chat-inputrow { height: 28px; gap: 6px; }
+    .chat-code-block {
+        height: auto;
+        background-color: #0E0E12;
+        border: 1px solid #2D2D34;
+        corner-radius: 4px;
+        padding: 6px;
+        gap: 4px;
+    }
+    .chat-code-bar { height: 16px; }
+    .chat-copy {
+        height: 16px;
+        background-color: #1C1C22;
+        border: 1px solid #2D2D34;
+        corner-radius: 3px;
+        color: #94A3B8;
+        font-size: 9px;
+        padding-left: 6px;
+        padding-right: 6px;
+        alignment: center;
+    }
+    .chat-copy:hover { border-color: #6366F1; }
+    .chat-code {
+        color: #CBD5E1;
+        font-family: monospace;
+        font-size: 10px;
+        line-height: 1.3;
+    }
+    .chat-plot {
+        width: 1s;
+        height: 96px;
+        background-color: #0E0E12;
+        border: 1px solid #2D2D34;
+        corner-radius: 4px;
+    }
+    .chat-inputrow { height: auto; gap: 6px; alignment: bottom-left; }
+    .chat-compose {
+        height: auto;
+        min-height: 28px;
+        max-height: 96px;
+        padding-top: 6px;
+        padding-bottom: 6px;
+    }
     .chat-input {
         background-color: #0F1115;
         border: 1px solid #2D2D34;
@@ -124,6 +180,19 @@ pub const CHAT_STYLES: &str = r#"
     .model-btn.selected { background-color: #6366F1; color: #F8FAFC; }
 "#;
 
+/// Most slash-command changes `/undo` can take back.
+const MAX_UNDO: usize = 32;
+/// Most chat messages kept with the project; older ones are dropped first.
+const MAX_CHAT_MESSAGES: usize = 200;
+/// Height of a line in a code block, and the tallest a block gets before it
+/// scrolls, in pixels.
+const CODE_LINE_HEIGHT: f32 = 13.0;
+const CODE_MAX_HEIGHT: f32 = 240.0;
+/// Level at the bottom of a spectrum plot, in dB; the top is 0 dB.
+const PLOT_BOTTOM_DB: f32 = -90.0;
+/// How often a running script checks for due commands.
+const SCRIPT_TICK: Duration = Duration::from_millis(20);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Data)]
 pub enum Role {
     User,
@@ -145,12 +214,57 @@ pub struct ChatMessage {
     pub text: String,
 }
 
+/// A run of a message: prose, code shown monospaced and unwrapped, or a plot
+/// the assistant attached.
+#[derive(Debug, PartialEq)]
+enum Block {
+    Text(String),
+    Code(String),
+    Plot(Plot),
+}
+
+/// Split a message into prose and code: its ``` fenced blocks, or all of it
+/// when it is a JSON object or array (a state dump), indented. A block fenced
+/// as `plot` is drawn (see [`super::plot`]).
+fn blocks(text: &str) -> Vec<Block> {
+    let trimmed = text.trim();
+    if trimmed.starts_with(['{', '[']) {
+        if let Ok(value) = serde_json::from_str::<serde_json::Value>(trimmed) {
+            let pretty =
+                serde_json::to_string_pretty(&value).unwrap_or_else(|_| trimmed.to_string());
+            return vec![Block::Code(pretty)];
+        }
+    }
+    text.split("```")
+        .enumerate()
+        .filter_map(|(i, part)| {
+            if i % 2 == 1 {
+                // The opening fence's line may name the language.
+                let (lang, code) = match part.split_once('\n') {
+                    Some((lang, code)) if lang.trim().chars().all(char::is_alphanumeric) => {
+                        (lang.trim(), code)
+                    }
+                    _ => ("", part),
+                };
+                match plot::parse(code).filter(|_| lang == plot::FENCE) {
+                    Some(plot) => Some(Block::Plot(plot)),
+                    None => Some(Block::Code(code.trim_end().to_string())),
+                }
+            } else {
+                let prose = part.trim();
+                (!prose.is_empty()).then(|| Block::Text(prose.to_string()))
+            }
+        })
+        .collect()
+}
+
 /// The opening assistant message, shown on launch and after "Clear".
 fn greeting() -> ChatMessage {
     ChatMessage {
         role: Role::Assistant,
         text: "Describe a sound and I'll dial it in — e.g. \"warm detuned pad\" — \
-               or ask me to save/load a preset. Set your API key in ⚙ first."
+               or ask me to save/load a preset. Set your API key in ⚙ first. \
+               Type /help for commands that work without one."
             .to_string(),
     }
 }
@@ -165,9 +279,21 @@ pub enum ChatEvent {
     Stop,
     /// Reset the transcript to the opening message.
     Clear,
+    /// Save the transcript, as `/export` with no name does.
+    Export,
+    /// Timer tick: run the script's commands that have come due.
+    ScriptTick,
     ToggleSettings,
     SetApiKey(String),
     SetModel(AiModel),
+    SetConflictPolicy(ConflictPolicy),
+    SetReadOnly(bool),
+    /// Step the master transpose by this many semitones.
+    NudgeTranspose(i32),
+    /// Step the master tune (A4 reference) by this many Hz.
+    NudgeTune(f32),
+    /// The background task panicked with this message.
+    Crashed(String),
 }
 
 #[derive(Lens)]
@@ -177,10 +303,33 @@ pub struct ChatState {
     sending: bool,
     status: String,
     is_settings_open: bool,
+    /// Engine health line shown in the settings panel; refreshed on open.
+    engine_status: String,
+    /// Background task health line for the settings panel.
+    task_status: String,
+    /// Background task panics caught this session.
+    crashes: u32,
+    /// Master tuning readout for the settings panel.
+    tuning: String,
+    /// Where the assistant settings came from, if this project saved them.
+    session_note: String,
     api_key: String,
     model: AiModel,
     temperature: f32,
-    params: Arc<SineParams>,
+    /// Mirrored into `tools.held`, which is what the tools consult.
+    conflict_policy: ConflictPolicy,
+    /// Restrict the assistant to tools that only observe the synth.
+    read_only: bool,
+    /// The live params and diagnostics the tools act on.
+    tools: ToolContext,
+    /// What `/undo` puts back, newest last: per command, each param it
+    /// changed and its normalized value before.
+    undo: Vec<Vec<(ParamPtr, f32)>>,
+    /// The `.toscript` being played, if any.
+    script: Option<ScriptRun>,
+    /// How many messages there were when the chat was last saved with the
+    /// project; `None` once they've been replaced since.
+    saved_len: Option<usize>,
     /// Shared async runtime, built once when the panel opens. Each send drives a
     /// request on it via `block_on` from a `cx.spawn` thread, instead of standing
     /// up a fresh runtime (and thread pool) per message.
@@ -191,22 +340,266 @@ pub struct ChatState {
 }
 
 impl ChatState {
-    fn persist(&self) {
-        let cfg = AiConfig {
+    fn refresh_engine_status(&mut self) {
+        self.engine_status = format!(
+            "Filter resets (NaN/Inf): {}",
+            self.tools.diagnostics.filter_resets()
+        );
+    }
+
+    fn show_tuning(&mut self, transpose: i32, tune: f32) {
+        self.tuning = format!("Transpose {transpose:+} st · A4 = {tune:.1} Hz");
+    }
+
+    /// The transcript as the assistant and an export see it.
+    fn conversation(&self) -> Vec<(Role, String)> {
+        self.messages
+            .iter()
+            .map(|m| (m.role, m.text.clone()))
+            .collect()
+    }
+
+    fn config(&self) -> AiConfig {
+        AiConfig {
             api_key: self.api_key.clone(),
             model: self.model,
             temperature: self.temperature,
+            conflict_policy: self.conflict_policy,
+            read_only: self.read_only,
+        }
+    }
+
+    /// Run a slash-command. Returns its reply, if it has one now; `/play`
+    /// replies once its notes have started.
+    fn run_command(
+        &mut self,
+        cx: &mut EventContext,
+        command: Command,
+    ) -> Result<Option<String>, String> {
+        let p = self.tools.params.clone();
+        let reply = match command {
+            Command::Set {
+                ref parameter,
+                ref value,
+            } => {
+                let events = commands::change_events(&p, &command)?;
+                self.record_undo(cx, events);
+                format!("🎛 {parameter} → {value}")
+            }
+            Command::Get { parameter } => commands::get(&p, parameter.as_deref())?,
+            Command::PresetList => match preset::list() {
+                names if names.is_empty() => "No saved presets yet.".to_string(),
+                names => format!("Presets: {}", names.join(", ")),
+            },
+            Command::PresetLoad(ref name) => {
+                let events = commands::change_events(&p, &command)?;
+                self.record_undo(cx, events);
+                if let Ok(data) = preset::load(name) {
+                    data.metadata.store(&p);
+                }
+                format!("📂 loaded preset '{name}'")
+            }
+            Command::PresetSave(name) => {
+                preset::save(&p, &name)?;
+                format!("💾 saved preset '{name}'")
+            }
+            Command::BankExport { name, presets } => {
+                let (path, count) = bank_io::export(&name, &presets)?;
+                format!("📦 exported {count} presets to {}", path.display())
+            }
+            Command::BankImport { name, conflict } => {
+                let report = bank_io::import(&name, conflict)?;
+                format!("📦 '{name}': {}", report.summary())
+            }
+            Command::Play { notes, seconds } => {
+                let diagnostics = self.tools.diagnostics.clone();
+                let scale = Quantizer::from_params(&p);
+                cx.spawn(move |proxy| {
+                    let play = || audition::play(&diagnostics, scale, &notes, 0.8, seconds);
+                    let log = match isolate::guard(play) {
+                        Ok(Ok(result)) => format!("🎹 played {}", result["played"]),
+                        Ok(Err(e)) => format!("⚠ {e}"),
+                        Err(e) => {
+                            tracing::error!(target: "mcp", "/play panicked: {e}");
+                            format!("⚠ {e}")
+                        }
+                    };
+                    let _ = proxy.emit(ChatEvent::ToolLog(log));
+                });
+                return Ok(None);
+            }
+            Command::Panic => {
+                self.tools.diagnostics.request_panic();
+                "🛑 released every note".to_string()
+            }
+            Command::Undo => {
+                let batch = self.undo.pop().ok_or("nothing to undo")?;
+                for &(ptr, before) in &batch {
+                    cx.emit(RawParamEvent::BeginSetParameter(ptr));
+                    cx.emit(RawParamEvent::SetParameterNormalized(ptr, before));
+                    cx.emit(RawParamEvent::EndSetParameter(ptr));
+                }
+                format!("↩ undid {} change(s)", batch.len())
+            }
+            Command::Export(name) => {
+                let name = name.unwrap_or_else(|| {
+                    let now = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap_or_default();
+                    format!("conversation-{}", now.as_secs())
+                });
+                let path = transcript::export(&self.conversation(), &name)?;
+                format!("📤 exported to {} (and .md)", path.display())
+            }
+            Command::Import(name) => {
+                let imported = transcript::import(&name)?;
+                self.cancel.store(true, Ordering::Relaxed);
+                self.sending = false;
+                self.status.clear();
+                let count = imported.len();
+                self.messages = imported
+                    .into_iter()
+                    .map(|(role, text)| ChatMessage { role, text })
+                    .collect();
+                self.saved_len = None;
+                format!("📥 imported '{name}' ({count} messages); /replay {name} redoes them")
+            }
+            Command::Replay { name, dry_run } => {
+                let lines = transcript::replay_commands(&transcript::import(&name)?);
+                let mut events = Vec::new();
+                let mut report = Vec::new();
+                for line in &lines {
+                    let command = commands::parse(line).unwrap_or(Err("not a command".into()));
+                    match command.and_then(|c| commands::change_events(&p, &c)) {
+                        Ok(more) => events.extend(more),
+                        Err(e) => report.push(format!("⚠ {line}: {e}")),
+                    }
+                }
+                let changes = commands::describe_changes(&p, &events);
+                let verb = if dry_run { "would change" } else { "changed" };
+                let mut reply = match changes.len() {
+                    0 => format!("'{name}' ({} commands) {verb} nothing", lines.len()),
+                    n => format!("'{name}' ({} commands) {verb} {n} params:", lines.len()),
+                };
+                for line in changes.iter().chain(&report) {
+                    reply.push_str(&format!("\n{line}"));
+                }
+                if !dry_run {
+                    self.record_undo(cx, events);
+                }
+                reply
+            }
+            Command::Script(ScriptAction::List) => match script::list() {
+                names if names.is_empty() => "No saved scripts yet.".to_string(),
+                names => format!("Scripts: {}", names.join(", ")),
+            },
+            Command::Script(ScriptAction::Run { name, synced }) => {
+                let loaded = script::load(&name)?;
+                let cues = loaded.len();
+                self.script = Some(ScriptRun::new(loaded, synced));
+                let when = if synced {
+                    "with the host's playback"
+                } else {
+                    "from now"
+                };
+                format!("▶ running script '{name}' ({cues} commands) {when}")
+            }
+            Command::Script(ScriptAction::Stop) => {
+                let run = self.script.take().ok_or("no script is running")?;
+                format!("⏹ stopped script '{}'", run.name())
+            }
+            Command::Help => commands::HELP.to_string(),
         };
+        Ok(Some(reply))
+    }
+
+    /// Emit `events`, keeping what it takes to `/undo` them as one step.
+    fn record_undo(&mut self, cx: &mut EventContext, events: Vec<RawParamEvent>) {
+        let mut changes = ChangeRecorder::new();
+        for event in events {
+            changes.record(&event);
+            cx.emit(event);
+        }
+        let undo = changes.into_undo();
+        if !undo.is_empty() {
+            if self.undo.len() == MAX_UNDO {
+                self.undo.remove(0);
+            }
+            self.undo.push(undo);
+        }
+    }
+
+    /// Keep the newest [`MAX_CHAT_MESSAGES`] of the chat with the project.
+    fn save_chat(&mut self) {
+        let skip = self.messages.len().saturating_sub(MAX_CHAT_MESSAGES);
+        let chat = self.messages[skip..]
+            .iter()
+            .map(|m| ChatLine {
+                role: transcript::role_id(m.role).to_string(),
+                text: m.text.clone(),
+            })
+            .collect();
+        ProjectState::update(&self.tools.params, |state| state.chat = chat);
+        self.saved_len = Some(self.messages.len());
+    }
+
+    /// Save the settings to the user's config and to this project's session.
+    fn persist(&self) {
+        let cfg = self.config();
         let _ = cfg.save();
+        ProjectState::update(&self.tools.params, |state| state.ai_session.remember(&cfg));
     }
 }
 
 impl Model for ChatState {
     fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        // A panic here would unwind into the host's GUI loop. Caught, the
+        // panel is freed up and says so.
+        if let Err(message) = isolate::guard(|| self.handle_event(cx, event)) {
+            tracing::error!(target: "chat", "chat event panicked: {message}");
+            self.sending = false;
+            self.status.clear();
+            self.messages.push(ChatMessage {
+                role: Role::Tool,
+                text: format!("⚠ That went wrong inside the plugin ({message}); see the log."),
+            });
+        }
+        if self.saved_len != Some(self.messages.len()) {
+            self.save_chat();
+        }
+    }
+}
+
+impl ChatState {
+    fn handle_event(&mut self, cx: &mut EventContext, event: &mut Event) {
         event.map(|ev: &ChatEvent, _meta| match ev {
             ChatEvent::EditInput(s) => self.input = s.clone(),
 
-            ChatEvent::ToggleSettings => self.is_settings_open = !self.is_settings_open,
+            ChatEvent::ToggleSettings => {
+                self.is_settings_open = !self.is_settings_open;
+                if self.is_settings_open {
+                    self.refresh_engine_status();
+                    let p = &self.tools.params;
+                    let (transpose, tune) = (p.master_transpose.value(), p.master_tune.value());
+                    self.show_tuning(transpose, tune);
+                }
+            }
+
+            // Written like any other param edit so the host records it. The
+            // readout shows the target value: the write lands asynchronously.
+            ChatEvent::NudgeTranspose(step) => {
+                let p = self.tools.params.clone();
+                let transpose = (p.master_transpose.value() + step).clamp(-24, 24);
+                emit_set(&p.master_transpose, transpose, &mut |e| cx.emit(e));
+                self.show_tuning(transpose, p.master_tune.value());
+            }
+
+            ChatEvent::NudgeTune(step) => {
+                let p = self.tools.params.clone();
+                let tune = (p.master_tune.value() + step).clamp(415.0, 465.0);
+                emit_set(&p.master_tune, tune, &mut |e| cx.emit(e));
+                self.show_tuning(p.master_transpose.value(), tune);
+            }
 
             ChatEvent::SetApiKey(k) => {
                 // Trim pasted whitespace/newlines so the saved key is usable as-is.
@@ -219,6 +612,17 @@ impl Model for ChatState {
                 self.persist();
             }
 
+            ChatEvent::SetReadOnly(read_only) => {
+                self.read_only = *read_only;
+                self.persist();
+            }
+
+            ChatEvent::SetConflictPolicy(policy) => {
+                self.conflict_policy = *policy;
+                self.tools.deferred.set_policy(*policy);
+                self.persist();
+            }
+
             ChatEvent::Status(s) => self.status = s.clone(),
 
             ChatEvent::Stop => {
@@ -239,6 +643,41 @@ impl Model for ChatState {
                 self.sending = false;
                 self.status.clear();
                 self.messages = vec![greeting()];
+                self.saved_len = None;
+            }
+
+            ChatEvent::Export => {
+                let text = match self.run_command(cx, Command::Export(None)) {
+                    Ok(reply) => reply.unwrap_or_default(),
+                    Err(e) => format!("⚠ {e}"),
+                };
+                self.messages.push(ChatMessage {
+                    role: Role::Tool,
+                    text,
+                });
+            }
+
+            ChatEvent::ScriptTick => {
+                let Some(run) = &mut self.script else {
+                    return;
+                };
+                let due = run.take_due(&self.tools.diagnostics);
+                let name = run.name().to_string();
+                if run.is_finished() {
+                    self.script = None;
+                }
+                for cue in due {
+                    let text = match self.run_command(cx, cue.command) {
+                        Ok(reply) => reply,
+                        Err(e) => Some(format!("⚠ {name} line {} ({}): {e}", cue.line, cue.text)),
+                    };
+                    if let Some(text) = text {
+                        self.messages.push(ChatMessage {
+                            role: Role::Tool,
+                            text,
+                        });
+                    }
+                }
             }
 
             ChatEvent::Receive(text) => {
@@ -250,6 +689,21 @@ impl Model for ChatState {
                 });
             }
 
+            ChatEvent::Crashed(message) => {
+                // Free the UI and start over on a fresh runtime, in case the
+                // panic left the old one in a bad state.
+                self.sending = false;
+                self.status.clear();
+                self.crashes += 1;
+                self.task_status =
+                    format!("Restarted after {} crash(es); last: {message}", self.crashes);
+                self.runtime = tokio::runtime::Runtime::new().ok().map(Arc::new);
+                self.messages.push(ChatMessage {
+                    role: Role::Tool,
+                    text: format!("⚠ The assistant crashed ({message}) and was restarted."),
+                });
+            }
+
             ChatEvent::ToolLog(text) => {
                 self.messages.push(ChatMessage {
                     role: Role::Tool,
@@ -259,7 +713,32 @@ impl Model for ChatState {
 
             ChatEvent::Send => {
                 let text = self.input.trim().to_string();
-                if text.is_empty() || self.sending {
+                if text.is_empty() {
+                    return;
+                }
+                // Slash-commands don't need the assistant, so they run even
+                // while it's busy; several lines run as several commands.
+                if let Some(parsed) = commands::parse_message(&text) {
+                    self.input.clear();
+                    self.messages.push(ChatMessage {
+                        role: Role::User,
+                        text: text.clone(),
+                    });
+                    for command in parsed {
+                        let reply = match command.and_then(|c| self.run_command(cx, c)) {
+                            Ok(reply) => reply,
+                            Err(e) => Some(format!("⚠ {e}")),
+                        };
+                        if let Some(text) = reply {
+                            self.messages.push(ChatMessage {
+                                role: Role::Tool,
+                                text,
+                            });
+                        }
+                    }
+                    return;
+                }
+                if self.sending {
                     return;
                 }
                 self.input.clear();
@@ -290,48 +769,109 @@ impl Model for ChatState {
                 // Fresh run: clear any stale Stop from a previous request.
                 self.cancel.store(false, Ordering::Relaxed);
 
-                let params = self.params.clone();
-                let cfg = AiConfig {
-                    api_key: self.api_key.clone(),
-                    model: self.model,
-                    temperature: self.temperature,
-                };
-                let convo: Vec<(Role, String)> =
-                    self.messages.iter().map(|m| (m.role, m.text.clone())).collect();
+                let tools = self.tools.clone();
+                let cfg = self.config();
+                let client = cfg.model.api_name().to_string();
+                ProjectState::update(&self.tools.params, |state| {
+                    state.ai_session.last_client = Some(client)
+                });
+                let convo = self.conversation();
                 let cancel = self.cancel.clone();
 
                 cx.spawn(move |proxy| {
-                    rt.block_on(super::llm::run_conversation(
-                        proxy, &params, &cfg, convo, cancel,
-                    ));
+                    // Supervise the task: a panic in the loop or a tool must
+                    // not leave the panel stuck on "Thinking…".
+                    let run = || {
+                        rt.block_on(super::llm::run_conversation(
+                            proxy, &tools, &cfg, convo, cancel,
+                        ))
+                    };
+                    if let Err(message) = isolate::guard(run) {
+                        tracing::error!(target: "chat", "assistant task panicked: {message}");
+                        let _ = proxy.emit(ChatEvent::Crashed(message));
+                    }
                 });
             }
         });
+        // What the tool calls did.
+        event.map(|ToolLog(text), _| {
+            self.messages.push(ChatMessage {
+                role: Role::Tool,
+                text: text.clone(),
+            });
+        });
     }
 }
 
-/// Build the AI chat panel. `params` is the live parameter set the tools drive.
-pub fn chat_panel(cx: &mut Context, params: Arc<SineParams>) {
-    let cfg = AiConfig::load();
+/// Build the AI chat panel. `tools` carries the live parameter set the tools
+/// drive and the diagnostics they report on.
+pub fn chat_panel(cx: &mut Context, tools: ToolContext) {
+    // The project's saved session wins over the user's defaults.
+    let mut cfg = AiConfig::load();
+    let mut session_note = String::new();
+    let state = ProjectState::current(&tools.params);
+    state.ai_session.restore_into(&mut cfg);
+    if let Some(client) = &state.ai_session.last_client {
+        session_note = format!("Restored from this project · last used with {client}");
+    }
+    tools.deferred.set_policy(cfg.conflict_policy);
+
+    // The conversation the project was saved with, if any.
+    let mut messages: Vec<ChatMessage> = state
+        .chat
+        .into_iter()
+        .filter_map(|line| {
+            let role = transcript::id_to_role(&line.role)?;
+            Some(ChatMessage {
+                role,
+                text: line.text,
+            })
+        })
+        .collect();
+    if messages.is_empty() {
+        messages.push(greeting());
+    }
+    let saved_len = Some(messages.len());
 
     ChatState {
-        messages: vec![greeting()],
+        messages,
         input: String::new(),
         sending: false,
         status: String::new(),
         is_settings_open: false,
+        engine_status: String::new(),
+        task_status: "No crashes this session".to_string(),
+        crashes: 0,
+        tuning: String::new(),
+        session_note,
         api_key: cfg.api_key,
         model: cfg.model,
         temperature: cfg.temperature,
-        params,
+        conflict_policy: cfg.conflict_policy,
+        read_only: cfg.read_only,
+        tools,
+        undo: Vec::new(),
+        script: None,
+        saved_len,
         runtime: tokio::runtime::Runtime::new().ok().map(Arc::new),
         cancel: Arc::new(AtomicBool::new(false)),
     }
     .build(cx);
 
+    let script_tick = cx.add_timer(SCRIPT_TICK, None, |cx, action| {
+        if let TimerAction::Tick(_) = action {
+            cx.emit(ChatEvent::ScriptTick);
+        }
+    });
+    cx.start_timer(script_tick);
+
     VStack::new(cx, |cx| {
         HStack::new(cx, |cx| {
             Label::new(cx, "AI SYNTH AGENT").class("chat-title");
+            Button::new(cx, |cx| Label::new(cx, "Export"))
+                .on_press(|cx| cx.emit(ChatEvent::Export))
+                .class("chat-iconbtn")
+                .width(Pixels(52.0));
             Button::new(cx, |cx| Label::new(cx, "Clear"))
                 .on_press(|cx| cx.emit(ChatEvent::Clear))
                 .class("chat-iconbtn")
@@ -344,13 +884,10 @@ pub fn chat_panel(cx: &mut Context, params: Arc<SineParams>) {
 
         let transcript = ScrollView::new(cx, |cx| {
             List::new(cx, ChatState::messages, |cx, _, item| {
-                VStack::new(cx, |cx| {
-                    Label::new(cx, item.map(|m| role_label(m.role).to_string())).class("chat-role");
-                    Label::new(cx, item.map(|m| m.text.clone()))
-                        .class("chat-text")
-                        .width(Stretch(1.0));
-                })
-                .class("chat-msg");
+                Binding::new(cx, item, |cx, item| {
+                    let message = item.get(cx);
+                    VStack::new(cx, |cx| message_view(cx, &message)).class("chat-msg");
+                });
             });
         })
         .class("chat-transcript")
@@ -368,11 +905,16 @@ pub fn chat_panel(cx: &mut Context, params: Arc<SineParams>) {
         Label::new(cx, ChatState::status).class("chat-status");
 
         HStack::new(cx, |cx| {
-            Textbox::new(cx, ChatState::input)
-                .class("chat-input")
+            ComposeBox
+                .build(cx, |cx| {
+                    Textbox::new_multiline(cx, ChatState::input, true)
+                        .class("chat-input")
+                        .class("chat-compose")
+                        .width(Stretch(1.0))
+                        .on_edit(|cx, text| cx.emit(ChatEvent::EditInput(text)));
+                })
                 .width(Stretch(1.0))
-                .on_edit(|cx, text| cx.emit(ChatEvent::EditInput(text)))
-                .on_submit(|cx, _, _| cx.emit(ChatEvent::Send));
+                .height(Auto);
             // While a request is in flight the button becomes a Stop control.
             Binding::new(cx, ChatState::sending, |cx, sending| {
                 if sending.get(cx) {
@@ -397,6 +939,134 @@ pub fn chat_panel(cx: &mut Context, params: Arc<SineParams>) {
     .class("chat-root");
 }
 
+/// One transcript entry: who it's from, then its prose and code blocks.
+fn message_view(cx: &mut Context, message: &ChatMessage) {
+    Label::new(cx, role_label(message.role)).class("chat-role");
+    for block in blocks(&message.text) {
+        match block {
+            Block::Text(text) => {
+                Label::new(cx, text).class("chat-text").width(Stretch(1.0));
+            }
+            Block::Code(code) => code_view(cx, code),
+            Block::Plot(plot) => {
+                PlotView { plot }.build(cx, |_| {}).class("chat-plot");
+            }
+        }
+    }
+}
+
+/// Code in a monospaced box that scrolls sideways rather than wrapping, with
+/// a button that copies it.
+fn code_view(cx: &mut Context, code: String) {
+    let lines = code.lines().count().max(1) as f32;
+    let height = (lines * CODE_LINE_HEIGHT).min(CODE_MAX_HEIGHT);
+    VStack::new(cx, move |cx| {
+        HStack::new(cx, |cx| {
+            Element::new(cx).width(Stretch(1.0)).height(Pixels(0.0));
+            let copy = code.clone();
+            Button::new(cx, |cx| Label::new(cx, "Copy"))
+                .on_press(move |cx| {
+                    let status = match cx.set_clipboard(copy.clone()) {
+                        Ok(()) => "Copied to the clipboard",
+                        Err(_) => "Couldn't reach the clipboard",
+                    };
+                    cx.emit(ChatEvent::Status(status.to_string()));
+                })
+                .class("chat-copy");
+        })
+        .class("chat-code-bar");
+        ScrollView::new(cx, move |cx| {
+            Label::new(cx, code)
+                .class("chat-code")
+                .text_wrap(false)
+                .width(Auto);
+        })
+        .height(Pixels(height));
+    })
+    .class("chat-code-block");
+}
+
+/// A plot the assistant attached: its curve across the full width, with a
+/// centre line for the LFO.
+struct PlotView {
+    plot: Plot,
+}
+
+impl View for PlotView {
+    fn element(&self) -> Option<&'static str> {
+        Some("chat-plot")
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &Canvas) {
+        let bounds = cx.bounds();
+        let values = &self.plot.values;
+        if bounds.w <= 0.0 || bounds.h <= 0.0 || values.len() < 2 {
+            return;
+        }
+        let pad = 4.0;
+        let (left, top) = (bounds.x + pad, bounds.y + pad);
+        let (width, height) = (bounds.w - pad * 2.0, bounds.h - pad * 2.0);
+        let kind = self.plot.kind;
+        // A value's height, as a share of the plot's.
+        let level = |value: f32| match kind {
+            PlotKind::Spectrum => 1.0 - value / PLOT_BOTTOM_DB,
+            _ if kind.bipolar() => (value + 1.0) * 0.5,
+            _ => value,
+        };
+
+        if kind.bipolar() {
+            let mut axis = vg::Paint::default();
+            axis.set_style(vg::PaintStyle::Stroke);
+            axis.set_stroke_width(1.0);
+            axis.set_color(vg::Color::from_argb(255, 45, 45, 52));
+            axis.set_alpha_f(cx.opacity());
+            let mid = top + height * 0.5;
+            canvas.draw_line((left, mid), (left + width, mid), &axis);
+        }
+
+        let last = (values.len() - 1) as f32;
+        let mut path = vg::Path::new();
+        for (index, &value) in values.iter().enumerate() {
+            let x = left + width * index as f32 / last;
+            let y = top + height * (1.0 - level(value).clamp(0.0, 1.0));
+            if index == 0 {
+                path.move_to((x, y));
+            } else {
+                path.line_to((x, y));
+            }
+        }
+        let mut paint = vg::Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_style(vg::PaintStyle::Stroke);
+        paint.set_stroke_width(1.5);
+        paint.set_stroke_join(vg::PaintJoin::Round);
+        paint.set_color(vg::Color::from_argb(255, 129, 140, 248)); // indigo
+        paint.set_alpha_f(cx.opacity());
+        canvas.draw_path(&path, &paint);
+    }
+}
+
+/// Holds the input box, which takes Shift+Enter as a new line: plain Enter
+/// sends instead.
+struct ComposeBox;
+
+impl View for ComposeBox {
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|window_event, meta| {
+            if let WindowEvent::KeyDown(Code::Enter | Code::NumpadEnter, _) = window_event {
+                if !cx.modifiers().shift() {
+                    // The box has already put the newline in (the edit is
+                    // queued); send the text from before it.
+                    let text = ChatState::input.get(cx);
+                    cx.emit(ChatEvent::EditInput(text));
+                    cx.emit(ChatEvent::Send);
+                    meta.consume();
+                }
+            }
+        });
+    }
+}
+
 fn settings_overlay(cx: &mut Context) {
     VStack::new(cx, |cx| {
         Label::new(cx, "AI SETTINGS").class("settings-label");
@@ -422,6 +1092,53 @@ fn settings_overlay(cx: &mut Context) {
         })
         .class("settings-models");
 
+        Label::new(cx, ChatState::session_note).class("settings-sublabel");
+
+        Label::new(cx, "Assistant Access").class("settings-sublabel");
+        HStack::new(cx, |cx| {
+            for (label, read_only) in [("Full", false), ("Read-only", true)] {
+                Button::new(cx, move |cx| Label::new(cx, label))
+                    .on_press(move |cx| cx.emit(ChatEvent::SetReadOnly(read_only)))
+                    .class("model-btn")
+                    .toggle_class("selected", ChatState::read_only.map(move |r| *r == read_only));
+            }
+        })
+        .class("settings-models");
+
+        Label::new(cx, "When I'm Dragging a Knob the AI Writes").class("settings-sublabel");
+        HStack::new(cx, |cx| {
+            for policy in [ConflictPolicy::Defer, ConflictPolicy::Reject] {
+                Button::new(cx, move |cx| Label::new(cx, policy.label()))
+                    .on_press(move |cx| cx.emit(ChatEvent::SetConflictPolicy(policy)))
+                    .class("model-btn")
+                    .toggle_class(
+                        "selected",
+                        ChatState::conflict_policy.map(move |sel| *sel == policy),
+                    );
+            }
+        })
+        .class("settings-models");
+
+        Label::new(cx, "Master Tuning").class("settings-sublabel");
+        HStack::new(cx, |cx| {
+            for (label, step) in [("−1 st", -1), ("+1 st", 1)] {
+                Button::new(cx, move |cx| Label::new(cx, label))
+                    .on_press(move |cx| cx.emit(ChatEvent::NudgeTranspose(step)))
+                    .class("model-btn");
+            }
+            for (label, step) in [("−1 Hz", -1.0), ("+1 Hz", 1.0)] {
+                Button::new(cx, move |cx| Label::new(cx, label))
+                    .on_press(move |cx| cx.emit(ChatEvent::NudgeTune(step)))
+                    .class("model-btn");
+            }
+        })
+        .class("settings-models");
+        Label::new(cx, ChatState::tuning).class("settings-sublabel");
+
+        Label::new(cx, "Engine").class("settings-sublabel");
+        Label::new(cx, ChatState::engine_status).class("settings-sublabel");
+        Label::new(cx, ChatState::task_status).class("settings-sublabel");
+
         Button::new(cx, |cx| Label::new(cx, "Done"))
             .on_press(|cx| cx.emit(ChatEvent::ToggleSettings))
             .class("chat-send");
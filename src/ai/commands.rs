@@ -0,0 +1,526 @@
+//! Chat slash-commands: `/set`, `/get`, `/preset`, `/bank` (see
+//! [`super::bank_io`]), `/play`, `/panic`, `/undo`, the
+//! transcript's `/export`, `/import` and `/replay` (see [`super::transcript`]),
+//! `/script` (see [`super::script`]) and `/help`, for when typing the change
+//! is quicker than describing it. They run right away, without the assistant
+//! (or an API key), and a bad argument is reported inline before anything is
+//! written. Anything not starting with `/` still goes to the assistant.
+//!
+//! Parameter names are the `set_parameter` vocabulary (see [`bridge`]).
+
+use std::fmt;
+
+use nih_plug::prelude::ParamPtr;
+use serde_json::Value;
+use vizia_plug::widgets::RawParamEvent;
+
+use crate::ai::bank_io::BankConflict;
+use crate::ai::{audition, bridge, preset, tool_bridge};
+use crate::ui::toast::param_label;
+use crate::SineParams;
+
+/// The `/help` reply.
+pub const HELP: &str = "/set <parameter> <value> — e.g. /set filter_cutoff 1200\n\
+                        /get <parameter> — e.g. /get attack; /get alone dumps everything\n\
+                        /preset list · /preset load <name> · /preset save <name>\n\
+                        /bank export <bank>[: <preset>, …] — all presets unless listed\n\
+                        /bank import <bank> [--rename|--overwrite|--skip] — taken names\n\
+                        /play <notes…> [seconds]s — e.g. /play C4 E4 G4 2s\n\
+                        /panic — fade out every sounding note\n\
+                        /undo — take back the last /set, /preset load or /replay\n\
+                        /export [name] · /import <name> — save or reopen this conversation\n\
+                        /replay <name> [--dry-run] — redo a saved conversation's changes\n\
+                        /script list · /script run <name> [--sync] · /script stop";
+
+/// How long `/play` holds its notes unless told otherwise.
+const DEFAULT_PLAY_SECONDS: f32 = 1.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Set {
+        parameter: String,
+        value: Value,
+    },
+    /// `None` for the whole state.
+    Get {
+        parameter: Option<String>,
+    },
+    PresetList,
+    PresetLoad(String),
+    PresetSave(String),
+    /// `presets` empty for every saved preset.
+    BankExport {
+        name: String,
+        presets: Vec<String>,
+    },
+    BankImport {
+        name: String,
+        conflict: BankConflict,
+    },
+    Play {
+        notes: Vec<u8>,
+        seconds: f32,
+    },
+    Panic,
+    Undo,
+    /// `None` to name the file by the time.
+    Export(Option<String>),
+    Import(String),
+    Replay {
+        name: String,
+        dry_run: bool,
+    },
+    Script(ScriptAction),
+    Help,
+}
+
+/// What `/script` does; see [`super::script`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScriptAction {
+    List,
+    /// `synced` follows the host's transport.
+    Run {
+        name: String,
+        synced: bool,
+    },
+    Stop,
+}
+
+impl fmt::Display for Command {
+    /// The command as it would be typed; [`parse`] reads it back as the same
+    /// command.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Set { parameter, value } => write!(f, "/set {parameter} {value}"),
+            Self::Get { parameter: None } => write!(f, "/get"),
+            Self::Get {
+                parameter: Some(parameter),
+            } => write!(f, "/get {parameter}"),
+            Self::PresetList => write!(f, "/preset list"),
+            Self::PresetLoad(name) => write!(f, "/preset load {name}"),
+            Self::PresetSave(name) => write!(f, "/preset save {name}"),
+            Self::BankExport { name, presets } => {
+                write!(f, "/bank export {name}")?;
+                if !presets.is_empty() {
+                    write!(f, ": {}", presets.join(", "))?;
+                }
+                Ok(())
+            }
+            Self::BankImport { name, conflict } => {
+                write!(f, "/bank import {name} --{}", conflict.id())
+            }
+            Self::Play { notes, seconds } => {
+                write!(f, "/play")?;
+                for note in notes {
+                    write!(f, " {note}")?;
+                }
+                write!(f, " {seconds}s")
+            }
+            Self::Panic => write!(f, "/panic"),
+            Self::Undo => write!(f, "/undo"),
+            Self::Export(None) => write!(f, "/export"),
+            Self::Export(Some(name)) => write!(f, "/export {name}"),
+            Self::Import(name) => write!(f, "/import {name}"),
+            Self::Replay { name, dry_run } => {
+                write!(f, "/replay {name}")?;
+                if *dry_run {
+                    write!(f, " --dry-run")?;
+                }
+                Ok(())
+            }
+            Self::Script(ScriptAction::List) => write!(f, "/script list"),
+            Self::Script(ScriptAction::Run { name, synced }) => {
+                write!(f, "/script run {name}")?;
+                if *synced {
+                    write!(f, " --sync")?;
+                }
+                Ok(())
+            }
+            Self::Script(ScriptAction::Stop) => write!(f, "/script stop"),
+            Self::Help => write!(f, "/help"),
+        }
+    }
+}
+
+/// Parse a chat message: each non-blank line a command. `None` if the message
+/// isn't a slash-command, so it's for the assistant; a line without a `/` is
+/// an error.
+pub fn parse_message(text: &str) -> Option<Vec<Result<Command, String>>> {
+    if !text.trim_start().starts_with('/') {
+        return None;
+    }
+    let lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    let commands = lines.map(|line| {
+        parse(line).unwrap_or_else(|| Err(format!("'{line}' isn't a command; send it on its own")))
+    });
+    Some(commands.collect())
+}
+
+/// Parse a chat line. `None` if it isn't a slash-command, so it's for the
+/// assistant.
+pub fn parse(text: &str) -> Option<Result<Command, String>> {
+    let rest = text.trim().strip_prefix('/')?;
+    let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let args = args.trim();
+    let command = match name {
+        "set" => parse_set(args),
+        "get" => match args.split_whitespace().collect::<Vec<_>>()[..] {
+            [] => Ok(Command::Get { parameter: None }),
+            [parameter] => Ok(Command::Get {
+                parameter: Some(parameter.to_string()),
+            }),
+            _ => Err("usage: /get [parameter]".to_string()),
+        },
+        "preset" => parse_preset(args),
+        "bank" => parse_bank(args),
+        "play" => parse_play(args),
+        "panic" | "undo" | "help" if !args.is_empty() => Err(format!("/{name} takes no arguments")),
+        "panic" => Ok(Command::Panic),
+        "undo" => Ok(Command::Undo),
+        "export" if args.is_empty() => Ok(Command::Export(None)),
+        "export" => Ok(Command::Export(Some(args.to_string()))),
+        "import" if args.is_empty() => Err("usage: /import <name>".to_string()),
+        "import" => Ok(Command::Import(args.to_string())),
+        "replay" => parse_replay(args),
+        "script" => parse_script(args),
+        "help" => Ok(Command::Help),
+        _ => Err(format!("unknown command '/{name}' (try /help)")),
+    };
+    Some(command)
+}
+
+/// `/set <parameter> <value>`. The value is read as JSON where it can be
+/// (`0.5`, `true`, `[1, 0.5]`) and as text otherwise (`saw`, `-6 dB`).
+fn parse_set(args: &str) -> Result<Command, String> {
+    let usage = || "usage: /set <parameter> <value>".to_string();
+    let (parameter, value) = args.split_once(char::is_whitespace).ok_or_else(usage)?;
+    let value = value.trim();
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+    Ok(Command::Set {
+        parameter: parameter.to_string(),
+        value,
+    })
+}
+
+fn parse_preset(args: &str) -> Result<Command, String> {
+    let (action, name) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    let name = name.trim().to_string();
+    match action {
+        "list" if name.is_empty() => Ok(Command::PresetList),
+        "load" | "save" if name.is_empty() => Err(format!("usage: /preset {action} <name>")),
+        "load" => Ok(Command::PresetLoad(name)),
+        "save" => Ok(Command::PresetSave(name)),
+        _ => Err("usage: /preset list | load <name> | save <name>".to_string()),
+    }
+}
+
+/// `/bank export <bank>[: <preset>, …]` or `/bank import <bank> [--<conflict>]`.
+fn parse_bank(args: &str) -> Result<Command, String> {
+    let usage = || {
+        "usage: /bank export <bank>[: <preset>, …] | import <bank> [--rename|--overwrite|--skip]"
+            .to_string()
+    };
+    let (action, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    let rest = rest.trim();
+    match action {
+        "export" => {
+            let (name, presets) = rest.split_once(':').unwrap_or((rest, ""));
+            let presets = presets
+                .split(',')
+                .map(str::trim)
+                .filter(|preset| !preset.is_empty())
+                .map(str::to_string)
+                .collect();
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(usage());
+            }
+            Ok(Command::BankExport {
+                name: name.to_string(),
+                presets,
+            })
+        }
+        "import" => {
+            let (name, conflict) = match rest.rsplit_once("--") {
+                Some((name, flag)) => (name.trim(), BankConflict::from_id(flag)?),
+                None => (rest, BankConflict::default()),
+            };
+            if name.is_empty() {
+                return Err(usage());
+            }
+            Ok(Command::BankImport {
+                name: name.to_string(),
+                conflict,
+            })
+        }
+        _ => Err(usage()),
+    }
+}
+
+fn parse_replay(args: &str) -> Result<Command, String> {
+    let (name, dry_run) = match args.strip_suffix("--dry-run") {
+        Some(name) => (name.trim(), true),
+        None => (args, false),
+    };
+    if name.is_empty() {
+        return Err("usage: /replay <name> [--dry-run]".to_string());
+    }
+    Ok(Command::Replay {
+        name: name.to_string(),
+        dry_run,
+    })
+}
+
+fn parse_script(args: &str) -> Result<Command, String> {
+    let (action, name) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    let (name, synced) = match name.trim().strip_suffix("--sync") {
+        Some(name) => (name.trim(), true),
+        None => (name.trim(), false),
+    };
+    let action = match action {
+        "list" if name.is_empty() => ScriptAction::List,
+        "stop" if name.is_empty() => ScriptAction::Stop,
+        "run" if !name.is_empty() => ScriptAction::Run {
+            name: name.to_string(),
+            synced,
+        },
+        _ => return Err("usage: /script list | run <name> [--sync] | stop".to_string()),
+    };
+    Ok(Command::Script(action))
+}
+
+/// `/play <notes…> [seconds]s`, notes by name or number as in `play_notes`.
+fn parse_play(args: &str) -> Result<Command, String> {
+    let mut notes = Vec::new();
+    let mut seconds = DEFAULT_PLAY_SECONDS;
+    for arg in args.split_whitespace() {
+        if let Some(s) = arg.strip_suffix('s').and_then(|s| s.parse::<f32>().ok()) {
+            if !(s > 0.0 && s <= audition::MAX_SECONDS) {
+                let max = audition::MAX_SECONDS;
+                return Err(format!("/play holds notes for up to {max} seconds"));
+            }
+            seconds = s;
+        } else {
+            notes.push(audition::parse_note(&Value::String(arg.to_string()))?);
+        }
+    }
+    if notes.is_empty() {
+        return Err("usage: /play <notes…> [seconds]s, e.g. /play C4 E4 G4 2s".to_string());
+    }
+    Ok(Command::Play { notes, seconds })
+}
+
+/// The writes `/set` makes, checked but not applied: an unknown parameter, a
+/// value of the wrong kind or a number outside the parameter's range is an
+/// error.
+pub fn set_events(
+    p: &SineParams,
+    parameter: &str,
+    value: &Value,
+) -> Result<Vec<RawParamEvent>, String> {
+    let mut events = Vec::new();
+    bridge::apply_write(p, parameter, value, &mut |event| events.push(event))?;
+    let written: Vec<ParamPtr> = events
+        .iter()
+        .filter_map(|event| match *event {
+            RawParamEvent::SetParameterNormalized(ptr, _) => Some(ptr),
+            _ => None,
+        })
+        .collect();
+    // A number for a single param must be in its range; the write itself
+    // would quietly clamp it.
+    if let (Some(number), [ptr]) = (value.as_f64(), written.as_slice()) {
+        // SAFETY: `apply_write` only writes params inside `p`.
+        let (min, max) = unsafe { (ptr.preview_plain(0.0), ptr.preview_plain(1.0)) };
+        let (min, max) = (f64::from(min.min(max)), f64::from(min.max(max)));
+        let slack = (max - min) * 1e-6;
+        if number < min - slack || number > max + slack {
+            // SAFETY: as above.
+            let (low, high) = unsafe {
+                (
+                    ptr.normalized_value_to_string(0.0, true),
+                    ptr.normalized_value_to_string(1.0, true),
+                )
+            };
+            return Err(format!(
+                "{number} is out of range for {parameter} ({low} to {high})"
+            ));
+        }
+    }
+    Ok(events)
+}
+
+/// The writes a `/set` or `/preset load` makes, checked but not applied.
+pub fn change_events(p: &SineParams, command: &Command) -> Result<Vec<RawParamEvent>, String> {
+    match command {
+        Command::Set { parameter, value } => set_events(p, parameter, value),
+        Command::PresetLoad(name) => {
+            let data = preset::load(name)?;
+            let mut events = Vec::new();
+            data.apply(p, &mut |event| events.push(event));
+            Ok(events)
+        }
+        _ => Err("only /set and /preset load change the patch".to_string()),
+    }
+}
+
+/// What `events` would change, a line per param: "Filter Cutoff: 800 Hz →
+/// 1.20 kHz". Params they leave as they are aren't listed.
+pub fn describe_changes(p: &SineParams, events: &[RawParamEvent]) -> Vec<String> {
+    // Each param's last write wins, in first-write order.
+    let mut writes: Vec<(ParamPtr, f32)> = Vec::new();
+    for event in events {
+        if let RawParamEvent::SetParameterNormalized(ptr, value) = *event {
+            match writes.iter_mut().find(|(p, _)| *p == ptr) {
+                Some(write) => write.1 = value,
+                None => writes.push((ptr, value)),
+            }
+        }
+    }
+    writes
+        .into_iter()
+        .filter_map(|(ptr, after)| {
+            // SAFETY: the events were made from `p`'s params.
+            let (before, from, to) = unsafe {
+                let before = ptr.unmodulated_normalized_value();
+                let from = ptr.normalized_value_to_string(before, true);
+                (before, from, ptr.normalized_value_to_string(after, true))
+            };
+            (before != after).then(|| format!("{}: {from} → {to}", param_label(p, ptr)))
+        })
+        .collect()
+}
+
+/// `/get`: a parameter's value as [`tool_bridge::read_state`] reports it, or the
+/// whole state as indented JSON.
+pub fn get(p: &SineParams, parameter: Option<&str>) -> Result<String, String> {
+    let state = tool_bridge::read_state(p);
+    let Some(parameter) = parameter else {
+        return serde_json::to_string_pretty(&state).map_err(|e| e.to_string());
+    };
+    state
+        .get(parameter)
+        .map(|value| format!("{parameter} = {value}"))
+        .ok_or_else(|| format!("unknown parameter '{parameter}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use nih_plug::prelude::Param;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// A name as the user would type one: no edge whitespace, and not ending
+    /// in a flag.
+    fn name() -> impl Strategy<Value = String> {
+        "[A-Za-z0-9_][A-Za-z0-9_ ]{0,14}[A-Za-z0-9_]"
+    }
+
+    fn value() -> impl Strategy<Value = Value> {
+        prop_oneof![
+            any::<bool>().prop_map(Value::from),
+            (-1.0e6f64..1.0e6).prop_map(Value::from),
+            "[a-z][a-z -]{0,10}".prop_map(Value::from),
+        ]
+    }
+
+    fn command() -> impl Strategy<Value = Command> {
+        let script = prop_oneof![
+            Just(ScriptAction::List),
+            Just(ScriptAction::Stop),
+            (name(), any::<bool>()).prop_map(|(name, synced)| ScriptAction::Run { name, synced }),
+        ];
+        let session = prop_oneof![
+            proptest::option::of(name()).prop_map(Command::Export),
+            name().prop_map(Command::Import),
+            (name(), any::<bool>()).prop_map(|(name, dry_run)| Command::Replay { name, dry_run }),
+            script.prop_map(Command::Script),
+            Just(Command::Help),
+        ];
+        prop_oneof![
+            session,
+            ("[a-z][a-z0-9_]{0,20}", value())
+                .prop_map(|(parameter, value)| Command::Set { parameter, value }),
+            proptest::option::of("[a-z][a-z0-9_]{0,20}")
+                .prop_map(|parameter| Command::Get { parameter }),
+            Just(Command::PresetList),
+            name().prop_map(Command::PresetLoad),
+            name().prop_map(Command::PresetSave),
+            (name(), proptest::collection::vec(name(), 0..4))
+                .prop_map(|(name, presets)| Command::BankExport { name, presets }),
+            (name(), proptest::sample::select(BankConflict::ALL.to_vec()))
+                .prop_map(|(name, conflict)| Command::BankImport { name, conflict }),
+            (
+                proptest::collection::vec(0u8..=127, 1..6),
+                (1u32..=1000).prop_map(|ms| ms as f32 / 100.0),
+            )
+                .prop_map(|(notes, seconds)| Command::Play { notes, seconds }),
+            Just(Command::Panic),
+            Just(Command::Undo),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn any_text_parses_without_panicking(text in any::<String>()) {
+            let _ = parse(&text);
+            let _ = parse_message(&text);
+        }
+
+        #[test]
+        fn any_slash_line_parses_without_panicking(
+            name in "[a-z]{0,8}",
+            args in "[ -~♯♭é]{0,40}",
+        ) {
+            let _ = parse(&format!("/{name} {args}"));
+        }
+
+        #[test]
+        fn commands_round_trip_through_text(command in command()) {
+            let text = command.to_string();
+            prop_assert_eq!(parse(&text), Some(Ok(command)), "{}", text);
+        }
+
+        #[test]
+        fn set_writes_the_value_typed(t in 0.0f32..=1.0) {
+            let p = SineParams::default();
+            let cutoff = &p.filter.cutoff;
+            let (min, max) = (cutoff.preview_plain(0.0), cutoff.preview_plain(1.0));
+            let hz = min + t * (max - min);
+            let command = parse(&format!("/set filter_cutoff {hz}")).unwrap().unwrap();
+            let events = change_events(&p, &command).unwrap();
+            let written = events.iter().find_map(|event| match *event {
+                RawParamEvent::SetParameterNormalized(ptr, value) => Some((ptr, value)),
+                _ => None,
+            });
+            let (ptr, normalized) = written.unwrap();
+            prop_assert!(ptr == cutoff.as_ptr());
+            let plain = cutoff.preview_plain(normalized);
+            prop_assert!((plain - hz).abs() <= hz.abs() * 1e-3, "{} → {}", hz, plain);
+        }
+
+        #[test]
+        fn set_rejects_values_out_of_range(over in 1.0f32..1.0e6) {
+            let p = SineParams::default();
+            let max = p.filter.cutoff.preview_plain(1.0);
+            let command = parse(&format!("/set filter_cutoff {}", max + over)).unwrap().unwrap();
+            prop_assert!(change_events(&p, &command).is_err());
+        }
+    }
+
+    #[test]
+    fn messages_split_into_commands() {
+        assert_eq!(parse_message("hello /set attack 1"), None);
+        let parsed = parse_message("/undo\n\n  /help  \nnot a command").unwrap();
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[0], Ok(Command::Undo));
+        assert_eq!(parsed[1], Ok(Command::Help));
+        assert!(parsed[2].is_err());
+    }
+
+    #[test]
+    fn huge_octaves_are_errors() {
+        assert!(parse("/play C9223372036854775807").unwrap().is_err());
+    }
+}
@@ -0,0 +1,118 @@
+//! The assistant's per-user settings: the Gemini model, key and temperature
+//! and what its tools may do, and what a project's saved session (see
+//! `session.rs`) overrides of them.
+
+use serde::{Deserialize, Serialize};
+
+use super::conflicts::ConflictPolicy;
+use super::preset;
+use super::session::{AiModel, AiSession};
+
+impl AiModel {
+    pub fn api_name(&self) -> &'static str {
+        match self {
+            AiModel::Gemini25Flash => "gemini-2.5-flash",
+            AiModel::Gemini25Pro => "gemini-2.5-pro",
+            AiModel::Gemini20Flash => "gemini-2.0-flash",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AiModel::Gemini25Flash => "2.5 Flash",
+            AiModel::Gemini25Pro => "2.5 Pro",
+            AiModel::Gemini20Flash => "2.0 Flash",
+        }
+    }
+}
+
+impl ConflictPolicy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConflictPolicy::Defer => "Defer",
+            ConflictPolicy::Reject => "Reject",
+        }
+    }
+}
+
+/// AI settings, persisted to `<config-dir>/TripleOscSynth/config.json` rather
+/// than host/project state (so the API key is not embedded in shared projects).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AiConfig {
+    #[serde(default)]
+    pub api_key: String,
+    pub model: AiModel,
+    pub temperature: f32,
+    /// What a tool write does to a knob the user is dragging.
+    #[serde(default)]
+    pub conflict_policy: ConflictPolicy,
+    /// Only let the assistant call tools that observe the synth; see
+    /// `tools::READ_ONLY_TOOLS`.
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+impl Default for AiConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            model: AiModel::Gemini25Flash,
+            temperature: 0.7,
+            conflict_policy: ConflictPolicy::default(),
+            read_only: false,
+        }
+    }
+}
+
+impl AiConfig {
+    fn path() -> std::path::PathBuf {
+        preset::app_dir().join("config.json")
+    }
+
+    /// Load config. If the file is missing, write out a default one so users
+    /// have a documented file to edit (e.g. to paste a key without the GUI). A
+    /// present-but-unparseable file is left untouched and defaults are used, so
+    /// a hand-edit with a typo isn't silently clobbered.
+    pub fn load() -> Self {
+        match std::fs::read_to_string(Self::path()) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                tracing::warn!(target: "chat", "config.json is invalid, using defaults: {e}");
+                Self::default()
+            }),
+            Err(_) => {
+                let cfg = Self::default();
+                let _ = cfg.save();
+                cfg
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let dir = preset::app_dir();
+        std::fs::create_dir_all(&dir).map_err(|e| format!("create config dir: {e}"))?;
+        let json = serde_json::to_string_pretty(self).map_err(|e| format!("serialize: {e}"))?;
+        std::fs::write(Self::path(), json).map_err(|e| format!("write config: {e}"))
+    }
+}
+
+impl AiSession {
+    /// Override `cfg` with whatever this project saved.
+    pub fn restore_into(&self, cfg: &mut AiConfig) {
+        if let Some(model) = self.model {
+            cfg.model = model;
+        }
+        if let Some(read_only) = self.read_only {
+            cfg.read_only = read_only;
+        }
+        if let Some(policy) = self.conflict_policy {
+            cfg.conflict_policy = policy;
+        }
+    }
+
+    /// Record `cfg`'s per-project settings.
+    pub fn remember(&mut self, cfg: &AiConfig) {
+        self.model = Some(cfg.model);
+        self.read_only = Some(cfg.read_only);
+        self.conflict_policy = Some(cfg.conflict_policy);
+    }
+}
@@ -0,0 +1,58 @@
+//! Knob fights: what happens when the assistant writes a param the user is
+//! dragging at that moment.
+//!
+//! The editor's knobs report their drags (see `ui::knob::KnobEvent`) into a
+//! shared [`HeldParams`], and `set_parameter` checks it before writing. Under
+//! [`ConflictPolicy::Defer`] the write is parked (see `deferred.rs`) and the
+//! editor applies it once the user lets go; under [`ConflictPolicy::Reject`]
+//! the tool call fails and the model is told why. Either way the chat
+//! transcript logs the conflict.
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use nih_plug::prelude::ParamPtr;
+use serde::{Deserialize, Serialize};
+use vizia_plug::vizia::prelude::*;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Data)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    /// Hold the write until the user releases the knob.
+    #[default]
+    Defer,
+    /// Refuse the write and report the conflict to the model.
+    Reject,
+}
+
+/// Which params the user is dragging right now.
+#[derive(Default)]
+pub struct HeldParams {
+    held: Mutex<Vec<ParamPtr>>,
+}
+
+pub(super) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl HeldParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn grab(&self, ptr: ParamPtr) {
+        lock(&self.held).push(ptr);
+    }
+
+    pub fn release(&self, ptr: ParamPtr) {
+        let mut held = lock(&self.held);
+        if let Some(i) = held.iter().position(|&p| p == ptr) {
+            held.swap_remove(i);
+        }
+    }
+
+    /// Whether the user is dragging any of `ptrs`.
+    pub fn any_held(&self, ptrs: &[ParamPtr]) -> bool {
+        let held = lock(&self.held);
+        ptrs.iter().any(|ptr| held.contains(ptr))
+    }
+}
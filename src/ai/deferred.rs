@@ -0,0 +1,101 @@
+//! The tool side of knob fights (see `conflicts.rs`): the [`ConflictPolicy`]
+//! the assistant's writes are under, and the `set_parameter` calls parked
+//! until the user lets go of what they touch.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+
+use nih_plug::prelude::ParamPtr;
+use serde_json::Value;
+use vizia_plug::vizia::prelude::EventContext;
+use vizia_plug::widgets::RawParamEvent;
+
+use super::bridge;
+use super::conflicts::{lock, ConflictPolicy, HeldParams};
+use crate::ui::toast::{ChangeRecorder, ToastEvent};
+use crate::SineParams;
+
+/// A `set_parameter` parked until the params it touches are let go.
+struct DeferredWrite {
+    parameter: String,
+    value: Value,
+}
+
+/// The writes waiting on held params, and the policy that parks them.
+#[derive(Default)]
+pub struct DeferredWrites {
+    deferred: Mutex<Vec<DeferredWrite>>,
+    /// A [`ConflictPolicy`], as its discriminant.
+    policy: AtomicU8,
+}
+
+/// The params a `set_parameter` call would write, found by a dry run.
+pub fn touched_params(
+    params: &SineParams,
+    parameter: &str,
+    value: &Value,
+) -> Result<Vec<ParamPtr>, String> {
+    let mut touched = Vec::new();
+    bridge::apply_write(params, parameter, value, &mut |event| {
+        if let RawParamEvent::SetParameterNormalized(ptr, _) = event {
+            touched.push(ptr);
+        }
+    })?;
+    Ok(touched)
+}
+
+impl DeferredWrites {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn policy(&self) -> ConflictPolicy {
+        match self.policy.load(Ordering::Relaxed) {
+            0 => ConflictPolicy::Defer,
+            _ => ConflictPolicy::Reject,
+        }
+    }
+
+    pub fn set_policy(&self, policy: ConflictPolicy) {
+        self.policy.store(policy as u8, Ordering::Relaxed);
+    }
+
+    /// Park `parameter = value` until its params are released. A later write to
+    /// the same parameter replaces it.
+    pub fn defer(&self, parameter: &str, value: Value) {
+        let mut deferred = lock(&self.deferred);
+        deferred.retain(|w| w.parameter != parameter);
+        deferred.push(DeferredWrite { parameter: parameter.to_string(), value });
+    }
+
+    /// Apply every parked write whose params `held` no longer holds, in the
+    /// order they were made, each shown in a toast. The writes were validated
+    /// when they were parked, so they can't fail.
+    pub fn apply_released(&self, cx: &mut EventContext, params: &SineParams, held: &HeldParams) {
+        for (parameter, value) in self.take_released(params, held) {
+            let mut changes = ChangeRecorder::new();
+            let _ = bridge::apply_write(params, &parameter, &value, &mut |event| {
+                changes.record(&event);
+                cx.emit(event);
+            });
+            let summary = || format!("Assistant set {parameter}");
+            if let Some(toast) = changes.into_toast(params, "Assistant", summary) {
+                cx.emit(ToastEvent::Show(toast));
+            }
+        }
+    }
+
+    fn take_released(&self, params: &SineParams, held: &HeldParams) -> Vec<(String, Value)> {
+        let mut deferred = lock(&self.deferred);
+        let mut ready = Vec::new();
+        deferred.retain(|w| {
+            let touched = touched_params(params, &w.parameter, &w.value).unwrap_or_default();
+            if held.any_held(&touched) {
+                return true;
+            }
+            ready.push((w.parameter.clone(), w.value.clone()));
+            false
+        });
+        ready
+    }
+}
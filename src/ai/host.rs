@@ -0,0 +1,34 @@
+//! Where the assistant's tools plug into the rest of the plugin, so the
+//! optional parts compile out cleanly: what a tool call can reach, and how it
+//! says what it did.
+//!
+//! The tool layer (the `mcp` feature) reports what its calls did as
+//! [`ToolLog`] events rather than through the chat's own, so it builds
+//! without the chat. The editor builds a [`ToolContext`] for the chat's AI
+//! tab; a build without the chat shows a note there instead (see
+//! `ui/no_assistant.rs`).
+
+use std::sync::Arc;
+
+use super::conflicts::HeldParams;
+use super::deferred::DeferredWrites;
+use crate::{Diagnostics, ParamRamps, PresetTransition, Schedule, SineParams};
+
+/// Everything a tool call can reach: the live parameters it reads and writes,
+/// the engine's read-only diagnostics, the parameter-ramp, schedule and
+/// preset-transition mailboxes, the knobs the user is holding and the writes
+/// waiting on them. Cheap to clone into a background task.
+#[derive(Clone)]
+pub struct ToolContext {
+    pub params: Arc<SineParams>,
+    pub diagnostics: Arc<Diagnostics>,
+    pub ramps: Arc<ParamRamps>,
+    pub schedule: Arc<Schedule>,
+    pub transition: Arc<PresetTransition>,
+    pub held: Arc<HeldParams>,
+    pub deferred: Arc<DeferredWrites>,
+}
+
+/// A line saying what a tool call did ("🎛 filter_cutoff → 1200"). The chat
+/// shows it as a tool message.
+pub struct ToolLog(pub String);
@@ -1,104 +1,74 @@
-//! Gemini configuration and the multi-turn (agentic) tool-calling loop.
+//! The multi-turn (agentic) tool-calling loop against Gemini.
 
-use crate::ai::{preset, tools};
-use crate::SineParams;
-use serde::{Deserialize, Serialize};
+use crate::ai::tools;
+use crate::isolate;
+use crate::settings::Settings;
 use serde_json::{json, Value};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use vizia_plug::vizia::prelude::*;
 
 use super::chat_ui::{ChatEvent, Role};
+use super::config::AiConfig;
+use super::host::ToolContext;
 
 /// Hard cap on tool-call rounds to avoid runaway chains.
 const MAX_ROUNDS: usize = 8;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Data)]
-pub enum AiModel {
-    Gemini25Flash,
-    Gemini25Pro,
-    Gemini20Flash,
-}
-
-impl AiModel {
-    pub fn api_name(&self) -> &'static str {
-        match self {
-            AiModel::Gemini25Flash => "gemini-2.5-flash",
-            AiModel::Gemini25Pro => "gemini-2.5-pro",
-            AiModel::Gemini20Flash => "gemini-2.0-flash",
-        }
-    }
-
-    pub fn label(&self) -> &'static str {
-        match self {
-            AiModel::Gemini25Flash => "2.5 Flash",
-            AiModel::Gemini25Pro => "2.5 Pro",
-            AiModel::Gemini20Flash => "2.0 Flash",
-        }
-    }
-}
-
-/// AI settings, persisted to `<config-dir>/TripleOscSynth/config.json` rather
-/// than host/project state (so the API key is not embedded in shared projects).
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct AiConfig {
-    #[serde(default)]
-    pub api_key: String,
-    pub model: AiModel,
-    pub temperature: f32,
-}
-
-impl Default for AiConfig {
-    fn default() -> Self {
-        Self {
-            api_key: String::new(),
-            model: AiModel::Gemini25Flash,
-            temperature: 0.7,
-        }
-    }
-}
-
-impl AiConfig {
-    fn path() -> std::path::PathBuf {
-        preset::app_dir().join("config.json")
-    }
-
-    /// Load config. If the file is missing, write out a default one so users
-    /// have a documented file to edit (e.g. to paste a key without the GUI). A
-    /// present-but-unparseable file is left untouched and defaults are used, so
-    /// a hand-edit with a typo isn't silently clobbered.
-    pub fn load() -> Self {
-        match std::fs::read_to_string(Self::path()) {
-            Ok(text) => serde_json::from_str(&text).unwrap_or_default(),
-            Err(_) => {
-                let cfg = Self::default();
-                let _ = cfg.save();
-                cfg
-            }
-        }
-    }
-
-    pub fn save(&self) -> Result<(), String> {
-        let dir = preset::app_dir();
-        std::fs::create_dir_all(&dir).map_err(|e| format!("create config dir: {e}"))?;
-        let json = serde_json::to_string_pretty(self).map_err(|e| format!("serialize: {e}"))?;
-        std::fs::write(Self::path(), json).map_err(|e| format!("write config: {e}"))
-    }
-}
+/// Retries for a request whose connection dropped or that hit a server error.
+const MAX_RETRIES: u32 = 3;
+/// Wait before the first retry; doubles with each one after.
+const RETRY_BASE: Duration = Duration::from_secs(1);
 
 fn system_prompt() -> &'static str {
     concat!(
         "You are an expert sound designer embedded in a triple-oscillator subtractive synthesizer. ",
-        "Each of the 3 oscillators has a waveform, frequency, detune, phase, gain, octave, and unison ",
-        "controls; there is a multimode filter (cutoff/resonance/drive), an ADSR amplitude envelope, ",
-        "and a separate ADSR filter envelope whose depth is set by filter_env_amount (in octaves).\n\n",
+        "Each of the 3 oscillators has a waveform, frequency, detune, phase, gain, octave, key ",
+        "tracking, and unison controls; there is a multimode filter (cutoff/resonance/drive), ",
+        "an ADSR amplitude envelope, a separate ADSR filter envelope whose depth is set by ",
+        "filter_env_amount (in octaves), portamento (glide_time/glide_legato/glide_mode), and an ",
+        "LFO plus a three-slot modulation matrix (lfo_*, modN_source/destination/amount).\n\n",
         "Design sounds by calling set_parameter (call it many times for one request). To tweak or copy ",
         "the existing sound, call get_state first. Save with save_preset, recall with load_preset, and ",
-        "use list_presets to discover names. After making changes, reply with a short, friendly summary ",
+        "use list_presets to discover names. When the user asks to solo (\"solo osc 2\") or ",
+        "unsolo an oscillator, call solo_oscillator rather than changing levels; to make one ",
+        "oscillator match another, use copy_oscillator. The two XY pads are macro controls: ",
+        "set_xy moves one and assign_xy picks what its axes drive. For gradual ",
+        "changes over time (\"open the filter over 4 bars\"), use ramp_parameter; for changes ",
+        "at a musical time (\"at bar 5 ...\"), use schedule_parameter. To move gradually from ",
+        "the current sound into a saved preset, use morph_to_preset; both it and load_preset ",
+        "leave the sections in get_state's locked_groups untouched (see lock_groups). For ",
+        "relative brightness requests (\"20% brighter\", \"a bit darker\"), use ",
+        "adjust_brightness. suggest_parameters proposes changes for goals like \"punchier\" or ",
+        "\"wider\"; show or apply its diff. To check ",
+        "what an envelope or the LFO actually does, trigger_envelope and trigger_lfo return ",
+        "its shape; attach_plot shows the user a spectrum, envelope or LFO plot in the chat. ",
+        "To let the user hear the patch, play_notes plays notes on it (with ",
+        "latch on they drone until played again) and panic stops every sounding note; ",
+        "set_scale sets the key and scale every played note ",
+        "snaps to. get_capabilities says which ",
+        "optional subsystems this build has, and get_usage_stats how this session has used ",
+        "it (polyphony, waveforms, CPU headroom). To match a recording the user has put in ",
+        "the references folder, call compare_to_reference after each round of changes and ",
+        "keep what brings it closer, or let match_reference search for it (slow). When the ",
+        "user gives a time as a note length ",
+        "(\"a quarter note\", \"an eighth\"), call get_transport for the tempo and its ",
+        "note_seconds. ",
+        "After making changes, reply with a short, friendly summary ",
         "of what you did. Choose musically sensible values within each parameter's stated range."
     )
 }
 
+/// Appended to the system prompt in read-only mode, so the model explains
+/// instead of trying every write tool in turn.
+const READ_ONLY_NOTE: &str = "The user has put you in read-only mode: you can inspect the \
+    synth (get_state, get_capabilities, list_presets, get_recovery, list_scheduled, \
+    get_voice_stats, get_diagnostics, get_usage_stats, get_transport, trigger_envelope, \
+    attach_plot, compare_to_reference, suggest_parameters) and play or stop notes \
+    (play_notes, panic) but not change it. Other tools fail with a permission error. Describe the \
+    changes you would make instead, and say read-only mode can be turned off in the ⚙ settings.";
+
 fn role_str(role: Role) -> &'static str {
     match role {
         Role::User => "user",
@@ -111,15 +81,17 @@ fn role_str(role: Role) -> &'static str {
 /// `ChatEvent::Receive` (success or error) so the UI clears its "sending" state.
 pub async fn run_conversation(
     proxy: &mut ContextProxy,
-    params: &SineParams,
+    ctx: &ToolContext,
     cfg: &AiConfig,
     convo: Vec<(Role, String)>,
     cancel: Arc<AtomicBool>,
 ) {
     // Keys copied from AI Studio often carry a trailing newline or surrounding
     // whitespace; left in the URL that yields an opaque API error, so trim it.
+    let endpoint = Settings::load().llm_endpoint;
     let url = format!(
-        "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
+        "{}/models/{}:generateContent?key={}",
+        endpoint.trim().trim_end_matches('/'),
         cfg.model.api_name(),
         cfg.api_key.trim()
     );
@@ -133,6 +105,11 @@ pub async fn run_conversation(
 
     let client = reqwest::Client::new();
 
+    let mut system_parts = vec![json!({ "text": system_prompt() })];
+    if cfg.read_only {
+        system_parts.push(json!({ "text": READ_ONLY_NOTE }));
+    }
+
     for _ in 0..MAX_ROUNDS {
         // Bail out between rounds if the user pressed Stop. The UI already reset
         // its "sending" state, so we exit quietly without another Receive.
@@ -141,18 +118,39 @@ pub async fn run_conversation(
         }
 
         let body = json!({
-            "system_instruction": { "parts": [{ "text": system_prompt() }] },
+            "system_instruction": { "parts": system_parts },
             "contents": contents,
             "tools": tools::gemini_tools(),
             "generationConfig": { "temperature": cfg.temperature }
         });
 
-        let resp = match client.post(&url).json(&body).send().await {
-            Ok(r) => r,
-            Err(e) => {
-                let _ = proxy.emit(ChatEvent::Receive(format!("Network error: {e}")));
+        // A dropped connection or a server-side (5xx) error is usually
+        // transient: retry it with exponential backoff before giving up.
+        let mut retries = 0;
+        let resp = loop {
+            let problem = match client.post(&url).json(&body).send().await {
+                Ok(r) if !r.status().is_server_error() || retries == MAX_RETRIES => break r,
+                Ok(r) => format!("server error {}", r.status()),
+                Err(e) if retries == MAX_RETRIES => {
+                    let msg = format!("Network error (gave up after {retries} retries): {e}");
+                    tracing::warn!(target: "chat", "{msg}");
+                    let _ = proxy.emit(ChatEvent::Receive(msg));
+                    return;
+                }
+                Err(e) => format!("network error: {e}"),
+            };
+            retries += 1;
+            let wait = RETRY_BASE * 2u32.pow(retries - 1);
+            tracing::warn!(target: "chat", retry = retries, "request failed ({problem})");
+            let _ = proxy.emit(ChatEvent::Status(format!(
+                "Connection problem ({problem}); retrying in {} s ({retries}/{MAX_RETRIES})…",
+                wait.as_secs()
+            )));
+            tokio::time::sleep(wait).await;
+            if cancel.load(Ordering::Relaxed) {
                 return;
             }
+            let _ = proxy.emit(ChatEvent::Status("Thinking…".to_string()));
         };
 
         if !resp.status().is_success() {
@@ -170,6 +168,7 @@ pub async fn run_conversation(
             } else {
                 format!("API error {code}: {detail}")
             };
+            tracing::warn!(target: "chat", status = %code, "API error");
             let _ = proxy.emit(ChatEvent::Receive(msg));
             return;
         }
@@ -218,7 +217,21 @@ pub async fn run_conversation(
         for fc in &calls {
             let name = fc.get("name").and_then(|n| n.as_str()).unwrap_or_default();
             let args = fc.get("args").cloned().unwrap_or_else(|| json!({}));
-            let result = tools::dispatch(proxy, params, name, &args);
+            let result = if cfg.read_only && !tools::READ_ONLY_TOOLS.contains(&name) {
+                let _ = proxy.emit(ChatEvent::ToolLog(format!("🔒 blocked {name} (read-only)")));
+                tools::permission_denied(name)
+            } else {
+                // One tool panicking fails its call, not the conversation.
+                let dispatch = || tools::dispatch(proxy, ctx, name, &args);
+                isolate::guard(dispatch).unwrap_or_else(|e| {
+                    tracing::error!(target: "mcp", "{name} panicked: {e}");
+                    json!({ "error": format!("the tool crashed: {e}") })
+                })
+            };
+            match result.get("error") {
+                Some(error) => tracing::warn!(target: "mcp", tool = name, %args, "failed: {error}"),
+                None => tracing::info!(target: "mcp", tool = name, %args, "called"),
+            }
             response_parts.push(json!({
                 "functionResponse": { "name": name, "response": { "result": result } }
             }));
@@ -0,0 +1,330 @@
+//! The `match_reference` tool: searches for the settings that bring the patch
+//! closest to a reference recording (see [`super::reference`]), rendering
+//! offline, and sets the best it finds.
+//!
+//! The search first tries each sounding oscillator's waveform in turn, then
+//! runs Nelder-Mead over the filter, both envelopes and the oscillator levels
+//! in normalized units. Sections that are locked (see `locks.rs`) and knobs
+//! the user is holding stay as they are. Each step renders the patch for the
+//! reference's length, so a search costs its budget of renders; it reports
+//! progress in the chat's tool log as it goes.
+
+use nih_plug::prelude::{Param, ParamPtr};
+use serde_json::{json, Value};
+use vizia_plug::vizia::prelude::*;
+use vizia_plug::widgets::RawParamEvent;
+
+use super::bridge::wave_to_id;
+use super::host::{ToolContext, ToolLog};
+use super::reference::Reference;
+use super::tools::show_toast;
+use crate::dsp::similarity::{self, Similarity};
+use crate::render::{self, PatchValues, RenderNote, RenderSpec};
+use crate::ui::toast::ChangeRecorder;
+use crate::{locks, SineParams, Waveform};
+
+/// Renders a search makes unless asked for another number.
+pub const DEFAULT_RENDERS: usize = 150;
+/// Most renders a search may make, which bounds how long it takes.
+pub const MAX_RENDERS: usize = 400;
+/// Fewest renders: enough for the waveform pass and a first simplex.
+pub const MIN_RENDERS: usize = 30;
+/// Renders between progress lines.
+const PROGRESS_EVERY: usize = 25;
+/// Size of the first simplex's steps, in normalized units.
+const INITIAL_STEP: f32 = 0.15;
+/// The search stops early once its points score within this of each other.
+const CONVERGED: f32 = 1e-3;
+
+/// How the reference is played, and how hard to look.
+pub struct Target<'a> {
+    pub file: &'a str,
+    pub reference: &'a Reference,
+    pub notes: &'a [u8],
+    pub velocity: f32,
+    /// Seconds before the notes are released.
+    pub hold: f32,
+    pub renders: usize,
+    /// Set the best patch found, rather than only report it.
+    pub apply: bool,
+}
+
+/// A parameter the search moves, by its `set_parameter` name.
+struct Dimension {
+    name: &'static str,
+    ptr: ParamPtr,
+    /// A waveform rather than a continuous value.
+    waveform: bool,
+}
+
+fn dimension<P: Param>(name: &'static str, param: &P) -> Dimension {
+    Dimension {
+        name,
+        ptr: param.as_ptr(),
+        waveform: false,
+    }
+}
+
+/// What the Nelder-Mead pass moves.
+fn continuous(p: &SineParams) -> [Dimension; 12] {
+    [
+        dimension("filter_cutoff", &p.filter.cutoff),
+        dimension("filter_resonance", &p.filter.resonance),
+        dimension("filter_env_amount", &p.filter.env_amount),
+        dimension("filter_attack", &p.filter_env.attack),
+        dimension("filter_decay", &p.filter_env.decay),
+        dimension("filter_sustain", &p.filter_env.sustain),
+        dimension("attack", &p.adsr.attack),
+        dimension("decay", &p.adsr.decay),
+        dimension("sustain", &p.adsr.sustain),
+        dimension("release", &p.adsr.release),
+        dimension("gain2", &p.osc2.gain),
+        dimension("gain3", &p.osc3.gain),
+    ]
+}
+
+/// Lower is closer: spectral shape first, then the level envelope.
+fn cost(similarity: &Similarity) -> f32 {
+    similarity.spectral_distance_db / 6.0 + (1.0 - similarity.envelope_correlation)
+}
+
+fn similarity_json(similarity: &Similarity) -> Value {
+    json!({
+        "spectral_distance_db": similarity.spectral_distance_db,
+        "envelope_correlation": similarity.envelope_correlation,
+        "level_difference_db": similarity.level_difference_db,
+    })
+}
+
+/// Renders patches and remembers the closest.
+struct Search<'a> {
+    p: &'a SineParams,
+    reference: &'a Reference,
+    spec: RenderSpec,
+    renders: usize,
+    best: (PatchValues, f32, Similarity),
+}
+
+impl Search<'_> {
+    fn measure(&mut self, values: &PatchValues) -> f32 {
+        let rendered: Vec<f32> = render::render(self.p, values, &self.spec)
+            .iter()
+            .map(|[left, right]| 0.5 * (left + right))
+            .collect();
+        let sample_rate = self.reference.sample_rate;
+        let similarity = similarity::compare(&self.reference.samples, &rendered, sample_rate);
+        let cost = cost(&similarity);
+        self.renders += 1;
+        if cost < self.best.1 {
+            self.best = (values.clone(), cost, similarity);
+        }
+        cost
+    }
+}
+
+/// Minimize `f` over `[0, 1]^n` from `start`, in at most `budget` calls.
+fn nelder_mead(start: Vec<f32>, budget: usize, f: &mut impl FnMut(&[f32]) -> f32) {
+    let n = start.len();
+    if n == 0 {
+        return;
+    }
+    let clamp = |x: Vec<f32>| -> Vec<f32> { x.into_iter().map(|v| v.clamp(0.0, 1.0)).collect() };
+    let mut simplex = vec![(start.clone(), f(&start))];
+    for i in 0..n {
+        let mut x = start.clone();
+        x[i] += if x[i] > 1.0 - INITIAL_STEP {
+            -INITIAL_STEP
+        } else {
+            INITIAL_STEP
+        };
+        let cost = f(&x);
+        simplex.push((x, cost));
+    }
+    let mut calls = n + 1;
+    while calls < budget {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        if simplex[n].1 - simplex[0].1 < CONVERGED {
+            break;
+        }
+        let centroid: Vec<f32> = (0..n)
+            .map(|i| simplex[..n].iter().map(|(x, _)| x[i]).sum::<f32>() / n as f32)
+            .collect();
+        let worst = simplex[n].0.clone();
+        // The point `t` times the worst's distance past the centroid.
+        let toward = |t: f32| {
+            clamp(
+                centroid
+                    .iter()
+                    .zip(&worst)
+                    .map(|(c, w)| c + t * (c - w))
+                    .collect(),
+            )
+        };
+
+        let reflected = toward(1.0);
+        let r = f(&reflected);
+        calls += 1;
+        if r < simplex[0].1 {
+            let expanded = toward(2.0);
+            let e = f(&expanded);
+            calls += 1;
+            simplex[n] = if e < r { (expanded, e) } else { (reflected, r) };
+        } else if r < simplex[n - 1].1 {
+            simplex[n] = (reflected, r);
+        } else {
+            let contracted = toward(-0.5);
+            let c = f(&contracted);
+            calls += 1;
+            if c < simplex[n].1 {
+                simplex[n] = (contracted, c);
+            } else {
+                // Shrink every point halfway toward the best.
+                let best = simplex[0].0.clone();
+                for (x, cost) in &mut simplex[1..] {
+                    *x = best
+                        .iter()
+                        .zip(x.iter())
+                        .map(|(b, v)| b + 0.5 * (v - b))
+                        .collect();
+                    *cost = f(x);
+                    calls += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Search for the patch closest to `target`, report it, and set it if asked.
+pub fn run(proxy: &mut ContextProxy, ctx: &ToolContext, target: &Target) -> Value {
+    let p: &SineParams = &ctx.params;
+    let locked = locks::current(p).locked_params(p);
+    let free = |ptr: &ParamPtr| !locked.contains(ptr) && !ctx.held.any_held(&[*ptr]);
+
+    let spec = RenderSpec {
+        notes: target
+            .notes
+            .iter()
+            .map(|&note| RenderNote {
+                note,
+                velocity: target.velocity,
+                start: 0.0,
+                length: target.hold,
+            })
+            .collect(),
+        seconds: target.reference.seconds(),
+        sample_rate: target.reference.sample_rate,
+        bpm: ctx.diagnostics.tempo(),
+    };
+    let mut search = Search {
+        p,
+        reference: target.reference,
+        spec,
+        renders: 0,
+        best: (PatchValues::new(), f32::INFINITY, Similarity::default()),
+    };
+    let budget = target.renders.clamp(MIN_RENDERS, MAX_RENDERS);
+    let mut report = |search: &Search| {
+        if search.renders % PROGRESS_EVERY == 0 {
+            let log = format!(
+                "🧬 matching '{}': {}/{budget} renders, {:.1} dB apart",
+                target.file, search.renders, search.best.2.spectral_distance_db
+            );
+            let _ = proxy.emit(ToolLog(log));
+        }
+    };
+
+    search.measure(&PatchValues::new());
+    let before = search.best.2;
+
+    // Waveforms first: a wrong one is a big step no small move can make up.
+    let mut base = PatchValues::new();
+    let mut dims: Vec<Dimension> = Vec::new();
+    for (n, osc) in [&p.osc1, &p.osc2, &p.osc3].into_iter().enumerate() {
+        let ptr = osc.waveform.as_ptr();
+        if osc.gain.value() <= 0.0 || !free(&ptr) {
+            continue;
+        }
+        let mut best = (osc.waveform.value(), search.best.1);
+        for waveform in Waveform::ALL
+            .into_iter()
+            .filter(|&w| w != osc.waveform.value())
+        {
+            let mut values = base.clone();
+            values.insert(ptr, osc.waveform.preview_normalized(waveform));
+            let cost = search.measure(&values);
+            report(&search);
+            if cost < best.1 {
+                best = (waveform, cost);
+            }
+        }
+        base.insert(ptr, osc.waveform.preview_normalized(best.0));
+        let name = ["waveform1", "waveform2", "waveform3"][n];
+        dims.push(Dimension {
+            name,
+            ptr,
+            waveform: true,
+        });
+    }
+
+    let moving: Vec<Dimension> = continuous(p).into_iter().filter(|d| free(&d.ptr)).collect();
+    // SAFETY: every `ParamPtr` here points into `p`.
+    let start = moving
+        .iter()
+        .map(|d| unsafe { d.ptr.unmodulated_normalized_value() })
+        .collect();
+    let remaining = budget.saturating_sub(search.renders);
+    nelder_mead(start, remaining, &mut |x| {
+        let mut values = base.clone();
+        values.extend(moving.iter().zip(x).map(|(d, &v)| (d.ptr, v)));
+        let cost = search.measure(&values);
+        report(&search);
+        cost
+    });
+    dims.extend(moving);
+
+    let (best, _, after) = search.best;
+    let mut changes = Vec::new();
+    let mut events = Vec::new();
+    for dim in &dims {
+        let Some(&to) = best.get(&dim.ptr) else {
+            continue;
+        };
+        // SAFETY: as above.
+        let from = unsafe { dim.ptr.unmodulated_normalized_value() };
+        if (to - from).abs() < 1e-4 {
+            continue;
+        }
+        let plain = |normalized: f32| {
+            if dim.waveform {
+                let index = (normalized * (Waveform::ALL.len() - 1) as f32).round() as usize;
+                json!(wave_to_id(Waveform::ALL[index]))
+            } else {
+                // SAFETY: as above.
+                json!(unsafe { dim.ptr.preview_plain(normalized) })
+            }
+        };
+        changes.push(json!({ "parameter": dim.name, "from": plain(from), "to": plain(to) }));
+        events.push(RawParamEvent::BeginSetParameter(dim.ptr));
+        events.push(RawParamEvent::SetParameterNormalized(dim.ptr, to));
+        events.push(RawParamEvent::EndSetParameter(dim.ptr));
+    }
+
+    let applied = target.apply && !events.is_empty();
+    if applied {
+        let mut recorder = ChangeRecorder::new();
+        for event in events {
+            recorder.record(&event);
+            let _ = proxy.emit(event);
+        }
+        show_toast(proxy, p, recorder, || {
+            format!("Assistant matched the patch to '{}'", target.file)
+        });
+    }
+    json!({
+        "renders": search.renders,
+        "before": similarity_json(&before),
+        "after": similarity_json(&after),
+        "changes": changes,
+        "applied": applied,
+    })
+}
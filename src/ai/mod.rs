@@ -2,13 +2,74 @@
 //! the Gemini tool-calling API and reads/writes JSON presets on disk.
 //!
 //! - [`chat_ui`] — the "AI ASSIST" tab (Vizia model + view).
-//! - [`llm`] — Gemini config + the multi-turn tool-calling loop.
+//! - [`commands`] — the chat's `/set`, `/get`, `/preset`, `/play` and `/undo`.
+//! - [`llm`] — the multi-turn Gemini tool-calling loop.
+//! - [`config`] — the assistant's per-user settings.
 //! - [`tools`] — tool schemas + the in-plugin dispatcher.
 //! - [`bridge`] — maps tool calls to real `nih_plug` parameter writes.
+//! - [`tool_bridge`] — the tools' `get_state` and argument parsing.
+//! - [`brightness`] — the measured `adjust_brightness` macro.
+//! - [`probe`] — the `trigger_envelope` / `trigger_lfo` debug tools.
+//! - [`plot`] — the `attach_plot` tool and the plots it puts in the chat.
+//! - [`capabilities`] — the `get_capabilities` tool.
+//! - [`audition`] — the `play_notes` tool.
+//! - [`reference`] — the `compare_to_reference` tool and its WAV reader.
+//! - [`matcher`] — the `match_reference` tool, a search toward a reference.
+//! - [`suggest`] — the rule-based `suggest_parameters` tool.
+//! - [`conflicts`] — what a tool write does to a knob the user is dragging.
+//! - [`deferred`] — the tool writes parked until the user lets go.
+//! - [`host`] — what a tool call can reach, and how it reports back.
 //! - [`preset`] — parameter snapshot capture/apply + JSON file storage.
+//! - [`bank`] — preset banks, for sharing a set of presets as one file.
+//! - [`bank_io`] — exporting and importing banks.
+//! - [`script`] — timed `.toscript` command files.
+//! - [`session`] — assistant settings saved with the project.
+//! - [`transcript`] — chat export, import and replay.
+//!
+//! The tools are behind the `mcp` feature and the chat (with its tokio and
+//! reqwest dependencies) behind `chat`; presets and banks, the parameter
+//! bridge and the saved session are always built.
 
+#[cfg(feature = "mcp")]
+pub mod audition;
+pub mod bank;
+#[cfg(feature = "mcp")]
+pub mod bank_io;
 pub mod bridge;
+#[cfg(feature = "mcp")]
+pub mod brightness;
+#[cfg(feature = "mcp")]
+pub mod capabilities;
+#[cfg(feature = "chat")]
 pub mod chat_ui;
+#[cfg(feature = "chat")]
+pub mod commands;
+#[cfg(feature = "chat")]
+pub mod config;
+pub mod conflicts;
+#[cfg(feature = "mcp")]
+pub mod deferred;
+#[cfg(feature = "mcp")]
+pub mod host;
+#[cfg(feature = "chat")]
 pub mod llm;
+#[cfg(feature = "mcp")]
+pub mod matcher;
+#[cfg(feature = "mcp")]
+pub mod plot;
 pub mod preset;
+#[cfg(feature = "mcp")]
+pub mod probe;
+#[cfg(feature = "mcp")]
+pub mod reference;
+#[cfg(feature = "chat")]
+pub mod script;
+pub mod session;
+#[cfg(feature = "mcp")]
+pub mod suggest;
+#[cfg(feature = "mcp")]
+pub mod tool_bridge;
+#[cfg(feature = "mcp")]
 pub mod tools;
+#[cfg(feature = "chat")]
+pub mod transcript;
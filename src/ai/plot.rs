@@ -0,0 +1,176 @@
+//! The `attach_plot` tool: draws the patch's spectrum, an envelope's curve or
+//! the LFO's shape into the chat, so the assistant can show what it describes.
+//! Plots come from the offline renderer (see `render.rs`): they don't need the
+//! host's audio, don't sound and don't restart the live LFO.
+//!
+//! A plot travels in the transcript as a fenced ```` ```plot ```` block of
+//! JSON, which the chat draws in place of the code; exports keep the block, so
+//! an imported transcript shows its plots again. The assistant never sees the
+//! block (tool lines aren't sent back), only the summary the tool returns.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::audition::note_name;
+use crate::dsp::spectrum::{self, FLOOR_DB, SPECTRUM_BANDS};
+use crate::dsp::ProbeTarget;
+use crate::render::{self, PatchValues, RenderSpec};
+use crate::SineParams;
+
+/// Language of a plot's fenced block.
+pub const FENCE: &str = "plot";
+/// Note a spectrum is rendered at unless asked for another (middle C).
+pub const DEFAULT_NOTE: u8 = 60;
+/// Sample rate of a plot's render.
+const SAMPLE_RATE: f32 = 48_000.0;
+/// Spectrum bands within this of the loudest count as part of the sound.
+const AUDIBLE_DB: f32 = 40.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlotKind {
+    Spectrum,
+    AmpEnvelope,
+    FilterEnvelope,
+    Lfo,
+}
+
+impl PlotKind {
+    pub fn parse(id: &str) -> Result<Self, String> {
+        match id {
+            "spectrum" => Ok(Self::Spectrum),
+            "amp_envelope" => Ok(Self::AmpEnvelope),
+            "filter_envelope" => Ok(Self::FilterEnvelope),
+            "lfo" => Ok(Self::Lfo),
+            other => Err(format!(
+                "unknown plot '{other}' (expected spectrum, amp_envelope, filter_envelope or lfo)"
+            )),
+        }
+    }
+
+    /// Whether the values are bipolar (`-1..=1`) rather than `0..=1`; a
+    /// spectrum's are in dB.
+    pub fn bipolar(self) -> bool {
+        self == Self::Lfo
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Plot {
+    pub kind: PlotKind,
+    /// Evenly spaced points: for a spectrum, band levels in dB on a log scale
+    /// from 20 Hz to 20 kHz; otherwise levels over time.
+    pub values: Vec<f32>,
+    /// Seconds the points span; `0` for a spectrum.
+    #[serde(default)]
+    pub seconds: f32,
+    /// The note a spectrum was rendered at.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<u8>,
+}
+
+impl Plot {
+    /// Plot `kind` for the live patch. `note` is what a spectrum plays; `bpm`
+    /// times synced stages and a synced LFO.
+    pub fn render(p: &SineParams, kind: PlotKind, note: u8, bpm: f32) -> Self {
+        // An empty map reads every param's live value.
+        let live = PatchValues::new();
+        let target = match kind {
+            PlotKind::Spectrum => {
+                let spec = RenderSpec {
+                    bpm,
+                    ..RenderSpec::single_note(note)
+                };
+                let mono: Vec<f32> = render::render(p, &live, &spec)
+                    .into_iter()
+                    .map(|[left, right]| (left + right) * 0.5)
+                    .collect();
+                let bands = spectrum::spectrum(&mono, spec.sample_rate);
+                return Self {
+                    kind,
+                    values: bands.iter().map(|db| (db * 10.0).round() / 10.0).collect(),
+                    seconds: 0.0,
+                    note: Some(note),
+                };
+            }
+            PlotKind::AmpEnvelope => ProbeTarget::AmpEnvelope,
+            PlotKind::FilterEnvelope => ProbeTarget::FilterEnvelope,
+            PlotKind::Lfo => ProbeTarget::Lfo,
+        };
+        let (values, spacing) = render::trace(p, &live, target, SAMPLE_RATE, bpm);
+        Self {
+            kind,
+            seconds: values.len() as f32 * spacing,
+            values: values
+                .iter()
+                .map(|v| (v * 1000.0).round() / 1000.0)
+                .collect(),
+            note: None,
+        }
+    }
+
+    /// What the chat shows above the plot.
+    pub fn caption(&self) -> String {
+        match self.kind {
+            PlotKind::Spectrum => {
+                let note = self.note.map(note_name).unwrap_or_default();
+                format!("Spectrum of {note} · 20 Hz – 20 kHz, 0 to −90 dB")
+            }
+            PlotKind::AmpEnvelope => format!("Amp envelope · {:.2} s", self.seconds),
+            PlotKind::FilterEnvelope => format!("Filter envelope · {:.2} s", self.seconds),
+            PlotKind::Lfo => format!("LFO · two cycles, {:.2} s", self.seconds),
+        }
+    }
+
+    /// The chat message that carries the plot.
+    pub fn message(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        format!("📈 {}\n```{FENCE}\n{json}\n```", self.caption())
+    }
+
+    /// What the assistant is told about the plot it attached.
+    pub fn summary(&self) -> Value {
+        if self.kind != PlotKind::Spectrum {
+            let (low, high) = self
+                .values
+                .iter()
+                .fold((f32::MAX, f32::MIN), |(low, high), &v| {
+                    (low.min(v), high.max(v))
+                });
+            return json!({
+                "attached": self.caption(),
+                "seconds": self.seconds,
+                "min": low,
+                "max": high,
+                "end": self.values.last(),
+            });
+        }
+        let band_hz = |band: usize| {
+            (spectrum::band_edge(band) * spectrum::band_edge(band + 1))
+                .sqrt()
+                .round()
+        };
+        let peak = (0..SPECTRUM_BANDS).max_by(|&a, &b| self.values[a].total_cmp(&self.values[b]));
+        let Some(peak) = peak.filter(|&band| self.values[band] > FLOOR_DB) else {
+            return json!({ "attached": self.caption(), "silent": true });
+        };
+        let loudest = self.values[peak];
+        let top = (0..SPECTRUM_BANDS)
+            .rev()
+            .find(|&band| self.values[band] > loudest - AUDIBLE_DB)
+            .unwrap_or(peak);
+        json!({
+            "attached": self.caption(),
+            "peak_hz": band_hz(peak),
+            "peak_db": loudest,
+            "highest_audible_hz": band_hz(top),
+        })
+    }
+}
+
+/// The plot in a fenced block's code, if that is what it holds.
+pub fn parse(code: &str) -> Option<Plot> {
+    let plot: Plot = serde_json::from_str(code).ok()?;
+    let bands = plot.kind != PlotKind::Spectrum || plot.values.len() == SPECTRUM_BANDS;
+    (bands && !plot.values.is_empty()).then_some(plot)
+}
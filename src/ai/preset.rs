@@ -5,13 +5,23 @@
 //! `get_state` tool. `capture` reads the live parameters; `apply` writes them
 //! back by emitting [`RawParamEvent`]s.
 
-use crate::ai::bridge::{emit_set, id_to_mode, id_to_wave, mode_to_id, wave_to_id};
+use crate::ai::bridge::{
+    arp_mode_to_id, bend_release_to_id, curve_to_id, division_to_id, drive_character_to_id,
+    emit_set, gate_pattern_to_id, glide_mode_to_id, id_to_arp_mode, id_to_bend_release,
+    id_to_curve, id_to_division, id_to_drive_character, id_to_glide_mode, id_to_lfo_reset,
+    id_to_mod_dest, id_to_mod_source, id_to_mode, id_to_noise_color, id_to_sub_wave, id_to_wave,
+    lfo_reset_to_id, mod_dest_to_id, mod_source_to_id, mode_to_id, noise_color_to_id,
+    sub_wave_to_id, wave_to_id,
+};
+use crate::locks;
+use crate::params::NUM_DRAWBARS;
+use crate::persist::ProjectState;
 use crate::SineParams;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use vizia_plug::widgets::RawParamEvent;
 
-const SCHEMA_VERSION: u32 = 2;
+pub const SCHEMA_VERSION: u32 = 36;
 
 // Defaults for the v2 filter-envelope fields so v1 presets (which lack them)
 // still load. `filter_env_amount` defaults to 0 (envelope disabled), and the
@@ -28,6 +38,198 @@ fn d_sustain() -> f32 {
 fn d_release() -> f32 {
     1.0
 }
+// v3 glide: off, constant-time.
+fn d_glide_mode() -> String {
+    "constant_time".to_string()
+}
+// v4 key tracking: on, as every oscillator behaved before.
+fn d_key_track() -> bool {
+    true
+}
+// v5 modulation: a 2 Hz sine LFO and empty slots.
+fn d_lfo_rate() -> f32 {
+    2.0
+}
+fn d_lfo_shape() -> String {
+    "sine".to_string()
+}
+fn d_mod_source() -> String {
+    "lfo".to_string()
+}
+fn d_mod_destination() -> String {
+    "none".to_string()
+}
+// v6 tempo sync: off, with the divisions `AdsrParams::default` uses.
+fn d_attack_division() -> String {
+    "1/16".to_string()
+}
+fn d_decay_division() -> String {
+    "1/8".to_string()
+}
+fn d_release_division() -> String {
+    "1/4".to_string()
+}
+// v11 declick: on, the param's default.
+fn d_declick() -> bool {
+    true
+}
+// v12 sidechain follower: the params' defaults.
+fn d_sidechain_attack() -> f32 {
+    5.0
+}
+fn d_sidechain_release() -> f32 {
+    150.0
+}
+// v14 additive osc1: off, with just the fundamental drawn.
+fn d_drawbars() -> Vec<f32> {
+    let mut levels = vec![0.0; NUM_DRAWBARS];
+    levels[0] = 1.0;
+    levels
+}
+// v15 unison shape: evenly spaced, as before.
+fn d_unison_curve() -> String {
+    "linear".to_string()
+}
+fn d_unison_density() -> f32 {
+    0.5
+}
+// v17 stereo width: unchanged stereo image, the params' defaults.
+fn d_stereo_width() -> f32 {
+    1.0
+}
+fn d_width_crossover() -> f32 {
+    150.0
+}
+// v18 trance gate: off, with the params' defaults.
+fn d_gate_division() -> String {
+    "1/16".to_string()
+}
+fn d_gate_depth() -> f32 {
+    1.0
+}
+fn d_gate_smoothing() -> f32 {
+    0.1
+}
+fn d_gate_pattern() -> String {
+    "x.xxx.x.x.xxx.x.".to_string()
+}
+// v19 LFO tempo sync: off, a cycle per beat, realigned when the transport starts.
+fn d_lfo_division() -> String {
+    "1/4".to_string()
+}
+fn d_lfo_reset() -> String {
+    "transport".to_string()
+}
+// v20 drive character: the original tanh curve, and `#[serde(default)]` for
+// the level compensation, off, so older presets keep their level.
+fn d_drive_character() -> String {
+    "soft".to_string()
+}
+// v21 arpeggiator: off, with the params' defaults.
+fn d_arp_mode() -> String {
+    "up".to_string()
+}
+fn d_arp_division() -> String {
+    "1/16".to_string()
+}
+fn d_arp_octaves() -> i32 {
+    1
+}
+fn d_arp_gate() -> f32 {
+    0.5
+}
+// v22 arp swing and humanize: `#[serde(default)]`, all off.
+// v23 wavefolders: `#[serde(default)]`, off.
+// v24 formant filter: `#[serde(default)]`, vowel A.
+// v25 comb: off, with the params' defaults.
+fn d_comb_feedback() -> f32 {
+    0.9
+}
+fn d_comb_damping() -> f32 {
+    0.3
+}
+fn d_comb_mix() -> f32 {
+    1.0
+}
+// v26 per-oscillator amp envelopes: off, with the same defaults as the
+// main envelope's fields above.
+// v27 Color macro: neutral, with the params' default reach.
+fn d_color_cutoff() -> f32 {
+    3.0
+}
+fn d_color_drive() -> f32 {
+    2.0
+}
+fn d_color_shape() -> f32 {
+    0.5
+}
+// v28 pitch bend: the params' defaults, held notes and tails following the
+// wheel.
+fn d_bend_range() -> i32 {
+    2
+}
+fn d_bend_smoothing() -> f32 {
+    0.01
+}
+fn d_bend_release() -> String {
+    "follow".to_string()
+}
+// v29 patch metadata: `#[serde(default)]`, all empty.
+// v30 unison gain compensation: `#[serde(default)]`, off, so older presets
+// keep their unison level.
+// v31 release blip: the param's default; no slot uses it.
+fn d_release_blip() -> f32 {
+    40.0
+}
+// v32 oscillator filter routing: through the filter, the param's default.
+fn d_to_filter() -> bool {
+    true
+}
+// v33 noise source: white, and `#[serde(default)]` for its level, off.
+fn d_noise_color() -> String {
+    "white".to_string()
+}
+// v34 sub oscillator: a sine an octave down, and `#[serde(default)]` for its
+// level, off.
+fn d_sub_waveform() -> String {
+    "sine".to_string()
+}
+fn d_sub_octave() -> i32 {
+    1
+}
+// v35 waveform morph: `#[serde(default)]`, off, at the sine.
+// v36 Color blend: `#[serde(default)]`, none, so older presets that morph
+// keep their sound.
+
+/// Who made a patch, what it is and what kind of sound it is. Saved in presets
+/// and with the project (see `ProjectState::metadata`), but it isn't part of the
+/// sound: `apply` leaves it alone, and whoever really loads a preset
+/// [`store`](Self::store)s it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatchMetadata {
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub description: String,
+    /// Free text, e.g. "Bass" or "Pad"; matched ignoring case when filtering.
+    #[serde(default)]
+    pub category: String,
+}
+
+impl PatchMetadata {
+    /// The project's metadata.
+    pub fn current(p: &SineParams) -> Self {
+        p.project_state
+            .read()
+            .map(|state| state.metadata.clone())
+            .unwrap_or_default()
+    }
+
+    /// Make this the project's metadata.
+    pub fn store(&self, p: &SineParams) {
+        ProjectState::update(p, |state| state.metadata = self.clone());
+    }
+}
 
 /// A complete, serializable snapshot of the synth's parameters.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -36,66 +238,377 @@ pub struct PresetData {
     pub name: String,
     #[serde(default)]
     pub schema_version: u32,
+    #[serde(flatten)]
+    pub metadata: PatchMetadata,
 
     // --- Oscillator 1 ---
     pub waveform1: String,
     pub frequency1: f32,
+    #[serde(default = "d_key_track")]
+    pub key_track1: bool,
+    #[serde(default)]
+    pub mute1: bool,
     pub detune1: f32,
     pub phase1: f32,
+    #[serde(default)]
+    pub phase_dist1: f32,
+    #[serde(default)]
+    pub wavefold1: f32,
+    #[serde(default)]
+    pub morph1: bool,
+    #[serde(default)]
+    pub morph_position1: f32,
     pub gain1: f32,
     pub octave1: i32,
     pub unison_voices1: i32,
     pub unison_detune1: f32,
     pub unison_blend1: f32,
     pub unison_volume1: f32,
+    #[serde(default = "d_unison_curve")]
+    pub unison_curve1: String,
+    #[serde(default = "d_unison_density")]
+    pub unison_density1: f32,
+    #[serde(default)]
+    pub unison_comp1: bool,
+    #[serde(default = "d_to_filter")]
+    pub to_filter1: bool,
+    #[serde(default)]
+    pub own_env1: bool,
+    #[serde(default = "d_attack")]
+    pub env_attack1: f32,
+    #[serde(default)]
+    pub env_hold1: f32,
+    #[serde(default = "d_decay")]
+    pub env_decay1: f32,
+    #[serde(default = "d_sustain")]
+    pub env_sustain1: f32,
+    #[serde(default = "d_release")]
+    pub env_release1: f32,
+    #[serde(default)]
+    pub env_attack_sync1: bool,
+    #[serde(default = "d_attack_division")]
+    pub env_attack_division1: String,
+    #[serde(default)]
+    pub env_decay_sync1: bool,
+    #[serde(default = "d_decay_division")]
+    pub env_decay_division1: String,
+    #[serde(default)]
+    pub env_release_sync1: bool,
+    #[serde(default = "d_release_division")]
+    pub env_release_division1: String,
+    #[serde(default)]
+    pub additive1: bool,
+    /// Harmonic 1 first; see `AdditiveParams`.
+    #[serde(default = "d_drawbars")]
+    pub drawbars1: Vec<f32>,
 
     // --- Oscillator 2 ---
     pub waveform2: String,
     pub frequency2: f32,
+    #[serde(default = "d_key_track")]
+    pub key_track2: bool,
+    #[serde(default)]
+    pub mute2: bool,
     pub detune2: f32,
     pub phase2: f32,
+    #[serde(default)]
+    pub phase_dist2: f32,
+    #[serde(default)]
+    pub wavefold2: f32,
+    #[serde(default)]
+    pub morph2: bool,
+    #[serde(default)]
+    pub morph_position2: f32,
     pub gain2: f32,
     pub octave2: i32,
     pub unison_voices2: i32,
     pub unison_detune2: f32,
     pub unison_blend2: f32,
     pub unison_volume2: f32,
+    #[serde(default = "d_unison_curve")]
+    pub unison_curve2: String,
+    #[serde(default = "d_unison_density")]
+    pub unison_density2: f32,
+    #[serde(default)]
+    pub unison_comp2: bool,
+    #[serde(default = "d_to_filter")]
+    pub to_filter2: bool,
+    #[serde(default)]
+    pub own_env2: bool,
+    #[serde(default = "d_attack")]
+    pub env_attack2: f32,
+    #[serde(default)]
+    pub env_hold2: f32,
+    #[serde(default = "d_decay")]
+    pub env_decay2: f32,
+    #[serde(default = "d_sustain")]
+    pub env_sustain2: f32,
+    #[serde(default = "d_release")]
+    pub env_release2: f32,
+    #[serde(default)]
+    pub env_attack_sync2: bool,
+    #[serde(default = "d_attack_division")]
+    pub env_attack_division2: String,
+    #[serde(default)]
+    pub env_decay_sync2: bool,
+    #[serde(default = "d_decay_division")]
+    pub env_decay_division2: String,
+    #[serde(default)]
+    pub env_release_sync2: bool,
+    #[serde(default = "d_release_division")]
+    pub env_release_division2: String,
 
     // --- Oscillator 3 ---
     pub waveform3: String,
     pub frequency3: f32,
+    #[serde(default = "d_key_track")]
+    pub key_track3: bool,
+    #[serde(default)]
+    pub mute3: bool,
     pub detune3: f32,
     pub phase3: f32,
+    #[serde(default)]
+    pub phase_dist3: f32,
+    #[serde(default)]
+    pub wavefold3: f32,
+    #[serde(default)]
+    pub morph3: bool,
+    #[serde(default)]
+    pub morph_position3: f32,
     pub gain3: f32,
     pub octave3: i32,
     pub unison_voices3: i32,
     pub unison_detune3: f32,
     pub unison_blend3: f32,
     pub unison_volume3: f32,
+    #[serde(default = "d_unison_curve")]
+    pub unison_curve3: String,
+    #[serde(default = "d_unison_density")]
+    pub unison_density3: f32,
+    #[serde(default)]
+    pub unison_comp3: bool,
+    #[serde(default = "d_to_filter")]
+    pub to_filter3: bool,
+    #[serde(default)]
+    pub own_env3: bool,
+    #[serde(default = "d_attack")]
+    pub env_attack3: f32,
+    #[serde(default)]
+    pub env_hold3: f32,
+    #[serde(default = "d_decay")]
+    pub env_decay3: f32,
+    #[serde(default = "d_sustain")]
+    pub env_sustain3: f32,
+    #[serde(default = "d_release")]
+    pub env_release3: f32,
+    #[serde(default)]
+    pub env_attack_sync3: bool,
+    #[serde(default = "d_attack_division")]
+    pub env_attack_division3: String,
+    #[serde(default)]
+    pub env_decay_sync3: bool,
+    #[serde(default = "d_decay_division")]
+    pub env_decay_division3: String,
+    #[serde(default)]
+    pub env_release_sync3: bool,
+    #[serde(default = "d_release_division")]
+    pub env_release_division3: String,
 
     // --- Filter ---
     pub filter_mode: String,
     pub filter_cutoff: f32,
     pub filter_resonance: f32,
     pub filter_drive: f32,
+    #[serde(default = "d_drive_character")]
+    pub filter_drive_character: String,
+    #[serde(default)]
+    pub filter_drive_compensation: bool,
     #[serde(default)]
     pub filter_env_amount: f32,
+    #[serde(default)]
+    pub filter_vowel: f32,
 
-    // --- Envelope (ADSR) ---
+    // --- Comb ---
+    #[serde(default)]
+    pub comb: bool,
+    #[serde(default)]
+    pub comb_pluck: bool,
+    #[serde(default = "d_comb_feedback")]
+    pub comb_feedback: f32,
+    #[serde(default = "d_comb_damping")]
+    pub comb_damping: f32,
+    #[serde(default = "d_comb_mix")]
+    pub comb_mix: f32,
+
+    // --- Noise ---
+    #[serde(default = "d_noise_color")]
+    pub noise_color: String,
+    #[serde(default)]
+    pub noise_level: f32,
+
+    // --- Sub oscillator ---
+    #[serde(default = "d_sub_waveform")]
+    pub sub_waveform: String,
+    #[serde(default = "d_sub_octave")]
+    pub sub_octave: i32,
+    #[serde(default)]
+    pub sub_level: f32,
+
+    // --- Envelope (AHDSR; presets before v16 have no hold) ---
     pub attack: f32,
+    #[serde(default)]
+    pub hold: f32,
     pub decay: f32,
     pub sustain: f32,
     pub release: f32,
+    #[serde(default)]
+    pub attack_sync: bool,
+    #[serde(default = "d_attack_division")]
+    pub attack_division: String,
+    #[serde(default)]
+    pub decay_sync: bool,
+    #[serde(default = "d_decay_division")]
+    pub decay_division: String,
+    #[serde(default)]
+    pub release_sync: bool,
+    #[serde(default = "d_release_division")]
+    pub release_division: String,
 
-    // --- Filter envelope (ADSR) ---
+    // --- Filter envelope (AHDSR) ---
     #[serde(default = "d_attack")]
     pub filter_attack: f32,
+    #[serde(default)]
+    pub filter_hold: f32,
     #[serde(default = "d_decay")]
     pub filter_decay: f32,
     #[serde(default = "d_sustain")]
     pub filter_sustain: f32,
     #[serde(default = "d_release")]
     pub filter_release: f32,
+    #[serde(default)]
+    pub filter_attack_sync: bool,
+    #[serde(default = "d_attack_division")]
+    pub filter_attack_division: String,
+    #[serde(default)]
+    pub filter_decay_sync: bool,
+    #[serde(default = "d_decay_division")]
+    pub filter_decay_division: String,
+    #[serde(default)]
+    pub filter_release_sync: bool,
+    #[serde(default = "d_release_division")]
+    pub filter_release_division: String,
+
+    // --- Glide ---
+    #[serde(default)]
+    pub glide_time: f32,
+    #[serde(default)]
+    pub glide_legato: bool,
+    #[serde(default = "d_glide_mode")]
+    pub glide_mode: String,
+
+    // --- Pitch bend ---
+    #[serde(default = "d_bend_range")]
+    pub bend_range: i32,
+    #[serde(default = "d_bend_smoothing")]
+    pub bend_smoothing: f32,
+    #[serde(default = "d_bend_release")]
+    pub bend_release: String,
+
+    // --- Modulation ---
+    #[serde(default = "d_lfo_rate")]
+    pub lfo_rate: f32,
+    #[serde(default = "d_lfo_shape")]
+    pub lfo_shape: String,
+    #[serde(default)]
+    pub lfo_sync: bool,
+    #[serde(default = "d_lfo_division")]
+    pub lfo_division: String,
+    #[serde(default = "d_lfo_reset")]
+    pub lfo_reset: String,
+    #[serde(default = "d_sidechain_attack")]
+    pub sidechain_attack: f32,
+    #[serde(default = "d_sidechain_release")]
+    pub sidechain_release: f32,
+    #[serde(default = "d_release_blip")]
+    pub release_blip: f32,
+    #[serde(default = "d_mod_source")]
+    pub mod1_source: String,
+    #[serde(default = "d_mod_destination")]
+    pub mod1_destination: String,
+    #[serde(default)]
+    pub mod1_amount: f32,
+    #[serde(default = "d_mod_source")]
+    pub mod2_source: String,
+    #[serde(default = "d_mod_destination")]
+    pub mod2_destination: String,
+    #[serde(default)]
+    pub mod2_amount: f32,
+    #[serde(default = "d_mod_source")]
+    pub mod3_source: String,
+    #[serde(default = "d_mod_destination")]
+    pub mod3_destination: String,
+    #[serde(default)]
+    pub mod3_amount: f32,
+
+    // --- Character ---
+    #[serde(default)]
+    pub analog: f32,
+    #[serde(default)]
+    pub keyboard_pan: f32,
+    #[serde(default)]
+    pub release_velocity: f32,
+    #[serde(default = "d_declick")]
+    pub declick: bool,
+    #[serde(default)]
+    pub color: f32,
+    #[serde(default = "d_color_cutoff")]
+    pub color_cutoff: f32,
+    #[serde(default = "d_color_drive")]
+    pub color_drive: f32,
+    #[serde(default = "d_color_shape")]
+    pub color_shape: f32,
+    #[serde(default)]
+    pub color_blend: f32,
+
+    // --- Master ---
+    #[serde(default = "d_stereo_width")]
+    pub stereo_width: f32,
+    #[serde(default = "d_width_crossover")]
+    pub width_crossover: f32,
+
+    // --- Trance gate ---
+    #[serde(default)]
+    pub gate: bool,
+    #[serde(default = "d_gate_division")]
+    pub gate_division: String,
+    #[serde(default = "d_gate_depth")]
+    pub gate_depth: f32,
+    #[serde(default = "d_gate_smoothing")]
+    pub gate_smoothing: f32,
+    #[serde(default)]
+    pub gate_pan: f32,
+    /// `x` for an open step, `.` for a gated one, step 1 first.
+    #[serde(default = "d_gate_pattern")]
+    pub gate_pattern: String,
+
+    // --- Arpeggiator (MIDI out, local play and the seed belong to the session,
+    // not stored) ---
+    #[serde(default)]
+    pub arp: bool,
+    #[serde(default = "d_arp_mode")]
+    pub arp_mode: String,
+    #[serde(default = "d_arp_division")]
+    pub arp_division: String,
+    #[serde(default = "d_arp_octaves")]
+    pub arp_octaves: i32,
+    #[serde(default = "d_arp_gate")]
+    pub arp_gate: f32,
+    #[serde(default)]
+    pub arp_swing: f32,
+    #[serde(default)]
+    pub arp_timing_jitter: f32,
+    #[serde(default)]
+    pub arp_velocity_jitter: f32,
 }
 
 impl PresetData {
@@ -104,108 +617,431 @@ impl PresetData {
         Self {
             name: String::new(),
             schema_version: SCHEMA_VERSION,
+            metadata: PatchMetadata::current(p),
 
             waveform1: wave_to_id(p.osc1.waveform.value()).into(),
             frequency1: p.osc1.frequency.value(),
+            key_track1: p.osc1.key_track.value(),
+            mute1: p.osc1.mute.value(),
             detune1: p.osc1.detune.value(),
             phase1: p.osc1.phase.value(),
+            phase_dist1: p.osc1.phase_distortion.value(),
+            wavefold1: p.osc1.wavefold.value(),
+            morph1: p.osc1.morph.value(),
+            morph_position1: p.osc1.morph_position.value(),
             gain1: p.osc1.gain.value(),
             octave1: p.osc1.octave.value(),
             unison_voices1: p.osc1.unison_voices.value(),
             unison_detune1: p.osc1.unison_detune.value(),
             unison_blend1: p.osc1.unison_blend.value(),
             unison_volume1: p.osc1.unison_volume.value(),
+            unison_curve1: curve_to_id(p.osc1.unison_curve.value()).into(),
+            unison_density1: p.osc1.unison_density.value(),
+            unison_comp1: p.osc1.unison_compensation.value(),
+            to_filter1: p.osc1.to_filter.value(),
+            own_env1: p.osc1.own_envelope.value(),
+            env_attack1: p.osc1.envelope.attack.value(),
+            env_hold1: p.osc1.envelope.hold.value(),
+            env_decay1: p.osc1.envelope.decay.value(),
+            env_sustain1: p.osc1.envelope.sustain.value(),
+            env_release1: p.osc1.envelope.release.value(),
+            env_attack_sync1: p.osc1.envelope.attack_sync.value(),
+            env_attack_division1: division_to_id(p.osc1.envelope.attack_division.value()).into(),
+            env_decay_sync1: p.osc1.envelope.decay_sync.value(),
+            env_decay_division1: division_to_id(p.osc1.envelope.decay_division.value()).into(),
+            env_release_sync1: p.osc1.envelope.release_sync.value(),
+            env_release_division1: division_to_id(p.osc1.envelope.release_division.value()).into(),
+            additive1: p.additive.enabled.value(),
+            drawbars1: p.additive.drawbars.iter().map(|d| d.level.value()).collect(),
 
             waveform2: wave_to_id(p.osc2.waveform.value()).into(),
             frequency2: p.osc2.frequency.value(),
+            key_track2: p.osc2.key_track.value(),
+            mute2: p.osc2.mute.value(),
             detune2: p.osc2.detune.value(),
             phase2: p.osc2.phase.value(),
+            phase_dist2: p.osc2.phase_distortion.value(),
+            wavefold2: p.osc2.wavefold.value(),
+            morph2: p.osc2.morph.value(),
+            morph_position2: p.osc2.morph_position.value(),
             gain2: p.osc2.gain.value(),
             octave2: p.osc2.octave.value(),
             unison_voices2: p.osc2.unison_voices.value(),
             unison_detune2: p.osc2.unison_detune.value(),
             unison_blend2: p.osc2.unison_blend.value(),
             unison_volume2: p.osc2.unison_volume.value(),
+            unison_curve2: curve_to_id(p.osc2.unison_curve.value()).into(),
+            unison_density2: p.osc2.unison_density.value(),
+            unison_comp2: p.osc2.unison_compensation.value(),
+            to_filter2: p.osc2.to_filter.value(),
+            own_env2: p.osc2.own_envelope.value(),
+            env_attack2: p.osc2.envelope.attack.value(),
+            env_hold2: p.osc2.envelope.hold.value(),
+            env_decay2: p.osc2.envelope.decay.value(),
+            env_sustain2: p.osc2.envelope.sustain.value(),
+            env_release2: p.osc2.envelope.release.value(),
+            env_attack_sync2: p.osc2.envelope.attack_sync.value(),
+            env_attack_division2: division_to_id(p.osc2.envelope.attack_division.value()).into(),
+            env_decay_sync2: p.osc2.envelope.decay_sync.value(),
+            env_decay_division2: division_to_id(p.osc2.envelope.decay_division.value()).into(),
+            env_release_sync2: p.osc2.envelope.release_sync.value(),
+            env_release_division2: division_to_id(p.osc2.envelope.release_division.value()).into(),
 
             waveform3: wave_to_id(p.osc3.waveform.value()).into(),
             frequency3: p.osc3.frequency.value(),
+            key_track3: p.osc3.key_track.value(),
+            mute3: p.osc3.mute.value(),
             detune3: p.osc3.detune.value(),
             phase3: p.osc3.phase.value(),
+            phase_dist3: p.osc3.phase_distortion.value(),
+            wavefold3: p.osc3.wavefold.value(),
+            morph3: p.osc3.morph.value(),
+            morph_position3: p.osc3.morph_position.value(),
             gain3: p.osc3.gain.value(),
             octave3: p.osc3.octave.value(),
             unison_voices3: p.osc3.unison_voices.value(),
             unison_detune3: p.osc3.unison_detune.value(),
             unison_blend3: p.osc3.unison_blend.value(),
             unison_volume3: p.osc3.unison_volume.value(),
+            unison_curve3: curve_to_id(p.osc3.unison_curve.value()).into(),
+            unison_density3: p.osc3.unison_density.value(),
+            unison_comp3: p.osc3.unison_compensation.value(),
+            to_filter3: p.osc3.to_filter.value(),
+            own_env3: p.osc3.own_envelope.value(),
+            env_attack3: p.osc3.envelope.attack.value(),
+            env_hold3: p.osc3.envelope.hold.value(),
+            env_decay3: p.osc3.envelope.decay.value(),
+            env_sustain3: p.osc3.envelope.sustain.value(),
+            env_release3: p.osc3.envelope.release.value(),
+            env_attack_sync3: p.osc3.envelope.attack_sync.value(),
+            env_attack_division3: division_to_id(p.osc3.envelope.attack_division.value()).into(),
+            env_decay_sync3: p.osc3.envelope.decay_sync.value(),
+            env_decay_division3: division_to_id(p.osc3.envelope.decay_division.value()).into(),
+            env_release_sync3: p.osc3.envelope.release_sync.value(),
+            env_release_division3: division_to_id(p.osc3.envelope.release_division.value()).into(),
 
             filter_mode: mode_to_id(p.filter.mode.value()).into(),
             filter_cutoff: p.filter.cutoff.value(),
             filter_resonance: p.filter.resonance.value(),
             filter_drive: p.filter.drive.value(),
+            filter_drive_character: drive_character_to_id(p.filter.drive_character.value()).into(),
+            filter_drive_compensation: p.filter.drive_compensation.value(),
             filter_env_amount: p.filter.env_amount.value(),
+            filter_vowel: p.filter.vowel.value(),
+
+            comb: p.comb.enabled.value(),
+            comb_pluck: p.comb.pluck.value(),
+            comb_feedback: p.comb.feedback.value(),
+            comb_damping: p.comb.damping.value(),
+            comb_mix: p.comb.mix.value(),
+            noise_color: noise_color_to_id(p.noise.color.value()).into(),
+            noise_level: p.noise.level.value(),
+            sub_waveform: sub_wave_to_id(p.sub.waveform.value()).into(),
+            sub_octave: p.sub.octave.value(),
+            sub_level: p.sub.level.value(),
 
             attack: p.adsr.attack.value(),
+            hold: p.adsr.hold.value(),
             decay: p.adsr.decay.value(),
             sustain: p.adsr.sustain.value(),
             release: p.adsr.release.value(),
+            attack_sync: p.adsr.attack_sync.value(),
+            attack_division: division_to_id(p.adsr.attack_division.value()).into(),
+            decay_sync: p.adsr.decay_sync.value(),
+            decay_division: division_to_id(p.adsr.decay_division.value()).into(),
+            release_sync: p.adsr.release_sync.value(),
+            release_division: division_to_id(p.adsr.release_division.value()).into(),
 
             filter_attack: p.filter_env.attack.value(),
+            filter_hold: p.filter_env.hold.value(),
             filter_decay: p.filter_env.decay.value(),
             filter_sustain: p.filter_env.sustain.value(),
             filter_release: p.filter_env.release.value(),
+            filter_attack_sync: p.filter_env.attack_sync.value(),
+            filter_attack_division: division_to_id(p.filter_env.attack_division.value()).into(),
+            filter_decay_sync: p.filter_env.decay_sync.value(),
+            filter_decay_division: division_to_id(p.filter_env.decay_division.value()).into(),
+            filter_release_sync: p.filter_env.release_sync.value(),
+            filter_release_division: division_to_id(p.filter_env.release_division.value()).into(),
+
+            glide_time: p.glide.time.value(),
+            glide_legato: p.glide.legato.value(),
+            glide_mode: glide_mode_to_id(p.glide.mode.value()).into(),
+
+            bend_range: p.bend.range.value(),
+            bend_smoothing: p.bend.smoothing.value(),
+            bend_release: bend_release_to_id(p.bend.release.value()).into(),
+
+            lfo_rate: p.modulation.lfo_rate.value(),
+            lfo_shape: wave_to_id(p.modulation.lfo_shape.value()).into(),
+            lfo_sync: p.modulation.lfo_sync.value(),
+            lfo_division: division_to_id(p.modulation.lfo_division.value()).into(),
+            lfo_reset: lfo_reset_to_id(p.modulation.lfo_reset.value()).into(),
+            sidechain_attack: p.modulation.sidechain_attack.value(),
+            sidechain_release: p.modulation.sidechain_release.value(),
+            release_blip: p.modulation.release_blip.value(),
+            mod1_source: mod_source_to_id(p.modulation.slots[0].source.value()).into(),
+            mod1_destination: mod_dest_to_id(p.modulation.slots[0].destination.value()).into(),
+            mod1_amount: p.modulation.slots[0].amount.value(),
+            mod2_source: mod_source_to_id(p.modulation.slots[1].source.value()).into(),
+            mod2_destination: mod_dest_to_id(p.modulation.slots[1].destination.value()).into(),
+            mod2_amount: p.modulation.slots[1].amount.value(),
+            mod3_source: mod_source_to_id(p.modulation.slots[2].source.value()).into(),
+            mod3_destination: mod_dest_to_id(p.modulation.slots[2].destination.value()).into(),
+            mod3_amount: p.modulation.slots[2].amount.value(),
+
+            analog: p.analog.value(),
+            keyboard_pan: p.keyboard_pan.value(),
+            release_velocity: p.release_velocity.value(),
+            declick: p.declick.value(),
+            color: p.color.amount.value(),
+            color_cutoff: p.color.cutoff.value(),
+            color_drive: p.color.drive.value(),
+            color_shape: p.color.shape.value(),
+            color_blend: p.color.blend.value(),
+
+            stereo_width: p.stereo_width.value(),
+            width_crossover: p.width_crossover.value(),
+
+            gate: p.gate.enabled.value(),
+            gate_division: division_to_id(p.gate.division.value()).into(),
+            gate_depth: p.gate.depth.value(),
+            gate_smoothing: p.gate.smoothing.value(),
+            gate_pan: p.gate.pan.value(),
+            gate_pattern: gate_pattern_to_id(p.gate.steps.iter().map(|s| s.on.value())),
+
+            arp: p.arp.enabled.value(),
+            arp_mode: arp_mode_to_id(p.arp.mode.value()).into(),
+            arp_division: division_to_id(p.arp.division.value()).into(),
+            arp_octaves: p.arp.octaves.value(),
+            arp_gate: p.arp.gate.value(),
+            arp_swing: p.arp.swing.value(),
+            arp_timing_jitter: p.arp.timing_jitter.value(),
+            arp_velocity_jitter: p.arp.velocity_jitter.value(),
         }
     }
 
     /// Apply this snapshot to the live parameters by emitting `RawParamEvent`s.
+    /// Locked groups (see `locks`) are left as they are, and so is the
+    /// project's metadata.
     pub fn apply(&self, p: &SineParams, emit: &mut impl FnMut(RawParamEvent)) {
+        let emit = &mut locks::skip_locked(p, emit);
         emit_set(&p.osc1.waveform, id_to_wave(&self.waveform1), emit);
         emit_set(&p.osc1.frequency, self.frequency1, emit);
+        emit_set(&p.osc1.key_track, self.key_track1, emit);
+        emit_set(&p.osc1.mute, self.mute1, emit);
         emit_set(&p.osc1.detune, self.detune1, emit);
         emit_set(&p.osc1.phase, self.phase1, emit);
+        emit_set(&p.osc1.phase_distortion, self.phase_dist1, emit);
+        emit_set(&p.osc1.wavefold, self.wavefold1, emit);
+        emit_set(&p.osc1.morph, self.morph1, emit);
+        emit_set(&p.osc1.morph_position, self.morph_position1, emit);
         emit_set(&p.osc1.gain, self.gain1, emit);
         emit_set(&p.osc1.octave, self.octave1, emit);
         emit_set(&p.osc1.unison_voices, self.unison_voices1, emit);
         emit_set(&p.osc1.unison_detune, self.unison_detune1, emit);
         emit_set(&p.osc1.unison_blend, self.unison_blend1, emit);
         emit_set(&p.osc1.unison_volume, self.unison_volume1, emit);
+        emit_set(&p.osc1.unison_curve, id_to_curve(&self.unison_curve1), emit);
+        emit_set(&p.osc1.unison_density, self.unison_density1, emit);
+        emit_set(&p.osc1.unison_compensation, self.unison_comp1, emit);
+        emit_set(&p.osc1.to_filter, self.to_filter1, emit);
+        emit_set(&p.osc1.own_envelope, self.own_env1, emit);
+        let envelope = &p.osc1.envelope;
+        emit_set(&envelope.attack, self.env_attack1, emit);
+        emit_set(&envelope.hold, self.env_hold1, emit);
+        emit_set(&envelope.decay, self.env_decay1, emit);
+        emit_set(&envelope.sustain, self.env_sustain1, emit);
+        emit_set(&envelope.release, self.env_release1, emit);
+        emit_set(&envelope.attack_sync, self.env_attack_sync1, emit);
+        emit_set(&envelope.attack_division, id_to_division(&self.env_attack_division1), emit);
+        emit_set(&envelope.decay_sync, self.env_decay_sync1, emit);
+        emit_set(&envelope.decay_division, id_to_division(&self.env_decay_division1), emit);
+        emit_set(&envelope.release_sync, self.env_release_sync1, emit);
+        emit_set(&envelope.release_division, id_to_division(&self.env_release_division1), emit);
+        emit_set(&p.additive.enabled, self.additive1, emit);
+        // A hand-edited file may list fewer drawbars; the rest stay put.
+        for (drawbar, level) in p.additive.drawbars.iter().zip(&self.drawbars1) {
+            emit_set(&drawbar.level, *level, emit);
+        }
 
         emit_set(&p.osc2.waveform, id_to_wave(&self.waveform2), emit);
         emit_set(&p.osc2.frequency, self.frequency2, emit);
+        emit_set(&p.osc2.key_track, self.key_track2, emit);
+        emit_set(&p.osc2.mute, self.mute2, emit);
         emit_set(&p.osc2.detune, self.detune2, emit);
         emit_set(&p.osc2.phase, self.phase2, emit);
+        emit_set(&p.osc2.phase_distortion, self.phase_dist2, emit);
+        emit_set(&p.osc2.wavefold, self.wavefold2, emit);
+        emit_set(&p.osc2.morph, self.morph2, emit);
+        emit_set(&p.osc2.morph_position, self.morph_position2, emit);
         emit_set(&p.osc2.gain, self.gain2, emit);
         emit_set(&p.osc2.octave, self.octave2, emit);
         emit_set(&p.osc2.unison_voices, self.unison_voices2, emit);
         emit_set(&p.osc2.unison_detune, self.unison_detune2, emit);
         emit_set(&p.osc2.unison_blend, self.unison_blend2, emit);
         emit_set(&p.osc2.unison_volume, self.unison_volume2, emit);
+        emit_set(&p.osc2.unison_curve, id_to_curve(&self.unison_curve2), emit);
+        emit_set(&p.osc2.unison_density, self.unison_density2, emit);
+        emit_set(&p.osc2.unison_compensation, self.unison_comp2, emit);
+        emit_set(&p.osc2.to_filter, self.to_filter2, emit);
+        emit_set(&p.osc2.own_envelope, self.own_env2, emit);
+        let envelope = &p.osc2.envelope;
+        emit_set(&envelope.attack, self.env_attack2, emit);
+        emit_set(&envelope.hold, self.env_hold2, emit);
+        emit_set(&envelope.decay, self.env_decay2, emit);
+        emit_set(&envelope.sustain, self.env_sustain2, emit);
+        emit_set(&envelope.release, self.env_release2, emit);
+        emit_set(&envelope.attack_sync, self.env_attack_sync2, emit);
+        emit_set(&envelope.attack_division, id_to_division(&self.env_attack_division2), emit);
+        emit_set(&envelope.decay_sync, self.env_decay_sync2, emit);
+        emit_set(&envelope.decay_division, id_to_division(&self.env_decay_division2), emit);
+        emit_set(&envelope.release_sync, self.env_release_sync2, emit);
+        emit_set(&envelope.release_division, id_to_division(&self.env_release_division2), emit);
 
         emit_set(&p.osc3.waveform, id_to_wave(&self.waveform3), emit);
         emit_set(&p.osc3.frequency, self.frequency3, emit);
+        emit_set(&p.osc3.key_track, self.key_track3, emit);
+        emit_set(&p.osc3.mute, self.mute3, emit);
         emit_set(&p.osc3.detune, self.detune3, emit);
         emit_set(&p.osc3.phase, self.phase3, emit);
+        emit_set(&p.osc3.phase_distortion, self.phase_dist3, emit);
+        emit_set(&p.osc3.wavefold, self.wavefold3, emit);
+        emit_set(&p.osc3.morph, self.morph3, emit);
+        emit_set(&p.osc3.morph_position, self.morph_position3, emit);
         emit_set(&p.osc3.gain, self.gain3, emit);
         emit_set(&p.osc3.octave, self.octave3, emit);
         emit_set(&p.osc3.unison_voices, self.unison_voices3, emit);
         emit_set(&p.osc3.unison_detune, self.unison_detune3, emit);
         emit_set(&p.osc3.unison_blend, self.unison_blend3, emit);
         emit_set(&p.osc3.unison_volume, self.unison_volume3, emit);
+        emit_set(&p.osc3.unison_curve, id_to_curve(&self.unison_curve3), emit);
+        emit_set(&p.osc3.unison_density, self.unison_density3, emit);
+        emit_set(&p.osc3.unison_compensation, self.unison_comp3, emit);
+        emit_set(&p.osc3.to_filter, self.to_filter3, emit);
+        emit_set(&p.osc3.own_envelope, self.own_env3, emit);
+        let envelope = &p.osc3.envelope;
+        emit_set(&envelope.attack, self.env_attack3, emit);
+        emit_set(&envelope.hold, self.env_hold3, emit);
+        emit_set(&envelope.decay, self.env_decay3, emit);
+        emit_set(&envelope.sustain, self.env_sustain3, emit);
+        emit_set(&envelope.release, self.env_release3, emit);
+        emit_set(&envelope.attack_sync, self.env_attack_sync3, emit);
+        emit_set(&envelope.attack_division, id_to_division(&self.env_attack_division3), emit);
+        emit_set(&envelope.decay_sync, self.env_decay_sync3, emit);
+        emit_set(&envelope.decay_division, id_to_division(&self.env_decay_division3), emit);
+        emit_set(&envelope.release_sync, self.env_release_sync3, emit);
+        emit_set(&envelope.release_division, id_to_division(&self.env_release_division3), emit);
 
         emit_set(&p.filter.mode, id_to_mode(&self.filter_mode), emit);
         emit_set(&p.filter.cutoff, self.filter_cutoff, emit);
         emit_set(&p.filter.resonance, self.filter_resonance, emit);
         emit_set(&p.filter.drive, self.filter_drive, emit);
+        let character = id_to_drive_character(&self.filter_drive_character);
+        emit_set(&p.filter.drive_character, character, emit);
+        emit_set(&p.filter.drive_compensation, self.filter_drive_compensation, emit);
         emit_set(&p.filter.env_amount, self.filter_env_amount, emit);
+        emit_set(&p.filter.vowel, self.filter_vowel, emit);
+
+        emit_set(&p.comb.enabled, self.comb, emit);
+        emit_set(&p.comb.pluck, self.comb_pluck, emit);
+        emit_set(&p.comb.feedback, self.comb_feedback, emit);
+        emit_set(&p.comb.damping, self.comb_damping, emit);
+        emit_set(&p.comb.mix, self.comb_mix, emit);
+        emit_set(&p.noise.color, id_to_noise_color(&self.noise_color), emit);
+        emit_set(&p.noise.level, self.noise_level, emit);
+        emit_set(&p.sub.waveform, id_to_sub_wave(&self.sub_waveform), emit);
+        emit_set(&p.sub.octave, self.sub_octave, emit);
+        emit_set(&p.sub.level, self.sub_level, emit);
 
         emit_set(&p.adsr.attack, self.attack, emit);
+        emit_set(&p.adsr.hold, self.hold, emit);
         emit_set(&p.adsr.decay, self.decay, emit);
         emit_set(&p.adsr.sustain, self.sustain, emit);
         emit_set(&p.adsr.release, self.release, emit);
+        emit_set(&p.adsr.attack_sync, self.attack_sync, emit);
+        emit_set(&p.adsr.attack_division, id_to_division(&self.attack_division), emit);
+        emit_set(&p.adsr.decay_sync, self.decay_sync, emit);
+        emit_set(&p.adsr.decay_division, id_to_division(&self.decay_division), emit);
+        emit_set(&p.adsr.release_sync, self.release_sync, emit);
+        emit_set(&p.adsr.release_division, id_to_division(&self.release_division), emit);
 
         emit_set(&p.filter_env.attack, self.filter_attack, emit);
+        emit_set(&p.filter_env.hold, self.filter_hold, emit);
         emit_set(&p.filter_env.decay, self.filter_decay, emit);
         emit_set(&p.filter_env.sustain, self.filter_sustain, emit);
         emit_set(&p.filter_env.release, self.filter_release, emit);
+        emit_set(&p.filter_env.attack_sync, self.filter_attack_sync, emit);
+        emit_set(&p.filter_env.attack_division, id_to_division(&self.filter_attack_division), emit);
+        emit_set(&p.filter_env.decay_sync, self.filter_decay_sync, emit);
+        emit_set(&p.filter_env.decay_division, id_to_division(&self.filter_decay_division), emit);
+        emit_set(&p.filter_env.release_sync, self.filter_release_sync, emit);
+        emit_set(
+            &p.filter_env.release_division,
+            id_to_division(&self.filter_release_division),
+            emit,
+        );
+
+        emit_set(&p.glide.time, self.glide_time, emit);
+        emit_set(&p.glide.legato, self.glide_legato, emit);
+        emit_set(&p.glide.mode, id_to_glide_mode(&self.glide_mode), emit);
+
+        emit_set(&p.bend.range, self.bend_range, emit);
+        emit_set(&p.bend.smoothing, self.bend_smoothing, emit);
+        emit_set(&p.bend.release, id_to_bend_release(&self.bend_release), emit);
+
+        emit_set(&p.modulation.lfo_rate, self.lfo_rate, emit);
+        emit_set(&p.modulation.lfo_shape, id_to_wave(&self.lfo_shape), emit);
+        emit_set(&p.modulation.lfo_sync, self.lfo_sync, emit);
+        emit_set(&p.modulation.lfo_division, id_to_division(&self.lfo_division), emit);
+        emit_set(&p.modulation.lfo_reset, id_to_lfo_reset(&self.lfo_reset), emit);
+        emit_set(&p.modulation.sidechain_attack, self.sidechain_attack, emit);
+        emit_set(&p.modulation.sidechain_release, self.sidechain_release, emit);
+        emit_set(&p.modulation.release_blip, self.release_blip, emit);
+        let slot = &p.modulation.slots[0];
+        emit_set(&slot.source, id_to_mod_source(&self.mod1_source), emit);
+        emit_set(&slot.destination, id_to_mod_dest(&self.mod1_destination), emit);
+        emit_set(&slot.amount, self.mod1_amount, emit);
+        let slot = &p.modulation.slots[1];
+        emit_set(&slot.source, id_to_mod_source(&self.mod2_source), emit);
+        emit_set(&slot.destination, id_to_mod_dest(&self.mod2_destination), emit);
+        emit_set(&slot.amount, self.mod2_amount, emit);
+        let slot = &p.modulation.slots[2];
+        emit_set(&slot.source, id_to_mod_source(&self.mod3_source), emit);
+        emit_set(&slot.destination, id_to_mod_dest(&self.mod3_destination), emit);
+        emit_set(&slot.amount, self.mod3_amount, emit);
+
+        emit_set(&p.analog, self.analog, emit);
+        emit_set(&p.keyboard_pan, self.keyboard_pan, emit);
+        emit_set(&p.release_velocity, self.release_velocity, emit);
+        emit_set(&p.declick, self.declick, emit);
+        emit_set(&p.color.amount, self.color, emit);
+        emit_set(&p.color.cutoff, self.color_cutoff, emit);
+        emit_set(&p.color.drive, self.color_drive, emit);
+        emit_set(&p.color.shape, self.color_shape, emit);
+        emit_set(&p.color.blend, self.color_blend, emit);
+
+        emit_set(&p.stereo_width, self.stereo_width, emit);
+        emit_set(&p.width_crossover, self.width_crossover, emit);
+
+        emit_set(&p.gate.enabled, self.gate, emit);
+        emit_set(&p.gate.division, id_to_division(&self.gate_division), emit);
+        emit_set(&p.gate.depth, self.gate_depth, emit);
+        emit_set(&p.gate.smoothing, self.gate_smoothing, emit);
+        emit_set(&p.gate.pan, self.gate_pan, emit);
+        let pattern = self.gate_pattern.chars().filter(|c| !c.is_whitespace());
+        for (step, c) in p.gate.steps.iter().zip(pattern) {
+            emit_set(&step.on, c == 'x', emit);
+        }
+
+        emit_set(&p.arp.enabled, self.arp, emit);
+        emit_set(&p.arp.mode, id_to_arp_mode(&self.arp_mode), emit);
+        emit_set(&p.arp.division, id_to_division(&self.arp_division), emit);
+        emit_set(&p.arp.octaves, self.arp_octaves, emit);
+        emit_set(&p.arp.gate, self.arp_gate, emit);
+        emit_set(&p.arp.swing, self.arp_swing, emit);
+        emit_set(&p.arp.timing_jitter, self.arp_timing_jitter, emit);
+        emit_set(&p.arp.velocity_jitter, self.arp_velocity_jitter, emit);
     }
 }
 
@@ -222,7 +1058,8 @@ pub fn presets_dir() -> PathBuf {
     app_dir().join("presets")
 }
 
-fn sanitize(name: &str) -> String {
+/// `name` made safe for a file name.
+pub(crate) fn sanitize(name: &str) -> String {
     let cleaned: String = name
         .chars()
         .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' || c == ' ' {
@@ -239,23 +1076,32 @@ fn sanitize(name: &str) -> String {
     }
 }
 
-/// Capture the current params and write `presets/<name>.json`.
+/// Capture the current params and metadata and write `presets/<name>.json`.
 pub fn save(p: &SineParams, name: &str) -> Result<PathBuf, String> {
-    let dir = presets_dir();
-    std::fs::create_dir_all(&dir).map_err(|e| format!("create presets dir: {e}"))?;
-
     let mut data = PresetData::capture(p);
     data.name = name.to_string();
+    write(&data)
+}
 
-    let path = dir.join(format!("{}.json", sanitize(name)));
-    let json = serde_json::to_string_pretty(&data).map_err(|e| format!("serialize: {e}"))?;
+/// Write `data` to `presets/<data.name>.json`, replacing any preset there.
+pub(crate) fn write(data: &PresetData) -> Result<PathBuf, String> {
+    let dir = presets_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| format!("create presets dir: {e}"))?;
+
+    let path = preset_path(&data.name);
+    let json = serde_json::to_string_pretty(data).map_err(|e| format!("serialize: {e}"))?;
     std::fs::write(&path, json).map_err(|e| format!("write {}: {e}", path.display()))?;
     Ok(path)
 }
 
+/// Where the preset `name` is, or would be, saved.
+pub(crate) fn preset_path(name: &str) -> PathBuf {
+    presets_dir().join(format!("{}.json", sanitize(name)))
+}
+
 /// Read `presets/<name>.json` into a [`PresetData`].
 pub fn load(name: &str) -> Result<PresetData, String> {
-    let path = presets_dir().join(format!("{}.json", sanitize(name)));
+    let path = preset_path(name);
     let text =
         std::fs::read_to_string(&path).map_err(|e| format!("read {}: {e}", path.display()))?;
     let data: PresetData =
@@ -0,0 +1,77 @@
+//! The `trigger_envelope` / `trigger_lfo` debug tools: fire a modulator
+//! without a note (see `dsp::probe`), wait for its trace and return the shape,
+//! so modulation can be tuned by looking at it rather than by ear. The trace
+//! also shows in the modulation card's scope.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::dsp::probe::ProbeTarget;
+use crate::Diagnostics;
+
+/// How long the audio thread gets to pick up the request.
+const START_TIMEOUT: Duration = Duration::from_millis(500);
+/// Longest wait for a trace to finish; longer ones are returned as far as
+/// they got.
+const FINISH_TIMEOUT: Duration = Duration::from_secs(5);
+/// Points in the returned shape; the scope draws the full trace.
+const RETURNED_POINTS: usize = 32;
+
+/// `"amp"` / `"filter"` -> the envelope's probe target.
+pub fn parse_envelope(name: &str) -> Result<ProbeTarget, String> {
+    match name.to_ascii_lowercase().as_str() {
+        "amp" | "amplitude" => Ok(ProbeTarget::AmpEnvelope),
+        "filter" => Ok(ProbeTarget::FilterEnvelope),
+        other => Err(format!(
+            "unknown envelope '{other}' (expected amp or filter)"
+        )),
+    }
+}
+
+/// Fire `target` and return its traced shape.
+pub fn trigger(diagnostics: &Diagnostics, target: ProbeTarget) -> Result<Value, String> {
+    let seen = diagnostics.traces();
+    diagnostics.request_probe(target);
+
+    let deadline = Instant::now() + START_TIMEOUT;
+    while diagnostics.traces() == seen {
+        if Instant::now() > deadline {
+            return Err("the audio engine isn't running, so nothing was triggered; \
+                        ask the user to start the host's audio"
+                .to_string());
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let deadline = Instant::now() + FINISH_TIMEOUT;
+    let trace = loop {
+        let trace = diagnostics.trace().ok_or("no trace was recorded")?;
+        if trace.done || Instant::now() > deadline {
+            break trace;
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+
+    let len = trace.values.len();
+    let step = len.div_ceil(RETURNED_POINTS).max(1);
+    let shape: Vec<f32> = trace
+        .values
+        .iter()
+        .step_by(step)
+        .map(|v| (v * 1000.0).round() / 1000.0)
+        .collect();
+    let peak = trace
+        .values
+        .iter()
+        .fold(0.0f32, |peak, v| peak.max(v.abs()));
+    Ok(json!({
+        "target": target.id(),
+        "complete": trace.done,
+        "duration_s": len as f32 * trace.spacing,
+        "seconds_per_point": step as f32 * trace.spacing,
+        "peak": peak,
+        "shape": shape,
+    }))
+}
@@ -0,0 +1,150 @@
+//! The `compare_to_reference` tool: renders the current patch for the notes a
+//! reference recording plays and says how close it comes (see
+//! [`crate::dsp::similarity`]), so the assistant can work toward "make it
+//! sound like this" one change at a time.
+//!
+//! References are WAV files in `references/` next to the presets: PCM at 8,
+//! 16, 24 or 32 bits, or 32/64-bit float, any channel count (mixed to mono).
+//! The render runs offline at the file's sample rate with the live params; it
+//! doesn't sound.
+
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+
+use super::preset::{app_dir, sanitize};
+use crate::dsp::similarity;
+use crate::render::{self, PatchValues, RenderNote, RenderSpec};
+use crate::SineParams;
+
+/// Longest reference compared; longer files are cut.
+pub const MAX_SECONDS: f32 = 10.0;
+
+pub fn references_dir() -> PathBuf {
+    app_dir().join("references")
+}
+
+/// A WAV file's audio, mixed to mono.
+pub struct Reference {
+    pub samples: Vec<f32>,
+    pub sample_rate: f32,
+}
+
+impl Reference {
+    pub fn seconds(&self) -> f32 {
+        self.samples.len() as f32 / self.sample_rate
+    }
+}
+
+/// Read `references/<name>.wav`.
+pub fn load(name: &str) -> Result<Reference, String> {
+    let stem = name.trim().trim_end_matches(".wav");
+    let path = references_dir().join(format!("{}.wav", sanitize(stem)));
+    let bytes = std::fs::read(&path).map_err(|e| format!("read {}: {e}", path.display()))?;
+    parse_wav(&bytes).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// Decode a RIFF/WAVE file.
+fn parse_wav(bytes: &[u8]) -> Result<Reference, String> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("not a WAV file".to_string());
+    }
+    let u16_at = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+    let u32_at =
+        |at: usize| u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+
+    let mut format = None;
+    let mut data = None;
+    let mut at = 12;
+    while at + 8 <= bytes.len() {
+        let id = &bytes[at..at + 4];
+        let len = u32_at(at + 4) as usize;
+        let body = at + 8;
+        let end = body.saturating_add(len).min(bytes.len());
+        match id {
+            b"fmt " if end - body >= 16 => {
+                let mut tag = u16_at(body);
+                // WAVE_FORMAT_EXTENSIBLE keeps the real tag in its sub-format.
+                if tag == 0xFFFE && end - body >= 26 {
+                    tag = u16_at(body + 24);
+                }
+                format = Some((tag, u16_at(body + 2), u32_at(body + 4), u16_at(body + 14)));
+            }
+            b"data" => data = Some(&bytes[body..end]),
+            _ => {}
+        }
+        // Chunks are padded to an even length.
+        at = body.saturating_add(len + (len & 1));
+    }
+    let (tag, channels, sample_rate, bits) = format.ok_or("no fmt chunk")?;
+    let data = data.ok_or("no data chunk")?;
+    if channels == 0 || sample_rate == 0 {
+        return Err("no channels or no sample rate".to_string());
+    }
+
+    let decode: fn(&[u8]) -> f32 = match (tag, bits) {
+        (1, 8) => |b| (f32::from(b[0]) - 128.0) / 128.0,
+        (1, 16) => |b| f32::from(i16::from_le_bytes([b[0], b[1]])) / 32_768.0,
+        (1, 24) => |b| (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8_388_608.0,
+        (1, 32) => |b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0,
+        (3, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        (3, 64) => |b| f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f32,
+        _ => return Err(format!("unsupported WAV format {tag} at {bits} bits")),
+    };
+    let width = usize::from(bits / 8);
+    let frame = width * usize::from(channels);
+    let max_frames = (MAX_SECONDS * sample_rate as f32) as usize;
+    let samples: Vec<f32> = data
+        .chunks_exact(frame)
+        .take(max_frames)
+        .map(|frame| {
+            let sum: f32 = frame.chunks_exact(width).map(decode).sum();
+            sum / f32::from(channels)
+        })
+        .collect();
+    if samples.is_empty() {
+        return Err("no audio".to_string());
+    }
+    Ok(Reference {
+        samples,
+        sample_rate: sample_rate as f32,
+    })
+}
+
+/// Render `notes` held together for `hold` seconds, over the reference's
+/// length, and compare that with it.
+pub fn compare(
+    params: &SineParams,
+    reference: &Reference,
+    notes: &[u8],
+    velocity: f32,
+    hold: f32,
+    bpm: f32,
+) -> Value {
+    let spec = RenderSpec {
+        notes: notes
+            .iter()
+            .map(|&note| RenderNote {
+                note,
+                velocity,
+                start: 0.0,
+                length: hold,
+            })
+            .collect(),
+        seconds: reference.seconds(),
+        sample_rate: reference.sample_rate,
+        bpm,
+    };
+    let rendered: Vec<f32> = render::render(params, &PatchValues::new(), &spec)
+        .iter()
+        .map(|[left, right]| 0.5 * (left + right))
+        .collect();
+    let similarity = similarity::compare(&reference.samples, &rendered, reference.sample_rate);
+    json!({
+        "seconds": reference.seconds(),
+        "sample_rate": reference.sample_rate,
+        "spectral_distance_db": similarity.spectral_distance_db,
+        "envelope_correlation": similarity.envelope_correlation,
+        "level_difference_db": similarity.level_difference_db,
+    })
+}
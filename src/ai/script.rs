@@ -0,0 +1,225 @@
+//! `.toscript` files: chat slash-commands with times, for scripting patch
+//! changes and performance gestures and keeping them under source control.
+//!
+//! A script lives in `scripts/<name>.toscript` next to the presets. Each line
+//! is a time and a command in the chat's grammar (see [`super::commands`]);
+//! blank lines and `#` comments are skipped:
+//!
+//! ```text
+//! # Open the filter over the first two bars, then a chord.
+//! 0s      /preset load Glass Pad
+//! 0.5s    /set filter_cutoff 800
+//! bar2    /set filter_cutoff 2400
+//! bar3.5  /play C4 E4 G4 1s
+//! ```
+//!
+//! `1.5s` is seconds from the start, `bar3.5` halfway through bar 3 (bars are
+//! 1-based and 4/4). Run free, a script starts when it's loaded and its bars
+//! go by at the host tempo; synced, each command fires when the host's
+//! playback reaches it (seconds counting from the top of the song), waits
+//! while the transport is stopped and plays again when the host loops back.
+
+use std::path::PathBuf;
+use std::time::Instant;
+
+use super::commands::{self, Command};
+use super::preset::{app_dir, sanitize};
+use crate::Diagnostics;
+
+/// Script bars are 4/4, like `ramp_parameter`'s.
+const BEATS_PER_BAR: f64 = 4.0;
+/// How far behind the playhead a synced cue may be and still fire when the
+/// script joins or the host loops back, in beats: enough for a timer tick.
+const CATCH_UP_BEATS: f64 = 0.25;
+
+pub fn scripts_dir() -> PathBuf {
+    app_dir().join("scripts")
+}
+
+/// When a cue fires.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CueTime {
+    Seconds(f64),
+    /// 1-based, fractional.
+    Bar(f64),
+}
+
+impl CueTime {
+    fn parse(token: &str) -> Option<Self> {
+        if let Some(bar) = token.strip_prefix("bar") {
+            let bar: f64 = bar.parse().ok()?;
+            (bar >= 1.0).then_some(Self::Bar(bar))
+        } else {
+            let seconds: f64 = token.strip_suffix('s')?.parse().ok()?;
+            (seconds >= 0.0).then_some(Self::Seconds(seconds))
+        }
+    }
+
+    fn beats(self, bpm: f64) -> f64 {
+        match self {
+            Self::Seconds(seconds) => seconds * bpm / 60.0,
+            Self::Bar(bar) => (bar - 1.0) * BEATS_PER_BAR,
+        }
+    }
+}
+
+/// One line of a script.
+#[derive(Clone, Debug)]
+pub struct Cue {
+    at: CueTime,
+    /// 1-based line number in the file.
+    pub line: usize,
+    /// The command as written.
+    pub text: String,
+    pub command: Command,
+}
+
+#[derive(Clone, Debug)]
+pub struct Script {
+    pub name: String,
+    /// In file order: which of a seconds cue and a bar cue comes first
+    /// depends on the tempo.
+    cues: Vec<Cue>,
+}
+
+impl Script {
+    /// Parse a script's text. Every bad line is reported, with its number.
+    pub fn parse(name: &str, text: &str) -> Result<Self, String> {
+        let mut cues = Vec::new();
+        let mut errors = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line_no = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match parse_cue(line) {
+                Ok((at, text, command)) => cues.push(Cue {
+                    at,
+                    line: line_no,
+                    text,
+                    command,
+                }),
+                Err(e) => errors.push(format!("line {line_no}: {e}")),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors.join("\n"));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            cues,
+        })
+    }
+
+    /// Number of commands.
+    pub fn len(&self) -> usize {
+        self.cues.len()
+    }
+}
+
+fn parse_cue(line: &str) -> Result<(CueTime, String, Command), String> {
+    let (time, text) = line
+        .split_once(char::is_whitespace)
+        .ok_or("expected a time and a command, e.g. 1.5s /set attack 0.2")?;
+    let at = CueTime::parse(time)
+        .ok_or_else(|| format!("'{time}' isn't a time: use seconds (1.5s) or a bar (bar3.5)"))?;
+    let text = text.trim().to_string();
+    let command = commands::parse(&text).ok_or("expected a /command after the time")??;
+    if matches!(
+        command,
+        Command::Import(_) | Command::Replay { .. } | Command::Script(_)
+    ) {
+        return Err(format!("'{text}' can't run from a script"));
+    }
+    Ok((at, text, command))
+}
+
+/// Read `scripts/<name>.toscript`.
+pub fn load(name: &str) -> Result<Script, String> {
+    let path = scripts_dir().join(format!("{}.toscript", sanitize(name)));
+    let text =
+        std::fs::read_to_string(&path).map_err(|e| format!("read {}: {e}", path.display()))?;
+    Script::parse(name, &text)
+}
+
+/// Names (file stems) of all saved scripts.
+pub fn list() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(scripts_dir()) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.extension().and_then(|x| x.to_str()) == Some("toscript"))
+        .filter_map(|path| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// A script being played.
+pub struct ScriptRun {
+    script: Script,
+    synced: bool,
+    /// Per cue, whether it has fired.
+    fired: Vec<bool>,
+    started: Instant,
+    /// Song position at the last tick, to notice the host looping back.
+    last_beats: Option<f64>,
+}
+
+impl ScriptRun {
+    pub fn new(script: Script, synced: bool) -> Self {
+        Self {
+            fired: vec![false; script.cues.len()],
+            script,
+            synced,
+            started: Instant::now(),
+            last_beats: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.script.name
+    }
+
+    /// Whether every cue has fired. A synced script never finishes on its
+    /// own: the host may loop back.
+    pub fn is_finished(&self) -> bool {
+        !self.synced && self.fired.iter().all(|fired| *fired)
+    }
+
+    /// The cues that have come due since the last tick, soonest first. Call
+    /// on a timer.
+    pub fn take_due(&mut self, diagnostics: &Diagnostics) -> Vec<Cue> {
+        let bpm = f64::from(diagnostics.tempo().max(1.0));
+        let now = if self.synced {
+            let Some(beats) = diagnostics.song_position() else {
+                return Vec::new();
+            };
+            // Joining a song that's already playing, or after the host jumped
+            // back: what's behind the playhead has (or hasn't) happened yet.
+            if self.last_beats.is_none_or(|last| beats < last) {
+                for (fired, cue) in self.fired.iter_mut().zip(&self.script.cues) {
+                    *fired = cue.at.beats(bpm) < beats - CATCH_UP_BEATS;
+                }
+            }
+            self.last_beats = Some(beats);
+            beats
+        } else {
+            self.started.elapsed().as_secs_f64() * bpm / 60.0
+        };
+
+        let mut due: Vec<&Cue> = Vec::new();
+        for (fired, cue) in self.fired.iter_mut().zip(&self.script.cues) {
+            if !*fired && cue.at.beats(bpm) <= now {
+                *fired = true;
+                due.push(cue);
+            }
+        }
+        // Stable, so cues at the same time keep the file's order.
+        due.sort_by(|a, b| a.at.beats(bpm).total_cmp(&b.at.beats(bpm)));
+        due.into_iter().cloned().collect()
+    }
+}
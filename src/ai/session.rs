@@ -0,0 +1,35 @@
+//! The assistant's per-project session, saved in the plugin state (see
+//! `ProjectState::ai_session`) so a reopened project brings back the same
+//! assistant setup without reconfiguring it.
+//!
+//! Only the settings that shape what the assistant may do to *this* patch are
+//! kept here; the API key stays in the per-user `config.json` (see
+//! `AiConfig` in `config.rs`) so it never ends up in shared project files.
+//! Fields a project hasn't saved yet are `None` and fall back to that config.
+
+use serde::{Deserialize, Serialize};
+use vizia_plug::vizia::prelude::*;
+
+use super::conflicts::ConflictPolicy;
+
+/// The Gemini model the assistant talks to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Data)]
+pub enum AiModel {
+    Gemini25Flash,
+    Gemini25Pro,
+    Gemini20Flash,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AiSession {
+    #[serde(default)]
+    pub model: Option<AiModel>,
+    #[serde(default)]
+    pub read_only: Option<bool>,
+    #[serde(default)]
+    pub conflict_policy: Option<ConflictPolicy>,
+    /// API name of the model that last worked on this project, shown in the
+    /// settings panel.
+    #[serde(default)]
+    pub last_client: Option<String>,
+}
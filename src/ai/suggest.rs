@@ -0,0 +1,321 @@
+//! The `suggest_parameters` tool: turns a goal ("punchier", "darker",
+//! "wider", ...) into a concrete parameter diff for the current patch by fixed
+//! rules, not by the model, so the same patch and goal always get the same
+//! answer and the logic works without an LLM. Nothing is applied; the diff is
+//! in `set_parameter` names and values, for the model to apply or show.
+//!
+//! Darker and brighter also read the output's measured brightness (see
+//! `dsp::analysis`) when something is sounding: a low-pass cutoff far above
+//! the sound is moved from where it would start to bite, not from where it is.
+
+use nih_plug::prelude::{FloatParam, IntParam, Param};
+use serde_json::{json, Value};
+
+use crate::{Diagnostics, FilterMode, SineParams};
+
+/// Quieter than this, the brightness reading is ignored.
+const SILENCE_RMS: f32 = 1e-3;
+/// A low-pass cutoff more than this many times the measured brightness is
+/// treated as open.
+const OPEN_CUTOFF_RATIO: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Goal {
+    Punchier,
+    Softer,
+    Darker,
+    Brighter,
+    Wider,
+    Narrower,
+    Warmer,
+    Fatter,
+    Longer,
+    Shorter,
+}
+
+impl Goal {
+    pub const ALL: [Goal; 10] = [
+        Goal::Punchier,
+        Goal::Softer,
+        Goal::Darker,
+        Goal::Brighter,
+        Goal::Wider,
+        Goal::Narrower,
+        Goal::Warmer,
+        Goal::Fatter,
+        Goal::Longer,
+        Goal::Shorter,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Goal::Punchier => "punchier",
+            Goal::Softer => "softer",
+            Goal::Darker => "darker",
+            Goal::Brighter => "brighter",
+            Goal::Wider => "wider",
+            Goal::Narrower => "narrower",
+            Goal::Warmer => "warmer",
+            Goal::Fatter => "fatter",
+            Goal::Longer => "longer",
+            Goal::Shorter => "shorter",
+        }
+    }
+
+    /// A goal by its id or a common synonym ("snappier", "duller", "thicker").
+    pub fn parse(s: &str) -> Result<Goal, String> {
+        let s = s.trim().to_lowercase();
+        let goal = match s.strip_prefix("more ").unwrap_or(&s) {
+            "punchier" | "punchy" | "punch" | "snappier" | "tighter" => Goal::Punchier,
+            "softer" | "soft" | "smoother" | "gentler" => Goal::Softer,
+            "darker" | "dark" | "duller" | "mellower" => Goal::Darker,
+            "brighter" | "bright" | "crisper" | "airier" => Goal::Brighter,
+            "wider" | "wide" | "bigger" | "stereo" => Goal::Wider,
+            "narrower" | "narrow" | "mono" | "focused" => Goal::Narrower,
+            "warmer" | "warm" | "analog" | "vintage" => Goal::Warmer,
+            "fatter" | "fat" | "thicker" | "richer" => Goal::Fatter,
+            "longer" | "long" | "sustained" => Goal::Longer,
+            "shorter" | "short" | "plucky" | "plucked" => Goal::Shorter,
+            _ => {
+                let ids: Vec<&str> = Goal::ALL.iter().map(|goal| goal.id()).collect();
+                return Err(format!("unknown goal '{s}' (expected {})", ids.join("/")));
+            }
+        };
+        Ok(goal)
+    }
+}
+
+/// One proposed write, in `set_parameter` terms.
+struct Change {
+    parameter: String,
+    from: Value,
+    to: Value,
+    reason: &'static str,
+}
+
+/// The proposed writes, each clamped to its param's range; moves that would
+/// change nothing are dropped.
+#[derive(Default)]
+struct Diff(Vec<Change>);
+
+impl Diff {
+    fn float(&mut self, name: impl Into<String>, param: &FloatParam, to: f32, why: &'static str) {
+        let from = param.value();
+        let to = param.preview_plain(param.preview_normalized(to));
+        if (to - from).abs() > 1e-4 * from.abs().max(1.0) {
+            self.push(name.into(), json!(round(from)), json!(round(to)), why);
+        }
+    }
+
+    fn int(&mut self, name: impl Into<String>, param: &IntParam, to: i32, why: &'static str) {
+        let from = param.value();
+        let to = param.preview_plain(param.preview_normalized(to));
+        if to != from {
+            self.push(name.into(), json!(from), json!(to), why);
+        }
+    }
+
+    fn push(&mut self, parameter: String, from: Value, to: Value, reason: &'static str) {
+        self.0.push(Change {
+            parameter,
+            from,
+            to,
+            reason,
+        });
+    }
+}
+
+/// Four significant-ish decimals, so the diff reads cleanly.
+fn round(value: f32) -> f32 {
+    (value * 10_000.0).round() / 10_000.0
+}
+
+/// Propose a diff moving the current patch towards `goal`. `strength` (0..1)
+/// scales every move; 0.5 is a clear but moderate step.
+pub fn suggest(p: &SineParams, diagnostics: &Diagnostics, goal: Goal, strength: f32) -> Value {
+    let s = strength.clamp(0.0, 1.0);
+    let reading = diagnostics.brightness();
+    let measured = (reading.rms > SILENCE_RMS).then_some(reading.hz);
+    let mut diff = Diff::default();
+    let adsr = &p.adsr;
+    let filter = &p.filter;
+    let oscs = [(1, &p.osc1), (2, &p.osc2), (3, &p.osc3)];
+    let sounding = oscs.iter().filter(|(_, osc)| !osc.mute.value());
+
+    match goal {
+        Goal::Punchier => {
+            if !adsr.attack_sync.value() {
+                let attack = adsr.attack.value() * (1.0 - 0.8 * s);
+                let why = "a faster attack hits harder";
+                diff.float("attack", &adsr.attack, attack, why);
+            }
+            if !adsr.decay_sync.value() {
+                let decay = adsr.decay.value() * (1.0 - 0.5 * s);
+                let why = "a shorter decay tightens the hit";
+                diff.float("decay", &adsr.decay, decay, why);
+            }
+            let sustain = adsr.sustain.value() * (1.0 - 0.5 * s);
+            let why = "a lower sustain lets the hit stand out";
+            diff.float("sustain", &adsr.sustain, sustain, why);
+            let amount = filter.env_amount.value().max(0.0) + 2.0 * s;
+            let why = "a filter snap per note";
+            diff.float("filter_env_amount", &filter.env_amount, amount, why);
+            if !p.filter_env.decay_sync.value() {
+                let decay = p.filter_env.decay.value().min(0.1 + 0.3 * (1.0 - s));
+                let why = "a quick filter envelope keeps the snap short";
+                diff.float("filter_decay", &p.filter_env.decay, decay, why);
+            }
+            let drive = filter.drive.value() + s;
+            let why = "a little drive adds bite";
+            diff.float("filter_drive", &filter.drive, drive, why);
+        }
+        Goal::Softer => {
+            if !adsr.attack_sync.value() {
+                let attack = adsr.attack.value().max(0.01) * (1.0 + 4.0 * s);
+                let why = "a slower attack rounds off the start";
+                diff.float("attack", &adsr.attack, attack, why);
+            }
+            let amount = filter.env_amount.value() * (1.0 - 0.6 * s);
+            let why = "less filter snap";
+            diff.float("filter_env_amount", &filter.env_amount, amount, why);
+            let drive = 1.0 + (filter.drive.value() - 1.0) * (1.0 - s);
+            let why = "less drive, less edge";
+            diff.float("filter_drive", &filter.drive, drive, why);
+            let resonance = filter.resonance.value() * (1.0 - 0.5 * s);
+            let why = "a softer filter peak";
+            diff.float("filter_resonance", &filter.resonance, resonance, why);
+        }
+        Goal::Darker | Goal::Brighter => {
+            let cutoff = filter.cutoff.value();
+            let open = filter.mode.value() == FilterMode::LowPass
+                && measured.is_some_and(|hz| cutoff > hz * OPEN_CUTOFF_RATIO);
+            if goal == Goal::Darker {
+                let (from, why) = match measured.filter(|_| open) {
+                    Some(hz) => (
+                        hz * OPEN_CUTOFF_RATIO,
+                        "the filter was open above the sound, so this starts where it bites",
+                    ),
+                    None => (cutoff, "a lower cutoff takes off the top end"),
+                };
+                let darker = from * 2f32.powf(-2.0 * s);
+                diff.float("filter_cutoff", &filter.cutoff, darker, why);
+                let resonance = filter.resonance.value() * (1.0 - 0.4 * s);
+                let why = "a tamer peak";
+                diff.float("filter_resonance", &filter.resonance, resonance, why);
+                let amount = filter.env_amount.value() * (1.0 - 0.5 * s);
+                let why = "the envelope opens the filter less";
+                diff.float("filter_env_amount", &filter.env_amount, amount, why);
+            } else if open || cutoff >= 19_000.0 {
+                // Opening the filter further changes nothing; add harmonics.
+                let drive = filter.drive.value() + 1.5 * s;
+                let why = "the filter is already open, so drive adds upper harmonics";
+                diff.float("filter_drive", &filter.drive, drive, why);
+            } else {
+                let why = "a higher cutoff lets the top end through";
+                let brighter = cutoff * 2f32.powf(2.0 * s);
+                diff.float("filter_cutoff", &filter.cutoff, brighter, why);
+                let amount = filter.env_amount.value() + s;
+                let why = "the envelope opens the filter further";
+                diff.float("filter_env_amount", &filter.env_amount, amount, why);
+            }
+        }
+        Goal::Wider => {
+            let width = p.stereo_width.value() + 0.8 * s;
+            let why = "more side signal";
+            diff.float("stereo_width", &p.stereo_width, width, why);
+            for (n, osc) in sounding {
+                let voices = osc.unison_voices.value().max(1 + (4.0 * s).round() as i32);
+                let why = "unison voices spread across the stereo field";
+                diff.int(format!("unison_voices{n}"), &osc.unison_voices, voices, why);
+                let detune = osc.unison_detune.value().max(10.0 + 20.0 * s);
+                let why = "detuned unison voices decorrelate left and right";
+                diff.float(format!("unison_detune{n}"), &osc.unison_detune, detune, why);
+            }
+            let pan = p.keyboard_pan.value() + 0.4 * s;
+            let why = "notes spread by pitch";
+            diff.float("keyboard_pan", &p.keyboard_pan, pan, why);
+        }
+        Goal::Narrower => {
+            let width = p.stereo_width.value() * (1.0 - 0.7 * s);
+            let why = "less side signal";
+            diff.float("stereo_width", &p.stereo_width, width, why);
+            for (n, osc) in sounding.filter(|(_, osc)| osc.unison_voices.value() > 1) {
+                let detune = osc.unison_detune.value() * (1.0 - 0.5 * s);
+                let why = "tighter unison spreads less";
+                diff.float(format!("unison_detune{n}"), &osc.unison_detune, detune, why);
+            }
+            let pan = p.keyboard_pan.value() * (1.0 - s);
+            let why = "notes stay centred";
+            diff.float("keyboard_pan", &p.keyboard_pan, pan, why);
+        }
+        Goal::Warmer => {
+            if filter.mode.value() == FilterMode::LowPass {
+                let cutoff = filter.cutoff.value() * 2f32.powf(-0.8 * s);
+                let why = "a gentler top end";
+                diff.float("filter_cutoff", &filter.cutoff, cutoff, why);
+            }
+            let drive = filter.drive.value() + s;
+            let why = "soft saturation";
+            diff.float("filter_drive", &filter.drive, drive, why);
+            let analog = p.analog.value() + 0.4 * s;
+            let why = "a little analog pitch drift";
+            diff.float("analog", &p.analog, analog, why);
+        }
+        Goal::Fatter => {
+            for (n, osc) in sounding {
+                let voices = osc.unison_voices.value() + (4.0 * s).round() as i32;
+                let why = "more unison voices thicken the sound";
+                diff.int(format!("unison_voices{n}"), &osc.unison_voices, voices, why);
+                let detune = osc.unison_detune.value().max(8.0 + 12.0 * s);
+                let why = "detuned voices beat against each other";
+                diff.float(format!("unison_detune{n}"), &osc.unison_detune, detune, why);
+            }
+            let drive = filter.drive.value() + 0.5 * s;
+            let why = "drive fills in harmonics";
+            diff.float("filter_drive", &filter.drive, drive, why);
+        }
+        Goal::Longer | Goal::Shorter => {
+            let (scale, release_why, decay_why) = if goal == Goal::Longer {
+                (
+                    1.0 + 3.0 * s,
+                    "notes ring on after the key",
+                    "the body of the note lasts",
+                )
+            } else {
+                (
+                    1.0 - 0.7 * s,
+                    "notes stop soon after the key",
+                    "the note dies away sooner",
+                )
+            };
+            if !adsr.release_sync.value() {
+                let release = adsr.release.value() * scale;
+                diff.float("release", &adsr.release, release, release_why);
+            }
+            if !adsr.decay_sync.value() {
+                let decay = adsr.decay.value() * scale.sqrt();
+                diff.float("decay", &adsr.decay, decay, decay_why);
+            }
+        }
+    }
+
+    let changes: Vec<Value> = diff
+        .0
+        .into_iter()
+        .map(|change| {
+            json!({
+                "parameter": change.parameter,
+                "from": change.from,
+                "to": change.to,
+                "reason": change.reason,
+            })
+        })
+        .collect();
+    json!({
+        "goal": goal.id(),
+        "strength": s,
+        "measured_brightness_hz": measured,
+        "changes": changes,
+        "applied": false,
+    })
+}
@@ -0,0 +1,206 @@
+//! What only the assistant's tools need of the parameter bridge (see
+//! `bridge.rs`): the `get_state` snapshot, and resolving the arguments that
+//! aren't plain parameter writes (ramp targets and values, XY axes, lock
+//! groups, oscillator and pad numbers, preset categories).
+
+use nih_plug::prelude::{Param, Params};
+use serde_json::{json, Value};
+use vizia_plug::widgets::RawParamEvent;
+
+use super::bridge::{apply_write, as_f32, as_gain};
+use super::preset::{self, PatchMetadata, PresetData};
+use crate::locks::{self, LockGroup};
+use crate::persist::XyAxis;
+use crate::ramps::RampTarget;
+use crate::xy::{self, Axis, NUM_XY_PADS};
+use crate::{Scale, ScaleRoot, SineParams, Smoothing};
+
+pub fn smoothing_to_id(s: Smoothing) -> &'static str {
+    match s {
+        Smoothing::Fast => "fast",
+        Smoothing::Medium => "medium",
+        Smoothing::Slow => "slow",
+    }
+}
+
+pub fn scale_to_id(s: Scale) -> &'static str {
+    match s {
+        Scale::Chromatic => "chromatic",
+        Scale::Major => "major",
+        Scale::Minor => "minor",
+        Scale::HarmonicMinor => "harmonic_minor",
+        Scale::MelodicMinor => "melodic_minor",
+        Scale::Dorian => "dorian",
+        Scale::Phrygian => "phrygian",
+        Scale::Lydian => "lydian",
+        Scale::Mixolydian => "mixolydian",
+        Scale::Locrian => "locrian",
+        Scale::MajorPentatonic => "major_pentatonic",
+        Scale::MinorPentatonic => "minor_pentatonic",
+        Scale::Blues => "blues",
+    }
+}
+
+/// The key's name, sharps for black keys ("C", "F#").
+pub fn scale_root_name(root: ScaleRoot) -> &'static str {
+    const NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+    NAMES[usize::from(root.semitone())]
+}
+
+/// Parse a `ramp_parameter` target value for `target`, clamped into the
+/// param's range. Gains take the same forms as in `set_parameter`.
+pub fn ramp_value(p: &SineParams, target: RampTarget, value: &Value) -> Result<f32, String> {
+    let plain = match target {
+        RampTarget::Gain1 | RampTarget::Gain2 | RampTarget::Gain3 => as_gain(value)?,
+        _ => as_f32(value)?,
+    };
+    let param = target.param(p);
+    Ok(param.preview_plain(param.preview_normalized(plain)))
+}
+
+/// Resolve an `assign_xy` target: the param `name` writes, by its host ID,
+/// swept from `min` to `max` (values as in `set_parameter`), as offsets from
+/// where it is now.
+pub fn xy_assignment(
+    p: &SineParams,
+    name: &str,
+    min: &Value,
+    max: &Value,
+) -> Result<XyAxis, String> {
+    let endpoint = |value: &Value| {
+        let mut written = Vec::new();
+        apply_write(p, name, value, &mut |event| {
+            if let RawParamEvent::SetParameterNormalized(ptr, normalized) = event {
+                written.push((ptr, normalized));
+            }
+        })?;
+        match written.as_slice() {
+            [one] => Ok(*one),
+            _ => Err(format!("'{name}' isn't a single parameter, so an XY axis can't drive it")),
+        }
+    };
+    let (ptr, min) = endpoint(min)?;
+    let (_, max) = endpoint(max)?;
+    // SAFETY: `apply_write` only writes params inside `p`.
+    let current = unsafe { ptr.unmodulated_normalized_value() };
+    let (min, max) = (min - current, max - current);
+    let (param, _, _) = p
+        .param_map()
+        .into_iter()
+        .find(|(_, mapped, _)| *mapped == ptr)
+        .ok_or_else(|| format!("'{name}' can't be driven by an XY axis"))?;
+    Ok(XyAxis { param, min, max })
+}
+
+/// Each XY pad's position and what its axes drive, for `get_state`.
+fn xy_state(p: &SineParams) -> Value {
+    let assigned = xy::assignments(p);
+    let pads: Vec<Value> = (0..NUM_XY_PADS)
+        .map(|i| {
+            let mut pad = json!({ "pad": i + 1 });
+            for axis in Axis::ALL {
+                let drives = axis.assignment(&assigned[i]).and_then(|assignment| {
+                    let ptr = xy::target(p, &assignment.param).ok()?;
+                    let (from, to) = xy::endpoints(ptr, assignment);
+                    // SAFETY: `xy::target` only returns params inside `p`.
+                    let (name, from, to) = unsafe {
+                        (
+                            ptr.name(),
+                            ptr.normalized_value_to_string(from, true),
+                            ptr.normalized_value_to_string(to, true),
+                        )
+                    };
+                    Some(json!({
+                        "parameter": name,
+                        "id": assignment.param,
+                        "from": from,
+                        "to": to
+                    }))
+                });
+                pad[axis.id()] = json!(axis.param(xy::pad(p, i)).value());
+                pad[format!("{}_drives", axis.id())] = json!(drives);
+            }
+            pad
+        })
+        .collect();
+    json!(pads)
+}
+
+/// Snapshot the live parameter values into the JSON shape the AI sees from the
+/// `get_state` tool (the same shape as a preset file's parameter block, plus
+/// the master tuning, scale, smoothing, input mix, arp routing and seed, solo
+/// states and XY pads).
+pub fn read_state(p: &SineParams) -> Value {
+    let mut state = serde_json::to_value(PresetData::capture(p)).unwrap_or_else(|_| Value::Null);
+    // Master tuning, scale, smoothing, auto quality, program change, latch,
+    // input mix, arp routing
+    // and seed, solo and the lock groups belong to the session rather than
+    // the sound, so presets don't store them, but the AI still needs to see
+    // them.
+    if let Value::Object(map) = &mut state {
+        map.insert("input_mix".into(), json!(p.input_mix.value()));
+        map.insert("smoothing".into(), json!(smoothing_to_id(p.smoothing.value())));
+        map.insert("auto_quality".into(), json!(p.auto_quality.value()));
+        map.insert("band_limited".into(), json!(p.band_limited.value()));
+        map.insert("program_change".into(), json!(p.program_change.value()));
+        map.insert("latch".into(), json!(p.latch.value()));
+        map.insert("master_transpose".into(), json!(p.master_transpose.value()));
+        map.insert("master_tune".into(), json!(p.master_tune.value()));
+        let root = scale_root_name(p.scale_root.value());
+        map.insert("scale_root".into(), json!(root));
+        map.insert("scale".into(), json!(scale_to_id(p.scale.value())));
+        map.insert("arp_midi_out".into(), json!(p.arp.midi_out.value()));
+        map.insert("arp_local".into(), json!(p.arp.local.value()));
+        map.insert("arp_seed".into(), json!(p.arp.seed.value()));
+        for (n, osc) in [(1, &p.osc1), (2, &p.osc2), (3, &p.osc3)] {
+            map.insert(format!("solo{n}"), json!(osc.solo.value()));
+        }
+        let locks = locks::current(p);
+        map.insert("locked_groups".into(), json!(locks.ids()));
+        map.insert("xy_pads".into(), xy_state(p));
+    }
+    state
+}
+
+/// The ramp target `ramp_parameter` names, if it can be ramped.
+pub fn ramp_target(name: &str) -> Option<RampTarget> {
+    RampTarget::ALL.into_iter().find(|target| target.name() == name)
+}
+
+/// The lock group `lock_groups` names.
+pub fn lock_group(id: &str) -> Result<LockGroup, String> {
+    LockGroup::ALL.into_iter().find(|group| group.id() == id).ok_or_else(|| {
+        let ids: Vec<&str> = LockGroup::ALL.iter().map(|group| group.id()).collect();
+        format!("unknown lock group '{id}' (expected one of {})", ids.join(", "))
+    })
+}
+
+/// `1`-based oscillator number -> index.
+pub fn parse_oscillator(number: u64) -> Result<usize, String> {
+    match number {
+        1..=3 => Ok(number as usize - 1),
+        _ => Err(format!("unknown oscillator {number} (expected 1, 2 or 3)")),
+    }
+}
+
+/// `1`-based pad number -> index.
+pub fn parse_pad(number: u64) -> Result<usize, String> {
+    match number {
+        1..=2 => Ok(number as usize - 1),
+        _ => Err(format!("unknown XY pad {number} (expected 1 or 2)")),
+    }
+}
+
+/// Every saved preset's name and metadata. Presets that don't load are left
+/// out.
+pub fn catalog() -> Vec<(String, PatchMetadata)> {
+    preset::list()
+        .into_iter()
+        .filter_map(|name| preset::load(&name).ok().map(|data| (name, data.metadata)))
+        .collect()
+}
+
+/// Whether `metadata` is in `category`, ignoring case.
+pub fn in_category(metadata: &PatchMetadata, category: &str) -> bool {
+    metadata.category.trim().eq_ignore_ascii_case(category.trim())
+}
@@ -1,12 +1,83 @@
 //! Tool definitions exposed to the model (as Gemini `functionDeclarations`) and
 //! the in-plugin dispatcher that executes a tool call.
 
-use crate::ai::{bridge, preset};
-use crate::SineParams;
+use crate::ai::conflicts::ConflictPolicy;
+use crate::ai::deferred;
+use crate::ai::suggest::{self, Goal};
+use crate::ai::bank_io::{self, BankConflict};
+use crate::ai::preset::PatchMetadata;
+use crate::ai::plot::{self, Plot, PlotKind};
+use crate::ai::{
+    audition, bridge, brightness, capabilities, matcher, preset, probe, reference, tool_bridge,
+};
+use crate::diagnostics::MAX_NOTE_REQUESTS;
+use crate::dsp::probe::ProbeTarget;
+use crate::dsp::Quantizer;
+use crate::locks::{self, LockGroup};
+use crate::osc_copy::OscSnapshot;
+use crate::persist::ProjectState;
+use crate::ramps::RampDuration;
+use crate::recovery;
+use crate::xy::{self, Axis};
+use crate::{NoteDivision, ScaleRoot, SineParams};
 use serde_json::{json, Value};
 use vizia_plug::vizia::prelude::*;
 
-use super::chat_ui::ChatEvent;
+use super::host::{ToolContext, ToolLog};
+use crate::ui::toast::{ChangeRecorder, ToastEvent};
+
+/// Tools that only observe the synth: the only ones read-only mode allows.
+pub const READ_ONLY_TOOLS: [&str; 15] = [
+    "get_state",
+    "get_capabilities",
+    "list_presets",
+    "get_recovery",
+    "list_scheduled",
+    "get_voice_stats",
+    "get_diagnostics",
+    "get_usage_stats",
+    "get_transport",
+    "trigger_envelope",
+    "attach_plot",
+    "play_notes",
+    "panic",
+    "compare_to_reference",
+    "suggest_parameters",
+];
+
+/// Every tool's name, in [`gemini_tools`] order.
+pub fn tool_names() -> Vec<String> {
+    let tools = gemini_tools();
+    let declarations = tools[0]["functionDeclarations"].as_array().cloned();
+    declarations
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|tool| tool["name"].as_str().map(str::to_string))
+        .collect()
+}
+
+/// What a write tool returns in read-only mode.
+pub fn permission_denied(name: &str) -> Value {
+    json!({
+        "error": format!("'{name}' would modify the synth, but the assistant is in read-only mode"),
+        "code": "permission_denied",
+        "tool": name,
+        "allowed_tools": READ_ONLY_TOOLS
+    })
+}
+
+/// `ramp_parameter` and `morph_to_preset` durations in bars assume 4/4.
+const BEATS_PER_BAR: f32 = 4.0;
+
+/// A `duration` of `length` in `unit`s: seconds, beats or bars.
+fn parse_duration(length: f32, unit: &str) -> Result<RampDuration, String> {
+    match unit {
+        "seconds" => Ok(RampDuration::Seconds(length)),
+        "beats" => Ok(RampDuration::Beats(length)),
+        "bars" => Ok(RampDuration::Beats(length * BEATS_PER_BAR)),
+        _ => Err(format!("unknown unit '{unit}'")),
+    }
+}
 
 /// The tool schema sent to Gemini under `tools: [{ functionDeclarations: [...] }]`.
 pub fn gemini_tools() -> Value {
@@ -23,15 +94,155 @@ pub fn gemini_tools() -> Value {
                     "Set one synth parameter. Call repeatedly to design a sound. Valid names and ranges:\n",
                     "  Oscillators (N = 1, 2, 3): waveformN (sine|square|triangle|sawtooth), ",
                     "frequencyN (20-20000 Hz), detuneN (-100..100 cents), phaseN (0..1), ",
-                    "gainN (linear 0.015..1.0), octaveN (-4..4), unison_voicesN (1..8), ",
-                    "unison_detuneN (0..50 cents), unison_blendN (0..1), unison_volumeN (0..1).\n",
-                    "  Filter: filter_mode (lowpass|highpass|bandpass|notch), filter_cutoff (20-20000 Hz), ",
-                    "filter_resonance (0..1), filter_drive (1..5), filter_env_amount (-8..8 octaves, ",
-                    "how far the filter envelope sweeps the cutoff; 0 = static).\n",
-                    "  Amp envelope: attack/decay (0.001..5 s), sustain (0..1), release (0.001..10 s).\n",
-                    "  Filter envelope: filter_attack/filter_decay (0.001..5 s), filter_sustain (0..1), ",
+                    "gainN (linear 0..1), octaveN (-4..4), unison_voicesN (1..8), ",
+                    "unison_detuneN (0..50 cents), unison_blendN (0..1), unison_volumeN (linear 0..1), ",
+                    "unison_curveN (linear|exponential|supersaw; how the unison voices are spread ",
+                    "across the detune range; supersaw is the classic uneven spacing), ",
+                    "unison_densityN (0..1; above 0.5 bunches the voices toward the center for a ",
+                    "thick core, below pushes them out for width; 0.5 = neutral), ",
+                    "unison_compN (true|false, default false; keeps the level steady as voices ",
+                    "are added or blended in). ",
+                    "Levels (gainN, unison_volumeN) also accept dB strings like '-6 dB'; 0 or '-inf' mutes. ",
+                    "key_trackN (true|false; false = the oscillator ignores the played note and ",
+                    "drones at frequencyN, e.g. for fixed partials). phase_distN (0..1; Casio ",
+                    "CZ-style phase distortion: bends the waveform for brassy, resonant tones, ",
+                    "strongest on sine; 0 = off). wavefoldN (0..1; West-coast wavefolder after ",
+                    "the waveform: folds the peaks back for bright, metallic overtones, richest ",
+                    "on sine and triangle; 0 = off). morphN (true|false; true replaces ",
+                    "waveformN with a continuous sweep) and morph_positionN (0..1: sine at 0, ",
+                    "triangle at 1/3, saw at 2/3, square at 1, crossfaded in between; automate ",
+                    "or modulate it for smooth timbre changes). muteN (true|false) silences an ",
+                    "oscillator without touching its level; soloN (true|false), see solo_oscillator. ",
+                    "to_filterN (true|false, default true; false mixes the oscillator in ",
+                    "after the comb, drive and filter, e.g. to keep a sub clean under a filter ",
+                    "sweep). ",
+                    "Oscillator 1 only: additive1 (true|false; replaces waveform1 with an ",
+                    "organ-style sum of harmonics) and drawbars1 (an array of 8 levels 0..1 for ",
+                    "harmonics 1-8, e.g. [1, 0.5, 0, 0.3, 0, 0, 0, 0.2]).\n",
+                    "  Per-oscillator envelopes: own_envN (true|false) plays oscillator N through ",
+                    "its own AHDSR instead of the amp envelope: env_attackN, env_holdN, env_decayN, ",
+                    "env_sustainN, env_releaseN (same ranges as the amp envelope) and ",
+                    "env_attack_syncN/env_attack_divisionN (likewise decay and release). Layer a ",
+                    "slow-swelling pad oscillator under a plucked one in the same voice.\n",
+                    "  Filter: filter_mode (lowpass|highpass|bandpass|notch|formant; formant is a ",
+                    "vowel filter for vocal pads: it ignores the cutoff and shapes the sound into ",
+                    "filter_vowel, with resonance sharpening the formants), filter_cutoff (20-20000 Hz), ",
+                    "filter_resonance (0..1), filter_drive (1..5; 1 = clean), ",
+                    "filter_drive_character (soft|asymmetric|tube; asymmetric and tube add even ",
+                    "harmonics for warmth), filter_drive_compensation (true|false; on by default, ",
+                    "keeps the level roughly steady as drive rises), filter_env_amount (-8..8 ",
+                    "octaves, how far the filter envelope sweeps the cutoff; 0 = static), ",
+                    "filter_vowel (0..1; morphs A, E, I, O, U at 0, 0.25, 0.5, 0.75, 1).\n",
+                    "  Comb (before the filter, tuned to each note): comb (true|false), ",
+                    "comb_feedback (0..0.99; how long it rings), comb_damping (0..1; 0 = bright ",
+                    "and metallic, higher = darker), comb_mix (0..1). comb_pluck (true|false) ",
+                    "excites it with just the start of each note for Karplus-Strong plucked ",
+                    "strings: try a short-attack noisy saw or square with comb_feedback ~0.98.\n",
+                    "  Noise (mixed with the oscillators before the comb and filter, through the ",
+                    "amp envelope): noise_color (white|pink; pink is darker, like wind or ",
+                    "breath), noise_level (linear 0..1 or dB strings; 0 = off, the default). For ",
+                    "breathy pads, hi-hats and snare-like hits, or a burst of pluck excitation.\n",
+                    "  Sub oscillator (under osc 1's pitch, before the comb and filter, through ",
+                    "the amp envelope): sub_waveform (sine|square), sub_octave (1|2 octaves ",
+                    "down), sub_level (linear 0..1 or dB strings; 0 = off, the default). For ",
+                    "bass weight without giving up an oscillator.\n",
+                    "  Amp envelope (AHDSR): attack/decay (0.001..5 s), hold (0..5 s at full ",
+                    "level between attack and decay; 0 = plain ADSR), sustain (0..1), ",
+                    "release (0.001..10 s).\n",
+                    "  Filter envelope: filter_attack/filter_decay (0.001..5 s), filter_hold ",
+                    "(0..5 s), filter_sustain (0..1), ",
                     "filter_release (0.001..10 s). For a classic filter sweep set a positive ",
-                    "filter_env_amount and a slow filter_attack."
+                    "filter_env_amount and a slow filter_attack.\n",
+                    "  Tempo sync (both envelopes; prefix filter_ for the filter envelope): ",
+                    "attack_sync/decay_sync/release_sync (true|false) make that stage last ",
+                    "attack_division/decay_division/release_division (1/32|1/16|1/8|1/4|1/2|1/1) ",
+                    "at the host tempo instead of its time in seconds.\n",
+                    "  Glide: glide_time (0..5 s, 0 = off), glide_legato (true|false; only glide ",
+                    "between overlapping notes), glide_mode (constant_time|constant_rate; with ",
+                    "constant_rate, glide_time is the time per octave).\n",
+                    "  Pitch bend: bend_range (0..24 semitones at full wheel), bend_smoothing ",
+                    "(0..0.5 s; how quickly the pitch catches up with the wheel, so wide bends ",
+                    "don't step), bend_release (follow|hold|return; whether released notes keep ",
+                    "following the wheel, keep the bend they had, or glide back to unbent).\n",
+                    "  Modulation: lfo_rate (0.01..20 Hz), lfo_shape (sine|square|triangle|sawtooth); ",
+                    "lfo_sync (true|false) runs the LFO at one cycle per lfo_division ",
+                    "(1/32|1/16|1/8|1/4|1/2|1/1) of the host tempo instead of lfo_rate; while ",
+                    "synced, lfo_reset (free|transport|bar) locks its phase to the song position ",
+                    "when playback starts (transport) or also at every bar line (bar), so ",
+                    "renders are repeatable; ",
+                    "sidechain_attack (0.1..100 ms), sidechain_release (10..1000 ms), ",
+                    "release_blip (1..500 ms); ",
+                    "three matrix slots (N = 1, 2, 3) with modN_source ",
+                    "(lfo|filter_env|velocity|sidechain|release; sidechain follows the level of ",
+                    "the track routed into the plugin's sidechain input, 0..1; release is a blip ",
+                    "fired at note-off at the release velocity, dying away over release_blip, ",
+                    "for key-off clicks and organ-style releases), ",
+                    "modN_destination (none|pitch|pulse_width|level|cutoff|resonance|vowel|morph) and ",
+                    "modN_amount ",
+                    "(-1..1; 1 = full range: pitch ±12 semitones, pulse_width ±45%, level ±100%, ",
+                    "cutoff ±4 octaves, resonance ±100%, vowel ±the whole A-U range, morph ±the whole ",
+                    "sine-square range on oscillators with morphN on). E.g. ",
+                    "vibrato: mod1_source=lfo, mod1_destination=pitch, mod1_amount=0.02. Sidechain ducking: ",
+                    "mod1_source=sidechain, mod1_destination=level, mod1_amount=-0.8.\n",
+                    "  Character: analog (0..1; slow per-voice pitch/level drift and per-note ",
+                    "cutoff variation like an unstable hardware synth, 0 = perfectly stable), ",
+                    "keyboard_pan (0..1; pans low notes left and high notes right for wider chords), ",
+                    "color (-1..1; one-knob timbre macro, 0 = neutral: up opens the cutoff, adds ",
+                    "drive and bends the oscillators' shape, down closes the cutoff, and both ways ",
+                    "it slides morphing oscillators toward square or sine; reach for it first for ",
+                    "'brighter/darker/dirtier' requests), color_cutoff (0..6 octaves at full ",
+                    "color), color_drive (0..4 drive at full color), color_shape (0..1 phase ",
+                    "distortion at full color), color_blend (0..1 of the morph position at full ",
+                    "color) set how far color reaches into each stage, ",
+                    "release_velocity (0..1; how much key-lift speed shortens or stretches the ",
+                    "release of both envelopes), declick (true|false; smooths the envelope edges so ",
+                    "1 ms attacks/releases don't click; on by default), smoothing ",
+                    "(fast|medium|slow; how quickly every knob change glides to its new value: ",
+                    "~5 ms, ~17 ms or 50 ms; fast keeps automated filter stabs snappy, slow avoids",
+                    " zipper noise; not stored in presets), auto_quality (true|false; while the ",
+                    "CPU can't keep up, halves every oscillator's unison voices until it can, so ",
+                    "huge chords thin out instead of crackling; off by default, not stored in ",
+                    "presets), band_limited (true|false; takes the aliasing out of the square and ",
+                    "saw so high notes don't sound harsh; off plays them naively, for comparison; ",
+                    "on by default, not stored in presets), program_change (true|false; whether ",
+                    "MIDI program changes load ",
+                    "presets, from the saved presets or, after a bank select, a bank file; on by ",
+                    "default, not stored in presets), latch (true|false; released keys and ",
+                    "play_notes notes keep sounding until the same note plays again or latch is ",
+                    "switched off, for drones; off by default, not stored in presets), input_mix ",
+                    "(0..1; blends the audio ",
+                    "on the plugin's main input, run through the filter and drive, against the ",
+                    "oscillators: 1 turns the synth into a filter effect; needs the host to route ",
+                    "audio in; not stored in presets).\n",
+                    "  Master: stereo_width (0..2; mid/side width of the whole output, 1 = ",
+                    "unchanged, 0 = mono, 2 = 200%), width_crossover (20-1000 Hz; below it the ",
+                    "width is capped at 1 so the bass stays mono-compatible).\n",
+                    "  Trance gate (master bus, locked to the host's bars): gate (true|false), ",
+                    "gate_division (1/32|1/16|1/8|1/4|1/2|1/1; one step's length), gate_pattern ",
+                    "(16 steps as a string, x = open, . = gated, e.g. 'x.xx x.x. x.xx x.x.'), ",
+                    "gate_depth (0..1; 1 = gated steps are silent), gate_smoothing (0..1; edge ",
+                    "fade as a fraction of a step), gate_pan (0..1; auto-pan, alternating steps ",
+                    "swing left and right).\n",
+                    "  Arpeggiator (plays the held keys one at a time): arp (true|false), arp_mode ",
+                    "(up|down|up_down|played|random), arp_division (1/32..1/1; one step's length), ",
+                    "arp_octaves (1..4), arp_gate (0.05..1; note length as a fraction of a step), ",
+                    "arp_swing (0..1; delays every second step, ~0.67 = triplet shuffle), ",
+                    "arp_timing_jitter (0..0.5; random lateness as a fraction of a step), ",
+                    "arp_velocity_jitter (0..1; random velocity change either way), arp_seed ",
+                    "(0..9999; 0 = new random choices each time, else the same ones every ",
+                    "playback), arp_midi_out (true|false; sends the arp's notes to the host as MIDI ",
+                    "to drive other instruments), arp_local (true|false; false silences this synth ",
+                    "so only the MIDI out plays). arp_seed, arp_midi_out and arp_local aren't ",
+                    "stored in presets.\n",
+                    "  Master tuning (not stored in presets): master_transpose (-24..24 semitones), ",
+                    "master_tune (415..465 Hz, the A4 reference; 440 = concert pitch). Applies ",
+                    "from the next note played.\n",
+                    "  Scale quantizer (not stored in presets; see set_scale): scale_root (C..B, ",
+                    "e.g. 'F#' or 'Bb'), scale (chromatic = off|major|minor|harmonic_minor|",
+                    "melodic_minor|dorian|phrygian|lydian|mixolydian|locrian|major_pentatonic|",
+                    "minor_pentatonic|blues).\n",
+                    "If the user is dragging the knob you write, the result is either 'deferred' ",
+                    "(it applies when they let go) or a 'conflict' error; don't retry a conflict."
                 ),
                 "parameters": {
                     "type": "object",
@@ -44,10 +255,19 @@ pub fn gemini_tools() -> Value {
             },
             {
                 "name": "save_preset",
-                "description": "Save the current sound as a named preset file on disk.",
+                "description": concat!(
+                    "Save the current sound as a named preset file on disk, with the patch's ",
+                    "author, description and category (see get_state). Any of those given here ",
+                    "replace the patch's own before it is saved."
+                ),
                 "parameters": {
                     "type": "object",
-                    "properties": { "name": { "type": "string", "description": "Preset name." } },
+                    "properties": {
+                        "name": { "type": "string", "description": "Preset name." },
+                        "author": { "type": "string", "description": "Who made the patch." },
+                        "description": { "type": "string", "description": "A sentence on the sound and how to play it." },
+                        "category": { "type": "string", "description": "Kind of sound, e.g. Bass, Lead, Pad, Pluck, Keys, FX." }
+                    },
                     "required": ["name"]
                 }
             },
@@ -60,10 +280,457 @@ pub fn gemini_tools() -> Value {
                     "required": ["name"]
                 }
             },
+            {
+                "name": "morph_to_preset",
+                "description": concat!(
+                    "Morph smoothly from the current sound into a saved preset over time, e.g. ",
+                    "'morph into Glass Pad over 8 bars'. Every continuous parameter glides to ",
+                    "the preset's value; switches and choices (waveforms, modes) flip halfway. ",
+                    "Recorded by the host as automation. Starting another morph replaces this one."
+                ),
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string", "description": "Preset name (see list_presets)." },
+                        "duration": { "type": "number", "description": "How long the morph takes, in 'unit's." },
+                        "unit": { "type": "string", "description": "seconds (default), beats, or bars (4/4 at the host tempo)." }
+                    },
+                    "required": ["name", "duration"]
+                }
+            },
             {
                 "name": "list_presets",
-                "description": "List the names of all saved presets.",
+                "description": concat!(
+                    "List the saved presets with their author, description and category, and ",
+                    "every category in use. Pass a category to list only its presets."
+                ),
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "category": { "type": "string", "description": "Only presets in this category (case-insensitive)." }
+                    }
+                }
+            },
+            {
+                "name": "export_bank",
+                "description": concat!(
+                    "Export saved presets, with their metadata, as one bank file for sharing ",
+                    "a sound pack. Returns the file's path."
+                ),
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string", "description": "Bank name." },
+                        "presets": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Presets to include (see list_presets); all of them if omitted."
+                        }
+                    },
+                    "required": ["name"]
+                }
+            },
+            {
+                "name": "import_bank",
+                "description": concat!(
+                    "Import the presets in a bank file into the saved presets. A preset whose ",
+                    "name is already taken is renamed (\"Pad 2\"), overwritten or skipped."
+                ),
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string", "description": "Bank name." },
+                        "on_conflict": { "type": "string", "description": "rename (default), overwrite or skip." }
+                    },
+                    "required": ["name"]
+                }
+            },
+            {
+                "name": "get_recovery",
+                "description": concat!(
+                    "List the unsaved patches the plugin recovered after the host went down ",
+                    "without closing it cleanly (newest first), with when each was saved and ",
+                    "its preset name and metadata. The editor offers the newest in a banner; ",
+                    "restore_recovery brings one back."
+                ),
+                "parameters": { "type": "object", "properties": {} }
+            },
+            {
+                "name": "restore_recovery",
+                "description": "Load a recovered patch (see get_recovery) into the synth, or with discard=true throw it away. Either way it is no longer offered.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string", "description": "The patch's id from get_recovery; the newest if omitted." },
+                        "discard": { "type": "boolean", "description": "Throw it away instead of loading it (default false)." }
+                    }
+                }
+            },
+            {
+                "name": "lock_groups",
+                "description": concat!(
+                    "Lock or unlock sections of the patch for live performance, e.g. 'keep this ",
+                    "envelope while I try presets'. Locked sections keep their settings when a ",
+                    "preset is loaded or morphed into and when the patch is randomized; single ",
+                    "parameter edits still apply. Groups: osc1 (with its drawbars and the sub ",
+                    "oscillator), osc2, osc3, ",
+                    "filter, amp_envelope (with release velocity and declick), filter_envelope ",
+                    "(with its amount), glide, modulation, character (analog, keyboard pan, color, ",
+                    "noise), fx ",
+                    "(stereo width, trance gate). Returns the groups now locked; call with no ",
+                    "arguments to just read them."
+                ),
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "lock": { "type": "array", "items": { "type": "string" }, "description": "Groups to lock." },
+                        "unlock": { "type": "array", "items": { "type": "string" }, "description": "Groups to unlock." }
+                    }
+                }
+            },
+            {
+                "name": "ramp_parameter",
+                "description": "Sweep a parameter smoothly from its current value to a target over time, e.g. 'open the filter over 4 bars'. Plays back sample-accurately and is recorded by the host as automation. Starting a new ramp on the same parameter replaces the old one. Rampable parameters: filter_cutoff, filter_resonance, filter_drive, filter_env_amount, gain1, gain2, gain3, analog, keyboard_pan.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "parameter": { "type": "string", "description": "Parameter name, as in set_parameter." },
+                        "target": { "type": "string", "description": "Value to end on, in the parameter's units (gains also accept '-6 dB')." },
+                        "duration": { "type": "number", "description": "How long the sweep takes, in 'unit's." },
+                        "unit": { "type": "string", "description": "seconds (default), beats, or bars (4/4 at the host tempo)." }
+                    },
+                    "required": ["parameter", "target", "duration"]
+                }
+            },
+            {
+                "name": "schedule_parameter",
+                "description": "Queue a set_parameter for a musical time, e.g. 'at bar 5 set cutoff 8000'. Fires when the host's playback reaches that bar (it does not fire if playback jumps past it). Returns an id for cancel_scheduled.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "parameter": { "type": "string", "description": "Parameter name, as in set_parameter." },
+                        "value": { "type": "string", "description": "Value, as in set_parameter." },
+                        "bar": { "type": "number", "description": "1-based bar to fire at; fractions reach into the bar (5.5 = halfway through bar 5)." }
+                    },
+                    "required": ["parameter", "value", "bar"]
+                }
+            },
+            {
+                "name": "list_scheduled",
+                "description": "List the pending scheduled parameter changes (id, parameter, value, bar), soonest first.",
+                "parameters": { "type": "object", "properties": {} }
+            },
+            {
+                "name": "cancel_scheduled",
+                "description": "Cancel a pending scheduled change by id, or every pending change with id 0.",
+                "parameters": {
+                    "type": "object",
+                    "properties": { "id": { "type": "integer", "description": "Id from schedule_parameter / list_scheduled; 0 cancels all." } },
+                    "required": ["id"]
+                }
+            },
+            {
+                "name": "solo_oscillator",
+                "description": "Audition one oscillator on its own: solo it and un-solo the others. Pass 0 to un-solo everything and hear the full mix again. Non-destructive: levels and mutes are untouched, so un-soloing restores the previous mix exactly.",
+                "parameters": {
+                    "type": "object",
+                    "properties": { "oscillator": { "type": "integer", "description": "1, 2 or 3 to solo that oscillator; 0 to un-solo." } },
+                    "required": ["oscillator"]
+                }
+            },
+            {
+                "name": "copy_oscillator",
+                "description": concat!(
+                    "Copy every setting of one oscillator (waveform, tuning, phase, level, ",
+                    "unison, ...) onto another, e.g. 'make osc 3 like osc 1'. Applied as one ",
+                    "edit; the target's mute and solo are kept. Returns how many parameters ",
+                    "changed."
+                ),
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "from": { "type": "integer", "description": "Oscillator to copy: 1, 2 or 3." },
+                        "to": { "type": "integer", "description": "Oscillator to paste onto: 1, 2 or 3." }
+                    },
+                    "required": ["from", "to"]
+                }
+            },
+            {
+                "name": "set_xy",
+                "description": concat!(
+                    "Move an XY pad, e.g. for an expressive macro move. Each axis drives ",
+                    "whatever it's assigned to (see assign_xy and get_state's xy_pads) over its ",
+                    "range; an unassigned axis only moves the pad. The pads are host ",
+                    "automatable, so a recording host captures the move."
+                ),
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "pad": { "type": "integer", "description": "1 or 2." },
+                        "x": { "type": "number", "description": "0-1, left to right; omit to leave it." },
+                        "y": { "type": "number", "description": "0-1, bottom to top; omit to leave it." }
+                    },
+                    "required": ["pad"]
+                }
+            },
+            {
+                "name": "assign_xy",
+                "description": concat!(
+                    "Choose what an XY pad axis drives: a parameter swept from 'min' at 0 to ",
+                    "'max' at 1 (min may be the larger, to reverse the axis). Omit parameter to ",
+                    "free the axis. Saved with the project, not in presets. Like a modulation ",
+                    "source the axis offsets the sound without moving the parameter itself, so ",
+                    "the range is kept relative to it: setting the parameter later shifts the ",
+                    "whole sweep."
+                ),
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "pad": { "type": "integer", "description": "1 or 2." },
+                        "axis": { "type": "string", "description": "x or y." },
+                        "parameter": { "type": "string", "description": "Parameter name, as in set_parameter; a single parameter, not a pattern." },
+                        "min": { "type": "string", "description": "Value at 0, as in set_parameter." },
+                        "max": { "type": "string", "description": "Value at 1, as in set_parameter." }
+                    },
+                    "required": ["pad", "axis"]
+                }
+            },
+            {
+                "name": "adjust_brightness",
+                "description": "Make the sound brighter or darker by a measured amount: 'make it 20% brighter' is percent=20. Measures the actual output's brightness (its spectrum's power-weighted frequency), moves filter_cutoff (and, once the cutoff runs out of range, an oscillator's waveform) and re-measures until the change is reached, so the result is calibrated to this patch. Needs a note sounding while it runs (about 1-2 s); returns the brightness before and after. Prefer this over guessing a cutoff for relative brightness requests.",
+                "parameters": {
+                    "type": "object",
+                    "properties": { "percent": { "type": "number", "description": "Relative change: positive = brighter, negative = darker, e.g. 20 or -15." } },
+                    "required": ["percent"]
+                }
+            },
+            {
+                "name": "suggest_parameters",
+                "description": concat!(
+                    "Propose parameter changes that move the current patch towards a goal, by ",
+                    "fixed sound-design rules (the same patch and goal always give the same ",
+                    "answer). Returns a diff: each change's parameter (set_parameter names), its ",
+                    "current value, the proposed value and why. Nothing is applied: show the ",
+                    "diff, or apply it with set_parameter. darker and brighter use the measured ",
+                    "output brightness when a note is sounding."
+                ),
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "goal": {
+                            "type": "string",
+                            "enum": [
+                                "punchier", "softer", "darker", "brighter", "wider", "narrower",
+                                "warmer", "fatter", "longer", "shorter"
+                            ]
+                        },
+                        "strength": { "type": "number", "description": "0..1, how far to go; default 0.5." }
+                    },
+                    "required": ["goal"]
+                }
+            },
+            {
+                "name": "get_voice_stats",
+                "description": "Return voice-allocator statistics: active voice count, total voice steals and steals per second, and each sounding voice's note, its current sounding pitch (fractional MIDI note including glide and pitch modulation), age in its current envelope stage (the stealing priority: oldest is stolen first), whether it is releasing, and its amp envelope stage (attack, hold, decay, sustain or release).",
+                "parameters": { "type": "object", "properties": {} }
+            },
+            {
+                "name": "get_capabilities",
+                "description": "Return which optional subsystems this build has (available) and the current patch uses (enabled): fx (drive, stereo width, trance gate), mod_matrix, arp, scale_quantizer, additive, comb, noise, sub, morph, sidechain, xy_pads, wavetables; plus the plugin version, the Cargo features it was built with, tools_version (bumped on incompatible tool changes), preset_schema_version and every tool's name. Call it first to adapt instead of probing with calls that fail.",
+                "parameters": { "type": "object", "properties": {} }
+            },
+            {
+                "name": "get_diagnostics",
+                "description": "Return engine health counters: the sample rate, how many times a voice filter blew up to NaN/Inf (extreme drive/resonance) and was reset, and total voice steals. A rising filter_resets count means the current filter settings are unstable. Also the output's current level (output_rms) and brightness (brightness_hz), measured over the last 100 ms. dsp_load is the share of real time the engine's processing takes (over 1 means audio dropouts); unison_cap is the most unison voices auto_quality currently lets each oscillator play (8 = no cap). For bug reports it also has the host's max_buffer_size and the last block_size in samples, the sounding voices as get_voice_stats lists them (note, age, envelope stage), and last_error, the most recent warning or error the plugin logged (null if none).",
+                "parameters": { "type": "object", "properties": {} }
+            },
+            {
+                "name": "get_usage_stats",
+                "description": concat!(
+                    "Return how the synth has been used this session (since it loaded or the ",
+                    "last reset), measured on this machine and never sent anywhere: ",
+                    "session_seconds and playing_seconds (with any voice sounding), ",
+                    "average_polyphony while playing and peak_polyphony, waveforms (most used ",
+                    "first, each with oscillator seconds and share; muted or silent oscillators ",
+                    "don't count), average_dsp_load and peak_dsp_load, and cpu_headroom (1 - ",
+                    "average load). Pass reset: true to start over after reading, e.g. before ",
+                    "trying a patch out with play_notes."
+                ),
+                "parameters": {
+                    "type": "object",
+                    "properties": { "reset": { "type": "boolean" } }
+                }
+            },
+            {
+                "name": "get_transport",
+                "description": concat!(
+                    "Return the tempo the synth plays at (bpm) and where it comes from ",
+                    "(tempo_source: host, tapped, or default when the host sends none), whether ",
+                    "the host is playing, its time_signature ([numerator, denominator], null for ",
+                    "an assumed 4/4), and while playing the song position in quarter-note beats ",
+                    "plus the 1-based bar and beat. note_seconds gives each note length (1/32 to ",
+                    "1/1) in seconds at this tempo: use it to turn \"a quarter note\" or \"an ",
+                    "eighth\" into the seconds a time parameter takes."
+                ),
+                "parameters": { "type": "object", "properties": {} }
+            },
+            {
+                "name": "trigger_envelope",
+                "description": concat!(
+                    "Debug: fire the amp or filter envelope once without playing a note and ",
+                    "return its shape, to check envelope settings. The probe is silent: it ",
+                    "holds at sustain for 0.3 s, then releases. Returns up to 32 evenly spaced ",
+                    "levels (0..1) with seconds_per_point and the total duration. The trace ",
+                    "also shows in the GUI's mod scope. Needs the host's audio running."
+                ),
+                "parameters": {
+                    "type": "object",
+                    "properties": { "envelope": { "type": "string", "enum": ["amp", "filter"] } },
+                    "required": ["envelope"]
+                }
+            },
+            {
+                "name": "set_scale",
+                "description": concat!(
+                    "Set the key and scale every note snaps to: played keys, the arpeggiator's ",
+                    "notes and play_notes all move to the nearest note in the scale (the lower ",
+                    "one on a tie). scale 'chromatic' turns snapping off. Returns the notes the ",
+                    "scale allows."
+                ),
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "root": { "type": "string", "description": "Key: a note name, e.g. C, F#, Bb." },
+                        "scale": {
+                            "type": "string",
+                            "enum": [
+                                "chromatic", "major", "minor", "harmonic_minor", "melodic_minor",
+                                "dorian", "phrygian", "lydian", "mixolydian", "locrian",
+                                "major_pentatonic", "minor_pentatonic", "blues"
+                            ]
+                        }
+                    },
+                    "required": ["root", "scale"]
+                }
+            },
+            {
+                "name": "play_notes",
+                "description": concat!(
+                    "Play notes on the synth so the user can hear the patch (or so ",
+                    "adjust_brightness has something to measure). The notes sound together, like ",
+                    "keys held for 'seconds', snapped to the scale (see set_scale) and through ",
+                    "the arpeggiator when it's on. Returns the notes as they sounded. Needs the ",
+                    "host's audio running."
+                ),
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "notes": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "1 to 8 notes: names with an octave (C4 = middle C, F#3, Bb2) or MIDI numbers."
+                        },
+                        "velocity": { "type": "number", "description": "0..1; default 0.8." },
+                        "seconds": { "type": "number", "description": "How long to hold them, up to 10; default 1." }
+                    },
+                    "required": ["notes"]
+                }
+            },
+            {
+                "name": "panic",
+                "description": concat!(
+                    "Stop every sounding note with a short fade: held keys, play_notes, the ",
+                    "arpeggiator and stuck notes alike. Use it when the user says notes are stuck ",
+                    "or asks for silence. Doesn't change the patch."
+                ),
                 "parameters": { "type": "object", "properties": {} }
+            },
+            {
+                "name": "compare_to_reference",
+                "description": concat!(
+                    "Compare the current patch with a reference recording: renders the given ",
+                    "notes offline (silently) for the reference's length and returns ",
+                    "spectral_distance_db (mean per-band difference between the two spectra's ",
+                    "shapes; 0 is identical, under 3 is close), envelope_correlation (-1..1 ",
+                    "between their levels over time; 1 is the same shape) and ",
+                    "level_difference_db (how much louder the patch is). Use it to work toward ",
+                    "\"make it sound like this\": change a few parameters, compare again, keep ",
+                    "what helps. The file is a WAV in the references folder next to the presets, ",
+                    "up to 10 s; pass the notes it plays."
+                ),
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "file": { "type": "string", "description": "The WAV's name, with or without .wav." },
+                        "notes": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "1 to 8 notes the reference plays together: names with an octave (C4, F#3) or MIDI numbers."
+                        },
+                        "velocity": { "type": "number", "description": "0..1; default 0.8." },
+                        "hold": { "type": "number", "description": "Seconds before the notes are released; default the whole file." }
+                    },
+                    "required": ["file", "notes"]
+                }
+            },
+            {
+                "name": "match_reference",
+                "description": concat!(
+                    "Search for the patch closest to a reference recording and set it: tries ",
+                    "each sounding oscillator's waveform, then tunes the filter (cutoff, ",
+                    "resonance, envelope amount), both envelopes and the levels of oscillators 2 ",
+                    "and 3, rendering offline and scoring like compare_to_reference. Locked ",
+                    "sections and knobs the user is holding are left alone. Slow: each render is ",
+                    "the reference's length, and progress shows in the tool log. Returns the ",
+                    "before and after scores and the changes as set_parameter names with from/to ",
+                    "values. The file is a WAV in the references folder, up to 10 s; pass the ",
+                    "notes it plays."
+                ),
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "file": { "type": "string", "description": "The WAV's name, with or without .wav." },
+                        "notes": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "1 to 8 notes the reference plays together: names with an octave (C4, F#3) or MIDI numbers."
+                        },
+                        "velocity": { "type": "number", "description": "0..1; default 0.8." },
+                        "hold": { "type": "number", "description": "Seconds before the notes are released; default the whole file." },
+                        "renders": { "type": "integer", "description": "Search budget, 30 to 400; default 150." },
+                        "apply": { "type": "boolean", "description": "Set the result; default true. false only reports it." }
+                    },
+                    "required": ["file", "notes"]
+                }
+            },
+            {
+                "name": "trigger_lfo",
+                "description": concat!(
+                    "Debug: restart the LFO from the start of its cycle and return two cycles ",
+                    "of its shape (values -1..1, up to 32 points with seconds_per_point), to ",
+                    "check lfo_shape and lfo_rate. Restarting is audible if the LFO is ",
+                    "modulating something. The trace also shows in the GUI's mod scope."
+                ),
+                "parameters": { "type": "object", "properties": {} }
+            },
+            {
+                "name": "attach_plot",
+                "description": concat!(
+                    "Show the user a plot in the chat, above your reply: the patch's spectrum ",
+                    "for a note, the amp or filter envelope's curve (held 0.3 s at sustain, ",
+                    "then released) or two cycles of the LFO. Rendered offline, so it is ",
+                    "silent and needs no audio. Returns what the plot shows in numbers: the ",
+                    "spectrum's peak and highest audible frequency, or a curve's range and ",
+                    "length. Use it to illustrate a change, not to measure one."
+                ),
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "plot": { "type": "string", "enum": ["spectrum", "amp_envelope", "filter_envelope", "lfo"] },
+                        "note": { "type": "string", "description": "The note a spectrum plays: a name like C4 or a MIDI number; default C4." }
+                    },
+                    "required": ["plot"]
+                }
             }
         ]
     }])
@@ -72,9 +739,10 @@ pub fn gemini_tools() -> Value {
 /// Execute a single tool call in-plugin. Parameter writes are emitted as
 /// `RawParamEvent`s through `proxy`; the returned `Value` is fed back to the
 /// model as the tool's `functionResponse`.
-pub fn dispatch(proxy: &mut ContextProxy, params: &SineParams, name: &str, args: &Value) -> Value {
+pub fn dispatch(proxy: &mut ContextProxy, ctx: &ToolContext, name: &str, args: &Value) -> Value {
+    let params: &SineParams = &ctx.params;
     match name {
-        "get_state" => bridge::read_state(params),
+        "get_state" => tool_bridge::read_state(params),
 
         "set_parameter" => {
             let pname = args.get("parameter").and_then(|v| v.as_str());
@@ -83,8 +751,38 @@ pub fn dispatch(proxy: &mut ContextProxy, params: &SineParams, name: &str, args:
                 return json!({ "error": "set_parameter requires 'parameter' and 'value'" });
             };
 
+            // Don't fight the user for a knob they are dragging.
+            let touched = match deferred::touched_params(params, pname, value) {
+                Ok(touched) => touched,
+                Err(e) => return json!({ "error": e }),
+            };
+            if ctx.held.any_held(&touched) {
+                return match ctx.deferred.policy() {
+                    ConflictPolicy::Defer => {
+                        ctx.deferred.defer(pname, value.clone());
+                        let log = format!("⏸ {pname} → {value} deferred: you're adjusting it");
+                        let _ = proxy.emit(ToolLog(log));
+                        json!({
+                            "status": "deferred",
+                            "parameter": pname,
+                            "reason": "the user is adjusting it; applies when they let go"
+                        })
+                    }
+                    ConflictPolicy::Reject => {
+                        let log = format!("⛔ {pname} → {value} rejected: you're adjusting it");
+                        let _ = proxy.emit(ToolLog(log));
+                        json!({
+                            "error": format!("'{pname}' is being adjusted by the user"),
+                            "conflict": "rejected"
+                        })
+                    }
+                };
+            }
+
+            let mut changes = ChangeRecorder::new();
             let result = {
                 let mut emit = |ev| {
+                    changes.record(&ev);
                     let _ = proxy.emit(ev);
                 };
                 bridge::apply_write(params, pname, value, &mut emit)
@@ -92,7 +790,8 @@ pub fn dispatch(proxy: &mut ContextProxy, params: &SineParams, name: &str, args:
 
             match result {
                 Ok(()) => {
-                    let _ = proxy.emit(ChatEvent::ToolLog(format!("🎛 {pname} → {value}")));
+                    show_toast(proxy, params, changes, || format!("Assistant set {pname}"));
+                    let _ = proxy.emit(ToolLog(format!("🎛 {pname} → {value}")));
                     json!({ "status": "ok", "parameter": pname })
                 }
                 Err(e) => json!({ "error": e }),
@@ -101,9 +800,20 @@ pub fn dispatch(proxy: &mut ContextProxy, params: &SineParams, name: &str, args:
 
         "save_preset" => {
             let nm = args.get("name").and_then(|v| v.as_str()).unwrap_or("Untitled");
+            let mut metadata = PatchMetadata::current(params);
+            for (key, field) in [
+                ("author", &mut metadata.author),
+                ("description", &mut metadata.description),
+                ("category", &mut metadata.category),
+            ] {
+                if let Some(text) = args.get(key).and_then(Value::as_str) {
+                    *field = text.trim().to_string();
+                }
+            }
+            metadata.store(params);
             match preset::save(params, nm) {
                 Ok(_) => {
-                    let _ = proxy.emit(ChatEvent::ToolLog(format!("💾 saved preset '{nm}'")));
+                    let _ = proxy.emit(ToolLog(format!("💾 saved preset '{nm}'")));
                     json!({ "status": "saved", "name": nm })
                 }
                 Err(e) => json!({ "error": e }),
@@ -114,21 +824,633 @@ pub fn dispatch(proxy: &mut ContextProxy, params: &SineParams, name: &str, args:
             let nm = args.get("name").and_then(|v| v.as_str()).unwrap_or("");
             match preset::load(nm) {
                 Ok(data) => {
+                    let mut changes = ChangeRecorder::new();
                     {
                         let mut emit = |ev| {
+                            changes.record(&ev);
                             let _ = proxy.emit(ev);
                         };
                         data.apply(params, &mut emit);
                     }
-                    let _ = proxy.emit(ChatEvent::ToolLog(format!("📂 loaded preset '{nm}'")));
-                    json!({ "status": "loaded", "name": nm })
+                    data.metadata.store(params);
+                    let summary = || format!("Assistant loaded preset '{nm}'");
+                    show_toast(proxy, params, changes, summary);
+                    let _ = proxy.emit(ToolLog(format!("📂 loaded preset '{nm}'")));
+                    let kept = locks::current(params).ids();
+                    json!({ "status": "loaded", "name": nm, "kept_locked": kept })
+                }
+                Err(e) => json!({ "error": e }),
+            }
+        }
+
+        "morph_to_preset" => {
+            let nm = args.get("name").and_then(Value::as_str).unwrap_or("");
+            let Some(length) = args.get("duration").and_then(Value::as_f64) else {
+                return json!({ "error": "morph_to_preset requires 'name' and 'duration'" });
+            };
+            let length = length.max(0.0) as f32;
+            let unit = args.get("unit").and_then(Value::as_str).unwrap_or("seconds");
+            let duration = match parse_duration(length, unit) {
+                Ok(duration) => duration,
+                Err(e) => return json!({ "error": e }),
+            };
+            match preset::load(nm) {
+                Ok(data) => {
+                    let moving = ctx.transition.start(params, nm, &data, duration);
+                    let log = format!("🌗 morphing to preset '{nm}' over {length} {unit}");
+                    let _ = proxy.emit(ToolLog(log));
+                    json!({
+                        "status": "morphing",
+                        "name": nm,
+                        "parameters_changing": moving,
+                        "kept_locked": locks::current(params).ids(),
+                    })
+                }
+                Err(e) => json!({ "error": e }),
+            }
+        }
+
+        "list_presets" => {
+            let category = args.get("category").and_then(Value::as_str);
+            let catalog = tool_bridge::catalog();
+            let mut categories: Vec<&str> = catalog
+                .iter()
+                .map(|(_, metadata)| metadata.category.trim())
+                .filter(|c| !c.is_empty())
+                .collect();
+            categories.sort_by_key(|c| c.to_lowercase());
+            categories.dedup_by_key(|c| c.to_lowercase());
+            let presets: Vec<Value> = catalog
+                .iter()
+                .filter(|(_, metadata)| {
+                    category.is_none_or(|c| tool_bridge::in_category(metadata, c))
+                })
+                .map(|(name, metadata)| {
+                    json!({
+                        "name": name,
+                        "author": metadata.author,
+                        "description": metadata.description,
+                        "category": metadata.category,
+                    })
+                })
+                .collect();
+            json!({ "presets": presets, "categories": categories })
+        }
+
+        "export_bank" => {
+            let nm = args.get("name").and_then(Value::as_str).unwrap_or("");
+            if nm.trim().is_empty() {
+                return json!({ "error": "export_bank requires 'name'" });
+            }
+            let presets: Vec<String> = args
+                .get("presets")
+                .and_then(Value::as_array)
+                .map(|names| {
+                    names
+                        .iter()
+                        .filter_map(|n| n.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default();
+            match bank_io::export(nm, &presets) {
+                Ok((path, count)) => {
+                    let log = format!("📦 exported {count} presets to bank '{nm}'");
+                    let _ = proxy.emit(ToolLog(log));
+                    json!({
+                        "status": "exported",
+                        "path": path.display().to_string(),
+                        "presets": count,
+                    })
+                }
+                Err(e) => json!({ "error": e }),
+            }
+        }
+
+        "import_bank" => {
+            let nm = args.get("name").and_then(Value::as_str).unwrap_or("");
+            let conflict = match args.get("on_conflict").and_then(Value::as_str) {
+                Some(id) => match BankConflict::from_id(id) {
+                    Ok(conflict) => conflict,
+                    Err(e) => return json!({ "error": e }),
+                },
+                None => BankConflict::default(),
+            };
+            match bank_io::import(nm, conflict) {
+                Ok(report) => {
+                    let _ = proxy.emit(ToolLog(format!("📦 bank '{nm}': {}", report.summary())));
+                    let renamed: Vec<Value> = report
+                        .renamed
+                        .iter()
+                        .map(|(from, to)| json!({ "from": from, "to": to }))
+                        .collect();
+                    json!({
+                        "status": "imported",
+                        "imported": report.imported,
+                        "renamed": renamed,
+                        "overwritten": report.overwritten,
+                        "skipped": report.skipped,
+                    })
                 }
                 Err(e) => json!({ "error": e }),
             }
         }
 
-        "list_presets" => json!({ "presets": preset::list() }),
+        "get_recovery" => {
+            let recovered: Vec<Value> = recovery::leftovers()
+                .into_iter()
+                .map(|leftover| {
+                    json!({
+                        "id": leftover.id,
+                        "saved": leftover.age(),
+                        "name": leftover.patch.name,
+                        "author": leftover.patch.metadata.author,
+                        "description": leftover.patch.metadata.description,
+                        "category": leftover.patch.metadata.category,
+                    })
+                })
+                .collect();
+            json!({ "recovered": recovered })
+        }
+
+        "restore_recovery" => {
+            let id = args.get("id").and_then(Value::as_str);
+            let discard = args.get("discard").and_then(Value::as_bool).unwrap_or(false);
+            let leftovers = recovery::leftovers();
+            let found = match id {
+                Some(id) => leftovers.into_iter().find(|leftover| leftover.id == id),
+                None => leftovers.into_iter().next(),
+            };
+            let Some(leftover) = found else {
+                return json!({ "error": "no such recovered patch (see get_recovery)" });
+            };
+            leftover.discard();
+            if discard {
+                let log = format!("🗑 discarded recovered patch {}", leftover.id);
+                let _ = proxy.emit(ToolLog(log));
+                return json!({ "status": "discarded", "id": leftover.id });
+            }
+            let mut changes = ChangeRecorder::new();
+            {
+                let mut emit = |ev| {
+                    changes.record(&ev);
+                    let _ = proxy.emit(ev);
+                };
+                leftover.patch.apply(params, &mut emit);
+            }
+            leftover.patch.metadata.store(params);
+            let summary = || "Assistant restored the recovered patch".to_string();
+            show_toast(proxy, params, changes, summary);
+            let _ = proxy.emit(ToolLog(format!("♻ restored recovered patch {}", leftover.id)));
+            json!({ "status": "restored", "id": leftover.id })
+        }
+
+        "lock_groups" => {
+            let ids = |key: &str| -> Result<Vec<LockGroup>, String> {
+                let Some(list) = args.get(key) else {
+                    return Ok(Vec::new());
+                };
+                let list = list.as_array().ok_or(format!("'{key}' must be an array"))?;
+                list.iter()
+                    .map(|id| tool_bridge::lock_group(id.as_str().unwrap_or("")))
+                    .collect()
+            };
+            let (lock, unlock) = match (ids("lock"), ids("unlock")) {
+                (Ok(lock), Ok(unlock)) => (lock, unlock),
+                (Err(e), _) | (_, Err(e)) => return json!({ "error": e }),
+            };
+            let mut locks = locks::current(params);
+            for group in &lock {
+                locks.set(*group, true);
+            }
+            for group in &unlock {
+                locks.set(*group, false);
+            }
+            ProjectState::update(params, |state| state.locks = locks);
+            if !lock.is_empty() || !unlock.is_empty() {
+                let log = match locks.ids() {
+                    ids if ids.is_empty() => "🔓 unlocked every group".to_string(),
+                    ids => format!("🔒 locked: {}", ids.join(", ")),
+                };
+                let _ = proxy.emit(ToolLog(log));
+            }
+            json!({ "locked": locks.ids() })
+        }
+
+        "ramp_parameter" => {
+            let pname = args.get("parameter").and_then(Value::as_str).unwrap_or("");
+            let target_value = args.get("target");
+            let duration = args.get("duration").and_then(Value::as_f64);
+            let (Some(target_value), Some(duration)) = (target_value, duration) else {
+                return json!({ "error": "ramp_parameter requires 'target' and 'duration'" });
+            };
+            let Some(target) = tool_bridge::ramp_target(pname) else {
+                return json!({ "error": format!("'{pname}' can't be ramped") });
+            };
+            let to = match tool_bridge::ramp_value(params, target, target_value) {
+                Ok(to) => to,
+                Err(e) => return json!({ "error": e }),
+            };
+            let length = duration.max(0.0) as f32;
+            let unit = args.get("unit").and_then(Value::as_str).unwrap_or("seconds");
+            let duration = match parse_duration(length, unit) {
+                Ok(duration) => duration,
+                Err(e) => return json!({ "error": e }),
+            };
+            ctx.ramps.start(target, to, duration);
+            let log = format!("📈 {pname} → {to} over {length} {unit}");
+            let _ = proxy.emit(ToolLog(log));
+            json!({ "status": "ramping", "parameter": pname, "target": to })
+        }
+
+        "schedule_parameter" => {
+            let pname = args.get("parameter").and_then(Value::as_str);
+            let value = args.get("value");
+            let bar = args.get("bar").and_then(Value::as_f64);
+            let (Some(pname), Some(value), Some(bar)) = (pname, value, bar) else {
+                return json!({ "error": "missing 'parameter', 'value' or 'bar'" });
+            };
+            if bar < 1.0 {
+                return json!({ "error": "bars start at 1" });
+            }
+            // Dry run, so a bad name or value is reported now rather than
+            // silently dropped when the bar comes round.
+            if let Err(e) = bridge::apply_write(params, pname, value, &mut |_| {}) {
+                return json!({ "error": e });
+            }
+            match ctx.schedule.add(pname, value.clone(), bar) {
+                Ok(id) => {
+                    let log = format!("⏱ bar {bar}: {pname} → {value}");
+                    let _ = proxy.emit(ToolLog(log));
+                    json!({ "status": "scheduled", "id": id })
+                }
+                Err(e) => json!({ "error": e }),
+            }
+        }
+
+        "list_scheduled" => json!({ "scheduled": ctx.schedule.list() }),
+
+        "cancel_scheduled" => {
+            let Some(id) = args.get("id").and_then(Value::as_u64) else {
+                return json!({ "error": "cancel_scheduled requires 'id'" });
+            };
+            if id == 0 {
+                json!({ "cancelled": ctx.schedule.cancel_all() })
+            } else if ctx.schedule.cancel(id as u32) {
+                json!({ "cancelled": 1 })
+            } else {
+                json!({ "error": format!("no pending change with id {id}") })
+            }
+        }
+
+        "solo_oscillator" => {
+            let which = args.get("oscillator").and_then(Value::as_u64);
+            let Some(which) = which.filter(|&n| n <= 3) else {
+                return json!({ "error": "solo_oscillator requires 'oscillator' (0-3)" });
+            };
+            let mut changes = ChangeRecorder::new();
+            let mut emit = |ev| {
+                changes.record(&ev);
+                let _ = proxy.emit(ev);
+            };
+            for (n, osc) in [(1, &params.osc1), (2, &params.osc2), (3, &params.osc3)] {
+                bridge::emit_set(&osc.solo, n == which, &mut emit);
+            }
+            let summary = || match which {
+                0 => "Assistant un-soloed all oscillators".to_string(),
+                n => format!("Assistant soloed oscillator {n}"),
+            };
+            show_toast(proxy, params, changes, summary);
+            let log = if which == 0 {
+                "🎧 un-soloed all oscillators".to_string()
+            } else {
+                format!("🎧 soloed osc {which}")
+            };
+            let _ = proxy.emit(ToolLog(log));
+            json!({ "status": "ok", "soloed": which })
+        }
+
+        "copy_oscillator" => {
+            let number = |key: &str| {
+                let number = args.get(key).and_then(Value::as_u64);
+                number
+                    .ok_or(format!("copy_oscillator requires '{key}' (1-3)"))
+                    .and_then(tool_bridge::parse_oscillator)
+            };
+            let (from, to) = match (number("from"), number("to")) {
+                (Ok(from), Ok(to)) => (from, to),
+                (Err(e), _) | (_, Err(e)) => return json!({ "error": e }),
+            };
+            let snapshot = OscSnapshot::copy(params, from);
+            let touched: Vec<_> = snapshot
+                .changes(params, to)
+                .into_iter()
+                .map(|(ptr, _)| ptr)
+                .collect();
+            if ctx.held.any_held(&touched) {
+                return json!({
+                    "error": format!("oscillator {} is being adjusted by the user", to + 1),
+                    "conflict": "rejected"
+                });
+            }
+            let mut changes = ChangeRecorder::new();
+            let changed = {
+                let mut emit = |ev| {
+                    changes.record(&ev);
+                    let _ = proxy.emit(ev);
+                };
+                snapshot.paste(params, to, &mut emit)
+            };
+            let (from, to) = (from + 1, to + 1);
+            show_toast(proxy, params, changes, || {
+                format!("Assistant copied oscillator {from} to oscillator {to}")
+            });
+            let _ = proxy.emit(ToolLog(format!("📋 osc {from} → osc {to}")));
+            json!({ "status": "ok", "from": from, "to": to, "changed": changed })
+        }
+
+        "set_xy" => {
+            let pad = args.get("pad").and_then(Value::as_u64);
+            let pad = pad
+                .ok_or("set_xy requires 'pad' (1 or 2)".to_string())
+                .and_then(tool_bridge::parse_pad);
+            let pad = match pad {
+                Ok(pad) => pad,
+                Err(e) => return json!({ "error": e }),
+            };
+            let moves = Axis::ALL.map(|axis| (axis, args.get(axis.id()).and_then(Value::as_f64)));
+            if moves.iter().all(|(_, value)| value.is_none()) {
+                return json!({ "error": "set_xy requires 'x' or 'y' (0-1)" });
+            }
+            if moves
+                .iter()
+                .any(|(_, value)| value.is_some_and(|v| !(0.0..=1.0).contains(&v)))
+            {
+                return json!({ "error": "XY positions are 0-1" });
+            }
+            let pad_params = xy::pad(params, pad);
+            let mut changes = ChangeRecorder::new();
+            let mut emit = |ev| {
+                changes.record(&ev);
+                let _ = proxy.emit(ev);
+            };
+            for (axis, value) in moves {
+                if let Some(value) = value {
+                    bridge::emit_set(axis.param(pad_params), value as f32, &mut emit);
+                }
+            }
+            let number = pad + 1;
+            show_toast(proxy, params, changes, || format!("Assistant moved XY pad {number}"));
+            // The writes land after this call, so report what was asked for.
+            let [x, y] = moves.map(|(axis, value)| {
+                value.unwrap_or_else(|| f64::from(axis.param(pad_params).value()))
+            });
+            let _ = proxy.emit(ToolLog(format!("🕹 XY {number} → {x:.2}, {y:.2}")));
+            json!({ "status": "ok", "pad": number, "x": x, "y": y })
+        }
+
+        "assign_xy" => {
+            let pad = args.get("pad").and_then(Value::as_u64);
+            let pad = pad
+                .ok_or("assign_xy requires 'pad' (1 or 2)".to_string())
+                .and_then(tool_bridge::parse_pad);
+            let axis = args.get("axis").and_then(Value::as_str).unwrap_or("");
+            let (pad, axis) = match (pad, Axis::parse(axis)) {
+                (Ok(pad), Ok(axis)) => (pad, axis),
+                (Err(e), _) | (_, Err(e)) => return json!({ "error": e }),
+            };
+            let assignment = match args.get("parameter").and_then(Value::as_str) {
+                None => None,
+                Some(pname) => {
+                    let (Some(min), Some(max)) = (args.get("min"), args.get("max")) else {
+                        return json!({ "error": "assign_xy requires 'min' and 'max'" });
+                    };
+                    match tool_bridge::xy_assignment(params, pname, min, max) {
+                        Ok(assignment) => Some(assignment),
+                        Err(e) => return json!({ "error": e }),
+                    }
+                }
+            };
+            let assigned = assignment.is_some();
+            if let Err(e) = xy::assign(params, pad, axis, assignment) {
+                return json!({ "error": e });
+            }
+            let (number, name) = (pad + 1, axis.id());
+            let log = match args.get("parameter").and_then(Value::as_str) {
+                Some(pname) if assigned => format!("🕹 XY {number} {name} → {pname}"),
+                _ => format!("🕹 XY {number} {name} freed"),
+            };
+            let _ = proxy.emit(ToolLog(log));
+            json!({ "status": "ok", "pad": number, "axis": name, "assigned": assigned })
+        }
+
+        "adjust_brightness" => {
+            let Some(percent) = args.get("percent").and_then(Value::as_f64) else {
+                return json!({ "error": "adjust_brightness requires 'percent'" });
+            };
+            let _ = proxy.emit(ToolLog(format!("🔆 brightness {percent:+}%…")));
+            match brightness::adjust(proxy, ctx, percent as f32) {
+                Ok(result) => result,
+                Err(e) => json!({ "error": e }),
+            }
+        }
+
+        "suggest_parameters" => {
+            let goal = args.get("goal").and_then(Value::as_str).unwrap_or("");
+            let goal = match Goal::parse(goal) {
+                Ok(goal) => goal,
+                Err(e) => return json!({ "error": e }),
+            };
+            let strength = args.get("strength").and_then(Value::as_f64).unwrap_or(0.5) as f32;
+            let result = suggest::suggest(params, &ctx.diagnostics, goal, strength);
+            let count = result["changes"].as_array().map_or(0, Vec::len);
+            let log = format!("💡 {count} suggested changes for '{}'", goal.id());
+            let _ = proxy.emit(ToolLog(log));
+            result
+        }
+
+        "get_voice_stats" => {
+            serde_json::to_value(ctx.diagnostics.voice_stats()).unwrap_or(Value::Null)
+        }
+
+        "get_capabilities" => capabilities::capabilities(params, tool_names()),
+
+        "get_diagnostics" => {
+            serde_json::to_value(ctx.diagnostics.engine_stats()).unwrap_or(Value::Null)
+        }
+
+        "get_usage_stats" => {
+            let stats = ctx.diagnostics.usage_stats();
+            if args.get("reset").and_then(|v| v.as_bool()) == Some(true) {
+                ctx.diagnostics.reset_usage();
+            }
+            serde_json::to_value(stats).unwrap_or(Value::Null)
+        }
+
+        "get_transport" => {
+            let transport = ctx.diagnostics.transport();
+            let mut result = serde_json::to_value(transport).unwrap_or_else(|_| json!({}));
+            let divisions = [
+                NoteDivision::ThirtySecond,
+                NoteDivision::Sixteenth,
+                NoteDivision::Eighth,
+                NoteDivision::Quarter,
+                NoteDivision::Half,
+                NoteDivision::Whole,
+            ];
+            let note_seconds: serde_json::Map<String, Value> = divisions
+                .into_iter()
+                .map(|d| (bridge::division_to_id(d).to_string(), json!(d.seconds(transport.bpm))))
+                .collect();
+            result["note_seconds"] = Value::Object(note_seconds);
+            result["beats_per_bar"] = json!(transport.beats_per_bar());
+            if let Some((bar, beat)) = transport.bar_and_beat() {
+                result["bar"] = json!(bar);
+                result["beat"] = json!(beat);
+            }
+            result
+        }
+
+        "trigger_envelope" => {
+            let name = args.get("envelope").and_then(Value::as_str).unwrap_or("amp");
+            let result = probe::parse_envelope(name)
+                .and_then(|target| probe::trigger(&ctx.diagnostics, target));
+            match result {
+                Ok(result) => result,
+                Err(e) => json!({ "error": e }),
+            }
+        }
+
+        "trigger_lfo" => match probe::trigger(&ctx.diagnostics, ProbeTarget::Lfo) {
+            Ok(result) => result,
+            Err(e) => json!({ "error": e }),
+        },
+
+        "attach_plot" => {
+            let kind = args.get("plot").and_then(Value::as_str).unwrap_or_default();
+            let note = args.get("note").map_or(Ok(plot::DEFAULT_NOTE), audition::parse_note);
+            let (kind, note) = match (PlotKind::parse(kind), note) {
+                (Ok(kind), Ok(note)) => (kind, note),
+                (Err(e), _) | (_, Err(e)) => return json!({ "error": e }),
+            };
+            let plot = Plot::render(params, kind, note, ctx.diagnostics.tempo());
+            let _ = proxy.emit(ToolLog(plot.message()));
+            plot.summary()
+        }
+
+        "set_scale" => {
+            let root = args.get("root").unwrap_or(&Value::Null);
+            let scale = args.get("scale").unwrap_or(&Value::Null);
+            let parsed = bridge::parse_scale_root(root)
+                .and_then(|root| bridge::parse_scale(scale).map(|scale| (root, scale)));
+            let (root, scale) = match parsed {
+                Ok(parsed) => parsed,
+                Err(e) => return json!({ "error": e }),
+            };
+            let mut changes = ChangeRecorder::new();
+            let mut emit = |ev| {
+                changes.record(&ev);
+                let _ = proxy.emit(ev);
+            };
+            bridge::emit_set(&params.scale_root, root, &mut emit);
+            bridge::emit_set(&params.scale, scale, &mut emit);
+            let (name, id) = (tool_bridge::scale_root_name(root), tool_bridge::scale_to_id(scale));
+            let key = format!("{name} {id}");
+            let summary = || format!("Assistant set the scale to {key}");
+            show_toast(proxy, params, changes, summary);
+            let _ = proxy.emit(ToolLog(format!("🎼 scale → {key}")));
+            let notes: Vec<&str> = scale
+                .steps()
+                .iter()
+                .map(|step| ScaleRoot::from_semitone(root.semitone() + step))
+                .map(tool_bridge::scale_root_name)
+                .collect();
+            json!({ "status": "ok", "scale": key, "notes": notes })
+        }
+
+        "play_notes" => {
+            let notes = args.get("notes").and_then(Value::as_array);
+            let Some(notes) = notes else {
+                return json!({ "error": "play_notes requires 'notes'" });
+            };
+            let notes: Result<Vec<u8>, String> = notes.iter().map(audition::parse_note).collect();
+            let notes = match notes {
+                Ok(notes) => notes,
+                Err(e) => return json!({ "error": e }),
+            };
+            let velocity = args.get("velocity").and_then(Value::as_f64).unwrap_or(0.8) as f32;
+            let seconds = args.get("seconds").and_then(Value::as_f64).unwrap_or(1.0) as f32;
+            let scale = Quantizer::from_params(params);
+            match audition::play(&ctx.diagnostics, scale, &notes, velocity, seconds) {
+                Ok(result) => {
+                    let log = format!("🎹 played {}", result["played"]);
+                    let _ = proxy.emit(ToolLog(log));
+                    result
+                }
+                Err(e) => json!({ "error": e }),
+            }
+        }
+
+        "panic" => {
+            ctx.diagnostics.request_panic();
+            let _ = proxy.emit(ToolLog("🛑 released every note".to_string()));
+            json!({ "status": "ok" })
+        }
+
+        "compare_to_reference" | "match_reference" => {
+            let file = args.get("file").and_then(Value::as_str);
+            let notes = args.get("notes").and_then(Value::as_array);
+            let (Some(file), Some(notes)) = (file, notes) else {
+                return json!({ "error": format!("{name} requires 'file' and 'notes'") });
+            };
+            let notes: Result<Vec<u8>, String> = notes.iter().map(audition::parse_note).collect();
+            let notes = match notes {
+                Ok(notes) if (1..=MAX_NOTE_REQUESTS).contains(&notes.len()) => notes,
+                Ok(_) => return json!({ "error": "give 1 to 8 notes" }),
+                Err(e) => return json!({ "error": e }),
+            };
+            let reference = match reference::load(file) {
+                Ok(reference) => reference,
+                Err(e) => return json!({ "error": e }),
+            };
+            let velocity = args.get("velocity").and_then(Value::as_f64).unwrap_or(0.8) as f32;
+            let hold = args.get("hold").and_then(Value::as_f64).map(|hold| hold as f32);
+            let hold = hold.unwrap_or_else(|| reference.seconds());
+            if name == "match_reference" {
+                let renders = args.get("renders").and_then(Value::as_u64);
+                let target = matcher::Target {
+                    file,
+                    reference: &reference,
+                    notes: &notes,
+                    velocity,
+                    hold,
+                    renders: renders.map_or(matcher::DEFAULT_RENDERS, |n| n as usize),
+                    apply: args.get("apply").and_then(Value::as_bool).unwrap_or(true),
+                };
+                let _ = proxy.emit(ToolLog(format!("🧬 matching '{file}'…")));
+                return matcher::run(proxy, ctx, &target);
+            }
+            let bpm = ctx.diagnostics.tempo();
+            let result = reference::compare(params, &reference, &notes, velocity, hold, bpm);
+            let log = format!(
+                "🎯 compared with '{file}': {:.1} dB apart",
+                result["spectral_distance_db"].as_f64().unwrap_or(0.0)
+            );
+            let _ = proxy.emit(ToolLog(log));
+            result
+        }
 
         _ => json!({ "error": format!("unknown tool '{name}'") }),
     }
 }
+
+/// Let the user know (and undo) what a tool changed, in the editor's toast.
+pub(super) fn show_toast(
+    proxy: &mut ContextProxy,
+    params: &SineParams,
+    changes: ChangeRecorder,
+    summary: impl FnOnce() -> String,
+) {
+    if let Some(toast) = changes.into_toast(params, "Assistant", summary) {
+        let _ = proxy.emit(ToastEvent::Show(toast));
+    }
+}
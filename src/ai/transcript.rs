@@ -0,0 +1,192 @@
+//! Chat transcripts on disk, for archiving a sound-design session and for
+//! replaying the changes it made.
+//!
+//! An export writes the conversation twice, as `transcripts/<name>.md` to read
+//! and `<name>.json` to import. A replay re-runs the session's changes as chat
+//! slash-commands (see [`super::commands`]): the user's own `/set` and
+//! `/preset load` lines, and the assistant's parameter writes and preset loads
+//! as its tool logs record them. Its other tools (ramps, morphs, locks, ...)
+//! aren't replayed.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::chat_ui::Role;
+use super::preset::{app_dir, sanitize};
+
+/// Bumped when the JSON layout changes incompatibly.
+const TRANSCRIPT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct TranscriptFile {
+    version: u32,
+    messages: Vec<Entry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    role: String,
+    text: String,
+}
+
+pub fn transcripts_dir() -> PathBuf {
+    app_dir().join("transcripts")
+}
+
+pub fn role_id(role: Role) -> &'static str {
+    match role {
+        Role::User => "user",
+        Role::Assistant => "assistant",
+        Role::Tool => "tool",
+    }
+}
+
+pub fn id_to_role(s: &str) -> Option<Role> {
+    match s {
+        "user" => Some(Role::User),
+        "assistant" => Some(Role::Assistant),
+        "tool" => Some(Role::Tool),
+        _ => None,
+    }
+}
+
+/// The Markdown heading each message starts under.
+fn heading(role: Role) -> &'static str {
+    match role {
+        Role::User => "### You",
+        Role::Assistant => "### AI",
+        Role::Tool => "### Tool",
+    }
+}
+
+/// Write `messages` as `<name>.md` and `<name>.json`. Returns the JSON file's
+/// path.
+pub fn export(messages: &[(Role, String)], name: &str) -> Result<PathBuf, String> {
+    let dir = transcripts_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| format!("create transcripts dir: {e}"))?;
+    let stem = sanitize(name);
+
+    let markdown: String = messages
+        .iter()
+        .map(|(role, text)| format!("{}\n\n{}\n\n", heading(*role), text.trim()))
+        .collect();
+    let md_path = dir.join(format!("{stem}.md"));
+    let markdown = format!("# {name}\n\n{markdown}");
+    std::fs::write(&md_path, markdown).map_err(|e| format!("write {}: {e}", md_path.display()))?;
+
+    let file = TranscriptFile {
+        version: TRANSCRIPT_VERSION,
+        messages: messages
+            .iter()
+            .map(|(role, text)| Entry {
+                role: role_id(*role).to_string(),
+                text: text.clone(),
+            })
+            .collect(),
+    };
+    let json = serde_json::to_string_pretty(&file).map_err(|e| format!("serialize: {e}"))?;
+    let path = dir.join(format!("{stem}.json"));
+    std::fs::write(&path, json).map_err(|e| format!("write {}: {e}", path.display()))?;
+    Ok(path)
+}
+
+/// Read an exported transcript back: `<name>.json`, or `<name>.md` if only
+/// that is there.
+pub fn import(name: &str) -> Result<Vec<(Role, String)>, String> {
+    let path = transcripts_dir().join(format!("{}.json", sanitize(name)));
+    if path.exists() {
+        let text =
+            std::fs::read_to_string(&path).map_err(|e| format!("read {}: {e}", path.display()))?;
+        let file: TranscriptFile =
+            serde_json::from_str(&text).map_err(|e| format!("parse {}: {e}", path.display()))?;
+        if file.version > TRANSCRIPT_VERSION {
+            return Err(format!(
+                "transcript '{name}' is version {} but this build reads up to {TRANSCRIPT_VERSION}",
+                file.version
+            ));
+        }
+        return file
+            .messages
+            .into_iter()
+            .map(|entry| match id_to_role(&entry.role) {
+                Some(role) => Ok((role, entry.text)),
+                None => Err(format!(
+                    "unknown role '{}' in {}",
+                    entry.role,
+                    path.display()
+                )),
+            })
+            .collect();
+    }
+
+    let md_path = path.with_extension("md");
+    let text = std::fs::read_to_string(&md_path).map_err(|_| {
+        format!(
+            "no transcript named '{name}' in {}",
+            transcripts_dir().display()
+        )
+    })?;
+    Ok(parse_markdown(&text))
+}
+
+/// Messages from an exported `.md`: each runs from its heading to the next.
+fn parse_markdown(text: &str) -> Vec<(Role, String)> {
+    let mut messages: Vec<(Role, String)> = Vec::new();
+    for line in text.lines() {
+        let role = [Role::User, Role::Assistant, Role::Tool]
+            .into_iter()
+            .find(|role| line.trim_end() == heading(*role));
+        match (role, messages.last_mut()) {
+            (Some(role), _) => messages.push((role, String::new())),
+            (None, Some((_, body))) => {
+                body.push_str(line);
+                body.push('\n');
+            }
+            // The title, before the first message.
+            (None, None) => {}
+        }
+    }
+    for (_, body) in &mut messages {
+        *body = body.trim().to_string();
+    }
+    messages
+}
+
+/// The changes `messages` made, as slash-commands in order. Tool logs count
+/// only while the assistant has the turn: after the user's own commands they
+/// are just those commands' replies.
+pub fn replay_commands(messages: &[(Role, String)]) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut assistant_turn = false;
+    for (role, text) in messages {
+        match role {
+            Role::User => {
+                assistant_turn = !text.starts_with('/');
+                if !assistant_turn {
+                    let lines = text.lines().map(str::trim);
+                    commands.extend(
+                        lines
+                            .filter(|l| l.starts_with("/set ") || l.starts_with("/preset load "))
+                            .map(str::to_string),
+                    );
+                }
+            }
+            Role::Tool if assistant_turn => {
+                if let Some((parameter, value)) = text
+                    .strip_prefix("🎛 ")
+                    .and_then(|rest| rest.split_once(" → "))
+                {
+                    commands.push(format!("/set {parameter} {value}"));
+                } else if let Some(name) = text
+                    .strip_prefix("📂 loaded preset '")
+                    .and_then(|rest| rest.strip_suffix('\''))
+                {
+                    commands.push(format!("/preset load {name}"));
+                }
+            }
+            Role::Tool | Role::Assistant => {}
+        }
+    }
+    commands
+}
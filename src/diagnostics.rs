@@ -0,0 +1,908 @@
+//! Engine diagnostics: counters and per-voice state that the audio thread
+//! publishes for the GUI (voice monitor, settings) and the AI tools, plus the
+//! probes and notes those ask it for.
+//!
+//! Same hand-off as [`crate::PeakMeter`]: `SineSynth::process` writes relaxed
+//! atomics once per block (no locks, no allocation) and readers sample them
+//! whenever they like. Readers get an owned [`VoiceStats`] snapshot, which may
+//! allocate because it is only ever built off the audio thread.
+//!
+//! The session usage counters behind [`UsageStats`] never leave the machine:
+//! nothing here is sent anywhere, only read by the stats panel and the
+//! `get_usage_stats` tool.
+
+use nih_plug::prelude::Enum;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU32, AtomicU64, Ordering};
+
+use crate::NUM_VOICES;
+use crate::ai::bridge::wave_to_id;
+use crate::{ModSource, Waveform};
+use crate::dsp::analysis::BrightnessReading;
+use crate::dsp::envelope::EnvelopeStage;
+use crate::dsp::mod_matrix::ModSources;
+use crate::dsp::probe::{ProbeTarget, TracePoint, SOURCE_POINTS, TRACE_POINTS};
+use crate::quality::MAX_UNISON;
+use crate::watch::WATCH_POINTS;
+
+/// Stored in a slot's `note` while the voice is idle.
+const NO_NOTE: u32 = u32::MAX;
+
+/// Stored in `probe_request` / `trace_target` when there is none.
+const NO_PROBE: u8 = u8::MAX;
+
+/// Notes that can be waiting for the audio thread at once.
+pub const MAX_NOTE_REQUESTS: usize = 8;
+/// Stored in an empty `note_requests` slot.
+const NO_NOTE_REQUEST: u32 = u32::MAX;
+
+/// Stored in `watch_param` while no param is watched.
+const NO_WATCH: u32 = u32::MAX;
+
+/// A note for the audio thread to play and release by itself.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoteRequest {
+    pub note: u8,
+    pub velocity: f32,
+    pub seconds: f32,
+}
+
+impl NoteRequest {
+    /// Packed into one slot: the note, the velocity in 7 bits and the length
+    /// in hundredths of a second.
+    fn pack(self) -> u32 {
+        let velocity = (self.velocity.clamp(0.0, 1.0) * 127.0).round() as u32;
+        let centis = (self.seconds * 100.0).round().clamp(1.0, 65_535.0) as u32;
+        u32::from(self.note & 0x7f) | velocity << 8 | centis << 16
+    }
+
+    fn unpack(bits: u32) -> Option<Self> {
+        (bits != NO_NOTE_REQUEST).then(|| Self {
+            note: (bits & 0x7f) as u8,
+            velocity: ((bits >> 8) & 0x7f) as f32 / 127.0,
+            seconds: (bits >> 16) as f32 / 100.0,
+        })
+    }
+}
+
+fn probe_code(target: ProbeTarget) -> u8 {
+    ProbeTarget::ALL.iter().position(|t| *t == target).unwrap_or(0) as u8
+}
+
+fn probe_from_code(code: u8) -> Option<ProbeTarget> {
+    ProbeTarget::ALL.get(usize::from(code)).copied()
+}
+
+struct VoiceSlot {
+    note: AtomicU32,
+    /// `f32` bits of the sounding pitch (fractional MIDI note).
+    pitch: AtomicU32,
+    gliding: AtomicBool,
+    age_samples: AtomicU32,
+    releasing: AtomicBool,
+    /// Index into [`EnvelopeStage::ALL`].
+    stage: AtomicU8,
+}
+
+impl Default for VoiceSlot {
+    fn default() -> Self {
+        Self {
+            note: AtomicU32::new(NO_NOTE),
+            pitch: AtomicU32::new(0),
+            gliding: AtomicBool::new(false),
+            age_samples: AtomicU32::new(0),
+            releasing: AtomicBool::new(false),
+            stage: AtomicU8::new(0),
+        }
+    }
+}
+
+/// One sounding voice, as handed to [`Diagnostics::publish_voice`].
+pub struct VoiceSnapshot {
+    pub note: u8,
+    /// Sounding pitch as a fractional MIDI note, including glide and pitch
+    /// modulation.
+    pub pitch: f32,
+    pub gliding: bool,
+    pub age_samples: u32,
+    pub releasing: bool,
+    /// The amp envelope's stage.
+    pub stage: EnvelopeStage,
+}
+
+/// Lock-free engine state shared between the audio thread and its readers.
+pub struct Diagnostics {
+    /// `f32` bits, so readers can turn sample counts into milliseconds.
+    sample_rate: AtomicU32,
+    active_voices: AtomicU32,
+    total_steals: AtomicU32,
+    /// `f32` bits; measured over roughly one-second windows.
+    steals_per_second: AtomicU32,
+    /// Voice filters reset after producing NaN/Inf.
+    filter_resets: AtomicU32,
+    /// `f32` bits of the last output analysis window's RMS level.
+    output_rms: AtomicU32,
+    /// `f32` bits of its brightness in Hz (see `dsp::analysis`).
+    brightness_hz: AtomicU32,
+    /// Analysis windows published so far, so readers can wait for a fresh one.
+    analysis_windows: AtomicU32,
+    /// A modulator probe asked for by the scope or the tools, not yet started.
+    probe_request: AtomicU8,
+    /// Set by the editor's panic shortcut until the audio thread releases
+    /// every note.
+    panic_request: AtomicBool,
+    /// What the current trace shows.
+    trace_target: AtomicU8,
+    /// `f32` bits of the seconds between trace points.
+    trace_spacing: AtomicU32,
+    /// Points written so far; [`TRACE_POINTS`] once the trace is done.
+    trace_len: AtomicU32,
+    /// Traces started so far, so readers can tell a fresh one apart.
+    traces: AtomicU32,
+    /// `f32` bits of each trace point.
+    trace: [AtomicU32; TRACE_POINTS],
+    /// `f32` bits of each mod source's recent values, a ring per source in
+    /// [`ModSource`] index order.
+    source_history: [[AtomicU32; SOURCE_POINTS]; ModSource::ALL.len()],
+    /// Source points written so far; the next goes at this modulo
+    /// [`SOURCE_POINTS`].
+    source_points: AtomicU32,
+    /// Index into `param_map()` of the param the automation preview watches,
+    /// or [`NO_WATCH`].
+    watch_param: AtomicU32,
+    /// `f32` bits of the watched param's recent normalized values, as the
+    /// host set them and as the engine smoothed them; rings like
+    /// `source_history`.
+    watch_set: [AtomicU32; WATCH_POINTS],
+    watch_smoothed: [AtomicU32; WATCH_POINTS],
+    /// Watch points written since the watched param last changed.
+    watch_points: AtomicU32,
+    /// Packed [`NoteRequest`]s not yet picked up.
+    note_requests: [AtomicU32; MAX_NOTE_REQUESTS],
+    /// `f32` bits of the tempo the last block played at.
+    tempo: AtomicU32,
+    /// `f64` bits of the song position in beats at the last block's start;
+    /// NaN while the transport is stopped or doesn't report one.
+    song_beats: AtomicU64,
+    /// Where `tempo` came from, as a [`TempoSource`] index.
+    tempo_source: AtomicU8,
+    playing: AtomicBool,
+    /// The host's time signature, numerator in the high half; 0 when it
+    /// doesn't report one.
+    time_signature: AtomicU32,
+    /// `f32` bits of the smoothed DSP load (see `quality.rs`).
+    dsp_load: AtomicU32,
+    /// The most samples the host said it would ask for at once.
+    max_buffer_size: AtomicU32,
+    /// Samples in the last block.
+    block_size: AtomicU32,
+    /// The auto-quality unison cap the last block played with.
+    unison_cap: AtomicU32,
+    voices: [VoiceSlot; NUM_VOICES],
+    /// Samples processed since the plugin loaded or the usage was reset.
+    usage_samples: AtomicU64,
+    /// Those of them with at least one voice sounding.
+    played_samples: AtomicU64,
+    /// Sounding voices summed over every sample.
+    voice_samples: AtomicU64,
+    peak_voices: AtomicU32,
+    /// Samples each waveform sounded on an audible oscillator, in
+    /// [`Waveform::ALL`] order; two oscillators on one waveform count twice.
+    waveform_samples: [AtomicU64; Waveform::ALL.len()],
+    /// Time spent in `process`, in nanoseconds.
+    busy_nanos: AtomicU64,
+    /// `f32` bits of the highest smoothed DSP load.
+    peak_load: AtomicU32,
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self {
+            sample_rate: AtomicU32::new(44100.0f32.to_bits()),
+            active_voices: AtomicU32::new(0),
+            total_steals: AtomicU32::new(0),
+            steals_per_second: AtomicU32::new(0),
+            filter_resets: AtomicU32::new(0),
+            output_rms: AtomicU32::new(0),
+            brightness_hz: AtomicU32::new(0),
+            analysis_windows: AtomicU32::new(0),
+            probe_request: AtomicU8::new(NO_PROBE),
+            panic_request: AtomicBool::new(false),
+            trace_target: AtomicU8::new(NO_PROBE),
+            trace_spacing: AtomicU32::new(0),
+            trace_len: AtomicU32::new(0),
+            traces: AtomicU32::new(0),
+            trace: std::array::from_fn(|_| AtomicU32::new(0)),
+            source_history: std::array::from_fn(|_| std::array::from_fn(|_| AtomicU32::new(0))),
+            source_points: AtomicU32::new(0),
+            watch_param: AtomicU32::new(NO_WATCH),
+            watch_set: std::array::from_fn(|_| AtomicU32::new(0)),
+            watch_smoothed: std::array::from_fn(|_| AtomicU32::new(0)),
+            watch_points: AtomicU32::new(0),
+            note_requests: std::array::from_fn(|_| AtomicU32::new(NO_NOTE_REQUEST)),
+            tempo: AtomicU32::new(120.0f32.to_bits()),
+            song_beats: AtomicU64::new(f64::NAN.to_bits()),
+            tempo_source: AtomicU8::new(TempoSource::Default as u8),
+            playing: AtomicBool::new(false),
+            time_signature: AtomicU32::new(0),
+            dsp_load: AtomicU32::new(0),
+            max_buffer_size: AtomicU32::new(0),
+            block_size: AtomicU32::new(0),
+            unison_cap: AtomicU32::new(MAX_UNISON as u32),
+            voices: std::array::from_fn(|_| VoiceSlot::default()),
+            usage_samples: AtomicU64::new(0),
+            played_samples: AtomicU64::new(0),
+            voice_samples: AtomicU64::new(0),
+            peak_voices: AtomicU32::new(0),
+            waveform_samples: std::array::from_fn(|_| AtomicU64::new(0)),
+            busy_nanos: AtomicU64::new(0),
+            peak_load: AtomicU32::new(0),
+        }
+    }
+
+    // --- Audio-thread side (RT-safe: relaxed stores only) --------------------
+
+    pub fn set_sample_rate(&self, sample_rate: f32) {
+        self.sample_rate
+            .store(sample_rate.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn set_max_buffer_size(&self, samples: u32) {
+        self.max_buffer_size.store(samples, Ordering::Relaxed);
+    }
+
+    /// Publish one voice slot, or mark it idle with `None`.
+    #[inline]
+    pub fn publish_voice(&self, index: usize, voice: Option<VoiceSnapshot>) {
+        let Some(slot) = self.voices.get(index) else {
+            return;
+        };
+        let Some(v) = voice else {
+            slot.note.store(NO_NOTE, Ordering::Relaxed);
+            return;
+        };
+        slot.note.store(u32::from(v.note), Ordering::Relaxed);
+        slot.pitch.store(v.pitch.to_bits(), Ordering::Relaxed);
+        slot.gliding.store(v.gliding, Ordering::Relaxed);
+        slot.age_samples.store(v.age_samples, Ordering::Relaxed);
+        slot.releasing.store(v.releasing, Ordering::Relaxed);
+        slot.stage.store(v.stage as u8, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn set_active_voices(&self, count: u32) {
+        self.active_voices.store(count, Ordering::Relaxed);
+    }
+
+    /// Count one voice stolen from a still-sounding note.
+    #[inline]
+    pub fn record_steal(&self) {
+        self.total_steals.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn set_steals_per_second(&self, rate: f32) {
+        self.steals_per_second
+            .store(rate.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Count `count` voice filters reset by the NaN/Inf scrubber.
+    #[inline]
+    pub fn record_filter_resets(&self, count: u32) {
+        if count > 0 {
+            self.filter_resets.fetch_add(count, Ordering::Relaxed);
+        }
+    }
+
+    /// Publish the block's tempo and transport state.
+    #[inline]
+    pub fn publish_transport(&self, transport: TransportInfo) {
+        self.tempo.store(transport.bpm.to_bits(), Ordering::Relaxed);
+        let beats = transport.song_beats.unwrap_or(f64::NAN);
+        self.song_beats.store(beats.to_bits(), Ordering::Relaxed);
+        self.tempo_source
+            .store(transport.tempo_source as u8, Ordering::Relaxed);
+        self.playing.store(transport.playing, Ordering::Relaxed);
+        let signature = transport
+            .time_signature
+            .map_or(0, |(num, den)| (num.min(0xffff) << 16) | den.min(0xffff));
+        self.time_signature.store(signature, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn set_block_size(&self, samples: usize) {
+        self.block_size.store(samples as u32, Ordering::Relaxed);
+    }
+
+    /// Publish the DSP load and the unison cap auto quality has set.
+    #[inline]
+    pub fn publish_quality(&self, load: f32, unison_cap: usize) {
+        self.dsp_load.store(load.to_bits(), Ordering::Relaxed);
+        self.unison_cap.store(unison_cap as u32, Ordering::Relaxed);
+    }
+
+    /// Add one block to the session usage.
+    #[inline]
+    pub fn record_usage(&self, block: UsageBlock) {
+        let samples = block.samples as u64;
+        self.usage_samples.fetch_add(samples, Ordering::Relaxed);
+        if block.voices > 0 {
+            self.played_samples.fetch_add(samples, Ordering::Relaxed);
+            let voice_samples = samples * u64::from(block.voices);
+            self.voice_samples
+                .fetch_add(voice_samples, Ordering::Relaxed);
+            self.peak_voices.fetch_max(block.voices, Ordering::Relaxed);
+            for waveform in block.sounding.into_iter().flatten() {
+                let counter = &self.waveform_samples[waveform.to_index()];
+                counter.fetch_add(samples, Ordering::Relaxed);
+            }
+        }
+        let busy_nanos = (block.busy_seconds * 1e9) as u64;
+        self.busy_nanos.fetch_add(busy_nanos, Ordering::Relaxed);
+        // Non-negative floats order the same as their bits.
+        self.peak_load
+            .fetch_max(block.load.max(0.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Publish one output analysis window.
+    #[inline]
+    pub fn publish_brightness(&self, reading: BrightnessReading) {
+        self.output_rms.store(reading.rms.to_bits(), Ordering::Relaxed);
+        self.brightness_hz
+            .store(reading.hz.to_bits(), Ordering::Relaxed);
+        self.analysis_windows.fetch_add(1, Ordering::Release);
+    }
+
+    /// Take the pending modulator probe request, if any.
+    #[inline]
+    pub fn take_probe_request(&self) -> Option<ProbeTarget> {
+        probe_from_code(self.probe_request.swap(NO_PROBE, Ordering::Relaxed))
+    }
+
+    /// Whether a panic was asked for since the last call.
+    #[inline]
+    pub fn take_panic_request(&self) -> bool {
+        self.panic_request.swap(false, Ordering::Relaxed)
+    }
+
+    /// Publish one probe trace point; point `0` starts a new trace.
+    #[inline]
+    pub fn publish_trace_point(&self, point: TracePoint) {
+        let Some(slot) = self.trace.get(point.index) else {
+            return;
+        };
+        if point.index == 0 {
+            self.trace_len.store(0, Ordering::Release);
+            self.trace_target.store(probe_code(point.target), Ordering::Relaxed);
+            self.trace_spacing
+                .store(point.spacing.to_bits(), Ordering::Relaxed);
+            self.traces.fetch_add(1, Ordering::Relaxed);
+        }
+        slot.store(point.value.to_bits(), Ordering::Relaxed);
+        self.trace_len
+            .store(point.index as u32 + 1, Ordering::Release);
+    }
+
+    /// Publish every mod source's current value to the source monitor.
+    #[inline]
+    pub fn publish_sources(&self, sources: &ModSources) {
+        let written = self.source_points.load(Ordering::Relaxed);
+        let slot = written as usize % SOURCE_POINTS;
+        for (history, source) in self.source_history.iter().zip(ModSource::ALL) {
+            history[slot].store(sources.get(source).to_bits(), Ordering::Relaxed);
+        }
+        self.source_points
+            .store(written.wrapping_add(1), Ordering::Release);
+    }
+
+    /// The param the automation preview watches, by its `param_map()` index.
+    #[inline]
+    pub fn watched_param(&self) -> Option<usize> {
+        let index = self.watch_param.load(Ordering::Relaxed);
+        (index != NO_WATCH).then_some(index as usize)
+    }
+
+    /// Empty the watch history, for a newly watched param.
+    #[inline]
+    pub fn restart_watch(&self) {
+        self.watch_points.store(0, Ordering::Release);
+    }
+
+    /// Publish the watched param's current normalized value, as set and as
+    /// smoothed.
+    #[inline]
+    pub fn publish_watch(&self, set: f32, smoothed: f32) {
+        let written = self.watch_points.load(Ordering::Relaxed);
+        let slot = written as usize % WATCH_POINTS;
+        self.watch_set[slot].store(set.to_bits(), Ordering::Relaxed);
+        self.watch_smoothed[slot].store(smoothed.to_bits(), Ordering::Relaxed);
+        self.watch_points
+            .store(written.wrapping_add(1), Ordering::Release);
+    }
+
+    /// Take the notes waiting to be played.
+    #[inline]
+    pub fn take_note_requests(&self) -> [Option<NoteRequest>; MAX_NOTE_REQUESTS] {
+        std::array::from_fn(|slot| {
+            NoteRequest::unpack(self.note_requests[slot].swap(NO_NOTE_REQUEST, Ordering::Relaxed))
+        })
+    }
+
+    // --- Reader side ---------------------------------------------------------
+
+    /// Ask the audio thread to fire `target` without a note and trace it. The
+    /// trace starts with the next process block, so it only runs while the
+    /// host is processing audio.
+    pub fn request_probe(&self, target: ProbeTarget) {
+        self.probe_request
+            .store(probe_code(target), Ordering::Relaxed);
+    }
+
+    /// Ask the audio thread to release every note from its next process
+    /// block.
+    pub fn request_panic(&self) {
+        self.panic_request.store(true, Ordering::Relaxed);
+    }
+
+    /// Ask the audio thread to play `request` from its next process block.
+    /// Returns `false` if every slot is still waiting.
+    pub fn request_note(&self, request: NoteRequest) -> bool {
+        self.note_requests.iter().any(|slot| {
+            slot.compare_exchange(
+                NO_NOTE_REQUEST,
+                request.pack(),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            )
+            .is_ok()
+        })
+    }
+
+    /// How many requested notes the audio thread hasn't picked up yet.
+    pub fn pending_note_requests(&self) -> usize {
+        self.note_requests
+            .iter()
+            .filter(|slot| slot.load(Ordering::Relaxed) != NO_NOTE_REQUEST)
+            .count()
+    }
+
+    /// How many traces have been started; changes when a requested probe
+    /// begins.
+    pub fn traces(&self) -> u32 {
+        self.traces.load(Ordering::Relaxed)
+    }
+
+    /// The latest (possibly still running) probe trace, or `None` before the
+    /// first one.
+    pub fn trace(&self) -> Option<ModTrace> {
+        let target = probe_from_code(self.trace_target.load(Ordering::Relaxed))?;
+        let len = self.trace_len.load(Ordering::Acquire) as usize;
+        let values = self.trace[..len.min(TRACE_POINTS)]
+            .iter()
+            .map(|v| f32::from_bits(v.load(Ordering::Relaxed)))
+            .collect();
+        Some(ModTrace {
+            target,
+            spacing: f32::from_bits(self.trace_spacing.load(Ordering::Relaxed)),
+            done: len >= TRACE_POINTS,
+            values,
+        })
+    }
+
+    /// `source`'s recent values, oldest first and
+    /// [`SOURCE_SECONDS`](crate::dsp::probe::SOURCE_SECONDS) long once the
+    /// history has filled. The per-voice sources follow the newest voice.
+    pub fn source_history(&self, source: ModSource) -> Vec<f32> {
+        let written = self.source_points.load(Ordering::Acquire) as usize;
+        let history = &self.source_history[source.to_index()];
+        let len = written.min(SOURCE_POINTS);
+        (written - len..written)
+            .map(|point| f32::from_bits(history[point % SOURCE_POINTS].load(Ordering::Relaxed)))
+            .collect()
+    }
+
+    /// Watch the param at `index` in `param_map()` in the automation preview,
+    /// or stop watching with `None`. Its history starts over.
+    pub fn watch_param(&self, index: Option<usize>) {
+        let code = index.map_or(NO_WATCH, |index| index as u32);
+        self.watch_param.store(code, Ordering::Relaxed);
+    }
+
+    /// The watched param's recent normalized values as `(set, smoothed)`,
+    /// oldest first and
+    /// [`WATCH_SECONDS`](crate::watch::WATCH_SECONDS) long once the history
+    /// has filled.
+    pub fn watch_history(&self) -> Vec<(f32, f32)> {
+        let written = self.watch_points.load(Ordering::Acquire) as usize;
+        let len = written.min(WATCH_POINTS);
+        let load = |ring: &[AtomicU32; WATCH_POINTS], point: usize| {
+            f32::from_bits(ring[point % WATCH_POINTS].load(Ordering::Relaxed))
+        };
+        (written - len..written)
+            .map(|point| (load(&self.watch_set, point), load(&self.watch_smoothed, point)))
+            .collect()
+    }
+
+    /// How many analysis windows have been published; changes when a new
+    /// [`Diagnostics::brightness`] reading is available.
+    pub fn analysis_windows(&self) -> u32 {
+        self.analysis_windows.load(Ordering::Acquire)
+    }
+
+    /// The latest output analysis window.
+    pub fn brightness(&self) -> BrightnessReading {
+        BrightnessReading {
+            rms: f32::from_bits(self.output_rms.load(Ordering::Relaxed)),
+            hz: f32::from_bits(self.brightness_hz.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// The tempo the engine is playing at, in BPM.
+    pub fn tempo(&self) -> f32 {
+        f32::from_bits(self.tempo.load(Ordering::Relaxed))
+    }
+
+    /// Where the host's playback is, in quarter-note beats from the start of
+    /// the song; `None` while it's stopped.
+    pub fn song_position(&self) -> Option<f64> {
+        let beats = f64::from_bits(self.song_beats.load(Ordering::Relaxed));
+        (!beats.is_nan()).then_some(beats)
+    }
+
+    /// The tempo and transport state the last block played with.
+    pub fn transport(&self) -> TransportInfo {
+        let signature = self.time_signature.load(Ordering::Relaxed);
+        TransportInfo {
+            bpm: self.tempo(),
+            tempo_source: TempoSource::ALL
+                .get(usize::from(self.tempo_source.load(Ordering::Relaxed)))
+                .copied()
+                .unwrap_or_default(),
+            playing: self.playing.load(Ordering::Relaxed),
+            song_beats: self.song_position(),
+            time_signature: (signature != 0).then_some((signature >> 16, signature & 0xffff)),
+        }
+    }
+
+    /// Total voice filters reset after producing NaN/Inf since the plugin
+    /// loaded.
+    pub fn filter_resets(&self) -> u32 {
+        self.filter_resets.load(Ordering::Relaxed)
+    }
+
+    /// Engine health counters, as returned by the `get_diagnostics` tool.
+    /// Allocates; never call from `process`.
+    pub fn engine_stats(&self) -> EngineStats {
+        let voices = self.voice_stats();
+        EngineStats {
+            sample_rate: f32::from_bits(self.sample_rate.load(Ordering::Relaxed)),
+            max_buffer_size: self.max_buffer_size.load(Ordering::Relaxed),
+            block_size: self.block_size.load(Ordering::Relaxed),
+            active_voices: voices.active_voices,
+            voices: voices.voices,
+            filter_resets: self.filter_resets(),
+            total_steals: self.total_steals.load(Ordering::Relaxed),
+            output_rms: self.brightness().rms,
+            brightness_hz: self.brightness().hz,
+            dsp_load: f32::from_bits(self.dsp_load.load(Ordering::Relaxed)),
+            unison_cap: self.unison_cap.load(Ordering::Relaxed),
+            last_error: crate::logging::last_problem().map(|line| line.to_string()),
+        }
+    }
+
+    /// The session's usage, as returned by the `get_usage_stats` tool and
+    /// shown in the stats panel. Allocates; never call from `process`.
+    pub fn usage_stats(&self) -> UsageStats {
+        let sample_rate = f32::from_bits(self.sample_rate.load(Ordering::Relaxed)).max(1.0);
+        let seconds = |samples: u64| samples as f32 / sample_rate;
+        let session_seconds = seconds(self.usage_samples.load(Ordering::Relaxed));
+        let played = self.played_samples.load(Ordering::Relaxed);
+        let voice_samples = self.voice_samples.load(Ordering::Relaxed);
+        let average_polyphony = if played == 0 {
+            0.0
+        } else {
+            voice_samples as f32 / played as f32
+        };
+
+        let counts = self
+            .waveform_samples
+            .each_ref()
+            .map(|samples| samples.load(Ordering::Relaxed));
+        let total: u64 = counts.iter().sum();
+        let mut waveforms: Vec<WaveformUsage> = Waveform::ALL
+            .into_iter()
+            .zip(counts)
+            .filter(|(_, samples)| *samples > 0)
+            .map(|(waveform, samples)| WaveformUsage {
+                waveform: wave_to_id(waveform),
+                seconds: seconds(samples),
+                share: samples as f32 / total as f32,
+            })
+            .collect();
+        waveforms.sort_by(|a, b| b.seconds.total_cmp(&a.seconds));
+
+        let busy_seconds = self.busy_nanos.load(Ordering::Relaxed) as f32 * 1e-9;
+        let average_dsp_load = if session_seconds > 0.0 {
+            busy_seconds / session_seconds
+        } else {
+            0.0
+        };
+        let peak_dsp_load = f32::from_bits(self.peak_load.load(Ordering::Relaxed));
+        UsageStats {
+            session_seconds,
+            playing_seconds: seconds(played),
+            average_polyphony,
+            peak_polyphony: self.peak_voices.load(Ordering::Relaxed),
+            waveforms,
+            average_dsp_load,
+            peak_dsp_load,
+            cpu_headroom: (1.0 - average_dsp_load).max(0.0),
+        }
+    }
+
+    /// Start the session usage over, e.g. before trying a patch out.
+    pub fn reset_usage(&self) {
+        let counters = [
+            &self.usage_samples,
+            &self.played_samples,
+            &self.voice_samples,
+            &self.busy_nanos,
+        ];
+        for counter in counters.into_iter().chain(&self.waveform_samples) {
+            counter.store(0, Ordering::Relaxed);
+        }
+        self.peak_voices.store(0, Ordering::Relaxed);
+        self.peak_load.store(0, Ordering::Relaxed);
+    }
+
+    /// Snapshot the voice allocator's state. Allocates; never call from
+    /// `process`.
+    pub fn voice_stats(&self) -> VoiceStats {
+        let sample_rate = f32::from_bits(self.sample_rate.load(Ordering::Relaxed)).max(1.0);
+        let voices = self
+            .voices
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, v)| {
+                let note = v.note.load(Ordering::Relaxed);
+                let pitch = f32::from_bits(v.pitch.load(Ordering::Relaxed));
+                (note != NO_NOTE).then(|| VoiceInfo {
+                    slot,
+                    note: note as u8,
+                    note_name: note_name(note as u8),
+                    pitch,
+                    pitch_name: pitch_name(pitch),
+                    gliding: v.gliding.load(Ordering::Relaxed),
+                    age_ms: v.age_samples.load(Ordering::Relaxed) as f32 / sample_rate * 1000.0,
+                    releasing: v.releasing.load(Ordering::Relaxed),
+                    stage: EnvelopeStage::ALL
+                        .get(usize::from(v.stage.load(Ordering::Relaxed)))
+                        .map_or("idle", |stage| stage.id()),
+                })
+            })
+            .collect();
+
+        VoiceStats {
+            active_voices: self.active_voices.load(Ordering::Relaxed),
+            max_voices: NUM_VOICES,
+            total_steals: self.total_steals.load(Ordering::Relaxed),
+            steals_per_second: f32::from_bits(self.steals_per_second.load(Ordering::Relaxed)),
+            voices,
+        }
+    }
+}
+
+/// A modulator probe's trace, as drawn by the mod scope and returned by the
+/// `trigger_envelope` / `trigger_lfo` tools.
+#[derive(Clone, Debug)]
+pub struct ModTrace {
+    pub target: ProbeTarget,
+    /// Seconds between values.
+    pub spacing: f32,
+    /// Whether the probe has finished.
+    pub done: bool,
+    /// `[0, 1]` for envelopes, `[-1, 1]` for the LFO.
+    pub values: Vec<f32>,
+}
+
+/// Where the engine's tempo comes from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TempoSource {
+    /// The host's transport.
+    Host,
+    /// Tapped in with the tap-tempo trigger, for hosts that don't send one.
+    Tapped,
+    /// Neither: the fallback 120 BPM.
+    #[default]
+    Default,
+}
+
+impl TempoSource {
+    const ALL: [Self; 3] = [Self::Host, Self::Tapped, Self::Default];
+}
+
+/// The tempo and transport state, as the editor's footer shows it and the
+/// `get_transport` tool returns it.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct TransportInfo {
+    pub bpm: f32,
+    pub tempo_source: TempoSource,
+    pub playing: bool,
+    /// Quarter-note beats from the start of the song; `None` while stopped.
+    pub song_beats: Option<f64>,
+    /// e.g. `(6, 8)`; `None` when the host doesn't say, and 4/4 is assumed.
+    pub time_signature: Option<(u32, u32)>,
+}
+
+impl TransportInfo {
+    /// The bar's length in quarter-note beats, e.g. three for 6/8.
+    pub fn beats_per_bar(&self) -> f64 {
+        match self.time_signature {
+            Some((num, den)) if num > 0 && den > 0 => f64::from(num) * 4.0 / f64::from(den),
+            _ => 4.0,
+        }
+    }
+
+    /// The 1-based bar and beat (in the time signature's unit) playback is
+    /// at, while playing.
+    pub fn bar_and_beat(&self) -> Option<(u64, u32)> {
+        let beats = self.song_beats?.max(0.0);
+        let per_bar = self.beats_per_bar();
+        let unit = self.time_signature.map_or(1.0, |(_, den)| f64::from(den.max(1)) / 4.0);
+        let bar = (beats / per_bar).floor();
+        let beat = ((beats - bar * per_bar) * unit).floor();
+        Some((bar as u64 + 1, beat as u32 + 1))
+    }
+
+    /// One line for the footer, e.g. "128.0 BPM · 4/4 · ▶ 12.3".
+    pub fn summary(&self) -> String {
+        let (num, den) = self.time_signature.unwrap_or((4, 4));
+        let mut summary = format!("{:.1} BPM", self.bpm);
+        match self.tempo_source {
+            TempoSource::Host => {}
+            TempoSource::Tapped => summary.push_str(" (tapped)"),
+            TempoSource::Default => summary.push_str(" (no host tempo)"),
+        }
+        summary.push_str(&format!(" · {num}/{den} · "));
+        match (self.playing, self.bar_and_beat()) {
+            (true, Some((bar, beat))) => summary.push_str(&format!("▶ {bar}.{beat}")),
+            (true, None) => summary.push_str("▶ playing"),
+            (false, _) => summary.push_str("■ stopped"),
+        }
+        summary
+    }
+}
+
+/// What the `get_diagnostics` tool returns.
+#[derive(Clone, Debug, Serialize)]
+pub struct EngineStats {
+    pub sample_rate: f32,
+    /// The largest block the host said it would process; `0` before it has
+    /// set the plugin up.
+    pub max_buffer_size: u32,
+    /// Samples in the last block, which hosts may vary below the largest.
+    pub block_size: u32,
+    pub active_voices: u32,
+    /// Sounding voices only, as `get_voice_stats` lists them.
+    pub voices: Vec<VoiceInfo>,
+    /// Voice filters that produced NaN/Inf (e.g. under extreme drive and
+    /// resonance) and were reset to silence.
+    pub filter_resets: u32,
+    pub total_steals: u32,
+    /// Output level over the last ~100 ms.
+    pub output_rms: f32,
+    /// Output brightness over the same window: the spectrum's power-weighted
+    /// RMS frequency in Hz. Only meaningful while something is sounding.
+    pub brightness_hz: f32,
+    /// Time spent in `process` over the audio's length, smoothed; over `1`
+    /// the host can't keep up.
+    pub dsp_load: f32,
+    /// Most unison voices per oscillator auto quality allows; 8 is no cap.
+    pub unison_cap: u32,
+    /// The most recent warning or error the plugin logged, as the log viewer
+    /// shows it.
+    pub last_error: Option<String>,
+}
+
+/// One block's worth of session usage, from `SineSynth::process`.
+#[derive(Clone, Copy, Debug)]
+pub struct UsageBlock {
+    pub samples: usize,
+    /// Voices sounding at the block's end.
+    pub voices: u32,
+    /// Each oscillator's waveform, `None` while it's silent, muted or soloed
+    /// out.
+    pub sounding: [Option<Waveform>; 3],
+    /// Time the block took to process.
+    pub busy_seconds: f32,
+    /// The smoothed DSP load after it.
+    pub load: f32,
+}
+
+/// What the `get_usage_stats` tool returns and the stats panel displays: how
+/// the synth has been used since it loaded or the usage was last reset.
+#[derive(Clone, Debug, Serialize)]
+pub struct UsageStats {
+    pub session_seconds: f32,
+    /// Time with at least one voice sounding.
+    pub playing_seconds: f32,
+    /// Voices sounding on average while any are.
+    pub average_polyphony: f32,
+    pub peak_polyphony: u32,
+    /// Most used first.
+    pub waveforms: Vec<WaveformUsage>,
+    /// Time spent processing over the audio's length, for the whole session.
+    pub average_dsp_load: f32,
+    /// The highest smoothed load, as `get_diagnostics` reports it.
+    pub peak_dsp_load: f32,
+    /// `1 - average_dsp_load`: the share of real time left over.
+    pub cpu_headroom: f32,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct WaveformUsage {
+    pub waveform: &'static str,
+    /// Oscillator time: two oscillators on it for a second count two.
+    pub seconds: f32,
+    /// Share of all oscillator time.
+    pub share: f32,
+}
+
+/// What the `get_voice_stats` tool returns and the voice monitor displays.
+#[derive(Clone, Debug, Serialize)]
+pub struct VoiceStats {
+    pub active_voices: u32,
+    pub max_voices: usize,
+    pub total_steals: u32,
+    pub steals_per_second: f32,
+    /// Sounding voices only, in slot order.
+    pub voices: Vec<VoiceInfo>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct VoiceInfo {
+    pub slot: usize,
+    pub note: u8,
+    pub note_name: String,
+    /// What the voice is actually sounding, as a fractional MIDI note: `note`
+    /// plus any glide in progress and pitch modulation.
+    pub pitch: f32,
+    /// `pitch` as a note name and cents offset (`"C4 +25c"`).
+    pub pitch_name: String,
+    pub gliding: bool,
+    /// Time spent in the current envelope stage — the allocator's stealing
+    /// priority (the oldest voice is stolen first).
+    pub age_ms: f32,
+    pub releasing: bool,
+    /// The amp envelope's stage: `attack`, `hold`, `decay`, `sustain` or
+    /// `release`.
+    pub stage: &'static str,
+}
+
+/// A fractional MIDI note as the nearest note name plus a cents offset:
+/// `60.25` -> `"C4 +25c"`, `60.0` -> `"C4"`.
+pub fn pitch_name(pitch: f32) -> String {
+    let nearest = pitch.round().clamp(0.0, 127.0);
+    let cents = ((pitch - nearest) * 100.0).round() as i32;
+    if cents == 0 {
+        note_name(nearest as u8)
+    } else {
+        format!("{} {:+}c", note_name(nearest as u8), cents)
+    }
+}
+
+/// `60` -> `"C4"`.
+pub fn note_name(note: u8) -> String {
+    const NAMES: [&str; 12] = [
+        "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+    ];
+    let octave = note as i32 / 12 - 1;
+    format!("{}{}", NAMES[note as usize % 12], octave)
+}
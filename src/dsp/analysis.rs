@@ -0,0 +1,71 @@
+//! Output brightness analysis, cheap enough to run on every sample.
+//!
+//! Rather than an FFT, brightness is read from how fast the signal moves: the
+//! power of the first difference over the power of the signal itself. For a
+//! sine at `f` that ratio is exactly `4·sin²(π·f/sr)`, so inverting it gives a
+//! frequency, and for a mix it gives the power-weighted RMS frequency of the
+//! spectrum — a centroid-style measure that rises and falls with the filter,
+//! waveform and harmonics the way a listener's sense of "bright" does.
+
+use std::f32::consts::PI;
+
+/// How much audio each published measurement covers.
+const WINDOW_SECONDS: f32 = 0.1;
+
+/// One finished analysis window.
+#[derive(Clone, Copy, Debug)]
+pub struct BrightnessReading {
+    /// RMS level of the window.
+    pub rms: f32,
+    /// Power-weighted RMS frequency in Hz; `0` for a silent window.
+    pub hz: f32,
+}
+
+pub struct BrightnessAnalyzer {
+    prev: f32,
+    power: f64,
+    diff_power: f64,
+    samples: u32,
+}
+
+impl Default for BrightnessAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BrightnessAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            prev: 0.0,
+            power: 0.0,
+            diff_power: 0.0,
+            samples: 0,
+        }
+    }
+
+    /// Feeds one (mono) sample; returns a reading each time a window fills.
+    #[inline]
+    pub fn push(&mut self, sample: f32, sample_rate: f32) -> Option<BrightnessReading> {
+        let diff = sample - self.prev;
+        self.prev = sample;
+        self.power += f64::from(sample * sample);
+        self.diff_power += f64::from(diff * diff);
+        self.samples += 1;
+        if (self.samples as f32) < WINDOW_SECONDS * sample_rate {
+            return None;
+        }
+
+        let rms = (self.power / f64::from(self.samples)).sqrt() as f32;
+        let hz = if self.power > 0.0 {
+            let ratio = (self.diff_power / self.power) as f32;
+            (ratio.sqrt() / 2.0).min(1.0).asin() * sample_rate / PI
+        } else {
+            0.0
+        };
+        self.power = 0.0;
+        self.diff_power = 0.0;
+        self.samples = 0;
+        Some(BrightnessReading { rms, hz })
+    }
+}
@@ -0,0 +1,244 @@
+//! Arpeggiator.
+//!
+//! Held keys go in here instead of to the voices; the arp plays them back one
+//! at a time, a step per note division at the block's tempo, as note on/off
+//! events for the caller to play, send out as MIDI or both. The clock starts
+//! when the first key goes down, so the first note is never late, and stops
+//! when the last one comes up.
+//!
+//! Swing and the timing humanize only ever delay a step's note within its
+//! step, never past the next one. Their random draws, the velocity humanize's
+//! and Random mode's come from one generator, reseeded from a fixed seed (when
+//! one is set) at the start of playback so renders repeat exactly.
+
+use super::rng::XorShift;
+use super::scale::Quantizer;
+use crate::params::{ArpMode, ArpParams};
+
+/// Every MIDI note can be held at once.
+const MAX_HELD: usize = 128;
+/// Latest a step's note may play, as a fraction of the step.
+const MAX_DELAY: f64 = 0.9;
+
+/// A note the arp starts or stops.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArpEvent {
+    On { note: u8, velocity: f32 },
+    Off { note: u8 },
+}
+
+/// The arp's settings for one process block.
+pub struct ArpFrame {
+    enabled: bool,
+    mode: ArpMode,
+    octaves: usize,
+    /// Step length in quarter-note beats.
+    step_beats: f64,
+    /// Note length as a fraction of a step.
+    gate: f64,
+    /// Every second step's delay, as a fraction of a step.
+    swing: f64,
+    /// Largest random delay, as a fraction of a step.
+    timing_jitter: f64,
+    velocity_jitter: f32,
+    /// 0 for no fixed seed.
+    seed: u32,
+    /// Every note the arp plays is snapped to this.
+    scale: Quantizer,
+    /// Beats per sample at the block's tempo.
+    beats_per_sample: f64,
+}
+
+impl ArpFrame {
+    pub fn new(p: &ArpParams, scale: Quantizer, bpm: f32, sample_rate: f32) -> Self {
+        Self {
+            enabled: p.enabled.value(),
+            mode: p.mode.value(),
+            octaves: p.octaves.value().max(1) as usize,
+            step_beats: f64::from(p.division.value().beats()),
+            gate: f64::from(p.gate.value()),
+            swing: f64::from(p.swing.value()) * 0.5,
+            timing_jitter: f64::from(p.timing_jitter.value()),
+            velocity_jitter: p.velocity_jitter.value(),
+            seed: p.seed.value().max(0) as u32,
+            scale,
+            beats_per_sample: f64::from(bpm) / 60.0 / f64::from(sample_rate),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+pub struct Arpeggiator {
+    /// Held keys and their velocities, in the order they went down: the
+    /// first `held_count` entries. A fixed array rather than a `Vec`, so
+    /// holding keys never allocates on the audio thread.
+    held: [(u8, f32); MAX_HELD],
+    held_count: usize,
+    /// Beats into the current step.
+    beats: f64,
+    /// Steps played since the keys went down.
+    step: usize,
+    /// Beats into the current step at which its note plays; `None` once it
+    /// has.
+    due_at: Option<f64>,
+    /// The note the arp has on, and the beats it has been on for.
+    sounding: Option<u8>,
+    sounding_beats: f64,
+    /// The fixed seed `rng` was last seeded from; 0 for none.
+    seed: u32,
+    rng: XorShift,
+}
+
+impl Default for Arpeggiator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Arpeggiator {
+    pub fn new() -> Self {
+        Self {
+            held: [(0, 0.0); MAX_HELD],
+            held_count: 0,
+            beats: 0.0,
+            step: 0,
+            due_at: Some(0.0),
+            sounding: None,
+            sounding_beats: 0.0,
+            seed: 0,
+            rng: XorShift::from_time(),
+        }
+    }
+
+    pub fn key_down(&mut self, note: u8, velocity: f32) {
+        if !self.held().iter().any(|(held, _)| *held == note) && self.held_count < MAX_HELD {
+            self.held[self.held_count] = (note, velocity);
+            self.held_count += 1;
+        }
+    }
+
+    pub fn key_up(&mut self, note: u8) {
+        if let Some(index) = self.held().iter().position(|(held, _)| *held == note) {
+            self.held.copy_within(index + 1..self.held_count, index);
+            self.held_count -= 1;
+        }
+    }
+
+    fn held(&self) -> &[(u8, f32)] {
+        &self.held[..self.held_count]
+    }
+
+    /// Stop dead, forgetting the sounding note too.
+    pub fn reset(&mut self) {
+        self.held_count = 0;
+        self.restart();
+        self.sounding = None;
+        self.reseed();
+    }
+
+    /// Forget the held keys. The sounding note still gets its off, on the
+    /// next sample.
+    pub fn release_all(&mut self) {
+        self.held_count = 0;
+    }
+
+    /// Start the random draws over from the fixed seed, if there is one.
+    /// Called when playback starts.
+    pub fn reseed(&mut self) {
+        if self.seed != 0 {
+            self.rng = XorShift::new(self.seed);
+        }
+    }
+
+    fn restart(&mut self) {
+        self.beats = 0.0;
+        self.step = 0;
+        self.due_at = Some(0.0);
+    }
+
+    /// Advances one sample. Returns the note changes it makes: the old note's
+    /// off before the new note's on.
+    pub fn next(&mut self, f: &ArpFrame) -> [Option<ArpEvent>; 2] {
+        if f.seed != self.seed {
+            self.seed = f.seed;
+            self.reseed();
+        }
+        if !f.enabled || self.held_count == 0 {
+            self.restart();
+            return [self.release(), None];
+        }
+
+        let mut events = [None; 2];
+        if self.sounding_beats >= f.gate * f.step_beats {
+            events[0] = self.release();
+        }
+        if self.due_at.is_some_and(|at| self.beats >= at) {
+            self.due_at = None;
+            events[0] = events[0].or_else(|| self.release());
+            let (note, velocity) = self.pick(f);
+            let velocity = velocity * (1.0 + f.velocity_jitter * self.rng.next_bipolar());
+            events[1] = Some(ArpEvent::On {
+                note,
+                velocity: velocity.clamp(0.01, 1.0),
+            });
+            self.sounding = Some(note);
+            self.sounding_beats = 0.0;
+            self.step += 1;
+        }
+
+        self.beats += f.beats_per_sample;
+        self.sounding_beats += f.beats_per_sample;
+        if self.beats >= f.step_beats {
+            self.beats -= f.step_beats;
+            self.due_at = Some(self.delay(f) * f.step_beats);
+        }
+        events
+    }
+
+    /// How late the next step's note plays, as a fraction of the step: the
+    /// swing on every second step plus the timing humanize.
+    fn delay(&mut self, f: &ArpFrame) -> f64 {
+        let swing = if self.step % 2 == 1 { f.swing } else { 0.0 };
+        let jitter = f.timing_jitter * f64::from(self.rng.next_f32());
+        (swing + jitter).min(MAX_DELAY)
+    }
+
+    fn release(&mut self) -> Option<ArpEvent> {
+        self.sounding.take().map(|note| ArpEvent::Off { note })
+    }
+
+    /// The note (and its key's velocity) for the current step.
+    fn pick(&mut self, f: &ArpFrame) -> (u8, f32) {
+        // Sorted on the stack; `next` runs on the audio thread.
+        let count = self.held_count;
+        let mut keys = self.held;
+        let keys = &mut keys[..count];
+        match f.mode {
+            ArpMode::Up | ArpMode::UpDown => keys.sort_unstable_by_key(|(note, _)| *note),
+            ArpMode::Down => keys.sort_unstable_by_key(|(note, _)| std::cmp::Reverse(*note)),
+            ArpMode::Played | ArpMode::Random => {}
+        }
+
+        let len = count * f.octaves;
+        let index = match f.mode {
+            ArpMode::UpDown if len > 1 => {
+                let period = 2 * len - 2;
+                let i = self.step % period;
+                if i < len { i } else { period - i }
+            }
+            ArpMode::Random => ((self.rng.next_f32() * len as f32) as usize).min(len - 1),
+            _ => self.step % len,
+        };
+        let octave = match f.mode {
+            // Down starts from the top octave.
+            ArpMode::Down => f.octaves - 1 - index / count,
+            _ => index / count,
+        };
+        let (note, velocity) = keys[index % count];
+        let note = (usize::from(note) + 12 * octave).min(127) as u8;
+        (f.scale.snap(note), velocity)
+    }
+}
@@ -0,0 +1,44 @@
+//! The pitch-bend wheel, smoothed so a wide bend glides instead of stepping
+//! through the controller's values.
+
+pub struct PitchBend {
+    /// The wheel's last position, `-1..=1`.
+    target: f32,
+    value: f32,
+}
+
+impl Default for PitchBend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PitchBend {
+    pub fn new() -> Self {
+        Self {
+            target: 0.0,
+            value: 0.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Takes a `MidiPitchBend` value: `0..=1`, centred on `0.5`.
+    pub fn set(&mut self, normalized: f32) {
+        self.target = (normalized * 2.0 - 1.0).clamp(-1.0, 1.0);
+    }
+
+    /// Advances one sample towards the wheel's position, with a time constant
+    /// of `smoothing` seconds (`0` follows it exactly). Returns `-1..=1`.
+    pub fn next(&mut self, smoothing: f32, sample_rate: f32) -> f32 {
+        if smoothing <= 0.0 {
+            self.value = self.target;
+        } else {
+            let coef = 1.0 - (-1.0 / (smoothing * sample_rate)).exp();
+            self.value += coef * (self.target - self.value);
+        }
+        self.value
+    }
+}
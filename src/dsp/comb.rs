@@ -0,0 +1,140 @@
+//! A tuned comb per voice, between the oscillators and the filter: a delay
+//! one period of the played note long, fed back through a damping lowpass.
+//! Fed continuously it rings at the note's harmonics, for metallic and
+//! resonator timbres; in pluck mode only the note's first period goes in and
+//! the loop rings on alone, which is Karplus-Strong string synthesis with the
+//! oscillators as the exciter.
+
+/// Delay line length: one period at 8192 samples is under 6 Hz at 48 kHz and
+/// about 23 Hz at 192 kHz, below any note worth combing. A power of two, so
+/// positions wrap with a mask.
+const MAX_DELAY: usize = 8192;
+/// Largest feedback (the param's maximum): just short of ringing forever.
+const MAX_FEEDBACK: f32 = 0.99;
+/// Rate the damping lowpass's coefficient is given at; other rates get the
+/// coefficient with the same time constant, so the loop loses its highs at the
+/// same speed, in Hz, whatever the host runs at.
+const REFERENCE_RATE: f32 = 48_000.0;
+
+/// The comb's settings for one sample frame, shared by every voice.
+#[derive(Clone, Copy)]
+pub(crate) struct CombFrame {
+    pub(crate) enabled: bool,
+    pub(crate) pluck: bool,
+    pub(crate) feedback: f32,
+    /// Coefficient of the one-pole lowpass in the loop at [`REFERENCE_RATE`];
+    /// `1` passes everything.
+    lowpass: f32,
+    /// Share of the comb in the output, `0..=1`.
+    pub(crate) mix: f32,
+}
+
+impl CombFrame {
+    /// `damping` is `0..=1`: how fast the loop loses its highs.
+    pub(crate) fn new(enabled: bool, pluck: bool, feedback: f32, damping: f32, mix: f32) -> Self {
+        Self {
+            enabled,
+            pluck,
+            feedback: feedback.clamp(0.0, MAX_FEEDBACK),
+            lowpass: 1.0 - 0.95 * damping.clamp(0.0, 1.0),
+            mix: mix.clamp(0.0, 1.0),
+        }
+    }
+}
+
+pub(crate) struct CombFilter {
+    /// Allocated once, at the longest delay, so nothing on the audio thread
+    /// allocates.
+    buffer: Box<[f32; MAX_DELAY]>,
+    write: usize,
+    /// The loop's lowpass state.
+    damped: f32,
+    /// Samples since the note started, for pluck mode's single period.
+    age: f32,
+    /// The loop holds a finished note's tail; cleared when the comb next runs.
+    stale: bool,
+    /// The loop's lowpass coefficient at the running rate, and the frame
+    /// coefficient and rate it was computed for, so it is only recomputed on
+    /// change.
+    lowpass: f32,
+    lowpass_for: (f32, f32),
+}
+
+impl CombFilter {
+    pub(crate) fn new() -> Self {
+        Self {
+            buffer: Box::new([0.0; MAX_DELAY]),
+            write: 0,
+            damped: 0.0,
+            age: 0.0,
+            stale: false,
+            lowpass: 1.0,
+            lowpass_for: (-1.0, 0.0),
+        }
+    }
+
+    /// Clears the loop.
+    pub(crate) fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.write = 0;
+        self.damped = 0.0;
+        self.age = 0.0;
+        self.stale = false;
+    }
+
+    /// Starts a note: pluck mode excites the loop again. On a voice that was
+    /// idle, the old tail is cleared, but only once the comb runs, so a voice
+    /// with the comb off never pays for it. A retriggered voice keeps ringing.
+    pub(crate) fn note_on(&mut self, was_idle: bool) {
+        self.age = 0.0;
+        self.stale |= was_idle;
+    }
+
+    /// Combs `input` at `frequency` (the voice's sounding pitch, in Hz).
+    pub(crate) fn process(
+        &mut self,
+        input: f32,
+        f: &CombFrame,
+        frequency: f32,
+        sample_rate: f32,
+    ) -> f32 {
+        if !f.enabled {
+            return input;
+        }
+        if self.stale {
+            self.reset();
+        }
+        if (f.lowpass, sample_rate) != self.lowpass_for {
+            self.lowpass_for = (f.lowpass, sample_rate);
+            self.lowpass = 1.0 - (1.0 - f.lowpass).powf(REFERENCE_RATE / sample_rate);
+        }
+        // The loop's lowpass delays it too; take that off so it stays in tune.
+        let lowpass_delay = (1.0 - self.lowpass) / self.lowpass;
+        let period = sample_rate / frequency.max(1.0);
+        let delay = (period - lowpass_delay).clamp(1.0, (MAX_DELAY - 2) as f32);
+
+        // Linear interpolation between the two samples around the delay.
+        let whole = delay as usize;
+        let frac = delay - whole as f32;
+        let mask = MAX_DELAY - 1;
+        let newer = self.buffer[(self.write + MAX_DELAY - whole) & mask];
+        let older = self.buffer[(self.write + MAX_DELAY - whole - 1) & mask];
+        let delayed = newer + (older - newer) * frac;
+        self.damped += self.lowpass * (delayed - self.damped);
+
+        let excitation = if f.pluck {
+            // One period in, then the string rings on its own.
+            if self.age < period { input } else { 0.0 }
+        } else {
+            // Fed continuously, the resonances build up to `1 / (1 -
+            // feedback)`; scaled back so the level holds as feedback rises.
+            input * (1.0 - f.feedback)
+        };
+        self.age += 1.0;
+        let output = excitation + f.feedback * self.damped;
+        self.buffer[self.write] = output;
+        self.write = (self.write + 1) & mask;
+
+        input + (output - input) * f.mix
+    }
+}
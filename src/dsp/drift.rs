@@ -0,0 +1,83 @@
+//! Analog drift: slow, per-voice random wander of pitch and level plus a fixed
+//! per-note cutoff offset, emulating the instability of hardware oscillators.
+//!
+//! Each voice owns a [`Drift`] seeded from its slot index, so voices wander
+//! independently (and reproducibly across sessions). All outputs are in
+//! `[-1, 1]`; the global `analog` amount scales them into real units in
+//! `Voice::render`.
+
+use super::rng::XorShift;
+
+/// How often (seconds) each wander picks a new target.
+const RETARGET_SECONDS: f32 = 0.4;
+/// One-pole glide rate towards the target, in Hz. Low enough to read as drift
+/// rather than vibrato.
+const GLIDE_HZ: f32 = 0.8;
+
+/// A smoothed random walk towards periodically re-rolled targets.
+struct Wander {
+    value: f32,
+    target: f32,
+}
+
+impl Wander {
+    fn advance(&mut self, coef: f32) -> f32 {
+        self.value += (self.target - self.value) * coef;
+        self.value
+    }
+}
+
+pub struct Drift {
+    rng: XorShift,
+    pitch: Wander,
+    level: Wander,
+    /// Re-rolled on each note-on, held for the note.
+    cutoff: f32,
+    /// Samples until the next retarget.
+    countdown: u32,
+}
+
+impl Drift {
+    pub fn new(seed: u32) -> Self {
+        let mut rng = XorShift::new(seed.wrapping_mul(0x9E37_79B9).wrapping_add(1));
+        // Start each voice somewhere different rather than all at centre.
+        let pitch = rng.next_bipolar();
+        let level = rng.next_bipolar();
+        Self {
+            rng,
+            pitch: Wander {
+                value: pitch,
+                target: pitch,
+            },
+            level: Wander {
+                value: level,
+                target: level,
+            },
+            cutoff: 0.0,
+            countdown: 0,
+        }
+    }
+
+    /// Rolls the per-note cutoff offset.
+    pub fn note_on(&mut self) {
+        self.cutoff = self.rng.next_bipolar();
+    }
+
+    /// Per-note cutoff offset in `[-1, 1]`.
+    pub fn cutoff(&self) -> f32 {
+        self.cutoff
+    }
+
+    /// Advances one sample and returns `(pitch, level)`, each in `[-1, 1]`.
+    pub fn next(&mut self, sample_rate: f32) -> (f32, f32) {
+        if self.countdown == 0 {
+            self.pitch.target = self.rng.next_bipolar();
+            self.level.target = self.rng.next_bipolar();
+            self.countdown = (RETARGET_SECONDS * sample_rate) as u32;
+        }
+        self.countdown -= 1;
+
+        let coef = (GLIDE_HZ / sample_rate).min(1.0);
+        (self.pitch.advance(coef), self.level.advance(coef))
+    }
+}
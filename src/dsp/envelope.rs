@@ -1,18 +1,50 @@
-#[derive(Clone, Debug, PartialEq)]
-enum EnvelopeStage {
+/// With declick on, the output never swings full scale faster than this, so a
+/// 1 ms attack or release (or a retrigger mid-note) can't produce a step.
+const DECLICK_SECONDS: f32 = 0.0005;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvelopeStage {
     Idle,
     Attack,
+    /// Holds full level between attack and decay (the "H" in AHDSR).
+    Hold,
     Decay,
     Sustain,
     Release,
 }
 
+impl EnvelopeStage {
+    pub const ALL: [EnvelopeStage; 6] = [
+        Self::Idle,
+        Self::Attack,
+        Self::Hold,
+        Self::Decay,
+        Self::Sustain,
+        Self::Release,
+    ];
+
+    /// Its name in the AI tools.
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Idle => "idle",
+            Self::Attack => "attack",
+            Self::Hold => "hold",
+            Self::Decay => "decay",
+            Self::Sustain => "sustain",
+            Self::Release => "release",
+        }
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct Envelope {
     stage: EnvelopeStage,
     current_level: f32,
+    /// `current_level` after the declick slew limiter; what `process` returns.
+    output_level: f32,
     sample_rate: f32,
     samples_elapsed: u32,
+    attack_start_level: f32,
     release_start_level: f32,
 }
 
@@ -21,8 +53,10 @@ impl Envelope {
         Self {
             stage: EnvelopeStage::Idle,
             current_level: 0.0,
+            output_level: 0.0,
             sample_rate,
             samples_elapsed: 0,
+            attack_start_level: 0.0,
             release_start_level: 0.0,
         }
     }
@@ -34,17 +68,30 @@ impl Envelope {
     pub(crate) fn note_on(&mut self) {
         self.stage = EnvelopeStage::Attack;
         self.samples_elapsed = 0;
+        self.attack_start_level = self.output_level;
     }
 
     pub(crate) fn note_off(&mut self) {
         if self.stage != EnvelopeStage::Idle {
-            self.release_start_level = self.current_level;
+            self.release_start_level = self.output_level;
             self.stage = EnvelopeStage::Release;
             self.samples_elapsed = 0;
         }
     }
 
-    pub(crate) fn process(&mut self, attack: f32, decay: f32, sustain: f32, release: f32) -> f32 {
+    /// Advances one sample. With `declick`, a retriggered attack rises from the
+    /// level the envelope was at instead of restarting from silence, and the
+    /// output is slew-limited (see [`DECLICK_SECONDS`]); the envelope only goes
+    /// idle once the limited output has reached zero.
+    pub(crate) fn process(
+        &mut self,
+        attack: f32,
+        hold: f32,
+        decay: f32,
+        sustain: f32,
+        release: f32,
+        declick: bool,
+    ) -> f32 {
         match self.stage {
             EnvelopeStage::Idle => {
                 self.current_level = 0.0;
@@ -53,11 +100,24 @@ impl Envelope {
                 let attack_samples = (attack * self.sample_rate).max(1.0) as u32;
                 if self.samples_elapsed >= attack_samples {
                     self.current_level = 1.0;
-                    self.stage = EnvelopeStage::Decay;
+                    // A zero hold goes straight on, exactly as an ADSR would.
+                    self.stage = if hold > 0.0 {
+                        EnvelopeStage::Hold
+                    } else {
+                        EnvelopeStage::Decay
+                    };
                     self.samples_elapsed = 0;
                 } else {
                     let progress = self.samples_elapsed as f32 / attack_samples as f32;
-                    self.current_level = 1.0 - (-5.0 * progress).exp();
+                    let start = if declick { self.attack_start_level } else { 0.0 };
+                    self.current_level = start + (1.0 - start) * (1.0 - (-5.0 * progress).exp());
+                }
+            }
+            EnvelopeStage::Hold => {
+                self.current_level = 1.0;
+                if self.samples_elapsed >= (hold * self.sample_rate) as u32 {
+                    self.stage = EnvelopeStage::Decay;
+                    self.samples_elapsed = 0;
                 }
             }
             EnvelopeStage::Decay => {
@@ -78,8 +138,10 @@ impl Envelope {
                 let release_samples = (release * self.sample_rate).max(1.0) as u32;
                 if self.samples_elapsed >= release_samples {
                     self.current_level = 0.0;
-                    self.stage = EnvelopeStage::Idle;
-                    self.samples_elapsed = 0;
+                    if !declick || self.output_level == 0.0 {
+                        self.stage = EnvelopeStage::Idle;
+                        self.samples_elapsed = 0;
+                    }
                 } else {
                     let progress = self.samples_elapsed as f32 / release_samples as f32;
                     self.current_level = self.release_start_level * (-5.0 * progress).exp();
@@ -87,14 +149,30 @@ impl Envelope {
             }
         }
 
+        if declick {
+            let max_step = 1.0 / (DECLICK_SECONDS * self.sample_rate).max(1.0);
+            let step = (self.current_level - self.output_level).clamp(-max_step, max_step);
+            self.output_level += step;
+        } else {
+            self.output_level = self.current_level;
+        }
+
         self.samples_elapsed += 1;
-        self.current_level
+        self.output_level
     }
 
     pub(crate) fn is_active(&self) -> bool {
         self.stage != EnvelopeStage::Idle
     }
 
+    pub(crate) fn is_releasing(&self) -> bool {
+        self.stage == EnvelopeStage::Release
+    }
+
+    pub(crate) fn stage(&self) -> EnvelopeStage {
+        self.stage
+    }
+
     /// How long the current note has been in its stage, used for voice-stealing
     /// priority (oldest voice wins).
     pub(crate) fn samples_elapsed(&self) -> u32 {
@@ -1,110 +1,423 @@
-use crate::FilterMode;
+use std::f32::consts::PI;
 
-#[derive(Clone)]
-pub(crate) struct BiquadFilter {
+use crate::{DriveCharacter, FilterMode};
+
+/// Input level the drive's makeup gain holds steady when compensating: about
+/// a single oscillator at full level.
+const MAKEUP_REFERENCE: f32 = 0.5;
+/// DC offset the asymmetric curves add is removed below this.
+const DC_BLOCK_HZ: f32 = 10.0;
+/// Highest cutoff or formant centre, whatever the rate: the knob's top. A
+/// modulated cutoff clamped at the Nyquist limit instead would reach further
+/// at 96 or 192 kHz, and a high-pass pushed past the audible range would go
+/// silent there while it still let the top octave through at 48 kHz.
+const MAX_CUTOFF_HZ: f32 = 20_000.0;
+
+/// Band-pass sections in formant mode, one per formant; the other modes use
+/// only the first.
+const NUM_FORMANTS: usize = 3;
+/// Formant mode keeps only narrow bands of the signal; this brings its level
+/// back near the other modes'.
+const FORMANT_MAKEUP: f32 = 2.0;
+/// Formant centre (Hz), level (dB) and bandwidth (Hz) for A, E, I, O and U
+/// sung by a bass, after the classic Csound table. The vowel knob morphs
+/// between neighbours.
+const VOWELS: [[(f32, f32, f32); NUM_FORMANTS]; 5] = [
+    // A
+    [
+        (800.0, 0.0, 80.0),
+        (1150.0, -6.0, 90.0),
+        (2900.0, -32.0, 120.0),
+    ],
+    // E
+    [
+        (400.0, 0.0, 60.0),
+        (1600.0, -24.0, 80.0),
+        (2700.0, -30.0, 120.0),
+    ],
+    // I
+    [
+        (250.0, 0.0, 60.0),
+        (1750.0, -30.0, 90.0),
+        (2600.0, -16.0, 100.0),
+    ],
+    // O
+    [
+        (400.0, 0.0, 40.0),
+        (750.0, -11.0, 80.0),
+        (2400.0, -21.0, 100.0),
+    ],
+    // U
+    [
+        (350.0, 0.0, 40.0),
+        (600.0, -20.0, 80.0),
+        (2400.0, -32.0, 100.0),
+    ],
+];
+
+/// The saturation ahead of the filter for one sample frame, shared by every
+/// voice.
+///
+/// The input is pushed into a curve by `drive`, then scaled back by a makeup
+/// gain. Without compensation that gain only normalizes full scale to `1`, so
+/// quiet signals come out up to `drive` times louder; with it, a signal at
+/// [`MAKEUP_REFERENCE`] comes out at the level it went in, so turning drive up
+/// mostly adds harmonics. The curve fades in over the first unit of drive, so
+/// `1` is clean and there's no jump leaving it.
+#[derive(Clone, Copy)]
+pub(crate) struct Drive {
+    gain: f32,
+    character: DriveCharacter,
+    makeup: f32,
+    /// Share of the shaped signal, `0..=1`.
+    wet: f32,
+}
+
+impl Drive {
+    pub(crate) fn new(drive: f32, character: DriveCharacter, compensate: bool) -> Self {
+        let gain = drive.max(1.0);
+        let level = if compensate { MAKEUP_REFERENCE } else { 1.0 };
+        Self {
+            gain,
+            character,
+            makeup: level / shape(character, level * gain).abs().max(1e-6),
+            wet: (gain - 1.0).min(1.0),
+        }
+    }
+}
+
+/// The saturation curves, each with a slope of about `1` at zero and
+/// flattening out towards `±1`.
+fn shape(character: DriveCharacter, x: f32) -> f32 {
+    /// Offset that makes the asymmetric curve clip its positive half later.
+    const BIAS: f32 = 0.4;
+    // Linear up to `knee`, then rounding off into `1`.
+    let soft_knee = |x: f32, knee: f32| {
+        if x <= knee {
+            x
+        } else {
+            knee + (1.0 - knee) * ((x - knee) / (1.0 - knee)).tanh()
+        }
+    };
+    match character {
+        DriveCharacter::Soft => x.tanh(),
+        DriveCharacter::Asymmetric => (x + BIAS).tanh() - BIAS.tanh(),
+        // Clean until a knee, earlier on the negative half, like a triode.
+        DriveCharacter::Tube => {
+            if x >= 0.0 {
+                soft_knee(x, 0.6)
+            } else {
+                -soft_knee(-x, 0.3)
+            }
+        }
+    }
+}
+
+/// One set of biquad coefficients (already normalized by `a0`).
+#[derive(Clone, Copy, Default)]
+struct Coefficients {
     b0: f32,
     b1: f32,
     b2: f32,
     a1: f32,
     a2: f32,
+}
+
+impl Coefficients {
+    /// A pass-through filter.
+    const IDENTITY: Self = Self {
+        b0: 1.0,
+        b1: 0.0,
+        b2: 0.0,
+        a1: 0.0,
+        a2: 0.0,
+    };
+
+    fn new(mode: FilterMode, cutoff: f32, resonance: f32, sample_rate: f32) -> Self {
+        let cutoff = cutoff.clamp(20.0, max_cutoff(sample_rate));
+        let q = (resonance * 10.0 + 0.5).max(0.1);
+
+        let omega = 2.0 * std::f32::consts::PI * cutoff / sample_rate;
+        let cos_omega = omega.cos();
+        let sin_omega = omega.sin();
+        let alpha = sin_omega / (2.0 * q);
+        let norm = 1.0 + alpha;
+
+        let (b0, b1, b2) = match mode {
+            FilterMode::LowPass => (
+                (1.0 - cos_omega) / 2.0,
+                1.0 - cos_omega,
+                (1.0 - cos_omega) / 2.0,
+            ),
+            FilterMode::HighPass => (
+                (1.0 + cos_omega) / 2.0,
+                -(1.0 + cos_omega),
+                (1.0 + cos_omega) / 2.0,
+            ),
+            // Formant mode builds its own band-passes (see `formants`).
+            FilterMode::BandPass | FilterMode::Formant => (alpha, 0.0, -alpha),
+            FilterMode::Notch => (1.0, -2.0 * cos_omega, 1.0),
+        };
+
+        Self {
+            b0: b0 / norm,
+            b1: b1 / norm,
+            b2: b2 / norm,
+            a1: -2.0 * cos_omega / norm,
+            a2: (1.0 - alpha) / norm,
+        }
+    }
+
+    /// A band-pass peaking at `gain` at `center`.
+    fn band_pass(center: f32, q: f32, gain: f32, sample_rate: f32) -> Self {
+        let omega = 2.0 * PI * center.clamp(20.0, max_cutoff(sample_rate)) / sample_rate;
+        let alpha = omega.sin() / (2.0 * q.max(0.1));
+        let norm = 1.0 + alpha;
+        Self {
+            b0: gain * alpha / norm,
+            b1: 0.0,
+            b2: -gain * alpha / norm,
+            a1: -2.0 * omega.cos() / norm,
+            a2: (1.0 - alpha) / norm,
+        }
+    }
+
+    /// Every section's coefficients for these settings. `vowel` (`0..=1`,
+    /// A to U) only matters in formant mode, where resonance narrows the
+    /// formants and the cutoff isn't used.
+    fn sections(
+        mode: FilterMode,
+        cutoff: f32,
+        resonance: f32,
+        vowel: f32,
+        sample_rate: f32,
+    ) -> [Self; NUM_FORMANTS] {
+        if mode != FilterMode::Formant {
+            let mut sections = [Self::IDENTITY; NUM_FORMANTS];
+            sections[0] = Self::new(mode, cutoff, resonance, sample_rate);
+            return sections;
+        }
+        let position = vowel.clamp(0.0, 1.0) * (VOWELS.len() - 1) as f32;
+        let index = (position as usize).min(VOWELS.len() - 2);
+        let frac = position - index as f32;
+        let lerp = |a: f32, b: f32| a + (b - a) * frac;
+        let sharpness = 0.25 + 1.5 * resonance;
+        std::array::from_fn(|formant| {
+            let (hz, db, bandwidth) = VOWELS[index][formant];
+            let (next_hz, next_db, next_bandwidth) = VOWELS[index + 1][formant];
+            let center = lerp(hz, next_hz);
+            let q = center / lerp(bandwidth, next_bandwidth) * sharpness;
+            let gain = 10.0_f32.powf(lerp(db, next_db) / 20.0);
+            Self::band_pass(center, q, gain, sample_rate)
+        })
+    }
+
+    /// Per-sample increment that walks `self` to `target` in `steps` samples.
+    fn step_towards(self, target: Self, steps: u32) -> Self {
+        let n = steps.max(1) as f32;
+        Self {
+            b0: (target.b0 - self.b0) / n,
+            b1: (target.b1 - self.b1) / n,
+            b2: (target.b2 - self.b2) / n,
+            a1: (target.a1 - self.a1) / n,
+            a2: (target.a2 - self.a2) / n,
+        }
+    }
+
+    fn add(&mut self, step: Self) {
+        self.b0 += step.b0;
+        self.b1 += step.b1;
+        self.b2 += step.b2;
+        self.a1 += step.a1;
+        self.a2 += step.a2;
+    }
+}
+
+/// One biquad section: its coefficients, their glide and its state.
+#[derive(Clone)]
+struct Section {
+    coeffs: Coefficients,
+    /// Added to `coeffs` each sample while `ramp_remaining > 0`.
+    step: Coefficients,
+    ramp_remaining: u32,
 
     x1: f32,
     x2: f32,
     y1: f32,
     y2: f32,
-
-    sample_rate: f32,
 }
 
-impl BiquadFilter {
-    pub(crate) fn new(sample_rate: f32) -> Self {
+impl Section {
+    fn new() -> Self {
         Self {
-            b0: 1.0,
-            b1: 0.0,
-            b2: 0.0,
-            a1: 0.0,
-            a2: 0.0,
+            coeffs: Coefficients::IDENTITY,
+            step: Coefficients::default(),
+            ramp_remaining: 0,
             x1: 0.0,
             x2: 0.0,
             y1: 0.0,
             y2: 0.0,
-            sample_rate,
         }
     }
 
-    pub(crate) fn set_coefficients(&mut self, mode: FilterMode, cutoff: f32, resonance: f32) {
-        let cutoff = cutoff.clamp(20.0, self.sample_rate * 0.49);
-        let q = (resonance * 10.0 + 0.5).max(0.1);
-
-        let omega = 2.0 * std::f32::consts::PI * cutoff / self.sample_rate;
-        let cos_omega = omega.cos();
-        let sin_omega = omega.sin();
-        let alpha = sin_omega / (2.0 * q);
+    fn set(&mut self, coeffs: Coefficients) {
+        self.coeffs = coeffs;
+        self.ramp_remaining = 0;
+    }
 
-        match mode {
-            FilterMode::LowPass => {
-                let norm = 1.0 + alpha;
-                self.b0 = (1.0 - cos_omega) / 2.0 / norm;
-                self.b1 = (1.0 - cos_omega) / norm;
-                self.b2 = (1.0 - cos_omega) / 2.0 / norm;
-                self.a1 = -2.0 * cos_omega / norm;
-                self.a2 = (1.0 - alpha) / norm;
-            }
-            FilterMode::HighPass => {
-                let norm = 1.0 + alpha;
-                self.b0 = (1.0 + cos_omega) / 2.0 / norm;
-                self.b1 = -(1.0 + cos_omega) / norm;
-                self.b2 = (1.0 + cos_omega) / 2.0 / norm;
-                self.a1 = -2.0 * cos_omega / norm;
-                self.a2 = (1.0 - alpha) / norm;
-            }
-            FilterMode::BandPass => {
-                let norm = 1.0 + alpha;
-                self.b0 = alpha / norm;
-                self.b1 = 0.0;
-                self.b2 = -alpha / norm;
-                self.a1 = -2.0 * cos_omega / norm;
-                self.a2 = (1.0 - alpha) / norm;
-            }
-            FilterMode::Notch => {
-                let norm = 1.0 + alpha;
-                self.b0 = 1.0 / norm;
-                self.b1 = -2.0 * cos_omega / norm;
-                self.b2 = 1.0 / norm;
-                self.a1 = -2.0 * cos_omega / norm;
-                self.a2 = (1.0 - alpha) / norm;
-            }
-        }
+    fn ramp(&mut self, target: Coefficients, samples: u32) {
+        self.step = self.coeffs.step_towards(target, samples);
+        self.ramp_remaining = samples;
     }
 
-    pub(crate) fn process(&mut self, input: f32, drive: f32) -> f32 {
-        let driven_input = if drive > 1.0 {
-            (input * drive).tanh() / drive.tanh()
-        } else {
-            input * drive
-        };
+    fn process(&mut self, input: f32) -> f32 {
+        if self.ramp_remaining > 0 {
+            self.coeffs.add(self.step);
+            self.ramp_remaining -= 1;
+        }
 
-        let output = self.b0 * driven_input + self.b1 * self.x1 + self.b2 * self.x2
-            - self.a1 * self.y1
-            - self.a2 * self.y2;
+        let c = &self.coeffs;
+        let output =
+            c.b0 * input + c.b1 * self.x1 + c.b2 * self.x2 - c.a1 * self.y1 - c.a2 * self.y2;
 
         self.x2 = self.x1;
-        self.x1 = driven_input;
+        self.x1 = input;
         self.y2 = self.y1;
         self.y1 = output;
 
         output
     }
 
-    pub(crate) fn reset(&mut self) {
+    fn clear(&mut self) {
         self.x1 = 0.0;
         self.x2 = 0.0;
         self.y1 = 0.0;
         self.y2 = 0.0;
+        self.ramp_remaining = 0;
+    }
+}
+
+/// A biquad whose coefficients glide between updates; in formant mode, a bank
+/// of parallel band-passes, one per formant of the vowel.
+///
+/// Cutoff and resonance only change at control rate (see `dsp::mod_matrix`),
+/// and jumping straight to each new set of coefficients zippers audibly on fast
+/// sweeps. [`BiquadFilter::ramp_to`] instead interpolates the coefficients
+/// linearly across the control interval, one small step per sample.
+#[derive(Clone)]
+pub(crate) struct BiquadFilter {
+    /// Only the first runs outside formant mode.
+    sections: [Section; NUM_FORMANTS],
+    formant: bool,
+    /// Set by `reset`: the next `ramp_to` snaps instead of ramping from the
+    /// previous note's coefficients.
+    snap: bool,
+
+    /// DC blocker on what the drive adds (see `process`).
+    dc_x1: f32,
+    dc_y1: f32,
+    dc_coef: f32,
+
+    sample_rate: f32,
+}
+
+impl BiquadFilter {
+    pub(crate) fn new(sample_rate: f32) -> Self {
+        Self {
+            sections: std::array::from_fn(|_| Section::new()),
+            formant: false,
+            snap: true,
+            dc_x1: 0.0,
+            dc_y1: 0.0,
+            dc_coef: dc_coef(sample_rate),
+            sample_rate,
+        }
+    }
+
+    /// Jumps straight to the coefficients for these settings.
+    pub(crate) fn set_coefficients(
+        &mut self,
+        mode: FilterMode,
+        cutoff: f32,
+        resonance: f32,
+        vowel: f32,
+    ) {
+        let targets = Coefficients::sections(mode, cutoff, resonance, vowel, self.sample_rate);
+        for (section, target) in self.sections.iter_mut().zip(targets) {
+            section.set(target);
+        }
+        self.formant = mode == FilterMode::Formant;
+        self.snap = false;
+    }
+
+    /// Glides the coefficients to these settings over the next `samples`
+    /// samples. Snaps instead right after a `reset`, and when switching in or
+    /// out of formant mode: the sections have nothing sensible to glide from.
+    pub(crate) fn ramp_to(
+        &mut self,
+        mode: FilterMode,
+        cutoff: f32,
+        resonance: f32,
+        vowel: f32,
+        samples: u32,
+    ) {
+        let formant = mode == FilterMode::Formant;
+        if self.snap || formant != self.formant {
+            for section in &mut self.sections[1..] {
+                section.clear();
+            }
+            self.set_coefficients(mode, cutoff, resonance, vowel);
+            return;
+        }
+        let targets = Coefficients::sections(mode, cutoff, resonance, vowel, self.sample_rate);
+        let used = if formant { NUM_FORMANTS } else { 1 };
+        for (section, target) in self.sections.iter_mut().zip(targets).take(used) {
+            section.ramp(target, samples);
+        }
+    }
+
+    pub(crate) fn process(&mut self, input: f32, drive: Drive) -> f32 {
+        // Only the distortion the curve adds is DC-blocked and faded in, so a
+        // clean signal passes untouched.
+        let added = shape(drive.character, input * drive.gain) * drive.makeup - input;
+        let blocked = added - self.dc_x1 + self.dc_coef * self.dc_y1;
+        self.dc_x1 = added;
+        self.dc_y1 = blocked;
+        let driven_input = input + drive.wet * blocked;
+
+        if !self.formant {
+            return self.sections[0].process(driven_input);
+        }
+        let sum: f32 = self
+            .sections
+            .iter_mut()
+            .map(|section| section.process(driven_input))
+            .sum();
+        sum * FORMANT_MAKEUP
+    }
+
+    pub(crate) fn reset(&mut self) {
+        for section in &mut self.sections {
+            section.clear();
+        }
+        self.dc_x1 = 0.0;
+        self.dc_y1 = 0.0;
+        self.snap = true;
     }
 
     pub(crate) fn set_sample_rate(&mut self, sample_rate: f32) {
         self.sample_rate = sample_rate;
+        self.dc_coef = dc_coef(sample_rate);
         self.reset();
     }
 }
+
+/// [`MAX_CUTOFF_HZ`], or just under Nyquist at rates too low to reach it.
+fn max_cutoff(sample_rate: f32) -> f32 {
+    MAX_CUTOFF_HZ.min(sample_rate * 0.49)
+}
+
+/// One-pole highpass feedback coefficient for [`DC_BLOCK_HZ`].
+fn dc_coef(sample_rate: f32) -> f32 {
+    1.0 - 2.0 * PI * DC_BLOCK_HZ / sample_rate
+}
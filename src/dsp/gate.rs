@@ -0,0 +1,101 @@
+//! Master-bus trance gate and auto-pan.
+//!
+//! The gate steps through a 16-step on/off pattern, one step per note
+//! division. While the host plays, the step comes from its song position, so
+//! the pattern lines up with the bar and a bounce always chops the same way;
+//! stopped, it free-runs at the current tempo. The level glides between steps
+//! over a fraction of a step (never less than a millisecond, so hard chops
+//! don't click), and the auto-pan swings the output to one side per step.
+
+use crate::params::{GateParams, NUM_GATE_STEPS};
+
+/// Shortest edge fade, so a `0` smoothing setting still doesn't click.
+const MIN_FADE_SECONDS: f32 = 0.001;
+
+/// The gate's settings for one process block.
+pub struct GateFrame {
+    enabled: bool,
+    /// Step length in quarter-note beats.
+    step_beats: f64,
+    depth: f32,
+    pan: f32,
+    steps: [bool; NUM_GATE_STEPS],
+    /// Per-sample glide coefficient for the level and pan.
+    coef: f32,
+    /// Beats per sample at the block's tempo.
+    beats_per_sample: f64,
+}
+
+impl GateFrame {
+    pub fn new(p: &GateParams, bpm: f32, sample_rate: f32) -> Self {
+        let division = p.division.value();
+        let fade = (p.smoothing.value() * division.seconds(bpm)).max(MIN_FADE_SECONDS);
+        Self {
+            enabled: p.enabled.value(),
+            step_beats: f64::from(division.beats()),
+            depth: p.depth.value(),
+            pan: p.pan.value(),
+            steps: std::array::from_fn(|i| p.steps[i].on.value()),
+            coef: 1.0 - (-1.0 / (fade * sample_rate)).exp(),
+            beats_per_sample: f64::from(bpm) / 60.0 / f64::from(sample_rate),
+        }
+    }
+}
+
+pub struct TranceGate {
+    /// Position in quarter-note beats.
+    beats: f64,
+    level: f32,
+    pan: f32,
+}
+
+impl Default for TranceGate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TranceGate {
+    pub fn new() -> Self {
+        Self {
+            beats: 0.0,
+            level: 1.0,
+            pan: 0.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Lock to the host's song position (in beats) at the start of a block;
+    /// `None` keeps free-running.
+    pub fn sync(&mut self, host_beats: Option<f64>) {
+        if let Some(beats) = host_beats {
+            self.beats = beats;
+        }
+    }
+
+    #[inline]
+    pub fn process(&mut self, f: &GateFrame, [left, right]: [f32; 2]) -> [f32; 2] {
+        let position = self.beats / f.step_beats;
+        self.beats += f.beats_per_sample;
+        if !f.enabled {
+            self.level = 1.0;
+            self.pan = 0.0;
+            return [left, right];
+        }
+
+        let step = position.floor().rem_euclid(NUM_GATE_STEPS as f64) as usize;
+        let level = if f.steps[step] { 1.0 } else { 1.0 - f.depth };
+        // Even steps lean left, odd steps right.
+        let pan = if step % 2 == 0 { -f.pan } else { f.pan };
+        self.level += f.coef * (level - self.level);
+        self.pan += f.coef * (pan - self.pan);
+
+        // Balance law like the keyboard pan: only the far side is turned down.
+        let gain_l = (1.0 - self.pan).min(1.0) * self.level;
+        let gain_r = (1.0 + self.pan).min(1.0) * self.level;
+        [left * gain_l, right * gain_r]
+    }
+}
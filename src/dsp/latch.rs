@@ -0,0 +1,83 @@
+//! Latch: while it's on, a released key keeps its note sounding until the
+//! same key is played again or latch is switched off, for drones that play
+//! on hands-free.
+//!
+//! It sits in front of the arp and the voices, on keys as they come in: a
+//! latched key stays held for the arp too, so it keeps arpeggiating.
+
+/// Where a key stands while latch is on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Key {
+    /// Not latched: up, or held down.
+    Free,
+    /// Let go of, but still sounding.
+    Latched,
+    /// Played again to stop its latched note; its key-up goes nowhere.
+    Stopping,
+}
+
+pub struct Latch {
+    keys: [Key; 128],
+    /// Whether latch was on last block, to catch it going off.
+    was_on: bool,
+}
+
+impl Default for Latch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Latch {
+    pub fn new() -> Self {
+        Self {
+            keys: [Key::Free; 128],
+            was_on: false,
+        }
+    }
+
+    /// A key went down. `true` if it stops its latched note, so the caller
+    /// plays a key-up instead of the note.
+    pub fn key_down(&mut self, note: u8) -> bool {
+        let key = &mut self.keys[usize::from(note)];
+        if *key == Key::Latched {
+            *key = Key::Stopping;
+            return true;
+        }
+        *key = Key::Free;
+        false
+    }
+
+    /// A key came up. `true` if the key-up goes through; with latch on it
+    /// never does.
+    pub fn key_up(&mut self, note: u8, on: bool) -> bool {
+        let key = &mut self.keys[usize::from(note)];
+        if !on {
+            *key = Key::Free;
+            return true;
+        }
+        *key = match *key {
+            Key::Stopping => Key::Free,
+            Key::Free | Key::Latched => Key::Latched,
+        };
+        false
+    }
+
+    /// Called once a block with whether latch is on. When it has just gone
+    /// off, which keys were still latched, for the caller to release.
+    pub fn update(&mut self, on: bool) -> Option<[bool; 128]> {
+        let switched_off = self.was_on && !on;
+        self.was_on = on;
+        if !switched_off {
+            return None;
+        }
+        let latched = self.keys.map(|key| key == Key::Latched);
+        self.clear();
+        Some(latched)
+    }
+
+    /// Forget every latched note, as their voices are being stopped anyway.
+    pub fn clear(&mut self) {
+        self.keys = [Key::Free; 128];
+    }
+}
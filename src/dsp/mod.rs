@@ -1,9 +1,41 @@
 //! Pure DSP primitives. These types contain only `f32` math and depend on the
 //! parameter enums (`Waveform`, `FilterMode`) but never on `nih_plug` plumbing.
+//!
+//! The master-bus [`gate`] and [`widener`] are behind the `fx` feature.
 
+pub mod analysis;
+pub mod arp;
+pub mod bend;
+pub mod comb;
+pub mod drift;
 pub mod envelope;
 pub mod filter;
+#[cfg(feature = "fx")]
+pub mod gate;
+pub mod latch;
+pub mod mod_matrix;
+pub mod noise;
 pub mod oscillator;
+pub mod probe;
+pub mod rng;
+pub mod scale;
+pub mod similarity;
+pub mod spectrum;
+pub mod sub;
+pub mod through;
 pub mod voice;
+#[cfg(feature = "fx")]
+pub mod widener;
 
-pub use voice::{FrameParams, Voice};
\ No newline at end of file
+pub use arp::{ArpEvent, ArpFrame, Arpeggiator};
+pub use bend::PitchBend;
+#[cfg(feature = "fx")]
+pub use gate::{GateFrame, TranceGate};
+pub use latch::Latch;
+pub use mod_matrix::{EnvelopeFollower, Lfo};
+pub use probe::{ModProbe, ProbeTarget, SourceSampler};
+pub use scale::Quantizer;
+pub use through::AudioThrough;
+pub use voice::{FrameParams, ParamRead, Tuning, Voice};
+#[cfg(feature = "fx")]
+pub use widener::StereoWidener;
\ No newline at end of file
@@ -0,0 +1,259 @@
+//! Modulation matrix: a global LFO plus a few source → destination slots.
+//!
+//! # Where modulation is applied
+//!
+//! Destinations are split by what they cost to update, not by how fast the
+//! sources move:
+//!
+//! - **Per sample** — [`SampleMods`]: pitch, pulse width, level and waveform
+//!   morph. These only feed into a phase increment, a comparison or a
+//!   multiply, so applying them every sample is free and keeps fast LFOs free
+//!   of zipper noise. Future audio-rate-ish destinations (wavetable position,
+//!   FM amount) belong here too.
+//! - **Control rate** — [`ControlMods`]: filter cutoff, resonance and vowel.
+//!   Every change means recomputing biquad coefficients (a `sin`, a `cos` and
+//!   a handful of divides), so [`Voice`](super::Voice) only does it every
+//!   [`CONTROL_INTERVAL`] samples and the filter interpolates the coefficients
+//!   per sample in between, so sweeps stay zipper-free. The filter envelope
+//!   goes through the same path.
+//!
+//! Sources are all bipolar or unipolar values in `[-1, 1]`; a slot's `amount`
+//! scales the source into the destination's range (see [`ModDestination`]).
+//! The LFO and the sidechain follower are global; the rest are per voice.
+//! The release blip is the one source that only moves after note-off.
+
+use super::ParamRead;
+use crate::params::{ModParams, NUM_MOD_SLOTS};
+use crate::{ModDestination, ModSource, Waveform};
+use std::f32::consts::TAU;
+
+/// Samples between control-rate updates (filter coefficients). Well under a
+/// millisecond at any common sample rate.
+pub const CONTROL_INTERVAL: u32 = 32;
+
+/// Destination ranges at `amount = ±1`.
+const PITCH_RANGE_SEMITONES: f32 = 12.0;
+const PULSE_WIDTH_RANGE: f32 = 0.45;
+const CUTOFF_RANGE_OCTAVES: f32 = 4.0;
+
+/// The free-running global LFO. One instance lives in `SineSynth` and is shared
+/// by every voice, so all voices move together.
+pub struct Lfo {
+    /// Normalized phase in `[0, 1)`.
+    phase: f32,
+}
+
+impl Default for Lfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Lfo {
+    pub fn new() -> Self {
+        Self { phase: 0.0 }
+    }
+
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    /// Set the phase a synced LFO has at song position `beats`, had it run
+    /// from the start of the song at one cycle per `cycle_beats`.
+    pub fn align(&mut self, beats: f64, cycle_beats: f32) {
+        self.phase = (beats / f64::from(cycle_beats)).rem_euclid(1.0) as f32;
+    }
+
+    /// Returns the current value in `[-1, 1]` and advances one sample.
+    pub fn next(&mut self, shape: Waveform, rate_hz: f32, sample_rate: f32) -> f32 {
+        let p = self.phase;
+        let value = match shape {
+            Waveform::Sine => (p * TAU).sin(),
+            Waveform::Triangle => 1.0 - 4.0 * (p - 0.5).abs(),
+            Waveform::Sawtooth => 2.0 * p - 1.0,
+            Waveform::Square => {
+                if p < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+        };
+
+        self.phase += rate_hz / sample_rate;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+        }
+        value
+    }
+}
+
+/// Tracks the level of the sidechain input so another track can drive the
+/// matrix (e.g. a kick ducking the filter). One instance lives in `SineSynth`.
+pub struct EnvelopeFollower {
+    level: f32,
+    attack_coef: f32,
+    release_coef: f32,
+}
+
+impl Default for EnvelopeFollower {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EnvelopeFollower {
+    pub fn new() -> Self {
+        Self {
+            level: 0.0,
+            attack_coef: 0.0,
+            release_coef: 0.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.level = 0.0;
+    }
+
+    /// Sets the attack/release times. Call once per block; the `exp`s are too
+    /// costly to redo every sample.
+    pub fn set_times(&mut self, attack_ms: f32, release_ms: f32, sample_rate: f32) {
+        let coef = |ms: f32| (-1000.0 / (ms.max(0.01) * sample_rate)).exp();
+        self.attack_coef = coef(attack_ms);
+        self.release_coef = coef(release_ms);
+    }
+
+    /// Follows the rectified `input` and returns the level in `[0, 1]`.
+    #[inline]
+    pub fn next(&mut self, input: f32) -> f32 {
+        let input = input.abs().min(1.0);
+        let coef = if input > self.level {
+            self.attack_coef
+        } else {
+            self.release_coef
+        };
+        self.level = input + (self.level - input) * coef;
+        self.level
+    }
+}
+
+/// One voice's source values for the current sample.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ModSources {
+    pub lfo: f32,
+    pub filter_env: f32,
+    pub velocity: f32,
+    pub sidechain: f32,
+    pub release: f32,
+}
+
+impl ModSources {
+    pub fn get(&self, source: ModSource) -> f32 {
+        match source {
+            ModSource::Lfo => self.lfo,
+            ModSource::FilterEnv => self.filter_env,
+            ModSource::Velocity => self.velocity,
+            ModSource::Sidechain => self.sidechain,
+            ModSource::Release => self.release,
+        }
+    }
+}
+
+/// Offsets for the per-sample destinations.
+#[derive(Default)]
+pub struct SampleMods {
+    /// Semitones, applied to all three oscillators.
+    pub pitch: f32,
+    /// Added to the square wave's 50% duty cycle.
+    pub pulse_width: f32,
+    /// Added to a unity voice gain (`-1` silences the voice).
+    pub level: f32,
+    /// Added to each morphing oscillator's morph position.
+    pub morph: f32,
+}
+
+/// Offsets for the control-rate destinations.
+#[derive(Default)]
+pub struct ControlMods {
+    /// Octaves, on top of the filter envelope's sweep.
+    pub cutoff: f32,
+    pub resonance: f32,
+    /// Added to the formant filter's vowel position.
+    pub vowel: f32,
+}
+
+#[derive(Clone, Copy)]
+struct ModSlot {
+    source: ModSource,
+    destination: ModDestination,
+    amount: f32,
+}
+
+/// The matrix's routing for one sample frame. Built inside
+/// [`FrameParams::next`](super::FrameParams::next) so every slot's `amount`
+/// smoother advances with the rest.
+pub struct ModFrame {
+    slots: [ModSlot; NUM_MOD_SLOTS],
+    /// Global LFO value for this sample.
+    pub lfo: f32,
+    /// Sidechain follower level for this sample.
+    pub sidechain: f32,
+    /// Seconds the release blip takes to fall by 60 dB.
+    pub release_blip: f32,
+}
+
+impl ModFrame {
+    pub fn next(p: &ModParams, read: ParamRead, lfo: f32, sidechain: f32) -> Self {
+        Self {
+            slots: std::array::from_fn(|i| {
+                let slot = &p.slots[i];
+                ModSlot {
+                    source: read.plain(&slot.source),
+                    destination: read.plain(&slot.destination),
+                    amount: read.float(&slot.amount),
+                }
+            }),
+            lfo,
+            sidechain,
+            release_blip: read.float(&p.release_blip) * 0.001,
+        }
+    }
+
+    /// Sums every slot routed to a per-sample destination.
+    pub fn sample_mods(&self, sources: &ModSources) -> SampleMods {
+        let mut mods = SampleMods::default();
+        for slot in &self.slots {
+            let value = sources.get(slot.source) * slot.amount;
+            match slot.destination {
+                ModDestination::Pitch => mods.pitch += value * PITCH_RANGE_SEMITONES,
+                ModDestination::PulseWidth => mods.pulse_width += value * PULSE_WIDTH_RANGE,
+                ModDestination::Level => mods.level += value,
+                ModDestination::Morph => mods.morph += value,
+                ModDestination::Off
+                | ModDestination::Cutoff
+                | ModDestination::Resonance
+                | ModDestination::Vowel => {}
+            }
+        }
+        mods
+    }
+
+    /// Sums every slot routed to a control-rate destination.
+    pub fn control_mods(&self, sources: &ModSources) -> ControlMods {
+        let mut mods = ControlMods::default();
+        for slot in &self.slots {
+            let value = sources.get(slot.source) * slot.amount;
+            match slot.destination {
+                ModDestination::Cutoff => mods.cutoff += value * CUTOFF_RANGE_OCTAVES,
+                ModDestination::Resonance => mods.resonance += value,
+                ModDestination::Vowel => mods.vowel += value,
+                ModDestination::Off
+                | ModDestination::Pitch
+                | ModDestination::PulseWidth
+                | ModDestination::Level
+                | ModDestination::Morph => {}
+            }
+        }
+        mods
+    }
+}
@@ -0,0 +1,52 @@
+//! Noise source: white or pink noise per voice, mixed in with the oscillators
+//! ahead of the comb and the filter, for breath, hiss and percussive attacks.
+//!
+//! Each voice owns a [`Noise`] seeded from its slot index, like its analog
+//! drift, so a chord's voices hiss independently rather than in unison.
+
+use super::rng::XorShift;
+use crate::NoiseColor;
+
+/// Brings the pink filter's output down to about white noise's level.
+const PINK_GAIN: f32 = 0.11;
+
+pub struct Noise {
+    rng: XorShift,
+    /// Paul Kellett's pink filter: white noise through seven one-poles at
+    /// spread-out corners, summed into a -3 dB/octave slope.
+    pink: [f32; 7],
+}
+
+impl Noise {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            rng: XorShift::new(seed.wrapping_mul(0x85EB_CA6B).wrapping_add(7)),
+            pink: [0.0; 7],
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.pink = [0.0; 7];
+    }
+
+    /// The next sample, in about `[-1, 1]`.
+    #[inline]
+    pub fn next(&mut self, color: NoiseColor) -> f32 {
+        let white = self.rng.next_bipolar();
+        // The pink filter runs either way, so switching colour mid-note
+        // doesn't start it from silence.
+        let b = &mut self.pink;
+        b[0] = 0.99886 * b[0] + white * 0.055_517_9;
+        b[1] = 0.99332 * b[1] + white * 0.075_075_9;
+        b[2] = 0.969 * b[2] + white * 0.153_852;
+        b[3] = 0.8665 * b[3] + white * 0.310_485_6;
+        b[4] = 0.55 * b[4] + white * 0.532_952_2;
+        b[5] = -0.7616 * b[5] - white * 0.016_898;
+        let pink = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+        b[6] = white * 0.115_926;
+        match color {
+            NoiseColor::White => white,
+            NoiseColor::Pink => pink * PINK_GAIN,
+        }
+    }
+}
@@ -1,52 +1,317 @@
-use crate::Waveform;
-use std::f32::consts::TAU;
+use crate::params::{NUM_DRAWBARS, morph_shapes};
+use crate::{DetuneCurve, Waveform};
+use std::f32::consts::{PI, TAU};
+use std::sync::OnceLock;
+
+/// Entries in the shared sine table the additive engine reads from.
+const SINE_TABLE_SIZE: usize = 2048;
+
+/// One sine cycle, built on first use (see [`UnisonOscillator::new`], which
+/// runs off the audio thread) and shared by every oscillator.
+fn sine_table() -> &'static [f32; SINE_TABLE_SIZE] {
+    static TABLE: OnceLock<[f32; SINE_TABLE_SIZE]> = OnceLock::new();
+    TABLE.get_or_init(|| std::array::from_fn(|i| (i as f32 / SINE_TABLE_SIZE as f32 * TAU).sin()))
+}
+
+/// The classic hardware super-saw's detune offsets for its outer three voice
+/// pairs (center voice first), normalized so the outermost pair sits at `±1`.
+/// Other voice counts interpolate along it.
+const SUPERSAW_SPREAD: [f32; 4] = [0.0, 0.18, 0.57, 1.0];
+
+/// How long the voices' detune offsets take to move to a new unison count or
+/// detune shape, so the change doesn't click.
+const OFFSET_GLIDE_SECONDS: f32 = 0.01;
+
+/// Where a unison voice sits in `[-1, 1]`: `position` is its evenly spaced
+/// slot, bent by `curve` and then by `density` (see
+/// `OscillatorParams::unison_density`).
+fn detune_offset(position: f32, curve: DetuneCurve, density: f32) -> f32 {
+    let distance = position.abs();
+    let shaped = match curve {
+        DetuneCurve::Linear => distance,
+        DetuneCurve::Exponential => ((distance * 3.0).exp2() - 1.0) / 7.0,
+        DetuneCurve::SuperSaw => {
+            let scaled = distance * (SUPERSAW_SPREAD.len() - 1) as f32;
+            let index = (scaled as usize).min(SUPERSAW_SPREAD.len() - 2);
+            let frac = scaled - index as f32;
+            SUPERSAW_SPREAD[index] + (SUPERSAW_SPREAD[index + 1] - SUPERSAW_SPREAD[index]) * frac
+        }
+    };
+    // 0 → exponent 0.5 (pushed out), 0.5 → 1, 1 → 2 (pulled in).
+    let exponent = 4.0_f32.powf(density - 0.5);
+    shaped.powf(exponent).copysign(position)
+}
+
+/// Input gain of the wavefolder at full depth: the waveform's peaks fold back
+/// this many times over.
+const MAX_FOLD_GAIN: f32 = 8.0;
+
+/// The folder's transfer curve: a triangle of period 4 that passes `[-1, 1]`
+/// straight through and reflects anything beyond back into it, like a
+/// Buchla-style folder driven hard.
+#[inline]
+fn fold_triangle(x: f32) -> f32 {
+    1.0 - ((x + 1.0).rem_euclid(4.0) - 2.0).abs()
+}
+
+/// An antiderivative of [`fold_triangle`]. Periodic too, since each period of
+/// the triangle integrates to 0, so it stays precise however hard the input
+/// is driven.
+#[inline]
+fn fold_integral(x: f32) -> f32 {
+    let u = (x + 1.0).rem_euclid(4.0);
+    if u <= 2.0 {
+        0.5 * u * u - u
+    } else {
+        3.0 * (u - 2.0) - 0.5 * (u * u - 4.0)
+    }
+}
+
+/// PolyBLEP: the difference between a unit step band-limited by a two-sample
+/// polynomial and the naive step, at `t` cycles from the step, for a phase
+/// that advances `dt` cycles per sample. Added at a waveform's jumps (scaled by
+/// half the jump), it takes most of the aliasing out of their edges for the
+/// cost of a few multiplies.
+#[inline]
+pub(super) fn poly_blep(t: f32, dt: f32) -> f32 {
+    let dt = dt.clamp(1e-6, 0.5);
+    if t < dt {
+        let x = t / dt;
+        x + x - x * x - 1.0
+    } else if t > 1.0 - dt {
+        let x = (t - 1.0) / dt;
+        x * x + x + x + 1.0
+    } else {
+        0.0
+    }
+}
+
+/// `sin(2π·cycle)` for `cycle` in `[0, 1)`, linearly interpolated.
+#[inline]
+fn table_sine(table: &[f32; SINE_TABLE_SIZE], cycle: f32) -> f32 {
+    let position = cycle * SINE_TABLE_SIZE as f32;
+    let index = position as usize % SINE_TABLE_SIZE;
+    let next = (index + 1) % SINE_TABLE_SIZE;
+    let frac = position.fract();
+    table[index] + (table[next] - table[index]) * frac
+}
 
 #[derive(Clone)]
 pub(crate) struct OscillatorVoice {
     phase: f32,
     detune_offset: f32,
+    /// Where `detune_offset` is gliding to.
+    target_offset: f32,
+    /// How far `detune_offset` moves per sample while gliding.
+    offset_step: f32,
 }
 
 #[derive(Clone)]
 pub(crate) struct UnisonOscillator {
     voices: Vec<OscillatorVoice>,
     num_voices: usize,
+    /// Square-wave duty cycle in `(0, 1)`; `0.5` is a symmetric square.
+    pulse_width: f32,
+    /// Phase-distortion depth in `[0, 1]` (see [`Self::distort`]).
+    phase_distortion: f32,
+    /// Wavefold depth in `[0, 1]` (see [`Self::fold`]).
+    wavefold: f32,
+    /// The folder's previous (gained) input, for its anti-aliasing.
+    fold_input: f32,
+    /// Harmonic levels while the additive engine replaces the waveform.
+    drawbars: Option<[f32; NUM_DRAWBARS]>,
+    /// Morph position while morphing replaces the waveform switch.
+    morph: Option<f32>,
+    /// How the voices' detune offsets are spread.
+    curve: DetuneCurve,
+    density: f32,
+    /// Hold the unison mix at one voice's power (see [`Self::compensation`]).
+    gain_compensation: bool,
+    /// Smooth the square's and the saw's jumps with [`poly_blep`]; off plays
+    /// them naively.
+    band_limited: bool,
+    /// The targets moved; the glide to them starts on the next sample, where
+    /// the sample rate is known.
+    glide_pending: bool,
+    /// Samples left in the detune offsets' glide.
+    glide_left: u32,
 }
 
 impl UnisonOscillator {
     pub(crate) fn new(max_voices: usize) -> Self {
-        let mut voices = Vec::with_capacity(max_voices);
-        for i in 0..max_voices {
-            let detune_offset = if max_voices == 1 {
-                0.0
-            } else {
-                (i as f32 - (max_voices - 1) as f32 / 2.0) / ((max_voices - 1) as f32 / 2.0)
-            };
-            voices.push(OscillatorVoice {
+        sine_table();
+        let voices = vec![
+            OscillatorVoice {
                 phase: 0.0,
-                detune_offset,
-            });
-        }
+                detune_offset: 0.0,
+                target_offset: 0.0,
+                offset_step: 0.0,
+            };
+            max_voices
+        ];
 
         Self {
             voices,
             num_voices: 1,
+            pulse_width: 0.5,
+            phase_distortion: 0.0,
+            wavefold: 0.0,
+            fold_input: 0.0,
+            drawbars: None,
+            morph: None,
+            curve: DetuneCurve::Linear,
+            density: 0.5,
+            gain_compensation: false,
+            band_limited: true,
+            glide_pending: false,
+            glide_left: 0,
         }
     }
 
+    /// Sets the square wave's duty cycle. Modulated per sample, so it is state
+    /// on the oscillator rather than another `process` argument.
+    pub(crate) fn set_pulse_width(&mut self, pulse_width: f32) {
+        self.pulse_width = pulse_width;
+    }
+
+    /// Sets the phase-distortion depth. Smoothed per sample, like the pulse
+    /// width.
+    pub(crate) fn set_phase_distortion(&mut self, amount: f32) {
+        self.phase_distortion = amount.clamp(0.0, 1.0);
+    }
+
+    /// Sets the wavefold depth. Smoothed per sample, like the pulse width.
+    pub(crate) fn set_wavefold(&mut self, amount: f32) {
+        self.wavefold = amount.clamp(0.0, 1.0);
+    }
+
+    /// Switches the additive engine on (with these harmonic levels) or off.
+    /// Set per sample, like the pulse width.
+    pub(crate) fn set_drawbars(&mut self, drawbars: Option<[f32; NUM_DRAWBARS]>) {
+        self.drawbars = drawbars;
+    }
+
+    /// Switches morphing on (at this position) or off. Smoothed and modulated
+    /// per sample, like the pulse width.
+    pub(crate) fn set_morph(&mut self, position: Option<f32>) {
+        self.morph = position;
+    }
+
+    /// Casio CZ-style phase distortion: the first half of the waveform is
+    /// squeezed into a shrinking slice of the cycle and the second half
+    /// stretched over the rest, sharpening the waveform's edges. The knee
+    /// never reaches zero, so full depth stays band-limited enough to play.
+    fn distort(phase: f32, amount: f32) -> f32 {
+        if amount == 0.0 {
+            return phase;
+        }
+        let p = phase.rem_euclid(TAU) / TAU;
+        let knee = 0.5 * (1.0 - 0.95 * amount);
+        let warped = if p < knee {
+            0.5 * p / knee
+        } else {
+            0.5 + 0.5 * (p - knee) / (1.0 - knee)
+        };
+        warped * TAU
+    }
+
+    /// West-coast wavefolding of the finished waveform: gained up with the
+    /// depth and folded back into `[-1, 1]` by [`fold_triangle`], so a sine or
+    /// triangle grows bright, harmonically rich overtones. The fold's corners
+    /// alias badly when taken sample by sample, so this uses first-order
+    /// antiderivative anti-aliasing: the curve's average between this input
+    /// and the last, from [`fold_integral`]. It delays the output by half a
+    /// sample, which nothing downstream can hear. At depth 0 the signal
+    /// passes untouched.
+    fn fold(&mut self, sample: f32) -> f32 {
+        let input = sample * (1.0 + self.wavefold * (MAX_FOLD_GAIN - 1.0));
+        let previous = std::mem::replace(&mut self.fold_input, input);
+        if self.wavefold == 0.0 {
+            return sample;
+        }
+        let step = input - previous;
+        if step.abs() < 1e-4 {
+            // Nearly flat: the average is the curve at the midpoint, and
+            // dividing would only amplify rounding.
+            fold_triangle(0.5 * (input + previous))
+        } else {
+            (fold_integral(input) - fold_integral(previous)) / step
+        }
+    }
+
+    pub(crate) fn set_gain_compensation(&mut self, on: bool) {
+        self.gain_compensation = on;
+    }
+
+    pub(crate) fn set_band_limited(&mut self, on: bool) {
+        self.band_limited = on;
+    }
+
+    /// Sets the unison voice count. Control-rate: called once per block and
+    /// only acts on a change. Voices that join start in phase with, and at the
+    /// detune of, the middle voice already playing, then glide out to their
+    /// own detune with the rest, so the count can change mid-note.
     pub(crate) fn set_num_voices(&mut self, num_voices: usize) {
         let num_voices = num_voices.min(self.voices.len()).max(1);
         if num_voices == self.num_voices {
             return;
         }
+        let middle = self.voices[(self.num_voices - 1) / 2].clone();
+        for voice in &mut self.voices[self.num_voices.min(num_voices)..num_voices] {
+            voice.phase = middle.phase;
+            voice.detune_offset = middle.detune_offset;
+        }
         self.num_voices = num_voices;
+        self.update_offsets();
+    }
 
+    /// Sets the detune curve and density. Checked per sample but only
+    /// recomputes the offsets when either changes.
+    pub(crate) fn set_detune_shape(&mut self, curve: DetuneCurve, density: f32) {
+        if curve == self.curve && density == self.density {
+            return;
+        }
+        self.curve = curve;
+        self.density = density;
+        self.update_offsets();
+    }
+
+    /// Retargets every voice's detune offset; [`Self::glide_offsets`] moves
+    /// them there.
+    fn update_offsets(&mut self) {
+        let half_span = (self.num_voices - 1) as f32 / 2.0;
         for (i, voice) in self.voices.iter_mut().enumerate() {
-            voice.detune_offset = if self.num_voices == 1 {
+            voice.target_offset = if self.num_voices == 1 {
                 0.0
             } else {
-                (i as f32 - (self.num_voices - 1) as f32 / 2.0)
-                    / ((self.num_voices - 1) as f32 / 2.0)
+                detune_offset((i as f32 - half_span) / half_span, self.curve, self.density)
+            };
+        }
+        self.glide_pending = true;
+    }
+
+    /// Moves the detune offsets one sample along their glide, a straight line
+    /// over [`OFFSET_GLIDE_SECONDS`] from wherever they were when the targets
+    /// last moved.
+    #[inline]
+    fn glide_offsets(&mut self, sample_rate: f32) {
+        if self.glide_pending {
+            self.glide_pending = false;
+            let samples = (OFFSET_GLIDE_SECONDS * sample_rate).round().max(1.0);
+            for voice in &mut self.voices {
+                voice.offset_step = (voice.target_offset - voice.detune_offset) / samples;
+            }
+            self.glide_left = samples as u32;
+        }
+        if self.glide_left == 0 {
+            return;
+        }
+        self.glide_left -= 1;
+        for voice in &mut self.voices {
+            voice.detune_offset = if self.glide_left == 0 {
+                voice.target_offset
+            } else {
+                voice.detune_offset + voice.offset_step
             };
         }
     }
@@ -61,17 +326,31 @@ impl UnisonOscillator {
         volume: f32,
         sample_rate: f32,
     ) -> f32 {
+        self.glide_offsets(sample_rate);
         if self.num_voices == 1 {
             let phase_incr = base_freq / sample_rate * TAU;
-            let current_phase = self.voices[0].phase + phase_offset * TAU;
-            let sample = Self::generate_waveform(waveform, current_phase);
+            let current_phase = Self::distort(
+                self.voices[0].phase + phase_offset * TAU,
+                self.phase_distortion,
+            );
+            let sample = match &self.drawbars {
+                Some(levels) => Self::additive(levels, current_phase, phase_incr),
+                None => Self::shape(
+                    self.morph,
+                    waveform,
+                    current_phase,
+                    phase_incr,
+                    self.pulse_width,
+                    self.band_limited,
+                ),
+            };
 
             self.voices[0].phase += phase_incr;
             if self.voices[0].phase >= TAU {
                 self.voices[0].phase -= TAU;
             }
 
-            return sample * volume;
+            return self.fold(sample) * volume;
         }
 
         let mut unison_sum = 0.0;
@@ -84,8 +363,19 @@ impl UnisonOscillator {
             let detuned_freq = base_freq * detune_factor;
             let phase_incr = detuned_freq / sample_rate * TAU;
 
-            let current_phase = voice.phase + phase_offset * TAU;
-            let sample = Self::generate_waveform(waveform, current_phase);
+            let current_phase =
+                Self::distort(voice.phase + phase_offset * TAU, self.phase_distortion);
+            let sample = match &self.drawbars {
+                Some(levels) => Self::additive(levels, current_phase, phase_incr),
+                None => Self::shape(
+                    self.morph,
+                    waveform,
+                    current_phase,
+                    phase_incr,
+                    self.pulse_width,
+                    self.band_limited,
+                ),
+            };
 
             if i == 0 {
                 mono_sample = sample;
@@ -100,20 +390,109 @@ impl UnisonOscillator {
         }
 
         let unison_sample = unison_sum / self.num_voices as f32;
-        let final_sample = mono_sample * (1.0 - blend) + unison_sample * blend;
+        let mut final_sample = mono_sample * (1.0 - blend) + unison_sample * blend;
+        if self.gain_compensation {
+            final_sample *= Self::compensation(self.num_voices, blend);
+        }
 
-        final_sample * volume
+        self.fold(final_sample) * volume
     }
 
-    fn generate_waveform(waveform: Waveform, phase: f32) -> f32 {
+    /// Gain that brings the blended mix back to one voice's power. Detuned
+    /// voices drift apart in phase, so their powers add rather than their
+    /// amplitudes: the first voice, in both the dry and the unison side, has
+    /// `1 - blend + blend / n` of its level and each of the others `blend /
+    /// n`. Equal-power, so it's exact once the voices have drifted apart; at
+    /// the note's start, while they're still in phase, it runs hot.
+    fn compensation(num_voices: usize, blend: f32) -> f32 {
+        let n = num_voices as f32;
+        let first = 1.0 - blend + blend / n;
+        let others = (n - 1.0) * (blend / n) * (blend / n);
+        1.0 / (first * first + others).sqrt()
+    }
+
+    /// Harmonics 1 to [`NUM_DRAWBARS`] at their drawbar levels, read from the
+    /// shared sine table. Harmonics at or above Nyquist are skipped, so high
+    /// notes thin out instead of aliasing. The sum is scaled down once the
+    /// levels add up past 1, so a full registration doesn't clip.
+    fn additive(levels: &[f32; NUM_DRAWBARS], phase: f32, phase_incr: f32) -> f32 {
+        let table = sine_table();
+        let cycle = phase.rem_euclid(TAU) / TAU;
+        let mut sum = 0.0;
+        for (index, level) in levels.iter().enumerate() {
+            let harmonic = (index + 1) as f32;
+            if phase_incr * harmonic >= PI {
+                break;
+            }
+            if *level > 0.0 {
+                sum += level * table_sine(table, (cycle * harmonic).fract());
+            }
+        }
+        sum / levels.iter().sum::<f32>().max(1.0)
+    }
+
+    /// [`Self::generate_waveform`], or while morphing, a crossfade of the
+    /// two shapes either side of the morph position instead of `waveform`.
+    /// On a shape, only that one is generated.
+    fn shape(
+        morph: Option<f32>,
+        waveform: Waveform,
+        phase: f32,
+        phase_incr: f32,
+        pulse_width: f32,
+        band_limited: bool,
+    ) -> f32 {
+        let Some(position) = morph else {
+            return Self::generate_waveform(waveform, phase, phase_incr, pulse_width, band_limited);
+        };
+        let generate = |waveform| {
+            let phase = phase + Self::morph_phase(waveform);
+            Self::generate_waveform(waveform, phase, phase_incr, pulse_width, band_limited)
+        };
+        let (from, to, amount) = morph_shapes(position);
+        if amount == 0.0 {
+            return generate(from);
+        }
+        if amount == 1.0 {
+            return generate(to);
+        }
+        generate(from) * (1.0 - amount) + generate(to) * amount
+    }
+
+    /// How far ahead `waveform` is read while morphing, so every shape's
+    /// fundamental rises with the sine's. Otherwise the triangle lags it by a
+    /// quarter cycle and the saw's is upside down, and crossfading them
+    /// cancels part of the level.
+    fn morph_phase(waveform: Waveform) -> f32 {
+        match waveform {
+            Waveform::Sine | Waveform::Square => 0.0,
+            Waveform::Triangle => 0.25 * TAU,
+            Waveform::Sawtooth => PI,
+        }
+    }
+
+    /// One sample of `waveform` at `phase`, which advances `phase_incr` per
+    /// sample. While band-limited, the square's two edges and the saw's reset
+    /// are smoothed with [`poly_blep`]; the step is the unwarped one, so at
+    /// deep phase distortion some aliasing is left.
+    fn generate_waveform(
+        waveform: Waveform,
+        phase: f32,
+        phase_incr: f32,
+        pulse_width: f32,
+        band_limited: bool,
+    ) -> f32 {
+        let t = (phase % TAU) / TAU;
+        let dt = phase_incr / TAU;
         match waveform {
             Waveform::Sine => phase.sin(),
             Waveform::Square => {
-                if (phase % TAU) < std::f32::consts::PI {
-                    1.0
-                } else {
-                    -1.0
+                let naive = if t < pulse_width { 1.0 } else { -1.0 };
+                if !band_limited {
+                    return naive;
                 }
+                // Up at the start of the cycle, down at the pulse width.
+                naive + poly_blep(t, dt) - poly_blep((t - pulse_width).rem_euclid(1.0), dt)
             }
             Waveform::Triangle => {
                 let normalized_phase = (phase % TAU) / TAU;
@@ -123,13 +502,23 @@ impl UnisonOscillator {
                     3.0 - 4.0 * normalized_phase
                 }
             }
-            Waveform::Sawtooth => 2.0 * ((phase % TAU) / TAU) - 1.0,
+            Waveform::Sawtooth => {
+                let naive = 2.0 * t - 1.0;
+                if !band_limited {
+                    return naive;
+                }
+                naive - poly_blep(t, dt)
+            }
         }
     }
 
     pub(crate) fn reset(&mut self) {
         for voice in &mut self.voices {
             voice.phase = 0.0;
+            voice.detune_offset = voice.target_offset;
         }
+        self.glide_pending = false;
+        self.glide_left = 0;
+        self.fold_input = 0.0;
     }
 }
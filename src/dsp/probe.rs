@@ -0,0 +1,210 @@
+//! Modulator probes: fire an envelope or restart the LFO without playing a
+//! note and trace its shape, for the mod scope and the `trigger_envelope` /
+//! `trigger_lfo` tools.
+//!
+//! A probed envelope runs on its own copy of the envelope generator, so it is
+//! silent and leaves sounding voices alone. It holds at sustain for a moment,
+//! then releases. The LFO is global, so probing it restarts its phase.
+//!
+//! The source monitor is the live counterpart: [`SourceSampler`] picks out
+//! the samples at which the engine publishes every mod source's current value,
+//! so the editor can scroll their recent history.
+
+use super::envelope::Envelope;
+use super::FrameParams;
+
+/// Points in one trace.
+pub const TRACE_POINTS: usize = 256;
+/// How long a probed envelope sits at sustain before its release.
+const SUSTAIN_SECONDS: f32 = 0.3;
+/// LFO cycles per trace.
+const LFO_CYCLES: f32 = 2.0;
+/// Longest trace, so a long release or a very slow LFO still finishes.
+const MAX_SECONDS: f32 = 20.0;
+
+/// Points in the source monitor's history.
+pub const SOURCE_POINTS: usize = 256;
+/// How far back the source monitor's history reaches: two beats at 60 BPM,
+/// eight at 240.
+pub const SOURCE_SECONDS: f32 = 2.0;
+
+/// What a probe traces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProbeTarget {
+    AmpEnvelope,
+    FilterEnvelope,
+    Lfo,
+}
+
+impl ProbeTarget {
+    pub const ALL: [ProbeTarget; 3] = [Self::AmpEnvelope, Self::FilterEnvelope, Self::Lfo];
+
+    /// Name used by the tools and the scope's label.
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::AmpEnvelope => "amp",
+            Self::FilterEnvelope => "filter",
+            Self::Lfo => "lfo",
+        }
+    }
+
+    /// Whether the traced values are bipolar (`[-1, 1]`) rather than
+    /// `[0, 1]`.
+    pub fn bipolar(self) -> bool {
+        self == Self::Lfo
+    }
+}
+
+/// One traced value.
+#[derive(Clone, Copy, Debug)]
+pub struct TracePoint {
+    pub target: ProbeTarget,
+    /// Position in the trace; `0` starts a new one.
+    pub index: usize,
+    pub value: f32,
+    /// Seconds between points.
+    pub spacing: f32,
+}
+
+pub struct ModProbe {
+    target: Option<ProbeTarget>,
+    /// Set by [`ModProbe::start`]; the timing is worked out from the next
+    /// frame, which has the stage times.
+    pending: bool,
+    envelope: Envelope,
+    lfo_rate: f32,
+    sample_rate: f32,
+    /// Samples until the probed envelope is released.
+    gate: u32,
+    elapsed: u32,
+    /// Samples per trace point.
+    interval: u32,
+    countdown: u32,
+    points: usize,
+}
+
+impl ModProbe {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            target: None,
+            pending: false,
+            envelope: Envelope::new(sample_rate),
+            lfo_rate: 1.0,
+            sample_rate,
+            gate: 0,
+            elapsed: 0,
+            interval: 1,
+            countdown: 0,
+            points: 0,
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.envelope.set_sample_rate(sample_rate);
+        self.target = None;
+    }
+
+    /// Begin tracing `target`, abandoning any trace in progress. `lfo_rate`
+    /// (Hz) sets how long an LFO trace runs.
+    pub fn start(&mut self, target: ProbeTarget, lfo_rate: f32) {
+        self.target = Some(target);
+        self.pending = true;
+        self.lfo_rate = lfo_rate;
+    }
+
+    /// Call once per sample, after the LFO has advanced. Returns a point each
+    /// time one is due.
+    #[inline]
+    pub fn next(&mut self, f: &FrameParams) -> Option<TracePoint> {
+        let target = self.target?;
+        let env = match target {
+            ProbeTarget::AmpEnvelope => Some(&f.amp_env),
+            ProbeTarget::FilterEnvelope => Some(&f.filter_env),
+            ProbeTarget::Lfo => None,
+        };
+
+        if self.pending {
+            self.pending = false;
+            let seconds = match env {
+                Some(e) => {
+                    let gate = e.attack + e.hold + e.decay + SUSTAIN_SECONDS;
+                    self.gate = (gate * self.sample_rate) as u32;
+                    gate + e.release
+                }
+                None => LFO_CYCLES / self.lfo_rate.max(1e-3),
+            };
+            let samples = seconds.min(MAX_SECONDS) * self.sample_rate;
+            self.interval = (samples / TRACE_POINTS as f32).max(1.0) as u32;
+            self.elapsed = 0;
+            self.countdown = 0;
+            self.points = 0;
+            self.envelope = Envelope::new(self.sample_rate);
+            self.envelope.note_on();
+        }
+
+        let value = match env {
+            Some(e) => {
+                if self.elapsed == self.gate {
+                    self.envelope.note_off();
+                }
+                self.envelope
+                    .process(e.attack, e.hold, e.decay, e.sustain, e.release, f.declick)
+            }
+            None => f.modulation.lfo,
+        };
+        self.elapsed = self.elapsed.saturating_add(1);
+
+        if self.countdown > 0 {
+            self.countdown -= 1;
+            return None;
+        }
+        self.countdown = self.interval - 1;
+        let index = self.points;
+        self.points += 1;
+        if self.points == TRACE_POINTS {
+            self.target = None;
+        }
+        Some(TracePoint {
+            target,
+            index,
+            value,
+            spacing: self.interval as f32 / self.sample_rate,
+        })
+    }
+}
+
+/// Picks one sample in every `SOURCE_SECONDS / SOURCE_POINTS` for the source
+/// monitor.
+pub struct SourceSampler {
+    interval: u32,
+    countdown: u32,
+}
+
+impl SourceSampler {
+    pub fn new(sample_rate: f32) -> Self {
+        let mut sampler = Self {
+            interval: 1,
+            countdown: 0,
+        };
+        sampler.set_sample_rate(sample_rate);
+        sampler
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        let samples = SOURCE_SECONDS * sample_rate / SOURCE_POINTS as f32;
+        self.interval = samples.round().max(1.0) as u32;
+        self.countdown = 0;
+    }
+
+    /// Call once per sample; `true` when this sample's sources are due.
+    #[inline]
+    pub fn tick(&mut self) -> bool {
+        if self.countdown > 0 {
+            self.countdown -= 1;
+            return false;
+        }
+        self.countdown = self.interval - 1;
+        true
+    }
+}
@@ -0,0 +1,37 @@
+//! A tiny xorshift PRNG for the places that need "different each time", not
+//! statistical quality: per-voice analog drift on the audio thread (no
+//! allocation, no locks) and the patch randomizer. Keeps the crate free of an
+//! RNG dependency.
+
+pub(crate) struct XorShift(u32);
+
+impl XorShift {
+    pub(crate) fn new(seed: u32) -> Self {
+        // Zero is xorshift's one fixed point.
+        Self(seed.max(1))
+    }
+
+    /// Seeded from the wall clock.
+    pub(crate) fn from_time() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(1);
+        Self::new(nanos)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub(crate) fn next_f32(&mut self) -> f32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        (x >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Uniform in `[-1, 1)`.
+    pub(crate) fn next_bipolar(&mut self) -> f32 {
+        self.next_f32() * 2.0 - 1.0
+    }
+}
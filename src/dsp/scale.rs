@@ -0,0 +1,62 @@
+//! Scale quantizer: snaps notes to the nearest one in a key and scale, for the
+//! played keys, the arp's notes and the assistant's `play_notes`. A chromatic
+//! scale lets every note through.
+
+use crate::params::{Scale, ScaleRoot};
+use crate::SineParams;
+
+/// A key and scale, as the pitch classes it allows (bit 0 is C, bit 11 B).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Quantizer {
+    classes: u16,
+}
+
+impl Quantizer {
+    pub fn new(root: ScaleRoot, scale: Scale) -> Self {
+        let root = root.semitone();
+        let classes = scale
+            .steps()
+            .iter()
+            .fold(0, |classes, step| classes | 1 << ((root + step) % 12));
+        Self { classes }
+    }
+
+    pub fn from_params(p: &SineParams) -> Self {
+        Self::new(p.scale_root.value(), p.scale.value())
+    }
+
+    /// The scale note nearest `note`; the lower one of two equally near.
+    pub fn snap(self, note: u8) -> u8 {
+        (0..12)
+            .flat_map(|distance| [note.checked_sub(distance), note.checked_add(distance)])
+            .flatten()
+            .find(|&candidate| candidate <= 127 && self.allows(candidate))
+            .unwrap_or(note)
+    }
+
+    fn allows(self, note: u8) -> bool {
+        self.classes & 1 << (note % 12) != 0
+    }
+}
+
+/// Semitones above C of a note name: a letter and any `#`/`b` (or `♯`/`♭`)
+/// after it, e.g. `"F#"` is 6 and `"Cb"` 11.
+pub fn pitch_class(name: &str) -> Option<u8> {
+    let mut chars = name.trim().chars();
+    let natural: i32 = match chars.next()?.to_ascii_uppercase() {
+        'C' => 0,
+        'D' => 2,
+        'E' => 4,
+        'F' => 5,
+        'G' => 7,
+        'A' => 9,
+        'B' => 11,
+        _ => return None,
+    };
+    let shift = chars.try_fold(0, |shift, c| match c {
+        '#' | '♯' => Some(shift + 1),
+        'b' | '♭' => Some(shift - 1),
+        _ => None,
+    })?;
+    Some((natural + shift).rem_euclid(12) as u8)
+}
@@ -0,0 +1,99 @@
+//! How alike two mono buffers sound, for matching a patch to a reference
+//! recording: the shape of their long-term spectra (see [`spectrum`]) and of
+//! their levels over time. Off the audio thread only: it allocates and runs
+//! FFTs.
+//!
+//! Both measures ignore overall level, which a reference rarely shares with a
+//! render; [`Similarity::level_difference_db`] reports it separately.
+
+use super::spectrum::{spectrum, FLOOR_DB};
+
+/// Length of a level window, in seconds.
+const ENVELOPE_WINDOW: f32 = 0.01;
+/// Bands this far under a spectrum's loudest, in both buffers, aren't
+/// compared: they hold only noise and leakage.
+const QUIET_DB: f32 = -60.0;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Similarity {
+    /// Mean difference per band, in dB, between the spectra each taken
+    /// relative to its loudest band; `0` is the same spectral shape.
+    pub spectral_distance_db: f32,
+    /// Correlation of the level envelopes, `-1..=1`; `1` is the same shape
+    /// over time, `0` no relation (or one of them steady).
+    pub envelope_correlation: f32,
+    /// How much louder `candidate` is than `reference` overall, in dB.
+    pub level_difference_db: f32,
+}
+
+/// Compare `candidate` with `reference`, both at `sample_rate`. Envelopes are
+/// compared over the shorter of the two.
+pub fn compare(reference: &[f32], candidate: &[f32], sample_rate: f32) -> Similarity {
+    let shape = |samples: &[f32]| {
+        let bands = spectrum(samples, sample_rate);
+        let loudest = bands.iter().copied().fold(FLOOR_DB, f32::max);
+        bands.map(|band| band - loudest)
+    };
+    let (want, got) = (shape(reference), shape(candidate));
+    let compared: Vec<f32> = want
+        .iter()
+        .zip(&got)
+        .filter(|(want, got)| want.max(**got) > QUIET_DB)
+        .map(|(want, got)| (want.max(QUIET_DB) - got.max(QUIET_DB)).abs())
+        .collect();
+    let spectral_distance_db = if compared.is_empty() {
+        0.0
+    } else {
+        compared.iter().sum::<f32>() / compared.len() as f32
+    };
+
+    let window = ((ENVELOPE_WINDOW * sample_rate) as usize).max(1);
+    let (want, got) = (envelope(reference, window), envelope(candidate, window));
+    let len = want.len().min(got.len());
+    let envelope_correlation = correlation(&want[..len], &got[..len]);
+
+    let level_difference_db = db(rms(candidate)) - db(rms(reference));
+    Similarity {
+        spectral_distance_db,
+        envelope_correlation,
+        level_difference_db,
+    }
+}
+
+/// RMS level of each `window` samples.
+fn envelope(samples: &[f32], window: usize) -> Vec<f32> {
+    samples.chunks(window).map(rms).collect()
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let power: f64 = samples.iter().map(|&s| f64::from(s * s)).sum();
+    (power / samples.len() as f64).sqrt() as f32
+}
+
+fn db(level: f32) -> f32 {
+    (20.0 * level.max(1e-6).log10()).max(FLOOR_DB)
+}
+
+/// Pearson correlation; `0` when either side doesn't vary.
+fn correlation(a: &[f32], b: &[f32]) -> f32 {
+    if a.is_empty() {
+        return 0.0;
+    }
+    let mean =
+        |values: &[f32]| values.iter().map(|&v| f64::from(v)).sum::<f64>() / values.len() as f64;
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (&a, &b) in a.iter().zip(b) {
+        let (dev_a, dev_b) = (f64::from(a) - mean_a, f64::from(b) - mean_b);
+        cov += dev_a * dev_b;
+        var_a += dev_a * dev_a;
+        var_b += dev_b * dev_b;
+    }
+    if var_a <= f64::EPSILON || var_b <= f64::EPSILON {
+        return 0.0;
+    }
+    (cov / (var_a * var_b).sqrt()) as f32
+}
@@ -0,0 +1,106 @@
+//! Long-term average spectrum of a rendered buffer, for drawing, not for the
+//! audio thread: Hann-windowed FFT frames at 50% overlap, their power averaged
+//! and then gathered into log-spaced bands from [`MIN_HZ`] to [`MAX_HZ`].
+
+use std::f32::consts::{PI, TAU};
+
+/// Bands in a [`spectrum`].
+pub const SPECTRUM_BANDS: usize = 64;
+pub const MIN_HZ: f32 = 20.0;
+pub const MAX_HZ: f32 = 20_000.0;
+/// Level of a silent band; every band is clamped to at least this.
+pub const FLOOR_DB: f32 = -100.0;
+
+/// FFT frame length (a power of two).
+const FFT_SIZE: usize = 4096;
+
+/// Band levels in dB (full scale) of `samples`, lowest band first.
+pub fn spectrum(samples: &[f32], sample_rate: f32) -> [f32; SPECTRUM_BANDS] {
+    let window: Vec<f32> = (0..FFT_SIZE)
+        .map(|i| 0.5 - 0.5 * (TAU * i as f32 / FFT_SIZE as f32).cos())
+        // Hann's coherent gain is a half; undo it so a full-scale sine reads 0 dB.
+        .map(|w| w * 2.0)
+        .collect();
+
+    let mut power = vec![0.0f32; FFT_SIZE / 2];
+    let mut frames = 0;
+    let mut re = vec![0.0f32; FFT_SIZE];
+    let mut im = vec![0.0f32; FFT_SIZE];
+    let mut start = 0;
+    while start < samples.len() {
+        // A short buffer, or the tail, is zero-padded.
+        for (i, (re, im)) in re.iter_mut().zip(&mut im).enumerate() {
+            *re = samples.get(start + i).copied().unwrap_or(0.0) * window[i];
+            *im = 0.0;
+        }
+        fft(&mut re, &mut im);
+        // Scaled so a sine's peak bin holds its amplitude squared.
+        let scale = (2.0 / FFT_SIZE as f32).powi(2);
+        for (bin, power) in power.iter_mut().enumerate() {
+            *power += (re[bin] * re[bin] + im[bin] * im[bin]) * scale;
+        }
+        frames += 1;
+        start += FFT_SIZE / 2;
+    }
+    if frames > 0 {
+        for power in &mut power {
+            *power /= frames as f32;
+        }
+    }
+
+    let bin_hz = sample_rate / FFT_SIZE as f32;
+    std::array::from_fn(|band| {
+        let (low, high) = (band_edge(band), band_edge(band + 1));
+        let first = (low / bin_hz).ceil() as usize;
+        let last = ((high / bin_hz).floor() as usize).min(power.len() - 1);
+        // Low bands can fall between two bins; take the nearest one.
+        let band_power = if first <= last {
+            power[first..=last].iter().sum::<f32>()
+        } else {
+            let nearest = ((low * high).sqrt() / bin_hz).round() as usize;
+            power[nearest.min(power.len() - 1)]
+        };
+        (10.0 * band_power.max(1e-12).log10()).max(FLOOR_DB)
+    })
+}
+
+/// Lower edge in Hz of `band`; `band_edge(SPECTRUM_BANDS)` is [`MAX_HZ`].
+pub fn band_edge(band: usize) -> f32 {
+    MIN_HZ * (MAX_HZ / MIN_HZ).powf(band as f32 / SPECTRUM_BANDS as f32)
+}
+
+/// In-place iterative radix-2 FFT; `re.len()` must be a power of two.
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * cos - im[b] * sin;
+                let t_im = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
@@ -0,0 +1,57 @@
+//! Sub oscillator: a sine or square one or two octaves under oscillator 1,
+//! mixed in with the oscillators ahead of the comb and the filter. It follows
+//! oscillator 1's pitch (its octave, frequency and detune) but none of its
+//! shaping or unison, so a bass keeps a solid, centred low end however wide
+//! or bright the main oscillators are.
+
+use std::f32::consts::TAU;
+
+use super::oscillator::poly_blep;
+use crate::SubWaveform;
+
+pub struct SubOscillator {
+    /// In cycles, `[0, 1)`.
+    phase: f32,
+}
+
+impl Default for SubOscillator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SubOscillator {
+    pub fn new() -> Self {
+        Self { phase: 0.0 }
+    }
+
+    /// Back to the start of a cycle, so every note starts from a zero
+    /// crossing (the sine's) or the square's rising edge.
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    /// The next sample at `freq` Hz, in `[-1, 1]`.
+    #[inline]
+    pub fn next(
+        &mut self,
+        waveform: SubWaveform,
+        freq: f32,
+        sample_rate: f32,
+        band_limited: bool,
+    ) -> f32 {
+        let t = self.phase;
+        let dt = freq / sample_rate;
+        self.phase = (t + dt).fract();
+        match waveform {
+            SubWaveform::Sine => (TAU * t).sin(),
+            SubWaveform::Square => {
+                let naive = if t < 0.5 { 1.0 } else { -1.0 };
+                if !band_limited {
+                    return naive;
+                }
+                naive + poly_blep(t, dt) - poly_blep((t + 0.5).fract(), dt)
+            }
+        }
+    }
+}
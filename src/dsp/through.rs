@@ -0,0 +1,74 @@
+//! Audio-through: the main input (when the host connects one) run through the
+//! same filter and drive as the voices, so the plugin doubles as a filter box.
+//!
+//! The voices' filters are per note, so the input gets its own stereo pair.
+//! It follows the filter knobs and the matrix's global sources (LFO and
+//! sidechain) at control rate, like a voice; per-note sources (filter
+//! envelope, velocity) read as `0`.
+
+use super::filter::BiquadFilter;
+use super::mod_matrix::{ModSources, CONTROL_INTERVAL};
+use super::FrameParams;
+
+pub struct AudioThrough {
+    filters: [BiquadFilter; 2],
+    control_countdown: u32,
+}
+
+impl AudioThrough {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            filters: [
+                BiquadFilter::new(sample_rate),
+                BiquadFilter::new(sample_rate),
+            ],
+            control_countdown: 0,
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        for filter in &mut self.filters {
+            filter.set_sample_rate(sample_rate);
+        }
+        self.control_countdown = 0;
+    }
+
+    pub fn reset(&mut self) {
+        for filter in &mut self.filters {
+            filter.reset();
+        }
+        self.control_countdown = 0;
+    }
+
+    /// Filters one stereo input frame.
+    pub fn process(&mut self, f: &FrameParams, input: [f32; 2]) -> [f32; 2] {
+        if self.control_countdown == 0 {
+            let sources = ModSources {
+                lfo: f.modulation.lfo,
+                filter_env: 0.0,
+                velocity: 0.0,
+                sidechain: f.modulation.sidechain,
+            };
+            let control = f.modulation.control_mods(&sources);
+            let cutoff = f.filter_cutoff * 2.0_f32.powf(control.cutoff);
+            let resonance = (f.filter_resonance + control.resonance).clamp(0.0, 1.0);
+            let vowel = (f.filter_vowel + control.vowel).clamp(0.0, 1.0);
+            for filter in &mut self.filters {
+                filter.ramp_to(f.filter_mode, cutoff, resonance, vowel, CONTROL_INTERVAL);
+            }
+            self.control_countdown = CONTROL_INTERVAL;
+        }
+        self.control_countdown -= 1;
+
+        let mut output = [0.0; 2];
+        for ((out, filter), sample) in output.iter_mut().zip(&mut self.filters).zip(input) {
+            *out = filter.process(sample, f.drive);
+            // As in `Voice::render`: a blown-up biquad never recovers.
+            if !out.is_finite() {
+                filter.reset();
+                *out = 0.0;
+            }
+        }
+        output
+    }
+}
@@ -1,8 +1,71 @@
-use super::envelope::Envelope;
-use super::filter::BiquadFilter;
+use std::collections::HashMap;
+
+use nih_plug::prelude::{FloatParam, Param, ParamPtr};
+
+use super::comb::{CombFilter, CombFrame};
+use super::drift::Drift;
+use super::envelope::{Envelope, EnvelopeStage};
+use super::filter::{BiquadFilter, Drive};
+use super::mod_matrix::{ModFrame, ModSources, CONTROL_INTERVAL};
+use super::noise::Noise;
 use super::oscillator::UnisonOscillator;
-use crate::params::{OscillatorParams, SineParams};
-use crate::{FilterMode, Waveform};
+use super::sub::SubOscillator;
+use crate::params::{AdsrParams, OscillatorParams, SineParams, NUM_DRAWBARS};
+use crate::ramps::{RampTarget, RampValues};
+use crate::xy::XyOffsets;
+use crate::{
+    BendRelease, DetuneCurve, FilterMode, GlideMode, NoiseColor, NoteDivision, SubWaveform,
+    Waveform,
+};
+
+/// Analog drift ranges at `analog = 1`.
+const DRIFT_PITCH_CENTS: f32 = 15.0;
+const DRIFT_LEVEL: f32 = 0.12;
+const DRIFT_CUTOFF_OCTAVES: f32 = 0.25;
+
+/// At full release-velocity depth, the fastest key lift shortens the release
+/// to a quarter and the gentlest stretches it to four times as long.
+const RELEASE_VELOCITY_RANGE: f32 = 4.0;
+
+/// How long a voice takes to fade out after a panic, whatever its release.
+const PANIC_FADE_SECONDS: f32 = 0.01;
+
+/// Keyboard pan reaches hard left/right this many semitones either side of
+/// middle C (so C1 and C7 at full amount).
+const KEYBOARD_PAN_SPAN: f32 = 36.0;
+
+/// Where a frame's param values come from.
+#[derive(Clone, Copy)]
+pub enum ParamRead<'a> {
+    /// The live params, advancing each smoother this many steps (see
+    /// [`Smoothing::steps_per_sample`](crate::Smoothing::steps_per_sample)),
+    /// with the XY pads' offsets on top (see `xy.rs`). The audio thread's way.
+    Smoothed(u32, &'a XyOffsets),
+    /// Normalized values by param, falling back to the live params' current
+    /// values for any param not in the map. Never touches a smoother, so an
+    /// offline render (see `render.rs`) can't disturb the audio thread's.
+    Fixed(&'a HashMap<ParamPtr, f32>),
+}
+
+impl ParamRead<'_> {
+    pub fn float(self, p: &FloatParam) -> f32 {
+        match self {
+            Self::Smoothed(steps, offsets) => offsets.apply(p, p.smoothed.next_step(steps)),
+            Self::Fixed(_) => self.plain(p),
+        }
+    }
+
+    /// A param's plain value; unsmoothed either way.
+    pub fn plain<P: Param>(self, p: &P) -> P::Plain {
+        match self {
+            Self::Fixed(values) => match values.get(&p.as_ptr()) {
+                Some(&normalized) => p.preview_plain(normalized),
+                None => p.modulated_plain_value(),
+            },
+            Self::Smoothed(_, offsets) => offsets.apply(p, p.modulated_plain_value()),
+        }
+    }
+}
 
 /// Per-oscillator parameter values for a single sample frame.
 ///
@@ -15,27 +78,106 @@ pub struct OscFrame {
     octave_mult: f32,
     /// Frequency knob expressed as a ratio relative to 440 Hz.
     freq_ratio: f32,
+    /// Follow the played note; otherwise the ratio applies to a fixed 440 Hz.
+    key_track: bool,
     /// `2^(detune_cents / 1200)`, precomputed.
     detune_mult: f32,
     unison_detune: f32,
     phase: f32,
+    /// Phase-distortion depth in `[0, 1]`.
+    phase_distortion: f32,
+    /// Wavefold depth in `[0, 1]`.
+    wavefold: f32,
+    /// Morph position while morphing replaces the waveform switch.
+    morph: Option<f32>,
+    unison_curve: DetuneCurve,
+    unison_density: f32,
+    unison_compensation: bool,
+    /// Harmonic levels while the additive engine is on (osc1 only).
+    drawbars: Option<[f32; NUM_DRAWBARS]>,
     blend: f32,
     volume: f32,
     gain: f32,
+    /// Off when muted, or when another oscillator is soloed and this one isn't.
+    audible: bool,
+    /// Through the comb and the filter, or around them.
+    to_filter: bool,
+    /// Play through `env` rather than the voice's amp envelope.
+    own_env: bool,
+    env: EnvFrame,
 }
 
 impl OscFrame {
-    fn next(p: &OscillatorParams) -> Self {
+    fn next(p: &OscillatorParams, read: ParamRead, bpm: f32) -> Self {
+        // Read either way, so the smoother keeps up while morphing is off.
+        let morph_position = read.float(&p.morph_position);
+        Self {
+            waveform: read.plain(&p.waveform),
+            octave_mult: 2.0_f32.powf(read.plain(&p.octave) as f32),
+            freq_ratio: read.float(&p.frequency) / 440.0,
+            key_track: read.plain(&p.key_track),
+            detune_mult: 2.0_f32.powf(read.float(&p.detune) / 1200.0),
+            unison_detune: read.float(&p.unison_detune),
+            phase: read.float(&p.phase),
+            phase_distortion: read.float(&p.phase_distortion),
+            wavefold: read.float(&p.wavefold),
+            morph: read.plain(&p.morph).then_some(morph_position),
+            unison_curve: read.plain(&p.unison_curve),
+            unison_density: read.plain(&p.unison_density),
+            unison_compensation: read.plain(&p.unison_compensation),
+            drawbars: None,
+            blend: read.float(&p.unison_blend),
+            volume: read.float(&p.unison_volume),
+            gain: read.float(&p.gain),
+            audible: true,
+            to_filter: read.plain(&p.to_filter),
+            own_env: read.plain(&p.own_envelope),
+            env: EnvFrame::next(&p.envelope, read, bpm),
+        }
+    }
+}
+
+/// One envelope's stage times (seconds) and sustain level for a sample frame.
+pub(super) struct EnvFrame {
+    pub(super) attack: f32,
+    pub(super) hold: f32,
+    pub(super) decay: f32,
+    pub(super) sustain: f32,
+    pub(super) release: f32,
+}
+
+impl EnvFrame {
+    /// Synced stages take their note division's length at `bpm`. The time
+    /// knobs' smoothers advance either way, so switching sync off doesn't
+    /// resume from a stale value.
+    fn next(p: &AdsrParams, read: ParamRead, bpm: f32) -> Self {
+        let attack = read.float(&p.attack);
+        let decay = read.float(&p.decay);
+        let release = read.float(&p.release);
+        let synced = |free: f32, sync: bool, division: NoteDivision| {
+            if sync { division.seconds(bpm) } else { free }
+        };
         Self {
-            waveform: p.waveform.value(),
-            octave_mult: 2.0_f32.powf(p.octave.value() as f32),
-            freq_ratio: p.frequency.smoothed.next() / 440.0,
-            detune_mult: 2.0_f32.powf(p.detune.smoothed.next() / 1200.0),
-            unison_detune: p.unison_detune.smoothed.next(),
-            phase: p.phase.smoothed.next(),
-            blend: p.unison_blend.smoothed.next(),
-            volume: p.unison_volume.smoothed.next(),
-            gain: p.gain.smoothed.next(),
+            attack: synced(
+                attack,
+                read.plain(&p.attack_sync),
+                read.plain(&p.attack_division),
+            )
+            .max(0.001),
+            hold: read.float(&p.hold),
+            decay: synced(
+                decay,
+                read.plain(&p.decay_sync),
+                read.plain(&p.decay_division),
+            )
+            .max(0.001),
+            sustain: read.float(&p.sustain).clamp(0.0, 1.0),
+            release: synced(
+                release,
+                read.plain(&p.release_sync),
+                read.plain(&p.release_division),
+            )
+            .max(0.001),
         }
     }
 }
@@ -44,109 +186,382 @@ impl OscFrame {
 /// per sample and fed to every active voice.
 pub struct FrameParams {
     osc: [OscFrame; 3],
-    filter_mode: FilterMode,
-    filter_cutoff: f32,
-    filter_resonance: f32,
-    filter_drive: f32,
+    noise_color: NoiseColor,
+    /// Linear gain; `0` skips the noise source.
+    noise_level: f32,
+    sub_waveform: SubWaveform,
+    /// Oscillator 1's frequency over the sub's: `2` or `4`.
+    sub_divisor: f32,
+    /// Linear gain; `0` skips the sub oscillator.
+    sub_level: f32,
+    pub(super) comb: CombFrame,
+    pub(super) filter_mode: FilterMode,
+    pub(super) filter_cutoff: f32,
+    pub(super) filter_resonance: f32,
+    /// Formant mode's vowel position, `0..=1`.
+    pub(super) filter_vowel: f32,
+    pub(super) drive: Drive,
     /// Filter-envelope depth in octaves (bipolar). `0` means the filter envelope
     /// has no effect on the cutoff.
     filter_env_amount: f32,
-    attack: f32,
-    decay: f32,
-    sustain: f32,
-    release: f32,
+    pub(super) amp_env: EnvFrame,
     /// Filter-envelope ADSR, separate from the amp envelope above.
-    filter_attack: f32,
-    filter_decay: f32,
-    filter_sustain: f32,
-    filter_release: f32,
+    pub(super) filter_env: EnvFrame,
+    /// Glide duration in seconds; per octave in [`GlideMode::ConstantRate`].
+    glide_time: f32,
+    glide_mode: GlideMode,
+    /// The bend wheel in semitones; see [`FrameParams::set_pitch_bend`].
+    pitch_bend: f32,
+    /// Semitones at full bend.
+    bend_range: f32,
+    bend_release: BendRelease,
+    /// Seconds a [`BendRelease::Return`] takes to settle.
+    bend_smoothing: f32,
+    pub(super) modulation: ModFrame,
+    /// Analog drift depth in `[0, 1]`.
+    analog: f32,
+    /// Keyboard pan spread in `[0, 1]`.
+    keyboard_pan: f32,
+    /// Slew-limit both envelopes (see `Envelope::process`).
+    pub(super) declick: bool,
+    /// PolyBLEP-correct the square and saw (see `UnisonOscillator`).
+    band_limited: bool,
 }
 
 impl FrameParams {
-    /// Advances every smoother exactly one step. Call once per output sample.
-    pub fn next(p: &SineParams) -> Self {
+    /// Reads every param through `read`; on the audio thread that advances
+    /// every smoother, so call it once per output sample. `lfo` and
+    /// `sidechain` are the global LFO's and sidechain follower's values for
+    /// this sample; `bpm` is the tempo
+    /// tempo-synced envelope stages resolve against; `ramps` overrides params
+    /// that a parameter ramp is driving (see `ramps.rs`).
+    pub fn next(
+        p: &SineParams,
+        read: ParamRead,
+        lfo: f32,
+        sidechain: f32,
+        bpm: f32,
+        ramps: &RampValues,
+    ) -> Self {
+        // Ramped params still advance their smoothers; the ramp's value wins.
+        let ramped = |target: RampTarget, smoothed: f32| ramps[target as usize].unwrap_or(smoothed);
+
+        let mut osc = [
+            OscFrame::next(&p.osc1, read, bpm),
+            OscFrame::next(&p.osc2, read, bpm),
+            OscFrame::next(&p.osc3, read, bpm),
+        ];
+        let oscs = [&p.osc1, &p.osc2, &p.osc3];
+        let any_solo = oscs.iter().any(|o| read.plain(&o.solo));
+        for (frame, o) in osc.iter_mut().zip(oscs) {
+            frame.audible = if any_solo {
+                read.plain(&o.solo)
+            } else {
+                !read.plain(&o.mute)
+            };
+        }
+        // The drawbar smoothers advance whether or not the engine is on.
+        let drawbars = p
+            .additive
+            .drawbars
+            .each_ref()
+            .map(|drawbar| read.float(&drawbar.level));
+        if read.plain(&p.additive.enabled) {
+            osc[0].drawbars = Some(drawbars);
+        }
+        let gains = [RampTarget::Gain1, RampTarget::Gain2, RampTarget::Gain3];
+        for (frame, target) in osc.iter_mut().zip(gains) {
+            frame.gain = ramped(target, frame.gain);
+        }
+
+        // The Color macro (see `ColorParams`): the cutoff and the morph
+        // position follow it both ways; the drive (square law) and phase
+        // distortion only come in above the middle.
+        let color = read.float(&p.color.amount);
+        let brighter = color.max(0.0);
+        let color_octaves = color * read.float(&p.color.cutoff);
+        let color_drive = brighter * brighter * read.float(&p.color.drive);
+        let color_shape = brighter * read.float(&p.color.shape);
+        let color_blend = color * read.float(&p.color.blend);
+        for frame in &mut osc {
+            frame.phase_distortion = (frame.phase_distortion + color_shape).min(1.0);
+            frame.morph = frame.morph.map(|position| (position + color_blend).clamp(0.0, 1.0));
+        }
+
         Self {
-            osc: [
-                OscFrame::next(&p.osc1),
-                OscFrame::next(&p.osc2),
-                OscFrame::next(&p.osc3),
-            ],
-            filter_mode: p.filter.mode.value(),
-            filter_cutoff: p.filter.cutoff.smoothed.next(),
-            filter_resonance: p.filter.resonance.smoothed.next(),
-            filter_drive: p.filter.drive.smoothed.next(),
-            filter_env_amount: p.filter.env_amount.smoothed.next(),
-            attack: p.adsr.attack.smoothed.next().max(0.001),
-            decay: p.adsr.decay.smoothed.next().max(0.001),
-            sustain: p.adsr.sustain.smoothed.next().clamp(0.0, 1.0),
-            release: p.adsr.release.smoothed.next().max(0.001),
-            filter_attack: p.filter_env.attack.smoothed.next().max(0.001),
-            filter_decay: p.filter_env.decay.smoothed.next().max(0.001),
-            filter_sustain: p.filter_env.sustain.smoothed.next().clamp(0.0, 1.0),
-            filter_release: p.filter_env.release.smoothed.next().max(0.001),
+            osc,
+            noise_color: read.plain(&p.noise.color),
+            noise_level: read.float(&p.noise.level),
+            sub_waveform: read.plain(&p.sub.waveform),
+            sub_divisor: 2.0_f32.powi(read.plain(&p.sub.octave)),
+            sub_level: read.float(&p.sub.level),
+            comb: CombFrame::new(
+                read.plain(&p.comb.enabled),
+                read.plain(&p.comb.pluck),
+                read.float(&p.comb.feedback),
+                read.float(&p.comb.damping),
+                read.float(&p.comb.mix),
+            ),
+            filter_mode: read.plain(&p.filter.mode),
+            filter_cutoff: ramped(RampTarget::FilterCutoff, read.float(&p.filter.cutoff))
+                * 2.0_f32.powf(color_octaves),
+            filter_resonance: ramped(RampTarget::FilterResonance, read.float(&p.filter.resonance)),
+            filter_vowel: read.float(&p.filter.vowel),
+            drive: Drive::new(
+                ramped(RampTarget::FilterDrive, read.float(&p.filter.drive)) + color_drive,
+                read.plain(&p.filter.drive_character),
+                read.plain(&p.filter.drive_compensation),
+            ),
+            filter_env_amount: ramped(
+                RampTarget::FilterEnvAmount,
+                read.float(&p.filter.env_amount),
+            ),
+            amp_env: EnvFrame::next(&p.adsr, read, bpm),
+            filter_env: EnvFrame::next(&p.filter_env, read, bpm),
+            glide_time: read.plain(&p.glide.time),
+            glide_mode: read.plain(&p.glide.mode),
+            pitch_bend: 0.0,
+            bend_range: read.plain(&p.bend.range) as f32,
+            bend_release: read.plain(&p.bend.release),
+            bend_smoothing: read.plain(&p.bend.smoothing),
+            modulation: ModFrame::next(&p.modulation, read, lfo, sidechain),
+            analog: ramped(RampTarget::Analog, read.float(&p.analog)),
+            keyboard_pan: ramped(RampTarget::KeyboardPan, read.float(&p.keyboard_pan)),
+            declick: read.plain(&p.declick),
+            band_limited: read.plain(&p.band_limited),
         }
     }
 }
 
+impl FrameParams {
+    /// Bends every voice by `wheel` (`-1..=1`, already smoothed; see
+    /// [`PitchBend`](super::PitchBend)) times the bend range. `0` unless set.
+    pub fn set_pitch_bend(&mut self, wheel: f32) {
+        self.pitch_bend = wheel * self.bend_range;
+    }
+}
+
+/// Frequency in Hz of a (possibly fractional) MIDI note number, with A4 at
+/// `reference_hz`.
+fn note_to_freq(note: f32, reference_hz: f32) -> f32 {
+    reference_hz * 2.0_f32.powf((note - 69.0) / 12.0)
+}
+
+/// Master tuning, sampled once per note-on: a held note keeps the tuning it
+/// started with.
+#[derive(Clone, Copy)]
+pub struct Tuning {
+    /// Semitones added to every played note.
+    pub transpose: f32,
+    /// Frequency of A4 in Hz.
+    pub reference_hz: f32,
+}
+
 pub struct Voice {
     active: bool,
     note: u8,
     velocity: f32,
     base_frequency: f32,
+    /// A4 frequency this note was started with (see [`Tuning`]).
+    reference_hz: f32,
+    /// Current pitch as a fractional MIDI note. Equal to `target_pitch` unless
+    /// the voice is gliding in from a previous note.
+    pitch: f32,
+    target_pitch: f32,
+    /// Semitones covered by the current glide, fixed at note-on so a
+    /// constant-time glide keeps a constant speed all the way in.
+    glide_span: f32,
+    /// Semitones of pitch modulation applied on the last rendered sample.
+    pitch_mod: f32,
+    /// Semitones of pitch bend, part of `pitch_mod`. Follows the wheel until
+    /// the note is released, then per `FrameParams::bend_release`.
+    bend: f32,
 
     osc1: UnisonOscillator,
     osc2: UnisonOscillator,
     osc3: UnisonOscillator,
 
+    noise: Noise,
+    sub: SubOscillator,
+    /// Tuned to the note, between the oscillators and the filter.
+    comb: CombFilter,
     filter: BiquadFilter,
     envelope: Envelope,
+    /// Each oscillator's own amp envelope, for `OscFrame::own_env`. They run
+    /// all the time, so switching one on mid-note picks it up where it is.
+    osc_envelopes: [Envelope; 3],
     /// Modulates the filter cutoff; runs in lockstep with `envelope` (same
     /// note-on/note-off), scaled by `FrameParams::filter_env_amount`.
     filter_env: Envelope,
+    /// Multiplier on both envelopes' release times, set from the release
+    /// velocity at note-off.
+    release_scale: f32,
+    /// Fading out after [`Voice::panic`]: every release lasts
+    /// [`PANIC_FADE_SECONDS`].
+    panicked: bool,
+    /// The `Release` mod source: set at note-off, then decaying.
+    release_blip: f32,
+    /// Per-sample factor `release_blip` decays by, and the (seconds, sample
+    /// rate) it was worked out for.
+    blip_decay: f32,
+    blip_decay_for: (f32, f32),
+    /// Samples until the next control-rate update of the filter coefficients
+    /// (see `dsp::mod_matrix`). `0` forces an update on the next sample.
+    control_countdown: u32,
+    /// Times the filter produced NaN/Inf and was reset since the last
+    /// [`Voice::take_filter_resets`].
+    filter_resets: u32,
+    /// Slow per-voice pitch/level wander and per-note cutoff offset, scaled by
+    /// `FrameParams::analog`.
+    drift: Drift,
+    /// Each oscillator's share of the last rendered sample, before the filter
+    /// (see [`Voice::osc_outputs`]).
+    osc_out: [f32; 3],
+    /// The mod sources on the last rendered sample (see
+    /// [`Voice::mod_sources`]).
+    sources: ModSources,
 }
 
 impl Voice {
-    pub fn new(sample_rate: f32) -> Self {
+    /// `seed` decorrelates this voice's analog drift and noise from the
+    /// others'; the voice's slot index is a good choice.
+    pub fn new(sample_rate: f32, seed: u32) -> Self {
         Self {
             active: false,
             note: 0,
             velocity: 0.0,
             base_frequency: 440.0,
+            reference_hz: 440.0,
+            pitch: 69.0,
+            target_pitch: 69.0,
+            glide_span: 0.0,
+            pitch_mod: 0.0,
+            bend: 0.0,
             osc1: UnisonOscillator::new(8),
             osc2: UnisonOscillator::new(8),
             osc3: UnisonOscillator::new(8),
+            noise: Noise::new(seed),
+            sub: SubOscillator::new(),
+            comb: CombFilter::new(),
             filter: BiquadFilter::new(sample_rate),
             envelope: Envelope::new(sample_rate),
+            osc_envelopes: std::array::from_fn(|_| Envelope::new(sample_rate)),
             filter_env: Envelope::new(sample_rate),
+            release_scale: 1.0,
+            panicked: false,
+            release_blip: 0.0,
+            blip_decay: 0.0,
+            blip_decay_for: (0.0, 0.0),
+            control_countdown: 0,
+            filter_resets: 0,
+            drift: Drift::new(seed),
+            osc_out: [0.0; 3],
+            sources: ModSources::default(),
         }
     }
 
-    pub fn note_on(&mut self, note: u8, velocity: f32) {
+    /// Starts a note. `glide_from` is the played note (MIDI note) to glide in
+    /// from, or `None` to start directly on `note`; `tuning` transposes both.
+    pub fn note_on(&mut self, note: u8, velocity: f32, glide_from: Option<f32>, tuning: Tuning) {
+        let was_idle = !self.is_active();
         self.active = true;
         self.note = note;
         self.velocity = velocity;
-        self.base_frequency = 440.0 * (2.0_f32).powf((note as f32 - 69.0) / 12.0);
+        self.reference_hz = tuning.reference_hz;
+        self.target_pitch = note as f32 + tuning.transpose;
+        self.pitch = glide_from.map_or(self.target_pitch, |from| from + tuning.transpose);
+        self.glide_span = (self.target_pitch - self.pitch).abs();
+        self.pitch_mod = 0.0;
+        self.bend = 0.0;
+        self.release_scale = 1.0;
+        self.panicked = false;
+        self.release_blip = 0.0;
+        self.base_frequency = note_to_freq(self.pitch, self.reference_hz);
         self.osc1.reset();
         self.osc2.reset();
         self.osc3.reset();
+        self.sub.reset();
+        self.comb.note_on(was_idle);
         self.filter.reset();
+        self.control_countdown = 0;
         self.envelope.note_on();
+        self.osc_envelopes.iter_mut().for_each(Envelope::note_on);
         self.filter_env.note_on();
+        self.drift.note_on();
     }
 
     pub fn note_off(&mut self) {
+        self.release_blip = 1.0;
         self.envelope.note_off();
+        self.osc_envelopes.iter_mut().for_each(Envelope::note_off);
         self.filter_env.note_off();
     }
 
+    /// Fades the voice out within [`PANIC_FADE_SECONDS`], without a release
+    /// blip. For all-notes-off.
+    pub fn panic(&mut self) {
+        if self.active {
+            self.note_off();
+            self.release_blip = 0.0;
+            self.panicked = true;
+        }
+    }
+
     /// Begins the release stage if this voice is playing the given note.
-    pub fn release_if_matches(&mut self, note: u8) {
+    /// `release_velocity` (`0` when the controller doesn't send one) scales the
+    /// release time by `depth`: hard lifts snap shorter, gentle ones ring out.
+    pub fn release_if_matches(&mut self, note: u8, release_velocity: f32, depth: f32) {
         if self.active && self.note == note {
+            self.release_scale = if release_velocity > 0.0 {
+                RELEASE_VELOCITY_RANGE.powf(depth * (1.0 - 2.0 * release_velocity))
+            } else {
+                1.0
+            };
+            self.release_blip = if release_velocity > 0.0 {
+                release_velocity
+            } else {
+                1.0
+            };
             self.envelope.note_off();
+            self.osc_envelopes.iter_mut().for_each(Envelope::note_off);
             self.filter_env.note_off();
         }
     }
 
+    /// The MIDI note this voice was last started with.
+    pub fn note(&self) -> u8 {
+        self.note
+    }
+
+    /// The pitch the voice is sounding right now, as a fractional MIDI note:
+    /// the played note plus master transpose, any glide still in progress,
+    /// pitch bend and pitch modulation. Relative to the master tuning's A4.
+    pub fn sounding_pitch(&self) -> f32 {
+        self.pitch + self.pitch_mod
+    }
+
+    /// Whether the voice is still gliding towards its note.
+    pub fn is_gliding(&self) -> bool {
+        self.pitch != self.target_pitch
+    }
+
+    /// Whether the voice has been released and is fading out.
+    pub fn is_releasing(&self) -> bool {
+        self.envelope.is_releasing()
+    }
+
+    /// The amp envelope's stage. Oscillators on their own envelopes may be
+    /// elsewhere in theirs.
+    pub fn envelope_stage(&self) -> EnvelopeStage {
+        self.envelope.stage()
+    }
+
+    /// Returns and clears the number of NaN/Inf filter resets since the last
+    /// call. Polled once per block for the diagnostics counter.
+    pub fn take_filter_resets(&mut self) -> u32 {
+        std::mem::take(&mut self.filter_resets)
+    }
+
     /// Whether this slot is available for a new note.
     pub fn is_free(&self) -> bool {
         !self.active
@@ -161,6 +576,9 @@ impl Voice {
     pub fn set_sample_rate(&mut self, sample_rate: f32) {
         self.filter.set_sample_rate(sample_rate);
         self.envelope.set_sample_rate(sample_rate);
+        for envelope in &mut self.osc_envelopes {
+            envelope.set_sample_rate(sample_rate);
+        }
         self.filter_env.set_sample_rate(sample_rate);
     }
 
@@ -171,6 +589,9 @@ impl Voice {
         self.osc1.reset();
         self.osc2.reset();
         self.osc3.reset();
+        self.noise.reset();
+        self.sub.reset();
+        self.comb.reset();
         self.filter.reset();
     }
 
@@ -183,48 +604,287 @@ impl Voice {
         self.osc3.set_num_voices(counts[2]);
     }
 
+    /// Moves `pitch` one sample closer to `target_pitch`.
+    fn advance_glide(&mut self, f: &FrameParams, sample_rate: f32) {
+        let glide_samples = (f.glide_time * sample_rate).max(1.0);
+        let step = match f.glide_mode {
+            GlideMode::ConstantTime => self.glide_span / glide_samples,
+            GlideMode::ConstantRate => 12.0 / glide_samples,
+        };
+        let remaining = self.target_pitch - self.pitch;
+        if remaining.abs() <= step {
+            self.pitch = self.target_pitch;
+        } else {
+            self.pitch += step.copysign(remaining);
+        }
+        self.base_frequency = note_to_freq(self.pitch, self.reference_hz);
+    }
+
+    /// The release blip's value for this sample, then decays it. The decay
+    /// factor is only worked out again when the time or rate changes.
+    #[inline]
+    fn next_release_blip(&mut self, seconds: f32, sample_rate: f32) -> f32 {
+        let blip = self.release_blip;
+        if blip > 0.0 {
+            if self.blip_decay_for != (seconds, sample_rate) {
+                self.blip_decay_for = (seconds, sample_rate);
+                // -60 dB over `seconds`.
+                self.blip_decay = 0.001f32.powf(1.0 / (seconds * sample_rate).max(1.0));
+            }
+            self.release_blip *= self.blip_decay;
+            if self.release_blip < 1e-4 {
+                self.release_blip = 0.0;
+            }
+        }
+        blip
+    }
+
     /// Renders one sample from the shared per-frame parameter snapshot.
     pub fn render(&mut self, f: &FrameParams, sample_rate: f32) -> f32 {
-        let base = self.base_frequency;
-        let mut sample = render_osc(&mut self.osc1, &f.osc[0], base, sample_rate)
-            + render_osc(&mut self.osc2, &f.osc[1], base, sample_rate)
-            + render_osc(&mut self.osc3, &f.osc[2], base, sample_rate);
-
-        // Advance the filter envelope in lockstep with the amp envelope and use
-        // it to push the cutoff up/down by `env_amount` octaves. `2^0 == 1`, so
-        // an amount of 0 leaves the cutoff exactly at the knob value. The filter
-        // itself re-clamps the result to [20 Hz, Nyquist].
+        if self.pitch != self.target_pitch {
+            self.advance_glide(f, sample_rate);
+        }
+
+        // Every envelope's release stretches with the release velocity, or is
+        // the short fade after a panic.
+        let (panicked, release_scale) = (self.panicked, self.release_scale);
+        let release = move |seconds: f32| {
+            if panicked {
+                PANIC_FADE_SECONDS
+            } else {
+                seconds * release_scale
+            }
+        };
+
+        // The filter envelope advances every sample, in lockstep with the amp
+        // envelope, even though it only reaches the filter at control rate: it
+        // is also a per-sample mod source.
+        let fe = &f.filter_env;
         let filter_env_level = self.filter_env.process(
-            f.filter_attack,
-            f.filter_decay,
-            f.filter_sustain,
-            f.filter_release,
+            fe.attack,
+            fe.hold,
+            fe.decay,
+            fe.sustain,
+            release(fe.release),
+            f.declick,
         );
-        let modulated_cutoff = f.filter_cutoff * 2.0_f32.powf(f.filter_env_amount * filter_env_level);
+        let sources = ModSources {
+            lfo: f.modulation.lfo,
+            filter_env: filter_env_level,
+            velocity: self.velocity,
+            sidechain: f.modulation.sidechain,
+            release: self.next_release_blip(f.modulation.release_blip, sample_rate),
+        };
+        self.sources = sources;
+
+        // Analog drift wanders whether or not it is dialled in, so turning
+        // the knob up doesn't make every voice start from the same place.
+        let (drift_pitch, drift_level) = self.drift.next(sample_rate);
+
+        if !self.envelope.is_releasing() || f.bend_release == BendRelease::Follow {
+            self.bend = f.pitch_bend;
+        } else if f.bend_release == BendRelease::Return {
+            self.bend -= self.bend / (f.bend_smoothing * sample_rate).max(1.0);
+        }
 
-        self.filter
-            .set_coefficients(f.filter_mode, modulated_cutoff, f.filter_resonance);
-        sample = self.filter.process(sample, f.filter_drive);
+        // Per-sample destinations. Drift and the bend ride along with pitch
+        // modulation.
+        let mods = f.modulation.sample_mods(&sources);
+        self.pitch_mod =
+            mods.pitch + self.bend + f.analog * drift_pitch * DRIFT_PITCH_CENTS / 100.0;
+        let pitch_mult = if self.pitch_mod == 0.0 {
+            1.0
+        } else {
+            2.0_f32.powf(self.pitch_mod / 12.0)
+        };
+        let pulse_width = (0.5 + mods.pulse_width).clamp(0.05, 0.95);
+        self.osc1.set_pulse_width(pulse_width);
+        self.osc2.set_pulse_width(pulse_width);
+        self.osc3.set_pulse_width(pulse_width);
+        self.osc1.set_band_limited(f.band_limited);
+        self.osc2.set_band_limited(f.band_limited);
+        self.osc3.set_band_limited(f.band_limited);
 
-        let envelope_level = self
-            .envelope
-            .process(f.attack, f.decay, f.sustain, f.release);
+        let morph = |fr: &OscFrame| fr.morph.map(|at| (at + mods.morph).clamp(0.0, 1.0));
+        self.osc1.set_morph(morph(&f.osc[0]));
+        self.osc2.set_morph(morph(&f.osc[1]));
+        self.osc3.set_morph(morph(&f.osc[2]));
 
-        if !self.envelope.is_active() {
+        let base = self.base_frequency * pitch_mult;
+        let oscs = [
+            render_osc(&mut self.osc1, &f.osc[0], base, pitch_mult, sample_rate),
+            render_osc(&mut self.osc2, &f.osc[1], base, pitch_mult, sample_rate),
+            render_osc(&mut self.osc3, &f.osc[2], base, pitch_mult, sample_rate),
+        ];
+
+        let ae = &f.amp_env;
+        let envelope_level = self.envelope.process(
+            ae.attack,
+            ae.hold,
+            ae.decay,
+            ae.sustain,
+            release(ae.release),
+            f.declick,
+        );
+        // Each oscillator's amp envelope level: the voice's, or its own.
+        let mut env_levels = [envelope_level; 3];
+        for (i, envelope) in self.osc_envelopes.iter_mut().enumerate() {
+            let e = &f.osc[i].env;
+            let own_level = envelope.process(
+                e.attack,
+                e.hold,
+                e.decay,
+                e.sustain,
+                release(e.release),
+                f.declick,
+            );
+            if f.osc[i].own_env {
+                env_levels[i] = own_level;
+            }
+        }
+        // With any oscillator on its own envelope, the envelopes have to act
+        // on each oscillator before they're mixed, so they come ahead of the
+        // comb and filter rather than after.
+        let own_envelopes = f.osc.iter().any(|frame| frame.own_env);
+        // Oscillators routed around the filter are mixed back in after it.
+        let (mut sample, mut dry) = (0.0, 0.0);
+        for ((&osc, env_level), frame) in oscs.iter().zip(env_levels).zip(&f.osc) {
+            let osc = if own_envelopes { osc * env_level } else { osc };
+            if frame.to_filter {
+                sample += osc;
+            } else {
+                dry += osc;
+            }
+        }
+        if f.noise_level > 0.0 {
+            // Through the amp envelope, like an oscillator on it.
+            let noise = self.noise.next(f.noise_color) * f.noise_level;
+            sample += if own_envelopes { noise * envelope_level } else { noise };
+        }
+        if f.sub_level > 0.0 {
+            // Under oscillator 1 whether or not it's heard, and always into
+            // the filter.
+            let freq = osc_frequency(&f.osc[0], base, pitch_mult) / f.sub_divisor;
+            let sub = self.sub.next(f.sub_waveform, freq, sample_rate, f.band_limited)
+                * f.sub_level;
+            sample += if own_envelopes { sub * envelope_level } else { sub };
+        }
+        sample = self.comb.process(sample, &f.comb, base, sample_rate);
+
+        // Control-rate destinations. The filter envelope pushes the cutoff
+        // up/down by `env_amount` octaves and the matrix adds its own octaves on
+        // top; `2^0 == 1`, so with both at 0 the cutoff sits exactly at the knob
+        // value. The filter itself re-clamps the result to [20 Hz, 20 kHz].
+        if self.control_countdown == 0 {
+            let control = f.modulation.control_mods(&sources);
+            let octaves = f.filter_env_amount * filter_env_level
+                + control.cutoff
+                + f.analog * self.drift.cutoff() * DRIFT_CUTOFF_OCTAVES;
+            let modulated_cutoff = f.filter_cutoff * 2.0_f32.powf(octaves);
+            let resonance = (f.filter_resonance + control.resonance).clamp(0.0, 1.0);
+            let vowel = (f.filter_vowel + control.vowel).clamp(0.0, 1.0);
+            self.filter.ramp_to(
+                f.filter_mode,
+                modulated_cutoff,
+                resonance,
+                vowel,
+                CONTROL_INTERVAL,
+            );
+            self.control_countdown = CONTROL_INTERVAL;
+        }
+        self.control_countdown -= 1;
+
+        sample = self.filter.process(sample, f.drive);
+
+        // Extreme drive/resonance can blow the biquad up. Once its state holds
+        // a NaN/Inf it never recovers on its own, so drop this sample and
+        // restart the filter from silence.
+        if !sample.is_finite() {
+            self.filter.reset();
+            self.filter_resets += 1;
+            sample = 0.0;
+        }
+        sample += dry;
+
+        // The voice is done once every envelope an oscillator plays through
+        // is.
+        let sounding = (0..3).any(|i| {
+            if f.osc[i].own_env {
+                self.osc_envelopes[i].is_active()
+            } else {
+                self.envelope.is_active()
+            }
+        });
+        if !sounding {
             self.active = false;
         }
 
-        sample * envelope_level * self.velocity
+        let level = (1.0 + mods.level + f.analog * drift_level * DRIFT_LEVEL).max(0.0);
+        self.osc_out = [0, 1, 2].map(|i| oscs[i] * (env_levels[i] * self.velocity * level));
+        if own_envelopes {
+            sample * self.velocity * level
+        } else {
+            sample * (envelope_level * self.velocity * level)
+        }
+    }
+
+    /// Each oscillator's part of the last [`Voice::render`]ed sample, through
+    /// the amp envelope but not the comb or the filter: the comb would need a
+    /// delay line per oscillator, and the drive makes the filter nonlinear, so
+    /// its output can't be split back up. For the per-oscillator output buses.
+    pub fn osc_outputs(&self) -> [f32; 3] {
+        self.osc_out
+    }
+
+    /// The mod source values the last [`Voice::render`]ed sample used, for
+    /// the source monitor.
+    pub fn mod_sources(&self) -> ModSources {
+        self.sources
+    }
+
+    /// Left/right gains for this voice's output: low notes lean left and high
+    /// notes right, by `FrameParams::keyboard_pan`. A balance law rather than
+    /// equal power, so a centred voice (or a pan amount of 0) keeps unity gain
+    /// in both channels.
+    pub fn pan_gains(&self, f: &FrameParams) -> (f32, f32) {
+        let pan = ((self.note as f32 - 60.0) / KEYBOARD_PAN_SPAN).clamp(-1.0, 1.0) * f.keyboard_pan;
+        ((1.0 - pan).min(1.0), (1.0 + pan).min(1.0))
     }
 
-    /// Whether the voice is still producing sound (envelope not idle).
+    /// Whether the voice is still producing sound: its envelope isn't idle,
+    /// or an oscillator's own envelope is still ringing past it.
     pub fn is_active(&self) -> bool {
-        self.envelope.is_active()
+        self.envelope.is_active() || self.active
     }
 }
 
-fn render_osc(osc: &mut UnisonOscillator, fr: &OscFrame, base_freq: f32, sample_rate: f32) -> f32 {
-    let freq = base_freq * fr.octave_mult * fr.freq_ratio * fr.detune_mult;
+/// An oscillator's frequency before unison spreads it. `base_freq` is the
+/// voice's (glided, pitch-modulated) note frequency; `pitch_mult` is the
+/// modulation alone, which a fixed-frequency oscillator still follows.
+#[inline]
+fn osc_frequency(fr: &OscFrame, base_freq: f32, pitch_mult: f32) -> f32 {
+    // Without key tracking, `freq_ratio * 440` is just the frequency knob.
+    let root = if fr.key_track { base_freq } else { 440.0 * pitch_mult };
+    root * fr.octave_mult * fr.freq_ratio * fr.detune_mult
+}
+
+/// `base_freq` and `pitch_mult` as for [`osc_frequency`].
+fn render_osc(
+    osc: &mut UnisonOscillator,
+    fr: &OscFrame,
+    base_freq: f32,
+    pitch_mult: f32,
+    sample_rate: f32,
+) -> f32 {
+    // Silenced oscillators still run so their phase stays continuous for
+    // when they come back.
+    let gain = if fr.audible { fr.gain } else { 0.0 };
+    let freq = osc_frequency(fr, base_freq, pitch_mult);
+    osc.set_phase_distortion(fr.phase_distortion);
+    osc.set_wavefold(fr.wavefold);
+    osc.set_drawbars(fr.drawbars);
+    osc.set_detune_shape(fr.unison_curve, fr.unison_density);
+    osc.set_gain_compensation(fr.unison_compensation);
     osc.process(
         fr.waveform,
         freq,
@@ -233,5 +893,5 @@ fn render_osc(osc: &mut UnisonOscillator, fr: &OscFrame, base_freq: f32, sample_
         fr.blend,
         fr.volume,
         sample_rate,
-    ) * fr.gain
+    ) * gain
 }
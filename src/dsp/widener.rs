@@ -0,0 +1,60 @@
+//! Master-bus stereo width: mid/side processing that scales the side signal.
+//!
+//! Only the side signal above the crossover is widened. Below it the width is
+//! capped at 100%, so bass never gets more out-of-phase content than the mix
+//! already had and stays solid when summed to mono. Narrowing applies to the
+//! whole spectrum. The crossover is a one-pole split whose bands sum back to
+//! the input exactly, so 100% width is transparent.
+
+use std::f32::consts::PI;
+
+pub struct StereoWidener {
+    /// One-pole lowpass state on the side signal.
+    low_side: f32,
+    coef: f32,
+    /// Crossover `coef` was computed for, so it is only recomputed on change.
+    crossover: f32,
+}
+
+impl Default for StereoWidener {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StereoWidener {
+    pub fn new() -> Self {
+        Self {
+            low_side: 0.0,
+            coef: 0.0,
+            crossover: -1.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.low_side = 0.0;
+        self.crossover = -1.0;
+    }
+
+    /// `width` is `0` (mono) to `2` (200%); `crossover` is in Hz.
+    #[inline]
+    pub fn process(
+        &mut self,
+        width: f32,
+        crossover: f32,
+        sample_rate: f32,
+        [left, right]: [f32; 2],
+    ) -> [f32; 2] {
+        if crossover != self.crossover {
+            self.crossover = crossover;
+            self.coef = 1.0 - (-2.0 * PI * crossover / sample_rate).exp();
+        }
+
+        let mid = (left + right) * 0.5;
+        let side = (left - right) * 0.5;
+        self.low_side += self.coef * (side - self.low_side);
+        let high_side = side - self.low_side;
+        let side = self.low_side * width.min(1.0) + high_side * width;
+        [mid + side, mid - side]
+    }
+}
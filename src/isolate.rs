@@ -0,0 +1,27 @@
+//! Panic isolation for everything that isn't the audio thread: the editor's
+//! event handling, the chat and the assistant's tools. A panic in one of them
+//! would otherwise unwind into the host's GUI loop and can take the host down
+//! with it; caught here, the caller logs it under its own target (so it shows
+//! in the log viewer) and carries on, while `process` keeps running as if
+//! nothing happened.
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+
+/// The message a caught panic was raised with.
+pub fn panic_message(panic: &(dyn Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Run `f`, catching a panic, which is returned as its message. `tracing`
+/// targets must be constants, so the caller logs it, under its own.
+///
+/// Whatever `f` was changing may be left half-changed: callers put their
+/// state back in order when this fails.
+pub fn guard<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|panic| panic_message(&*panic))
+}
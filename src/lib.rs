@@ -3,19 +3,84 @@ use nih_plug::prelude::*;
 
 use std::num::NonZeroU32;
 use std::sync::Arc;
+use std::time::Instant;
 
+mod actions;
 mod ai;
-mod dsp;
+mod diagnostics;
+mod isolate;
+// Public for the benchmarks (`benches/`) and golden-audio tests (`tests/`).
+pub mod dsp;
+mod locks;
+mod logging;
+mod osc_copy;
 mod params;
+mod persist;
+mod quality;
+mod ramps;
+mod recovery;
+pub mod render;
+mod schedule;
+mod settings;
+mod transition;
 mod ui;
+mod watch;
+mod xy;
 
-pub use params::{AdsrParams, FilterMode, FilterParams, OscillatorParams, SineParams, Waveform};
-pub use ui::PeakMeter;
+pub use params::{
+    AdsrParams, ArpMode, ArpParams, BendParams, BendRelease, ColorParams, CombParams,
+    DetuneCurve, DriveCharacter, FilterMode, FilterParams, GateParams, GlideMode, GlideParams,
+    LfoReset, ModDestination, ModParams, ModSlotParams, ModSource, NoiseColor, NoiseParams,
+    NoteDivision, OscillatorParams, Scale, ScaleRoot, SineParams, Smoothing, SubParams,
+    SubWaveform, TriggerParams, Waveform, XyPadParams,
+};
+pub use actions::{PatchAction, PatchActions};
+pub use diagnostics::Diagnostics;
+pub use ramps::ParamRamps;
+pub use schedule::Schedule;
+pub use transition::PresetTransition;
+use actions::HostLink;
+use diagnostics::{TempoSource, TransportInfo, UsageBlock, VoiceSnapshot, MAX_NOTE_REQUESTS};
+pub use ui::{MixMeters, PeakMeter};
+/// The assistant's tools (schemas and dispatcher), for an MCP server.
+#[cfg(feature = "mcp")]
+pub use ai::host::ToolContext;
+#[cfg(feature = "mcp")]
+pub use ai::tools::{
+    dispatch as dispatch_tool, gemini_tools, permission_denied, tool_names, READ_ONLY_TOOLS,
+};
+/// The chat's slash-command parser, for the fuzz targets in `fuzz/`.
+#[cfg(feature = "chat")]
+#[doc(hidden)]
+pub use ai::commands::parse_message as parse_chat_message;
 
-use dsp::{FrameParams, Voice};
+use dsp::analysis::BrightnessAnalyzer;
+use dsp::{
+    ArpEvent, ArpFrame, Arpeggiator, AudioThrough, EnvelopeFollower, FrameParams, Latch, Lfo,
+    ModProbe, ParamRead, PitchBend, ProbeTarget, Quantizer, SourceSampler, Tuning, Voice,
+};
+#[cfg(feature = "fx")]
+use dsp::{GateFrame, StereoWidener, TranceGate};
+use quality::AutoQuality;
+use persist::ProjectState;
+use ramps::RampRunner;
+use recovery::{Recovery, AUTOSAVE_SECONDS};
+use watch::ParamWatch;
+use xy::XyRunner;
 
 /// Number of polyphonic voices in the pool.
-const NUM_VOICES: usize = 16;
+pub(crate) const NUM_VOICES: usize = 16;
+
+/// How often the task executor writes a running preset morph while the
+/// editor is closed; about the editor's own poll rate.
+const TRANSITION_SYNC_SECONDS: f32 = 0.05;
+
+/// Tempo used for synced times when neither the host nor tap tempo gives one.
+const DEFAULT_BPM: f32 = 120.0;
+
+/// Per-oscillator output buses in the layouts that have them.
+const NUM_OSC_OUTPUTS: usize = 3;
+const OSC_OUTPUT_NAMES: [&str; NUM_OSC_OUTPUTS] = ["Osc 1", "Osc 2", "Osc 3"];
 
 /// Per-block decay applied to the published output peak so the meter falls back
 /// smoothly between transients (~0.85 ≈ a natural VU-style release at audio
@@ -26,67 +91,598 @@ pub struct SineSynth {
     params: Arc<SineParams>,
     sample_rate: f32,
     voices: Vec<Voice>,
+    /// Global LFO feeding the modulation matrix; shared by every voice.
+    lfo: Lfo,
+    /// Whether the transport was playing last block, to spot it starting.
+    was_playing: bool,
+    /// Follows the sidechain input's level, the matrix's `Sidechain` source.
+    sidechain: EnvelopeFollower,
+    /// Filters the main input when the host connects one.
+    through: AudioThrough,
+    /// Rhythmic gate and auto-pan on the master bus.
+    #[cfg(feature = "fx")]
+    gate: TranceGate,
+    /// Master stereo width, the last stage before the output.
+    #[cfg(feature = "fx")]
+    widener: StereoWidener,
+    /// Main input channels in the active layout, 0 without one (then the
+    /// buffer holds no input to read).
+    input_channels: usize,
+    /// Main output channels in the active layout: 1 gets the stereo mix
+    /// summed to mono, and any past the front pair pass the input through.
+    output_channels: usize,
     /// Output level published to the GUI meter. Lock-free; written once per block.
     peak_meter: Arc<PeakMeter>,
+    /// Each oscillator's level before the filter and the voice mix's after
+    /// it, for the GUI's mixer meters. Written once per block.
+    mix_meters: MixMeters,
+    /// Voice-allocator state for the voice monitor and the AI tools. Lock-free;
+    /// written once per block.
+    diagnostics: Arc<Diagnostics>,
+    /// Measures the output's brightness for the `adjust_brightness` tool.
+    brightness: BrightnessAnalyzer,
+    /// Fires and traces a modulator on request (see `dsp::probe`).
+    probe: ModProbe,
+    /// Picks the samples the source monitor shows (see `dsp::probe`).
+    source_sampler: SourceSampler,
+    /// Samples the param the automation preview watches (see `watch.rs`).
+    watch: ParamWatch,
+    /// Offsets the XY pads' targets (see `xy.rs`).
+    xy: XyRunner,
+    /// Caps the unison voices under heavy load (see `quality.rs`).
+    quality: AutoQuality,
+    /// The bend wheel, smoothed per sample.
+    pitch_bend: PitchBend,
+    /// Steals counted in the current steals-per-second window.
+    steals_in_window: u32,
+    /// Samples processed in the current steals-per-second window.
+    stats_window_samples: u32,
+    /// Actions raised by the trigger params and program changes, carried out
+    /// by the task executor.
+    actions: Arc<PatchActions>,
+    /// The host's context for writing params, once the editor has lent it.
+    host: Arc<HostLink>,
+    /// Trigger param states seen last block, for edge detection: next, prev,
+    /// randomize, tap.
+    triggers_down: [bool; 4],
+    /// Samples since the last tap-tempo tap, while within the tap timeout.
+    samples_since_tap: Option<u64>,
+    /// Parameter ramps started by the AI tools, mirrored into the params by
+    /// the editor or the task executor.
+    ramps: Arc<ParamRamps>,
+    /// Plays those ramps back sample by sample.
+    ramp_runner: RampRunner,
+    /// Preset morphs; clocked here, written into the params by the editor or
+    /// the task executor.
+    transition: Arc<PresetTransition>,
+    /// Samples until the task executor next writes the morph, while the
+    /// editor is closed.
+    transition_countdown: usize,
+    /// Parameter changes queued for musical times; fired against the
+    /// transport here, applied by the task executor.
+    schedule: Arc<Schedule>,
+    /// Keys currently held down, for legato (fingered) glide.
+    held_notes: [bool; 128],
+    /// The most recently played note; new notes glide in from here.
+    last_note: Option<u8>,
+    /// The working patch's recovery file (see `recovery.rs`).
+    recovery: Arc<Recovery>,
+    /// Samples until the next autosave.
+    autosave_countdown: usize,
+    /// The note each key sounds, as snapped to the scale when it went down,
+    /// so its key-up ends that note even if the scale has changed since.
+    key_notes: [u8; 128],
+    /// Notes the assistant asked to play (see `Diagnostics::request_note`):
+    /// the key and the samples left until its key-up.
+    requested_notes: [Option<(u8, usize)>; MAX_NOTE_REQUESTS],
+    /// Holds released keys down while the latch param is on (see
+    /// `dsp::latch`).
+    latch: Latch,
+    /// Takes the keys while it's on (see `dsp::arp`).
+    arp: Arpeggiator,
+    /// The arp note last sent out as MIDI and not yet ended, so switching
+    /// MIDI out off mid-note still sends its note-off.
+    arp_midi_note: Option<u8>,
+    /// The last bank select MSB (CC 0) and LSB (CC 32) received.
+    bank_select: [Option<u8>; 2],
 }
 
 impl Default for SineSynth {
     fn default() -> Self {
+        logging::init();
         let sample_rate = 44100.0;
         let mut voices = Vec::with_capacity(NUM_VOICES);
-        for _ in 0..NUM_VOICES {
-            voices.push(Voice::new(sample_rate));
+        for index in 0..NUM_VOICES {
+            voices.push(Voice::new(sample_rate, index as u32));
         }
 
+        let params = Arc::new(SineParams::default());
+        let watch = ParamWatch::new(&params, sample_rate);
+        let xy = XyRunner::new(&params);
+
         Self {
-            params: Arc::new(SineParams::default()),
+            params,
             sample_rate,
             voices,
+            lfo: Lfo::new(),
+            was_playing: false,
+            sidechain: EnvelopeFollower::new(),
+            through: AudioThrough::new(sample_rate),
+            #[cfg(feature = "fx")]
+            gate: TranceGate::new(),
+            #[cfg(feature = "fx")]
+            widener: StereoWidener::new(),
+            input_channels: 0,
+            output_channels: 2,
             peak_meter: Arc::new(PeakMeter::new()),
+            mix_meters: MixMeters::default(),
+            diagnostics: Arc::new(Diagnostics::new()),
+            brightness: BrightnessAnalyzer::new(),
+            probe: ModProbe::new(sample_rate),
+            source_sampler: SourceSampler::new(sample_rate),
+            watch,
+            xy,
+            quality: AutoQuality::new(),
+            pitch_bend: PitchBend::new(),
+            steals_in_window: 0,
+            stats_window_samples: 0,
+            actions: Arc::new(PatchActions::new()),
+            host: Arc::new(HostLink::new()),
+            triggers_down: [false; 4],
+            samples_since_tap: None,
+            ramps: Arc::new(ParamRamps::new()),
+            transition: Arc::new(PresetTransition::new()),
+            transition_countdown: 0,
+            ramp_runner: RampRunner::new(),
+            schedule: Arc::new(Schedule::new()),
+            held_notes: [false; 128],
+            last_note: None,
+            recovery: Arc::new(Recovery::new()),
+            autosave_countdown: 0,
+            key_notes: std::array::from_fn(|note| note as u8),
+            requested_notes: [None; MAX_NOTE_REQUESTS],
+            latch: Latch::new(),
+            arp: Arpeggiator::new(),
+            arp_midi_note: None,
+            bank_select: [None; 2],
         }
     }
 }
 
 impl SineSynth {
-    /// Pushes the current unison voice counts to every voice. Control-rate, so
-    /// this runs once per process block rather than per sample.
+    /// Pushes the current unison voice counts, within auto quality's cap, to
+    /// every voice. Control-rate, so this runs once per process block rather
+    /// than per sample.
     fn sync_unison_voice_counts(&mut self) {
-        let counts = [
-            self.params.osc1.unison_voices.value() as usize,
-            self.params.osc2.unison_voices.value() as usize,
-            self.params.osc3.unison_voices.value() as usize,
-        ];
+        let cap = self.quality.unison_cap();
+        let counts = [&self.params.osc1, &self.params.osc2, &self.params.osc3]
+            .map(|osc| (osc.unison_voices.value() as usize).min(cap));
         for voice in &mut self.voices {
             voice.set_unison_voices(counts);
         }
     }
 
+    /// The pitch a new note should glide in from, if glide applies to it. With
+    /// legato glide on, only notes played while another key is held glide.
+    fn glide_source(&self) -> Option<f32> {
+        if self.params.glide.time.value() <= 0.0 {
+            return None;
+        }
+        if self.params.glide.legato.value() && !self.held_notes.iter().any(|&held| held) {
+            return None;
+        }
+        self.last_note.map(|note| note as f32)
+    }
+
+    /// Adds the block to the session usage statistics. RT-safe: atomic
+    /// adds only.
+    fn record_usage(&self, samples: usize, busy_seconds: f32) {
+        let oscs = [&self.params.osc1, &self.params.osc2, &self.params.osc3];
+        let any_solo = oscs.iter().any(|osc| osc.solo.value());
+        let sounding = oscs.map(|osc| {
+            let audible = if any_solo { osc.solo.value() } else { !osc.mute.value() };
+            (audible && osc.gain.value() > 0.0).then(|| osc.waveform.value())
+        });
+        self.diagnostics.record_usage(UsageBlock {
+            samples,
+            voices: self.voices.iter().filter(|v| v.is_active()).count() as u32,
+            sounding,
+            busy_seconds,
+            load: self.quality.load(),
+        });
+    }
+
+    /// The master tuning new notes start with.
+    fn tuning(&self) -> Tuning {
+        Tuning {
+            transpose: self.params.master_transpose.value() as f32,
+            reference_hz: self.params.master_tune.value(),
+        }
+    }
+
+    /// Publishes the voice allocator's state and the voices' NaN/Inf filter
+    /// resets for the GUI / tools, and rolls the steals-per-second window.
+    /// RT-safe: atomic stores only.
+    fn publish_voice_stats(&mut self, block_len: usize) {
+        let mut active = 0;
+        let mut filter_resets = 0;
+        for (index, voice) in self.voices.iter_mut().enumerate() {
+            filter_resets += voice.take_filter_resets();
+            if voice.is_active() {
+                active += 1;
+                self.diagnostics.publish_voice(
+                    index,
+                    Some(VoiceSnapshot {
+                        note: voice.note(),
+                        pitch: voice.sounding_pitch(),
+                        gliding: voice.is_gliding(),
+                        age_samples: voice.age(),
+                        releasing: voice.is_releasing(),
+                        stage: voice.envelope_stage(),
+                    }),
+                );
+            } else {
+                self.diagnostics.publish_voice(index, None);
+            }
+        }
+        self.diagnostics.set_active_voices(active);
+        self.diagnostics.set_block_size(block_len);
+        self.diagnostics.record_filter_resets(filter_resets);
+
+        self.stats_window_samples += block_len as u32;
+        if self.stats_window_samples as f32 >= self.sample_rate {
+            let seconds = self.stats_window_samples as f32 / self.sample_rate;
+            self.diagnostics
+                .set_steals_per_second(self.steals_in_window as f32 / seconds);
+            self.steals_in_window = 0;
+            self.stats_window_samples = 0;
+        }
+    }
+
+    /// Turns off → on edges of the trigger params into actions. Called once
+    /// per block, so triggers resolve to block boundaries. RT-safe: the task
+    /// executor does the real work (see `actions.rs`); tap tempo only needs
+    /// arithmetic.
+    fn poll_triggers(&mut self, block_len: usize) {
+        let t = &self.params.triggers;
+        let down = [
+            t.next_preset.value(),
+            t.prev_preset.value(),
+            t.randomize.value(),
+            t.tap_tempo.value(),
+        ];
+        let pressed: [bool; 4] = std::array::from_fn(|i| down[i] && !self.triggers_down[i]);
+        self.triggers_down = down;
+
+        if pressed[0] {
+            self.actions.request(PatchAction::NextPreset);
+        }
+        if pressed[1] {
+            self.actions.request(PatchAction::PrevPreset);
+        }
+        if pressed[2] {
+            self.actions.request(PatchAction::Randomize);
+        }
+
+        let timeout = (actions::TAP_TIMEOUT_SECONDS * self.sample_rate) as u64;
+        if pressed[3] {
+            if let Some(samples) = self.samples_since_tap.filter(|&s| s > 0) {
+                self.actions
+                    .set_tapped_bpm(60.0 * self.sample_rate / samples as f32);
+            }
+            self.samples_since_tap = Some(0);
+        } else if let Some(samples) = self.samples_since_tap.as_mut() {
+            *samples += block_len as u64;
+            if *samples > timeout {
+                self.samples_since_tap = None;
+            }
+        }
+    }
+
+    /// Flags scheduled changes whose bar falls inside this block, returning
+    /// whether any did. Only while the host is playing and reports a position;
+    /// `bpm` converts the block's length to beats.
+    fn poll_schedule(&self, transport: &Transport, block_len: usize, bpm: f32) -> bool {
+        let Some(start) = transport.pos_beats().filter(|_| transport.playing) else {
+            return false;
+        };
+        let end = start + block_len as f64 / self.sample_rate as f64 * bpm as f64 / 60.0;
+        self.schedule.mark_due(start, end, beats_per_bar(transport))
+    }
+
+    /// Where this block's tempo-synced LFO locks its phase to the song
+    /// position, per the `lfo_reset` mode: the sample index and the song
+    /// position there, in beats. Call once per block.
+    fn lfo_realign(
+        &mut self,
+        transport: &Transport,
+        block_len: usize,
+        bpm: f32,
+    ) -> Option<(usize, f64)> {
+        let started = transport.playing && !self.was_playing;
+        self.was_playing = transport.playing;
+        let modulation = &self.params.modulation;
+        if !modulation.lfo_sync.value() || !transport.playing {
+            return None;
+        }
+        let pos = transport.pos_beats()?;
+        match modulation.lfo_reset.value() {
+            LfoReset::Free => None,
+            _ if started => Some((0, pos)),
+            LfoReset::Transport => None,
+            LfoReset::Bar => {
+                let bar = beats_per_bar(transport);
+                let bar_start = transport.bar_start_pos_beats().unwrap_or(0.0);
+                let into_bar = (pos - bar_start).rem_euclid(bar);
+                let to_bar = if into_bar < 1e-6 { 0.0 } else { bar - into_bar };
+                let samples_per_beat = 60.0 / bpm as f64 * self.sample_rate as f64;
+                let index = (to_bar * samples_per_beat) as usize;
+                (index < block_len).then_some((index, pos + to_bar))
+            }
+        }
+    }
+
+    /// Keys pass the latch, are snapped to the scale, then go to the arp
+    /// while it's on, else straight to the voices. Key-ups reach the voices
+    /// either way, so notes held down when the arp was switched on still end.
+    fn route_note_event(&mut self, event: NoteEvent<()>, arp_on: bool, scale: Quantizer) {
+        let event = match event {
+            NoteEvent::NoteOn {
+                timing,
+                voice_id,
+                channel,
+                note,
+                velocity,
+            } if velocity > 0.0 && self.latch.key_down(note) => NoteEvent::NoteOff {
+                timing,
+                voice_id,
+                channel,
+                note,
+                velocity: 0.0,
+            },
+            NoteEvent::NoteOff { note, .. }
+                if !self.latch.key_up(note, self.params.latch.value()) =>
+            {
+                return;
+            }
+            other => other,
+        };
+        let event = match event {
+            NoteEvent::NoteOn {
+                timing,
+                voice_id,
+                channel,
+                note,
+                velocity,
+            } => {
+                let snapped = scale.snap(note);
+                self.key_notes[usize::from(note)] = snapped;
+                NoteEvent::NoteOn {
+                    timing,
+                    voice_id,
+                    channel,
+                    note: snapped,
+                    velocity,
+                }
+            }
+            NoteEvent::NoteOff {
+                timing,
+                voice_id,
+                channel,
+                note,
+                velocity,
+            } => NoteEvent::NoteOff {
+                timing,
+                voice_id,
+                channel,
+                note: self.key_notes[usize::from(note)],
+                velocity,
+            },
+            other => other,
+        };
+        match event {
+            NoteEvent::NoteOn { note, velocity, .. } if arp_on && velocity > 0.0 => {
+                self.arp.key_down(note, velocity);
+            }
+            NoteEvent::NoteOff { note, .. } => {
+                self.arp.key_up(note);
+                self.handle_note_event(event);
+            }
+            NoteEvent::Choke { .. } => {
+                // The arp stops, and ends the note it sent out.
+                self.arp.release_all();
+                self.latch.clear();
+                self.handle_note_event(event);
+            }
+            _ => self.handle_note_event(event),
+        }
+    }
+
+    /// Releases the assistant's requested notes whose time is up and starts
+    /// the newly requested ones, at the top of the block.
+    fn play_requested_notes(&mut self, block_len: usize, arp_on: bool, scale: Quantizer) {
+        for slot in 0..MAX_NOTE_REQUESTS {
+            let Some((note, left)) = self.requested_notes[slot] else {
+                continue;
+            };
+            if left > block_len {
+                self.requested_notes[slot] = Some((note, left - block_len));
+                continue;
+            }
+            self.requested_notes[slot] = None;
+            let off = NoteEvent::NoteOff {
+                timing: 0,
+                voice_id: None,
+                channel: 0,
+                note,
+                velocity: 0.0,
+            };
+            self.route_note_event(off, arp_on, scale);
+        }
+
+        for request in self.diagnostics.take_note_requests().into_iter().flatten() {
+            let Some(slot) = self.requested_notes.iter().position(Option::is_none) else {
+                break;
+            };
+            let samples = (request.seconds * self.sample_rate) as usize;
+            self.requested_notes[slot] = Some((request.note, samples));
+            let on = NoteEvent::NoteOn {
+                timing: 0,
+                voice_id: None,
+                channel: 0,
+                note: request.note,
+                velocity: request.velocity,
+            };
+            self.route_note_event(on, arp_on, scale);
+        }
+    }
+
+    /// Release every voice and forget every held key: the arp's, the
+    /// assistant's requested notes, the keyboard's and the latched ones.
+    fn all_notes_off(&mut self) {
+        self.arp.release_all();
+        self.latch.clear();
+        self.requested_notes = [None; MAX_NOTE_REQUESTS];
+        self.held_notes = [false; 128];
+        for voice in &mut self.voices {
+            voice.panic();
+        }
+    }
+
+    /// Play one sample's arp notes on the voices and/or send them out as MIDI,
+    /// stamped with the sample they fall on.
+    fn play_arp(
+        &mut self,
+        events: [Option<ArpEvent>; 2],
+        index: usize,
+        context: &mut impl ProcessContext<Self>,
+    ) {
+        let timing = index as u32;
+        for event in events.into_iter().flatten() {
+            let note_event = match event {
+                ArpEvent::On { note, velocity } => NoteEvent::NoteOn {
+                    timing,
+                    voice_id: None,
+                    channel: 0,
+                    note,
+                    velocity,
+                },
+                ArpEvent::Off { note } => NoteEvent::NoteOff {
+                    timing,
+                    voice_id: None,
+                    channel: 0,
+                    note,
+                    velocity: 0.0,
+                },
+            };
+            match event {
+                ArpEvent::On { note, .. } => {
+                    if self.params.arp.local.value() {
+                        self.handle_note_event(note_event);
+                    }
+                    if self.params.arp.midi_out.value() {
+                        context.send_event(note_event);
+                        self.arp_midi_note = Some(note);
+                    }
+                }
+                ArpEvent::Off { note } => {
+                    // Releasing a note the voices never got is harmless.
+                    self.handle_note_event(note_event);
+                    if self.arp_midi_note == Some(note) {
+                        context.send_event(note_event);
+                        self.arp_midi_note = None;
+                    }
+                }
+            }
+        }
+    }
+
     fn handle_note_event(&mut self, event: NoteEvent<()>) {
         match event {
             NoteEvent::NoteOn { note, velocity, .. } => {
                 if velocity > 0.0 {
+                    let glide_from = self.glide_source();
+                    let tuning = self.tuning();
+                    self.held_notes[note as usize] = true;
+                    self.last_note = Some(note);
+
                     if let Some(voice) = self.voices.iter_mut().find(|v| v.is_free()) {
-                        voice.note_on(note, velocity);
+                        voice.note_on(note, velocity, glide_from, tuning);
                     } else if let Some((oldest_idx, _)) =
                         self.voices.iter().enumerate().min_by_key(|(_, v)| v.age())
                     {
-                        self.voices[oldest_idx].note_on(note, velocity);
+                        self.voices[oldest_idx].note_on(note, velocity, glide_from, tuning);
+                        self.steals_in_window += 1;
+                        self.diagnostics.record_steal();
                     }
                 }
             }
-            NoteEvent::NoteOff { note, .. } => {
+            NoteEvent::NoteOff { note, velocity, .. } => {
+                self.held_notes[note as usize] = false;
+                let depth = self.params.release_velocity.value();
                 for voice in &mut self.voices {
-                    voice.release_if_matches(note);
+                    voice.release_if_matches(note, velocity, depth);
                 }
             }
             NoteEvent::Choke { .. } => {
+                self.held_notes = [false; 128];
                 for voice in &mut self.voices {
                     voice.note_off();
                 }
             }
+            NoteEvent::MidiPitchBend { value, .. } => self.pitch_bend.set(value),
+            // All Notes Off.
+            NoteEvent::MidiCC { cc: 123, .. } => self.all_notes_off(),
+            NoteEvent::MidiCC { cc: cc @ (0 | 32), value, .. } => {
+                let half = usize::from(cc == 32);
+                self.bank_select[half] = Some((value * 127.0).round() as u8);
+            }
+            NoteEvent::MidiProgramChange { program, .. } if self.params.program_change.value() => {
+                self.actions.request_program(self.bank(), program);
+            }
             _ => {}
         }
     }
+
+    /// The bank a program change picks from. With only one bank select
+    /// controller sent, its value alone is the bank, as most controllers
+    /// that send one mean it.
+    fn bank(&self) -> u16 {
+        match self.bank_select.map(|half| half.map(u16::from)) {
+            [Some(msb), Some(lsb)] => msb << 7 | lsb,
+            [Some(half), None] | [None, Some(half)] => half,
+            [None, None] => 0,
+        }
+    }
+}
+
+/// The host's bar length in quarter-note beats, e.g. 6/8 is three; 4/4 when it
+/// doesn't say.
+fn beats_per_bar(transport: &Transport) -> f64 {
+    match (transport.time_sig_numerator, transport.time_sig_denominator) {
+        (Some(num), Some(den)) if num > 0 && den > 0 => num as f64 * 4.0 / den as f64,
+        _ => 4.0,
+    }
+}
+
+/// The plugin's background tasks.
+pub enum SynthTask {
+    /// Snapshot the patch for crash recovery.
+    Autosave,
+    /// Carry out the actions the trigger params raised and load the waiting
+    /// program change. Runs on the GUI thread, where the host takes param
+    /// writes.
+    PatchActions,
+    /// Write finished parameter ramps' targets into their params, so the
+    /// host keeps them, and hand the ramps back to the audio thread. Only
+    /// queued while the editor is closed; its poll timer does it otherwise.
+    FinishRamps,
+    /// Write the scheduled changes that came due this block.
+    ScheduledChanges,
+    /// Write the running preset morph's current values into the params.
+    /// Only queued while the editor is closed; its poll timer does it
+    /// otherwise.
+    Transition,
 }
 
 impl Plugin for SineSynth {
@@ -96,84 +692,492 @@ impl Plugin for SineSynth {
     const EMAIL: &'static str = "your@email.com";
     const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 
-    const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[AudioIOLayout {
-        main_input_channels: None,
-        main_output_channels: NonZeroU32::new(2),
-        ..AudioIOLayout::const_default()
-    }];
+    // The second layout adds a main input for audio-through (see
+    // `dsp::through`); hosts that only want an instrument pick the first.
+    // The next two add a stereo output per oscillator (see
+    // `Voice::osc_outputs`), for hosts that can route them to mixer channels.
+    // The mono ones sum the mix to one channel, and the 5.1 one plays on the
+    // front pair and passes the other input channels through. They come last
+    // so hosts that saved a layout index keep the one they had.
+    const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[
+        AudioIOLayout {
+            main_input_channels: None,
+            main_output_channels: NonZeroU32::new(2),
+            aux_input_ports: &[new_nonzero_u32(2)],
+            names: PortNames {
+                aux_inputs: &["Sidechain"],
+                ..PortNames::const_default()
+            },
+            ..AudioIOLayout::const_default()
+        },
+        AudioIOLayout {
+            main_input_channels: NonZeroU32::new(2),
+            main_output_channels: NonZeroU32::new(2),
+            aux_input_ports: &[new_nonzero_u32(2)],
+            names: PortNames {
+                main_input: Some("Input"),
+                aux_inputs: &["Sidechain"],
+                ..PortNames::const_default()
+            },
+            ..AudioIOLayout::const_default()
+        },
+        AudioIOLayout {
+            main_input_channels: None,
+            main_output_channels: NonZeroU32::new(2),
+            aux_input_ports: &[new_nonzero_u32(2)],
+            aux_output_ports: &[new_nonzero_u32(2); NUM_OSC_OUTPUTS],
+            names: PortNames {
+                aux_inputs: &["Sidechain"],
+                aux_outputs: &OSC_OUTPUT_NAMES,
+                ..PortNames::const_default()
+            },
+            ..AudioIOLayout::const_default()
+        },
+        AudioIOLayout {
+            main_input_channels: NonZeroU32::new(2),
+            main_output_channels: NonZeroU32::new(2),
+            aux_input_ports: &[new_nonzero_u32(2)],
+            aux_output_ports: &[new_nonzero_u32(2); NUM_OSC_OUTPUTS],
+            names: PortNames {
+                main_input: Some("Input"),
+                aux_inputs: &["Sidechain"],
+                aux_outputs: &OSC_OUTPUT_NAMES,
+                ..PortNames::const_default()
+            },
+            ..AudioIOLayout::const_default()
+        },
+        AudioIOLayout {
+            main_input_channels: None,
+            main_output_channels: NonZeroU32::new(1),
+            aux_input_ports: &[new_nonzero_u32(2)],
+            names: PortNames {
+                layout: Some("Mono"),
+                aux_inputs: &["Sidechain"],
+                ..PortNames::const_default()
+            },
+            ..AudioIOLayout::const_default()
+        },
+        AudioIOLayout {
+            main_input_channels: NonZeroU32::new(1),
+            main_output_channels: NonZeroU32::new(1),
+            aux_input_ports: &[new_nonzero_u32(2)],
+            names: PortNames {
+                layout: Some("Mono"),
+                main_input: Some("Input"),
+                aux_inputs: &["Sidechain"],
+                ..PortNames::const_default()
+            },
+            ..AudioIOLayout::const_default()
+        },
+        AudioIOLayout {
+            main_input_channels: NonZeroU32::new(6),
+            main_output_channels: NonZeroU32::new(6),
+            aux_input_ports: &[new_nonzero_u32(2)],
+            names: PortNames {
+                layout: Some("5.1"),
+                main_input: Some("Input"),
+                aux_inputs: &["Sidechain"],
+                ..PortNames::const_default()
+            },
+            ..AudioIOLayout::const_default()
+        },
+    ];
 
-    const MIDI_INPUT: MidiConfig = MidiConfig::Basic;
+    // `MidiCCs` for pitch bend.
+    const MIDI_INPUT: MidiConfig = MidiConfig::MidiCCs;
+    // Only the arpeggiator's notes go out.
+    const MIDI_OUTPUT: MidiConfig = MidiConfig::Basic;
 
     type SysExMessage = ();
-    type BackgroundTask = ();
+    type BackgroundTask = SynthTask;
 
     fn params(&self) -> Arc<dyn Params> {
         self.params.clone()
     }
 
+    fn task_executor(&mut self) -> TaskExecutor<Self> {
+        let params = self.params.clone();
+        let recovery = self.recovery.clone();
+        let actions = self.actions.clone();
+        let host = self.host.clone();
+        let ramps = self.ramps.clone();
+        let schedule = self.schedule.clone();
+        let transition = self.transition.clone();
+        Box::new(move |task| match task {
+            SynthTask::Autosave => recovery.snapshot(&params),
+            SynthTask::PatchActions => {
+                // Without the host's context the editor carries them out
+                // when it opens.
+                host.write(|emit| actions.carry_out(&params, &mut |event| emit(event)));
+            }
+            // Without the host's context the ramps hold their targets.
+            SynthTask::FinishRamps => {
+                host.write(|emit| ramps.sync_params(&params, &mut |event| emit(event)));
+            }
+            SynthTask::ScheduledChanges => {
+                let written =
+                    host.write(|emit| schedule.apply_due(&params, &mut |event| emit(event)));
+                // By the time the editor opens the bar has passed.
+                if !written {
+                    schedule.drop_due();
+                }
+            }
+            SynthTask::Transition => {
+                host.write(|emit| {
+                    let finished = transition.sync_params(None, &mut |event| emit(event));
+                    if let Some((name, metadata)) = finished {
+                        metadata.store(&params);
+                        actions.rename(&name);
+                    }
+                });
+            }
+        })
+    }
+
     fn editor(&mut self, _async_executor: AsyncExecutor<Self>) -> Option<Box<dyn Editor>> {
         ui::editor::create(
             self.params.clone(),
             self.peak_meter.clone(),
+            self.mix_meters.clone(),
+            self.diagnostics.clone(),
+            self.actions.clone(),
+            self.host.clone(),
+            self.ramps.clone(),
+            #[cfg(feature = "chat")]
+            self.schedule.clone(),
+            self.transition.clone(),
             self.params.editor_state.clone(),
         )
     }
 
     fn initialize(
         &mut self,
-        _audio_io_layout: &AudioIOLayout,
+        audio_io_layout: &AudioIOLayout,
         buffer_config: &BufferConfig,
         _context: &mut impl InitContext<Self>,
     ) -> bool {
         self.sample_rate = buffer_config.sample_rate;
+        let channels = |count: Option<NonZeroU32>| count.map_or(0, |n| n.get() as usize);
+        self.input_channels = channels(audio_io_layout.main_input_channels);
+        self.output_channels = channels(audio_io_layout.main_output_channels);
+        self.through.set_sample_rate(self.sample_rate);
+        self.probe.set_sample_rate(self.sample_rate);
+        self.source_sampler.set_sample_rate(self.sample_rate);
+        self.watch.set_sample_rate(self.sample_rate);
+        // Also runs after the host restores a project.
+        ProjectState::migrate(&self.params);
+        xy::publish(&self.params);
+        #[cfg(feature = "fx")]
+        self.widener.reset();
+        self.diagnostics.set_sample_rate(self.sample_rate);
+        self.diagnostics.set_max_buffer_size(buffer_config.max_buffer_size);
+        tracing::info!(
+            target: "dsp",
+            sample_rate = self.sample_rate,
+            max_block = buffer_config.max_buffer_size,
+            inputs = self.input_channels,
+            outputs = self.output_channels,
+            "initialized"
+        );
         for voice in &mut self.voices {
             voice.set_sample_rate(self.sample_rate);
         }
+        self.autosave_countdown = (AUTOSAVE_SECONDS * self.sample_rate) as usize;
         true
     }
 
+    fn deactivate(&mut self) {
+        // Stopped cleanly, so there's nothing to recover.
+        self.recovery.discard();
+        // The host's context holds the plugin; don't keep it alive in turn.
+        self.host.detach();
+    }
+
     fn reset(&mut self) {
         for voice in &mut self.voices {
             voice.reset();
         }
+        self.held_notes = [false; 128];
+        self.last_note = None;
+        self.latch.clear();
+        self.lfo.reset();
+        self.was_playing = false;
+        self.sidechain.reset();
+        self.through.reset();
+        #[cfg(feature = "fx")]
+        {
+            self.gate.reset();
+            self.widener.reset();
+        }
+        self.ramp_runner.finish_all();
+        self.arp.reset();
+        self.arp_midi_note = None;
+        self.requested_notes = [None; MAX_NOTE_REQUESTS];
+        self.quality.reset();
+        self.pitch_bend.reset();
     }
 
     fn process(
         &mut self,
         buffer: &mut Buffer,
-        _aux: &mut AuxiliaryBuffers,
+        aux: &mut AuxiliaryBuffers,
         context: &mut impl ProcessContext<Self>,
     ) -> ProcessStatus {
-        while let Some(event) = context.next_event() {
-            self.handle_note_event(event);
-        }
-
+        // `Instant` reads a clock; it doesn't allocate.
+        let started = Instant::now();
         self.sync_unison_voice_counts();
+        self.poll_triggers(buffer.samples());
 
         let mut block_peak = 0.0f32;
+        // The mixer meters' peaks: each oscillator, and the voices' sum.
+        let mut osc_peaks = [0.0f32; NUM_OSC_OUTPUTS];
+        let mut bus_peak = 0.0f32;
 
-        for channel_samples in buffer.iter_samples() {
-            // Advance every smoother exactly once for this sample, then share
-            // the snapshot across all voices.
-            let frame = FrameParams::next(&self.params);
+        // Tempo for synced envelope stages and beat-length ramps: the host's,
+        // else the tapped tempo.
+        let (bpm, tempo_source) = match (context.transport().tempo, self.actions.tapped_bpm()) {
+            (Some(bpm), _) => (bpm as f32, TempoSource::Host),
+            (None, Some(bpm)) => (bpm, TempoSource::Tapped),
+            (None, None) => (DEFAULT_BPM, TempoSource::Default),
+        };
 
-            let mut sample = 0.0;
+        self.ramp_runner.begin_block(&self.ramps, &self.params, self.sample_rate, bpm);
+        let transitioning = self.transition.advance(buffer.samples(), self.sample_rate, bpm);
+        // With the editor open its poll timer writes the morph.
+        self.transition_countdown = self.transition_countdown.saturating_sub(buffer.samples());
+        if transitioning && !self.params.editor_state.is_open() && self.transition_countdown == 0 {
+            self.transition_countdown = (TRANSITION_SYNC_SECONDS * self.sample_rate) as usize;
+            context.execute_gui(SynthTask::Transition);
+        }
+        // Crash recovery: snapshot the patch now and then, off this thread.
+        self.autosave_countdown = self.autosave_countdown.saturating_sub(buffer.samples());
+        if self.autosave_countdown == 0 {
+            self.autosave_countdown = (AUTOSAVE_SECONDS * self.sample_rate) as usize;
+            context.execute_background(SynthTask::Autosave);
+        }
+        if self.poll_schedule(context.transport(), buffer.samples(), bpm) {
+            context.execute_gui(SynthTask::ScheduledChanges);
+        }
+        let transport = context.transport();
+        let song_beats = transport.pos_beats().filter(|_| transport.playing);
+        let time_signature = match (transport.time_sig_numerator, transport.time_sig_denominator) {
+            (Some(num), Some(den)) if num > 0 && den > 0 => Some((num as u32, den as u32)),
+            _ => None,
+        };
+        self.diagnostics.publish_transport(TransportInfo {
+            bpm,
+            tempo_source,
+            playing: transport.playing,
+            song_beats,
+            time_signature,
+        });
+        if context.transport().playing && !self.was_playing {
+            self.arp.reseed();
+        }
+        let lfo_realign = self.lfo_realign(context.transport(), buffer.samples(), bpm);
+
+        // Hosts without sidechain routing leave the aux input silent.
+        let modulation = &self.params.modulation;
+        self.sidechain.set_times(
+            modulation.sidechain_attack.value(),
+            modulation.sidechain_release.value(),
+            self.sample_rate,
+        );
+        let sidechain_in = aux.inputs.first().map(|input| input.as_slice_immutable());
+
+        #[cfg(feature = "fx")]
+        let gate = {
+            self.gate.sync(song_beats);
+            GateFrame::new(&self.params.gate, bpm, self.sample_rate)
+        };
+        let scale = Quantizer::from_params(&self.params);
+        let arp = ArpFrame::new(&self.params.arp, scale, bpm, self.sample_rate);
+
+        // A synced LFO runs one cycle per division at the block's tempo.
+        let lfo_cycle_beats = modulation
+            .lfo_sync
+            .value()
+            .then(|| modulation.lfo_division.value().beats());
+        let lfo_synced_rate = lfo_cycle_beats.map(|beats| bpm / 60.0 / beats);
+
+        if let Some(target) = self.diagnostics.take_probe_request() {
+            if target == ProbeTarget::Lfo {
+                self.lfo.reset();
+            }
+            let lfo_rate = lfo_synced_rate.unwrap_or(modulation.lfo_rate.value());
+            self.probe.start(target, lfo_rate);
+        }
+        self.watch.begin_block(&self.diagnostics);
+        if self.diagnostics.take_panic_request() {
+            self.all_notes_off();
+        }
+        if let Some(latched) = self.latch.update(self.params.latch.value()) {
+            for note in (0..=127).filter(|&note| latched[usize::from(note)]) {
+                let off = NoteEvent::NoteOff {
+                    timing: 0,
+                    voice_id: None,
+                    channel: 0,
+                    note,
+                    velocity: 0.0,
+                };
+                self.route_note_event(off, arp.enabled(), scale);
+            }
+        }
+        self.play_requested_notes(buffer.samples(), arp.enabled(), scale);
+
+        let mut next_event = context.next_event();
+        for (index, mut channel_samples) in buffer.iter_samples().enumerate() {
+            // Notes land on the sample the host stamped them with rather than
+            // at the top of the buffer, so fast runs keep their timing at
+            // large buffer sizes.
+            while let Some(event) = next_event.take_if(|e| e.timing() as usize <= index) {
+                self.route_note_event(event, arp.enabled(), scale);
+                next_event = context.next_event();
+            }
+            let arp_events = self.arp.next(&arp);
+            self.play_arp(arp_events, index, context);
+
+            // Advance every smoother once for this sample (by the smoothing
+            // setting's step count), then share the snapshot across all voices.
+            let steps = self.params.smoothing.value().steps_per_sample();
+            if let (Some((at, beats)), Some(cycle)) = (lfo_realign, lfo_cycle_beats) {
+                if at == index {
+                    self.lfo.align(beats, cycle);
+                }
+            }
+            let offsets = self.xy.next(&self.params, steps);
+            let read = ParamRead::Smoothed(steps, &offsets);
+            let lfo_rate = read.float(&self.params.modulation.lfo_rate);
+            let lfo = self.lfo.next(
+                self.params.modulation.lfo_shape.value(),
+                lfo_synced_rate.unwrap_or(lfo_rate),
+                self.sample_rate,
+            );
+            let sidechain_peak = sidechain_in.map_or(0.0, |channels| {
+                channels
+                    .iter()
+                    .filter_map(|channel| channel.get(index))
+                    .fold(0.0f32, |peak, sample| peak.max(sample.abs()))
+            });
+            let sidechain = self.sidechain.next(sidechain_peak);
+            let ramped = self.ramp_runner.next(&self.ramps, &self.params);
+            let mut frame = FrameParams::next(&self.params, read, lfo, sidechain, bpm, &ramped);
+            let bend_smoothing = self.params.bend.smoothing.value();
+            frame.set_pitch_bend(self.pitch_bend.next(bend_smoothing, self.sample_rate));
+            if let Some(point) = self.probe.next(&frame) {
+                self.diagnostics.publish_trace_point(point);
+            }
+            self.watch.next(&self.diagnostics);
+
+            let (mut left, mut right) = (0.0, 0.0);
+            let mut osc_mix = [[0.0f32; 2]; NUM_OSC_OUTPUTS];
             for voice in self.voices.iter_mut().filter(|v| v.is_active()) {
-                sample += voice.render(&frame, self.sample_rate);
+                let sample = voice.render(&frame, self.sample_rate);
+                let (gain_l, gain_r) = voice.pan_gains(&frame);
+                left += sample * gain_l;
+                right += sample * gain_r;
+                for (mix, osc) in osc_mix.iter_mut().zip(voice.osc_outputs()) {
+                    mix[0] += osc * gain_l;
+                    mix[1] += osc * gain_r;
+                }
+            }
+            // The per-voice sources follow the newest voice.
+            if self.source_sampler.tick() {
+                let newest = self.voices.iter().filter(|v| v.is_active()).min_by_key(|v| v.age());
+                let mut sources = newest.map(Voice::mod_sources).unwrap_or_default();
+                sources.lfo = lfo;
+                sources.sidechain = sidechain;
+                self.diagnostics.publish_sources(&sources);
+            }
+            for (peak, [mix_l, mix_r]) in osc_peaks.iter_mut().zip(osc_mix) {
+                *peak = peak.max(mix_l.abs()).max(mix_r.abs());
+            }
+            bus_peak = bus_peak.max(left.abs()).max(right.abs());
+            // Per-oscillator buses get the same soft clip as the main mix, but
+            // not the input, gate or width stages.
+            for (output, mix) in aux.outputs.iter_mut().zip(osc_mix) {
+                for (channel, sample) in output.as_slice().iter_mut().zip(mix) {
+                    channel[index] = sample.tanh() * 0.5;
+                }
             }
 
-            sample = sample.tanh() * 0.5;
-            block_peak = block_peak.max(sample.abs());
+            let mut left = left.tanh() * 0.5;
+            let mut right = right.tanh() * 0.5;
+
+            // The buffer is processed in place, so it still holds the input.
+            let input_mix = read.float(&self.params.input_mix);
+            if self.input_channels > 0 {
+                let mut input = [0.0; 2];
+                for (sample, input) in channel_samples.iter_mut().zip(&mut input) {
+                    *input = *sample;
+                }
+                // A mono input feeds both sides.
+                if self.input_channels == 1 {
+                    input[1] = input[0];
+                }
+                let [through_l, through_r] = self.through.process(&frame, input);
+                left = left * (1.0 - input_mix) + through_l * input_mix;
+                right = right * (1.0 - input_mix) + through_r * input_mix;
+            }
+            // Without the `fx` feature their params are kept, so presets and
+            // projects still load, but do nothing.
+            #[cfg(feature = "fx")]
+            let [left, right] = {
+                let [left, right] = self.gate.process(&gate, [left, right]);
+                self.widener.process(
+                    read.float(&self.params.stereo_width),
+                    self.params.width_crossover.value(),
+                    self.sample_rate,
+                    [left, right],
+                )
+            };
+            block_peak = block_peak.max(left.abs()).max(right.abs());
+            if let Some(reading) = self.brightness.push(left + right, self.sample_rate) {
+                self.diagnostics.publish_brightness(reading);
+            }
 
-            for output_sample in channel_samples {
-                *output_sample = sample;
+            for (channel, output_sample) in channel_samples.into_iter().enumerate() {
+                match channel {
+                    _ if self.output_channels == 1 => *output_sample = (left + right) * 0.5,
+                    0 => *output_sample = left,
+                    1 => *output_sample = right,
+                    // Past the front pair the buffer still holds the input.
+                    _ if channel < self.input_channels => {}
+                    _ => *output_sample = 0.0,
+                }
             }
         }
+        // Events stamped past the end of the buffer (a host bug) still count.
+        while let Some(event) = next_event {
+            self.route_note_event(event, arp.enabled(), scale);
+            next_event = context.next_event();
+        }
+
+        // Publish the block peaks to the GUI meters, decaying the previous
+        // values so the bars release smoothly. Relaxed loads + stores — RT-safe.
+        self.peak_meter.publish(block_peak, METER_DECAY);
+        for (meter, peak) in self.mix_meters.oscs.iter().zip(osc_peaks) {
+            meter.publish(peak, METER_DECAY);
+        }
+        self.mix_meters.bus.publish(bus_peak, METER_DECAY);
+
+        self.ramp_runner.end_block(&self.ramps, &self.params);
+        self.publish_voice_stats(buffer.samples());
+        if self.actions.take_raised() {
+            context.execute_gui(SynthTask::PatchActions);
+        }
+        // With the editor open its poll timer writes finished ramps' targets;
+        // the flag waits for it to close otherwise.
+        if !self.params.editor_state.is_open() && self.ramps.take_finished() {
+            context.execute_gui(SynthTask::FinishRamps);
+        }
 
-        // Publish the block peak to the GUI meter, decaying the previous value
-        // so the bar releases smoothly. One relaxed load + store — RT-safe.
-        let released = self.peak_meter.load() * METER_DECAY;
-        self.peak_meter.store(block_peak.max(released));
+        // Measured last, so the cap for the next block covers all of this one.
+        let block_seconds = buffer.samples() as f32 / self.sample_rate;
+        let busy = started.elapsed().as_secs_f32();
+        self.quality.update(self.params.auto_quality.value(), busy, block_seconds);
+        self.diagnostics.publish_quality(self.quality.load(), self.quality.unison_cap());
+        self.record_usage(buffer.samples(), busy);
 
         ProcessStatus::Normal
     }
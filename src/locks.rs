@@ -0,0 +1,185 @@
+//! Parameter lock groups for live performance: a locked section (say, the amp
+//! envelope) keeps its settings while presets are loaded, morphed into or
+//! randomized around it, whichever of the GUI, the chat or a host trigger does
+//! it.
+//!
+//! The locks are saved with the project (see `ProjectState::locks`) rather than
+//! in presets. They only filter whole-patch writes; a knob turned by hand or a
+//! single `set_parameter` still goes through.
+
+use nih_plug::prelude::{ParamPtr, Params};
+use serde::{Deserialize, Serialize};
+use vizia_plug::widgets::RawParamEvent;
+
+use crate::SineParams;
+
+/// A lockable section of the patch, matching the editor's cards.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockGroup {
+    /// Oscillator 1, including its additive drawbars and the sub under it.
+    Osc1,
+    Osc2,
+    Osc3,
+    /// The comb and the filter, except the filter envelope's amount.
+    Filter,
+    /// The amp envelope, release velocity and declick.
+    AmpEnvelope,
+    /// The filter envelope and its amount.
+    FilterEnvelope,
+    /// Glide and pitch bend.
+    Glide,
+    /// The LFO, the sidechain follower and the matrix slots.
+    Modulation,
+    /// Analog drift, keyboard pan, the Color macro and the noise source.
+    Character,
+    /// Stereo width and the trance gate.
+    Fx,
+}
+
+impl LockGroup {
+    pub const ALL: [LockGroup; 10] = [
+        Self::Osc1,
+        Self::Osc2,
+        Self::Osc3,
+        Self::Filter,
+        Self::AmpEnvelope,
+        Self::FilterEnvelope,
+        Self::Glide,
+        Self::Modulation,
+        Self::Character,
+        Self::Fx,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Osc1 => "osc1",
+            Self::Osc2 => "osc2",
+            Self::Osc3 => "osc3",
+            Self::Filter => "filter",
+            Self::AmpEnvelope => "amp_envelope",
+            Self::FilterEnvelope => "filter_envelope",
+            Self::Glide => "glide",
+            Self::Modulation => "modulation",
+            Self::Character => "character",
+            Self::Fx => "fx",
+        }
+    }
+
+    fn bit(self) -> u32 {
+        1 << self as u32
+    }
+
+    /// Whether the param with ID `id` in the `nih_plug` group `group` belongs
+    /// to this section.
+    fn covers(self, id: &str, group: &str) -> bool {
+        // Nested groups are `/`-separated, e.g. "Trance Gate/Step 3".
+        let within = |name: &str| {
+            group
+                .strip_prefix(name)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        };
+        match self {
+            Self::Osc1 => {
+                within("Oscillator 1") || within("Oscillator 1 Additive") || within("Sub")
+            }
+            Self::Osc2 => within("Oscillator 2"),
+            Self::Osc3 => within("Oscillator 3"),
+            Self::Filter => (within("Filter") && id != "filter_env_amount") || within("Comb"),
+            Self::AmpEnvelope => within("Envelope") || matches!(id, "release_vel" | "declick"),
+            Self::FilterEnvelope => within("Filter Envelope") || id == "filter_env_amount",
+            Self::Glide => within("Glide") || within("Pitch Bend"),
+            Self::Modulation => within("Modulation"),
+            Self::Character => {
+                within("Color") || within("Noise") || matches!(id, "analog" | "keyboard_pan")
+            }
+            Self::Fx => within("Trance Gate") || matches!(id, "stereo_width" | "width_crossover"),
+        }
+    }
+}
+
+/// The set of locked groups. Saved as a list of group IDs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "Vec<LockGroup>", into = "Vec<LockGroup>")]
+pub struct ParamLocks {
+    /// One bit per [`LockGroup`].
+    bits: u32,
+}
+
+impl From<Vec<LockGroup>> for ParamLocks {
+    fn from(groups: Vec<LockGroup>) -> Self {
+        let mut locks = Self::default();
+        for group in groups {
+            locks.set(group, true);
+        }
+        locks
+    }
+}
+
+impl From<ParamLocks> for Vec<LockGroup> {
+    fn from(locks: ParamLocks) -> Self {
+        locks.groups().collect()
+    }
+}
+
+impl ParamLocks {
+    pub fn is_locked(self, group: LockGroup) -> bool {
+        self.bits & group.bit() != 0
+    }
+
+    pub fn set(&mut self, group: LockGroup, locked: bool) {
+        if locked {
+            self.bits |= group.bit();
+        } else {
+            self.bits &= !group.bit();
+        }
+    }
+
+    /// The locked groups, in [`LockGroup::ALL`] order.
+    pub fn groups(self) -> impl Iterator<Item = LockGroup> {
+        LockGroup::ALL
+            .into_iter()
+            .filter(move |group| self.is_locked(*group))
+    }
+
+    /// IDs of the locked groups, for the AI tools.
+    pub fn ids(self) -> Vec<&'static str> {
+        self.groups().map(LockGroup::id).collect()
+    }
+
+    /// Every param in a locked group.
+    pub fn locked_params(self, p: &SineParams) -> Vec<ParamPtr> {
+        if self.bits == 0 {
+            return Vec::new();
+        }
+        p.param_map()
+            .into_iter()
+            .filter(|(id, _, group)| self.groups().any(|lock| lock.covers(id, group)))
+            .map(|(_, ptr, _)| ptr)
+            .collect()
+    }
+}
+
+/// The project's locks.
+pub fn current(p: &SineParams) -> ParamLocks {
+    p.project_state.read().map(|state| state.locks).unwrap_or_default()
+}
+
+/// Wrap `emit` so it drops every event for a param in a locked group. Used by
+/// the whole-patch writes: preset loads (and so morphs) and randomize.
+pub fn skip_locked<'a>(
+    p: &SineParams,
+    emit: &'a mut impl FnMut(RawParamEvent),
+) -> impl FnMut(RawParamEvent) + 'a {
+    let locked = current(p).locked_params(p);
+    move |event| {
+        let ptr = match &event {
+            RawParamEvent::BeginSetParameter(ptr)
+            | RawParamEvent::SetParameterNormalized(ptr, _)
+            | RawParamEvent::EndSetParameter(ptr) => ptr,
+        };
+        if !locked.contains(ptr) {
+            emit(event);
+        }
+    }
+}
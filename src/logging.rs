@@ -0,0 +1,223 @@
+//! Structured logging: `tracing` events from the plugin go to a rotating log
+//! file and to an in-memory backlog that the editor's log viewer shows, since
+//! stderr is invisible when running inside a DAW.
+//!
+//! Log with the usual `tracing` macros and one of the [`TARGETS`] as the
+//! target, e.g. `tracing::warn!(target: "chat", "...")`; events with any other
+//! target (including those from dependencies) are dropped. Never log from
+//! `process`: formatting allocates.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, Once, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+/// The parts of the plugin that log, also the viewer's filters.
+pub const TARGETS: [&str; 5] = ["dsp", "mcp", "chat", "editor", "state"];
+
+/// Lines kept for the log viewer.
+const BACKLOG_LINES: usize = 500;
+/// The log file is rotated once it grows past this.
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// Rotated files kept (`synth.1.log` is the newest).
+const KEEP_FILES: usize = 3;
+
+/// One logged event.
+#[derive(Clone, Debug)]
+pub struct LogLine {
+    /// Wall-clock time of day, UTC (`HH:MM:SS`).
+    pub time: String,
+    pub level: Level,
+    pub target: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for LogLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:<5} [{}] {}",
+            self.time, self.level, self.target, self.message
+        )
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn time_of_day() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+        % 86_400;
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Collects an event's `message` and any other fields as `key=value`.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.fields, " {}={value:?}", field.name());
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={value}", field.name());
+        }
+    }
+}
+
+/// `synth.log` in the app's config directory, rotated by size.
+struct LogFile {
+    dir: PathBuf,
+    file: Option<File>,
+}
+
+impl LogFile {
+    fn path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.dir.join("synth.log")
+        } else {
+            self.dir.join(format!("synth.{index}.log"))
+        }
+    }
+
+    fn open(&mut self) -> Option<&mut File> {
+        if self.file.is_none() {
+            let _ = fs::create_dir_all(&self.dir);
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.path(0))
+                .ok();
+        }
+        self.file.as_mut()
+    }
+
+    fn rotate(&mut self) {
+        self.file = None;
+        for index in (0..KEEP_FILES).rev() {
+            let _ = fs::rename(self.path(index), self.path(index + 1));
+        }
+    }
+
+    fn write(&mut self, line: &LogLine) {
+        let Some(file) = self.open() else {
+            return;
+        };
+        let _ = writeln!(file, "{line}");
+        let full = file.metadata().is_ok_and(|m| m.len() > MAX_FILE_BYTES);
+        if full {
+            self.rotate();
+        }
+    }
+}
+
+/// Recent lines for the log viewer, shared by every plugin instance.
+static BACKLOG: Mutex<VecDeque<LogLine>> = Mutex::new(VecDeque::new());
+
+struct PluginSubscriber {
+    file: Mutex<LogFile>,
+    next_span: AtomicU64,
+}
+
+impl Subscriber for PluginSubscriber {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() <= Level::INFO && TARGETS.contains(&metadata.target())
+    }
+
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        // Spans aren't recorded, but each still needs a distinct non-zero id.
+        Id::from_u64(self.next_span.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let metadata = event.metadata();
+        let Some(&target) = TARGETS.iter().find(|t| **t == metadata.target()) else {
+            return;
+        };
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let line = LogLine {
+            time: time_of_day(),
+            level: *metadata.level(),
+            target,
+            message: visitor.message + &visitor.fields,
+        };
+
+        lock(&self.file).write(&line);
+        let mut backlog = lock(&BACKLOG);
+        if backlog.len() == BACKLOG_LINES {
+            backlog.pop_front();
+        }
+        backlog.push_back(line);
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+static INIT: Once = Once::new();
+
+/// Install the subscriber, once per process (hosts may load several plugin
+/// instances; they share the log). Leaves an already-installed global
+/// subscriber alone.
+pub fn init() {
+    INIT.call_once(|| {
+        let subscriber = PluginSubscriber {
+            file: Mutex::new(LogFile {
+                dir: log_dir(),
+                file: None,
+            }),
+            next_span: AtomicU64::new(0),
+        };
+        let _ = tracing::subscriber::set_global_default(subscriber);
+    });
+}
+
+/// Look through the most recent log lines, oldest first.
+pub fn with_backlog<R>(f: impl FnOnce(&VecDeque<LogLine>) -> R) -> R {
+    f(&lock(&BACKLOG))
+}
+
+/// The most recent warning or error, if any is still in the backlog.
+pub fn last_problem() -> Option<LogLine> {
+    with_backlog(|backlog| {
+        backlog
+            .iter()
+            .rev()
+            .find(|line| line.level <= Level::WARN)
+            .cloned()
+    })
+}
+
+/// Where the log files are written.
+pub fn log_dir() -> PathBuf {
+    crate::ai::preset::app_dir().join("logs")
+}
@@ -0,0 +1,79 @@
+//! Copy and paste of a whole oscillator's settings between the three
+//! oscillators, from the cards' menus and the `copy_oscillator` tool.
+//!
+//! A paste is one batched transaction: every param's gesture opens, all the
+//! values are set, then every gesture closes, so a recording host sees one
+//! edit rather than a string of them. Mute and solo stay with the target; they
+//! are how the user is listening, not part of the sound.
+
+use nih_plug::prelude::{ParamPtr, Params};
+use vizia_plug::widgets::RawParamEvent;
+
+use crate::{OscillatorParams, SineParams};
+
+pub const NUM_OSCILLATORS: usize = 3;
+
+/// Params a copy leaves alone.
+const SKIPPED: [&str; 2] = ["mute", "solo"];
+
+/// Oscillator `index` (`0..NUM_OSCILLATORS`).
+pub fn oscillator(p: &SineParams, index: usize) -> &OscillatorParams {
+    [&p.osc1, &p.osc2, &p.osc3][index]
+}
+
+/// A copied oscillator: normalized values keyed by their ID within
+/// [`OscillatorParams`].
+#[derive(Clone, Debug, Default)]
+pub struct OscSnapshot {
+    values: Vec<(String, f32)>,
+}
+
+impl OscSnapshot {
+    pub fn copy(p: &SineParams, index: usize) -> Self {
+        let values = oscillator(p, index)
+            .param_map()
+            .into_iter()
+            .filter(|(id, _, _)| !SKIPPED.contains(&id.as_str()))
+            // SAFETY: the pointers come from the plugin's `SineParams`, which
+            // outlives the editor and the tools.
+            .map(|(id, ptr, _)| (id, unsafe { ptr.unmodulated_normalized_value() }))
+            .collect();
+        Self { values }
+    }
+
+    /// The params of oscillator `index` this would change, with their new
+    /// normalized values.
+    pub fn changes(&self, p: &SineParams, index: usize) -> Vec<(ParamPtr, f32)> {
+        oscillator(p, index)
+            .param_map()
+            .into_iter()
+            .filter_map(|(id, ptr, _)| {
+                let (_, value) = self.values.iter().find(|(copied, _)| *copied == id)?;
+                // SAFETY: as in `copy`.
+                let current = unsafe { ptr.unmodulated_normalized_value() };
+                (current != *value).then_some((ptr, *value))
+            })
+            .collect()
+    }
+
+    /// Write the copy into oscillator `index` as one transaction. Returns how
+    /// many params changed.
+    pub fn paste(
+        &self,
+        p: &SineParams,
+        index: usize,
+        emit: &mut impl FnMut(RawParamEvent),
+    ) -> usize {
+        let changes = self.changes(p, index);
+        for (ptr, _) in &changes {
+            emit(RawParamEvent::BeginSetParameter(*ptr));
+        }
+        for (ptr, value) in &changes {
+            emit(RawParamEvent::SetParameterNormalized(*ptr, *value));
+        }
+        for (ptr, _) in &changes {
+            emit(RawParamEvent::EndSetParameter(*ptr));
+        }
+        changes.len()
+    }
+}
@@ -5,9 +5,16 @@
 //! oscillators share one `OscillatorParams` definition via `#[nested]`.
 
 use nih_plug::prelude::*;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use vizia_plug::ViziaState;
 
+use crate::ai::preset::PatchMetadata;
+use crate::ai::session::AiSession;
+use crate::locks::ParamLocks;
+use crate::persist::ProjectState;
+use crate::ui::layout::EditorLayout;
+use crate::xy::XyRoutes;
+
 #[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FilterMode {
     #[id = "lowpass"]
@@ -18,6 +25,9 @@ pub enum FilterMode {
     BandPass,
     #[id = "notch"]
     Notch,
+    /// Parallel band-passes at a vowel's formants (see `FilterParams::vowel`).
+    #[id = "formant"]
+    Formant,
 }
 
 impl Default for FilterMode {
@@ -44,6 +54,538 @@ impl Default for Waveform {
     }
 }
 
+impl Waveform {
+    pub const ALL: [Waveform; 4] = [Self::Sine, Self::Square, Self::Triangle, Self::Sawtooth];
+
+    /// The waveforms a morph passes through, in order.
+    pub const MORPH: [Waveform; 4] = [Self::Sine, Self::Triangle, Self::Sawtooth, Self::Square];
+
+    fn short_name(self) -> &'static str {
+        match self {
+            Self::Sine => "Sine",
+            Self::Square => "Square",
+            Self::Triangle => "Tri",
+            Self::Sawtooth => "Saw",
+        }
+    }
+}
+
+/// The two neighbouring waveforms at morph `position` (`0..=1`) and how far
+/// from the first to the second, in `[0, 1)` (`1` only at the very end).
+pub fn morph_shapes(position: f32) -> (Waveform, Waveform, f32) {
+    let scaled = position.clamp(0.0, 1.0) * (Waveform::MORPH.len() - 1) as f32;
+    let index = (scaled as usize).min(Waveform::MORPH.len() - 2);
+    (
+        Waveform::MORPH[index],
+        Waveform::MORPH[index + 1],
+        scaled - index as f32,
+    )
+}
+
+/// `"Sine"` on a shape, `"Tri > Saw 40%"` between two.
+fn morph_to_string(position: f32) -> String {
+    let (from, to, amount) = morph_shapes(position);
+    if amount < 0.005 {
+        from.short_name().to_string()
+    } else if amount > 0.995 {
+        to.short_name().to_string()
+    } else {
+        format!("{} > {} {:.0}%", from.short_name(), to.short_name(), amount * 100.0)
+    }
+}
+
+/// A shape's name, or a position as `0..1` or a percentage.
+fn string_to_morph(string: &str) -> Option<f32> {
+    let string = string.trim().to_lowercase();
+    let shape = |name: &str| match name {
+        "sine" | "sin" => Some(Waveform::Sine),
+        "triangle" | "tri" => Some(Waveform::Triangle),
+        "sawtooth" | "saw" => Some(Waveform::Sawtooth),
+        "square" | "sqr" => Some(Waveform::Square),
+        _ => None,
+    };
+    if let Some(waveform) = shape(&string) {
+        let index = Waveform::MORPH.iter().position(|w| *w == waveform)?;
+        return Some(index as f32 / (Waveform::MORPH.len() - 1) as f32);
+    }
+    match string.strip_suffix('%') {
+        Some(percent) => percent.trim().parse().ok().map(|x: f32| x / 100.0),
+        None => string.parse().ok(),
+    }
+}
+
+/// How the unison voices' detune offsets are spread between `-1` and `1`
+/// (scaled by the unison detune amount).
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetuneCurve {
+    /// Evenly spaced.
+    #[id = "linear"]
+    #[name = "Linear"]
+    Linear,
+    /// Inner voices close to the center, outer voices far out.
+    #[id = "exponential"]
+    #[name = "Exponential"]
+    Exponential,
+    /// The uneven spacing of the classic hardware super-saw: a tight inner
+    /// pair and wide outer voices.
+    #[id = "supersaw"]
+    #[name = "Super-Saw"]
+    SuperSaw,
+}
+
+impl Default for DetuneCurve {
+    fn default() -> Self {
+        Self::Linear
+    }
+}
+
+/// The noise source's spectrum (see `dsp::noise`).
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseColor {
+    /// Flat: equal energy per Hz, bright and hissy.
+    #[id = "white"]
+    #[name = "White"]
+    White,
+    /// Equal energy per octave: darker, closer to wind and breath.
+    #[id = "pink"]
+    #[name = "Pink"]
+    Pink,
+}
+
+/// The sub oscillator's waveform (see `dsp::sub`).
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubWaveform {
+    #[id = "sine"]
+    #[name = "Sine"]
+    Sine,
+    /// Hollow and buzzier; odd harmonics only.
+    #[id = "square"]
+    #[name = "Square"]
+    Square,
+}
+
+/// The saturation curve of the filter's drive stage.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriveCharacter {
+    /// Symmetric `tanh`: odd harmonics, smooth clipping.
+    #[id = "soft"]
+    #[name = "Soft"]
+    Soft,
+    /// Biased `tanh`: adds even harmonics for a warmer, fuller tone.
+    #[id = "asymmetric"]
+    #[name = "Asymmetric"]
+    Asymmetric,
+    /// Clean up to a knee that comes earlier on the negative half, then
+    /// rounding off.
+    #[id = "tube"]
+    #[name = "Tube"]
+    Tube,
+}
+
+impl Default for DriveCharacter {
+    fn default() -> Self {
+        Self::Soft
+    }
+}
+
+/// How a glide's duration is derived from the `glide_time` knob.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlideMode {
+    /// Every glide takes `glide_time`, however far apart the notes are.
+    #[id = "constant_time"]
+    #[name = "Constant Time"]
+    ConstantTime,
+    /// `glide_time` is the time per octave, so wider intervals glide longer.
+    #[id = "constant_rate"]
+    #[name = "Constant Rate"]
+    ConstantRate,
+}
+
+impl Default for GlideMode {
+    fn default() -> Self {
+        Self::ConstantTime
+    }
+}
+
+/// What a released note's pitch does with the bend wheel while it rings out.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BendRelease {
+    /// Keeps following the wheel, like every note still held.
+    #[id = "follow"]
+    #[name = "Follow"]
+    Follow,
+    /// Keeps the bend it had at note-off, so moving the wheel for the next
+    /// note doesn't drag the tail along.
+    #[id = "hold"]
+    #[name = "Hold"]
+    Hold,
+    /// Glides back to the unbent pitch over the bend smoothing time.
+    #[id = "return"]
+    #[name = "Return"]
+    Return,
+}
+
+impl Default for BendRelease {
+    fn default() -> Self {
+        Self::Follow
+    }
+}
+
+/// When a tempo-synced LFO locks its phase to the host's song position, so a
+/// bounce modulates the same way every time.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LfoReset {
+    /// Never: the LFO free-runs at the synced rate.
+    #[id = "free"]
+    #[name = "Free"]
+    Free,
+    /// When the transport starts playing.
+    #[id = "transport"]
+    #[name = "Play"]
+    Transport,
+    /// When the transport starts playing and at every bar line, which also
+    /// undoes any drift from tempo changes.
+    #[id = "bar"]
+    #[name = "Bar"]
+    Bar,
+}
+
+impl Default for LfoReset {
+    fn default() -> Self {
+        Self::Transport
+    }
+}
+
+/// How quickly parameter changes glide to their new value. The smoothers are
+/// built once with their slowest times (50 ms for most knobs); faster settings
+/// advance them several steps per sample, scaling every smoothing time alike.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Smoothing {
+    /// About 5 ms: filter stabs and fast automation stay crisp.
+    #[id = "fast"]
+    #[name = "Fast"]
+    Fast,
+    /// About 17 ms.
+    #[id = "medium"]
+    #[name = "Medium"]
+    Medium,
+    /// 50 ms: no zipper noise even on big, slow knob sweeps.
+    #[id = "slow"]
+    #[name = "Slow"]
+    Slow,
+}
+
+impl Smoothing {
+    /// Smoother steps taken per output sample.
+    pub fn steps_per_sample(self) -> u32 {
+        match self {
+            Self::Fast => 10,
+            Self::Medium => 3,
+            Self::Slow => 1,
+        }
+    }
+}
+
+impl Default for Smoothing {
+    fn default() -> Self {
+        Self::Slow
+    }
+}
+
+/// What drives a modulation slot. Every source is in `[-1, 1]` (the LFO) or
+/// `[0, 1]` (the filter envelope, velocity, sidechain).
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModSource {
+    #[id = "lfo"]
+    #[name = "LFO"]
+    Lfo,
+    #[id = "filter_env"]
+    #[name = "Filter Env"]
+    FilterEnv,
+    #[id = "velocity"]
+    Velocity,
+    /// The level of the sidechain input (another track), via an envelope
+    /// follower. A negative amount ducks the destination while it plays.
+    #[id = "sidechain"]
+    Sidechain,
+    /// A blip fired at note-off: it jumps to the release velocity (full when
+    /// the controller doesn't send one) and dies away over
+    /// [`ModParams::release_blip`]. For key-off clicks and organ-style
+    /// releases.
+    #[id = "release"]
+    Release,
+}
+
+impl Default for ModSource {
+    fn default() -> Self {
+        Self::Lfo
+    }
+}
+
+impl ModSource {
+    pub const ALL: [ModSource; 5] = [
+        Self::Lfo,
+        Self::FilterEnv,
+        Self::Velocity,
+        Self::Sidechain,
+        Self::Release,
+    ];
+}
+
+/// What a modulation slot changes. See `dsp::mod_matrix` for which of these
+/// update per sample and which at control rate.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModDestination {
+    #[id = "none"]
+    #[name = "None"]
+    Off,
+    /// ±12 semitones on all oscillators.
+    #[id = "pitch"]
+    Pitch,
+    /// Square-wave duty cycle, ±45% around 50%.
+    #[id = "pulse_width"]
+    #[name = "Pulse Width"]
+    PulseWidth,
+    /// Voice level, ±100%.
+    #[id = "level"]
+    Level,
+    /// Filter cutoff, ±4 octaves.
+    #[id = "cutoff"]
+    Cutoff,
+    /// Filter resonance, ±100%.
+    #[id = "resonance"]
+    Resonance,
+    /// Formant filter vowel, ±the whole A–U range.
+    #[id = "vowel"]
+    Vowel,
+    /// Waveform morph position, ±the whole sine–square range, on every
+    /// oscillator with morphing on.
+    #[id = "morph"]
+    Morph,
+}
+
+impl Default for ModDestination {
+    fn default() -> Self {
+        Self::Off
+    }
+}
+
+/// The order the arpeggiator plays the held keys in.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArpMode {
+    #[id = "up"]
+    Up,
+    #[id = "down"]
+    Down,
+    /// Up then back down, without repeating the top and bottom notes.
+    #[id = "up_down"]
+    #[name = "Up/Down"]
+    UpDown,
+    /// The order the keys went down in.
+    #[id = "played"]
+    Played,
+    #[id = "random"]
+    Random,
+}
+
+impl Default for ArpMode {
+    fn default() -> Self {
+        Self::Up
+    }
+}
+
+/// Key of the scale quantizer (see `dsp::scale`).
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleRoot {
+    #[id = "c"]
+    C,
+    #[id = "c_sharp"]
+    #[name = "C#"]
+    CSharp,
+    #[id = "d"]
+    D,
+    #[id = "d_sharp"]
+    #[name = "D#"]
+    DSharp,
+    #[id = "e"]
+    E,
+    #[id = "f"]
+    F,
+    #[id = "f_sharp"]
+    #[name = "F#"]
+    FSharp,
+    #[id = "g"]
+    G,
+    #[id = "g_sharp"]
+    #[name = "G#"]
+    GSharp,
+    #[id = "a"]
+    A,
+    #[id = "a_sharp"]
+    #[name = "A#"]
+    ASharp,
+    #[id = "b"]
+    B,
+}
+
+impl Default for ScaleRoot {
+    fn default() -> Self {
+        Self::C
+    }
+}
+
+impl ScaleRoot {
+    /// Semitones above C.
+    pub fn semitone(self) -> u8 {
+        self.to_index() as u8
+    }
+
+    pub fn from_semitone(semitone: u8) -> Self {
+        Self::from_index(usize::from(semitone % 12))
+    }
+}
+
+/// Scale of the quantizer. Chromatic lets every note through.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scale {
+    #[id = "chromatic"]
+    Chromatic,
+    #[id = "major"]
+    Major,
+    /// Natural minor.
+    #[id = "minor"]
+    Minor,
+    #[id = "harmonic_minor"]
+    #[name = "Harmonic Minor"]
+    HarmonicMinor,
+    #[id = "melodic_minor"]
+    #[name = "Melodic Minor"]
+    MelodicMinor,
+    #[id = "dorian"]
+    Dorian,
+    #[id = "phrygian"]
+    Phrygian,
+    #[id = "lydian"]
+    Lydian,
+    #[id = "mixolydian"]
+    Mixolydian,
+    #[id = "locrian"]
+    Locrian,
+    #[id = "major_pentatonic"]
+    #[name = "Major Pentatonic"]
+    MajorPentatonic,
+    #[id = "minor_pentatonic"]
+    #[name = "Minor Pentatonic"]
+    MinorPentatonic,
+    #[id = "blues"]
+    Blues,
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Self::Chromatic
+    }
+}
+
+impl Scale {
+    /// The scale's notes, in semitones above its root.
+    pub fn steps(self) -> &'static [u8] {
+        match self {
+            Self::Chromatic => &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            Self::Major => &[0, 2, 4, 5, 7, 9, 11],
+            Self::Minor => &[0, 2, 3, 5, 7, 8, 10],
+            Self::HarmonicMinor => &[0, 2, 3, 5, 7, 8, 11],
+            Self::MelodicMinor => &[0, 2, 3, 5, 7, 9, 11],
+            Self::Dorian => &[0, 2, 3, 5, 7, 9, 10],
+            Self::Phrygian => &[0, 1, 3, 5, 7, 8, 10],
+            Self::Lydian => &[0, 2, 4, 6, 7, 9, 11],
+            Self::Mixolydian => &[0, 2, 4, 5, 7, 9, 10],
+            Self::Locrian => &[0, 1, 3, 5, 6, 8, 10],
+            Self::MajorPentatonic => &[0, 2, 4, 7, 9],
+            Self::MinorPentatonic => &[0, 3, 5, 7, 10],
+            Self::Blues => &[0, 3, 5, 6, 7, 10],
+        }
+    }
+}
+
+/// Number of slots in the modulation matrix.
+pub const NUM_MOD_SLOTS: usize = 3;
+
+/// A note length for tempo-synced times, in straight note values.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteDivision {
+    #[id = "1/32"]
+    #[name = "1/32"]
+    ThirtySecond,
+    #[id = "1/16"]
+    #[name = "1/16"]
+    Sixteenth,
+    #[id = "1/8"]
+    #[name = "1/8"]
+    Eighth,
+    #[id = "1/4"]
+    #[name = "1/4"]
+    Quarter,
+    #[id = "1/2"]
+    #[name = "1/2"]
+    Half,
+    #[id = "1/1"]
+    #[name = "1 Bar"]
+    Whole,
+}
+
+impl Default for NoteDivision {
+    fn default() -> Self {
+        Self::Eighth
+    }
+}
+
+impl NoteDivision {
+    /// Length in quarter-note beats.
+    pub fn beats(self) -> f32 {
+        match self {
+            Self::ThirtySecond => 0.125,
+            Self::Sixteenth => 0.25,
+            Self::Eighth => 0.5,
+            Self::Quarter => 1.0,
+            Self::Half => 2.0,
+            Self::Whole => 4.0,
+        }
+    }
+
+    /// Length in seconds at `bpm`.
+    pub fn seconds(self, bpm: f32) -> f32 {
+        self.beats() * 60.0 / bpm.max(1.0)
+    }
+}
+
+/// Bottom of the level knobs' dB scale. The knob still travels to a true `0.0`
+/// (-inf dB, muted); this only shapes the skew so the travel above it reads
+/// roughly linear in dB.
+const LEVEL_FLOOR_DB: f32 = -60.0;
+
+/// A level control shared by every oscillator gain stage. The value is stored as
+/// linear gain (so saved projects and presets keep their meaning) but is shown
+/// and typed in dB, with "-inf" at the bottom of the range.
+///
+/// Smoothing is linear rather than logarithmic: a logarithmic smoother can't
+/// reach or leave `0.0`.
+fn level_param(name: &str, default_db: f32) -> FloatParam {
+    FloatParam::new(
+        name,
+        util::db_to_gain(default_db),
+        FloatRange::Skewed {
+            min: 0.0,
+            max: util::db_to_gain(0.0),
+            factor: FloatRange::gain_skew_factor(LEVEL_FLOOR_DB, 0.0),
+        },
+    )
+    .with_smoother(SmoothingStyle::Linear(50.0))
+    .with_unit(" dB")
+    .with_value_to_string(formatters::v2s_f32_gain_to_db(2))
+    .with_string_to_value(formatters::s2v_f32_gain_to_db())
+}
+
 /// One oscillator's parameters. Nested three times in [`SineParams`]; the
 /// `id_prefix` on each `#[nested]` keeps host automation IDs unique
 /// (`osc1_freq`, `osc2_freq`, ...).
@@ -53,10 +595,33 @@ pub struct OscillatorParams {
     pub waveform: EnumParam<Waveform>,
     #[id = "freq"]
     pub frequency: FloatParam,
+    /// When off, the oscillator ignores the played note and sounds `frequency`
+    /// itself (octave and detune still apply) — for drones and fixed-pitch
+    /// partials.
+    #[id = "key_track"]
+    pub key_track: BoolParam,
     #[id = "detune"]
     pub detune: FloatParam,
     #[id = "phase"]
     pub phase: FloatParam,
+    /// Casio CZ-style phase distortion: bends the phase ramp before the
+    /// waveform lookup, so a sine turns brassy and resonant without FM. `0`
+    /// leaves the waveform as is.
+    #[id = "phase_dist"]
+    pub phase_distortion: FloatParam,
+    /// West-coast wavefolder after the waveform (see
+    /// `UnisonOscillator::fold`): rich overtones from a sine or triangle. `0`
+    /// leaves the waveform as is.
+    #[id = "wavefold"]
+    pub wavefold: FloatParam,
+    /// Replace the waveform switch with `morph_position`, a continuous sweep
+    /// through sine, triangle, saw and square.
+    #[id = "morph"]
+    pub morph: BoolParam,
+    /// Where along sine → triangle → saw → square the morph sits: each shape
+    /// a third apart, crossfaded in between (see [`morph_shapes`]).
+    #[id = "morph_position"]
+    pub morph_position: FloatParam,
     #[id = "gain"]
     pub gain: FloatParam,
     #[id = "octave"]
@@ -69,6 +634,38 @@ pub struct OscillatorParams {
     pub unison_blend: FloatParam,
     #[id = "unison_volume"]
     pub unison_volume: FloatParam,
+    #[id = "unison_curve"]
+    pub unison_curve: EnumParam<DetuneCurve>,
+    /// Width vs. density: above `0.5` the voices bunch toward the center (a
+    /// thick, chorused core), below it they push out to the edges (wide and
+    /// beating). `0.5` leaves the curve as is.
+    #[id = "unison_density"]
+    pub unison_density: FloatParam,
+    /// Keeps the level steady as voices are added or blended in: averaged,
+    /// detuned voices lose power as their phases drift apart (`1 / n` of one
+    /// voice's for `n`), which this makes up.
+    #[id = "unison_comp"]
+    pub unison_compensation: BoolParam,
+    #[id = "mute"]
+    pub mute: BoolParam,
+    /// While any oscillator is soloed, only soloed oscillators sound; mutes
+    /// are ignored. Leaves every level untouched, so un-soloing restores the
+    /// mix exactly.
+    #[id = "solo"]
+    pub solo: BoolParam,
+    /// Run through the comb and the filter (and its drive). Off, the
+    /// oscillator joins the voice after them, dry: a sub or a click layer the
+    /// filter sweep shouldn't touch.
+    #[id = "to_filter"]
+    pub to_filter: BoolParam,
+    /// Play through [`OscillatorParams::envelope`] instead of the main amp
+    /// envelope, so a pad layer can swell under a pluck in the same voice.
+    #[id = "own_env"]
+    pub own_envelope: BoolParam,
+    /// This oscillator's own amp envelope (`osc1_env_attack`, ...), used while
+    /// `own_envelope` is on.
+    #[nested(id_prefix = "env", group = "Envelope")]
+    pub envelope: AdsrParams,
 }
 
 impl OscillatorParams {
@@ -97,6 +694,8 @@ impl OscillatorParams {
             .with_value_to_string(formatters::v2s_f32_hz_then_khz(2))
             .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
 
+            key_track: BoolParam::new("Key Track", true),
+
             detune: FloatParam::new(
                 "Detune",
                 0.0,
@@ -121,18 +720,31 @@ impl OscillatorParams {
                         .map(|x: f32| x / 360.0)
                 })),
 
-            gain: FloatParam::new(
-                "Gain",
-                util::db_to_gain(default_gain_db),
-                FloatRange::Linear {
-                    min: util::db_to_gain(-36.0),
-                    max: util::db_to_gain(0.0),
-                },
+            phase_distortion: FloatParam::new(
+                "Phase Distortion",
+                0.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
             )
-            .with_smoother(SmoothingStyle::Logarithmic(50.0))
-            .with_unit(" dB")
-            .with_value_to_string(formatters::v2s_f32_gain_to_db(2))
-            .with_string_to_value(formatters::s2v_f32_gain_to_db()),
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_unit("%"),
+
+            wavefold: FloatParam::new("Wavefold", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 })
+                .with_smoother(SmoothingStyle::Linear(50.0))
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_unit("%"),
+
+            morph: BoolParam::new("Morph", false),
+            morph_position: FloatParam::new(
+                "Morph Position",
+                0.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(Arc::new(morph_to_string))
+            .with_string_to_value(Arc::new(string_to_morph)),
+
+            gain: level_param("Gain", default_gain_db),
 
             octave: IntParam::new("Octave", default_octave, IntRange::Linear { min: -4, max: 4 }),
 
@@ -155,13 +767,23 @@ impl OscillatorParams {
             )
             .with_smoother(SmoothingStyle::Linear(50.0))
             .with_value_to_string(formatters::v2s_f32_percentage(1)),
-            unison_volume: FloatParam::new(
-                "Unison Volume",
-                1.0,
+            unison_volume: level_param("Unison Volume", 0.0),
+            unison_curve: EnumParam::new("Unison Curve", DetuneCurve::Linear),
+            unison_density: FloatParam::new(
+                "Unison Density",
+                0.5,
                 FloatRange::Linear { min: 0.0, max: 1.0 },
             )
-            .with_smoother(SmoothingStyle::Linear(50.0))
-            .with_value_to_string(formatters::v2s_f32_percentage(1)),
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_unit("%"),
+            // Off, so projects saved before it existed keep their level.
+            unison_compensation: BoolParam::new("Unison Gain Comp", false),
+
+            mute: BoolParam::new("Mute", false),
+            solo: BoolParam::new("Solo", false),
+            to_filter: BoolParam::new("To Filter", true),
+            own_envelope: BoolParam::new("Own Envelope", false),
+            envelope: AdsrParams::default(),
         }
     }
 }
@@ -176,14 +798,28 @@ pub struct FilterParams {
     pub resonance: FloatParam,
     #[id = "drive"]
     pub drive: FloatParam,
+    /// The saturation curve drive pushes into (see `dsp::filter::Drive`).
+    #[id = "drive_character"]
+    pub drive_character: EnumParam<DriveCharacter>,
+    /// Turn the output down as drive goes up, so drive changes the tone more
+    /// than the level.
+    #[id = "drive_comp"]
+    pub drive_compensation: BoolParam,
     /// Bipolar filter-envelope depth, in octaves. The per-voice filter envelope
     /// (see [`SineParams::filter_env`]) scales the cutoff by `2^(env_amount *
     /// env_level)`. `0` (the default) disables the envelope, so existing patches
     /// are unchanged.
     #[id = "env_amount"]
     pub env_amount: FloatParam,
+    /// The vowel formant mode shapes the sound into, morphing A → E → I → O →
+    /// U across the range.
+    #[id = "vowel"]
+    pub vowel: FloatParam,
 }
 
+/// The vowels along [`FilterParams::vowel`], evenly spaced.
+const VOWEL_NAMES: [&str; 5] = ["A", "E", "I", "O", "U"];
+
 impl Default for FilterParams {
     fn default() -> Self {
         Self {
@@ -216,6 +852,8 @@ impl Default for FilterParams {
                 FloatRange::Linear { min: 1.0, max: 5.0 },
             )
             .with_smoother(SmoothingStyle::Linear(50.0)),
+            drive_character: EnumParam::new("Drive Character", DriveCharacter::Soft),
+            drive_compensation: BoolParam::new("Drive Compensation", true),
 
             env_amount: FloatParam::new(
                 "Filter Env Amount",
@@ -228,20 +866,232 @@ impl Default for FilterParams {
             .with_smoother(SmoothingStyle::Linear(50.0))
             .with_unit(" oct")
             .with_value_to_string(formatters::v2s_f32_rounded(1)),
+
+            vowel: FloatParam::new("Filter Vowel", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 })
+                .with_smoother(SmoothingStyle::Linear(50.0))
+                .with_value_to_string(Arc::new(|value| {
+                    let last = (VOWEL_NAMES.len() - 1) as f32;
+                    VOWEL_NAMES[(value * last).round() as usize].to_string()
+                }))
+                .with_string_to_value(Arc::new(|string| {
+                    let last = (VOWEL_NAMES.len() - 1) as f32;
+                    VOWEL_NAMES
+                        .iter()
+                        .position(|name| name.eq_ignore_ascii_case(string.trim()))
+                        .map(|index| index as f32 / last)
+                })),
+        }
+    }
+}
+
+/// The Color macro: one bipolar knob for the overall timbre, and how far it
+/// reaches into each stage. Turning it up bends the oscillators (phase
+/// distortion), opens the cutoff and adds drive; turning it down closes the
+/// cutoff. Both ways it moves morphing oscillators along their blend, up
+/// toward square and down toward sine. `0` leaves the patch as it is.
+#[derive(Params)]
+pub struct ColorParams {
+    #[id = "amount"]
+    pub amount: FloatParam,
+    /// Octaves the cutoff moves at full Color, either way.
+    #[id = "cutoff"]
+    pub cutoff: FloatParam,
+    /// Drive added at full Color, on a square-law curve so the first half of
+    /// the knob stays fairly clean. Positive Color only.
+    #[id = "drive"]
+    pub drive: FloatParam,
+    /// Phase distortion added to every oscillator at full Color. Positive
+    /// Color only.
+    #[id = "shape"]
+    pub shape: FloatParam,
+    /// How far full Color moves `morph_position`, either way, on oscillators
+    /// that are morphing.
+    #[id = "blend"]
+    pub blend: FloatParam,
+}
+
+impl Default for ColorParams {
+    fn default() -> Self {
+        Self {
+            amount: FloatParam::new(
+                "Color",
+                0.0,
+                FloatRange::Linear {
+                    min: -1.0,
+                    max: 1.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_unit("%"),
+            cutoff: FloatParam::new(
+                "Color Cutoff",
+                3.0,
+                FloatRange::Linear { min: 0.0, max: 6.0 },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit(" oct")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            drive: FloatParam::new(
+                "Color Drive",
+                2.0,
+                FloatRange::Linear { min: 0.0, max: 4.0 },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            shape: FloatParam::new(
+                "Color Shape",
+                0.5,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_unit("%"),
+            // One shape along sine → triangle → saw → square.
+            blend: FloatParam::new(
+                "Color Blend",
+                1.0 / 3.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_unit("%"),
+        }
+    }
+}
+
+/// A tuned comb per voice between the oscillators and the filter, its delay
+/// one period of the played note (see `dsp::comb`).
+#[derive(Params)]
+pub struct CombParams {
+    #[id = "enabled"]
+    pub enabled: BoolParam,
+    /// Karplus-Strong: only the note's first period excites the comb, which
+    /// then rings on alone like a plucked string.
+    #[id = "pluck"]
+    pub pluck: BoolParam,
+    /// How long the comb rings: the share of each period fed back.
+    #[id = "feedback"]
+    pub feedback: FloatParam,
+    /// How fast the ringing loses its highs; `0` rings bright and metallic.
+    #[id = "damping"]
+    pub damping: FloatParam,
+    #[id = "mix"]
+    pub mix: FloatParam,
+}
+
+impl Default for CombParams {
+    fn default() -> Self {
+        Self {
+            enabled: BoolParam::new("Comb", false),
+            pluck: BoolParam::new("Comb Pluck", false),
+            feedback: FloatParam::new(
+                "Comb Feedback",
+                0.9,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 0.99,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_unit("%"),
+            damping: FloatParam::new(
+                "Comb Damping",
+                0.3,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_unit("%"),
+            mix: FloatParam::new("Comb Mix", 1.0, FloatRange::Linear { min: 0.0, max: 1.0 })
+                .with_smoother(SmoothingStyle::Linear(50.0))
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_unit("%"),
+        }
+    }
+}
+
+/// The per-voice noise source, mixed in with the oscillators ahead of the
+/// comb and the filter and played through the amp envelope (see
+/// `dsp::noise`).
+#[derive(Params)]
+pub struct NoiseParams {
+    #[id = "color"]
+    pub color: EnumParam<NoiseColor>,
+    /// Off (-inf dB) by default.
+    #[id = "level"]
+    pub level: FloatParam,
+}
+
+impl Default for NoiseParams {
+    fn default() -> Self {
+        Self {
+            color: EnumParam::new("Noise Color", NoiseColor::White),
+            level: level_param("Noise Level", util::MINUS_INFINITY_DB),
+        }
+    }
+}
+
+/// The per-voice sub oscillator: one or two octaves under oscillator 1, mixed
+/// in with the oscillators ahead of the comb and the filter and played
+/// through the amp envelope (see `dsp::sub`).
+#[derive(Params)]
+pub struct SubParams {
+    #[id = "waveform"]
+    pub waveform: EnumParam<SubWaveform>,
+    /// Octaves under oscillator 1: `1` or `2`.
+    #[id = "octave"]
+    pub octave: IntParam,
+    /// Off (-inf dB) by default.
+    #[id = "level"]
+    pub level: FloatParam,
+}
+
+impl Default for SubParams {
+    fn default() -> Self {
+        Self {
+            waveform: EnumParam::new("Sub Waveform", SubWaveform::Sine),
+            octave: IntParam::new("Sub Octave", 1, IntRange::Linear { min: 1, max: 2 })
+                .with_value_to_string(Arc::new(|octaves| format!("-{octaves} Oct")))
+                .with_string_to_value(Arc::new(|string| {
+                    let string = string.trim().trim_end_matches(|c: char| c.is_alphabetic());
+                    string.trim().trim_start_matches('-').parse().ok()
+                })),
+            level: level_param("Sub Level", util::MINUS_INFINITY_DB),
         }
     }
 }
 
+/// An AHDSR (an ADSR with a hold stage after the attack). Attack, decay and
+/// release can each be synced to the host tempo, in which case the stage lasts
+/// its `*_division` note value and the time knob is ignored.
 #[derive(Params)]
 pub struct AdsrParams {
     #[id = "attack"]
     pub attack: FloatParam,
+    /// Time at full level between attack and decay; `0` makes it a plain ADSR.
+    #[id = "hold"]
+    pub hold: FloatParam,
     #[id = "decay"]
     pub decay: FloatParam,
     #[id = "sustain"]
     pub sustain: FloatParam,
     #[id = "release"]
     pub release: FloatParam,
+
+    #[id = "attack_sync"]
+    pub attack_sync: BoolParam,
+    #[id = "attack_div"]
+    pub attack_division: EnumParam<NoteDivision>,
+    #[id = "decay_sync"]
+    pub decay_sync: BoolParam,
+    #[id = "decay_div"]
+    pub decay_division: EnumParam<NoteDivision>,
+    #[id = "release_sync"]
+    pub release_sync: BoolParam,
+    #[id = "release_div"]
+    pub release_division: EnumParam<NoteDivision>,
 }
 
 impl Default for AdsrParams {
@@ -260,6 +1110,19 @@ impl Default for AdsrParams {
             .with_unit(" s")
             .with_value_to_string(formatters::v2s_f32_rounded(3)),
 
+            hold: FloatParam::new(
+                "Hold",
+                0.0,
+                FloatRange::Skewed {
+                    min: 0.0,
+                    max: 5.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(10.0))
+            .with_unit(" s")
+            .with_value_to_string(formatters::v2s_f32_rounded(3)),
+
             decay: FloatParam::new(
                 "Decay",
                 0.5,
@@ -288,6 +1151,467 @@ impl Default for AdsrParams {
             )
             .with_smoother(SmoothingStyle::Linear(10.0))
             .with_unit(" s"),
+
+            attack_sync: BoolParam::new("Attack Sync", false),
+            attack_division: EnumParam::new("Attack Division", NoteDivision::Sixteenth),
+            decay_sync: BoolParam::new("Decay Sync", false),
+            decay_division: EnumParam::new("Decay Division", NoteDivision::Eighth),
+            release_sync: BoolParam::new("Release Sync", false),
+            release_division: EnumParam::new("Release Division", NoteDivision::Quarter),
+        }
+    }
+}
+
+/// Portamento. A new note glides in from the previously played note's pitch;
+/// `time = 0` (the default) disables it.
+#[derive(Params)]
+pub struct GlideParams {
+    #[id = "time"]
+    pub time: FloatParam,
+    /// Fingered portamento: only glide when the new note overlaps a held one,
+    /// so detached phrases start on pitch and legato runs slide.
+    #[id = "legato"]
+    pub legato: BoolParam,
+    #[id = "mode"]
+    pub mode: EnumParam<GlideMode>,
+}
+
+impl Default for GlideParams {
+    fn default() -> Self {
+        Self {
+            time: FloatParam::new(
+                "Glide Time",
+                0.0,
+                FloatRange::Skewed {
+                    min: 0.0,
+                    max: 5.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_unit(" s")
+            .with_value_to_string(formatters::v2s_f32_rounded(3)),
+
+            legato: BoolParam::new("Legato Glide", false),
+
+            mode: EnumParam::new("Glide Mode", GlideMode::ConstantTime),
+        }
+    }
+}
+
+/// The pitch-bend wheel (see `dsp::bend`).
+#[derive(Params)]
+pub struct BendParams {
+    /// Semitones at full bend, either way.
+    #[id = "range"]
+    pub range: IntParam,
+    /// How quickly the pitch catches up with the wheel; smooths out the steps
+    /// a wide range makes of the controller's values.
+    #[id = "smoothing"]
+    pub smoothing: FloatParam,
+    #[id = "release"]
+    pub release: EnumParam<BendRelease>,
+}
+
+impl Default for BendParams {
+    fn default() -> Self {
+        Self {
+            range: IntParam::new("Bend Range", 2, IntRange::Linear { min: 0, max: 24 })
+                .with_unit(" st"),
+            smoothing: FloatParam::new(
+                "Bend Smoothing",
+                0.01,
+                FloatRange::Skewed {
+                    min: 0.0,
+                    max: 0.5,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_unit(" s")
+            .with_value_to_string(formatters::v2s_f32_rounded(3)),
+            release: EnumParam::new("Bend Release", BendRelease::Follow),
+        }
+    }
+}
+
+/// One modulation slot: `source * amount` is added to `destination`.
+#[derive(Params)]
+pub struct ModSlotParams {
+    #[id = "source"]
+    pub source: EnumParam<ModSource>,
+    #[id = "dest"]
+    pub destination: EnumParam<ModDestination>,
+    /// Bipolar depth; `±1` reaches the destination's full range.
+    #[id = "amount"]
+    pub amount: FloatParam,
+}
+
+impl Default for ModSlotParams {
+    fn default() -> Self {
+        Self {
+            source: EnumParam::new("Mod Source", ModSource::Lfo),
+            destination: EnumParam::new("Mod Destination", ModDestination::Off),
+            amount: FloatParam::new(
+                "Mod Amount",
+                0.0,
+                FloatRange::Linear {
+                    min: -1.0,
+                    max: 1.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(20.0))
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_unit("%"),
+        }
+    }
+}
+
+/// Number of drawbars in oscillator 1's additive engine: harmonics 1 to 8.
+pub const NUM_DRAWBARS: usize = 8;
+
+/// One drawbar: the level of one harmonic.
+#[derive(Params)]
+pub struct DrawbarParams {
+    #[id = "level"]
+    pub level: FloatParam,
+}
+
+impl DrawbarParams {
+    fn new(harmonic: usize, default: f32) -> Self {
+        Self {
+            level: FloatParam::new(
+                format!("Drawbar {harmonic}"),
+                default,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_unit("%"),
+        }
+    }
+}
+
+/// Oscillator 1's additive (organ drawbar) engine. While enabled it replaces
+/// osc1's waveform with a sum of harmonics; every other osc1 control
+/// (pitch, unison, phase distortion, level) still applies.
+#[derive(Params)]
+pub struct AdditiveParams {
+    #[id = "enabled"]
+    pub enabled: BoolParam,
+    /// Param IDs get a `_1`, `_2`, ... suffix per harmonic.
+    #[nested(array, group = "Drawbar")]
+    pub drawbars: [DrawbarParams; NUM_DRAWBARS],
+}
+
+impl Default for AdditiveParams {
+    fn default() -> Self {
+        Self {
+            enabled: BoolParam::new("Additive", false),
+            // Just the fundamental, so switching the engine on starts as a sine.
+            drawbars: std::array::from_fn(|i| {
+                DrawbarParams::new(i + 1, if i == 0 { 1.0 } else { 0.0 })
+            }),
+        }
+    }
+}
+
+/// The global LFO and the modulation matrix slots it (and the other sources)
+/// feed.
+#[derive(Params)]
+pub struct ModParams {
+    #[id = "lfo_rate"]
+    pub lfo_rate: FloatParam,
+    #[id = "lfo_shape"]
+    pub lfo_shape: EnumParam<Waveform>,
+    /// Replaces `lfo_rate` with one cycle per `lfo_division` at the host tempo.
+    #[id = "lfo_sync"]
+    pub lfo_sync: BoolParam,
+    #[id = "lfo_division"]
+    pub lfo_division: EnumParam<NoteDivision>,
+    /// Only applies while synced.
+    #[id = "lfo_reset"]
+    pub lfo_reset: EnumParam<LfoReset>,
+    /// How fast the sidechain follower rises to a louder input.
+    #[id = "sc_attack"]
+    pub sidechain_attack: FloatParam,
+    /// How fast it falls back once the input gets quieter.
+    #[id = "sc_release"]
+    pub sidechain_release: FloatParam,
+    /// How long the release blip (the `Release` source) takes to fall by
+    /// 60 dB after note-off.
+    #[id = "release_blip"]
+    pub release_blip: FloatParam,
+    /// Param IDs get a `_1`, `_2`, ... suffix per slot (`mod_amount_1`).
+    #[nested(array, group = "Mod Slot")]
+    pub slots: [ModSlotParams; NUM_MOD_SLOTS],
+}
+
+impl Default for ModParams {
+    fn default() -> Self {
+        Self {
+            lfo_rate: FloatParam::new(
+                "LFO Rate",
+                2.0,
+                FloatRange::Skewed {
+                    min: 0.01,
+                    max: 20.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Logarithmic(50.0))
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+
+            lfo_shape: EnumParam::new("LFO Shape", Waveform::Sine),
+            lfo_sync: BoolParam::new("LFO Sync", false),
+            lfo_division: EnumParam::new("LFO Division", NoteDivision::Quarter),
+            lfo_reset: EnumParam::new("LFO Reset", LfoReset::default()),
+
+            sidechain_attack: FloatParam::new(
+                "Sidechain Attack",
+                5.0,
+                FloatRange::Skewed {
+                    min: 0.1,
+                    max: 100.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            sidechain_release: FloatParam::new(
+                "Sidechain Release",
+                150.0,
+                FloatRange::Skewed {
+                    min: 10.0,
+                    max: 1000.0,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+            release_blip: FloatParam::new(
+                "Release Blip",
+                40.0,
+                FloatRange::Skewed {
+                    min: 1.0,
+                    max: 500.0,
+                    factor: FloatRange::skew_factor(-1.5),
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+
+            slots: Default::default(),
+        }
+    }
+}
+
+/// Steps in the trance gate's pattern.
+pub const NUM_GATE_STEPS: usize = 16;
+
+/// One step of the trance gate's pattern: open (full level) or gated.
+#[derive(Params)]
+pub struct GateStepParams {
+    #[id = "on"]
+    pub on: BoolParam,
+}
+
+impl GateStepParams {
+    fn new(step: usize, on: bool) -> Self {
+        Self {
+            on: BoolParam::new(format!("Step {step}"), on),
+        }
+    }
+}
+
+/// Master-bus trance gate and auto-pan, stepped through its pattern at a
+/// tempo-synced division and locked to the host's bar position while it plays
+/// (see `dsp::gate`).
+#[derive(Params)]
+pub struct GateParams {
+    #[id = "enabled"]
+    pub enabled: BoolParam,
+    /// Length of one step.
+    #[id = "division"]
+    pub division: EnumParam<NoteDivision>,
+    /// How far a gated step drops: `1` is silence, `0` leaves it open.
+    #[id = "depth"]
+    pub depth: FloatParam,
+    /// Edge fade as a fraction of a step; `0` is a hard (but click-free) chop.
+    #[id = "smooth"]
+    pub smoothing: FloatParam,
+    /// Auto-pan depth: swings the output left and right, one side per step.
+    #[id = "pan"]
+    pub pan: FloatParam,
+    /// Param IDs get a `_1`, `_2`, ... suffix per step.
+    #[nested(array, group = "Step")]
+    pub steps: [GateStepParams; NUM_GATE_STEPS],
+}
+
+impl Default for GateParams {
+    fn default() -> Self {
+        // A classic trance chop, so switching the gate on does something musical.
+        const PATTERN: [bool; NUM_GATE_STEPS] = [
+            true, false, true, true, true, false, true, false, true, false, true, true, true,
+            false, true, false,
+        ];
+        Self {
+            enabled: BoolParam::new("Gate", false),
+            division: EnumParam::new("Gate Division", NoteDivision::Sixteenth),
+            depth: FloatParam::new("Gate Depth", 1.0, FloatRange::Linear { min: 0.0, max: 1.0 })
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_unit("%"),
+            smoothing: FloatParam::new(
+                "Gate Smoothing",
+                0.1,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_unit("%"),
+            pan: FloatParam::new("Auto-Pan", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 })
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_unit("%"),
+            steps: std::array::from_fn(|i| GateStepParams::new(i + 1, PATTERN[i])),
+        }
+    }
+}
+
+/// Arpeggiator: the held keys play one at a time, a step per note division,
+/// on this synth and/or out as MIDI (see `dsp::arp`).
+#[derive(Params)]
+pub struct ArpParams {
+    #[id = "enabled"]
+    pub enabled: BoolParam,
+    #[id = "mode"]
+    pub mode: EnumParam<ArpMode>,
+    /// Length of one step.
+    #[id = "division"]
+    pub division: EnumParam<NoteDivision>,
+    /// Octaves the pattern climbs through, the held keys' own first.
+    #[id = "octaves"]
+    pub octaves: IntParam,
+    /// Note length as a fraction of a step.
+    #[id = "gate"]
+    pub gate: FloatParam,
+    /// How late every second step plays, as a share of half a step: 0 is
+    /// straight, about 67% a triplet shuffle.
+    #[id = "swing"]
+    pub swing: FloatParam,
+    /// Each note plays up to this share of a step late, at random.
+    #[id = "timing_jitter"]
+    pub timing_jitter: FloatParam,
+    /// Each note's velocity moves up or down by up to this share, at random.
+    #[id = "velocity_jitter"]
+    pub velocity_jitter: FloatParam,
+    /// Seed for the humanize and Random mode's choices. 0 picks a new one
+    /// every time; any other value replays the same choices from the start of
+    /// playback, for renders that come out the same each time. Saved with the
+    /// project, not in presets.
+    #[id = "seed"]
+    pub seed: IntParam,
+    /// Send the arp's notes to the host as MIDI, to drive other instruments.
+    #[id = "midi_out"]
+    pub midi_out: BoolParam,
+    /// Play the arp's notes on this synth. Off (with MIDI out on) the synth
+    /// stays silent and only drives whatever the host routes its notes to.
+    #[id = "local"]
+    pub local: BoolParam,
+}
+
+impl Default for ArpParams {
+    fn default() -> Self {
+        Self {
+            enabled: BoolParam::new("Arp", false),
+            mode: EnumParam::new("Arp Mode", ArpMode::Up),
+            division: EnumParam::new("Arp Division", NoteDivision::Sixteenth),
+            octaves: IntParam::new("Arp Octaves", 1, IntRange::Linear { min: 1, max: 4 }),
+            gate: FloatParam::new("Arp Gate", 0.5, FloatRange::Linear { min: 0.05, max: 1.0 })
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_unit("%"),
+            swing: FloatParam::new("Arp Swing", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 })
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_unit("%"),
+            timing_jitter: FloatParam::new(
+                "Arp Timing Humanize",
+                0.0,
+                FloatRange::Linear { min: 0.0, max: 0.5 },
+            )
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_unit("%"),
+            velocity_jitter: FloatParam::new(
+                "Arp Velocity Humanize",
+                0.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_unit("%"),
+            seed: IntParam::new("Arp Seed", 0, IntRange::Linear { min: 0, max: 9999 })
+                .with_value_to_string(Arc::new(|value| match value {
+                    0 => "Random".to_string(),
+                    seed => seed.to_string(),
+                }))
+                .with_string_to_value(Arc::new(|string| match string.trim() {
+                    s if s.eq_ignore_ascii_case("random") => Some(0),
+                    s => s.parse().ok(),
+                })),
+            midi_out: BoolParam::new("Arp MIDI Out", false),
+            local: BoolParam::new("Arp Local", true),
+        }
+    }
+}
+
+/// Momentary "buttons" for hosts and controllers. Each fires on the
+/// off → on edge; see `actions.rs` for where the work actually happens.
+#[derive(Params)]
+pub struct TriggerParams {
+    #[id = "next_preset"]
+    pub next_preset: BoolParam,
+    #[id = "prev_preset"]
+    pub prev_preset: BoolParam,
+    #[id = "randomize"]
+    pub randomize: BoolParam,
+    /// Two or more taps set the tempo used when the host doesn't report one.
+    #[id = "tap_tempo"]
+    pub tap_tempo: BoolParam,
+}
+
+impl Default for TriggerParams {
+    fn default() -> Self {
+        Self {
+            next_preset: BoolParam::new("Next Preset", false),
+            prev_preset: BoolParam::new("Prev Preset", false),
+            randomize: BoolParam::new("Randomize", false),
+            tap_tempo: BoolParam::new("Tap Tempo", false),
+        }
+    }
+}
+
+/// One XY pad's position, each axis `0..1`. What the axes drive is assigned
+/// per project (see `xy.rs`); these are only the handles hosts automate.
+#[derive(Params)]
+pub struct XyPadParams {
+    #[id = "x"]
+    pub x: FloatParam,
+    #[id = "y"]
+    pub y: FloatParam,
+}
+
+impl XyPadParams {
+    /// Pad `number` (1-based, for the names).
+    pub fn new(number: usize) -> Self {
+        let axis = |name: &str| {
+            FloatParam::new(
+                format!("XY {number} {name}"),
+                0.5,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            // The audio thread moves the targets with it, every sample.
+            .with_smoother(SmoothingStyle::Linear(20.0))
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_unit("%")
+        };
+        Self {
+            x: axis("X"),
+            y: axis("Y"),
         }
     }
 }
@@ -297,15 +1621,42 @@ pub struct SineParams {
     #[persist = "editor-state"]
     pub editor_state: Arc<ViziaState>,
 
+    /// The keys the assistant's session, the locks, the editor layout and the
+    /// patch metadata were saved under before `project_state` held them. Only
+    /// read, once, by `ProjectState::migrate`; empty after that.
+    #[persist = "ai-session"]
+    pub legacy_ai_session: RwLock<Option<AiSession>>,
+    #[persist = "param-locks"]
+    pub legacy_locks: RwLock<Option<ParamLocks>>,
+    #[persist = "editor-layout"]
+    pub legacy_editor_layout: RwLock<Option<EditorLayout>>,
+    #[persist = "patch-metadata"]
+    pub legacy_metadata: RwLock<Option<PatchMetadata>>,
+
+    /// Every non-param state, one section per subsystem (see `persist.rs`).
+    /// Saved with the project, not in presets.
+    #[persist = "project-state"]
+    pub project_state: RwLock<ProjectState>,
+    /// The XY pads' assignments from `project_state`, for the audio thread.
+    pub xy_routes: XyRoutes,
+
     #[nested(id_prefix = "osc1", group = "Oscillator 1")]
     pub osc1: OscillatorParams,
     #[nested(id_prefix = "osc2", group = "Oscillator 2")]
     pub osc2: OscillatorParams,
     #[nested(id_prefix = "osc3", group = "Oscillator 3")]
     pub osc3: OscillatorParams,
+    #[nested(id_prefix = "osc1_additive", group = "Oscillator 1 Additive")]
+    pub additive: AdditiveParams,
 
     #[nested(id_prefix = "filter", group = "Filter")]
     pub filter: FilterParams,
+    #[nested(id_prefix = "comb", group = "Comb")]
+    pub comb: CombParams,
+    #[nested(id_prefix = "noise", group = "Noise")]
+    pub noise: NoiseParams,
+    #[nested(id_prefix = "sub", group = "Sub")]
+    pub sub: SubParams,
 
     #[nested(group = "Envelope")]
     pub adsr: AdsrParams,
@@ -315,20 +1666,207 @@ pub struct SineParams {
     /// Its depth/direction is set by [`FilterParams::env_amount`].
     #[nested(id_prefix = "fenv", group = "Filter Envelope")]
     pub filter_env: AdsrParams,
+
+    #[nested(id_prefix = "glide", group = "Glide")]
+    pub glide: GlideParams,
+    #[nested(id_prefix = "bend", group = "Pitch Bend")]
+    pub bend: BendParams,
+
+    #[nested(id_prefix = "mod", group = "Modulation")]
+    pub modulation: ModParams,
+
+    #[nested(id_prefix = "trigger", group = "Triggers")]
+    pub triggers: TriggerParams,
+
+    #[nested(id_prefix = "gate", group = "Trance Gate")]
+    pub gate: GateParams,
+
+    #[nested(id_prefix = "arp", group = "Arpeggiator")]
+    pub arp: ArpParams,
+
+    #[nested(id_prefix = "color", group = "Color")]
+    pub color: ColorParams,
+
+    #[nested(id_prefix = "xy1", group = "XY Pad 1")]
+    pub xy1: XyPadParams,
+    #[nested(id_prefix = "xy2", group = "XY Pad 2")]
+    pub xy2: XyPadParams,
+
+    /// Analog drift: slow per-voice pitch/level wander and a per-note cutoff
+    /// offset. `0` is perfectly stable.
+    #[id = "analog"]
+    pub analog: FloatParam,
+
+    /// How much release velocity shortens (fast lifts) or stretches (slow
+    /// lifts) both envelopes' release times.
+    #[id = "release_vel"]
+    pub release_velocity: FloatParam,
+
+    /// Slew-limit the envelopes so 1 ms attacks/releases and retriggers don't
+    /// click.
+    #[id = "declick"]
+    pub declick: BoolParam,
+
+    /// Pans each voice by its note: low notes left, high notes right.
+    #[id = "keyboard_pan"]
+    pub keyboard_pan: FloatParam,
+
+    /// Blend of the main audio input (run through the filter, see
+    /// `dsp::through`) against the oscillators. `0` is a plain synth, `1` a
+    /// filter box. Only does anything when the host connects an input.
+    #[id = "input_mix"]
+    pub input_mix: FloatParam,
+
+    /// Master stereo width, `0` (mono) to `2` (200%); see `dsp::widener`.
+    #[id = "stereo_width"]
+    pub stereo_width: FloatParam,
+    /// Below this the width is capped at 100%, keeping the bass mono-safe.
+    #[id = "width_crossover"]
+    pub width_crossover: FloatParam,
+
+    /// Global parameter smoothing time.
+    #[id = "smoothing"]
+    pub smoothing: EnumParam<Smoothing>,
+
+    /// Thin out unison voices while the DSP load is too high for the host to
+    /// keep up (see `quality.rs`).
+    #[id = "auto_quality"]
+    pub auto_quality: BoolParam,
+
+    /// PolyBLEP-correct the square and saw oscillators so high notes don't
+    /// alias; off plays the naive waveforms, for comparison.
+    #[id = "band_limited"]
+    pub band_limited: BoolParam,
+
+    /// Load presets on MIDI program changes (see `ai::bank::program`).
+    #[id = "program_change"]
+    pub program_change: BoolParam,
+
+    /// Released keys keep sounding until played again (see `dsp::latch`).
+    #[id = "latch"]
+    pub latch: BoolParam,
+
+    /// Semitones added to every played note.
+    #[id = "master_transpose"]
+    pub master_transpose: IntParam,
+    /// A4 reference frequency.
+    #[id = "master_tune"]
+    pub master_tune: FloatParam,
+
+    /// Played keys, the arp's notes and the assistant's `play_notes` snap to
+    /// this key and scale (see `dsp::scale`).
+    #[id = "scale_root"]
+    pub scale_root: EnumParam<ScaleRoot>,
+    #[id = "scale"]
+    pub scale: EnumParam<Scale>,
 }
 
 impl Default for SineParams {
     fn default() -> Self {
         Self {
             editor_state: crate::ui::editor::default_state(),
+            legacy_ai_session: RwLock::new(None),
+            legacy_locks: RwLock::new(None),
+            legacy_editor_layout: RwLock::new(None),
+            legacy_metadata: RwLock::new(None),
+            project_state: RwLock::new(ProjectState::default()),
+            xy_routes: XyRoutes::default(),
 
             osc1: OscillatorParams::new(Waveform::Sine, 440.0, -6.0, 0),
             osc2: OscillatorParams::new(Waveform::Sawtooth, 880.0, -12.0, -1),
             osc3: OscillatorParams::new(Waveform::Square, 220.0, -18.0, 1),
+            additive: AdditiveParams::default(),
 
             filter: FilterParams::default(),
+            comb: CombParams::default(),
+            noise: NoiseParams::default(),
+            sub: SubParams::default(),
             adsr: AdsrParams::default(),
             filter_env: AdsrParams::default(),
+            glide: GlideParams::default(),
+            bend: BendParams::default(),
+            modulation: ModParams::default(),
+            triggers: TriggerParams::default(),
+            gate: GateParams::default(),
+            arp: ArpParams::default(),
+            color: ColorParams::default(),
+            xy1: XyPadParams::new(1),
+            xy2: XyPadParams::new(2),
+
+            analog: FloatParam::new("Analog", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 })
+                .with_smoother(SmoothingStyle::Linear(50.0))
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_unit("%"),
+
+            release_velocity: FloatParam::new(
+                "Release Velocity",
+                0.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_unit("%"),
+
+            declick: BoolParam::new("Declick", true),
+
+            input_mix: FloatParam::new(
+                "Input Mix",
+                0.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_unit("%"),
+
+            stereo_width: FloatParam::new(
+                "Stereo Width",
+                1.0,
+                FloatRange::Linear { min: 0.0, max: 2.0 },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_unit("%"),
+            width_crossover: FloatParam::new(
+                "Width Crossover",
+                150.0,
+                FloatRange::Skewed {
+                    min: 20.0,
+                    max: 1000.0,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
+            .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+
+            smoothing: EnumParam::new("Smoothing", Smoothing::Slow),
+            auto_quality: BoolParam::new("Auto Quality", false),
+            band_limited: BoolParam::new("Band-Limited", true),
+            program_change: BoolParam::new("Program Change", true),
+            latch: BoolParam::new("Latch", false),
+
+            keyboard_pan: FloatParam::new(
+                "Keyboard Pan",
+                0.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_unit("%"),
+
+            master_transpose: IntParam::new("Transpose", 0, IntRange::Linear { min: -24, max: 24 })
+                .with_unit(" st"),
+            master_tune: FloatParam::new(
+                "Master Tune",
+                440.0,
+                FloatRange::Linear {
+                    min: 415.0,
+                    max: 465.0,
+                },
+            )
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+
+            scale_root: EnumParam::new("Key", ScaleRoot::C),
+            scale: EnumParam::new("Scale", Scale::Chromatic),
         }
     }
 }
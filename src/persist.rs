@@ -0,0 +1,133 @@
+//! Non-parameter state saved with the project, in one versioned container
+//! (see `SineParams::project_state`), so every subsystem that has something
+//! to keep that isn't a param persists the same way: as a section here.
+//!
+//! A new section is a `#[serde(default)]` field with a `// vN` comment, and
+//! [`VERSION`] goes up by one, as with presets: projects saved before it load
+//! with the section empty, and a project from a newer build loads the sections
+//! this one knows. Nothing here goes into presets.
+//!
+//! Mod matrix routings are params and persist as such. The state that came
+//! before this container (the assistant's session, the locks, the editor
+//! layout and the patch metadata) had a key each; projects saved with those
+//! still load, and [`ProjectState::migrate`] moves them into their sections.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ai::preset::PatchMetadata;
+use crate::ai::session::AiSession;
+use crate::locks::ParamLocks;
+use crate::ui::layout::EditorLayout;
+use crate::xy::NUM_XY_PADS;
+use crate::SineParams;
+
+/// Bumped with each new section.
+pub const VERSION: u32 = 3;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectState {
+    /// The [`VERSION`] that last saved it; `0` before anything was saved.
+    #[serde(default)]
+    pub version: u32,
+    /// The assistant chat, oldest first, so a reopened project shows the
+    /// conversation it was left with.
+    #[serde(default)]
+    pub chat: Vec<ChatLine>, // v1
+    /// What each XY pad's axes drive (see `xy.rs`).
+    #[serde(default)]
+    pub xy_pads: [XyPad; NUM_XY_PADS], // v2
+    /// The assistant's per-project settings (see `ai/session.rs`).
+    #[serde(default)]
+    pub ai_session: AiSession, // v3
+    /// Sections kept as they are through preset loads and randomize (see
+    /// `locks.rs`).
+    #[serde(default)]
+    pub locks: ParamLocks, // v3
+    /// Collapsed oscillator cards and compact mode, per instance.
+    #[serde(default)]
+    pub editor_layout: EditorLayout, // v3
+    /// Author, description and category of the patch. Presets carry their
+    /// own copy.
+    #[serde(default)]
+    pub metadata: PatchMetadata, // v3
+}
+
+/// A chat message, by the role names transcripts use.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatLine {
+    pub role: String,
+    pub text: String,
+}
+
+/// An XY pad's assignments; an unassigned axis drives nothing.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct XyPad {
+    pub x: Option<XyAxis>,
+    pub y: Option<XyAxis>,
+}
+
+/// The param an XY pad axis drives, by its host ID, and how far the axis
+/// moves it off its own value: normalized offsets, `min` at `0` and `max` at
+/// `1`, each within `-1..=1` (`min` may be the larger, to turn the axis
+/// round).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct XyAxis {
+    pub param: String,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl ProjectState {
+    /// The project's state.
+    pub fn current(p: &SineParams) -> Self {
+        let state = p.project_state.read().map(|s| s.clone()).unwrap_or_default();
+        if state.version > VERSION {
+            tracing::warn!(
+                target: "state",
+                "project state is version {} but this build knows up to {VERSION}; \
+                 newer sections are dropped when it saves",
+                state.version
+            );
+        }
+        state
+    }
+
+    /// Move what a project saved under the keys that came before this
+    /// container into its sections, and clear those keys. Runs after the host
+    /// restores a project; a project without them is left as it is.
+    pub fn migrate(p: &SineParams) {
+        fn take<T>(key: &std::sync::RwLock<Option<T>>) -> Option<T> {
+            key.write().ok().and_then(|mut value| value.take())
+        }
+        let session = take(&p.legacy_ai_session);
+        let locks = take(&p.legacy_locks);
+        let layout = take(&p.legacy_editor_layout);
+        let metadata = take(&p.legacy_metadata);
+        if session.is_none() && locks.is_none() && layout.is_none() && metadata.is_none() {
+            return;
+        }
+        Self::update(p, |state| {
+            if let Some(session) = session {
+                state.ai_session = session;
+            }
+            if let Some(locks) = locks {
+                state.locks = locks;
+            }
+            if let Some(layout) = layout {
+                state.editor_layout = layout;
+            }
+            if let Some(metadata) = metadata {
+                state.metadata = metadata;
+            }
+        });
+        tracing::info!(target: "state", "moved the project's older state into its sections");
+    }
+
+    /// Change the project's state; it's saved as this build's [`VERSION`].
+    pub fn update(p: &SineParams, change: impl FnOnce(&mut Self)) {
+        if let Ok(mut state) = p.project_state.write() {
+            change(&mut state);
+            state.version = VERSION;
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Auto quality: under heavy DSP load, caps every oscillator's unison voices
+//! so a huge chord thins out instead of the host's audio dropping out.
+//!
+//! The load is the time `process` took over the length of audio it made,
+//! smoothed over a few blocks. Above [`HIGH_LOAD`] the cap halves (8 → 4 → 2 →
+//! 1), at most once per [`STEP_DOWN_SECONDS`] so each step gets measured
+//! before the next. It only doubles back once the load has stayed under
+//! [`LOW_LOAD`] for [`STEP_UP_SECONDS`]: doubling the unison at most doubles
+//! the load, so stepping up from there can't push it straight back over, and
+//! the cap doesn't flap around the threshold. Unison is the only stage with a
+//! quality to trade; there is no oversampling to switch off.
+//!
+//! Called once per block from `process`: arithmetic only.
+
+/// The unison param's maximum: no cap.
+pub const MAX_UNISON: usize = 8;
+/// Share of the block's length spent in `process` above which the cap drops.
+const HIGH_LOAD: f32 = 0.7;
+/// Below this the cap may come back up.
+const LOW_LOAD: f32 = HIGH_LOAD / 2.0;
+const STEP_DOWN_SECONDS: f32 = 0.1;
+const STEP_UP_SECONDS: f32 = 2.0;
+/// Time constant of the load's smoothing, so one slow block (a page fault,
+/// the host preempting us) doesn't cost quality.
+const LOAD_SECONDS: f32 = 0.05;
+
+pub struct AutoQuality {
+    /// Smoothed load, `0..` (over `1` is a dropout).
+    load: f32,
+    /// Most unison voices an oscillator may play.
+    cap: usize,
+    /// Seconds since the cap last changed.
+    since_change: f32,
+    /// Seconds the load has been under [`LOW_LOAD`].
+    calm: f32,
+}
+
+impl Default for AutoQuality {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AutoQuality {
+    pub fn new() -> Self {
+        Self {
+            load: 0.0,
+            cap: MAX_UNISON,
+            since_change: 0.0,
+            calm: 0.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Takes in one block: `busy` seconds of work for `block` seconds of
+    /// audio. With auto quality off the load is still measured, for the
+    /// diagnostics, but the cap goes straight back to full.
+    pub fn update(&mut self, enabled: bool, busy: f32, block: f32) {
+        if block <= 0.0 {
+            return;
+        }
+        let coef = 1.0 - (-block / LOAD_SECONDS).exp();
+        self.load += coef * (busy / block - self.load);
+        if !enabled {
+            self.cap = MAX_UNISON;
+            self.since_change = 0.0;
+            self.calm = 0.0;
+            return;
+        }
+
+        self.since_change += block;
+        self.calm = if self.load < LOW_LOAD {
+            self.calm + block
+        } else {
+            0.0
+        };
+        if self.load > HIGH_LOAD && self.cap > 1 && self.since_change >= STEP_DOWN_SECONDS {
+            self.cap /= 2;
+            self.since_change = 0.0;
+        } else if self.calm >= STEP_UP_SECONDS && self.cap < MAX_UNISON {
+            self.cap *= 2;
+            self.since_change = 0.0;
+            self.calm = 0.0;
+        }
+    }
+
+    pub fn load(&self) -> f32 {
+        self.load
+    }
+
+    /// Most unison voices an oscillator may play this block.
+    pub fn unison_cap(&self) -> usize {
+        self.cap
+    }
+}
@@ -0,0 +1,325 @@
+//! Parameter ramps: timed sweeps ("open the filter over 4 bars") that the AI
+//! tools start and the audio thread plays back sample-accurately.
+//!
+//! Params can only be written from the GUI side, so a ramp runs in two places
+//! at once. [`RampRunner`] on the audio thread overrides the param's value
+//! every sample from the moment it picks the ramp up until the ramp has
+//! finished and been handed back. Meanwhile the editor mirrors the ramp's
+//! progress into the param on its poll timer, inside one Begin/End gesture, so
+//! the knob follows along and a recording host captures the sweep as
+//! automation. When the ramp ends the target value is written and the gesture
+//! closed, and once it has the audio thread hands the param back to its
+//! smoother. With the editor closed the audio thread has the plugin's task
+//! executor write it instead, through the host context the editor left (see
+//! `actions::HostLink`); until something has, the ramp holds its target.
+//!
+//! Only continuous params that are read per sample can be ramped; see
+//! [`RampTarget`].
+
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU32, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use nih_plug::prelude::{FloatParam, Param};
+use vizia_plug::widgets::RawParamEvent;
+
+use crate::SineParams;
+
+pub const NUM_RAMP_TARGETS: usize = 9;
+
+/// A param a ramp can drive. Names match `set_parameter`'s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RampTarget {
+    FilterCutoff,
+    FilterResonance,
+    FilterDrive,
+    FilterEnvAmount,
+    Gain1,
+    Gain2,
+    Gain3,
+    Analog,
+    KeyboardPan,
+}
+
+impl RampTarget {
+    pub const ALL: [RampTarget; NUM_RAMP_TARGETS] = [
+        Self::FilterCutoff,
+        Self::FilterResonance,
+        Self::FilterDrive,
+        Self::FilterEnvAmount,
+        Self::Gain1,
+        Self::Gain2,
+        Self::Gain3,
+        Self::Analog,
+        Self::KeyboardPan,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::FilterCutoff => "filter_cutoff",
+            Self::FilterResonance => "filter_resonance",
+            Self::FilterDrive => "filter_drive",
+            Self::FilterEnvAmount => "filter_env_amount",
+            Self::Gain1 => "gain1",
+            Self::Gain2 => "gain2",
+            Self::Gain3 => "gain3",
+            Self::Analog => "analog",
+            Self::KeyboardPan => "keyboard_pan",
+        }
+    }
+
+    pub fn param(self, p: &SineParams) -> &FloatParam {
+        match self {
+            Self::FilterCutoff => &p.filter.cutoff,
+            Self::FilterResonance => &p.filter.resonance,
+            Self::FilterDrive => &p.filter.drive,
+            Self::FilterEnvAmount => &p.filter.env_amount,
+            Self::Gain1 => &p.osc1.gain,
+            Self::Gain2 => &p.osc2.gain,
+            Self::Gain3 => &p.osc3.gain,
+            Self::Analog => &p.analog,
+            Self::KeyboardPan => &p.keyboard_pan,
+        }
+    }
+}
+
+/// How long a ramp takes. Beats resolve against the tempo when the audio
+/// thread picks the ramp up.
+#[derive(Clone, Copy, Debug)]
+pub enum RampDuration {
+    Seconds(f32),
+    Beats(f32),
+}
+
+// Slot states. Tools arm, the audio thread runs and finishes, whoever writes
+// the target (the editor, or with it closed the task executor) acknowledges
+// back to idle.
+const IDLE: u8 = 0;
+const ARMED: u8 = 1;
+const RUNNING: u8 = 2;
+const FINISHED: u8 = 3;
+
+struct RampSlot {
+    state: AtomicU8,
+    /// `f32` bits of the plain value to ramp to.
+    target: AtomicU32,
+    /// `f32` bits of the duration, in beats if `in_beats`, else seconds.
+    duration: AtomicU32,
+    in_beats: AtomicBool,
+    /// `f32` bits of the plain value the audio thread last played.
+    current: AtomicU32,
+}
+
+impl Default for RampSlot {
+    fn default() -> Self {
+        Self {
+            state: AtomicU8::new(IDLE),
+            target: AtomicU32::new(0),
+            duration: AtomicU32::new(0),
+            in_beats: AtomicBool::new(false),
+            current: AtomicU32::new(0),
+        }
+    }
+}
+
+/// Lock-free mailbox between whoever starts ramps (the AI tools), the audio
+/// thread that plays them, and the editor that mirrors them into the params.
+#[derive(Default)]
+pub struct ParamRamps {
+    slots: [RampSlot; NUM_RAMP_TARGETS],
+    /// Set when a ramp finishes, until the audio thread has asked for its
+    /// target to be written.
+    finished: AtomicBool,
+    /// Which ramped params have an open automation gesture. GUI side only.
+    gestures: Mutex<[bool; NUM_RAMP_TARGETS]>,
+}
+
+impl ParamRamps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ramp `target` to the plain value `to`, starting from wherever it is
+    /// now. Replaces any ramp already running on it.
+    pub fn start(&self, target: RampTarget, to: f32, duration: RampDuration) {
+        let slot = &self.slots[target as usize];
+        let (length, in_beats) = match duration {
+            RampDuration::Seconds(seconds) => (seconds, false),
+            RampDuration::Beats(beats) => (beats, true),
+        };
+        slot.target.store(to.to_bits(), Ordering::Relaxed);
+        slot.duration.store(length.to_bits(), Ordering::Relaxed);
+        slot.in_beats.store(in_beats, Ordering::Relaxed);
+        slot.state.store(ARMED, Ordering::Release);
+    }
+
+    /// Whether a ramp finished since the last call, i.e. whether its target
+    /// still needs writing. RT-safe.
+    pub fn take_finished(&self) -> bool {
+        self.finished.swap(false, Ordering::Relaxed)
+    }
+
+    /// Mirror every ramp's progress into its param and hand finished ramps
+    /// back. Called from the editor's poll timer, and with the editor closed
+    /// from the task executor.
+    pub fn sync_params(&self, p: &SineParams, emit: &mut impl FnMut(RawParamEvent)) {
+        let mut gestures = self.gestures.lock().unwrap_or_else(PoisonError::into_inner);
+        for target in RampTarget::ALL {
+            let i = target as usize;
+            let slot = &self.slots[i];
+            let param = target.param(p);
+            let ptr = param.as_ptr();
+            let state = slot.state.load(Ordering::Acquire);
+            if matches!(state, RUNNING | FINISHED) && !gestures[i] {
+                emit(RawParamEvent::BeginSetParameter(ptr));
+                gestures[i] = true;
+            }
+            match state {
+                RUNNING => {
+                    let current = f32::from_bits(slot.current.load(Ordering::Relaxed));
+                    emit(RawParamEvent::SetParameterNormalized(
+                        ptr,
+                        param.preview_normalized(current),
+                    ));
+                }
+                FINISHED => {
+                    let to = f32::from_bits(slot.target.load(Ordering::Relaxed));
+                    emit(RawParamEvent::SetParameterNormalized(
+                        ptr,
+                        param.preview_normalized(to),
+                    ));
+                    emit(RawParamEvent::EndSetParameter(ptr));
+                    gestures[i] = false;
+                    // A new ramp may have been armed meanwhile; leave it be.
+                    let _ = slot.state.compare_exchange(
+                        FINISHED,
+                        IDLE,
+                        Ordering::AcqRel,
+                        Ordering::Relaxed,
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct ActiveRamp {
+    /// Normalized start and end, so skewed params (cutoff) sweep evenly.
+    from: f32,
+    to: f32,
+    total: u32,
+    elapsed: u32,
+}
+
+impl ActiveRamp {
+    fn normalized(&self) -> f32 {
+        let progress = (self.elapsed as f32 / self.total as f32).min(1.0);
+        self.from + (self.to - self.from) * progress
+    }
+}
+
+/// Each ramp target's override for one sample; `None` means use the param.
+pub type RampValues = [Option<f32>; NUM_RAMP_TARGETS];
+
+/// Audio-thread side of [`ParamRamps`]. Allocation-free.
+pub struct RampRunner {
+    ramps: [Option<ActiveRamp>; NUM_RAMP_TARGETS],
+}
+
+impl Default for RampRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RampRunner {
+    pub fn new() -> Self {
+        Self {
+            ramps: [None; NUM_RAMP_TARGETS],
+        }
+    }
+
+    /// Once per block: picks up newly armed ramps and lets go of finished ones
+    /// once their target has been written and they were handed back.
+    pub fn begin_block(&mut self, shared: &ParamRamps, p: &SineParams, sample_rate: f32, bpm: f32) {
+        for target in RampTarget::ALL {
+            let i = target as usize;
+            let slot = &shared.slots[i];
+            let armed = slot
+                .state
+                .compare_exchange(ARMED, RUNNING, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok();
+            if armed {
+                let param = target.param(p);
+                let to = f32::from_bits(slot.target.load(Ordering::Relaxed));
+                let length = f32::from_bits(slot.duration.load(Ordering::Relaxed));
+                let seconds = if slot.in_beats.load(Ordering::Relaxed) {
+                    length * 60.0 / bpm
+                } else {
+                    length
+                };
+                // Start from whatever is sounding, even mid-ramp.
+                let from = match self.ramps[i] {
+                    Some(ramp) => ramp.normalized(),
+                    None => param.preview_normalized(param.smoothed.previous_value()),
+                };
+                // So the editor never mirrors a stale value before `end_block`.
+                let start = param.preview_plain(from);
+                slot.current.store(start.to_bits(), Ordering::Relaxed);
+                self.ramps[i] = Some(ActiveRamp {
+                    from,
+                    to: param.preview_normalized(to),
+                    total: ((seconds * sample_rate) as u32).max(1),
+                    elapsed: 0,
+                });
+            } else if slot.state.load(Ordering::Acquire) == IDLE {
+                self.ramps[i] = None;
+            }
+        }
+    }
+
+    /// Advances every ramp one sample and returns the overrides.
+    pub fn next(&mut self, shared: &ParamRamps, p: &SineParams) -> RampValues {
+        std::array::from_fn(|i| {
+            let ramp = self.ramps[i].as_mut()?;
+            let target = RampTarget::ALL[i];
+            if ramp.elapsed < ramp.total {
+                ramp.elapsed += 1;
+                if ramp.elapsed == ramp.total {
+                    let finished = shared.slots[i].state.compare_exchange(
+                        RUNNING,
+                        FINISHED,
+                        Ordering::AcqRel,
+                        Ordering::Relaxed,
+                    );
+                    if finished.is_ok() {
+                        shared.finished.store(true, Ordering::Relaxed);
+                    }
+                }
+            }
+            Some(target.param(p).preview_plain(ramp.normalized()))
+        })
+    }
+
+    /// Once per block: publishes where each ramp is for the editor to mirror.
+    pub fn end_block(&self, shared: &ParamRamps, p: &SineParams) {
+        for (i, ramp) in self.ramps.iter().enumerate() {
+            if let Some(ramp) = ramp {
+                let value = RampTarget::ALL[i].param(p).preview_plain(ramp.normalized());
+                shared.slots[i]
+                    .current
+                    .store(value.to_bits(), Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Jumps every ramp to its end, e.g. when the host resets playback.
+    pub fn finish_all(&mut self) {
+        for ramp in self.ramps.iter_mut().flatten() {
+            if ramp.elapsed < ramp.total {
+                ramp.elapsed = ramp.total - 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Crash recovery for the working patch. Every [`AUTOSAVE_SECONDS`] of audio
+//! the plugin snapshots its patch to `recovery/<instance>.json`, on a
+//! background task so the audio thread does no IO; the file goes when the
+//! plugin is deactivated or unloaded. A file still there once it's older than
+//! [`STALE_SECONDS`] was left by an instance that never got that far, so the
+//! host went down with it: the editor offers it back in a banner, and the
+//! assistant through `get_recovery` / `restore_recovery`.
+//!
+//! An instance that's loaded but not processing refreshes nothing, so it can
+//! look stale to another one; restoring its patch is harmless, and it writes a
+//! new file once it runs again.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ai::preset::{app_dir, PresetData};
+use crate::SineParams;
+
+/// Audio time between snapshots.
+pub const AUTOSAVE_SECONDS: f32 = 30.0;
+/// Age past which a recovery file is a leftover rather than a live instance's.
+const STALE_SECONDS: u64 = 120;
+/// Bumped when the file layout changes incompatibly.
+const RECOVERY_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct RecoveryFile {
+    version: u32,
+    /// Unix seconds.
+    saved_at: u64,
+    patch: PresetData,
+}
+
+pub fn recovery_dir() -> PathBuf {
+    app_dir().join("recovery")
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// This instance's recovery file. Shared by the plugin and its background
+/// task; the file goes with the last of them.
+pub struct Recovery {
+    path: PathBuf,
+    /// Whether the file may be on disk.
+    written: AtomicBool,
+}
+
+impl Default for Recovery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Recovery {
+    /// Named after the process and the time, so every instance gets its own.
+    pub fn new() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let stem = format!("{}-{nanos}", std::process::id());
+        Self {
+            path: recovery_dir().join(format!("{stem}.json")),
+            written: AtomicBool::new(false),
+        }
+    }
+
+    /// Write the current patch to the recovery file. Background thread.
+    pub fn snapshot(&self, p: &SineParams) {
+        let file = RecoveryFile {
+            version: RECOVERY_VERSION,
+            saved_at: now(),
+            patch: PresetData::capture(p),
+        };
+        let written = serde_json::to_string(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                std::fs::create_dir_all(recovery_dir()).map_err(|e| e.to_string())?;
+                std::fs::write(&self.path, json).map_err(|e| e.to_string())
+            });
+        match written {
+            Ok(()) => self.written.store(true, Ordering::Relaxed),
+            Err(e) => tracing::warn!(target: "state", "couldn't write the recovery file: {e}"),
+        }
+    }
+
+    /// Remove the recovery file: there's nothing left to lose.
+    pub fn discard(&self) {
+        if self.written.swap(false, Ordering::Relaxed) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+impl Drop for Recovery {
+    fn drop(&mut self) {
+        self.discard();
+    }
+}
+
+/// A patch an unclean shutdown left behind.
+#[derive(Clone, Debug)]
+pub struct Leftover {
+    /// The file's stem, which `restore_recovery` takes.
+    pub id: String,
+    /// Unix seconds.
+    pub saved_at: u64,
+    pub patch: PresetData,
+}
+
+impl Leftover {
+    /// How long ago it was saved, e.g. "5 min ago".
+    pub fn age(&self) -> String {
+        let secs = now().saturating_sub(self.saved_at);
+        match secs {
+            0..60 => "just now".to_string(),
+            60..3600 => format!("{} min ago", secs / 60),
+            3600..86_400 => format!("{} h ago", secs / 3600),
+            _ => format!("{} days ago", secs / 86_400),
+        }
+    }
+
+    /// Remove its file, once it's been restored or turned down.
+    pub fn discard(&self) {
+        let _ = std::fs::remove_file(recovery_dir().join(format!("{}.json", self.id)));
+    }
+}
+
+/// Patches left behind by unclean shutdowns, newest first.
+pub fn leftovers() -> Vec<Leftover> {
+    let Ok(entries) = std::fs::read_dir(recovery_dir()) else {
+        return Vec::new();
+    };
+    let stale = Duration::from_secs(STALE_SECONDS);
+    let mut found: Vec<Leftover> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let path = e.path();
+            if path.extension().and_then(|x| x.to_str()) != Some("json") {
+                return None;
+            }
+            let modified = e.metadata().and_then(|m| m.modified()).ok()?;
+            if modified.elapsed().unwrap_or_default() < stale {
+                return None;
+            }
+            let id = path.file_stem()?.to_string_lossy().to_string();
+            let text = std::fs::read_to_string(&path).ok()?;
+            let file: RecoveryFile = serde_json::from_str(&text).ok()?;
+            (file.version <= RECOVERY_VERSION).then(|| Leftover {
+                id,
+                saved_at: file.saved_at,
+                patch: file.patch,
+            })
+        })
+        .collect();
+    found.sort_by(|a, b| b.saved_at.cmp(&a.saved_at));
+    found
+}
@@ -0,0 +1,161 @@
+//! Offline rendering: plays a patch through its own voices into a buffer, off
+//! the audio thread, for anything that wants to hear a patch without loading
+//! it: the editor's A/B spectra, the benchmarks and the golden-audio tests.
+//! [`trace`] runs a mod probe (see `dsp::probe`) the same way, for plotting a
+//! modulator's shape without the audio thread.
+//!
+//! A patch is a set of normalized values by param ([`PatchValues`]), read
+//! through [`ParamRead::Fixed`], so a render never moves the live params or
+//! their smoothers. Params the patch doesn't set keep their live values. The
+//! output is the voices' mix through the main soft clip; the input, trance gate
+//! and width stages are left out, as is the sidechain.
+
+use std::collections::HashMap;
+
+use nih_plug::prelude::ParamPtr;
+use vizia_plug::widgets::RawParamEvent;
+
+use crate::ai::preset::PresetData;
+use crate::dsp::probe::TRACE_POINTS;
+use crate::dsp::{FrameParams, Lfo, ModProbe, ParamRead, ProbeTarget, Tuning, Voice};
+use crate::ramps::NUM_RAMP_TARGETS;
+use crate::{SineParams, Waveform};
+
+/// Normalized values by param.
+pub type PatchValues = HashMap<ParamPtr, f32>;
+
+/// One note of a render, timed in seconds from its start.
+#[derive(Clone, Copy, Debug)]
+pub struct RenderNote {
+    pub note: u8,
+    pub velocity: f32,
+    pub start: f32,
+    /// Seconds until the note's release.
+    pub length: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct RenderSpec {
+    pub notes: Vec<RenderNote>,
+    /// Length of the render; notes still sounding are cut off.
+    pub seconds: f32,
+    pub sample_rate: f32,
+    /// Tempo for synced envelope stages and a synced LFO.
+    pub bpm: f32,
+}
+
+impl RenderSpec {
+    /// `note` held for a second, then half a second of its release.
+    pub fn single_note(note: u8) -> Self {
+        Self {
+            notes: vec![RenderNote {
+                note,
+                velocity: 0.8,
+                start: 0.0,
+                length: 1.0,
+            }],
+            seconds: 1.5,
+            sample_rate: 48_000.0,
+            bpm: 120.0,
+        }
+    }
+}
+
+/// The values loading `preset` would give the params. Locked groups (see
+/// `locks.rs`) are left out, as a load would leave them.
+pub fn patch_values(p: &SineParams, preset: &PresetData) -> PatchValues {
+    let mut values = PatchValues::new();
+    preset.apply(p, &mut |event| {
+        if let RawParamEvent::SetParameterNormalized(ptr, value) = event {
+            values.insert(ptr, value);
+        }
+    });
+    values
+}
+
+/// Render `spec` with the patch `values`, as stereo frames.
+pub fn render(p: &SineParams, values: &PatchValues, spec: &RenderSpec) -> Vec<[f32; 2]> {
+    let read = ParamRead::Fixed(values);
+    let sample_rate = spec.sample_rate;
+    let unison = [&p.osc1, &p.osc2, &p.osc3].map(|osc| read.plain(&osc.unison_voices) as usize);
+    // One voice per note, so nothing is stolen.
+    let mut voices: Vec<Voice> = (0..spec.notes.len())
+        .map(|index| {
+            let mut voice = Voice::new(sample_rate, index as u32);
+            voice.set_unison_voices(unison);
+            voice
+        })
+        .collect();
+    let tuning = Tuning {
+        transpose: 0.0,
+        reference_hz: 440.0,
+    };
+    let to_sample = |seconds: f32| (seconds * sample_rate).round() as usize;
+
+    let (lfo_shape, lfo_rate) = lfo_settings(p, read, spec.bpm);
+    let mut lfo = Lfo::new();
+    let ramps = [None; NUM_RAMP_TARGETS];
+
+    (0..to_sample(spec.seconds))
+        .map(|index| {
+            for (voice, note) in voices.iter_mut().zip(&spec.notes) {
+                if index == to_sample(note.start) {
+                    voice.note_on(note.note, note.velocity, None, tuning);
+                } else if index == to_sample(note.start + note.length) {
+                    voice.note_off();
+                }
+            }
+            let lfo = lfo.next(lfo_shape, lfo_rate, sample_rate);
+            let frame = FrameParams::next(p, read, lfo, 0.0, spec.bpm, &ramps);
+            let (mut left, mut right) = (0.0, 0.0);
+            for voice in voices.iter_mut().filter(|v| v.is_active()) {
+                let sample = voice.render(&frame, sample_rate);
+                let (gain_l, gain_r) = voice.pan_gains(&frame);
+                left += sample * gain_l;
+                right += sample * gain_r;
+            }
+            [left.tanh() * 0.5, right.tanh() * 0.5]
+        })
+        .collect()
+}
+
+/// The LFO's shape and rate in Hz, synced to `bpm` if it is set to.
+fn lfo_settings(p: &SineParams, read: ParamRead, bpm: f32) -> (Waveform, f32) {
+    let modulation = &p.modulation;
+    let rate = if read.plain(&modulation.lfo_sync) {
+        bpm / 60.0 / read.plain(&modulation.lfo_division).beats()
+    } else {
+        read.plain(&modulation.lfo_rate)
+    };
+    (read.plain(&modulation.lfo_shape), rate)
+}
+
+/// Trace `target` with the patch `values`, as the mod scope would draw it:
+/// [`TRACE_POINTS`] values and the seconds between them.
+pub fn trace(
+    p: &SineParams,
+    values: &PatchValues,
+    target: ProbeTarget,
+    sample_rate: f32,
+    bpm: f32,
+) -> (Vec<f32>, f32) {
+    let read = ParamRead::Fixed(values);
+    let (lfo_shape, lfo_rate) = lfo_settings(p, read, bpm);
+    let mut lfo = Lfo::new();
+    let mut probe = ModProbe::new(sample_rate);
+    probe.start(target, lfo_rate);
+    let ramps = [None; NUM_RAMP_TARGETS];
+
+    let mut points = Vec::with_capacity(TRACE_POINTS);
+    let mut spacing = 0.0;
+    // The probe stops itself after its last point.
+    while points.len() < TRACE_POINTS {
+        let lfo = lfo.next(lfo_shape, lfo_rate, sample_rate);
+        let frame = FrameParams::next(p, read, lfo, 0.0, bpm, &ramps);
+        if let Some(point) = probe.next(&frame) {
+            points.push(point.value);
+            spacing = point.spacing;
+        }
+    }
+    (points, spacing)
+}
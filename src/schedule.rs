@@ -0,0 +1,174 @@
+//! Scheduled parameter changes at musical times ("at bar 5 set cutoff 8000"),
+//! queued by the AI tools and fired against the host's transport.
+//!
+//! Same split as [`crate::PatchActions`]: the audio thread only *detects* that
+//! playback has reached a change's bar, by flipping one atomic per slot, and
+//! hands the plugin's task executor a task that writes the change through the
+//! host, editor open or closed. Without the host's context (the editor hasn't
+//! opened yet) a due change is dropped rather than fired late. The payloads
+//! (parameter name and value) live behind a mutex that the audio thread never
+//! touches.
+
+use std::sync::atomic::{AtomicU8, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use serde::Serialize;
+use serde_json::Value;
+use vizia_plug::widgets::RawParamEvent;
+
+use crate::SineParams;
+use crate::ai::bridge;
+
+/// Most changes that can be pending at once.
+pub const MAX_SCHEDULED: usize = 16;
+
+// Slot states. Tools fill a slot, the audio thread marks it due, the task
+// executor runs it and frees it.
+const FREE: u8 = 0;
+const PENDING: u8 = 1;
+const DUE: u8 = 2;
+
+#[derive(Default)]
+struct Slot {
+    state: AtomicU8,
+    /// `f64` bits of the (1-based, fractional) bar to fire at.
+    at_bar: AtomicU64,
+}
+
+/// One queued `set_parameter`.
+#[derive(Clone, Debug, Serialize)]
+pub struct ScheduledChange {
+    pub id: u32,
+    pub parameter: String,
+    pub value: Value,
+    /// 1-based bar; `5.5` is halfway through bar 5.
+    pub bar: f64,
+}
+
+#[derive(Default)]
+pub struct Schedule {
+    slots: [Slot; MAX_SCHEDULED],
+    /// Payload per slot. Locked by tools and the task executor only.
+    changes: Mutex<[Option<ScheduledChange>; MAX_SCHEDULED]>,
+    next_id: AtomicU32,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn changes(&self) -> MutexGuard<'_, [Option<ScheduledChange>; MAX_SCHEDULED]> {
+        self.changes.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Queue `parameter = value` for when playback reaches `bar`. Returns the
+    /// change's id for [`Schedule::cancel`].
+    pub fn add(&self, parameter: &str, value: Value, bar: f64) -> Result<u32, String> {
+        let mut changes = self.changes();
+        let Some(index) = changes.iter().position(Option::is_none) else {
+            return Err(format!(
+                "at most {MAX_SCHEDULED} changes can be scheduled at once"
+            ));
+        };
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        changes[index] = Some(ScheduledChange {
+            id,
+            parameter: parameter.to_string(),
+            value,
+            bar,
+        });
+        let slot = &self.slots[index];
+        slot.at_bar.store(bar.to_bits(), Ordering::Relaxed);
+        slot.state.store(PENDING, Ordering::Release);
+        Ok(id)
+    }
+
+    /// Every pending change, soonest first.
+    pub fn list(&self) -> Vec<ScheduledChange> {
+        let mut pending: Vec<_> = self.changes().iter().flatten().cloned().collect();
+        pending.sort_by(|a, b| a.bar.total_cmp(&b.bar));
+        pending
+    }
+
+    /// Drop the change with `id`. Returns whether it was still pending.
+    pub fn cancel(&self, id: u32) -> bool {
+        let mut changes = self.changes();
+        let Some(index) = changes
+            .iter()
+            .position(|c| c.as_ref().is_some_and(|c| c.id == id))
+        else {
+            return false;
+        };
+        self.slots[index].state.store(FREE, Ordering::Release);
+        changes[index] = None;
+        true
+    }
+
+    /// Drop every pending change. Returns how many there were.
+    pub fn cancel_all(&self) -> usize {
+        let mut changes = self.changes();
+        let mut cancelled = 0;
+        for (slot, change) in self.slots.iter().zip(changes.iter_mut()) {
+            if change.take().is_some() {
+                slot.state.store(FREE, Ordering::Release);
+                cancelled += 1;
+            }
+        }
+        cancelled
+    }
+
+    /// Marks every change whose bar falls in `[start, end)` (in quarter-note
+    /// beats from the start of the song) as due. Called by the audio thread
+    /// once per block while the transport is playing. Returns whether any
+    /// came due. RT-safe: atomics only.
+    pub fn mark_due(&self, start_beats: f64, end_beats: f64, beats_per_bar: f64) -> bool {
+        let mut any_due = false;
+        for slot in &self.slots {
+            if slot.state.load(Ordering::Acquire) != PENDING {
+                continue;
+            }
+            let bar = f64::from_bits(slot.at_bar.load(Ordering::Relaxed));
+            let at_beats = (bar - 1.0) * beats_per_bar;
+            if (start_beats..end_beats).contains(&at_beats) {
+                any_due |= slot
+                    .state
+                    .compare_exchange(PENDING, DUE, Ordering::AcqRel, Ordering::Relaxed)
+                    .is_ok();
+            }
+        }
+        any_due
+    }
+
+    /// Write every change the audio thread has marked due. Called from the
+    /// task executor.
+    pub fn apply_due(&self, p: &SineParams, emit: &mut impl FnMut(RawParamEvent)) {
+        for change in self.take_due() {
+            let (parameter, bar) = (&change.parameter, change.bar);
+            match bridge::apply_write(p, parameter, &change.value, emit) {
+                Ok(()) => tracing::info!(target: "mcp", bar, %parameter, "scheduled change"),
+                Err(e) => tracing::warn!(target: "mcp", bar, %parameter, "scheduled change: {e}"),
+            }
+        }
+    }
+
+    /// Drop every change marked due, when there's no way to write it on time.
+    pub fn drop_due(&self) {
+        for change in self.take_due() {
+            let (parameter, bar) = (&change.parameter, change.bar);
+            tracing::warn!(target: "mcp", bar, %parameter, "scheduled change dropped");
+        }
+    }
+
+    fn take_due(&self) -> Vec<ScheduledChange> {
+        let mut changes = self.changes();
+        let mut due = Vec::new();
+        for (slot, change) in self.slots.iter().zip(changes.iter_mut()) {
+            if slot.state.load(Ordering::Acquire) == DUE {
+                due.extend(change.take());
+                slot.state.store(FREE, Ordering::Release);
+            }
+        }
+        due
+    }
+}
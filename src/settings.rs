@@ -0,0 +1,172 @@
+//! Machine-level settings: `<config-dir>/TripleOscSynth/settings.toml`. Unlike
+//! the params and the `#[persist]` fields, these belong to the computer rather
+//! than the project, so opening someone else's project doesn't change them and
+//! they aren't saved into it. The assistant's key and model stay in
+//! `config.json` (see `AiConfig` in `ai/config.rs`).
+//!
+//! The file carries a `version`. One older than [`SETTINGS_VERSION`] is
+//! brought up to date by [`MIGRATIONS`], on the raw TOML so renamed keys can be
+//! carried over, and written back (dropping any comments); one newer
+//! is read as far as it can be and left alone.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ai::preset::app_dir;
+
+/// Bumped whenever a key is renamed or changes meaning; add a step to
+/// [`MIGRATIONS`] with it.
+pub const SETTINGS_VERSION: i64 = 1;
+
+/// Each step rewrites a file of the version before its own into that version.
+/// Steps run in order from the file's version up, so keep them sorted.
+const MIGRATIONS: &[(i64, fn(&mut toml::Table))] = &[];
+
+/// UI scales the editor accepts.
+const UI_SCALE_RANGE: std::ops::RangeInclusive<f64> = 0.5..=3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    #[default]
+    Dark,
+    /// Dark, with brighter labels and card edges.
+    HighContrast,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub version: i64,
+    /// Port an MCP server would serve the assistant's tools on. This build has
+    /// no server, so nothing reads it yet.
+    pub mcp_port: u16,
+    /// Base URL of the Gemini API, for a proxy or a compatible server.
+    pub llm_endpoint: String,
+    pub theme: Theme,
+    /// Editor scale, applied each time the editor opens.
+    pub ui_scale: f64,
+    pub shortcuts: Shortcuts,
+}
+
+/// The editor's keyboard shortcuts, as keys like `"Ctrl+S"` or
+/// `"Shift+Escape"` (see `ui::shortcuts`); an empty one is unbound.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Shortcuts {
+    pub save_preset: String,
+    pub next_preset: String,
+    pub prev_preset: String,
+    /// Show the AI ASSIST tab, or go back to the tab shown before it.
+    pub toggle_chat: String,
+    /// Release every sounding note.
+    pub panic: String,
+    pub palette: String,
+}
+
+impl Default for Shortcuts {
+    fn default() -> Self {
+        Self {
+            save_preset: "Ctrl+S".to_string(),
+            next_preset: "Ctrl+]".to_string(),
+            prev_preset: "Ctrl+[".to_string(),
+            toggle_chat: "Ctrl+J".to_string(),
+            panic: "Shift+Escape".to_string(),
+            palette: "Ctrl+K".to_string(),
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            mcp_port: 7331,
+            llm_endpoint: "https://generativelanguage.googleapis.com/v1beta".to_string(),
+            theme: Theme::Dark,
+            ui_scale: 1.0,
+            shortcuts: Shortcuts::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn path() -> PathBuf {
+        app_dir().join("settings.toml")
+    }
+
+    /// Read the settings, migrating an older file. A missing file gives the
+    /// defaults; an unparseable one is left for the user to fix and gives the
+    /// defaults too.
+    pub fn load() -> Self {
+        let Ok(text) = std::fs::read_to_string(Self::path()) else {
+            return Self::default();
+        };
+        let mut table: toml::Table = match text.parse() {
+            Ok(table) => table,
+            Err(e) => {
+                tracing::warn!(target: "editor", "settings.toml is invalid, using defaults: {e}");
+                return Self::default();
+            }
+        };
+        // A hand-written file without a version is taken as current.
+        let version = table.get("version").and_then(|v| v.as_integer());
+        let version = version.unwrap_or(SETTINGS_VERSION);
+        let migrate = version < SETTINGS_VERSION;
+        if migrate {
+            for (_, step) in MIGRATIONS.iter().filter(|(to, _)| *to > version) {
+                step(&mut table);
+            }
+            table.insert("version".into(), SETTINGS_VERSION.into());
+        } else if version > SETTINGS_VERSION {
+            tracing::warn!(
+                target: "editor",
+                "settings.toml is version {version}; this build reads up to {SETTINGS_VERSION}"
+            );
+        }
+        let settings = match toml::Value::Table(table).try_into::<Settings>() {
+            Ok(settings) => settings.clamped(),
+            Err(e) => {
+                tracing::warn!(target: "editor", "settings.toml is invalid, using defaults: {e}");
+                return Self::default();
+            }
+        };
+        if migrate {
+            match settings.save() {
+                Ok(()) => tracing::info!(target: "editor", from = version, "migrated settings"),
+                Err(e) => tracing::warn!(target: "editor", "couldn't migrate settings: {e}"),
+            }
+        }
+        settings
+    }
+
+    /// [`Self::load`], writing out the defaults when there's no file yet so
+    /// users have one to edit.
+    pub fn load_or_create() -> Self {
+        let settings = Self::load();
+        if !Self::path().exists() {
+            let _ = settings.save();
+        }
+        settings
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let dir = app_dir();
+        std::fs::create_dir_all(&dir).map_err(|e| format!("create config dir: {e}"))?;
+        let text = toml::to_string_pretty(self).map_err(|e| format!("serialize: {e}"))?;
+        std::fs::write(Self::path(), text).map_err(|e| format!("write settings: {e}"))
+    }
+
+    fn clamped(mut self) -> Self {
+        if !UI_SCALE_RANGE.contains(&self.ui_scale) {
+            tracing::warn!(target: "editor", ui_scale = self.ui_scale, "ui_scale out of range");
+            let (low, high) = (*UI_SCALE_RANGE.start(), *UI_SCALE_RANGE.end());
+            self.ui_scale = match self.ui_scale {
+                scale if scale.is_nan() => 1.0,
+                scale => scale.clamp(low, high),
+            };
+        }
+        self
+    }
+}
@@ -0,0 +1,231 @@
+//! Preset transitions: "morph into preset X over 8 bars". Every continuous
+//! param glides from where it is to the target preset's value; switches,
+//! choices and counts flip halfway through.
+//!
+//! Like parameter ramps (see `ramps.rs`), the clock runs on the audio thread,
+//! so a length in beats follows the host tempo, and the params are written on
+//! the GUI thread, inside one gesture per param, so a recording host captures
+//! the morph as automation: by the editor's poll timer while it's open, else
+//! by the plugin's task executor, which the audio thread asks at about the
+//! same rate. Unlike a ramp, the in-between values reach the audio only
+//! through the params (at the poll rate, smoothed by each param's smoother),
+//! which is what lets a morph cover every param.
+
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU32, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use nih_plug::prelude::ParamPtr;
+use vizia_plug::widgets::RawParamEvent;
+
+use crate::ai::conflicts::HeldParams;
+use crate::ai::preset::{PatchMetadata, PresetData};
+use crate::ramps::RampDuration;
+use crate::SineParams;
+
+// Transition states. `start` arms, the audio thread runs and finishes, the
+// final write (the editor's or the task executor's) acknowledges back to idle.
+const IDLE: u8 = 0;
+const ARMED: u8 = 1;
+const RUNNING: u8 = 2;
+const FINISHED: u8 = 3;
+
+/// Progress at which discrete params switch to the target's value.
+const SWITCH_POINT: f32 = 0.5;
+
+/// One param's path through a transition, normalized.
+struct Step {
+    ptr: ParamPtr,
+    from: f32,
+    to: f32,
+    /// Glides; otherwise it switches at [`SWITCH_POINT`].
+    continuous: bool,
+}
+
+struct Plan {
+    /// The target preset's name.
+    name: String,
+    metadata: PatchMetadata,
+    steps: Vec<Step>,
+}
+
+/// Mailbox between whoever starts a transition (the AI tools, the editor's
+/// MORPH button), the audio thread that clocks it and the GUI side that writes
+/// it into the params.
+#[derive(Default)]
+pub struct PresetTransition {
+    plan: Mutex<Option<Plan>>,
+    /// Params with a Begin but no End yet. GUI side only.
+    gestures: Mutex<Vec<ParamPtr>>,
+    state: AtomicU8,
+    /// `f32` bits of the length, in beats if `in_beats`, else seconds.
+    duration: AtomicU32,
+    in_beats: AtomicBool,
+    /// `f32` bits of the length in seconds, resolved when the audio thread
+    /// picks the transition up.
+    seconds: AtomicU32,
+    /// `f32` bits of how far along it is, `0..=1`.
+    progress: AtomicU32,
+}
+
+impl PresetTransition {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn plan(&self) -> MutexGuard<'_, Option<Plan>> {
+        self.plan.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Morph from the current params into `preset` (named `name`), replacing
+    /// any transition already running. Returns how many params will move.
+    pub fn start(
+        &self,
+        p: &SineParams,
+        name: &str,
+        preset: &PresetData,
+        duration: RampDuration,
+    ) -> usize {
+        let mut steps: Vec<Step> = Vec::new();
+        preset.apply(p, &mut |event| {
+            let RawParamEvent::SetParameterNormalized(ptr, to) = event else {
+                return;
+            };
+            // SAFETY: every `ParamPtr` here points into the plugin's
+            // `SineParams`, which outlives the editor and the tools.
+            let (from, continuous) = unsafe {
+                (
+                    ptr.unmodulated_normalized_value(),
+                    ptr.step_count().is_none(),
+                )
+            };
+            if from != to {
+                steps.push(Step {
+                    ptr,
+                    from,
+                    to,
+                    continuous,
+                });
+            }
+        });
+        let moved = steps.len();
+
+        let (length, in_beats) = match duration {
+            RampDuration::Seconds(seconds) => (seconds, false),
+            RampDuration::Beats(beats) => (beats, true),
+        };
+        // Armed under the lock, so `sync_params` never pairs the new plan
+        // with the old transition's state.
+        let mut plan = self.plan();
+        *plan = Some(Plan {
+            name: name.to_string(),
+            metadata: preset.metadata.clone(),
+            steps,
+        });
+        self.duration.store(length.to_bits(), Ordering::Relaxed);
+        self.in_beats.store(in_beats, Ordering::Relaxed);
+        self.state.store(ARMED, Ordering::Release);
+        moved
+    }
+
+    /// Audio thread, once per block: picks up an armed transition and moves
+    /// the running one on by `block_len` samples. Returns whether there's a
+    /// transition still to write. RT-safe: atomics only.
+    pub fn advance(&self, block_len: usize, sample_rate: f32, bpm: f32) -> bool {
+        let armed = self
+            .state
+            .compare_exchange(ARMED, RUNNING, Ordering::AcqRel, Ordering::Relaxed)
+            .is_ok();
+        if armed {
+            let length = f32::from_bits(self.duration.load(Ordering::Relaxed));
+            let seconds = if self.in_beats.load(Ordering::Relaxed) {
+                length * 60.0 / bpm
+            } else {
+                length
+            };
+            self.seconds
+                .store(seconds.max(1e-3).to_bits(), Ordering::Relaxed);
+            self.progress.store(0.0f32.to_bits(), Ordering::Relaxed);
+        } else {
+            match self.state.load(Ordering::Acquire) {
+                RUNNING => {}
+                FINISHED => return true,
+                _ => return false,
+            }
+        }
+
+        let seconds = f32::from_bits(self.seconds.load(Ordering::Relaxed));
+        let progress = f32::from_bits(self.progress.load(Ordering::Relaxed))
+            + block_len as f32 / sample_rate / seconds;
+        self.progress
+            .store(progress.min(1.0).to_bits(), Ordering::Relaxed);
+        if progress >= 1.0 {
+            let _ = self.state.compare_exchange(
+                RUNNING,
+                FINISHED,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            );
+        }
+        true
+    }
+
+    /// Write the running transition's current values into the params, leaving
+    /// alone any knob the user is holding (`held`, with the editor open).
+    /// Called from the editor's poll timer, and with the editor closed from
+    /// the task executor. Returns the preset's name and metadata once the
+    /// transition has finished.
+    pub fn sync_params(
+        &self,
+        held: Option<&HeldParams>,
+        emit: &mut impl FnMut(RawParamEvent),
+    ) -> Option<(String, PatchMetadata)> {
+        let mut plan = self.plan();
+        let mut gestures = self.gestures.lock().unwrap_or_else(PoisonError::into_inner);
+        let state = self.state.load(Ordering::Acquire);
+        let Some(current) = plan
+            .as_ref()
+            .filter(|_| matches!(state, RUNNING | FINISHED))
+        else {
+            return None;
+        };
+
+        let progress = if state == FINISHED {
+            1.0
+        } else {
+            f32::from_bits(self.progress.load(Ordering::Relaxed))
+        };
+        for step in &current.steps {
+            if held.is_some_and(|held| held.any_held(&[step.ptr])) {
+                continue;
+            }
+            if !gestures.contains(&step.ptr) {
+                emit(RawParamEvent::BeginSetParameter(step.ptr));
+                gestures.push(step.ptr);
+            }
+            let value = if step.continuous {
+                step.from + (step.to - step.from) * progress
+            } else if progress >= SWITCH_POINT {
+                step.to
+            } else {
+                step.from
+            };
+            emit(RawParamEvent::SetParameterNormalized(step.ptr, value));
+        }
+        // Close the gestures of params a replaced transition was moving and
+        // this one isn't, and all of them once it has finished.
+        gestures.retain(|ptr| {
+            let moving = state == RUNNING && current.steps.iter().any(|s| s.ptr == *ptr);
+            if !moving {
+                emit(RawParamEvent::EndSetParameter(*ptr));
+            }
+            moving
+        });
+
+        if state != FINISHED {
+            return None;
+        }
+        // `start` arms under the same lock, so nothing new can be armed here.
+        self.state.store(IDLE, Ordering::Release);
+        plan.take().map(|plan| (plan.name, plan.metadata))
+    }
+}
@@ -0,0 +1,164 @@
+//! Automation preview: an overlay plotting the last few seconds of one param,
+//! picked with WATCH in the parameter palette, for finding out why a sweep
+//! sounds wrong. The grey line is the value as the host set it (automation
+//! or host modulation), the indigo one what the engine played after
+//! smoothing; stairs in the grey line are coarse automation, a lag between
+//! the two is the smoothing (see `watch.rs`).
+//!
+//! Like [`super::mod_scope`], the view samples [`Diagnostics`] in `draw` on a
+//! redraw timer. It asks to be closed with [`AutomationPreviewEvent::Close`].
+
+use std::sync::Arc;
+
+use vizia_plug::vizia::prelude::*;
+use vizia_plug::vizia::vg;
+
+use crate::watch::{WATCH_POINTS, WATCH_SECONDS};
+use crate::Diagnostics;
+
+/// CSS for the overlay panel and its plot.
+pub const AUTOMATION_PREVIEW_CSS: &str = r#"
+    .automation-preview {
+        position-type: absolute;
+        top: 64px;
+        right: 18px;
+        left: 1s;
+        width: 340px;
+        height: auto;
+        background-color: #121216F2;
+        border: 1px solid #2E3340;
+        corner-radius: 8px;
+        padding: 12px;
+        gap: 6px;
+    }
+    .automation-preview-head {
+        height: 18px;
+        alignment: left;
+    }
+    .automation-preview-title {
+        color: #F8FAFC;
+        font-size: 10px;
+        font-weight: 700;
+        width: 1s;
+    }
+    .automation-scope {
+        width: 1s;
+        height: 96px;
+        corner-radius: 4px;
+        background-color: #0E0E12;
+        border-width: 1px;
+        border-color: #2D2D34;
+    }
+    .automation-legend {
+        color: #94A3B8;
+        font-size: 8px;
+        font-weight: 700;
+    }
+"#;
+
+/// Redraw cadence (~30 fps).
+const REFRESH: Duration = Duration::from_millis(33);
+
+/// Emitted (bubbling up) when the preview wants to be closed.
+pub enum AutomationPreviewEvent {
+    Close,
+}
+
+/// Build the overlay for the param called `label`.
+pub fn automation_preview(cx: &mut Context, diagnostics: Arc<Diagnostics>, label: String) {
+    VStack::new(cx, move |cx| {
+        HStack::new(cx, |cx| {
+            Label::new(cx, format!("AUTOMATION · {label}")).class("automation-preview-title");
+            Button::new(cx, |cx| Label::new(cx, "CLOSE"))
+                .on_press(|cx| cx.emit(AutomationPreviewEvent::Close))
+                .class("header-btn");
+        })
+        .class("automation-preview-head");
+        AutomationScope::new(cx, diagnostics.clone());
+        Label::new(
+            cx,
+            format!("GREY: AS SET · INDIGO: AS PLAYED · LAST {WATCH_SECONDS:.0} S"),
+        )
+        .class("automation-legend");
+    })
+    .class("automation-preview");
+}
+
+/// Scrolls the watched param's recent values, newest on the right, from its
+/// minimum at the bottom to its maximum at the top.
+pub struct AutomationScope {
+    diagnostics: Arc<Diagnostics>,
+}
+
+impl AutomationScope {
+    pub fn new(cx: &mut Context, diagnostics: Arc<Diagnostics>) -> Handle<'_, Self> {
+        Self { diagnostics }
+            .build(cx, |cx| {
+                let timer = cx.add_timer(REFRESH, None, |cx, action| {
+                    if let TimerAction::Tick(_) = action {
+                        cx.needs_redraw();
+                    }
+                });
+                cx.start_timer(timer);
+            })
+            .class("automation-scope")
+    }
+}
+
+impl View for AutomationScope {
+    fn element(&self) -> Option<&'static str> {
+        Some("automation-scope")
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &Canvas) {
+        let bounds = cx.bounds();
+        if bounds.w <= 0.0 || bounds.h <= 0.0 {
+            return;
+        }
+        let pad = 3.0;
+        let (left, top) = (bounds.x + pad, bounds.y + pad);
+        let (width, height) = (bounds.w - pad * 2.0, bounds.h - pad * 2.0);
+
+        // A line a second.
+        let mut grid = vg::Paint::default();
+        grid.set_style(vg::PaintStyle::Stroke);
+        grid.set_stroke_width(1.0);
+        grid.set_color(vg::Color::from_argb(255, 45, 45, 52));
+        grid.set_alpha_f(cx.opacity());
+        for second in 1..WATCH_SECONDS as usize {
+            let x = left + width * (1.0 - second as f32 / WATCH_SECONDS);
+            canvas.draw_line((x, top), (x, top + height), &grid);
+        }
+
+        // Right-aligned, so a history that hasn't filled yet grows from the
+        // right like the rest scrolls.
+        let history = self.diagnostics.watch_history();
+        let start = WATCH_POINTS - history.len();
+        let line = |value: fn(&(f32, f32)) -> f32| {
+            let mut path = vg::Path::new();
+            for (index, point) in history.iter().enumerate() {
+                let x = left + width * (start + index) as f32 / (WATCH_POINTS - 1) as f32;
+                let y = top + height * (1.0 - value(point).clamp(0.0, 1.0));
+                if index == 0 {
+                    path.move_to((x, y));
+                } else {
+                    path.line_to((x, y));
+                }
+            }
+            path
+        };
+
+        let mut paint = vg::Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_style(vg::PaintStyle::Stroke);
+        paint.set_stroke_join(vg::PaintJoin::Round);
+        paint.set_stroke_width(1.0);
+        paint.set_color(vg::Color::from_argb(255, 100, 116, 139)); // slate
+        paint.set_alpha_f(cx.opacity());
+        canvas.draw_path(&line(|&(set, _)| set), &paint);
+        paint.set_stroke_width(1.5);
+        paint.set_color(vg::Color::from_argb(255, 129, 140, 248)); // indigo
+        paint.set_alpha_f(cx.opacity());
+        canvas.draw_path(&line(|&(_, smoothed)| smoothed), &paint);
+    }
+}
@@ -0,0 +1,286 @@
+//! A/B compare: two patches, one in the params and one held aside, swapped
+//! with the header's A and B buttons, plus an overlay drawing both patches'
+//! spectra over each other so the difference can be seen as well as heard.
+//!
+//! Both spectra come from the offline renderer (see `render.rs`) playing the
+//! same note through each patch, on a background thread; rendering never
+//! touches the live params. The overlay asks to be re-rendered or closed with
+//! [`SpectraEvent`], and the render posts its result back the same way.
+
+use vizia_plug::vizia::prelude::*;
+use vizia_plug::vizia::vg;
+use vizia_plug::widgets::RawParamEvent;
+
+use crate::ai::preset::PresetData;
+use crate::dsp::spectrum::{self, FLOOR_DB, SPECTRUM_BANDS};
+use crate::render::{self, PatchValues, RenderSpec};
+use crate::SineParams;
+
+/// CSS for the overlay panel and its plot.
+pub const COMPARE_CSS: &str = r#"
+    .spectra {
+        position-type: absolute;
+        top: 64px;
+        left: 1s;
+        right: 18px;
+        width: 420px;
+        height: auto;
+        background-color: #121216F2;
+        border: 1px solid #2E3340;
+        corner-radius: 8px;
+        padding: 12px;
+        gap: 6px;
+    }
+    .spectra-title {
+        color: #F8FAFC;
+        font-size: 10px;
+        font-weight: 700;
+    }
+    .spectra-legend {
+        color: #94A3B8;
+        font-size: 9px;
+    }
+    .spectra-plot {
+        width: 1s;
+        height: 180px;
+        corner-radius: 6px;
+        background-color: #0E0E12;
+        border-width: 1px;
+        border-color: #2D2D34;
+    }
+"#;
+
+/// Note both patches play for their spectra (middle C).
+const RENDER_NOTE: u8 = 60;
+/// Levels the plot spans, in dB.
+const TOP_DB: f32 = 0.0;
+const BOTTOM_DB: f32 = -90.0;
+/// Difference the lower strip's full height stands for, either way, in dB.
+const DIFF_RANGE_DB: f32 = 24.0;
+/// Share of the plot's height given to the difference strip.
+const DIFF_STRIP: f32 = 0.3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Data)]
+pub enum AbSlot {
+    A,
+    B,
+}
+
+impl AbSlot {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::A => "A",
+            Self::B => "B",
+        }
+    }
+}
+
+/// The A/B pair. The active slot's patch is whatever the params hold; the
+/// other slot's waits in `other`.
+#[derive(Debug)]
+pub struct AbCompare {
+    pub active: AbSlot,
+    /// `None` until the first switch, which copies the active patch over: B
+    /// starts out as A.
+    other: Option<PresetData>,
+}
+
+impl Default for AbCompare {
+    fn default() -> Self {
+        Self {
+            active: AbSlot::A,
+            other: None,
+        }
+    }
+}
+
+impl AbCompare {
+    /// Make `slot` active: hold the live patch aside (named `name`) and load
+    /// the other one. Returns the loaded patch's name, if one was loaded.
+    pub fn switch(
+        &mut self,
+        p: &SineParams,
+        slot: AbSlot,
+        name: &str,
+        emit: &mut impl FnMut(RawParamEvent),
+    ) -> Option<String> {
+        if slot == self.active {
+            return None;
+        }
+        let mut current = PresetData::capture(p);
+        current.name = name.to_string();
+        let loaded = self.other.replace(current).map(|other| {
+            other.apply(p, emit);
+            other.metadata.store(p);
+            other.name
+        });
+        self.active = slot;
+        loaded
+    }
+
+    /// Both slots' patches for [`render_spectra`], A first.
+    pub fn spectra_patches(&self, p: &SineParams) -> (PatchValues, PatchValues) {
+        // An empty map reads every param's live value.
+        let live = PatchValues::new();
+        let held = self
+            .other
+            .as_ref()
+            .map_or_else(PatchValues::new, |other| render::patch_values(p, other));
+        match self.active {
+            AbSlot::A => (live, held),
+            AbSlot::B => (held, live),
+        }
+    }
+}
+
+/// Render patches `a` and `b` and take their spectra. Takes a good part of a
+/// second, so it runs off the GUI thread.
+pub fn render_spectra(p: &SineParams, a: &PatchValues, b: &PatchValues) -> AbSpectra {
+    let spec = RenderSpec::single_note(RENDER_NOTE);
+    let levels = |values: &PatchValues| {
+        let mono: Vec<f32> = render::render(p, values, &spec)
+            .into_iter()
+            .map(|[left, right]| (left + right) * 0.5)
+            .collect();
+        spectrum::spectrum(&mono, spec.sample_rate).to_vec()
+    };
+    AbSpectra {
+        a: levels(a),
+        b: levels(b),
+    }
+}
+
+/// Band levels in dB of each slot; see [`spectrum::spectrum`].
+#[derive(Clone, Debug, PartialEq, Data)]
+pub struct AbSpectra {
+    pub a: Vec<f32>,
+    pub b: Vec<f32>,
+}
+
+impl AbSpectra {
+    /// Where the two differ most, as a line for the overlay.
+    fn summary(&self) -> String {
+        let largest = self
+            .a
+            .iter()
+            .zip(&self.b)
+            .enumerate()
+            // Bands both patches leave silent don't count.
+            .filter(|(_, (a, b))| a.max(**b) > FLOOR_DB)
+            .map(|(band, (a, b))| (band, b - a))
+            .max_by(|(_, x), (_, y)| x.abs().total_cmp(&y.abs()));
+        match largest {
+            Some((band, diff)) if diff.abs() >= 0.5 => {
+                let hz = (spectrum::band_edge(band) * spectrum::band_edge(band + 1)).sqrt();
+                let at = if hz >= 1000.0 {
+                    format!("{:.1} kHz", hz / 1000.0)
+                } else {
+                    format!("{hz:.0} Hz")
+                };
+                format!("Largest difference: B is {diff:+.1} dB at {at}")
+            }
+            _ => "A and B sound the same".to_string(),
+        }
+    }
+}
+
+/// Emitted (bubbling up) by the overlay's buttons, and by the background
+/// render once its spectra are ready.
+pub enum SpectraEvent {
+    Rerender,
+    Close,
+    Rendered(AbSpectra),
+}
+
+/// Build the overlay panel for `spectra`.
+pub fn spectra_overlay(cx: &mut Context, spectra: AbSpectra) {
+    VStack::new(cx, move |cx| {
+        HStack::new(cx, |cx| {
+            Label::new(cx, "A / B SPECTRA").class("spectra-title");
+            Element::new(cx).width(Stretch(1.0)).height(Pixels(0.0));
+            Button::new(cx, |cx| Label::new(cx, "RENDER"))
+                .on_press(|cx| cx.emit(SpectraEvent::Rerender))
+                .class("header-btn");
+            Button::new(cx, |cx| Label::new(cx, "CLOSE"))
+                .on_press(|cx| cx.emit(SpectraEvent::Close))
+                .class("header-btn");
+        })
+        .height(Pixels(24.0))
+        .gap(Pixels(6.0))
+        .alignment(Alignment::Center);
+
+        Label::new(cx, "A cyan, B rose; below, B minus A").class("spectra-legend");
+        Label::new(cx, spectra.summary()).class("spectra-legend");
+        SpectraPlot { spectra }
+            .build(cx, |_| {})
+            .class("spectra-plot");
+    })
+    .class("spectra");
+}
+
+/// Both spectra on a log-frequency axis, with B minus A in a strip below.
+struct SpectraPlot {
+    spectra: AbSpectra,
+}
+
+impl View for SpectraPlot {
+    fn element(&self) -> Option<&'static str> {
+        Some("spectra-plot")
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &Canvas) {
+        let bounds = cx.bounds();
+        if bounds.w <= 0.0 || bounds.h <= 0.0 {
+            return;
+        }
+        let pad = 4.0;
+        let (left, top) = (bounds.x + pad, bounds.y + pad);
+        let (width, height) = (bounds.w - pad * 2.0, bounds.h - pad * 2.0);
+        let curve_height = height * (1.0 - DIFF_STRIP);
+        let strip_mid = top + curve_height + height * DIFF_STRIP * 0.5;
+        let x = |band: usize| left + width * (band as f32 + 0.5) / SPECTRUM_BANDS as f32;
+
+        let stroke = |r: u8, g: u8, b: u8| {
+            let mut paint = vg::Paint::default();
+            paint.set_anti_alias(true);
+            paint.set_style(vg::PaintStyle::Stroke);
+            paint.set_stroke_width(1.5);
+            paint.set_stroke_join(vg::PaintJoin::Round);
+            paint.set_color(vg::Color::from_argb(255, r, g, b));
+            paint.set_alpha_f(cx.opacity());
+            paint
+        };
+
+        let mut axis = stroke(45, 45, 52);
+        axis.set_stroke_width(1.0);
+        canvas.draw_line((left, strip_mid), (left + width, strip_mid), &axis);
+
+        let curve = |levels: &[f32]| {
+            let mut path = vg::Path::new();
+            for (band, level) in levels.iter().enumerate() {
+                let norm = (level - BOTTOM_DB) / (TOP_DB - BOTTOM_DB);
+                let y = top + curve_height * (1.0 - norm.clamp(0.0, 1.0));
+                if band == 0 {
+                    path.move_to((x(band), y));
+                } else {
+                    path.line_to((x(band), y));
+                }
+            }
+            path
+        };
+        canvas.draw_path(&curve(&self.spectra.a), &stroke(56, 189, 248)); // cyan
+        canvas.draw_path(&curve(&self.spectra.b), &stroke(244, 63, 94)); // rose
+
+        // One bar per band, up where B is louder.
+        let mut bars = stroke(129, 140, 248); // indigo
+        bars.set_stroke_width((width / SPECTRUM_BANDS as f32 - 1.0).max(1.0));
+        let half_strip = height * DIFF_STRIP * 0.5;
+        for (band, (a, b)) in self.spectra.a.iter().zip(&self.spectra.b).enumerate() {
+            let diff = ((b - a) / DIFF_RANGE_DB).clamp(-1.0, 1.0);
+            if diff != 0.0 {
+                let y = strip_mid - diff * half_strip;
+                canvas.draw_line((x(band), strip_mid), (x(band), y), &bars);
+            }
+        }
+    }
+}
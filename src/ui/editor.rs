@@ -1,6 +1,46 @@
-use super::{Meter, ParamKnob, PeakMeter, TabDefinition, TabSwitcher};
-use crate::{FilterMode, OscillatorParams, SineParams, Waveform};
-use nih_plug::prelude::{Editor, EnumParam, Param};
+use super::automation_preview::AutomationPreviewEvent;
+use super::compare::{self, AbCompare, AbSlot, AbSpectra, SpectraEvent};
+use super::knob::{HighlightParam, SetDragMode};
+use super::layout::{EditorLayout, OscView};
+use super::mod_assign::{self, ModAssignEvent, ModChip, ModRings};
+use super::palette::PaletteEvent;
+use super::save_dialog::SaveDialogEvent;
+use super::shortcuts::{KeyMap, Shortcut};
+use super::tab_switcher::TabSwitcherEvent;
+use super::toast::{ChangeRecorder, ToastEvent};
+use super::{
+    KnobEvent, Meter, MixMeters, ParamKnob, ParamKnobModifiers, PeakMeter, TabDefinition,
+    TabSwitcher, XyPadView,
+};
+use crate::actions::{self, HostLink, PatchAction, PatchActions};
+use crate::ai::preset;
+#[cfg(feature = "chat")]
+use crate::ai::chat_ui;
+#[cfg(feature = "mcp")]
+use crate::ai::deferred::DeferredWrites;
+#[cfg(feature = "chat")]
+use crate::ai::host::ToolContext;
+use crate::ai::bridge;
+use crate::ai::conflicts::HeldParams;
+use crate::isolate;
+use crate::locks::{self, LockGroup, ParamLocks};
+use crate::osc_copy::OscSnapshot;
+use crate::params::{NUM_DRAWBARS, NUM_MOD_SLOTS};
+#[cfg(feature = "fx")]
+use crate::params::NUM_GATE_STEPS;
+use crate::persist::{ProjectState, XyAxis};
+use crate::ramps::{ParamRamps, RampDuration};
+use crate::recovery::{self, Leftover};
+#[cfg(feature = "chat")]
+use crate::schedule::Schedule;
+use crate::settings::{Settings, Theme};
+use crate::transition::PresetTransition;
+use crate::xy::{self, Axis, NUM_XY_PADS};
+use crate::{
+    Diagnostics, FilterMode, ModDestination, ModSource, NoteDivision, OscillatorParams, SineParams,
+    Waveform, XyPadParams,
+};
+use nih_plug::prelude::{BoolParam, Editor, EnumParam, FloatParam, Param, ParamPtr};
 use std::sync::Arc;
 use vizia_plug::vizia::prelude::*;
 use vizia_plug::widgets::param_base::ParamWidgetBase;
@@ -15,7 +55,6 @@ impl ColorPalette {
     pub const OSC3_ACCENT: Color = Color::rgb(244, 63, 94); // Rose
     pub const FILTER_ACCENT: Color = Color::rgb(168, 85, 247); // Purple
     pub const ENV_ACCENT: Color = Color::rgb(129, 140, 248); // Indigo
-    pub const BG_CARD_ALT: Color = Color::rgb(28, 28, 34);
     pub const TEXT_HIGH: Color = Color::rgb(248, 250, 252);
     pub const TEXT_MED: Color = Color::rgb(148, 163, 184);
 }
@@ -27,12 +66,437 @@ const ACCENT_OSC3: &str = "accent-rose";
 const ACCENT_FILTER: &str = "accent-purple";
 const ACCENT_DEFAULT: &str = "accent-indigo";
 
+/// How often the editor checks for actions raised by the trigger params.
+const ACTION_POLL: Duration = Duration::from_millis(50);
+/// Length of the header's MORPH into the next preset: one 4/4 bar.
+const MORPH_BEATS: f32 = 4.0;
+
 #[derive(Lens)]
 struct Data {
     params: Arc<SineParams>,
+    /// Whether the voice monitor overlay is showing.
+    show_voice_monitor: bool,
+    /// Whether the log viewer overlay is showing.
+    show_log: bool,
+    /// Whether the session stats overlay is showing.
+    show_usage: bool,
+    /// Whether the parameter palette is showing.
+    show_palette: bool,
+    /// The param the automation preview shows, by its palette label; `None`
+    /// while it's closed.
+    watching: Option<String>,
+    /// Whether the save-preset dialog is showing.
+    show_save: bool,
+    /// Mailbox for the host-automatable trigger params.
+    actions: Arc<PatchActions>,
+    /// Where the panic shortcut asks the engine to release every note.
+    diagnostics: Arc<Diagnostics>,
+    /// The keyboard shortcuts from `settings.toml`.
+    shortcuts: KeyMap,
+    /// Name of the preset last stepped to or loaded here; empty until then.
+    preset_name: String,
+    /// Parameter ramps the AI tools started, mirrored into the params.
+    ramps: Arc<ParamRamps>,
+    /// Preset morphs, written into the params as they run.
+    transition: Arc<PresetTransition>,
+    /// Knobs the user is dragging.
+    held: Arc<HeldParams>,
+    /// The assistant's writes waiting on `held`.
+    #[cfg(feature = "mcp")]
+    deferred: Arc<DeferredWrites>,
+    /// Mirror of `ProjectState::locks` for the cards' LOCK buttons.
+    locks: ParamLocks,
+    /// Mirror of `ProjectState::editor_layout`.
+    layout: EditorLayout,
+    /// The oscillator last copied from a card's menu.
+    osc_clipboard: Option<OscSnapshot>,
+    /// The header's A/B pair.
+    ab: AbCompare,
+    /// Both A/B patches' spectra while their overlay is showing.
+    spectra: Option<AbSpectra>,
+    /// Whether spectra are being rendered for the overlay.
+    spectra_pending: bool,
+    /// Patches unclean shutdowns left behind, newest first; the banner
+    /// offers the first.
+    recovered: Vec<Leftover>,
+    /// The knob each mod slot's route shows on, last broadcast as
+    /// [`ModRings`].
+    mod_rings: Vec<(ParamPtr, usize, f32)>,
+    /// The modulation card's Routes tab, one line per slot.
+    mod_routes: Vec<String>,
+    /// What each XY pad axis drives, one line per axis, pad by pad.
+    xy_lines: Vec<String>,
+}
+
+enum EditorEvent {
+    #[cfg(feature = "gui-extras")]
+    ToggleVoiceMonitor,
+    #[cfg(feature = "gui-extras")]
+    ToggleLog,
+    ToggleUsage,
+    TogglePalette,
+    ToggleSave,
+    /// Run a patch action right away (header buttons).
+    Run(PatchAction),
+    /// Morph into the next preset over [`MORPH_BEATS`].
+    MorphNext,
+    /// Fade out every voice and forget every held note.
+    Panic,
+    /// Lock or unlock a card's group.
+    ToggleLock(LockGroup),
+    /// Collapse or expand an oscillator card.
+    ToggleCollapsed(usize),
+    ToggleCompact,
+    /// Switch every knob to the next [`super::KnobDragMode`].
+    CycleKnobDrag,
+    /// Copy an oscillator's settings, or paste the copy onto one.
+    CopyOsc(usize),
+    PasteOsc(usize),
+    /// Swap in the patch held in the other A/B slot.
+    SwitchAb(AbSlot),
+    /// Render and show the A/B spectra, or hide them.
+    ToggleSpectra,
+    /// Load the patch the recovery banner offers, or turn it down.
+    RestoreRecovered,
+    DiscardRecovered,
+    /// Free an XY pad's axis.
+    ClearXy(usize, Axis),
+    /// Scale the editor by `settings.toml`'s `ui_scale`, read as it opened.
+    SetScale(f64),
+    /// Run any trigger actions raised before the host's context was attached,
+    /// mirror running parameter ramps and the preset morph into their params,
+    /// and apply AI writes that were waiting on a knob drag. Also refreshes
+    /// the mod routes the knobs and the Routes tab show, and the XY pads'
+    /// assignments.
+    PollActions,
 }
 
-impl Model for Data {}
+impl Data {
+    /// Open or close the parameter palette; closing it clears the knob it
+    /// highlighted.
+    fn set_palette(&mut self, cx: &mut EventContext, show: bool) {
+        self.show_palette = show;
+        if !show {
+            cx.emit_custom(
+                Event::new(HighlightParam(None))
+                    .target(Entity::root())
+                    .propagate(Propagation::Subtree),
+            );
+        }
+    }
+
+    /// Render the A/B spectra in the background and show them when they
+    /// arrive, or hide them.
+    fn render_spectra(&mut self, cx: &mut EventContext, show: bool) {
+        self.spectra_pending = show;
+        if !show {
+            self.spectra = None;
+            return;
+        }
+        let params = self.params.clone();
+        let (a, b) = self.ab.spectra_patches(&self.params);
+        cx.spawn(move |proxy| {
+            let spectra = compare::render_spectra(&params, &a, &b);
+            let _ = proxy.emit(SpectraEvent::Rendered(spectra));
+        });
+    }
+
+    fn run_shortcut(&mut self, cx: &mut EventContext, shortcut: Shortcut) {
+        match shortcut {
+            Shortcut::SavePreset => self.show_save = true,
+            Shortcut::NextPreset => self.run(cx, PatchAction::NextPreset),
+            Shortcut::PrevPreset => self.run(cx, PatchAction::PrevPreset),
+            Shortcut::ToggleChat => cx.emit_custom(
+                Event::new(TabSwitcherEvent::Toggle("ai".to_string()))
+                    .target(Entity::root())
+                    .propagate(Propagation::Subtree),
+            ),
+            Shortcut::Panic => self.panic(),
+            Shortcut::Palette => self.set_palette(cx, !self.show_palette),
+        }
+    }
+
+    fn panic(&self) {
+        self.diagnostics.request_panic();
+        tracing::info!(target: "editor", "panic: releasing every note");
+    }
+
+    fn save_layout(&self) {
+        let layout = self.layout;
+        ProjectState::update(&self.params, |state| state.editor_layout = layout);
+    }
+
+    fn run(&mut self, cx: &mut EventContext, action: PatchAction) {
+        self.sync_preset_name();
+        self.actions.run(&self.params, action, &mut |event| cx.emit(event));
+        self.sync_preset_name();
+    }
+
+    /// Keep the shown preset name and the one the patch actions step from in
+    /// step, whichever side changed it last.
+    fn sync_preset_name(&mut self) {
+        match self.actions.take_renamed() {
+            Some(name) => self.preset_name = name,
+            None => self.actions.set_preset_name(&self.preset_name),
+        }
+    }
+
+    /// Route a dropped mod source chip to the knob it landed on.
+    fn assign_mod(&mut self, cx: &mut EventContext, source: ModSource, ptr: ParamPtr) {
+        let mut changes = ChangeRecorder::new();
+        let result = mod_assign::assign(&self.params, source, ptr, &mut |event| {
+            changes.record(&event);
+            cx.emit(event);
+        });
+        match result {
+            Ok(line) => {
+                let summary = || format!("Mod: {line}");
+                if let Some(toast) = changes.into_toast(&self.params, "Mod", summary) {
+                    cx.emit(ToastEvent::Show(toast));
+                }
+            }
+            Err(e) => tracing::info!(target: "editor", "mod source not assigned: {e}"),
+        }
+    }
+
+    /// Broadcast the mod routes to the knobs if they changed.
+    fn sync_mod_routes(&mut self, cx: &mut EventContext) {
+        let rings = mod_assign::rings(&self.params);
+        if rings != self.mod_rings {
+            self.mod_rings = rings;
+            cx.emit_custom(
+                Event::new(ModRings(self.mod_rings.clone()))
+                    .target(Entity::root())
+                    .propagate(Propagation::Subtree),
+            );
+        }
+        let routes = mod_assign::route_lines(&self.params);
+        if routes != self.mod_routes {
+            self.mod_routes = routes;
+        }
+    }
+
+    fn morph_next(&mut self) {
+        let names = preset::list();
+        let Some(name) = actions::step_preset(&names, &self.preset_name, true) else {
+            return;
+        };
+        match preset::load(name) {
+            Ok(data) => {
+                let duration = RampDuration::Beats(MORPH_BEATS);
+                self.transition.start(&self.params, name, &data, duration);
+                tracing::info!(target: "editor", preset = %name, "morphing to preset");
+            }
+            Err(e) => tracing::warn!(target: "editor", "couldn't load '{name}': {e}"),
+        }
+    }
+}
+
+impl Model for Data {
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        // Kept from unwinding into the host's GUI loop; the editor carries on.
+        if let Err(message) = isolate::guard(|| self.handle_event(cx, event)) {
+            tracing::error!(target: "editor", "editor event panicked: {message}");
+        }
+    }
+}
+
+impl Data {
+    fn handle_event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|e, _| match e {
+            KnobEvent::DragStarted(ptr) => self.held.grab(*ptr),
+            KnobEvent::DragEnded(ptr) => self.held.release(*ptr),
+        });
+        event.map(|e, _| {
+            let amount = |slot: usize| &self.params.modulation.slots[slot].amount;
+            match e {
+                ModAssignEvent::Assign(source, ptr) => self.assign_mod(cx, *source, *ptr),
+                // A ring drag is a gesture on the slot's amount, held like a
+                // knob drag.
+                ModAssignEvent::BeginDepth(slot) => {
+                    let ptr = amount(*slot).as_ptr();
+                    self.held.grab(ptr);
+                    cx.emit(RawParamEvent::BeginSetParameter(ptr));
+                }
+                ModAssignEvent::SetDepth(slot, depth) => {
+                    let normalized = amount(*slot).preview_normalized(*depth);
+                    let ptr = amount(*slot).as_ptr();
+                    cx.emit(RawParamEvent::SetParameterNormalized(ptr, normalized));
+                }
+                ModAssignEvent::EndDepth(slot) => {
+                    let ptr = amount(*slot).as_ptr();
+                    cx.emit(RawParamEvent::EndSetParameter(ptr));
+                    self.held.release(ptr);
+                }
+                ModAssignEvent::Clear(slot) => {
+                    let destination = &self.params.modulation.slots[*slot].destination;
+                    bridge::emit_set(destination, ModDestination::Off, &mut |event| {
+                        cx.emit(event)
+                    });
+                }
+            }
+        });
+        event.map(|e, _| match e {
+            PaletteEvent::Close => self.set_palette(cx, false),
+            PaletteEvent::Watch { index, label } => {
+                self.diagnostics.watch_param(Some(*index));
+                self.watching = Some(label.clone());
+                self.set_palette(cx, false);
+            }
+            PaletteEvent::AssignXy { index, pad, axis } => {
+                let Some((id, _, _)) = self.params.param_map().into_iter().nth(*index) else {
+                    return;
+                };
+                // Half the target's range either way, nothing with the pad
+                // centred.
+                let assignment = XyAxis {
+                    param: id,
+                    min: -0.5,
+                    max: 0.5,
+                };
+                match xy::assign(&self.params, *pad, *axis, Some(assignment)) {
+                    Ok(()) => self.xy_lines = xy_lines(&self.params),
+                    Err(e) => tracing::info!(target: "editor", "XY axis not assigned: {e}"),
+                }
+                self.set_palette(cx, false);
+            }
+        });
+        event.map(|AutomationPreviewEvent::Close, _| {
+            self.diagnostics.watch_param(None);
+            self.watching = None;
+        });
+        event.map(|e, _| match e {
+            SaveDialogEvent::Saved(name) => {
+                self.preset_name = name.clone();
+                self.show_save = false;
+            }
+            SaveDialogEvent::Close => self.show_save = false,
+        });
+        event.map(|e, _| match e {
+            SpectraEvent::Rerender => self.render_spectra(cx, true),
+            SpectraEvent::Close => self.render_spectra(cx, false),
+            // Unless the overlay was closed while it rendered.
+            SpectraEvent::Rendered(spectra) => {
+                if self.spectra_pending {
+                    self.spectra_pending = false;
+                    self.spectra = Some(spectra.clone());
+                }
+            }
+        });
+        event.map(|window_event, meta| {
+            if let WindowEvent::KeyDown(code, _) = window_event {
+                if let Some(shortcut) = self.shortcuts.find(*code, cx.modifiers()) {
+                    self.run_shortcut(cx, shortcut);
+                    meta.consume();
+                }
+            }
+        });
+        event.map(|e, _| match e {
+            #[cfg(feature = "gui-extras")]
+            EditorEvent::ToggleVoiceMonitor => self.show_voice_monitor ^= true,
+            #[cfg(feature = "gui-extras")]
+            EditorEvent::ToggleLog => self.show_log ^= true,
+            EditorEvent::ToggleUsage => self.show_usage ^= true,
+            EditorEvent::SetScale(scale) => cx.set_user_scale_factor(*scale),
+            EditorEvent::TogglePalette => self.set_palette(cx, !self.show_palette),
+            EditorEvent::ToggleSave => self.show_save ^= true,
+            EditorEvent::Run(action) => self.run(cx, *action),
+            EditorEvent::MorphNext => self.morph_next(),
+            EditorEvent::Panic => self.panic(),
+            EditorEvent::ToggleLock(group) => {
+                self.locks.set(*group, !self.locks.is_locked(*group));
+                let locks = self.locks;
+                ProjectState::update(&self.params, |state| state.locks = locks);
+            }
+            EditorEvent::ToggleCollapsed(index) => {
+                self.layout.collapsed[*index] ^= true;
+                self.save_layout();
+            }
+            EditorEvent::ToggleCompact => {
+                self.layout.compact ^= true;
+                self.save_layout();
+            }
+            EditorEvent::CycleKnobDrag => {
+                self.layout.knob_drag = self.layout.knob_drag.next();
+                self.save_layout();
+                cx.emit_custom(
+                    Event::new(SetDragMode(self.layout.knob_drag))
+                        .target(Entity::root())
+                        .propagate(Propagation::Subtree),
+                );
+            }
+            EditorEvent::CopyOsc(index) => {
+                self.osc_clipboard = Some(OscSnapshot::copy(&self.params, *index));
+            }
+            EditorEvent::PasteOsc(index) => {
+                if let Some(snapshot) = &self.osc_clipboard {
+                    snapshot.paste(&self.params, *index, &mut |event| cx.emit(event));
+                }
+            }
+            EditorEvent::SwitchAb(slot) => {
+                let mut emit = |event: RawParamEvent| cx.emit(event);
+                let name = &self.preset_name;
+                if let Some(name) = self.ab.switch(&self.params, *slot, name, &mut emit) {
+                    self.preset_name = name;
+                }
+                // A and B still hold the same patches, so the spectra stand.
+            }
+            EditorEvent::ToggleSpectra => {
+                let show = self.spectra.is_none() && !self.spectra_pending;
+                self.render_spectra(cx, show);
+            }
+            EditorEvent::RestoreRecovered => {
+                if !self.recovered.is_empty() {
+                    let leftover = self.recovered.remove(0);
+                    leftover.patch.apply(&self.params, &mut |event| cx.emit(event));
+                    leftover.patch.metadata.store(&self.params);
+                    leftover.discard();
+                    self.preset_name.clear();
+                    tracing::info!(target: "editor", id = %leftover.id, "restored recovered patch");
+                }
+            }
+            EditorEvent::DiscardRecovered => {
+                if !self.recovered.is_empty() {
+                    self.recovered.remove(0).discard();
+                }
+            }
+            EditorEvent::ClearXy(pad, axis) => {
+                if let Err(e) = xy::assign(&self.params, *pad, *axis, None) {
+                    tracing::info!(target: "editor", "XY axis not cleared: {e}");
+                }
+                self.xy_lines = xy_lines(&self.params);
+            }
+            EditorEvent::PollActions => {
+                // Normally the task executor has carried these out already;
+                // this covers actions raised before the editor first opened.
+                self.sync_preset_name();
+                self.actions.carry_out(&self.params, &mut |event| cx.emit(event));
+                self.sync_preset_name();
+                let mut emit = |event: RawParamEvent| cx.emit(event);
+                self.ramps.sync_params(&self.params, &mut emit);
+                if let Some((name, metadata)) =
+                    self.transition.sync_params(Some(&self.held), &mut emit)
+                {
+                    metadata.store(&self.params);
+                    self.preset_name = name;
+                }
+                #[cfg(feature = "mcp")]
+                self.deferred.apply_released(cx, &self.params, &self.held);
+                // The AI tools lock groups too.
+                let locks = locks::current(&self.params);
+                if locks != self.locks {
+                    self.locks = locks;
+                }
+                self.sync_mod_routes(cx);
+                // The AI tools assign XY axes too.
+                let lines = xy_lines(&self.params);
+                if lines != self.xy_lines {
+                    self.xy_lines = lines;
+                }
+            }
+        });
+    }
+}
 
 pub(crate) fn default_state() -> Arc<ViziaState> {
     ViziaState::new(|| (760, 740))
@@ -80,6 +544,49 @@ const UI_STYLESHEET: &str = r#"
         alignment: center;
         width: auto;
     }
+    .header-btn {
+        background-color: #1C1C22;
+        border: 1px solid #2E3340;
+        corner-radius: 6px;
+        color: #94A3B8;
+        font-size: 9px;
+        font-weight: 700;
+        height: 24px;
+        padding-left: 10px;
+        padding-right: 10px;
+        alignment: center;
+        transition: background-color 120ms, color 120ms;
+    }
+    .header-btn:hover {
+        border-color: #6366F1;
+    }
+    .header-btn.selected {
+        background-color: #6366F1;
+        color: #F8FAFC;
+    }
+    .recovery-banner {
+        height: 32px;
+        gap: 6px;
+        padding-left: 16px;
+        padding-right: 16px;
+        alignment: center;
+        background-color: #312E81;
+    }
+    .recovery-text {
+        color: #F8FAFC;
+        font-size: 10px;
+    }
+    .preset-bar {
+        gap: 6px;
+        alignment: center;
+        width: auto;
+    }
+    .preset-name {
+        width: 110px;
+        color: #CBD5E1;
+        font-size: 10px;
+        text-align: center;
+    }
     .meter-caption {
         color: #64748B;
         font-size: 8px;
@@ -104,6 +611,35 @@ const UI_STYLESHEET: &str = r#"
         font-size: 11px;
         font-weight: 700;
     }
+    .lock-toggle {
+        background-color: #1C1C22;
+        border: 1px solid #2E3340;
+        corner-radius: 4px;
+        color: #64748B;
+        font-size: 8px;
+        font-weight: 700;
+        height: 18px;
+        padding-left: 8px;
+        padding-right: 8px;
+        alignment: center;
+    }
+    .collapse-toggle {
+        background-color: transparent;
+        border-width: 0px;
+        color: #94A3B8;
+        font-size: 11px;
+        width: 14px;
+        height: 18px;
+        alignment: center;
+    }
+    .collapse-toggle:hover {
+        color: #F8FAFC;
+    }
+    .lock-toggle.selected {
+        background-color: #F59E0B;
+        border-color: #F59E0B;
+        color: #0A0A0C;
+    }
 
     /* ---- Knobs ---- */
     .knob-stack {
@@ -188,6 +724,52 @@ const UI_STYLESHEET: &str = r#"
         background-color: #6366F1;
         color: #F8FAFC;
     }
+
+    /* ---- On/off switches (BoolParam) ---- */
+    .param-toggle {
+        width: 60px;
+        height: 26px;
+        background-color: #1C1C22;
+        border: 1px solid #2E3340;
+        corner-radius: 6px;
+        color: #94A3B8;
+        font-size: 9px;
+        font-weight: 700;
+        transition: background-color 120ms, color 120ms;
+    }
+    .param-toggle:hover {
+        border-color: #6366F1;
+    }
+    .param-toggle:checked {
+        background-color: #6366F1;
+        color: #F8FAFC;
+    }
+    /* Compact variant under the envelope stage knobs and in the oscillator
+       headers (mute / solo). */
+    .sync-toggle {
+        height: 18px;
+        font-size: 8px;
+    }
+    /* The trance gate's 16 step buttons. */
+    .gate-step {
+        width: 40px;
+        height: 36px;
+        font-size: 8px;
+    }
+"#;
+
+/// Added after the others for [`Theme::HighContrast`]: brighter text for the
+/// muted labels, and card edges that stand out from the background.
+const HIGH_CONTRAST_CSS: &str = r#"
+    .app-version, .meter-caption, .knob-label, .lock-toggle {
+        color: #CBD5E1;
+    }
+    .knob-value, .header-btn, .collapse-toggle, .counter-btn, .param-toggle {
+        color: #F1F5F9;
+    }
+    .module-card {
+        border: 1px solid #64748B;
+    }
 "#;
 
 // --- LOGIC HELPERS ---
@@ -223,6 +805,7 @@ fn filter_mode_to_str(mode: &FilterMode) -> &'static str {
         FilterMode::HighPass => "High Pass",
         FilterMode::BandPass => "Band Pass",
         FilterMode::Notch => "Notch",
+        FilterMode::Formant => "Formant",
     }
 }
 
@@ -396,6 +979,7 @@ where
                     FilterMode::HighPass,
                     FilterMode::BandPass,
                     FilterMode::Notch,
+                    FilterMode::Formant,
                 ] {
                     Button::new(cx, |cx| Label::new(cx, filter_mode_to_str(&option)))
                         .class("dropdown-option")
@@ -434,7 +1018,9 @@ where
 {
     VStack::new(cx, |cx| {
         Label::new(cx, label).class("knob-label");
+        let drag_mode = Data::layout.map(|layout| layout.knob_drag).get(cx);
         ParamKnob::new(cx, params.clone(), map)
+            .drag_mode(drag_mode)
             .size(Pixels(44.0))
             .class(accent);
         // Live, formatted value (e.g. "440 Hz", "-6.0 dB") — updates reactively
@@ -450,6 +1036,50 @@ where
     .class("knob-stack");
 }
 
+/// A labelled on/off switch for a `BoolParam`, laid out to sit in a row of
+/// [`knob_cell`]s. Uses `ParamButton`, so clicks are proper host gestures.
+fn toggle_cell<L, FMap>(cx: &mut Context, label: &str, params: L, map: FMap)
+where
+    L: Lens<Target = Arc<SineParams>> + Clone + 'static + Send + Sync,
+    FMap: Fn(&Arc<SineParams>) -> &BoolParam + Copy + Send + Sync + 'static,
+{
+    VStack::new(cx, |cx| {
+        Label::new(cx, label).class("knob-label");
+        ParamButton::new(cx, params, map).class("param-toggle");
+    })
+    .class("knob-stack");
+}
+
+/// An envelope stage (or LFO rate) knob with a tempo-sync switch under it.
+/// While synced the knob swaps to the note division; the time knob keeps its
+/// value for when sync is switched off again.
+fn synced_stage_cell<FT, FS, FD>(
+    cx: &mut Context,
+    label: &'static str,
+    accent: &'static str,
+    time: FT,
+    sync: FS,
+    division: FD,
+) where
+    FT: Fn(&Arc<SineParams>) -> &FloatParam + Copy + Send + Sync + 'static,
+    FS: Fn(&Arc<SineParams>) -> &BoolParam + Copy + Send + Sync + 'static,
+    FD: Fn(&Arc<SineParams>) -> &EnumParam<NoteDivision> + Copy + Send + Sync + 'static,
+{
+    VStack::new(cx, move |cx| {
+        Binding::new(cx, Data::params.map(move |p| sync(p).value()), move |cx, synced| {
+            if synced.get(cx) {
+                knob_cell(cx, label, accent, Data::params, division);
+            } else {
+                knob_cell(cx, label, accent, Data::params, time);
+            }
+        });
+        ParamButton::new(cx, Data::params, sync)
+            .class("param-toggle")
+            .class("sync-toggle");
+    })
+    .class("knob-stack");
+}
+
 /// A small accent bar + uppercase title used as a module header.
 fn module_header(cx: &mut Context, title: &str, accent: Color) {
     HStack::new(cx, |cx| {
@@ -463,66 +1093,533 @@ fn module_header(cx: &mut Context, title: &str, accent: Color) {
     .class("module-head");
 }
 
+/// A card's LOCK button: keeps `group` as it is through preset loads, morphs
+/// and randomize (see `locks`).
+fn lock_toggle(cx: &mut Context, group: LockGroup) {
+    Button::new(cx, |cx| Label::new(cx, "LOCK"))
+        .on_press(move |cx| cx.emit(EditorEvent::ToggleLock(group)))
+        .class("lock-toggle")
+        .toggle_class(
+            "selected",
+            Data::locks.map(move |locks| locks.is_locked(group)),
+        );
+}
+
+/// [`module_header`] with the card's LOCK button at the right.
+fn locked_module_header(cx: &mut Context, title: &str, accent: Color, group: LockGroup) {
+    HStack::new(cx, |cx| {
+        module_header(cx, title, accent);
+        Element::new(cx).width(Stretch(1.0)).height(Pixels(0.0));
+        lock_toggle(cx, group);
+    })
+    .height(Pixels(18.0))
+    .alignment(Alignment::Center);
+}
+
 /// Builds one oscillator module card. `osc` selects which of the three
 /// oscillator param groups this section drives; every knob is derived from it,
-/// so the three call sites differ only by selector and accent colour.
+/// so the three call sites differ only by selector, accent colour and index
+/// (which picks the lock group and layout slot, and gives osc1 its drawbar
+/// tab). The body follows the card's collapsed state and compact mode.
+/// `level` is the oscillator's pre-filter meter, shown in the header.
 fn create_osc_section(
     cx: &mut Context,
     title: &str,
     accent: Color,
     accent_class: &'static str,
     osc: impl Fn(&SineParams) -> &OscillatorParams + Copy + Send + Sync + 'static,
+    index: usize,
+    level: Arc<PeakMeter>,
+) {
+    let lock = [LockGroup::Osc1, LockGroup::Osc2, LockGroup::Osc3][index];
+    VStack::new(cx, |cx| {
+        HStack::new(cx, |cx| {
+            Button::new(cx, |cx| {
+                Label::new(
+                    cx,
+                    Data::layout.map(move |layout| {
+                        if layout.collapsed[index] {
+                            "▸"
+                        } else {
+                            "▾"
+                        }
+                    }),
+                )
+            })
+            .on_press(move |cx| cx.emit(EditorEvent::ToggleCollapsed(index)))
+            .class("collapse-toggle");
+            module_header(cx, title, accent);
+            Meter::new(cx, level).class("mini-meter");
+            Element::new(cx).width(Stretch(1.0)).height(Pixels(0.0));
+            ParamButton::new(cx, Data::params, move |p| &osc(p).mute)
+                .class("param-toggle")
+                .class("sync-toggle");
+            ParamButton::new(cx, Data::params, move |p| &osc(p).solo)
+                .class("param-toggle")
+                .class("sync-toggle");
+            lock_toggle(cx, lock);
+            osc_menu(cx, index);
+        })
+        .height(Pixels(18.0))
+        .gap(Pixels(6.0))
+        .alignment(Alignment::Center);
+
+        let view = Data::layout.map(move |layout| layout.osc_view(index));
+        Binding::new(cx, view, move |cx, view| match view.get(cx) {
+            OscView::Collapsed => {}
+            OscView::Compact => osc_compact_row(cx, accent_class, osc),
+            OscView::Full => osc_tabs(cx, accent_class, osc, index == 0),
+        });
+    })
+    .class("module-card");
+}
+
+/// The MIXER tab: each oscillator's level, mute, solo and filter routing side
+/// by side, with its pre-filter meter, then the noise source, the sub
+/// oscillator and the summed mix's meter. The oscillators' params are the same as on their cards,
+/// gathered for balancing the three.
+fn create_mixer(cx: &mut Context, mixer: &MixMeters) {
+    VStack::new(cx, |cx| {
+        module_header(cx, "MIXER", ColorPalette::FILTER_ACCENT);
+        HStack::new(cx, |cx| {
+            mixer_strip(
+                cx,
+                "OSC 1",
+                ColorPalette::OSC1_ACCENT,
+                ACCENT_OSC1,
+                |p| &p.osc1,
+                mixer.oscs[0].clone(),
+            );
+            mixer_strip(
+                cx,
+                "OSC 2",
+                ColorPalette::OSC2_ACCENT,
+                ACCENT_OSC2,
+                |p| &p.osc2,
+                mixer.oscs[1].clone(),
+            );
+            mixer_strip(
+                cx,
+                "OSC 3",
+                ColorPalette::OSC3_ACCENT,
+                ACCENT_OSC3,
+                |p| &p.osc3,
+                mixer.oscs[2].clone(),
+            );
+            VStack::new(cx, |cx| {
+                module_header(cx, "NOISE", ColorPalette::ENV_ACCENT);
+                knob_cell(cx, "LEVEL", ACCENT_DEFAULT, Data::params, |p| &p.noise.level);
+                knob_cell(cx, "COLOR", ACCENT_DEFAULT, Data::params, |p| &p.noise.color);
+            })
+            .gap(Pixels(8.0))
+            .alignment(Alignment::Center);
+            VStack::new(cx, |cx| {
+                module_header(cx, "SUB", ColorPalette::OSC1_ACCENT);
+                knob_cell(cx, "LEVEL", ACCENT_OSC1, Data::params, |p| &p.sub.level);
+                knob_cell(cx, "WAVE", ACCENT_OSC1, Data::params, |p| &p.sub.waveform);
+                knob_cell(cx, "OCTAVE", ACCENT_OSC1, Data::params, |p| &p.sub.octave);
+            })
+            .gap(Pixels(8.0))
+            .alignment(Alignment::Center);
+            let bus = mixer.bus.clone();
+            VStack::new(cx, move |cx| {
+                Label::new(cx, "MIX").class("meter-caption");
+                Meter::new(cx, bus.clone());
+            })
+            .gap(Pixels(8.0))
+            .alignment(Alignment::Center);
+        })
+        .gap(Pixels(24.0))
+        .alignment(Alignment::Center);
+    })
+    .class("module-card");
+}
+
+/// One oscillator's column in [`create_mixer`].
+fn mixer_strip(
+    cx: &mut Context,
+    title: &str,
+    accent: Color,
+    accent_class: &'static str,
+    osc: impl Fn(&SineParams) -> &OscillatorParams + Copy + Send + Sync + 'static,
+    level: Arc<PeakMeter>,
 ) {
     VStack::new(cx, |cx| {
         module_header(cx, title, accent);
+        Meter::new(cx, level);
+        knob_cell(cx, "LEVEL", accent_class, Data::params, move |p| &osc(p).gain);
+        HStack::new(cx, |cx| {
+            ParamButton::new(cx, Data::params, move |p| &osc(p).mute)
+                .class("param-toggle")
+                .class("sync-toggle");
+            ParamButton::new(cx, Data::params, move |p| &osc(p).solo)
+                .class("param-toggle")
+                .class("sync-toggle");
+        })
+        .height(Pixels(18.0))
+        .gap(Pixels(6.0));
+        toggle_cell(cx, "FILTER", Data::params, move |p| &osc(p).to_filter);
+    })
+    .gap(Pixels(8.0))
+    .alignment(Alignment::Center);
+}
+
+/// The XY pads, with what each axis drives. Axes are assigned from the
+/// parameter palette or by the assistant.
+fn create_perform(cx: &mut Context) {
+    VStack::new(cx, |cx| {
+        module_header(cx, "XY PADS", ColorPalette::FILTER_ACCENT);
+        HStack::new(cx, |cx| {
+            xy_pad_column(cx, 0, |p| &p.xy1);
+            xy_pad_column(cx, 1, |p| &p.xy2);
+        })
+        .gap(Pixels(24.0))
+        .alignment(Alignment::Center);
+    })
+    .class("module-card");
+}
+
+/// One pad in [`create_perform`], with a line and a CLEAR button per axis.
+fn xy_pad_column(
+    cx: &mut Context,
+    index: usize,
+    pad: impl Fn(&SineParams) -> &XyPadParams + Copy + Send + Sync + 'static,
+) {
+    VStack::new(cx, move |cx| {
+        let title = format!("XY {}", index + 1);
+        Label::new(cx, title.as_str()).class("knob-label");
+        XyPadView::new(cx, Data::params, move |p| &pad(p).x, move |p| &pad(p).y);
+        for axis in Axis::ALL {
+            let line = index * 2 + axis as usize;
+            HStack::new(cx, move |cx| {
+                let text = Data::xy_lines
+                    .map(move |lines| lines.get(line).cloned().unwrap_or_default());
+                Label::new(cx, text).class("xy-target");
+                Button::new(cx, |cx| Label::new(cx, "CLEAR"))
+                    .on_press(move |cx| cx.emit(EditorEvent::ClearXy(index, axis)))
+                    .class("header-btn");
+            })
+            .height(Pixels(20.0))
+            .gap(Pixels(6.0))
+            .alignment(Alignment::Center);
+        }
+    })
+    .width(Pixels(190.0))
+    .gap(Pixels(6.0))
+    .alignment(Alignment::Center);
+}
+
+/// What each XY pad axis drives, e.g. "X: Filter Cutoff, 20 Hz to 2 kHz".
+fn xy_lines(params: &SineParams) -> Vec<String> {
+    let assigned = xy::assignments(params);
+    (0..NUM_XY_PADS)
+        .flat_map(|pad| Axis::ALL.map(|axis| (pad, axis)))
+        .map(|(pad, axis)| {
+            let name = axis.id().to_uppercase();
+            let Some(assignment) = axis.assignment(&assigned[pad]) else {
+                return format!("{name}: unassigned");
+            };
+            let Ok(ptr) = xy::target(params, &assignment.param) else {
+                return format!("{name}: {} (missing)", assignment.param);
+            };
+            let (min, max) = xy::endpoints(ptr, assignment);
+            // SAFETY: `xy::target` only returns params inside `params`.
+            let (min, max) = unsafe {
+                (
+                    ptr.normalized_value_to_string(min, true),
+                    ptr.normalized_value_to_string(max, true),
+                )
+            };
+            let label = super::toast::param_label(params, ptr);
+            format!("{name}: {label}, {min} to {max}")
+        })
+        .collect()
+}
+
+/// The oscillator card's menu: copy this oscillator, or paste the last copy
+/// onto it.
+fn osc_menu(cx: &mut Context, index: usize) {
+    Dropdown::new(
+        cx,
+        |cx| {
+            Button::new(cx, |cx| Label::new(cx, "⋯"))
+                .class("lock-toggle")
+                .on_press(|cx| cx.emit(PopupEvent::Switch));
+        },
+        move |cx| {
+            VStack::new(cx, |cx| {
+                Button::new(cx, |cx| Label::new(cx, "Copy Osc"))
+                    .class("dropdown-option")
+                    .width(Stretch(1.0))
+                    .height(Pixels(24.0))
+                    .on_press(move |cx| {
+                        cx.emit(EditorEvent::CopyOsc(index));
+                        cx.emit(PopupEvent::Close);
+                    });
+                let copied = Data::osc_clipboard.map(Option::is_some);
+                Binding::new(cx, copied, move |cx, copied| {
+                    if copied.get(cx) {
+                        let label = format!("Paste to Osc {}", index + 1);
+                        Button::new(cx, |cx| Label::new(cx, label.as_str()))
+                            .class("dropdown-option")
+                            .width(Stretch(1.0))
+                            .height(Pixels(24.0))
+                            .on_press(move |cx| {
+                                cx.emit(EditorEvent::PasteOsc(index));
+                                cx.emit(PopupEvent::Close);
+                            });
+                    }
+                });
+            })
+            .class("dropdown-list")
+            .width(Pixels(110.0));
+        },
+    )
+    .placement(Placement::BottomEnd);
+}
+
+/// Compact mode's single row: the controls reached for most.
+fn osc_compact_row(
+    cx: &mut Context,
+    accent_class: &'static str,
+    osc: impl Fn(&SineParams) -> &OscillatorParams + Copy + Send + Sync + 'static,
+) {
+    HStack::new(cx, |cx| {
+        VStack::new(cx, |cx| {
+            Label::new(cx, "SHAPE").class("knob-label");
+            waveform_dropdown(cx, Data::params, move |p| &osc(p).waveform);
+        })
+        .class("knob-stack");
+        octave_counter(cx, Data::params, move |p| &osc(p).octave);
+        knob_cell(cx, "DETUNE", accent_class, Data::params, move |p| {
+            &osc(p).detune
+        });
+        knob_cell(cx, "VOICES", accent_class, Data::params, move |p| {
+            &osc(p).unison_voices
+        });
+        knob_cell(cx, "LEVEL", accent_class, Data::params, move |p| {
+            &osc(p).gain
+        });
+    })
+    .height(Pixels(96.0))
+    .gap(Pixels(16.0))
+    .alignment(Alignment::Center);
+}
+
+/// The full oscillator card body: waveform, unison and envelope tabs, plus the
+/// drawbars when `additive`.
+fn osc_tabs(
+    cx: &mut Context,
+    accent_class: &'static str,
+    osc: impl Fn(&SineParams) -> &OscillatorParams + Copy + Send + Sync + 'static,
+    additive: bool,
+) {
+    let mut tabs = vec![
+        TabDefinition::new("wave", "Waveform").with_width(80.0),
+        TabDefinition::new("unison", "Unison").with_width(80.0),
+        TabDefinition::new("envelope", "Envelope").with_width(80.0),
+    ];
+    if additive {
+        tabs.push(TabDefinition::new("additive", "Additive").with_width(80.0));
+    }
+    TabSwitcher::new(cx, tabs, move |cx, id, _| match id {
+        "wave" => {
+            HStack::new(cx, |cx| {
+                VStack::new(cx, |cx| {
+                    Label::new(cx, "SHAPE").class("knob-label");
+                    waveform_dropdown(cx, Data::params, move |p| &osc(p).waveform);
+                })
+                .class("knob-stack");
+                // While on, the position sweeps the shapes in place of SHAPE.
+                toggle_cell(cx, "MORPH", Data::params, move |p| &osc(p).morph);
+                knob_cell(cx, "POSITION", accent_class, Data::params, move |p| {
+                    &osc(p).morph_position
+                });
+                octave_counter(cx, Data::params, move |p| &osc(p).octave);
+                knob_cell(cx, "FREQ", accent_class, Data::params, move |p| {
+                    &osc(p).frequency
+                });
+                knob_cell(cx, "DETUNE", accent_class, Data::params, move |p| {
+                    &osc(p).detune
+                });
+                knob_cell(cx, "PHASE", accent_class, Data::params, move |p| {
+                    &osc(p).phase
+                });
+                knob_cell(cx, "PD", accent_class, Data::params, move |p| {
+                    &osc(p).phase_distortion
+                });
+                knob_cell(cx, "FOLD", accent_class, Data::params, move |p| {
+                    &osc(p).wavefold
+                });
+                knob_cell(cx, "LEVEL", accent_class, Data::params, move |p| {
+                    &osc(p).gain
+                });
+                toggle_cell(cx, "KEY TRK", Data::params, move |p| &osc(p).key_track);
+            })
+            .gap(Pixels(16.0))
+            .alignment(Alignment::Center);
+        }
+        "unison" => {
+            HStack::new(cx, |cx| {
+                knob_cell(cx, "VOICES", accent_class, Data::params, move |p| {
+                    &osc(p).unison_voices
+                });
+                knob_cell(cx, "DETUNE", accent_class, Data::params, move |p| {
+                    &osc(p).unison_detune
+                });
+                knob_cell(cx, "BLEND", accent_class, Data::params, move |p| {
+                    &osc(p).unison_blend
+                });
+                knob_cell(cx, "GAIN", accent_class, Data::params, move |p| {
+                    &osc(p).unison_volume
+                });
+                knob_cell(cx, "CURVE", accent_class, Data::params, move |p| {
+                    &osc(p).unison_curve
+                });
+                knob_cell(cx, "DENSITY", accent_class, Data::params, move |p| {
+                    &osc(p).unison_density
+                });
+                toggle_cell(cx, "GAIN COMP", Data::params, move |p| &osc(p).unison_compensation);
+            })
+            .gap(Pixels(16.0))
+            .alignment(Alignment::Center);
+        }
+        "envelope" => {
+            HStack::new(cx, |cx| {
+                toggle_cell(cx, "OWN ENV", Data::params, move |p| &osc(p).own_envelope);
+                synced_stage_cell(
+                    cx,
+                    "ATTACK",
+                    accent_class,
+                    move |p| &osc(p).envelope.attack,
+                    move |p| &osc(p).envelope.attack_sync,
+                    move |p| &osc(p).envelope.attack_division,
+                );
+                knob_cell(cx, "HOLD", accent_class, Data::params, move |p| {
+                    &osc(p).envelope.hold
+                });
+                synced_stage_cell(
+                    cx,
+                    "DECAY",
+                    accent_class,
+                    move |p| &osc(p).envelope.decay,
+                    move |p| &osc(p).envelope.decay_sync,
+                    move |p| &osc(p).envelope.decay_division,
+                );
+                knob_cell(cx, "SUSTAIN", accent_class, Data::params, move |p| {
+                    &osc(p).envelope.sustain
+                });
+                synced_stage_cell(
+                    cx,
+                    "RELEASE",
+                    accent_class,
+                    move |p| &osc(p).envelope.release,
+                    move |p| &osc(p).envelope.release_sync,
+                    move |p| &osc(p).envelope.release_division,
+                );
+            })
+            .gap(Pixels(16.0))
+            .alignment(Alignment::Center);
+        }
+        "additive" => {
+            HStack::new(cx, |cx| {
+                toggle_cell(cx, "ON", Data::params, |p| &p.additive.enabled);
+                for harmonic in 0..NUM_DRAWBARS {
+                    let label = format!("H{}", harmonic + 1);
+                    knob_cell(cx, &label, accent_class, Data::params, move |p| {
+                        &p.additive.drawbars[harmonic].level
+                    });
+                }
+            })
+            .gap(Pixels(8.0))
+            .alignment(Alignment::Center);
+        }
+        _ => {}
+    })
+    .height(Pixels(96.0));
+}
+
+/// Builds the modulation card: the global LFO and the sidechain follower on
+/// their own tabs, then each matrix slot, so the card stays one knob row tall.
+/// Then the routes, as a list, every source's live output, and the mod scope.
+/// Under the header is a chip per source to drag onto a knob (see
+/// `mod_assign`).
+fn create_mod_section(cx: &mut Context, diagnostics: Arc<Diagnostics>) {
+    VStack::new(cx, |cx| {
+        locked_module_header(
+            cx,
+            "MODULATION",
+            ColorPalette::FILTER_ACCENT,
+            LockGroup::Modulation,
+        );
+        HStack::new(cx, |cx| {
+            for (source, _) in mod_assign::SOURCES {
+                ModChip::new(cx, source);
+            }
+        })
+        .height(Pixels(18.0))
+        .gap(Pixels(6.0))
+        .alignment(Alignment::Left);
 
         let tabs = vec![
-            TabDefinition::new("wave", "Waveform").with_width(80.0),
-            TabDefinition::new("unison", "Unison").with_width(80.0),
+            TabDefinition::new("lfo", "LFO").with_width(60.0),
+            TabDefinition::new("sidechain", "SC").with_width(40.0),
+            TabDefinition::new("release", "Rel").with_width(40.0),
+            TabDefinition::new("slot1", "Slot 1").with_width(60.0),
+            TabDefinition::new("slot2", "Slot 2").with_width(60.0),
+            TabDefinition::new("slot3", "Slot 3").with_width(60.0),
+            TabDefinition::new("routes", "Routes").with_width(60.0),
+            TabDefinition::new("live", "Live").with_width(50.0),
+            TabDefinition::new("scope", "Scope").with_width(60.0),
         ];
         TabSwitcher::new(cx, tabs, move |cx, id, _| match id {
-            "wave" => {
+            "lfo" => {
                 HStack::new(cx, |cx| {
-                    VStack::new(cx, |cx| {
-                        Label::new(cx, "SHAPE").class("knob-label");
-                        waveform_dropdown(cx, Data::params, move |p| &osc(p).waveform);
-                    })
-                    .class("knob-stack");
-                    octave_counter(cx, Data::params, move |p| &osc(p).octave);
-                    knob_cell(cx, "FREQ", accent_class, Data::params, move |p| {
-                        &osc(p).frequency
-                    });
-                    knob_cell(cx, "DETUNE", accent_class, Data::params, move |p| {
-                        &osc(p).detune
+                    synced_stage_cell(
+                        cx,
+                        "RATE",
+                        ACCENT_FILTER,
+                        |p| &p.modulation.lfo_rate,
+                        |p| &p.modulation.lfo_sync,
+                        |p| &p.modulation.lfo_division,
+                    );
+                    knob_cell(cx, "SHAPE", ACCENT_FILTER, Data::params, |p| {
+                        &p.modulation.lfo_shape
                     });
-                    knob_cell(cx, "PHASE", accent_class, Data::params, move |p| {
-                        &osc(p).phase
-                    });
-                    knob_cell(cx, "LEVEL", accent_class, Data::params, move |p| {
-                        &osc(p).gain
+                    knob_cell(cx, "RESET", ACCENT_FILTER, Data::params, |p| {
+                        &p.modulation.lfo_reset
                     });
                 })
                 .gap(Pixels(16.0))
                 .alignment(Alignment::Center);
             }
-            "unison" => {
+            "sidechain" => {
                 HStack::new(cx, |cx| {
-                    knob_cell(cx, "VOICES", accent_class, Data::params, move |p| {
-                        &osc(p).unison_voices
-                    });
-                    knob_cell(cx, "DETUNE", accent_class, Data::params, move |p| {
-                        &osc(p).unison_detune
+                    knob_cell(cx, "ATTACK", ACCENT_FILTER, Data::params, |p| {
+                        &p.modulation.sidechain_attack
                     });
-                    knob_cell(cx, "BLEND", accent_class, Data::params, move |p| {
-                        &osc(p).unison_blend
+                    knob_cell(cx, "RELEASE", ACCENT_FILTER, Data::params, |p| {
+                        &p.modulation.sidechain_release
                     });
-                    knob_cell(cx, "GAIN", accent_class, Data::params, move |p| {
-                        &osc(p).unison_volume
+                })
+                .gap(Pixels(16.0))
+                .alignment(Alignment::Center);
+            }
+            "release" => {
+                HStack::new(cx, |cx| {
+                    knob_cell(cx, "BLIP", ACCENT_FILTER, Data::params, |p| {
+                        &p.modulation.release_blip
                     });
                 })
                 .gap(Pixels(16.0))
                 .alignment(Alignment::Center);
             }
+            "slot1" => mod_slot_row(cx, 0),
+            "slot2" => mod_slot_row(cx, 1),
+            "slot3" => mod_slot_row(cx, 2),
+            "routes" => mod_routes(cx),
+            "live" => super::mod_scope::source_monitor(cx, diagnostics.clone()),
+            "scope" => super::mod_scope::mod_scope(cx, diagnostics.clone()),
             _ => {}
         })
         .height(Pixels(96.0));
@@ -530,31 +1627,269 @@ fn create_osc_section(
     .class("module-card");
 }
 
+/// The Routes tab: each mod slot's route, with a button to clear it.
+fn mod_routes(cx: &mut Context) {
+    VStack::new(cx, |cx| {
+        for slot in 0..NUM_MOD_SLOTS {
+            HStack::new(cx, move |cx| {
+                let line = Data::mod_routes
+                    .map(move |routes| routes.get(slot).cloned().unwrap_or_default());
+                Label::new(cx, line).class("mod-route");
+                Button::new(cx, |cx| Label::new(cx, "CLEAR"))
+                    .on_press(move |cx| cx.emit(ModAssignEvent::Clear(slot)))
+                    .class("header-btn");
+            })
+            .height(Pixels(24.0))
+            .gap(Pixels(8.0))
+            .alignment(Alignment::Center);
+        }
+    })
+    .gap(Pixels(4.0))
+    .padding(Pixels(4.0));
+}
+
+/// The master-bus effects: stereo width, the trance gate's controls and its
+/// step pattern, one tab each.
+#[cfg(feature = "fx")]
+fn create_fx_tabs(cx: &mut Context) {
+    let tabs = vec![
+        TabDefinition::new("width", "Width").with_width(60.0),
+        TabDefinition::new("gate", "Gate").with_width(60.0),
+        TabDefinition::new("pattern", "Pattern").with_width(70.0),
+    ];
+    TabSwitcher::new(cx, tabs, |cx, id, _| match id {
+        "width" => {
+            HStack::new(cx, |cx| {
+                knob_cell(cx, "WIDTH", ACCENT_FILTER, Data::params, |p| {
+                    &p.stereo_width
+                });
+                knob_cell(cx, "X-OVER", ACCENT_FILTER, Data::params, |p| {
+                    &p.width_crossover
+                });
+            })
+            .gap(Pixels(16.0))
+            .alignment(Alignment::Center);
+        }
+        "gate" => {
+            HStack::new(cx, |cx| {
+                toggle_cell(cx, "ON", Data::params, |p| &p.gate.enabled);
+                knob_cell(cx, "STEP", ACCENT_FILTER, Data::params, |p| {
+                    &p.gate.division
+                });
+                knob_cell(cx, "DEPTH", ACCENT_FILTER, Data::params, |p| &p.gate.depth);
+                knob_cell(cx, "SMOOTH", ACCENT_FILTER, Data::params, |p| {
+                    &p.gate.smoothing
+                });
+                knob_cell(cx, "PAN", ACCENT_FILTER, Data::params, |p| &p.gate.pan);
+            })
+            .gap(Pixels(16.0))
+            .alignment(Alignment::Center);
+        }
+        "pattern" => {
+            HStack::new(cx, |cx| {
+                for step in 0..NUM_GATE_STEPS {
+                    ParamButton::new(cx, Data::params, move |p| &p.gate.steps[step].on)
+                        .class("param-toggle")
+                        .class("gate-step");
+                }
+            })
+            .gap(Pixels(3.0))
+            .alignment(Alignment::Center);
+        }
+        _ => {}
+    })
+    .height(Pixels(96.0));
+}
+
+/// The arpeggiator: its pattern settings, then where its notes go (this
+/// synth, MIDI out or both).
+fn create_arp_section(cx: &mut Context) {
+    VStack::new(cx, |cx| {
+        module_header(cx, "ARPEGGIATOR", ColorPalette::ENV_ACCENT);
+        HStack::new(cx, |cx| {
+            toggle_cell(cx, "ON", Data::params, |p| &p.arp.enabled);
+            knob_cell(cx, "MODE", ACCENT_DEFAULT, Data::params, |p| &p.arp.mode);
+            knob_cell(cx, "STEP", ACCENT_DEFAULT, Data::params, |p| {
+                &p.arp.division
+            });
+            knob_cell(cx, "OCTAVES", ACCENT_DEFAULT, Data::params, |p| {
+                &p.arp.octaves
+            });
+            knob_cell(cx, "GATE", ACCENT_DEFAULT, Data::params, |p| &p.arp.gate);
+            toggle_cell(cx, "MIDI OUT", Data::params, |p| &p.arp.midi_out);
+            toggle_cell(cx, "LOCAL", Data::params, |p| &p.arp.local);
+        })
+        .gap(Pixels(16.0))
+        .alignment(Alignment::Center);
+        HStack::new(cx, |cx| {
+            knob_cell(cx, "SWING", ACCENT_DEFAULT, Data::params, |p| &p.arp.swing);
+            knob_cell(cx, "TIMING", ACCENT_DEFAULT, Data::params, |p| {
+                &p.arp.timing_jitter
+            });
+            knob_cell(cx, "VELOCITY", ACCENT_DEFAULT, Data::params, |p| {
+                &p.arp.velocity_jitter
+            });
+            knob_cell(cx, "SEED", ACCENT_DEFAULT, Data::params, |p| &p.arp.seed);
+        })
+        .gap(Pixels(16.0))
+        .alignment(Alignment::Center);
+    })
+    .class("module-card");
+}
+
+/// Key and scale that played keys and the arp's notes snap to.
+fn create_scale_section(cx: &mut Context) {
+    VStack::new(cx, |cx| {
+        module_header(cx, "SCALE", ColorPalette::ENV_ACCENT);
+        HStack::new(cx, |cx| {
+            knob_cell(cx, "KEY", ACCENT_DEFAULT, Data::params, |p| &p.scale_root);
+            knob_cell(cx, "SCALE", ACCENT_DEFAULT, Data::params, |p| &p.scale);
+        })
+        .gap(Pixels(16.0))
+        .alignment(Alignment::Center);
+    })
+    .class("module-card");
+}
+
+/// One modulation slot's SOURCE / DEST / AMOUNT knobs.
+fn mod_slot_row(cx: &mut Context, slot: usize) {
+    HStack::new(cx, move |cx| {
+        knob_cell(cx, "SOURCE", ACCENT_FILTER, Data::params, move |p| {
+            &p.modulation.slots[slot].source
+        });
+        knob_cell(cx, "DEST", ACCENT_FILTER, Data::params, move |p| {
+            &p.modulation.slots[slot].destination
+        });
+        knob_cell(cx, "AMOUNT", ACCENT_FILTER, Data::params, move |p| {
+            &p.modulation.slots[slot].amount
+        });
+    })
+    .gap(Pixels(16.0))
+    .alignment(Alignment::Center);
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn create(
     params: Arc<SineParams>,
     peak: Arc<PeakMeter>,
+    mixer: MixMeters,
+    diagnostics: Arc<Diagnostics>,
+    actions: Arc<PatchActions>,
+    host: Arc<HostLink>,
+    ramps: Arc<ParamRamps>,
+    #[cfg(feature = "chat")] schedule: Arc<Schedule>,
+    transition: Arc<PresetTransition>,
     editor_state: Arc<ViziaState>,
 ) -> Option<Box<dyn Editor>> {
-    create_vizia_editor(editor_state, ViziaTheming::Custom, move |cx, _| {
+    create_vizia_editor(editor_state, ViziaTheming::Custom, move |cx, context| {
+        host.attach(context);
         // Register every stylesheet once here rather than per-widget-construction.
         cx.add_stylesheet(UI_STYLESHEET)
             .expect("Failed to load styles");
         cx.add_stylesheet(super::knob::KNOB_CSS).ok();
         cx.add_stylesheet(super::meter::METER_CSS).ok();
+        cx.add_stylesheet(super::mod_scope::MOD_SCOPE_CSS).ok();
         cx.add_stylesheet(super::tab_switcher::TABSWITCHER_THEME).ok();
-        cx.add_stylesheet(crate::ai::chat_ui::CHAT_STYLES).ok();
+        #[cfg(feature = "chat")]
+        cx.add_stylesheet(chat_ui::CHAT_STYLES).ok();
+        #[cfg(not(feature = "chat"))]
+        cx.add_stylesheet(super::no_assistant::NO_ASSISTANT_CSS).ok();
+        #[cfg(feature = "gui-extras")]
+        {
+            cx.add_stylesheet(super::voice_monitor::VOICE_MONITOR_CSS).ok();
+            cx.add_stylesheet(super::log_viewer::LOG_VIEWER_CSS).ok();
+        }
+        cx.add_stylesheet(super::palette::PALETTE_CSS).ok();
+        cx.add_stylesheet(super::save_dialog::SAVE_DIALOG_CSS).ok();
+        cx.add_stylesheet(super::compare::COMPARE_CSS).ok();
+        cx.add_stylesheet(super::toast::TOAST_CSS).ok();
+        cx.add_stylesheet(super::transport_bar::TRANSPORT_BAR_CSS).ok();
+        cx.add_stylesheet(super::mod_assign::MOD_ASSIGN_CSS).ok();
+        cx.add_stylesheet(super::usage_panel::USAGE_PANEL_CSS).ok();
+        cx.add_stylesheet(super::automation_preview::AUTOMATION_PREVIEW_CSS).ok();
+        cx.add_stylesheet(super::xy_pad::XY_PAD_CSS).ok();
+        let settings = Settings::load_or_create();
+        if settings.theme == Theme::HighContrast {
+            cx.add_stylesheet(HIGH_CONTRAST_CSS).ok();
+        }
 
+        let held = Arc::new(HeldParams::new());
+        #[cfg(feature = "mcp")]
+        let deferred = Arc::new(DeferredWrites::new());
+        // A reopened editor starts with the automation preview closed.
+        diagnostics.watch_param(None);
         Data {
             params: params.clone(),
+            show_voice_monitor: false,
+            show_log: false,
+            show_usage: false,
+            show_palette: false,
+            watching: None,
+            show_save: false,
+            actions: actions.clone(),
+            diagnostics: diagnostics.clone(),
+            shortcuts: KeyMap::new(&settings.shortcuts),
+            preset_name: String::new(),
+            ramps: ramps.clone(),
+            transition: transition.clone(),
+            held: held.clone(),
+            #[cfg(feature = "mcp")]
+            deferred: deferred.clone(),
+            locks: locks::current(&params),
+            layout: params
+                .project_state
+                .read()
+                .map(|state| state.editor_layout)
+                .unwrap_or_default(),
+            osc_clipboard: None,
+            ab: AbCompare::default(),
+            spectra: None,
+            spectra_pending: false,
+            recovered: recovery::leftovers(),
+            mod_rings: Vec::new(),
+            mod_routes: mod_assign::route_lines(&params),
+            xy_lines: xy_lines(&params),
         }
         .build(cx);
+        super::toast::install(cx);
+        cx.emit(EditorEvent::SetScale(settings.ui_scale));
+
+        // Trigger params are edge-detected on the audio thread; the work (file
+        // IO, param writes) happens here, as does mirroring parameter ramps.
+        let poll = cx.add_timer(ACTION_POLL, None, |cx, action| {
+            if let TimerAction::Tick(_) = action {
+                cx.emit(EditorEvent::PollActions);
+            }
+        });
+        cx.start_timer(poll);
 
-        // The AI tab's tools drive the live parameters directly.
-        let ai_params = params.clone();
+        // The AI tab's tools drive the live parameters directly and can read
+        // the engine diagnostics.
+        #[cfg(feature = "chat")]
+        let ai_tools = ToolContext {
+            params: params.clone(),
+            diagnostics: diagnostics.clone(),
+            ramps: ramps.clone(),
+            schedule: schedule.clone(),
+            transition: transition.clone(),
+            held,
+            deferred,
+        };
         let meter = peak.clone();
+        let mix_meter = mixer.bus.clone();
+        #[cfg(feature = "gui-extras")]
+        let monitor = diagnostics.clone();
+        let palette_params = params.clone();
+        let save_params = params.clone();
+        let scope = diagnostics.clone();
+        let transport = diagnostics.clone();
+        let usage = diagnostics.clone();
+        let watch = diagnostics.clone();
+        let mixer = mixer.clone();
 
         VStack::new(cx, move |cx| {
-            // Header: title block, flexible spacer, live output meter, version.
+            // Header: title block, flexible spacer, live mix and output meters,
+            // version.
             HStack::new(cx, move |cx| {
                 Label::new(cx, "TONEMORPH").class("app-title");
                 Label::new(cx, "POLY SYNTH").class("app-subtitle");
@@ -562,18 +1897,135 @@ pub(crate) fn create(
                 // Flexible spacer pushes the meter/version to the right edge.
                 Element::new(cx).width(Stretch(1.0)).height(Pixels(0.0));
 
+                // Preset stepping and randomize; the same actions the
+                // `trigger_*` params run for the host.
+                HStack::new(cx, |cx| {
+                    Button::new(cx, |cx| Label::new(cx, "◀"))
+                        .on_press(|cx| cx.emit(EditorEvent::Run(PatchAction::PrevPreset)))
+                        .class("header-btn");
+                    Label::new(
+                        cx,
+                        Data::preset_name.map(|n| {
+                            if n.is_empty() {
+                                "—".to_string()
+                            } else {
+                                n.clone()
+                            }
+                        }),
+                    )
+                    .class("preset-name");
+                    Button::new(cx, |cx| Label::new(cx, "▶"))
+                        .on_press(|cx| cx.emit(EditorEvent::Run(PatchAction::NextPreset)))
+                        .class("header-btn");
+                    Button::new(cx, |cx| Label::new(cx, "SAVE"))
+                        .on_press(|cx| cx.emit(EditorEvent::ToggleSave))
+                        .class("header-btn")
+                        .toggle_class("selected", Data::show_save);
+                    Button::new(cx, |cx| Label::new(cx, "MORPH"))
+                        .on_press(|cx| cx.emit(EditorEvent::MorphNext))
+                        .class("header-btn");
+                    Button::new(cx, |cx| Label::new(cx, "RANDOM"))
+                        .on_press(|cx| cx.emit(EditorEvent::Run(PatchAction::Randomize)))
+                        .class("header-btn");
+                    for slot in [AbSlot::A, AbSlot::B] {
+                        Button::new(cx, move |cx| Label::new(cx, slot.name()))
+                            .on_press(move |cx| cx.emit(EditorEvent::SwitchAb(slot)))
+                            .class("header-btn")
+                            .toggle_class("selected", Data::ab.map(move |ab| ab.active == slot));
+                    }
+                    Button::new(cx, |cx| Label::new(cx, "SPECTRA"))
+                        .on_press(|cx| cx.emit(EditorEvent::ToggleSpectra))
+                        .class("header-btn")
+                        .toggle_class("selected", Data::spectra.map(Option::is_some));
+                })
+                .class("preset-bar");
+
+                // The voices' sum after the filter, before the soft clip, the
+                // input blend and the master effects.
+                VStack::new(cx, move |cx| {
+                    Label::new(cx, "MIX").class("meter-caption");
+                    Meter::new(cx, mix_meter.clone());
+                })
+                .class("meter-stack");
+
                 VStack::new(cx, move |cx| {
                     Label::new(cx, "OUTPUT").class("meter-caption");
                     Meter::new(cx, meter.clone());
                 })
                 .class("meter-stack");
 
+                #[cfg(feature = "gui-extras")]
+                Button::new(cx, |cx| Label::new(cx, "VOICES"))
+                    .on_press(|cx| cx.emit(EditorEvent::ToggleVoiceMonitor))
+                    .class("header-btn")
+                    .toggle_class("selected", Data::show_voice_monitor);
+
+                Button::new(cx, |cx| Label::new(cx, "PANIC"))
+                    .on_press(|cx| cx.emit(EditorEvent::Panic))
+                    .class("header-btn");
+                ParamButton::new(cx, Data::params, |p| &p.latch).class("param-toggle");
+
+                Button::new(cx, |cx| Label::new(cx, "STATS"))
+                    .on_press(|cx| cx.emit(EditorEvent::ToggleUsage))
+                    .class("header-btn")
+                    .toggle_class("selected", Data::show_usage);
+
+                Button::new(cx, |cx| Label::new(cx, "COMPACT"))
+                    .on_press(|cx| cx.emit(EditorEvent::ToggleCompact))
+                    .class("header-btn")
+                    .toggle_class("selected", Data::layout.map(|layout| layout.compact));
+
+                Button::new(cx, |cx| {
+                    Label::new(
+                        cx,
+                        Data::layout.map(|layout| format!("KNOBS: {}", layout.knob_drag.name())),
+                    )
+                })
+                .on_press(|cx| cx.emit(EditorEvent::CycleKnobDrag))
+                .class("header-btn");
+
+                Button::new(cx, |cx| Label::new(cx, "FIND"))
+                    .on_press(|cx| cx.emit(EditorEvent::TogglePalette))
+                    .class("header-btn")
+                    .toggle_class("selected", Data::show_palette);
+
+                #[cfg(feature = "gui-extras")]
+                Button::new(cx, |cx| Label::new(cx, "LOG"))
+                    .on_press(|cx| cx.emit(EditorEvent::ToggleLog))
+                    .class("header-btn")
+                    .toggle_class("selected", Data::show_log);
+
                 Label::new(cx, "v1.0.0").class("app-version");
             })
             .class("header");
 
+            // Offers back a patch the host went down with.
+            let offered = Data::recovered.map(|recovered| {
+                recovered.first().map(|leftover| {
+                    format!("An unsaved patch from {} was recovered.", leftover.age())
+                })
+            });
+            Binding::new(cx, offered, |cx, offered| {
+                let Some(text) = offered.get(cx) else {
+                    return;
+                };
+                HStack::new(cx, |cx| {
+                    Label::new(cx, text).class("recovery-text");
+                    Element::new(cx).width(Stretch(1.0)).height(Pixels(0.0));
+                    Button::new(cx, |cx| Label::new(cx, "RESTORE"))
+                        .on_press(|cx| cx.emit(EditorEvent::RestoreRecovered))
+                        .class("header-btn");
+                    Button::new(cx, |cx| Label::new(cx, "DISCARD"))
+                        .on_press(|cx| cx.emit(EditorEvent::DiscardRecovered))
+                        .class("header-btn");
+                })
+                .class("recovery-banner");
+            });
+
             let main_tabs = vec![
                 TabDefinition::new("oscillators", "OSCILLATORS"),
+                TabDefinition::new("mixer", "MIXER"),
+                TabDefinition::new("perform", "PERFORM"),
                 TabDefinition::new("envelope", "ENVELOPE"),
                 TabDefinition::new("filters_fx", "FILTER & FX"),
                 TabDefinition::new("ai", "AI ASSIST"),
@@ -589,6 +2041,8 @@ pub(crate) fn create(
                                 ColorPalette::OSC1_ACCENT,
                                 ACCENT_OSC1,
                                 |p| &p.osc1,
+                                0,
+                                mixer.oscs[0].clone(),
                             );
                             create_osc_section(
                                 cx,
@@ -596,6 +2050,8 @@ pub(crate) fn create(
                                 ColorPalette::OSC2_ACCENT,
                                 ACCENT_OSC2,
                                 |p| &p.osc2,
+                                1,
+                                mixer.oscs[1].clone(),
                             );
                             create_osc_section(
                                 cx,
@@ -603,14 +2059,23 @@ pub(crate) fn create(
                                 ColorPalette::OSC3_ACCENT,
                                 ACCENT_OSC3,
                                 |p| &p.osc3,
+                                2,
+                                mixer.oscs[2].clone(),
                             );
                         })
                         .gap(Pixels(12.0));
                     }
+                    "mixer" => create_mixer(cx, &mixer),
+                    "perform" => create_perform(cx),
                     "filters_fx" => {
                         VStack::new(cx, |cx| {
                             VStack::new(cx, |cx| {
-                                module_header(cx, "FILTER ENGINE", ColorPalette::FILTER_ACCENT);
+                                locked_module_header(
+                                    cx,
+                                    "FILTER ENGINE",
+                                    ColorPalette::FILTER_ACCENT,
+                                    LockGroup::Filter,
+                                );
                                 HStack::new(cx, |cx| {
                                     VStack::new(cx, |cx| {
                                         Label::new(cx, "MODE").class("knob-label");
@@ -623,21 +2088,108 @@ pub(crate) fn create(
                                     knob_cell(cx, "RES", ACCENT_FILTER, Data::params, |p| {
                                         &p.filter.resonance
                                     });
+                                    knob_cell(cx, "VOWEL", ACCENT_FILTER, Data::params, |p| {
+                                        &p.filter.vowel
+                                    });
                                     knob_cell(cx, "DRIVE", ACCENT_FILTER, Data::params, |p| {
                                         &p.filter.drive
                                     });
+                                    knob_cell(cx, "CHAR", ACCENT_FILTER, Data::params, |p| {
+                                        &p.filter.drive_character
+                                    });
+                                    toggle_cell(cx, "AUTO GAIN", Data::params, |p| {
+                                        &p.filter.drive_compensation
+                                    });
+                                    knob_cell(cx, "INPUT", ACCENT_FILTER, Data::params, |p| {
+                                        &p.input_mix
+                                    });
                                 })
                                 .gap(Pixels(16.0))
                                 .alignment(Alignment::Center);
                             })
                             .class("module-card");
 
+                            // Locked with the filter.
                             VStack::new(cx, |cx| {
-                                module_header(cx, "POST-PROCESS FX", ColorPalette::FILTER_ACCENT);
-                                Element::new(cx)
-                                    .height(Pixels(60.0))
-                                    .background_color(ColorPalette::BG_CARD_ALT)
-                                    .corner_radius(Pixels(6.0));
+                                module_header(cx, "COMB", ColorPalette::FILTER_ACCENT);
+                                HStack::new(cx, |cx| {
+                                    toggle_cell(cx, "ON", Data::params, |p| &p.comb.enabled);
+                                    toggle_cell(cx, "PLUCK", Data::params, |p| &p.comb.pluck);
+                                    knob_cell(cx, "FEEDBACK", ACCENT_FILTER, Data::params, |p| {
+                                        &p.comb.feedback
+                                    });
+                                    knob_cell(cx, "DAMP", ACCENT_FILTER, Data::params, |p| {
+                                        &p.comb.damping
+                                    });
+                                    knob_cell(cx, "MIX", ACCENT_FILTER, Data::params, |p| {
+                                        &p.comb.mix
+                                    });
+                                })
+                                .gap(Pixels(16.0))
+                                .alignment(Alignment::Center);
+                            })
+                            .class("module-card");
+
+                            create_mod_section(cx, scope.clone());
+
+                            VStack::new(cx, |cx| {
+                                locked_module_header(
+                                    cx,
+                                    "CHARACTER",
+                                    ColorPalette::FILTER_ACCENT,
+                                    LockGroup::Character,
+                                );
+                                HStack::new(cx, |cx| {
+                                    // The Color macro, then its reach into
+                                    // each stage.
+                                    knob_cell(cx, "COLOR", ACCENT_FILTER, Data::params, |p| {
+                                        &p.color.amount
+                                    });
+                                    knob_cell(cx, "TO CUTOFF", ACCENT_FILTER, Data::params, |p| {
+                                        &p.color.cutoff
+                                    });
+                                    knob_cell(cx, "TO DRIVE", ACCENT_FILTER, Data::params, |p| {
+                                        &p.color.drive
+                                    });
+                                    knob_cell(cx, "TO SHAPE", ACCENT_FILTER, Data::params, |p| {
+                                        &p.color.shape
+                                    });
+                                    knob_cell(cx, "TO BLEND", ACCENT_FILTER, Data::params, |p| {
+                                        &p.color.blend
+                                    });
+                                    knob_cell(cx, "ANALOG", ACCENT_FILTER, Data::params, |p| {
+                                        &p.analog
+                                    });
+                                    knob_cell(cx, "KEY PAN", ACCENT_FILTER, Data::params, |p| {
+                                        &p.keyboard_pan
+                                    });
+                                    knob_cell(cx, "SMOOTHING", ACCENT_FILTER, Data::params, |p| {
+                                        &p.smoothing
+                                    });
+                                    toggle_cell(cx, "AUTO QUALITY", Data::params, |p| {
+                                        &p.auto_quality
+                                    });
+                                    toggle_cell(cx, "BAND-LIMIT", Data::params, |p| {
+                                        &p.band_limited
+                                    });
+                                    toggle_cell(cx, "PROG CHANGE", Data::params, |p| {
+                                        &p.program_change
+                                    });
+                                })
+                                .gap(Pixels(16.0))
+                                .alignment(Alignment::Center);
+                            })
+                            .class("module-card");
+
+                            #[cfg(feature = "fx")]
+                            VStack::new(cx, |cx| {
+                                locked_module_header(
+                                    cx,
+                                    "POST-PROCESS FX",
+                                    ColorPalette::FILTER_ACCENT,
+                                    LockGroup::Fx,
+                                );
+                                create_fx_tabs(cx);
                             })
                             .class("module-card");
                         })
@@ -646,65 +2198,143 @@ pub(crate) fn create(
                     "envelope" => {
                         VStack::new(cx, |cx| {
                             VStack::new(cx, |cx| {
-                                module_header(
+                                locked_module_header(
                                     cx,
                                     "AMPLITUDE ENVELOPE",
                                     ColorPalette::ENV_ACCENT,
+                                    LockGroup::AmpEnvelope,
                                 );
                                 HStack::new(cx, |cx| {
-                                    knob_cell(cx, "ATTACK", ACCENT_DEFAULT, Data::params, |p| {
-                                        &p.adsr.attack
-                                    });
-                                    knob_cell(cx, "DECAY", ACCENT_DEFAULT, Data::params, |p| {
-                                        &p.adsr.decay
+                                    synced_stage_cell(
+                                        cx,
+                                        "ATTACK",
+                                        ACCENT_DEFAULT,
+                                        |p| &p.adsr.attack,
+                                        |p| &p.adsr.attack_sync,
+                                        |p| &p.adsr.attack_division,
+                                    );
+                                    knob_cell(cx, "HOLD", ACCENT_DEFAULT, Data::params, |p| {
+                                        &p.adsr.hold
                                     });
+                                    synced_stage_cell(
+                                        cx,
+                                        "DECAY",
+                                        ACCENT_DEFAULT,
+                                        |p| &p.adsr.decay,
+                                        |p| &p.adsr.decay_sync,
+                                        |p| &p.adsr.decay_division,
+                                    );
                                     knob_cell(cx, "SUSTAIN", ACCENT_DEFAULT, Data::params, |p| {
                                         &p.adsr.sustain
                                     });
-                                    knob_cell(cx, "RELEASE", ACCENT_DEFAULT, Data::params, |p| {
-                                        &p.adsr.release
+                                    synced_stage_cell(
+                                        cx,
+                                        "RELEASE",
+                                        ACCENT_DEFAULT,
+                                        |p| &p.adsr.release,
+                                        |p| &p.adsr.release_sync,
+                                        |p| &p.adsr.release_division,
+                                    );
+                                    knob_cell(cx, "REL VEL", ACCENT_DEFAULT, Data::params, |p| {
+                                        &p.release_velocity
                                     });
+                                    toggle_cell(cx, "DECLICK", Data::params, |p| &p.declick);
                                 })
                                 .gap(Pixels(16.0))
                                 .alignment(Alignment::Center);
                             })
                             .class("module-card");
 
-                            // Filter envelope: same ADSR shape, plus a bipolar
+                            // Filter envelope: same AHDSR shape, plus a bipolar
                             // AMOUNT (octaves) that sets how far it sweeps the
                             // cutoff. AMOUNT = 0 leaves the filter static.
                             VStack::new(cx, |cx| {
-                                module_header(
+                                locked_module_header(
                                     cx,
                                     "FILTER ENVELOPE",
                                     ColorPalette::FILTER_ACCENT,
+                                    LockGroup::FilterEnvelope,
                                 );
                                 HStack::new(cx, |cx| {
                                     knob_cell(cx, "AMOUNT", ACCENT_FILTER, Data::params, |p| {
                                         &p.filter.env_amount
                                     });
-                                    knob_cell(cx, "ATTACK", ACCENT_FILTER, Data::params, |p| {
-                                        &p.filter_env.attack
-                                    });
-                                    knob_cell(cx, "DECAY", ACCENT_FILTER, Data::params, |p| {
-                                        &p.filter_env.decay
+                                    synced_stage_cell(
+                                        cx,
+                                        "ATTACK",
+                                        ACCENT_FILTER,
+                                        |p| &p.filter_env.attack,
+                                        |p| &p.filter_env.attack_sync,
+                                        |p| &p.filter_env.attack_division,
+                                    );
+                                    knob_cell(cx, "HOLD", ACCENT_FILTER, Data::params, |p| {
+                                        &p.filter_env.hold
                                     });
+                                    synced_stage_cell(
+                                        cx,
+                                        "DECAY",
+                                        ACCENT_FILTER,
+                                        |p| &p.filter_env.decay,
+                                        |p| &p.filter_env.decay_sync,
+                                        |p| &p.filter_env.decay_division,
+                                    );
                                     knob_cell(cx, "SUSTAIN", ACCENT_FILTER, Data::params, |p| {
                                         &p.filter_env.sustain
                                     });
-                                    knob_cell(cx, "RELEASE", ACCENT_FILTER, Data::params, |p| {
-                                        &p.filter_env.release
+                                    synced_stage_cell(
+                                        cx,
+                                        "RELEASE",
+                                        ACCENT_FILTER,
+                                        |p| &p.filter_env.release,
+                                        |p| &p.filter_env.release_sync,
+                                        |p| &p.filter_env.release_division,
+                                    );
+                                })
+                                .gap(Pixels(16.0))
+                                .alignment(Alignment::Center);
+                            })
+                            .class("module-card");
+
+                            VStack::new(cx, |cx| {
+                                locked_module_header(
+                                    cx,
+                                    "GLIDE & BEND",
+                                    ColorPalette::ENV_ACCENT,
+                                    LockGroup::Glide,
+                                );
+                                HStack::new(cx, |cx| {
+                                    knob_cell(cx, "TIME", ACCENT_DEFAULT, Data::params, |p| {
+                                        &p.glide.time
+                                    });
+                                    knob_cell(cx, "MODE", ACCENT_DEFAULT, Data::params, |p| {
+                                        &p.glide.mode
+                                    });
+                                    toggle_cell(cx, "LEGATO", Data::params, |p| &p.glide.legato);
+                                    knob_cell(cx, "BEND", ACCENT_DEFAULT, Data::params, |p| {
+                                        &p.bend.range
+                                    });
+                                    knob_cell(cx, "BEND SMOOTH", ACCENT_DEFAULT, Data::params, |p| {
+                                        &p.bend.smoothing
+                                    });
+                                    knob_cell(cx, "BEND REL", ACCENT_DEFAULT, Data::params, |p| {
+                                        &p.bend.release
                                     });
                                 })
                                 .gap(Pixels(16.0))
                                 .alignment(Alignment::Center);
                             })
                             .class("module-card");
+
+                            create_arp_section(cx);
+                            create_scale_section(cx);
                         })
                         .gap(Pixels(12.0));
                     }
                     "ai" => {
-                        crate::ai::chat_ui::chat_panel(cx, ai_params.clone());
+                        #[cfg(feature = "chat")]
+                        chat_ui::chat_panel(cx, ai_tools.clone());
+                        #[cfg(not(feature = "chat"))]
+                        super::no_assistant::no_assistant(cx);
                     }
                     _ => {}
                 })
@@ -712,6 +2342,52 @@ pub(crate) fn create(
             })
             .width(Stretch(1.0))
             .height(Stretch(1.0));
+
+            super::transport_bar::transport_bar(cx, transport.clone());
+
+            // Debug overlays, floated under the header's VOICES and LOG buttons.
+            #[cfg(feature = "gui-extras")]
+            {
+                Binding::new(cx, Data::show_voice_monitor, move |cx, show| {
+                    if show.get(cx) {
+                        super::voice_monitor::voice_monitor(cx, monitor.clone());
+                    }
+                });
+                Binding::new(cx, Data::show_log, |cx, show| {
+                    if show.get(cx) {
+                        super::log_viewer::log_viewer(cx);
+                    }
+                });
+            }
+            Binding::new(cx, Data::show_usage, move |cx, show| {
+                if show.get(cx) {
+                    super::usage_panel::usage_panel(cx, usage.clone());
+                }
+            });
+            Binding::new(cx, Data::watching, move |cx, watching| {
+                if let Some(label) = watching.get(cx) {
+                    super::automation_preview::automation_preview(cx, watch.clone(), label);
+                }
+            });
+            Binding::new(cx, Data::show_palette, move |cx, show| {
+                if show.get(cx) {
+                    super::palette::palette(cx, palette_params.clone());
+                }
+            });
+            Binding::new(cx, Data::show_save, move |cx, show| {
+                if show.get(cx) {
+                    let name = Data::preset_name.get(cx);
+                    super::save_dialog::save_dialog(cx, save_params.clone(), name);
+                }
+            });
+            Binding::new(cx, Data::spectra, |cx, spectra| {
+                if let Some(spectra) = spectra.get(cx) {
+                    super::compare::spectra_overlay(cx, spectra);
+                }
+            });
+
+            // Notices for changes made by the AI tools or the schedule.
+            super::toast::toast_overlay(cx);
         })
         .class("root");
     })
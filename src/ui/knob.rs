@@ -9,12 +9,34 @@
 //!
 //! Colours come from CSS: `background-color` drives the track, `color` drives
 //! the accent (so each oscillator can tint its knobs via an `accent-*` class).
+//!
+//! How a drag maps to the value is a [`KnobDragMode`]: vertical (the cursor
+//! hides and returns to where the drag started, like host knobs), circular
+//! (the value follows the cursor's angle) or relative-circular (circling turns
+//! it from where it was). Shift refines every mode; in vertical mode so does
+//! moving sideways away from the knob, which touch screens can do without a
+//! modifier key.
+//!
+//! Stepped params (ints and enums) get detents: drags snap to valid values and
+//! move a step every [`STEP_PIXELS`] at most, a scroll line is one step, and
+//! the track shows a tick per value when there are few enough to tell apart.
+//!
+//! Drags are also announced as [`KnobEvent`]s, so an ancestor can tell when
+//! the user is holding a param (see `ai::conflicts`). A [`HighlightParam`]
+//! sent down the tree lights up the knob for that param (see `palette`).
+//!
+//! A knob the mod matrix routes to draws the route's depth as a thin ring
+//! from its value; dragging on the ring (outside the body) sets the depth, and
+//! a mod source chip can be dropped on it (see `mod_assign`).
 
-use nih_plug::prelude::Param;
+use nih_plug::prelude::{Param, ParamPtr};
+use serde::{Deserialize, Serialize};
 use vizia_plug::vizia::prelude::*;
 use vizia_plug::vizia::vg;
 use vizia_plug::widgets::param_base::ParamWidgetBase;
 
+use super::mod_assign::{DropSource, ModAssignEvent, ModRings};
+
 pub const KNOB_CSS: &str = r#"
     .param-knob {
         width: 44px;
@@ -37,18 +59,88 @@ pub const KNOB_CSS: &str = r#"
 const ARC_START: f32 = 135.0;
 const ARC_SWEEP: f32 = 270.0;
 
+/// Sideways drag distance that halves vertical-mode sensitivity.
+const SCRUB_PIXELS: f32 = 60.0;
+/// Relative-circular sensitivity while Shift is held.
+const FINE_CIRCULAR: f32 = 0.15;
+
+/// Drag distance per step on stepped knobs with few steps; knobs with many
+/// steps keep the continuous sensitivity.
+const STEP_PIXELS: f32 = 12.0;
+/// Most values a stepped knob draws ticks for.
+const MAX_TICKS: usize = 16;
+
+/// Emitted (bubbling up) when a drag on a knob starts and ends.
+pub enum KnobEvent {
+    DragStarted(ParamPtr),
+    DragEnded(ParamPtr),
+}
+
+/// How dragging a knob changes its value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KnobDragMode {
+    /// Up/down changes the value; the cursor hides and goes back to where
+    /// the drag started.
+    #[default]
+    Vertical,
+    /// The value points at the cursor.
+    Circular,
+    /// Circling the knob turns it from its current value.
+    RelativeCircular,
+}
+
+impl KnobDragMode {
+    pub const ALL: [KnobDragMode; 3] = [Self::Vertical, Self::Circular, Self::RelativeCircular];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Vertical => "VERTICAL",
+            Self::Circular => "CIRCULAR",
+            Self::RelativeCircular => "REL. CIRCULAR",
+        }
+    }
+
+    /// The next mode, for a button that cycles through them.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&mode| mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// Broadcast to the whole tree: every knob switches to this drag mode.
+pub struct SetDragMode(pub KnobDragMode);
+
+/// Broadcast to the whole tree: the knob for this param draws its glow until
+/// the next one. `None` clears it.
+pub struct HighlightParam(pub Option<ParamPtr>);
+
 #[derive(Lens)]
 pub struct ParamKnob {
     param_base: ParamWidgetBase,
+    param_ptr: ParamPtr,
+    /// Number of steps for ints and enums (one less than the number of
+    /// values); `None` for continuous params.
+    steps: Option<usize>,
     hovered: bool,
+    highlighted: bool,
+    drag_mode: KnobDragMode,
     drag_active: bool,
+    /// Where the drag started, in window coordinates.
+    drag_origin: (f32, f32),
     drag_start_y: f32,
+    /// Cursor angle around the knob at the last move, in degrees.
+    drag_angle: f32,
     /// Running normalized value for the active drag. `set_normalized_value` only
     /// *emits* a deferred `RawParamEvent`, so reading the param back on the next
     /// `MouseMove` can see a stale value when baseview batches several moves into
     /// one event pass. We accumulate locally instead and never read back mid-drag.
     drag_value: f32,
     scrolled_lines: f32,
+    /// The mod slot routed to this knob and its depth, from [`ModRings`].
+    mod_ring: Option<(usize, f32)>,
+    /// Whether the active drag sets the ring's depth rather than the value.
+    ring_drag: bool,
 }
 
 impl ParamKnob {
@@ -64,14 +156,31 @@ impl ParamKnob {
         FMap: Fn(&Params) -> &P + Copy + 'static,
     {
         let param_base = ParamWidgetBase::new(cx, params.clone(), params_to_param);
+        let param_ptr = params
+            .clone()
+            .map(move |p| params_to_param(p).as_ptr())
+            .get(cx);
+        let steps = params
+            .clone()
+            .map(move |p| params_to_param(p).step_count())
+            .get(cx)
+            .filter(|&steps| steps > 0);
 
         let mut handle = Self {
             param_base,
+            param_ptr,
+            steps,
             hovered: false,
+            highlighted: false,
+            drag_mode: KnobDragMode::default(),
             drag_active: false,
+            drag_origin: (0.0, 0.0),
             drag_start_y: 0.0,
+            drag_angle: 0.0,
             drag_value: 0.0,
             scrolled_lines: 0.0,
+            mod_ring: None,
+            ring_drag: false,
         }
         .build(cx, |_| {})
         .class("param-knob");
@@ -92,6 +201,68 @@ impl ParamKnob {
     }
 }
 
+/// Modifiers for a [`ParamKnob`]'s handle.
+pub trait ParamKnobModifiers {
+    fn drag_mode(self, mode: KnobDragMode) -> Self;
+}
+
+impl ParamKnobModifiers for Handle<'_, ParamKnob> {
+    fn drag_mode(self, mode: KnobDragMode) -> Self {
+        self.modify(|knob| knob.drag_mode = mode)
+    }
+}
+
+/// Value for a cursor at `angle` degrees around the knob, clamped to the
+/// nearer end when it points into the gap at the bottom.
+fn angle_to_value(angle: f32) -> f32 {
+    let along = (angle - ARC_START).rem_euclid(360.0);
+    if along <= ARC_SWEEP {
+        along / ARC_SWEEP
+    } else if along < ARC_SWEEP + (360.0 - ARC_SWEEP) * 0.5 {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+impl ParamKnob {
+    /// Angle of `(x, y)` around the knob's centre, in Skia degrees.
+    fn cursor_angle(cx: &EventContext, x: f32, y: f32) -> f32 {
+        let bounds = cx.bounds();
+        let dx = x - (bounds.x + bounds.w * 0.5);
+        let dy = y - (bounds.y + bounds.h * 0.5);
+        dy.atan2(dx).to_degrees()
+    }
+
+    /// Whether `(x, y)` is on the track ring rather than the body.
+    fn on_ring(cx: &EventContext, x: f32, y: f32) -> bool {
+        let bounds = cx.bounds();
+        let dx = x - (bounds.x + bounds.w * 0.5);
+        let dy = y - (bounds.y + bounds.h * 0.5);
+        let size = bounds.w.min(bounds.h);
+        let stroke = (size * 0.11).max(2.5);
+        let body_radius = size * 0.5 - stroke * 1.45 - 1.0;
+        dx.hypot(dy) > body_radius
+    }
+
+    /// Round `value` to the nearest detent on stepped knobs.
+    fn snap(&self, value: f32) -> f32 {
+        match self.steps {
+            Some(steps) => (value * steps as f32).round() / steps as f32,
+            None => value,
+        }
+    }
+
+    /// Normalized change per pixel of drag.
+    fn drag_sensitivity(&self, fine: bool) -> f32 {
+        let continuous = if fine { 0.0008 } else { 0.005 };
+        match self.steps {
+            Some(steps) if !fine => continuous.max(1.0 / (steps as f32 * STEP_PIXELS)),
+            _ => continuous,
+        }
+    }
+}
+
 impl View for ParamKnob {
     fn element(&self) -> Option<&'static str> {
         Some("param-knob")
@@ -120,7 +291,7 @@ impl View for ParamKnob {
         let value_sweep = ARC_SWEEP * self.param_base.modulated_normalized_value().clamp(0.0, 1.0);
 
         // Hover glow — a soft accent ring drawn behind everything.
-        if self.hovered {
+        if self.hovered || self.highlighted {
             let mut glow = vg::Paint::default();
             glow.set_anti_alias(true);
             glow.set_style(vg::PaintStyle::Stroke);
@@ -153,6 +324,40 @@ impl View for ParamKnob {
             canvas.draw_arc(oval, ARC_START, value_sweep, false, &value_paint);
         }
 
+        // Mod depth: a thin light arc from the value, over the track.
+        if let Some((_, amount)) = self.mod_ring {
+            let end = (value_sweep + amount * ARC_SWEEP).clamp(0.0, ARC_SWEEP);
+            if end != value_sweep {
+                let mut ring = vg::Paint::default();
+                ring.set_anti_alias(true);
+                ring.set_style(vg::PaintStyle::Stroke);
+                ring.set_stroke_width(stroke * 0.4);
+                ring.set_stroke_cap(vg::PaintCap::Round);
+                ring.set_color(vg::Color::from_argb(220, 216, 180, 254));
+                ring.set_alpha_f(ring.alpha_f() * opacity);
+                let sweep = end - value_sweep;
+                canvas.draw_arc(oval, ARC_START + value_sweep, sweep, false, &ring);
+            }
+        }
+
+        // Detent ticks: notches in the track, one per value.
+        if let Some(steps) = self.steps.filter(|&steps| steps < MAX_TICKS) {
+            let mut tick = vg::Paint::default();
+            tick.set_anti_alias(true);
+            tick.set_style(vg::PaintStyle::Fill);
+            tick.set_color(vg::Color::from_argb(255, 26, 26, 32));
+            tick.set_alpha_f(opacity);
+            for step in 0..=steps {
+                let angle = (ARC_START + ARC_SWEEP * step as f32 / steps as f32).to_radians();
+                let (sin, cos) = angle.sin_cos();
+                canvas.draw_circle(
+                    (cx0 + cos * radius, cy0 + sin * radius),
+                    stroke * 0.2,
+                    &tick,
+                );
+            }
+        }
+
         // Recessed knob body.
         let body_radius = radius - stroke * 0.95;
         if body_radius > 0.0 {
@@ -193,6 +398,25 @@ impl View for ParamKnob {
     }
 
     fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|HighlightParam(ptr), _| {
+            self.highlighted = *ptr == Some(self.param_ptr);
+            cx.needs_redraw();
+        });
+        event.map(|SetDragMode(mode), _| self.drag_mode = *mode);
+        event.map(|ModRings(rings), _| {
+            let ring = rings
+                .iter()
+                .find(|(ptr, _, _)| *ptr == self.param_ptr)
+                .map(|&(_, slot, amount)| (slot, amount));
+            if ring != self.mod_ring {
+                self.mod_ring = ring;
+                cx.needs_redraw();
+            }
+        });
+        event.map(|DropSource(source), meta| {
+            cx.emit(ModAssignEvent::Assign(*source, self.param_ptr));
+            meta.consume();
+        });
         event.map(|window_event, meta| match window_event {
             WindowEvent::MouseEnter => {
                 self.hovered = true;
@@ -202,31 +426,100 @@ impl View for ParamKnob {
                 self.hovered = false;
                 cx.needs_redraw();
             }
-            WindowEvent::MouseDown(MouseButton::Left) => {
+            WindowEvent::MouseDown(MouseButton::Left)
+                if self.mod_ring.is_some()
+                    && Self::on_ring(cx, cx.mouse().cursor_x, cx.mouse().cursor_y) =>
+            {
+                let Some((slot, amount)) = self.mod_ring else {
+                    return;
+                };
                 cx.capture();
                 cx.set_active(true);
                 self.drag_start_y = cx.mouse().cursor_y;
+                self.drag_value = amount;
+                self.ring_drag = true;
+                cx.emit(ModAssignEvent::BeginDepth(slot));
+                meta.consume();
+            }
+            WindowEvent::MouseMove(_, y) if self.ring_drag => {
+                if let Some((slot, _)) = self.mod_ring {
+                    // The depth spans -1..1, twice a value's range.
+                    let fine = cx.modifiers().shift();
+                    let delta = (self.drag_start_y - y) * self.drag_sensitivity(fine) * 2.0;
+                    self.drag_value = (self.drag_value + delta).clamp(-1.0, 1.0);
+                    self.drag_start_y = *y;
+                    cx.emit(ModAssignEvent::SetDepth(slot, self.drag_value));
+                }
+                meta.consume();
+            }
+            WindowEvent::MouseUp(MouseButton::Left) if self.ring_drag => {
+                cx.release();
+                cx.set_active(false);
+                self.ring_drag = false;
+                if let Some((slot, _)) = self.mod_ring {
+                    cx.emit(ModAssignEvent::EndDepth(slot));
+                }
+                meta.consume();
+            }
+            WindowEvent::MouseDown(MouseButton::Left) => {
+                cx.capture();
+                cx.set_active(true);
+                let (x, y) = (cx.mouse().cursor_x, cx.mouse().cursor_y);
+                self.drag_origin = (x, y);
+                self.drag_start_y = y;
+                self.drag_angle = Self::cursor_angle(cx, x, y);
                 self.drag_active = true;
                 // Snapshot the value once; the drag accumulates onto this locally
                 // so it never depends on the deferred param write being applied.
                 self.drag_value = self.param_base.unmodulated_normalized_value();
                 self.param_base.begin_set_parameter(cx);
+                match self.drag_mode {
+                    KnobDragMode::Vertical => cx.emit(WindowEvent::SetCursor(CursorIcon::None)),
+                    KnobDragMode::Circular => {
+                        self.drag_value = angle_to_value(self.drag_angle);
+                        self.param_base
+                            .set_normalized_value(cx, self.snap(self.drag_value));
+                    }
+                    KnobDragMode::RelativeCircular => {}
+                }
+                cx.emit(KnobEvent::DragStarted(self.param_ptr));
                 meta.consume();
             }
-            WindowEvent::MouseMove(_, y) if self.drag_active => {
-                let drag_delta = self.drag_start_y - y;
+            WindowEvent::MouseMove(x, y) if self.drag_active => {
                 // Finer control while holding Shift.
-                let sensitivity = if cx.modifiers().shift() { 0.0008 } else { 0.005 };
-                self.drag_value = (self.drag_value + drag_delta * sensitivity).clamp(0.0, 1.0);
+                let fine = cx.modifiers().shift();
+                self.drag_value = match self.drag_mode {
+                    KnobDragMode::Vertical => {
+                        let drag_delta = self.drag_start_y - y;
+                        let scrub = 1.0 + (x - self.drag_origin.0).abs() / SCRUB_PIXELS;
+                        self.drag_value + drag_delta * self.drag_sensitivity(fine) / scrub
+                    }
+                    KnobDragMode::Circular => angle_to_value(Self::cursor_angle(cx, *x, *y)),
+                    KnobDragMode::RelativeCircular => {
+                        let angle = Self::cursor_angle(cx, *x, *y);
+                        let turned = (angle - self.drag_angle + 180.0).rem_euclid(360.0) - 180.0;
+                        self.drag_angle = angle;
+                        let scale = if fine { FINE_CIRCULAR } else { 1.0 };
+                        self.drag_value + turned / ARC_SWEEP * scale
+                    }
+                }
+                .clamp(0.0, 1.0);
                 self.drag_start_y = *y;
-                self.param_base.set_normalized_value(cx, self.drag_value);
+                self.param_base
+                    .set_normalized_value(cx, self.snap(self.drag_value));
                 meta.consume();
             }
             WindowEvent::MouseUp(MouseButton::Left) if self.drag_active => {
                 cx.release();
                 cx.set_active(false);
+                if self.drag_mode == KnobDragMode::Vertical {
+                    let (x, y) = self.drag_origin;
+                    cx.emit(WindowEvent::SetCursorPosition(x as u32, y as u32));
+                    cx.emit(WindowEvent::SetCursor(CursorIcon::Hand));
+                }
                 self.drag_active = false;
                 self.param_base.end_set_parameter(cx);
+                cx.emit(KnobEvent::DragEnded(self.param_ptr));
                 meta.consume();
             }
             WindowEvent::MouseDoubleClick(MouseButton::Left) => {
@@ -241,7 +534,11 @@ impl View for ParamKnob {
                 if self.scrolled_lines.abs() >= 1.0 {
                     self.param_base.begin_set_parameter(cx);
                     let current_value = self.param_base.unmodulated_normalized_value();
-                    let scroll_sensitivity = 0.02;
+                    // One detent per line on stepped knobs.
+                    let scroll_sensitivity = match self.steps {
+                        Some(steps) => 1.0 / steps as f32,
+                        None => 0.02,
+                    };
                     let new_value = if self.scrolled_lines >= 1.0 {
                         self.scrolled_lines -= 1.0;
                         (current_value + scroll_sensitivity).clamp(0.0, 1.0)
@@ -249,7 +546,8 @@ impl View for ParamKnob {
                         self.scrolled_lines += 1.0;
                         (current_value - scroll_sensitivity).clamp(0.0, 1.0)
                     };
-                    self.param_base.set_normalized_value(cx, new_value);
+                    self.param_base
+                        .set_normalized_value(cx, self.snap(new_value));
                     self.param_base.end_set_parameter(cx);
                 }
                 meta.consume();
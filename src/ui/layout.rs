@@ -0,0 +1,45 @@
+//! The editor's per-instance layout: which oscillator cards are collapsed,
+//! whether compact mode is on and how knobs are dragged. Saved with the
+//! project (see `ProjectState::editor_layout`), not in presets, so every instance
+//! reopens the way it was left.
+
+use serde::{Deserialize, Serialize};
+use vizia_plug::vizia::prelude::Data;
+
+use super::KnobDragMode;
+
+/// Number of oscillator cards.
+pub const NUM_OSC_CARDS: usize = 3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EditorLayout {
+    /// Per oscillator card: only the header shows.
+    #[serde(default)]
+    pub collapsed: [bool; NUM_OSC_CARDS],
+    /// Expanded oscillator cards show one row of the most-used controls
+    /// instead of their tabs.
+    #[serde(default)]
+    pub compact: bool,
+    #[serde(default)]
+    pub knob_drag: KnobDragMode,
+}
+
+/// How an oscillator card's body is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Data)]
+pub enum OscView {
+    Collapsed,
+    Compact,
+    Full,
+}
+
+impl EditorLayout {
+    pub fn osc_view(self, index: usize) -> OscView {
+        if self.collapsed[index] {
+            OscView::Collapsed
+        } else if self.compact {
+            OscView::Compact
+        } else {
+            OscView::Full
+        }
+    }
+}
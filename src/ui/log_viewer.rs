@@ -0,0 +1,132 @@
+//! Log viewer: an overlay showing the plugin's recent log lines (see
+//! [`crate::logging`]), filterable by the part of the plugin that logged them.
+//! For debugging inside a DAW, where stderr goes nowhere.
+
+use vizia_plug::vizia::prelude::*;
+
+use crate::logging::{self, LogLine, TARGETS};
+
+/// CSS for the overlay panel, its filter row and its lines.
+pub const LOG_VIEWER_CSS: &str = r#"
+    .log-viewer {
+        position-type: absolute;
+        top: 64px;
+        right: 18px;
+        left: 1s;
+        width: 460px;
+        height: 420px;
+        background-color: #121216F2;
+        border: 1px solid #2E3340;
+        corner-radius: 8px;
+        padding: 12px;
+        gap: 6px;
+    }
+    .log-viewer-title {
+        color: #F8FAFC;
+        font-size: 10px;
+        font-weight: 700;
+    }
+    .log-viewer-filters {
+        height: 22px;
+        gap: 4px;
+    }
+    .log-viewer-path {
+        color: #64748B;
+        font-size: 8px;
+    }
+    .log-viewer-lines {
+        height: 1s;
+    }
+    .log-viewer-line {
+        color: #CBD5E1;
+        font-size: 9px;
+        height: 14px;
+    }
+"#;
+
+/// Refresh cadence.
+const REFRESH: Duration = Duration::from_millis(250);
+/// Most lines shown at once (the newest).
+const SHOWN_LINES: usize = 200;
+
+#[derive(Lens)]
+struct LogViewerData {
+    /// One of [`TARGETS`], or `None` for everything.
+    filter: Option<&'static str>,
+    lines: Vec<String>,
+}
+
+enum LogViewerEvent {
+    Refresh,
+    SetFilter(Option<&'static str>),
+}
+
+impl LogViewerData {
+    fn refresh(&mut self) {
+        let filter = self.filter;
+        let lines: Vec<LogLine> = logging::with_backlog(|backlog| {
+            backlog
+                .iter()
+                .filter(|line| filter.is_none_or(|t| line.target == t))
+                .cloned()
+                .collect()
+        });
+        let skip = lines.len().saturating_sub(SHOWN_LINES);
+        self.lines = lines.iter().skip(skip).map(ToString::to_string).collect();
+    }
+}
+
+impl Model for LogViewerData {
+    fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
+        event.map(|e, _| match e {
+            LogViewerEvent::Refresh => self.refresh(),
+            LogViewerEvent::SetFilter(filter) => {
+                self.filter = *filter;
+                self.refresh();
+            }
+        });
+    }
+}
+
+/// Build the log viewer panel. Like the voice monitor it owns its model and
+/// timer, so it can be mounted and unmounted behind a `Binding`.
+pub fn log_viewer(cx: &mut Context) {
+    VStack::new(cx, |cx| {
+        let mut data = LogViewerData {
+            filter: None,
+            lines: Vec::new(),
+        };
+        data.refresh();
+        data.build(cx);
+
+        let timer = cx.add_timer(REFRESH, None, |cx, action| {
+            if let TimerAction::Tick(_) = action {
+                cx.emit(LogViewerEvent::Refresh);
+            }
+        });
+        cx.start_timer(timer);
+
+        Label::new(cx, "LOG").class("log-viewer-title");
+        HStack::new(cx, |cx| {
+            let filters = std::iter::once(None).chain(TARGETS.into_iter().map(Some));
+            for filter in filters {
+                let label = filter.unwrap_or("all").to_uppercase();
+                Button::new(cx, move |cx| Label::new(cx, &label))
+                    .on_press(move |cx| cx.emit(LogViewerEvent::SetFilter(filter)))
+                    .class("header-btn")
+                    .toggle_class("selected", LogViewerData::filter.map(move |f| *f == filter));
+            }
+        })
+        .class("log-viewer-filters");
+        Label::new(cx, format!("Written to {}", logging::log_dir().display()))
+            .class("log-viewer-path");
+
+        ScrollView::new(cx, |cx| {
+            List::new(cx, LogViewerData::lines, |cx, _, line| {
+                Label::new(cx, line).class("log-viewer-line");
+            });
+        })
+        .class("log-viewer-lines");
+    })
+    .class("log-viewer");
+}
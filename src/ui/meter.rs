@@ -45,6 +45,22 @@ impl PeakMeter {
     pub fn load(&self) -> f32 {
         f32::from_bits(self.bits.load(Ordering::Relaxed))
     }
+
+    /// Publish a block's peak, letting the previous reading fall by `decay`
+    /// first so the bar releases smoothly. One relaxed load + store.
+    #[inline]
+    pub fn publish(&self, block_peak: f32, decay: f32) {
+        self.store(block_peak.max(self.load() * decay));
+    }
+}
+
+/// The oscillator mixer's levels, for gain staging: each oscillator before the
+/// filter (through the amp envelope, as on its output bus) and the voices' sum
+/// after it, ahead of the output's soft clip.
+#[derive(Clone, Debug, Default)]
+pub struct MixMeters {
+    pub oscs: [Arc<PeakMeter>; 3],
+    pub bus: Arc<PeakMeter>,
 }
 
 /// CSS for the meter. Colours are read from `draw` directly (zone-based), so the
@@ -58,6 +74,11 @@ pub const METER_CSS: &str = r#"
         border-width: 1px;
         border-color: #2D2D34;
     }
+    .level-meter.mini-meter {
+        width: 48px;
+        height: 6px;
+        corner-radius: 3px;
+    }
 "#;
 
 /// Floor of the meter's dB scale. Levels at or below this read as empty.
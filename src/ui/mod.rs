@@ -1,19 +1,57 @@
 //! GUI layer: the `vizia` editor and its reusable view components.
 //!
 //! - [`editor`] assembles the whole window (header, tabs, module cards).
-//! - [`knob`], [`tab_switcher`], [`meter`] are self-contained, reusable widgets
-//!   that the editor composes. Each owns its own CSS and event handling, so they
-//!   can be dropped into any `vizia` tree.
+//! - [`knob`], [`xy_pad`], [`tab_switcher`], [`meter`], [`mod_scope`] are
+//!   self-contained, reusable widgets that the editor composes. Each owns its
+//!   own CSS and event handling, so they can be dropped into any `vizia` tree.
+//!
+//! - [`voice_monitor`] is the header's debug overlay over the engine's
+//!   [`crate::Diagnostics`].
+//! - [`palette`] is the Ctrl+K parameter search overlay.
+//! - [`save_dialog`] is the header's SAVE dialog, with the patch's metadata.
+//! - [`compare`] is the header's A/B pair and its spectra overlay.
+//! - [`log_viewer`] is the header's overlay over the plugin log (see
+//!   [`crate::logging`]).
+//!
+//!   It and [`voice_monitor`] are behind the `gui-extras` feature.
+//! - [`layout`] is the per-instance collapsed/compact state of the oscillator
+//!   cards.
+//! - [`toast`] shows (and can undo) parameter changes made by the AI tools and
+//!   the schedule.
+//! - [`transport_bar`] is the footer with the tempo and the host's transport.
+//! - [`mod_assign`] drags mod sources from the modulation card onto knobs.
+//! - [`usage_panel`] is the header's session stats overlay.
+//! - [`shortcuts`] reads the editor's keyboard shortcuts from `settings.toml`.
+//! - `no_assistant` is the AI tab in a build without the `chat` feature.
 //!
 //! [`PeakMeter`] is the lock-free hand-off between the audio thread and the
 //! [`Meter`] view; it lives here next to its consumer but is written from
 //! `SineSynth::process` (see `lib.rs`).
 
+pub mod automation_preview;
+pub mod compare;
 pub mod editor;
 pub mod knob;
+pub mod layout;
+#[cfg(feature = "gui-extras")]
+pub mod log_viewer;
 pub mod meter;
+pub mod mod_assign;
+pub mod mod_scope;
+#[cfg(not(feature = "chat"))]
+pub mod no_assistant;
+pub mod palette;
+pub mod save_dialog;
+pub mod shortcuts;
 pub mod tab_switcher;
+pub mod toast;
+pub mod transport_bar;
+pub mod usage_panel;
+#[cfg(feature = "gui-extras")]
+pub mod voice_monitor;
+pub mod xy_pad;
 
-pub use knob::ParamKnob;
-pub use meter::{Meter, PeakMeter};
+pub use knob::{KnobDragMode, KnobEvent, ParamKnob, ParamKnobModifiers};
+pub use meter::{Meter, MixMeters, PeakMeter};
 pub use tab_switcher::{TabDefinition, TabSwitcher};
+pub use xy_pad::XyPadView;
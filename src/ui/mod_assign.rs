@@ -0,0 +1,239 @@
+//! Drag-to-assign for the modulation matrix. The modulation card shows a chip
+//! per source; dropping one on a knob the matrix can reach routes that source
+//! to it through a slot (the one already routing that pair, else the first
+//! unused one). The knob then draws the route's depth as a ring over its
+//! track, and dragging on the ring sets the depth while the body still turns
+//! the knob. The card's Routes tab lists the slots, for when dragging is
+//! awkward.
+//!
+//! Chips and knobs only send events: [`ModAssignEvent`]s bubble up to the
+//! editor, which writes the slot params and broadcasts [`ModRings`] back down
+//! whenever the routes change.
+
+use nih_plug::prelude::{Param, ParamPtr};
+use vizia_plug::vizia::prelude::*;
+use vizia_plug::widgets::RawParamEvent;
+
+use crate::ai::bridge::emit_set;
+use crate::params::NUM_MOD_SLOTS;
+use crate::{ModDestination, ModSource, SineParams};
+
+/// CSS for the chips and the Routes tab's rows.
+pub const MOD_ASSIGN_CSS: &str = r#"
+    .mod-chip {
+        height: 18px;
+        width: auto;
+        padding-left: 8px;
+        padding-right: 8px;
+        background-color: #1E1B2E;
+        border: 1px solid #A855F7;
+        corner-radius: 9px;
+        cursor: hand;
+        alignment: center;
+    }
+    .mod-chip:hover, .mod-chip:active {
+        background-color: #A855F7;
+    }
+    .mod-chip-label {
+        color: #D8B4FE;
+        font-size: 8px;
+        font-weight: 700;
+    }
+    .mod-chip:hover .mod-chip-label, .mod-chip:active .mod-chip-label {
+        color: #0A0A0C;
+    }
+    .mod-route {
+        color: #CBD5E1;
+        font-size: 9px;
+        width: 1s;
+    }
+"#;
+
+/// Every matrix source, with its chip's label.
+pub const SOURCES: [(ModSource, &str); 5] = [
+    (ModSource::Lfo, "LFO"),
+    (ModSource::FilterEnv, "F.ENV"),
+    (ModSource::Velocity, "VEL"),
+    (ModSource::Sidechain, "SC"),
+    (ModSource::Release, "REL"),
+];
+
+/// Depth a new route starts at.
+const DEFAULT_DEPTH: f32 = 0.5;
+
+/// Emitted (bubbling up) by chips and knobs; the editor acts on them.
+pub enum ModAssignEvent {
+    /// A chip carrying this source was dropped on the knob for this param.
+    Assign(ModSource, ParamPtr),
+    /// A drag on a knob's ring started, moved to a depth, or ended, for the
+    /// slot the ring shows.
+    BeginDepth(usize),
+    SetDepth(usize, f32),
+    EndDepth(usize),
+    /// Route this slot nowhere.
+    Clear(usize),
+}
+
+/// Sent by a chip to the view under the cursor when it's let go there.
+pub struct DropSource(pub ModSource);
+
+/// Broadcast to the whole tree: the knob each slot's route shows on, with the
+/// slot and its depth.
+pub struct ModRings(pub Vec<(ParamPtr, usize, f32)>);
+
+/// The knobs the matrix can reach, and the destination each stands for.
+/// Pitch, pulse width, level and morph act on every oscillator at once, so they
+/// have no knob of their own; the slot tabs route them.
+fn knobs(params: &SineParams) -> [(ModDestination, ParamPtr); 3] {
+    [
+        (ModDestination::Cutoff, params.filter.cutoff.as_ptr()),
+        (ModDestination::Resonance, params.filter.resonance.as_ptr()),
+        (ModDestination::Vowel, params.filter.vowel.as_ptr()),
+    ]
+}
+
+fn source_label(source: ModSource) -> &'static str {
+    SOURCES
+        .iter()
+        .find(|(s, _)| *s == source)
+        .map_or("?", |(_, label)| label)
+}
+
+fn destination_label(destination: ModDestination) -> &'static str {
+    match destination {
+        ModDestination::Off => "None",
+        ModDestination::Pitch => "Pitch",
+        ModDestination::PulseWidth => "Pulse Width",
+        ModDestination::Level => "Level",
+        ModDestination::Cutoff => "Cutoff",
+        ModDestination::Resonance => "Resonance",
+        ModDestination::Vowel => "Vowel",
+        ModDestination::Morph => "Morph",
+    }
+}
+
+/// Route `source` to the knob for `ptr`. Returns a line saying what was
+/// routed, or why nothing was.
+pub fn assign(
+    params: &SineParams,
+    source: ModSource,
+    ptr: ParamPtr,
+    emit: &mut impl FnMut(RawParamEvent),
+) -> Result<String, String> {
+    let Some((destination, _)) = knobs(params).into_iter().find(|(_, p)| *p == ptr) else {
+        return Err("Only the filter's cutoff, resonance and vowel take a drop".to_string());
+    };
+    let slots = &params.modulation.slots;
+    let existing = slots
+        .iter()
+        .position(|s| s.source.value() == source && s.destination.value() == destination);
+    let free = || {
+        slots
+            .iter()
+            .position(|s| s.destination.value() == ModDestination::Off)
+    };
+    let Some(index) = existing.or_else(free) else {
+        return Err(format!("All {NUM_MOD_SLOTS} mod slots are in use"));
+    };
+    let slot = &slots[index];
+    emit_set(&slot.source, source, emit);
+    emit_set(&slot.destination, destination, emit);
+    if existing.is_none() {
+        emit_set(&slot.amount, DEFAULT_DEPTH, emit);
+    }
+    Ok(format!(
+        "{} → {} in slot {}",
+        source_label(source),
+        destination_label(destination),
+        index + 1
+    ))
+}
+
+/// The rings the knobs should show: each slot routed to a knob.
+pub fn rings(params: &SineParams) -> Vec<(ParamPtr, usize, f32)> {
+    let knobs = knobs(params);
+    params
+        .modulation
+        .slots
+        .iter()
+        .enumerate()
+        .filter_map(|(index, slot)| {
+            let destination = slot.destination.value();
+            let (_, ptr) = knobs.iter().find(|(d, _)| *d == destination)?;
+            Some((*ptr, index, slot.amount.value()))
+        })
+        .collect()
+}
+
+/// One line per slot for the Routes tab, e.g. "1  LFO → Cutoff  +50%".
+pub fn route_lines(params: &SineParams) -> Vec<String> {
+    params
+        .modulation
+        .slots
+        .iter()
+        .enumerate()
+        .map(|(index, slot)| match slot.destination.value() {
+            ModDestination::Off => format!("{}  —", index + 1),
+            destination => format!(
+                "{}  {} → {}  {:+.0}%",
+                index + 1,
+                source_label(slot.source.value()),
+                destination_label(destination),
+                slot.amount.value() * 100.0
+            ),
+        })
+        .collect()
+}
+
+/// A source to drag onto a knob.
+pub struct ModChip {
+    source: ModSource,
+    dragging: bool,
+}
+
+impl ModChip {
+    pub fn new(cx: &mut Context, source: ModSource) -> Handle<'_, Self> {
+        Self {
+            source,
+            dragging: false,
+        }
+        .build(cx, move |cx| {
+            Label::new(cx, source_label(source))
+                .class("mod-chip-label")
+                .hoverable(false);
+        })
+        .class("mod-chip")
+    }
+}
+
+impl View for ModChip {
+    fn element(&self) -> Option<&'static str> {
+        Some("mod-chip")
+    }
+
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|window_event, meta| match window_event {
+            WindowEvent::MouseDown(MouseButton::Left) => {
+                cx.capture();
+                cx.set_active(true);
+                cx.emit(WindowEvent::SetCursor(CursorIcon::Grabbing));
+                self.dragging = true;
+                meta.consume();
+            }
+            WindowEvent::MouseUp(MouseButton::Left) if self.dragging => {
+                cx.release();
+                cx.set_active(false);
+                cx.emit(WindowEvent::SetCursor(CursorIcon::Hand));
+                self.dragging = false;
+                // Whatever is under the cursor gets the drop; only knobs
+                // handle it.
+                let target = cx.hovered();
+                if target != cx.current() {
+                    cx.emit_to(target, DropSource(self.source));
+                }
+                meta.consume();
+            }
+            _ => {}
+        });
+    }
+}
@@ -0,0 +1,253 @@
+//! Mod scope: fires an envelope or the LFO without a note (see `dsp::probe`)
+//! and draws the traced shape, for tuning modulation while designing it. The
+//! `trigger_envelope` / `trigger_lfo` tools fire the same probes, so their
+//! traces show up here too.
+//!
+//! The source monitor ([`source_monitor`]) is the live view: every mod
+//! source's output as the engine plays it, scrolling over a beat grid, so a
+//! synced LFO can be seen landing on the beats.
+//!
+//! Like [`super::Meter`], the views only sample [`Diagnostics`] in `draw` on a
+//! redraw timer; the audio thread writes the values as relaxed atomics.
+
+use std::sync::Arc;
+
+use nih_plug::prelude::Enum;
+use vizia_plug::vizia::prelude::*;
+use vizia_plug::vizia::vg;
+
+use crate::dsp::probe::{ProbeTarget, SOURCE_POINTS, SOURCE_SECONDS, TRACE_POINTS};
+use crate::{Diagnostics, ModSource};
+
+/// CSS for the scope and its trigger buttons.
+pub const MOD_SCOPE_CSS: &str = r#"
+    .mod-scope {
+        width: 1s;
+        height: 72px;
+        corner-radius: 6px;
+        background-color: #0E0E12;
+        border-width: 1px;
+        border-color: #2D2D34;
+    }
+    .mod-scope-triggers {
+        width: 72px;
+        height: auto;
+        gap: 4px;
+    }
+    .source-scope {
+        width: 1s;
+        height: 56px;
+        corner-radius: 4px;
+        background-color: #0E0E12;
+        border-width: 1px;
+        border-color: #2D2D34;
+    }
+    .source-scope-label {
+        color: #94A3B8;
+        font-size: 8px;
+        font-weight: 700;
+    }
+"#;
+
+/// Redraw cadence (~30 fps), so a running trace visibly draws itself.
+const REFRESH: Duration = Duration::from_millis(33);
+
+/// The trigger buttons and the scope, side by side.
+pub fn mod_scope(cx: &mut Context, diagnostics: Arc<Diagnostics>) {
+    HStack::new(cx, move |cx| {
+        VStack::new(cx, |cx| {
+            for target in ProbeTarget::ALL {
+                let diagnostics = diagnostics.clone();
+                Button::new(cx, move |cx| Label::new(cx, target.id().to_uppercase()))
+                    .on_press(move |_| diagnostics.request_probe(target))
+                    .class("header-btn");
+            }
+        })
+        .class("mod-scope-triggers");
+        ModScope::new(cx, diagnostics.clone());
+    })
+    .gap(Pixels(12.0))
+    .height(Auto);
+}
+
+/// A scope per mod source, side by side.
+pub fn source_monitor(cx: &mut Context, diagnostics: Arc<Diagnostics>) {
+    HStack::new(cx, move |cx| {
+        for source in ModSource::ALL {
+            VStack::new(cx, |cx| {
+                let name = ModSource::variants()[source.to_index()];
+                Label::new(cx, name.to_uppercase()).class("source-scope-label");
+                SourceScope::new(cx, diagnostics.clone(), source);
+            })
+            .gap(Pixels(4.0));
+        }
+    })
+    .gap(Pixels(8.0))
+    .height(Auto);
+}
+
+/// Draws the latest probe trace across its full width: envelopes bottom to
+/// top, the LFO around a centre line.
+pub struct ModScope {
+    diagnostics: Arc<Diagnostics>,
+}
+
+impl ModScope {
+    pub fn new(cx: &mut Context, diagnostics: Arc<Diagnostics>) -> Handle<'_, Self> {
+        Self { diagnostics }
+            .build(cx, |cx| {
+                let timer = cx.add_timer(REFRESH, None, |cx, action| {
+                    if let TimerAction::Tick(_) = action {
+                        cx.needs_redraw();
+                    }
+                });
+                cx.start_timer(timer);
+            })
+            .class("mod-scope")
+    }
+}
+
+impl View for ModScope {
+    fn element(&self) -> Option<&'static str> {
+        Some("mod-scope")
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &Canvas) {
+        let bounds = cx.bounds();
+        if bounds.w <= 0.0 || bounds.h <= 0.0 {
+            return;
+        }
+        let Some(trace) = self.diagnostics.trace() else {
+            return;
+        };
+
+        let pad = 4.0;
+        let (left, top) = (bounds.x + pad, bounds.y + pad);
+        let (width, height) = (bounds.w - pad * 2.0, bounds.h - pad * 2.0);
+        let bipolar = trace.target.bipolar();
+
+        if bipolar {
+            let mut axis = vg::Paint::default();
+            axis.set_style(vg::PaintStyle::Stroke);
+            axis.set_stroke_width(1.0);
+            axis.set_color(vg::Color::from_argb(255, 45, 45, 52));
+            axis.set_alpha_f(cx.opacity());
+            let mid = top + height * 0.5;
+            canvas.draw_line((left, mid), (left + width, mid), &axis);
+        }
+
+        let mut path = vg::Path::new();
+        for (index, value) in trace.values.iter().enumerate() {
+            let norm = if bipolar { (value + 1.0) * 0.5 } else { *value };
+            let x = left + width * index as f32 / (TRACE_POINTS - 1) as f32;
+            let y = top + height * (1.0 - norm.clamp(0.0, 1.0));
+            if index == 0 {
+                path.move_to((x, y));
+            } else {
+                path.line_to((x, y));
+            }
+        }
+
+        let mut paint = vg::Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_style(vg::PaintStyle::Stroke);
+        paint.set_stroke_width(1.5);
+        paint.set_stroke_join(vg::PaintJoin::Round);
+        paint.set_color(vg::Color::from_argb(255, 129, 140, 248)); // indigo
+        paint.set_alpha_f(cx.opacity());
+        canvas.draw_path(&path, &paint);
+    }
+}
+
+/// Scrolls one mod source's recent output, newest on the right, over a line
+/// per beat at the engine's tempo (on the song's beats while the host plays).
+pub struct SourceScope {
+    diagnostics: Arc<Diagnostics>,
+    source: ModSource,
+}
+
+impl SourceScope {
+    pub fn new(
+        cx: &mut Context,
+        diagnostics: Arc<Diagnostics>,
+        source: ModSource,
+    ) -> Handle<'_, Self> {
+        Self {
+            diagnostics,
+            source,
+        }
+        .build(cx, |cx| {
+            let timer = cx.add_timer(REFRESH, None, |cx, action| {
+                if let TimerAction::Tick(_) = action {
+                    cx.needs_redraw();
+                }
+            });
+            cx.start_timer(timer);
+        })
+        .class("source-scope")
+    }
+}
+
+impl View for SourceScope {
+    fn element(&self) -> Option<&'static str> {
+        Some("source-scope")
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &Canvas) {
+        let bounds = cx.bounds();
+        if bounds.w <= 0.0 || bounds.h <= 0.0 {
+            return;
+        }
+        let pad = 3.0;
+        let (left, top) = (bounds.x + pad, bounds.y + pad);
+        let (width, height) = (bounds.w - pad * 2.0, bounds.h - pad * 2.0);
+        // Only the LFO swings both ways.
+        let bipolar = self.source == ModSource::Lfo;
+
+        let mut grid = vg::Paint::default();
+        grid.set_style(vg::PaintStyle::Stroke);
+        grid.set_stroke_width(1.0);
+        grid.set_color(vg::Color::from_argb(255, 45, 45, 52));
+        grid.set_alpha_f(cx.opacity());
+        if bipolar {
+            let mid = top + height * 0.5;
+            canvas.draw_line((left, mid), (left + width, mid), &grid);
+        }
+        let transport = self.diagnostics.transport();
+        let beat_seconds = 60.0 / transport.bpm.max(1.0);
+        // Seconds back from now to the last beat.
+        let mut back = transport
+            .song_beats
+            .map_or(0.0, |beats| beats.rem_euclid(1.0) as f32 * beat_seconds);
+        while back <= SOURCE_SECONDS {
+            let x = left + width * (1.0 - back / SOURCE_SECONDS);
+            canvas.draw_line((x, top), (x, top + height), &grid);
+            back += beat_seconds;
+        }
+
+        // Right-aligned, so a history that hasn't filled yet grows from the
+        // right like the rest scrolls.
+        let values = self.diagnostics.source_history(self.source);
+        let start = SOURCE_POINTS - values.len();
+        let mut path = vg::Path::new();
+        for (index, value) in values.iter().enumerate() {
+            let norm = if bipolar { (value + 1.0) * 0.5 } else { *value };
+            let x = left + width * (start + index) as f32 / (SOURCE_POINTS - 1) as f32;
+            let y = top + height * (1.0 - norm.clamp(0.0, 1.0));
+            if index == 0 {
+                path.move_to((x, y));
+            } else {
+                path.line_to((x, y));
+            }
+        }
+
+        let mut paint = vg::Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_style(vg::PaintStyle::Stroke);
+        paint.set_stroke_width(1.5);
+        paint.set_stroke_join(vg::PaintJoin::Round);
+        paint.set_color(vg::Color::from_argb(255, 168, 85, 247)); // purple
+        paint.set_alpha_f(cx.opacity());
+        canvas.draw_path(&path, &paint);
+    }
+}
@@ -0,0 +1,11 @@
+//! The AI tab in a build without the `chat` feature: a note saying how to get
+//! the assistant back.
+
+use vizia_plug::vizia::prelude::*;
+
+pub const NO_ASSISTANT_CSS: &str = ".assist-unavailable { color: #94A3B8; font-size: 12px; }";
+
+pub fn no_assistant(cx: &mut Context) {
+    Label::new(cx, "This build has no assistant. Rebuild with `--features chat` to get it back.")
+        .class("assist-unavailable");
+}
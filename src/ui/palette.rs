@@ -0,0 +1,315 @@
+//! Parameter palette (Ctrl+K): find any parameter by typing part of its name
+//! or section, then type a value for it directly. Picking one also highlights
+//! its knob if it's on screen (see [`HighlightParam`]); WATCH plots its recent
+//! values (see [`super::automation_preview`]), and the XY row puts it on an XY
+//! pad's axis over its full range (see `crate::xy`).
+//!
+//! Like the log viewer it owns its model, so it can be mounted and unmounted
+//! behind a `Binding`; it asks to be closed with [`PaletteEvent::Close`].
+
+use std::sync::Arc;
+
+use nih_plug::prelude::{ParamPtr, Params};
+use vizia_plug::vizia::prelude::*;
+use vizia_plug::widgets::RawParamEvent;
+
+use super::knob::HighlightParam;
+use crate::xy::{Axis, NUM_XY_PADS};
+use crate::SineParams;
+
+/// CSS for the overlay panel, its search box and its rows.
+pub const PALETTE_CSS: &str = r#"
+    .palette {
+        position-type: absolute;
+        top: 64px;
+        left: 1s;
+        right: 1s;
+        width: 380px;
+        height: auto;
+        background-color: #121216F2;
+        border: 1px solid #2E3340;
+        corner-radius: 8px;
+        padding: 12px;
+        gap: 6px;
+    }
+    .palette-title {
+        color: #F8FAFC;
+        font-size: 10px;
+        font-weight: 700;
+    }
+    .palette-row {
+        background-color: transparent;
+        border-width: 0px;
+        color: #CBD5E1;
+        font-size: 9px;
+        height: 18px;
+        width: 1s;
+        padding-left: 4px;
+    }
+    .palette-row:hover {
+        background-color: #1C1C22;
+        color: #F8FAFC;
+    }
+    .palette-selected {
+        color: #818CF8;
+        font-size: 9px;
+        font-weight: 700;
+    }
+    .palette-status {
+        color: #F43F5E;
+        font-size: 9px;
+    }
+"#;
+
+/// Most matches listed at once.
+const SHOWN_MATCHES: usize = 12;
+
+/// Emitted (bubbling up) when the palette wants to be closed.
+pub enum PaletteEvent {
+    Close,
+    /// Show the automation preview for the param at `index` in
+    /// `param_map()`.
+    Watch { index: usize, label: String },
+    /// Have an XY pad's axis drive the param at `index` in `param_map()`.
+    AssignXy { index: usize, pad: usize, axis: Axis },
+}
+
+#[derive(Lens)]
+struct PaletteData {
+    /// Every param, with its "Section › Name" label.
+    entries: Vec<(ParamPtr, String)>,
+    query: String,
+    /// Indices into `entries` of the listed matches.
+    matches: Vec<usize>,
+    /// Labels of the listed matches.
+    shown: Vec<String>,
+    selected: Option<ParamPtr>,
+    /// The picked param's label and current value.
+    selected_label: String,
+    /// What's typed into the value box.
+    value: String,
+    /// Why the last typed value wasn't applied.
+    status: String,
+}
+
+enum PaletteDataEvent {
+    Query(String),
+    /// Pick the `n`th listed match.
+    Select(usize),
+    EditValue(String),
+    SubmitValue,
+    Watch,
+    AssignXy(usize, Axis),
+}
+
+impl PaletteData {
+    fn new(params: &SineParams) -> Self {
+        let entries = params
+            .param_map()
+            .into_iter()
+            .map(|(_, ptr, group)| {
+                // SAFETY: the pointers come from the plugin's `SineParams`,
+                // which outlives the editor.
+                let name = unsafe { ptr.name() };
+                let label = match group.as_str() {
+                    "" => name.to_string(),
+                    group => format!("{} › {name}", group.replace('/', " › ")),
+                };
+                (ptr, label)
+            })
+            .collect();
+        let mut data = Self {
+            entries,
+            query: String::new(),
+            matches: Vec::new(),
+            shown: Vec::new(),
+            selected: None,
+            selected_label: String::new(),
+            value: String::new(),
+            status: String::new(),
+        };
+        data.search();
+        data
+    }
+
+    /// Refilter: every word of the query must appear in the label.
+    fn search(&mut self) {
+        let query = self.query.to_lowercase();
+        let words: Vec<&str> = query.split_whitespace().collect();
+        self.matches = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, label))| {
+                let label = label.to_lowercase();
+                words.iter().all(|word| label.contains(word))
+            })
+            .map(|(i, _)| i)
+            .take(SHOWN_MATCHES)
+            .collect();
+        self.shown = self
+            .matches
+            .iter()
+            .map(|&i| self.entries[i].1.clone())
+            .collect();
+    }
+
+    /// Show the picked param's current value.
+    fn describe(&mut self, index: usize) {
+        let (ptr, label) = &self.entries[index];
+        // SAFETY: as in `new`.
+        let value =
+            unsafe { ptr.normalized_value_to_string(ptr.unmodulated_normalized_value(), true) };
+        self.selected_label = format!("{label} = {value}");
+        self.value = value;
+    }
+}
+
+impl Model for PaletteData {
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|e, _| match e {
+            PaletteDataEvent::Query(query) => {
+                self.query = query.clone();
+                self.search();
+            }
+            PaletteDataEvent::Select(n) => {
+                let Some(&index) = self.matches.get(*n) else {
+                    return;
+                };
+                let ptr = self.entries[index].0;
+                self.selected = Some(ptr);
+                self.status.clear();
+                self.describe(index);
+                cx.emit_custom(
+                    Event::new(HighlightParam(Some(ptr)))
+                        .target(Entity::root())
+                        .propagate(Propagation::Subtree),
+                );
+            }
+            PaletteDataEvent::EditValue(value) => self.value = value.clone(),
+            PaletteDataEvent::Watch => {
+                let index = self.entries.iter().position(|(p, _)| Some(*p) == self.selected);
+                if let Some(index) = index {
+                    let label = self.entries[index].1.clone();
+                    cx.emit(PaletteEvent::Watch { index, label });
+                }
+            }
+            PaletteDataEvent::AssignXy(pad, axis) => {
+                let index = self.entries.iter().position(|(p, _)| Some(*p) == self.selected);
+                if let Some(index) = index {
+                    cx.emit(PaletteEvent::AssignXy {
+                        index,
+                        pad: *pad,
+                        axis: *axis,
+                    });
+                }
+            }
+            PaletteDataEvent::SubmitValue => {
+                let Some(ptr) = self.selected else {
+                    return;
+                };
+                // SAFETY: as in `new`.
+                let Some(normalized) = (unsafe { ptr.string_to_normalized_value(&self.value) })
+                else {
+                    self.status = format!("'{}' isn't a valid value", self.value);
+                    return;
+                };
+                cx.emit(RawParamEvent::BeginSetParameter(ptr));
+                cx.emit(RawParamEvent::SetParameterNormalized(ptr, normalized));
+                cx.emit(RawParamEvent::EndSetParameter(ptr));
+                self.status.clear();
+                // The write is applied after this event, so show what was
+                // asked for rather than reading the param back.
+                let label = self.entries.iter().find(|(p, _)| *p == ptr);
+                if let Some((_, label)) = label {
+                    // SAFETY: as in `new`.
+                    let value = unsafe { ptr.normalized_value_to_string(normalized, true) };
+                    self.selected_label = format!("{label} = {value}");
+                }
+            }
+        });
+        event.map(|window_event, meta| {
+            if let WindowEvent::KeyDown(Code::Escape, _) = window_event {
+                cx.emit(PaletteEvent::Close);
+                meta.consume();
+            }
+        });
+    }
+}
+
+/// Build the palette panel.
+pub fn palette(cx: &mut Context, params: Arc<SineParams>) {
+    VStack::new(cx, |cx| {
+        PaletteData::new(&params).build(cx);
+
+        HStack::new(cx, |cx| {
+            Label::new(cx, "FIND PARAMETER").class("palette-title");
+            Element::new(cx).width(Stretch(1.0)).height(Pixels(0.0));
+            Button::new(cx, |cx| Label::new(cx, "ESC"))
+                .on_press(|cx| cx.emit(PaletteEvent::Close))
+                .class("header-btn");
+        })
+        .height(Pixels(24.0))
+        .alignment(Alignment::Center);
+
+        let search = Textbox::new(cx, PaletteData::query)
+            .class("chat-input")
+            .width(Stretch(1.0))
+            .on_edit(|cx, text| cx.emit(PaletteDataEvent::Query(text)))
+            // Enter picks the first match.
+            .on_submit(|cx, _, _| cx.emit(PaletteDataEvent::Select(0)))
+            .entity();
+        cx.emit_to(search, TextEvent::StartEdit);
+
+        VStack::new(cx, |cx| {
+            List::new(cx, PaletteData::shown, |cx, n, label| {
+                Button::new(cx, |cx| Label::new(cx, label))
+                    .on_press(move |cx| cx.emit(PaletteDataEvent::Select(n)))
+                    .class("palette-row");
+            });
+        })
+        .height(Auto);
+
+        Binding::new(
+            cx,
+            PaletteData::selected.map(Option::is_some),
+            |cx, picked| {
+                if !picked.get(cx) {
+                    return;
+                }
+                HStack::new(cx, |cx| {
+                    Label::new(cx, PaletteData::selected_label)
+                        .class("palette-selected")
+                        .width(Stretch(1.0));
+                    Button::new(cx, |cx| Label::new(cx, "WATCH"))
+                        .on_press(|cx| cx.emit(PaletteDataEvent::Watch))
+                        .class("header-btn");
+                })
+                .height(Auto)
+                .alignment(Alignment::Center);
+                HStack::new(cx, |cx| {
+                    Label::new(cx, "XY PAD").class("palette-title");
+                    Element::new(cx).width(Stretch(1.0)).height(Pixels(0.0));
+                    for pad in 0..NUM_XY_PADS {
+                        for axis in Axis::ALL {
+                            let name = format!("{}{}", axis.id().to_uppercase(), pad + 1);
+                            Button::new(cx, |cx| Label::new(cx, name.as_str()))
+                                .on_press(move |cx| cx.emit(PaletteDataEvent::AssignXy(pad, axis)))
+                                .class("header-btn");
+                        }
+                    }
+                })
+                .height(Auto)
+                .gap(Pixels(4.0))
+                .alignment(Alignment::Center);
+                Textbox::new(cx, PaletteData::value)
+                    .class("chat-input")
+                    .width(Stretch(1.0))
+                    .on_edit(|cx, text| cx.emit(PaletteDataEvent::EditValue(text)))
+                    .on_submit(|cx, _, _| cx.emit(PaletteDataEvent::SubmitValue));
+                Label::new(cx, PaletteData::status).class("palette-status");
+            },
+        );
+    })
+    .class("palette");
+}
@@ -0,0 +1,163 @@
+//! The header's SAVE dialog: the preset's name plus the patch's author,
+//! category and description (see [`PatchMetadata`]), all editable before it
+//! is written to disk.
+//!
+//! Like the palette it owns its model, so it can be mounted and unmounted
+//! behind a `Binding`; it reports back with [`SaveDialogEvent`].
+
+use std::sync::Arc;
+
+use vizia_plug::vizia::prelude::*;
+
+use crate::ai::preset::{self, PatchMetadata};
+use crate::SineParams;
+
+/// CSS for the dialog panel and its fields; the rest is the palette's.
+pub const SAVE_DIALOG_CSS: &str = r#"
+    .save-dialog {
+        position-type: absolute;
+        top: 64px;
+        left: 1s;
+        right: 1s;
+        width: 380px;
+        height: auto;
+        background-color: #121216F2;
+        border: 1px solid #2E3340;
+        corner-radius: 8px;
+        padding: 12px;
+        gap: 6px;
+    }
+    .save-field {
+        color: #94A3B8;
+        font-size: 8px;
+        font-weight: 700;
+    }
+"#;
+
+/// Emitted (bubbling up) when the dialog is done.
+pub enum SaveDialogEvent {
+    /// The preset was written under this name.
+    Saved(String),
+    Close,
+}
+
+#[derive(Lens)]
+struct SaveDialogData {
+    params: Arc<SineParams>,
+    name: String,
+    author: String,
+    category: String,
+    description: String,
+    /// Why the last save failed.
+    status: String,
+}
+
+enum SaveDialogDataEvent {
+    Name(String),
+    Author(String),
+    Category(String),
+    Description(String),
+    Save,
+}
+
+impl Model for SaveDialogData {
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|e, _| match e {
+            SaveDialogDataEvent::Name(text) => self.name = text.clone(),
+            SaveDialogDataEvent::Author(text) => self.author = text.clone(),
+            SaveDialogDataEvent::Category(text) => self.category = text.clone(),
+            SaveDialogDataEvent::Description(text) => self.description = text.clone(),
+            SaveDialogDataEvent::Save => {
+                let name = self.name.trim();
+                if name.is_empty() {
+                    self.status = "The preset needs a name".to_string();
+                    return;
+                }
+                let metadata = PatchMetadata {
+                    author: self.author.trim().to_string(),
+                    description: self.description.trim().to_string(),
+                    category: self.category.trim().to_string(),
+                };
+                metadata.store(&self.params);
+                match preset::save(&self.params, name) {
+                    Ok(path) => {
+                        tracing::info!(target: "editor", path = %path.display(), "saved preset");
+                        cx.emit(SaveDialogEvent::Saved(name.to_string()));
+                    }
+                    Err(e) => self.status = e,
+                }
+            }
+        });
+        event.map(|window_event, meta| {
+            if let WindowEvent::KeyDown(Code::Escape, _) = window_event {
+                cx.emit(SaveDialogEvent::Close);
+                meta.consume();
+            }
+        });
+    }
+}
+
+/// One labelled text field.
+fn field<L>(cx: &mut Context, label: &str, lens: L, edit: fn(String) -> SaveDialogDataEvent)
+where
+    L: Lens<Target = String>,
+{
+    Label::new(cx, label).class("save-field");
+    Textbox::new(cx, lens)
+        .class("chat-input")
+        .width(Stretch(1.0))
+        .on_edit(move |cx, text| cx.emit(edit(text)))
+        .on_submit(|cx, _, _| cx.emit(SaveDialogDataEvent::Save));
+}
+
+/// Build the dialog, filled in with `name` (the preset last loaded, if any)
+/// and the patch's metadata.
+pub fn save_dialog(cx: &mut Context, params: Arc<SineParams>, name: String) {
+    VStack::new(cx, |cx| {
+        let metadata = PatchMetadata::current(&params);
+        SaveDialogData {
+            params,
+            name,
+            author: metadata.author,
+            category: metadata.category,
+            description: metadata.description,
+            status: String::new(),
+        }
+        .build(cx);
+
+        HStack::new(cx, |cx| {
+            Label::new(cx, "SAVE PRESET").class("palette-title");
+            Element::new(cx).width(Stretch(1.0)).height(Pixels(0.0));
+            Button::new(cx, |cx| Label::new(cx, "SAVE"))
+                .on_press(|cx| cx.emit(SaveDialogDataEvent::Save))
+                .class("header-btn");
+            Button::new(cx, |cx| Label::new(cx, "ESC"))
+                .on_press(|cx| cx.emit(SaveDialogEvent::Close))
+                .class("header-btn");
+        })
+        .height(Pixels(24.0))
+        .alignment(Alignment::Center);
+
+        field(cx, "NAME", SaveDialogData::name, SaveDialogDataEvent::Name);
+        field(
+            cx,
+            "AUTHOR",
+            SaveDialogData::author,
+            SaveDialogDataEvent::Author,
+        );
+        field(
+            cx,
+            "CATEGORY",
+            SaveDialogData::category,
+            SaveDialogDataEvent::Category,
+        );
+        field(
+            cx,
+            "DESCRIPTION",
+            SaveDialogData::description,
+            SaveDialogDataEvent::Description,
+        );
+        Label::new(cx, SaveDialogData::status).class("palette-status");
+    })
+    .class("save-dialog");
+}
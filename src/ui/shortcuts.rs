@@ -0,0 +1,256 @@
+//! Keyboard shortcuts: the editor-wide keys for saving, stepping presets,
+//! showing the chat, panic and the palette. The keys come from the
+//! `[shortcuts]` table of `settings.toml` (see [`crate::settings::Shortcuts`]),
+//! written as modifiers and a key joined by `+`: `"Ctrl+S"`, `"Shift+Escape"`,
+//! `"Ctrl+Alt+Right"`.
+//!
+//! The editor's model looks each key press up in its [`KeyMap`]; a key that
+//! doesn't parse is logged and falls back to its default.
+
+use vizia_plug::vizia::prelude::*;
+
+use crate::settings::Shortcuts;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shortcut {
+    SavePreset,
+    NextPreset,
+    PrevPreset,
+    ToggleChat,
+    Panic,
+    Palette,
+}
+
+impl Shortcut {
+    pub const ALL: [Shortcut; 6] = [
+        Self::SavePreset,
+        Self::NextPreset,
+        Self::PrevPreset,
+        Self::ToggleChat,
+        Self::Panic,
+        Self::Palette,
+    ];
+
+    /// Its key in `[shortcuts]`.
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::SavePreset => "save_preset",
+            Self::NextPreset => "next_preset",
+            Self::PrevPreset => "prev_preset",
+            Self::ToggleChat => "toggle_chat",
+            Self::Panic => "panic",
+            Self::Palette => "palette",
+        }
+    }
+
+    fn binding(self, shortcuts: &Shortcuts) -> &str {
+        match self {
+            Self::SavePreset => &shortcuts.save_preset,
+            Self::NextPreset => &shortcuts.next_preset,
+            Self::PrevPreset => &shortcuts.prev_preset,
+            Self::ToggleChat => &shortcuts.toggle_chat,
+            Self::Panic => &shortcuts.panic,
+            Self::Palette => &shortcuts.palette,
+        }
+    }
+}
+
+/// A key with the modifiers that must be down with it, and no others.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyCombo {
+    code: Code,
+    ctrl: bool,
+    shift: bool,
+    alt: bool,
+    /// Cmd on macOS, the Windows key elsewhere.
+    logo: bool,
+}
+
+impl KeyCombo {
+    /// Read a key like `"Ctrl+Shift+S"`. Modifiers and names are
+    /// case-insensitive.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parts = text.rsplit('+').map(str::trim);
+        let key = parts.next().unwrap_or_default();
+        let code = key_code(key).ok_or_else(|| format!("unknown key '{key}' in '{text}'"))?;
+        let mut combo = Self {
+            code,
+            ctrl: false,
+            shift: false,
+            alt: false,
+            logo: false,
+        };
+        for modifier in parts {
+            let flag = match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => &mut combo.ctrl,
+                "shift" => &mut combo.shift,
+                "alt" | "option" => &mut combo.alt,
+                "cmd" | "super" | "meta" | "win" => &mut combo.logo,
+                other => return Err(format!("unknown modifier '{other}' in '{text}'")),
+            };
+            *flag = true;
+        }
+        Ok(combo)
+    }
+
+    pub fn matches(&self, code: Code, modifiers: &Modifiers) -> bool {
+        self.code == code
+            && self.ctrl == modifiers.ctrl()
+            && self.shift == modifiers.shift()
+            && self.alt == modifiers.alt()
+            && self.logo == modifiers.logo()
+    }
+}
+
+/// A key by name: a letter or digit, a punctuation key as typed (`[`, `.`),
+/// `F1`..`F12`, or a named key such as `Escape`, `Space` or `Left`.
+fn key_code(name: &str) -> Option<Code> {
+    const LETTERS: [Code; 26] = [
+        Code::KeyA,
+        Code::KeyB,
+        Code::KeyC,
+        Code::KeyD,
+        Code::KeyE,
+        Code::KeyF,
+        Code::KeyG,
+        Code::KeyH,
+        Code::KeyI,
+        Code::KeyJ,
+        Code::KeyK,
+        Code::KeyL,
+        Code::KeyM,
+        Code::KeyN,
+        Code::KeyO,
+        Code::KeyP,
+        Code::KeyQ,
+        Code::KeyR,
+        Code::KeyS,
+        Code::KeyT,
+        Code::KeyU,
+        Code::KeyV,
+        Code::KeyW,
+        Code::KeyX,
+        Code::KeyY,
+        Code::KeyZ,
+    ];
+    const DIGITS: [Code; 10] = [
+        Code::Digit0,
+        Code::Digit1,
+        Code::Digit2,
+        Code::Digit3,
+        Code::Digit4,
+        Code::Digit5,
+        Code::Digit6,
+        Code::Digit7,
+        Code::Digit8,
+        Code::Digit9,
+    ];
+    const F_KEYS: [Code; 12] = [
+        Code::F1,
+        Code::F2,
+        Code::F3,
+        Code::F4,
+        Code::F5,
+        Code::F6,
+        Code::F7,
+        Code::F8,
+        Code::F9,
+        Code::F10,
+        Code::F11,
+        Code::F12,
+    ];
+
+    let lower = name.to_ascii_lowercase();
+    let mut chars = lower.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return match c {
+            'a'..='z' => Some(LETTERS[c as usize - 'a' as usize]),
+            '0'..='9' => Some(DIGITS[c as usize - '0' as usize]),
+            '[' => Some(Code::BracketLeft),
+            ']' => Some(Code::BracketRight),
+            ',' => Some(Code::Comma),
+            '.' => Some(Code::Period),
+            '/' => Some(Code::Slash),
+            ';' => Some(Code::Semicolon),
+            '\'' => Some(Code::Quote),
+            '-' => Some(Code::Minus),
+            '=' => Some(Code::Equal),
+            '`' => Some(Code::Backquote),
+            '\\' => Some(Code::Backslash),
+            _ => None,
+        };
+    }
+    if let Some(n) = lower
+        .strip_prefix('f')
+        .and_then(|n| n.parse::<usize>().ok())
+    {
+        return n.checked_sub(1).and_then(|i| F_KEYS.get(i)).copied();
+    }
+    Some(match lower.as_str() {
+        "escape" | "esc" => Code::Escape,
+        "space" => Code::Space,
+        "enter" | "return" => Code::Enter,
+        "tab" => Code::Tab,
+        "backspace" => Code::Backspace,
+        "delete" | "del" => Code::Delete,
+        "insert" => Code::Insert,
+        "home" => Code::Home,
+        "end" => Code::End,
+        "pageup" => Code::PageUp,
+        "pagedown" => Code::PageDown,
+        "left" => Code::ArrowLeft,
+        "right" => Code::ArrowRight,
+        "up" => Code::ArrowUp,
+        "down" => Code::ArrowDown,
+        _ => return None,
+    })
+}
+
+/// Which shortcut each key runs.
+#[derive(Clone, Debug)]
+pub struct KeyMap {
+    bindings: Vec<(KeyCombo, Shortcut)>,
+}
+
+impl KeyMap {
+    /// The keys in `shortcuts`, with the default for any that doesn't parse.
+    /// When two shortcuts share a key, the first in [`Shortcut::ALL`] wins.
+    pub fn new(shortcuts: &Shortcuts) -> Self {
+        let defaults = Shortcuts::default();
+        let mut bindings: Vec<(KeyCombo, Shortcut)> = Vec::new();
+        for shortcut in Shortcut::ALL {
+            let text = shortcut.binding(shortcuts).trim();
+            if text.is_empty() {
+                continue;
+            }
+            let combo = KeyCombo::parse(text).or_else(|e| {
+                let id = shortcut.id();
+                tracing::warn!(target: "editor", shortcut = id, "{e}; using the default");
+                KeyCombo::parse(shortcut.binding(&defaults))
+            });
+            let Ok(combo) = combo else {
+                continue;
+            };
+            if let Some((_, taken)) = bindings.iter().find(|(bound, _)| *bound == combo) {
+                tracing::warn!(
+                    target: "editor",
+                    "'{text}' is bound to both {} and {}; {} keeps it",
+                    taken.id(),
+                    shortcut.id(),
+                    taken.id()
+                );
+                continue;
+            }
+            bindings.push((combo, shortcut));
+        }
+        Self { bindings }
+    }
+
+    /// The shortcut `code` runs with `modifiers` held, if any.
+    pub fn find(&self, code: Code, modifiers: &Modifiers) -> Option<Shortcut> {
+        self.bindings
+            .iter()
+            .find(|(combo, _)| combo.matches(code, modifiers))
+            .map(|(_, shortcut)| *shortcut)
+    }
+}
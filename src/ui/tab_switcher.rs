@@ -1,3 +1,16 @@
+//! `TabSwitcher`: a tab bar over one content area per tab.
+//!
+//! Each tab's content is built the first time the tab is shown and then kept,
+//! hidden while another tab is active, so switching back and forth doesn't
+//! rebuild dozens of param widgets (or lose their state, like a chat
+//! history).
+//!
+//! A [`TabSwitcherEvent::Toggle`] sent down the tree shows a tab from outside
+//! the bar (the editor's shortcuts do this); switchers without that tab
+//! ignore it.
+
+use std::rc::Rc;
+
 use vizia_plug::vizia::prelude::*;
 
 pub const TABSWITCHER_THEME: &str = r#"
@@ -62,20 +75,30 @@ impl TabDefinition {
 #[derive(Lens, Clone, Data)]
 pub struct TabSwitcherData {
     pub active_tab_id: String,
+    /// The tab shown before the active one, for [`TabSwitcherEvent::Toggle`].
+    pub previous_tab_id: String,
     pub tabs: Vec<TabDefinition>,
+    /// Tabs whose content has been built, in the order they were first shown.
+    pub built: Vec<String>,
 }
 
 pub enum TabSwitcherEvent {
     SetActiveTab(String),
     SetTabs(Vec<TabDefinition>),
+    /// Show this tab, or go back to the one before it if it's showing.
+    Toggle(String),
 }
 
 impl Model for TabSwitcherData {
     fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
         event.map(|tab_event, _| match tab_event {
-            TabSwitcherEvent::SetActiveTab(tab_id) => {
-                if self.tabs.iter().any(|t| t.id == *tab_id) {
-                    self.active_tab_id = tab_id.clone();
+            TabSwitcherEvent::SetActiveTab(tab_id) => self.show(tab_id),
+            TabSwitcherEvent::Toggle(tab_id) => {
+                if self.active_tab_id == *tab_id {
+                    let previous = self.previous_tab_id.clone();
+                    self.show(&previous);
+                } else {
+                    self.show(tab_id);
                 }
             }
             TabSwitcherEvent::SetTabs(tabs) => {
@@ -85,6 +108,7 @@ impl Model for TabSwitcherData {
                         self.active_tab_id = first.id.clone();
                     }
                 }
+                self.mark_built();
             }
         });
     }
@@ -94,16 +118,26 @@ impl TabSwitcherData {
     pub fn new(tabs: Vec<TabDefinition>) -> Self {
         let active_tab_id = tabs.first().map(|t| t.id.clone()).unwrap_or_default();
         Self {
+            built: vec![active_tab_id.clone()],
+            previous_tab_id: active_tab_id.clone(),
             active_tab_id,
             tabs,
         }
     }
 
-    pub fn get_active_tab_index(&self) -> usize {
-        self.tabs
-            .iter()
-            .position(|t| t.id == self.active_tab_id)
-            .unwrap_or(0)
+    /// Make `tab_id` the active tab, if it is one of ours.
+    fn show(&mut self, tab_id: &str) {
+        if tab_id != self.active_tab_id && self.tabs.iter().any(|t| t.id == tab_id) {
+            self.previous_tab_id = std::mem::replace(&mut self.active_tab_id, tab_id.to_string());
+            self.mark_built();
+        }
+    }
+
+    /// Note that the active tab's content exists (or is about to).
+    fn mark_built(&mut self) {
+        if !self.built.contains(&self.active_tab_id) {
+            self.built.push(self.active_tab_id.clone());
+        }
     }
 }
 
@@ -140,19 +174,40 @@ impl TabSwitcher {
             .height(Pixels(40.0))
             .class("tabbar");
 
-            Binding::new(
-                cx,
-                TabSwitcherData::active_tab_id,
-                move |cx, active_tab_id| {
-                    let data = cx.data::<TabSwitcherData>().unwrap();
-                    let active_index = data.get_active_tab_index();
-                    let active_id = active_tab_id.get(cx);
-                    VStack::new(cx, |cx| {
-                        content_builder(cx, &*active_id, active_index);
-                    })
-                    .class("tabcontent");
-                },
-            );
+            // One content area per tab, built on first show and hidden while
+            // inactive. Only a change to the tab list rebuilds them all.
+            let content_builder = Rc::new(content_builder);
+            Binding::new(cx, TabSwitcherData::tabs, move |cx, tabs_lens| {
+                let tabs_vec = tabs_lens.get(cx);
+                for (index, tab) in tabs_vec.into_iter().enumerate() {
+                    let content_builder = content_builder.clone();
+                    let id = tab.id;
+                    let shown = TabSwitcherData::built.map({
+                        let id = id.clone();
+                        move |built| built.contains(&id)
+                    });
+                    Binding::new(cx, shown, move |cx, shown| {
+                        if !shown.get(cx) {
+                            return;
+                        }
+                        let active = TabSwitcherData::active_tab_id.map({
+                            let id = id.clone();
+                            move |active| {
+                                if *active == id {
+                                    Display::Flex
+                                } else {
+                                    Display::None
+                                }
+                            }
+                        });
+                        VStack::new(cx, |cx| {
+                            content_builder(cx, &id, index);
+                        })
+                        .class("tabcontent")
+                        .display(active);
+                    });
+                }
+            });
         })
     }
 
@@ -0,0 +1,243 @@
+//! Toasts: transient notices for parameter changes the user didn't make by
+//! hand (the AI assistant's tools, scheduled changes), so knobs never seem to
+//! move on their own. Each toast can undo the change it reports.
+//!
+//! Whoever writes the params records its `RawParamEvent`s in a
+//! [`ChangeRecorder`], which remembers each param's value from before the
+//! write, and emits the resulting [`Toast`] as a [`ToastEvent::Show`]. The
+//! tools do this from the chat's background task through its `ContextProxy`;
+//! the event bubbles up to the [`ToastState`] that [`install`] builds at the
+//! editor root.
+
+use std::time::Instant;
+
+use nih_plug::prelude::{ParamPtr, Params};
+use vizia_plug::vizia::prelude::*;
+use vizia_plug::widgets::RawParamEvent;
+
+use crate::SineParams;
+
+/// CSS for the toast card, floated over the bottom of the window.
+pub const TOAST_CSS: &str = r#"
+    .toast {
+        position-type: absolute;
+        bottom: 18px;
+        left: 1s;
+        right: 1s;
+        width: auto;
+        height: 34px;
+        background-color: #1C1C22F2;
+        border: 1px solid #6366F1;
+        corner-radius: 8px;
+        padding-left: 12px;
+        padding-right: 6px;
+        gap: 8px;
+        alignment: center;
+    }
+    .toast-text {
+        color: #E5E7EB;
+        font-size: 10px;
+        width: auto;
+    }
+    .toast-btn {
+        height: 22px;
+        background-color: transparent;
+        border: 1px solid #2E3340;
+        corner-radius: 4px;
+        color: #A5B4FC;
+        font-size: 9px;
+        font-weight: 700;
+        padding-left: 8px;
+        padding-right: 8px;
+        alignment: center;
+    }
+    .toast-btn:hover {
+        border-color: #6366F1;
+        color: #F8FAFC;
+    }
+"#;
+
+/// How long a toast stays up.
+const TOAST_LIFETIME: Duration = Duration::from_secs(5);
+/// How often an open toast checks whether it has expired.
+const EXPIRY_CHECK: Duration = Duration::from_millis(250);
+
+/// A notice for one batch of param writes, with what it takes to undo them.
+pub struct Toast {
+    text: String,
+    /// Each changed param and its normalized value before the change.
+    undo: Vec<(ParamPtr, f32)>,
+}
+
+/// Collects the params a batch of `RawParamEvent`s changes. Feed it every
+/// event *before* it is emitted: it reads each param's old value the first
+/// time the param is written.
+#[derive(Default)]
+pub struct ChangeRecorder {
+    /// `(param, before, after)`, normalized, in first-write order.
+    changes: Vec<(ParamPtr, f32, f32)>,
+}
+
+impl ChangeRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, event: &RawParamEvent) {
+        let RawParamEvent::SetParameterNormalized(ptr, normalized) = *event else {
+            return;
+        };
+        match self.changes.iter_mut().find(|(p, _, _)| *p == ptr) {
+            Some(change) => change.2 = normalized,
+            None => {
+                // SAFETY: every `ParamPtr` written here points into the
+                // plugin's `SineParams`, which outlives the editor and its
+                // background tasks.
+                let before = unsafe { ptr.unmodulated_normalized_value() };
+                self.changes.push((ptr, before, normalized));
+            }
+        }
+    }
+
+    /// The toast for what was recorded, or `None` if nothing actually changed.
+    /// A single change is spelled out ("Assistant set Filter Cutoff → 2.1
+    /// kHz"); anything bigger uses `summary`.
+    pub fn into_toast(
+        mut self,
+        params: &SineParams,
+        source: &str,
+        summary: impl FnOnce() -> String,
+    ) -> Option<Toast> {
+        self.changes.retain(|&(_, before, after)| before != after);
+        let text = match self.changes.as_slice() {
+            [] => return None,
+            [(ptr, _, after)] => {
+                // SAFETY: as in `record`.
+                let value = unsafe { ptr.normalized_value_to_string(*after, true) };
+                format!("{source} set {} → {value}", param_label(params, *ptr))
+            }
+            _ => summary(),
+        };
+        Some(Toast {
+            text,
+            undo: self.into_undo(),
+        })
+    }
+
+    /// What it takes to undo what was recorded: each param that actually
+    /// changed and its normalized value before the change.
+    pub fn into_undo(self) -> Vec<(ParamPtr, f32)> {
+        self.changes
+            .into_iter()
+            .filter(|&(_, before, after)| before != after)
+            .map(|(ptr, before, _)| (ptr, before))
+            .collect()
+    }
+}
+
+/// A param's display name with its group where the name alone is ambiguous
+/// ("Oscillator 2 Gain", but plain "Filter Cutoff").
+pub fn param_label(params: &SineParams, ptr: ParamPtr) -> String {
+    // SAFETY: as in `ChangeRecorder::record`.
+    let name = unsafe { ptr.name() }.to_string();
+    let group = params
+        .param_map()
+        .into_iter()
+        .find(|(_, p, _)| *p == ptr)
+        .map(|(_, _, group)| group)
+        .unwrap_or_default();
+    match group.split_whitespace().next() {
+        Some(first) if !name.starts_with(first) => format!("{group} {name}"),
+        _ => name,
+    }
+}
+
+pub enum ToastEvent {
+    Show(Toast),
+    /// Put every param the current toast reports back how it was.
+    Undo,
+    Dismiss,
+    /// Timer tick: hide the toast once it has been up long enough.
+    Expire,
+}
+
+#[derive(Lens)]
+pub struct ToastState {
+    visible: bool,
+    text: String,
+    undo: Vec<(ParamPtr, f32)>,
+    shown_at: Option<Instant>,
+}
+
+impl ToastState {
+    fn hide(&mut self) {
+        self.visible = false;
+        self.undo.clear();
+        self.shown_at = None;
+    }
+}
+
+impl Model for ToastState {
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|e, _| match e {
+            ToastEvent::Show(toast) => {
+                self.text = toast.text.clone();
+                self.undo = toast.undo.clone();
+                self.shown_at = Some(Instant::now());
+                self.visible = true;
+            }
+            ToastEvent::Undo => {
+                for &(ptr, before) in &self.undo {
+                    cx.emit(RawParamEvent::BeginSetParameter(ptr));
+                    cx.emit(RawParamEvent::SetParameterNormalized(ptr, before));
+                    cx.emit(RawParamEvent::EndSetParameter(ptr));
+                }
+                self.hide();
+            }
+            ToastEvent::Dismiss => self.hide(),
+            ToastEvent::Expire => {
+                if self.shown_at.is_some_and(|t| t.elapsed() >= TOAST_LIFETIME) {
+                    self.hide();
+                }
+            }
+        });
+    }
+}
+
+/// Build the toast model and its expiry timer. Call at the editor root, so
+/// `ToastEvent`s emitted anywhere below reach it.
+pub fn install(cx: &mut Context) {
+    ToastState {
+        visible: false,
+        text: String::new(),
+        undo: Vec::new(),
+        shown_at: None,
+    }
+    .build(cx);
+
+    let timer = cx.add_timer(EXPIRY_CHECK, None, |cx, action| {
+        if let TimerAction::Tick(_) = action {
+            cx.emit(ToastEvent::Expire);
+        }
+    });
+    cx.start_timer(timer);
+}
+
+/// The toast card. Build it last in the root stack so it draws on top.
+pub fn toast_overlay(cx: &mut Context) {
+    Binding::new(cx, ToastState::visible, |cx, visible| {
+        if !visible.get(cx) {
+            return;
+        }
+        HStack::new(cx, |cx| {
+            Label::new(cx, ToastState::text).class("toast-text");
+            Button::new(cx, |cx| Label::new(cx, "UNDO"))
+                .on_press(|cx| cx.emit(ToastEvent::Undo))
+                .class("toast-btn");
+            Button::new(cx, |cx| Label::new(cx, "✕"))
+                .on_press(|cx| cx.emit(ToastEvent::Dismiss))
+                .class("toast-btn");
+        })
+        .class("toast");
+    });
+}
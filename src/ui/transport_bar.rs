@@ -0,0 +1,78 @@
+//! The editor's footer: the tempo the engine plays at, where it comes from,
+//! the time signature and the host's transport (see
+//! [`TransportInfo`](crate::diagnostics::TransportInfo)).
+//!
+//! Reads [`Diagnostics`] on a refresh timer like the voice monitor: the audio
+//! thread publishes the transport once per block and the text is built here.
+
+use std::sync::Arc;
+
+use vizia_plug::vizia::prelude::*;
+
+use crate::Diagnostics;
+
+/// CSS for the footer strip.
+pub const TRANSPORT_BAR_CSS: &str = r#"
+    .transport-bar {
+        height: 22px;
+        background-color: #121216;
+        border-width: 1px 0px 0px 0px;
+        border-color: #26262E;
+        padding-left: 18px;
+        padding-right: 18px;
+        alignment: left;
+    }
+    .transport-text {
+        color: #94A3B8;
+        font-size: 9px;
+    }
+"#;
+
+/// Refresh cadence: fast enough for the beat counter to tick along.
+const REFRESH: Duration = Duration::from_millis(100);
+
+#[derive(Lens)]
+struct TransportBarData {
+    diagnostics: Arc<Diagnostics>,
+    summary: String,
+}
+
+enum TransportBarEvent {
+    Refresh,
+}
+
+impl TransportBarData {
+    fn refresh(&mut self) {
+        self.summary = self.diagnostics.transport().summary();
+    }
+}
+
+impl Model for TransportBarData {
+    fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
+        event.map(|e, _| match e {
+            TransportBarEvent::Refresh => self.refresh(),
+        });
+    }
+}
+
+/// Build the footer. It owns its model and timer.
+pub fn transport_bar(cx: &mut Context, diagnostics: Arc<Diagnostics>) {
+    HStack::new(cx, move |cx| {
+        let mut data = TransportBarData {
+            diagnostics: diagnostics.clone(),
+            summary: String::new(),
+        };
+        data.refresh();
+        data.build(cx);
+
+        let timer = cx.add_timer(REFRESH, None, |cx, action| {
+            if let TimerAction::Tick(_) = action {
+                cx.emit(TransportBarEvent::Refresh);
+            }
+        });
+        cx.start_timer(timer);
+
+        Label::new(cx, TransportBarData::summary).class("transport-text");
+    })
+    .class("transport-bar");
+}
@@ -0,0 +1,135 @@
+//! Session stats: an overlay summing up how the synth has been used since it
+//! loaded — playing time, polyphony, which waveforms sounded and how much CPU
+//! was left over. Everything stays on this machine (see
+//! [`UsageStats`](crate::diagnostics::UsageStats)).
+//!
+//! Reads [`Diagnostics`] on a refresh timer like the voice monitor.
+
+use std::sync::Arc;
+
+use vizia_plug::vizia::prelude::*;
+
+use crate::Diagnostics;
+
+/// CSS for the overlay panel and its rows.
+pub const USAGE_PANEL_CSS: &str = r#"
+    .usage-panel {
+        position-type: absolute;
+        top: 64px;
+        right: 18px;
+        left: 1s;
+        width: 260px;
+        height: auto;
+        background-color: #121216F2;
+        border: 1px solid #2E3340;
+        corner-radius: 8px;
+        padding: 12px;
+        gap: 6px;
+    }
+    .usage-panel-head {
+        height: 18px;
+        alignment: left;
+    }
+    .usage-panel-title {
+        color: #F8FAFC;
+        font-size: 10px;
+        font-weight: 700;
+        width: 1s;
+    }
+    .usage-panel-row {
+        color: #CBD5E1;
+        font-size: 9px;
+        height: 14px;
+    }
+"#;
+
+/// Refresh cadence; the numbers are session averages, so they move slowly.
+const REFRESH: Duration = Duration::from_millis(500);
+
+#[derive(Lens)]
+struct UsagePanelData {
+    diagnostics: Arc<Diagnostics>,
+    rows: Vec<String>,
+}
+
+enum UsagePanelEvent {
+    Refresh,
+    Reset,
+}
+
+impl UsagePanelData {
+    fn refresh(&mut self) {
+        let stats = self.diagnostics.usage_stats();
+        let minutes = |seconds: f32| format!("{}:{:02}", seconds as u32 / 60, seconds as u32 % 60);
+        let mut rows = vec![
+            format!(
+                "Session {} · playing {}",
+                minutes(stats.session_seconds),
+                minutes(stats.playing_seconds)
+            ),
+            format!(
+                "Polyphony {:.1} average · {} peak",
+                stats.average_polyphony, stats.peak_polyphony
+            ),
+            format!(
+                "CPU headroom {:.0}% · load peak {:.0}%",
+                stats.cpu_headroom * 100.0,
+                stats.peak_dsp_load * 100.0
+            ),
+        ];
+        if stats.waveforms.is_empty() {
+            rows.push("No waveforms played yet".to_string());
+        }
+        rows.extend(
+            stats
+                .waveforms
+                .iter()
+                .map(|w| format!("{:<9} {:>4.0}%", w.waveform, w.share * 100.0)),
+        );
+        self.rows = rows;
+    }
+}
+
+impl Model for UsagePanelData {
+    fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
+        event.map(|e, _| match e {
+            UsagePanelEvent::Refresh => self.refresh(),
+            UsagePanelEvent::Reset => {
+                self.diagnostics.reset_usage();
+                self.refresh();
+            }
+        });
+    }
+}
+
+/// Build the stats panel. It owns its model and timer, so it can be mounted
+/// behind a `Binding`.
+pub fn usage_panel(cx: &mut Context, diagnostics: Arc<Diagnostics>) {
+    VStack::new(cx, move |cx| {
+        let mut data = UsagePanelData {
+            diagnostics: diagnostics.clone(),
+            rows: Vec::new(),
+        };
+        data.refresh();
+        data.build(cx);
+
+        let timer = cx.add_timer(REFRESH, None, |cx, action| {
+            if let TimerAction::Tick(_) = action {
+                cx.emit(UsagePanelEvent::Refresh);
+            }
+        });
+        cx.start_timer(timer);
+
+        HStack::new(cx, |cx| {
+            Label::new(cx, "SESSION STATS").class("usage-panel-title");
+            Button::new(cx, |cx| Label::new(cx, "RESET"))
+                .on_press(|cx| cx.emit(UsagePanelEvent::Reset))
+                .class("header-btn");
+        })
+        .class("usage-panel-head");
+        List::new(cx, UsagePanelData::rows, |cx, _, row| {
+            Label::new(cx, row).class("usage-panel-row");
+        });
+    })
+    .class("usage-panel");
+}
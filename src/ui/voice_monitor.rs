@@ -0,0 +1,120 @@
+//! Voice monitor: a debug overlay listing what the voice allocator is doing and
+//! the pitch each voice is sounding, so glides can be watched as they happen.
+//!
+//! Reads [`Diagnostics`] on a refresh timer, the same way [`super::Meter`]
+//! samples the [`super::PeakMeter`]: the audio thread only ever stores
+//! atomics, and all formatting happens here on the GUI thread.
+
+use std::sync::Arc;
+
+use vizia_plug::vizia::prelude::*;
+
+use crate::Diagnostics;
+
+/// CSS for the overlay panel and its rows.
+pub const VOICE_MONITOR_CSS: &str = r#"
+    .voice-monitor {
+        position-type: absolute;
+        top: 64px;
+        right: 18px;
+        left: 1s;
+        width: 280px;
+        height: auto;
+        background-color: #121216F2;
+        border: 1px solid #2E3340;
+        corner-radius: 8px;
+        padding: 12px;
+        gap: 6px;
+    }
+    .voice-monitor-title {
+        color: #F8FAFC;
+        font-size: 10px;
+        font-weight: 700;
+    }
+    .voice-monitor-summary {
+        color: #94A3B8;
+        font-size: 9px;
+    }
+    .voice-monitor-row {
+        color: #CBD5E1;
+        font-size: 9px;
+        height: 14px;
+    }
+"#;
+
+/// Refresh cadence. Fast enough to watch steals happen, slow enough to read.
+const REFRESH: Duration = Duration::from_millis(100);
+
+#[derive(Lens)]
+struct VoiceMonitorData {
+    diagnostics: Arc<Diagnostics>,
+    summary: String,
+    rows: Vec<String>,
+}
+
+enum VoiceMonitorEvent {
+    Refresh,
+}
+
+impl VoiceMonitorData {
+    fn refresh(&mut self) {
+        let stats = self.diagnostics.voice_stats();
+        self.summary = format!(
+            "{}/{} active · {} steals · {:.1}/s",
+            stats.active_voices, stats.max_voices, stats.total_steals, stats.steals_per_second
+        );
+        self.rows = stats
+            .voices
+            .iter()
+            .map(|v| {
+                // The played note, then what is actually sounding: they differ
+                // while a glide is in flight or pitch modulation is active.
+                format!(
+                    "#{:<2} {:<4} {} {:<9} {:>6.0} ms{}",
+                    v.slot,
+                    v.note_name,
+                    if v.gliding { "↝" } else { "→" },
+                    v.pitch_name,
+                    v.age_ms,
+                    if v.releasing { "  rel" } else { "" }
+                )
+            })
+            .collect();
+    }
+}
+
+impl Model for VoiceMonitorData {
+    fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
+        event.map(|e, _| match e {
+            VoiceMonitorEvent::Refresh => self.refresh(),
+        });
+    }
+}
+
+/// Build the voice monitor panel. It owns its own model and timer, so it can be
+/// mounted and unmounted freely (e.g. behind a `Binding`).
+pub fn voice_monitor(cx: &mut Context, diagnostics: Arc<Diagnostics>) {
+    VStack::new(cx, move |cx| {
+        let mut data = VoiceMonitorData {
+            diagnostics: diagnostics.clone(),
+            summary: String::new(),
+            rows: Vec::new(),
+        };
+        data.refresh();
+        data.build(cx);
+
+        let timer = cx.add_timer(REFRESH, None, |cx, action| {
+            if let TimerAction::Tick(_) = action {
+                cx.emit(VoiceMonitorEvent::Refresh);
+            }
+        });
+        cx.start_timer(timer);
+
+        Label::new(cx, "VOICE MONITOR").class("voice-monitor-title");
+        Label::new(cx, VoiceMonitorData::summary).class("voice-monitor-summary");
+        List::new(cx, VoiceMonitorData::rows, |cx, _, row| {
+            Label::new(cx, row).class("voice-monitor-row");
+        });
+    })
+    .class("voice-monitor");
+}
@@ -0,0 +1,182 @@
+//! `XyPadView` — a Skia-drawn two-axis pad bound to a pair of `nih_plug`
+//! params, one per axis, via [`ParamWidgetBase`].
+//!
+//! Clicking jumps the puck to the cursor and dragging moves it; both params
+//! share one gesture from mouse down to mouse up. Double-click puts both back
+//! to their defaults. Like [`super::ParamKnob`], it redraws from a `Binding`
+//! on the live values, so host automation and AI writes move the puck too.
+//!
+//! Colours come from CSS: `background-color` is the pad, `color` the puck and
+//! its crosshair. The editor uses it for the XY pads (see `crate::xy`), but it
+//! takes any two params.
+
+use nih_plug::prelude::Param;
+use vizia_plug::vizia::prelude::*;
+use vizia_plug::vizia::vg;
+use vizia_plug::widgets::param_base::ParamWidgetBase;
+
+pub const XY_PAD_CSS: &str = r#"
+    .xy-pad {
+        width: 150px;
+        height: 150px;
+        background-color: #16161C;
+        color: #818CF8;
+        cursor: hand;
+    }
+    /* What an axis drives, under the pad in the editor. */
+    .xy-target {
+        color: #CBD5E1;
+        font-size: 9px;
+        width: 1s;
+    }
+"#;
+
+/// Puck radius, in pixels.
+const PUCK_RADIUS: f32 = 7.0;
+
+pub struct XyPadView {
+    x: ParamWidgetBase,
+    y: ParamWidgetBase,
+    dragging: bool,
+}
+
+impl XyPadView {
+    pub fn new<L, Params, PX, PY, FX, FY>(
+        cx: &mut Context,
+        params: L,
+        x_param: FX,
+        y_param: FY,
+    ) -> Handle<'_, Self>
+    where
+        L: Lens<Target = Params> + Clone,
+        Params: 'static,
+        PX: Param + 'static,
+        PY: Param + 'static,
+        FX: Fn(&Params) -> &PX + Copy + 'static,
+        FY: Fn(&Params) -> &PY + Copy + 'static,
+    {
+        let mut handle = Self {
+            x: ParamWidgetBase::new(cx, params.clone(), x_param),
+            y: ParamWidgetBase::new(cx, params.clone(), y_param),
+            dragging: false,
+        }
+        .build(cx, |_| {})
+        .class("xy-pad");
+
+        // `draw` reads both values from the param bases; these bindings only
+        // mark the view dirty when either moves.
+        let entity = handle.entity();
+        let x_lens =
+            ParamWidgetBase::make_lens(params.clone(), x_param, |p| p.modulated_normalized_value());
+        Binding::new(handle.context(), x_lens, move |cx, _| {
+            cx.needs_redraw(entity)
+        });
+        let y_lens =
+            ParamWidgetBase::make_lens(params, y_param, |p| p.modulated_normalized_value());
+        Binding::new(handle.context(), y_lens, move |cx, _| {
+            cx.needs_redraw(entity)
+        });
+
+        handle
+    }
+
+    /// Move both params to the cursor; up is `1` on the Y axis.
+    fn follow_cursor(&self, cx: &mut EventContext) {
+        let bounds = cx.bounds();
+        if bounds.w <= 0.0 || bounds.h <= 0.0 {
+            return;
+        }
+        let x = ((cx.mouse().cursor_x - bounds.x) / bounds.w).clamp(0.0, 1.0);
+        let y = (1.0 - (cx.mouse().cursor_y - bounds.y) / bounds.h).clamp(0.0, 1.0);
+        self.x.set_normalized_value(cx, x);
+        self.y.set_normalized_value(cx, y);
+    }
+}
+
+impl View for XyPadView {
+    fn element(&self) -> Option<&'static str> {
+        Some("xy-pad")
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &Canvas) {
+        let bounds = cx.bounds();
+        if bounds.w <= 0.0 || bounds.h <= 0.0 {
+            return;
+        }
+
+        let opacity = cx.opacity();
+        let pad_color = cx.background_color();
+        let accent_color = cx.font_color();
+        let pad = vg::Color::from_argb(255, pad_color.r(), pad_color.g(), pad_color.b());
+        let accent =
+            vg::Color::from_argb(255, accent_color.r(), accent_color.g(), accent_color.b());
+
+        let rect = vg::Rect::new(bounds.x, bounds.y, bounds.x + bounds.w, bounds.y + bounds.h);
+        let mut surface = vg::Paint::default();
+        surface.set_anti_alias(true);
+        surface.set_color(pad);
+        surface.set_alpha_f(opacity);
+        canvas.draw_round_rect(rect, 6.0, 6.0, &surface);
+
+        let px = bounds.x + bounds.w * self.x.modulated_normalized_value().clamp(0.0, 1.0);
+        let py = bounds.y + bounds.h * (1.0 - self.y.modulated_normalized_value().clamp(0.0, 1.0));
+
+        // Crosshair through the puck, dim.
+        let mut line = vg::Paint::default();
+        line.set_anti_alias(true);
+        line.set_style(vg::PaintStyle::Stroke);
+        line.set_stroke_width(1.0);
+        line.set_color(vg::Color::from_argb(60, accent.r(), accent.g(), accent.b()));
+        line.set_alpha_f(line.alpha_f() * opacity);
+        canvas.draw_line((px, bounds.y), (px, bounds.y + bounds.h), &line);
+        canvas.draw_line((bounds.x, py), (bounds.x + bounds.w, py), &line);
+
+        // The puck, with a soft halo while it's held.
+        let mut puck = vg::Paint::default();
+        puck.set_anti_alias(true);
+        if self.dragging {
+            puck.set_color(vg::Color::from_argb(70, accent.r(), accent.g(), accent.b()));
+            puck.set_alpha_f(puck.alpha_f() * opacity);
+            canvas.draw_circle((px, py), PUCK_RADIUS * 1.8, &puck);
+        }
+        puck.set_color(accent);
+        puck.set_alpha_f(opacity);
+        canvas.draw_circle((px, py), PUCK_RADIUS, &puck);
+    }
+
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|window_event, meta| match window_event {
+            WindowEvent::MouseDown(MouseButton::Left) => {
+                cx.capture();
+                cx.set_active(true);
+                self.dragging = true;
+                self.x.begin_set_parameter(cx);
+                self.y.begin_set_parameter(cx);
+                self.follow_cursor(cx);
+                meta.consume();
+            }
+            WindowEvent::MouseMove(_, _) if self.dragging => {
+                self.follow_cursor(cx);
+                meta.consume();
+            }
+            WindowEvent::MouseUp(MouseButton::Left) if self.dragging => {
+                cx.release();
+                cx.set_active(false);
+                self.dragging = false;
+                self.x.end_set_parameter(cx);
+                self.y.end_set_parameter(cx);
+                cx.needs_redraw();
+                meta.consume();
+            }
+            WindowEvent::MouseDoubleClick(MouseButton::Left) => {
+                for base in [&self.x, &self.y] {
+                    base.begin_set_parameter(cx);
+                    base.set_normalized_value(cx, base.default_normalized_value());
+                    base.end_set_parameter(cx);
+                }
+                meta.consume();
+            }
+            _ => {}
+        });
+    }
+}
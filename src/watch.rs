@@ -0,0 +1,96 @@
+//! Automation preview: the audio thread samples one param the editor picked
+//! over the last few seconds, both as the host set it (automation, host
+//! modulation) and as the engine smoothed it, so a sweep that sounds wrong can
+//! be seen. The editor draws it (see `ui::automation_preview`).
+//!
+//! Params are picked by their index in `param_map()`; [`ParamWatch`] keeps the
+//! pointers, looked up once when the plugin is built, so the audio thread
+//! never searches for one.
+
+use nih_plug::prelude::{Param, ParamPtr, Params};
+
+use crate::{Diagnostics, SineParams};
+
+/// Points in the watch history.
+pub const WATCH_POINTS: usize = 256;
+/// How far back the watch history reaches.
+pub const WATCH_SECONDS: f32 = 4.0;
+
+pub struct ParamWatch {
+    /// Every param, in `param_map()` order.
+    params: Vec<ParamPtr>,
+    watched: Option<ParamPtr>,
+    /// Samples per watch point.
+    interval: u32,
+    countdown: u32,
+}
+
+impl ParamWatch {
+    pub fn new(p: &SineParams, sample_rate: f32) -> Self {
+        let mut watch = Self {
+            params: p.param_map().into_iter().map(|(_, ptr, _)| ptr).collect(),
+            watched: None,
+            interval: 1,
+            countdown: 0,
+        };
+        watch.set_sample_rate(sample_rate);
+        watch
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        let samples = WATCH_SECONDS * sample_rate / WATCH_POINTS as f32;
+        self.interval = samples.round().max(1.0) as u32;
+        self.countdown = 0;
+    }
+
+    /// Once per block: picks up the param the editor asked for, starting its
+    /// history over when it changed.
+    pub fn begin_block(&mut self, diagnostics: &Diagnostics) {
+        let watched = diagnostics
+            .watched_param()
+            .and_then(|index| self.params.get(index).copied());
+        if watched != self.watched {
+            self.watched = watched;
+            self.countdown = 0;
+            diagnostics.restart_watch();
+        }
+    }
+
+    /// Once per sample, after the smoothers have advanced.
+    #[inline]
+    pub fn next(&mut self, diagnostics: &Diagnostics) {
+        let Some(ptr) = self.watched else {
+            return;
+        };
+        if self.countdown > 0 {
+            self.countdown -= 1;
+            return;
+        }
+        self.countdown = self.interval - 1;
+        let (set, smoothed) = values(ptr);
+        diagnostics.publish_watch(set, smoothed);
+    }
+}
+
+/// `ptr`'s normalized value as set and as smoothed. Once a smoother has
+/// arrived, and for params without one (switches, choices), both are the
+/// same.
+fn values(ptr: ParamPtr) -> (f32, f32) {
+    // SAFETY: the pointers come from the plugin's own `SineParams`, which it
+    // holds for as long as it has a `ParamWatch`.
+    unsafe {
+        let set = ptr.modulated_normalized_value();
+        let smoothed = match ptr {
+            ParamPtr::FloatParam(param) if (*param).smoothed.is_smoothing() => {
+                let param = &*param;
+                param.preview_normalized(param.smoothed.previous_value())
+            }
+            ParamPtr::IntParam(param) if (*param).smoothed.is_smoothing() => {
+                let param = &*param;
+                param.preview_normalized(param.smoothed.previous_value())
+            }
+            _ => set,
+        };
+        (set, smoothed)
+    }
+}
@@ -0,0 +1,233 @@
+//! XY pads: two performance pads whose axes each drive a param, over a chosen
+//! part of its range, for macro moves like sweeping cutoff and resonance at
+//! once.
+//!
+//! A pad's position is a pair of params (`xy1_x` ... `xy2_y`), so hosts
+//! automate it and the `set_xy` tool moves it like any other write. What each
+//! axis drives is project state (see [`XyPad`]). An axis never writes its
+//! target: like a mod source, it offsets the value the engine reads, every
+//! sample on the audio thread ([`XyRunner`]), so the target's knob and its
+//! automation stay as they were and the pads work with the editor closed.
+//!
+//! The audio thread can't read the project state, so whoever assigns an axis
+//! publishes it as [`XyRoutes`]: the target's index in `param_map()` order,
+//! as for the automation preview (see `watch.rs`), and its offsets.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use nih_plug::prelude::{FloatParam, Param, ParamPtr, Params};
+
+use crate::persist::{ProjectState, XyAxis, XyPad};
+use crate::{SineParams, XyPadParams};
+
+pub const NUM_XY_PADS: usize = 2;
+
+/// Pad `index` (`0..NUM_XY_PADS`).
+pub fn pad(p: &SineParams, index: usize) -> &XyPadParams {
+    [&p.xy1, &p.xy2][index]
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+impl Axis {
+    pub const ALL: [Axis; 2] = [Self::X, Self::Y];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::X => "x",
+            Self::Y => "y",
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|axis| axis.id().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown axis '{s}' (expected x or y)"))
+    }
+
+    pub fn param(self, pad: &XyPadParams) -> &FloatParam {
+        match self {
+            Self::X => &pad.x,
+            Self::Y => &pad.y,
+        }
+    }
+
+    pub fn assignment(self, pad: &XyPad) -> Option<&XyAxis> {
+        match self {
+            Self::X => pad.x.as_ref(),
+            Self::Y => pad.y.as_ref(),
+        }
+    }
+
+    fn assignment_mut(self, pad: &mut XyPad) -> &mut Option<XyAxis> {
+        match self {
+            Self::X => &mut pad.x,
+            Self::Y => &mut pad.y,
+        }
+    }
+}
+
+/// The project's pad assignments.
+pub fn assignments(p: &SineParams) -> [XyPad; NUM_XY_PADS] {
+    p.project_state
+        .read()
+        .map(|state| state.xy_pads.clone())
+        .unwrap_or_default()
+}
+
+/// The param with host ID `id`, unless it's one of the pads' own.
+pub fn target(p: &SineParams, id: &str) -> Result<ParamPtr, String> {
+    let (_, ptr, _) = p
+        .param_map()
+        .into_iter()
+        .find(|(param, _, _)| param == id)
+        .ok_or_else(|| format!("unknown parameter '{id}'"))?;
+    let own = (0..NUM_XY_PADS)
+        .flat_map(|i| Axis::ALL.map(|axis| axis.param(pad(p, i)).as_ptr()))
+        .any(|own| own == ptr);
+    if own {
+        return Err("an XY pad can't drive another pad".to_string());
+    }
+    Ok(ptr)
+}
+
+/// Point an axis at a param, or with `None` free it.
+pub fn assign(
+    p: &SineParams,
+    index: usize,
+    axis: Axis,
+    assignment: Option<XyAxis>,
+) -> Result<(), String> {
+    if let Some(assignment) = &assignment {
+        target(p, &assignment.param)?;
+        let in_range = |v: f32| (-1.0..=1.0).contains(&v);
+        if !in_range(assignment.min) || !in_range(assignment.max) {
+            return Err("an XY offset is normalized, within -1..1".to_string());
+        }
+    }
+    ProjectState::update(p, |state| {
+        *axis.assignment_mut(&mut state.xy_pads[index]) = assignment;
+    });
+    publish(p);
+    Ok(())
+}
+
+/// Where an axis's ends put its target right now, normalized: the target's
+/// own value offset by `min` and by `max`.
+pub fn endpoints(ptr: ParamPtr, assignment: &XyAxis) -> (f32, f32) {
+    // SAFETY: callers pass a pointer `target` returned, into the live params.
+    let value = unsafe { ptr.unmodulated_normalized_value() };
+    let offset = |by: f32| (value + by).clamp(0.0, 1.0);
+    (offset(assignment.min), offset(assignment.max))
+}
+
+/// An axis with no target.
+const NO_TARGET: u32 = u32::MAX;
+
+/// One axis's assignment as the audio thread reads it.
+struct Route {
+    /// Index in `param_map()` order, or [`NO_TARGET`].
+    target: AtomicU32,
+    /// `f32` bits of the offsets at `0` and `1`.
+    min: AtomicU32,
+    max: AtomicU32,
+}
+
+impl Default for Route {
+    fn default() -> Self {
+        Self {
+            target: AtomicU32::new(NO_TARGET),
+            min: AtomicU32::new(0),
+            max: AtomicU32::new(0),
+        }
+    }
+}
+
+/// Lock-free copy of the pads' assignments for the audio thread, kept in
+/// `SineParams` so anything that can assign an axis can publish it.
+#[derive(Default)]
+pub struct XyRoutes {
+    /// Every axis, pad by pad.
+    routes: [Route; NUM_XY_PADS * 2],
+}
+
+/// Hand the project's assignments to the audio thread. Called whenever they
+/// change, and when a project is loaded.
+pub fn publish(p: &SineParams) {
+    let assigned = assignments(p);
+    let map = p.param_map();
+    for (i, route) in p.xy_routes.routes.iter().enumerate() {
+        let resolved = Axis::ALL[i % 2]
+            .assignment(&assigned[i / 2])
+            .and_then(|assignment| {
+                // A param a later build dropped just leaves the axis idle.
+                let ptr = target(p, &assignment.param).ok()?;
+                let index = map.iter().position(|(_, mapped, _)| *mapped == ptr)?;
+                Some((index as u32, assignment.min, assignment.max))
+            });
+        let (index, min, max) = resolved.unwrap_or((NO_TARGET, 0.0, 0.0));
+        route.min.store(min.to_bits(), Ordering::Relaxed);
+        route.max.store(max.to_bits(), Ordering::Relaxed);
+        route.target.store(index, Ordering::Release);
+    }
+}
+
+/// Each axis's target and how far it moves it this sample, normalized.
+#[derive(Clone, Copy)]
+pub struct XyOffsets([Option<(ParamPtr, f32)>; NUM_XY_PADS * 2]);
+
+impl XyOffsets {
+    /// `plain`, `p`'s value, moved by every axis on `p` and kept in range.
+    #[inline]
+    pub fn apply<P: Param>(&self, p: &P, plain: P::Plain) -> P::Plain {
+        if self.0.iter().all(Option::is_none) {
+            return plain;
+        }
+        let ptr = p.as_ptr();
+        let offset: f32 = self
+            .0
+            .iter()
+            .flatten()
+            .filter(|(target, _)| *target == ptr)
+            .map(|(_, offset)| offset)
+            .sum();
+        if offset == 0.0 {
+            return plain;
+        }
+        p.preview_plain((p.preview_normalized(plain) + offset).clamp(0.0, 1.0))
+    }
+}
+
+/// The audio thread's half of the pads. Allocation-free after `new`.
+pub struct XyRunner {
+    /// Every param, in `param_map()` order.
+    params: Vec<ParamPtr>,
+}
+
+impl XyRunner {
+    pub fn new(p: &SineParams) -> Self {
+        Self {
+            params: p.param_map().into_iter().map(|(_, ptr, _)| ptr).collect(),
+        }
+    }
+
+    /// Once per sample: advances the pads' smoothers and returns where they
+    /// put their targets.
+    #[inline]
+    pub fn next(&self, p: &SineParams, steps: u32) -> XyOffsets {
+        XyOffsets(std::array::from_fn(|i| {
+            let position = Axis::ALL[i % 2].param(pad(p, i / 2)).smoothed.next_step(steps);
+            let route = &p.xy_routes.routes[i];
+            let index = route.target.load(Ordering::Acquire);
+            let ptr = *self.params.get(index as usize)?;
+            let min = f32::from_bits(route.min.load(Ordering::Relaxed));
+            let max = f32::from_bits(route.max.load(Ordering::Relaxed));
+            Some((ptr, min + (max - min) * position))
+        }))
+    }
+}
@@ -0,0 +1,218 @@
+//! Golden-audio tests: a fixed note sequence rendered offline through each
+//! waveform and each filter mode, compared with references in `tests/golden/`
+//! so aliasing fixes and DSP rewrites can't quietly change the sound.
+//!
+//! A render is compared by its fingerprint, not sample by sample: its
+//! long-term spectrum and its level over time, each within a tolerance. A
+//! missing reference fails the test. Write the references with `GOLDEN_BLESS=1
+//! cargo test --test golden` — at first, and after a change that is meant to
+//! alter the sound — then review the diff and commit them.
+//!
+//! The waveform morph needs no reference: its level across the range is
+//! checked against the shapes it morphs between.
+
+use std::path::PathBuf;
+
+use nih_plug::prelude::{Enum, Param};
+use serde::{Deserialize, Serialize};
+
+use osc3_mcp_rust::dsp::spectrum::{band_edge, spectrum, FLOOR_DB};
+use osc3_mcp_rust::render::{render, PatchValues, RenderNote, RenderSpec};
+use osc3_mcp_rust::{FilterMode, SineParams, Waveform};
+
+/// How far a spectrum band may move, in dB.
+const SPECTRUM_TOLERANCE_DB: f32 = 1.5;
+/// Reference bands quieter than this aren't compared: they hold only noise
+/// and leakage.
+const QUIET_DB: f32 = -80.0;
+/// Length of a level window, in seconds.
+const ENVELOPE_WINDOW: f32 = 0.05;
+/// How far a window's level may move, in dB.
+const ENVELOPE_TOLERANCE_DB: f32 = 1.0;
+/// How far a morph between two shapes may dip below the quieter of them, in dB.
+const MORPH_DIP_DB: f32 = 1.0;
+/// Morph positions checked across the range.
+const MORPH_STEPS: usize = 30;
+
+#[derive(Serialize, Deserialize)]
+struct Fingerprint {
+    /// Band levels in dB, as [`spectrum`] gives them.
+    spectrum: Vec<f32>,
+    /// RMS level per window in dB.
+    envelope: Vec<f32>,
+}
+
+fn db(level: f32) -> f32 {
+    (20.0 * level.max(1e-6).log10()).max(FLOOR_DB)
+}
+
+fn fingerprint(frames: &[[f32; 2]], sample_rate: f32) -> Fingerprint {
+    let mono: Vec<f32> = frames
+        .iter()
+        .map(|[left, right]| 0.5 * (left + right))
+        .collect();
+    let window = (ENVELOPE_WINDOW * sample_rate) as usize;
+    let envelope = mono
+        .chunks(window)
+        .map(|chunk| db((chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len() as f32).sqrt()))
+        .collect();
+    Fingerprint {
+        spectrum: spectrum(&mono, sample_rate).to_vec(),
+        envelope,
+    }
+}
+
+/// A bass note, a fifth over it and the octave, overlapping, then their
+/// release tails.
+fn sequence() -> RenderSpec {
+    let note = |note, start, length| RenderNote {
+        note,
+        velocity: 0.8,
+        start,
+        length,
+    };
+    RenderSpec {
+        notes: vec![note(45, 0.0, 1.0), note(52, 0.25, 0.75), note(57, 0.5, 0.5)],
+        seconds: 1.5,
+        sample_rate: 48_000.0,
+        bpm: 120.0,
+    }
+}
+
+fn set<P: Param>(values: &mut PatchValues, param: &P, value: P::Plain) {
+    values.insert(param.as_ptr(), param.preview_normalized(value));
+}
+
+/// Osc 1 alone as `waveform`, through the filter in `mode` at a fixed cutoff.
+fn patch(p: &SineParams, waveform: Waveform, mode: FilterMode) -> PatchValues {
+    let mut values = PatchValues::new();
+    for osc in [&p.osc2, &p.osc3] {
+        values.insert(osc.gain.as_ptr(), 0.0);
+    }
+    set(&mut values, &p.osc1.waveform, waveform);
+    set(&mut values, &p.filter.mode, mode);
+    set(&mut values, &p.filter.cutoff, 1_000.0);
+    values
+}
+
+fn golden_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+/// Render `values` and compare it with the reference `name`.
+fn check(name: &str, p: &SineParams, values: &PatchValues) {
+    let spec = sequence();
+    let actual = fingerprint(&render(p, values, &spec), spec.sample_rate);
+    let path = golden_dir().join(format!("{name}.json"));
+    if std::env::var_os("GOLDEN_BLESS").is_some() {
+        std::fs::create_dir_all(golden_dir()).unwrap();
+        std::fs::write(&path, serde_json::to_string_pretty(&actual).unwrap()).unwrap();
+        eprintln!("wrote reference {}", path.display());
+        return;
+    }
+    assert!(
+        path.exists(),
+        "no reference {}; write it with GOLDEN_BLESS=1 cargo test --test golden",
+        path.display()
+    );
+    let text = std::fs::read_to_string(&path).unwrap();
+    let expected: Fingerprint = serde_json::from_str(&text).unwrap();
+
+    let mut failures = Vec::new();
+    for (band, (want, got)) in expected.spectrum.iter().zip(&actual.spectrum).enumerate() {
+        if *want > QUIET_DB && (want - got).abs() > SPECTRUM_TOLERANCE_DB {
+            let hz = band_edge(band);
+            failures.push(format!("band from {hz:.0} Hz: {want:.1} dB → {got:.1} dB"));
+        }
+    }
+    if expected.envelope.len() != actual.envelope.len() {
+        failures.push(format!(
+            "{} level windows, expected {}",
+            actual.envelope.len(),
+            expected.envelope.len()
+        ));
+    }
+    for (index, (want, got)) in expected.envelope.iter().zip(&actual.envelope).enumerate() {
+        if (want - got).abs() > ENVELOPE_TOLERANCE_DB {
+            let at = index as f32 * ENVELOPE_WINDOW;
+            failures.push(format!("level at {at:.2}s: {want:.1} dB → {got:.1} dB"));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{name} no longer matches {}:\n{}",
+        path.display(),
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn waveforms_match_references() {
+    let p = SineParams::default();
+    for index in 0..Waveform::variants().len() {
+        let waveform = Waveform::from_index(index);
+        let values = patch(&p, waveform, FilterMode::LowPass);
+        let name = format!("waveform_{waveform:?}").to_lowercase();
+        check(&name, &p, &values);
+    }
+}
+
+#[test]
+fn filter_modes_match_references() {
+    let p = SineParams::default();
+    for index in 0..FilterMode::variants().len() {
+        let mode = FilterMode::from_index(index);
+        let values = patch(&p, Waveform::Sawtooth, mode);
+        let name = format!("filter_{mode:?}").to_lowercase();
+        check(&name, &p, &values);
+    }
+}
+
+/// Level of a held note, in dB: the RMS between its attack and its release.
+fn held_level(frames: &[[f32; 2]], sample_rate: f32) -> f32 {
+    let held = &frames[(0.5 * sample_rate) as usize..(0.9 * sample_rate) as usize];
+    let power = held
+        .iter()
+        .map(|[left, right]| (0.5 * (left + right)).powi(2))
+        .sum::<f32>();
+    db((power / held.len() as f32).sqrt())
+}
+
+/// Crossfading shapes whose fundamentals are out of phase cancels them, so
+/// the level between two shapes must not fall below either's.
+#[test]
+fn morph_keeps_its_level() {
+    let p = SineParams::default();
+    let spec = RenderSpec::single_note(45);
+    let level = |position: f32| {
+        let mut values = patch(&p, Waveform::Sine, FilterMode::LowPass);
+        set(&mut values, &p.filter.cutoff, 20_000.0);
+        set(&mut values, &p.analog, 0.0);
+        set(&mut values, &p.osc1.morph, true);
+        set(&mut values, &p.osc1.morph_position, position);
+        held_level(&render(&p, &values, &spec), spec.sample_rate)
+    };
+    // Sine, triangle, saw and square, on their own.
+    let shapes: Vec<f32> = (0..4).map(|index| level(index as f32 / 3.0)).collect();
+
+    let mut failures = Vec::new();
+    for step in 0..=MORPH_STEPS {
+        let position = step as f32 / MORPH_STEPS as f32;
+        let segment = ((position * 3.0) as usize).min(2);
+        let floor = shapes[segment].min(shapes[segment + 1]) - MORPH_DIP_DB;
+        let got = level(position);
+        if got < floor {
+            failures.push(format!("at {position:.2}: {got:.1} dB, under {floor:.1} dB"));
+        }
+    }
+    assert!(failures.is_empty(), "the morph dips:\n{}", failures.join("\n"));
+}
+
+/// The render is deterministic, or no reference could hold.
+#[test]
+fn renders_repeat_exactly() {
+    let p = SineParams::default();
+    let values = patch(&p, Waveform::Sawtooth, FilterMode::LowPass);
+    let spec = sequence();
+    assert_eq!(render(&p, &values, &spec), render(&p, &values, &spec));
+}
@@ -0,0 +1,145 @@
+//! The audio path never allocates: a counting allocator watches the voices,
+//! the arp's key tracking and the post-process stages while every parameter
+//! is swept through its range.
+//!
+//! `nih_plug`'s `assert_process_allocs` catches an allocation in `process`
+//! while the plugin runs in a debug build; this catches one in the DSP types
+//! before anything gets that far.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use nih_plug::prelude::Params;
+
+use osc3_mcp_rust::dsp::{Arpeggiator, FrameParams, Lfo, ParamRead, Tuning, Voice};
+use osc3_mcp_rust::render::PatchValues;
+use osc3_mcp_rust::SineParams;
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// Only this thread's allocations count: the test harness allocates on
+    /// others meanwhile.
+    static WATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if WATCHING.with(Cell::get) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if WATCHING.with(Cell::get) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// How many times `f` allocated.
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    WATCHING.with(|watching| watching.set(true));
+    f();
+    WATCHING.with(|watching| watching.set(false));
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+const SAMPLE_RATE: f32 = 48_000.0;
+const BPM: f32 = 120.0;
+
+/// A block's worth of what `process` does per voice: pick up the unison
+/// counts, then play a chord through its attack and into its release.
+fn play(p: &SineParams, values: &PatchValues, voices: &mut [Voice], lfo: &mut Lfo) {
+    let read = ParamRead::Fixed(values);
+    let unison = [&p.osc1, &p.osc2, &p.osc3].map(|osc| read.plain(&osc.unison_voices) as usize);
+    let tuning = Tuning {
+        transpose: 0.0,
+        reference_hz: 440.0,
+    };
+    for (index, voice) in voices.iter_mut().enumerate() {
+        voice.set_unison_voices(unison);
+        voice.note_on(48 + 7 * index as u8, 0.8, Some(40.0), tuning);
+    }
+    for sample in 0..512 {
+        if sample == 256 {
+            voices.iter_mut().for_each(Voice::note_off);
+        }
+        let lfo = lfo.next(read.plain(&p.modulation.lfo_shape), 2.0, SAMPLE_RATE);
+        let frame = FrameParams::next(p, read, lfo, 0.5, BPM, &Default::default());
+        for voice in voices.iter_mut().filter(|v| v.is_active()) {
+            voice.render(&frame, SAMPLE_RATE);
+            voice.pan_gains(&frame);
+            voice.osc_outputs();
+        }
+    }
+}
+
+#[test]
+fn voices_never_allocate() {
+    let p = SineParams::default();
+    let mut voices: Vec<Voice> = (0..4).map(|seed| Voice::new(SAMPLE_RATE, seed)).collect();
+    let mut lfo = Lfo::new();
+
+    // Every param at its ends and middle, one at a time; the maps are built
+    // up front so only the audio path is watched.
+    let mut patches = vec![("the defaults".to_string(), PatchValues::new())];
+    for (id, ptr, _) in p.param_map() {
+        for normalized in [0.0, 0.5, 1.0] {
+            let values = PatchValues::from([(ptr, normalized)]);
+            patches.push((format!("{id} at {normalized}"), values));
+        }
+    }
+    for (name, values) in &patches {
+        let count = allocations(|| play(&p, values, &mut voices, &mut lfo));
+        assert_eq!(count, 0, "the voices allocated with {name}");
+    }
+}
+
+#[test]
+fn arp_key_tracking_never_allocates() {
+    let mut arp = Arpeggiator::new();
+    let count = allocations(|| {
+        for note in 0..=127 {
+            arp.key_down(note, 0.8);
+        }
+        for note in (0..=127).step_by(3) {
+            arp.key_up(note);
+        }
+        arp.release_all();
+    });
+    assert_eq!(count, 0);
+}
+
+#[cfg(feature = "fx")]
+#[test]
+fn post_process_never_allocates() {
+    use osc3_mcp_rust::dsp::{GateFrame, StereoWidener, TranceGate};
+
+    let p = SineParams::default();
+    let mut gate = TranceGate::new();
+    let mut widener = StereoWidener::new();
+    let count = allocations(|| {
+        gate.sync(Some(0.0));
+        let frame = GateFrame::new(&p.gate, BPM, SAMPLE_RATE);
+        for (sample, width) in (0..512).zip([0.0, 1.0, 2.0].into_iter().cycle()) {
+            let input = [(sample as f32 * 0.01).sin(); 2];
+            let output = gate.process(&frame, input);
+            widener.process(width, 200.0 + sample as f32, SAMPLE_RATE, output);
+        }
+    });
+    assert_eq!(count, 0);
+}
@@ -0,0 +1,157 @@
+//! Sample-rate independence: the same patch rendered at 44.1, 96 and 192 kHz
+//! must sound like it does at 48 kHz. Envelope timing, detune, the filter and
+//! its envelope, and the comb's damping are each covered by a patch.
+//!
+//! Renders are compared window by window on level and brightness (see
+//! [`BrightnessAnalyzer`]), which read the same at any rate. A spectrum
+//! wouldn't: its bins widen with the rate.
+
+use nih_plug::prelude::Param;
+
+use osc3_mcp_rust::dsp::analysis::{BrightnessAnalyzer, BrightnessReading};
+use osc3_mcp_rust::render::{render, PatchValues, RenderNote, RenderSpec};
+use osc3_mcp_rust::{FilterMode, SineParams, Waveform};
+
+const REFERENCE_RATE: f32 = 48_000.0;
+const RATES: [f32; 3] = [44_100.0, 96_000.0, 192_000.0];
+/// How far a window's level may move, in dB.
+const LEVEL_TOLERANCE_DB: f32 = 1.0;
+/// How far a window's brightness may move, as a share of the reference's.
+const BRIGHTNESS_TOLERANCE: f32 = 0.1;
+/// Reference windows quieter than this aren't compared.
+const QUIET_DB: f32 = -60.0;
+/// Length of a [`BrightnessAnalyzer`] window, in seconds.
+const WINDOW_SECONDS: f32 = 0.1;
+
+fn db(level: f32) -> f32 {
+    20.0 * level.max(1e-6).log10()
+}
+
+/// Two overlapping notes and a release tail.
+fn spec(sample_rate: f32) -> RenderSpec {
+    let note = |note, start, length| RenderNote {
+        note,
+        velocity: 0.8,
+        start,
+        length,
+    };
+    RenderSpec {
+        notes: vec![note(45, 0.0, 0.8), note(57, 0.3, 0.5)],
+        seconds: 1.5,
+        sample_rate,
+        bpm: 120.0,
+    }
+}
+
+fn set<P: Param>(values: &mut PatchValues, param: &P, value: P::Plain) {
+    values.insert(param.as_ptr(), param.preview_normalized(value));
+}
+
+/// Osc 1 alone as a triangle (nearly alias-free, so the rates don't differ in
+/// what folds back), through a low-pass at `cutoff`, with no drift.
+fn base(p: &SineParams, cutoff: f32) -> PatchValues {
+    let mut values = PatchValues::new();
+    for osc in [&p.osc2, &p.osc3] {
+        values.insert(osc.gain.as_ptr(), 0.0);
+    }
+    set(&mut values, &p.osc1.waveform, Waveform::Triangle);
+    set(&mut values, &p.filter.mode, FilterMode::LowPass);
+    set(&mut values, &p.filter.cutoff, cutoff);
+    set(&mut values, &p.analog, 0.0);
+    values
+}
+
+fn windows(p: &SineParams, values: &PatchValues, sample_rate: f32) -> Vec<BrightnessReading> {
+    let mut analyzer = BrightnessAnalyzer::new();
+    render(p, values, &spec(sample_rate))
+        .iter()
+        .filter_map(|[left, right]| analyzer.push(0.5 * (left + right), sample_rate))
+        .collect()
+}
+
+/// Render `values` at every rate and compare it with the reference rate.
+fn check(name: &str, p: &SineParams, values: &PatchValues) {
+    let expected = windows(p, values, REFERENCE_RATE);
+    assert!(
+        expected.iter().any(|w| db(w.rms) > QUIET_DB),
+        "{name} is silent"
+    );
+    let mut failures = Vec::new();
+    for rate in RATES {
+        let actual = windows(p, values, rate);
+        if actual.len() != expected.len() {
+            failures.push(format!(
+                "{rate} Hz: {} windows, expected {}",
+                actual.len(),
+                expected.len()
+            ));
+            continue;
+        }
+        for (index, (want, got)) in expected.iter().zip(&actual).enumerate() {
+            if db(want.rms) <= QUIET_DB {
+                continue;
+            }
+            let at = index as f32 * WINDOW_SECONDS;
+            let (want_db, got_db) = (db(want.rms), db(got.rms));
+            if (want_db - got_db).abs() > LEVEL_TOLERANCE_DB {
+                failures.push(format!(
+                    "{rate} Hz, {at:.1}s: level {want_db:.1} → {got_db:.1} dB"
+                ));
+            }
+            if (want.hz - got.hz).abs() > want.hz * BRIGHTNESS_TOLERANCE {
+                let (want, got) = (want.hz, got.hz);
+                failures.push(format!(
+                    "{rate} Hz, {at:.1}s: brightness {want:.0} → {got:.0} Hz"
+                ));
+            }
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{name} depends on the sample rate:\n{}",
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn envelope_timing_holds_across_rates() {
+    let p = SineParams::default();
+    let mut values = base(&p, 2_000.0);
+    set(&mut values, &p.adsr.attack, 0.05);
+    set(&mut values, &p.adsr.decay, 0.2);
+    set(&mut values, &p.adsr.sustain, 0.4);
+    set(&mut values, &p.adsr.release, 0.3);
+    check("envelope", &p, &values);
+}
+
+#[test]
+fn unison_detune_holds_across_rates() {
+    let p = SineParams::default();
+    let mut values = base(&p, 2_000.0);
+    set(&mut values, &p.osc1.unison_voices, 4);
+    set(&mut values, &p.osc1.unison_detune, 30.0);
+    check("unison", &p, &values);
+}
+
+#[test]
+fn filter_envelope_holds_across_rates() {
+    let p = SineParams::default();
+    let mut values = base(&p, 300.0);
+    set(&mut values, &p.filter.env_amount, 4.0);
+    set(&mut values, &p.filter_env.attack, 0.01);
+    set(&mut values, &p.filter_env.decay, 0.3);
+    set(&mut values, &p.filter_env.sustain, 0.0);
+    check("filter envelope", &p, &values);
+}
+
+#[test]
+fn comb_damping_holds_across_rates() {
+    let p = SineParams::default();
+    let mut values = base(&p, 20_000.0);
+    set(&mut values, &p.comb.enabled, true);
+    set(&mut values, &p.comb.pluck, true);
+    set(&mut values, &p.comb.feedback, 0.95);
+    set(&mut values, &p.comb.damping, 0.7);
+    set(&mut values, &p.comb.mix, 1.0);
+    check("comb", &p, &values);
+}